@@ -0,0 +1,310 @@
+//! Model-based fuzzing of whole game lifecycles.
+//!
+//! Each input configures one game and then replays a random sequence of
+//! actions from five actors against it through the in-process harness:
+//! honest and dishonest commits, recommits and reveals, timeouts, claims, restarts,
+//! bots, fee collection, forged signatures and clock warps. After every
+//! action the global invariants are checked:
+//!
+//! - lamports are conserved across all accounts
+//! - the pot and uncollected fees are backed by lamports above rent
+//! - scores never go backwards, except a reset to zero on restart, and never
+//!   grow by more than one point per opponent per instruction
+//! - only winners are marked claimed, and once all are the pot is empty
+//! - a finished game only leaves `Finished` through an explicit restart
+//!
+//! Run with `cargo fuzz run game_lifecycle` from `backend/`.
+
+#![no_main]
+
+use std::collections::HashMap;
+
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+use rps_client::instruction::{self, GameConfig};
+use rps_fixtures::harness::Harness;
+use solana_rps::{compute_commitment, Choice, Game, GameState};
+use solana_sdk::{instruction::Instruction, pubkey::Pubkey, rent::Rent};
+
+const ACTORS: usize = 5;
+const WALLET_LAMPORTS: u64 = 1_000_000_000_000;
+
+#[derive(Debug, Arbitrary)]
+struct Input {
+    config: Config,
+    actions: Vec<Action>,
+}
+
+#[derive(Debug, Arbitrary)]
+struct Config {
+    min_players: u8,
+    max_players: u8,
+    total_rounds: u8,
+    entry_fee: u32,
+    timeout_seconds: u8,
+    losers_can_rejoin: bool,
+    automated: bool,
+    max_auto_rounds: u8,
+}
+
+#[derive(Debug, Arbitrary)]
+enum Action {
+    Join { actor: u8 },
+    Commit { actor: u8, choice: u8, salt: u8 },
+    Recommit { actor: u8, choice: u8, salt: u8 },
+    Reveal { actor: u8, honest: bool, choice: u8 },
+    // Targets the current phase unless `stale`
+    ResolveTimeout { actor: u8, stale: bool },
+    Claim { actor: u8 },
+    Rejoin { actor: u8 },
+    StartNewGameRound { actor: u8 },
+    AutoPlayNextRound { actor: u8 },
+    AddBotPlayers { actor: u8, count: u8, strategy: u8 },
+    CollectFees { actor: u8 },
+    // The inner action with every signature stripped
+    Unsigned(Box<Action>),
+    Warp { seconds: u16 },
+}
+
+impl Action {
+    // Restarts are the only way out of a finished game
+    fn is_restart(&self) -> bool {
+        matches!(self, Action::StartNewGameRound { .. } | Action::AutoPlayNextRound { .. })
+    }
+}
+
+fn choice_from(byte: u8) -> Choice {
+    match byte % 4 {
+        0 => Choice::None,
+        1 => Choice::Rock,
+        2 => Choice::Paper,
+        _ => Choice::Scissors,
+    }
+}
+
+struct World {
+    harness: Harness,
+    game: Pubkey,
+    actors: Vec<Pubkey>,
+    // Last accepted commitment preimage per actor, for honest reveals
+    secrets: HashMap<Pubkey, (Choice, [u8; 32])>,
+}
+
+impl World {
+    fn new(config: &Config) -> Self {
+        let mut harness = Harness::new(Pubkey::new_unique());
+        let actors: Vec<Pubkey> = (0..ACTORS).map(|_| harness.new_wallet(WALLET_LAMPORTS)).collect();
+        let game = Pubkey::new_unique();
+
+        let config = GameConfig {
+            min_players: config.min_players % 6,
+            max_players: config.max_players % 6,
+            total_rounds: config.total_rounds % 4,
+            entry_fee: config.entry_fee as u64,
+            timeout_seconds: config.timeout_seconds as u64,
+            losers_can_rejoin: config.losers_can_rejoin,
+            game_mode: config.automated as u8,
+            max_auto_rounds: (config.max_auto_rounds % 4) as u64,
+            ..GameConfig::default()
+        };
+        let program_id = harness.program_id();
+        // Invalid configurations are rejected; the actions then exercise a
+        // missing game
+        let _ = harness.process(&[instruction::initialize_game(&program_id, &actors[0], &game, &config)]);
+
+        Self {
+            harness,
+            game,
+            actors,
+            secrets: HashMap::new(),
+        }
+    }
+
+    fn actor(&self, index: u8) -> Pubkey {
+        self.actors[index as usize % ACTORS]
+    }
+
+    // Commitment preimage an action would record, if it commits
+    fn secret_for(&self, action: &Action) -> Option<(Pubkey, (Choice, [u8; 32]))> {
+        match *action {
+            Action::Commit { actor, choice, salt } | Action::Recommit { actor, choice, salt } => {
+                Some((self.actor(actor), (choice_from(choice % 3 + 1), [salt; 32])))
+            }
+            _ => None,
+        }
+    }
+
+    fn instruction_for(&mut self, action: &Action) -> Option<Instruction> {
+        let program_id = self.harness.program_id();
+        let game = self.game;
+        let ix = match *action {
+            Action::Join { actor } => {
+                instruction::with_slot_hashes(instruction::join_game(&program_id, &self.actor(actor), &game))
+            }
+            Action::Commit { .. } | Action::Recommit { .. } => {
+                let (player, (choice, salt)) = self.secret_for(action)?;
+                let round = self.harness.game(&game).map_or(1, |g| g.current_round);
+                let commitment = compute_commitment(&game, round, &player, &salt, &choice).expect("playable choice");
+                if matches!(action, Action::Recommit { .. }) {
+                    instruction::recommit_choice(&program_id, &player, &game, commitment)
+                } else {
+                    instruction::commit_choice(&program_id, &player, &game, commitment)
+                }
+            }
+            Action::Reveal { actor, honest, choice } => {
+                let player = self.actor(actor);
+                let (choice, salt) = match self.secrets.get(&player) {
+                    Some((committed, salt)) if honest => (committed.clone(), *salt),
+                    _ => (choice_from(choice), [0; 32]),
+                };
+                instruction::reveal_choice(&program_id, &player, &game, choice, salt)
+            }
+            Action::ResolveTimeout { actor, stale } => {
+                let nonce = self.harness.game(&game).map_or(0, |g| g.phase_nonce);
+                let nonce = if stale { nonce.wrapping_sub(1) } else { nonce };
+                instruction::resolve_timeout(&program_id, &self.actor(actor), &game, nonce)
+            }
+            Action::Claim { actor } => instruction::claim_winnings(&program_id, &self.actor(actor), &game),
+            Action::Rejoin { actor } => instruction::rejoin_game(&program_id, &self.actor(actor), &game),
+            Action::StartNewGameRound { actor } => {
+                instruction::start_new_game_round(&program_id, &self.actor(actor), &game)
+            }
+            Action::AutoPlayNextRound { actor } => {
+                instruction::auto_play_next_round(&program_id, &self.actor(actor), &game)
+            }
+            Action::AddBotPlayers { actor, count, strategy } => {
+                let ix = instruction::add_bot_players(&program_id, &self.actor(actor), &game, count, strategy);
+                instruction::with_slot_hashes(ix)
+            }
+            Action::CollectFees { actor } => instruction::collect_fees(&program_id, &self.actor(actor), &game),
+            Action::Unsigned(ref inner) => {
+                let mut ix = self.instruction_for(inner)?;
+                for meta in &mut ix.accounts {
+                    meta.is_signer = false;
+                }
+                ix
+            }
+            Action::Warp { .. } => return None,
+        };
+        Some(ix)
+    }
+
+    fn step(&mut self, action: &Action) {
+        if let Action::Warp { seconds } = *action {
+            self.harness.warp_seconds(seconds as i64);
+            return;
+        }
+        let Some(ix) = self.instruction_for(action) else {
+            return;
+        };
+
+        let lamports_before = self.harness.total_lamports();
+        let data_before = self.game_data();
+        let before = self.harness.game(&self.game);
+        let result = self.harness.process(&[ix]);
+        let after = self.harness.game(&self.game);
+
+        assert_eq!(
+            self.harness.total_lamports(),
+            lamports_before,
+            "lamports not conserved by {:?}",
+            action
+        );
+
+        if result.is_err() {
+            // Failed transactions leave no trace
+            assert_eq!(data_before, self.game_data(), "failed {:?} changed the game", action);
+            return;
+        }
+        if let Some((player, secret)) = self.secret_for(action) {
+            self.secrets.insert(player, secret);
+        }
+
+        let Some(after) = after else {
+            return;
+        };
+        self.check_backing(&after, action);
+        check_claims(&after, action);
+        if let Some(before) = before {
+            check_scores(&before, &after, action);
+            if matches!(before.state, GameState::Finished) && !action.is_restart() {
+                assert!(
+                    matches!(after.state, GameState::Finished),
+                    "{:?} moved a finished game to {:?}",
+                    action,
+                    after.state
+                );
+            }
+        }
+    }
+
+    fn game_data(&self) -> Option<Vec<u8>> {
+        self.harness.account(&self.game).map(|a| a.data.clone())
+    }
+
+    fn check_backing(&self, game: &Game, action: &Action) {
+        let account = self.harness.account(&self.game).expect("game account");
+        let spendable = account
+            .lamports
+            .saturating_sub(Rent::default().minimum_balance(account.data.len()));
+        let owed = game.game_pot as u128 + game.fee_collected as u128;
+        assert!(
+            owed <= spendable as u128,
+            "pot {} + fees {} exceed spendable balance {} after {:?}",
+            game.game_pot,
+            game.fee_collected,
+            spendable,
+            action
+        );
+    }
+}
+
+fn check_claims(game: &Game, action: &Action) {
+    if !matches!(game.state, GameState::Finished) {
+        return;
+    }
+    let winners = game.winner_bitmap();
+    assert_eq!(
+        game.claimed_bitmap & !winners,
+        0,
+        "{:?} marked a non-winner as claimed",
+        action
+    );
+    if game.claimed_bitmap != 0 && game.all_claimed() {
+        assert_eq!(game.game_pot, 0, "pot left over after every winner claimed");
+    }
+}
+
+fn check_scores(before: &Game, after: &Game, action: &Action) {
+    let opponents = after.players.len().saturating_sub(1);
+    for player in &after.players {
+        let Some(previous) = before.players.iter().find(|p| p.pubkey == player.pubkey) else {
+            assert_eq!(player.score, 0, "{:?} seated a player with a score", action);
+            continue;
+        };
+        if player.score == 0 {
+            continue;
+        }
+        assert!(
+            player.score >= previous.score,
+            "{:?} lowered a score from {} to {}",
+            action,
+            previous.score,
+            player.score
+        );
+        assert!(
+            (player.score - previous.score) as usize <= opponents,
+            "{:?} awarded {} points against {} opponents",
+            action,
+            player.score - previous.score,
+            opponents
+        );
+    }
+}
+
+fuzz_target!(|input: Input| {
+    let mut world = World::new(&input.config);
+    for action in input.actions.iter().take(64) {
+        world.step(action);
+    }
+});
@@ -0,0 +1,246 @@
+//! The bot's event loop.
+
+use std::{
+    collections::HashMap,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use rps_client::{
+    instruction,
+    send::{decode_game, fetch_game, send_and_confirm, ActionGuard, SendConfig},
+    Commitment, Result, RpsClientError,
+};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{
+    instruction::Instruction,
+    pubkey::Pubkey,
+    signature::Keypair,
+    signer::Signer,
+    transaction::{Transaction, VersionedTransaction},
+};
+use solana_rps::Game;
+
+use crate::{
+    plan::{next_step, StakeLimits, Step},
+    strategy::Strategy,
+};
+
+#[derive(Debug, Clone)]
+pub struct BotConfig {
+    pub program_id: Pubkey,
+    pub stakes: StakeLimits,
+    /// Games played at once; no new lobby is joined beyond this.
+    pub max_games: usize,
+    /// Delay between ticks of [`Bot::run`].
+    pub poll_interval: Duration,
+    pub send: SendConfig,
+}
+
+/// What a tick did, for the operator's logs.
+#[derive(Debug)]
+pub enum BotEvent {
+    Joined { game: Pubkey },
+    Committed { game: Pubkey, round: u8 },
+    Revealed { game: Pubkey, round: u8 },
+    Claimed { game: Pubkey },
+    Refunded { game: Pubkey },
+    TimedOut { game: Pubkey, phase_nonce: u64 },
+    /// The game needs nothing more from the bot, or its account is gone.
+    Left { game: Pubkey },
+    /// A step failed; it is retried on the next tick. `game` is `None`
+    /// when discovering lobbies failed.
+    Failed { game: Option<Pubkey>, error: RpsClientError },
+}
+
+/// One autonomous player: a wallet, a strategy and the games it sits in.
+pub struct Bot<S> {
+    rpc: RpcClient,
+    wallet: Keypair,
+    config: BotConfig,
+    strategy: S,
+    // The commitment of the current round, kept until it is revealed
+    games: HashMap<Pubkey, Option<Commitment>>,
+}
+
+impl<S: Strategy> Bot<S> {
+    pub fn new(rpc: RpcClient, wallet: Keypair, config: BotConfig, strategy: S) -> Self {
+        Self {
+            rpc,
+            wallet,
+            config,
+            strategy,
+            games: HashMap::new(),
+        }
+    }
+
+    pub fn player(&self) -> Pubkey {
+        self.wallet.pubkey()
+    }
+
+    /// Games the bot currently sits in.
+    pub fn games(&self) -> impl Iterator<Item = &Pubkey> {
+        self.games.keys()
+    }
+
+    /// Ticks forever, `poll_interval` apart, handing every event to
+    /// `on_event`.
+    pub async fn run(&mut self, mut on_event: impl FnMut(&BotEvent)) {
+        loop {
+            for event in self.tick().await {
+                on_event(&event);
+            }
+            tokio::time::sleep(self.config.poll_interval).await;
+        }
+    }
+
+    /// Joins lobbies up to `max_games`, then takes the next step in every
+    /// game the bot sits in.
+    pub async fn tick(&mut self) -> Vec<BotEvent> {
+        let mut events = Vec::new();
+        if self.games.len() < self.config.max_games {
+            match self.open_games().await {
+                Ok(open) => {
+                    let free = self.config.max_games - self.games.len();
+                    for game in open.into_iter().take(free) {
+                        events.push(self.join(game).await);
+                    }
+                }
+                Err(error) => events.push(BotEvent::Failed { game: None, error }),
+            }
+        }
+
+        let games: Vec<Pubkey> = self.games.keys().copied().collect();
+        for game in games {
+            if let Some(event) = self.advance(game).await {
+                events.push(event);
+            }
+        }
+        events
+    }
+
+    // Lobbies within the stake limits the bot does not sit in yet
+    async fn open_games(&self) -> Result<Vec<Pubkey>> {
+        let accounts = self.rpc.get_program_accounts(&self.config.program_id).await?;
+        Ok(accounts
+            .into_iter()
+            .filter(|(key, _)| !self.games.contains_key(key))
+            .filter(|(key, account)| {
+                decode_game(key, &account.data).is_ok_and(|state| self.config.stakes.accepts(&state))
+            })
+            .map(|(key, _)| key)
+            .collect())
+    }
+
+    async fn join(&mut self, game: Pubkey) -> BotEvent {
+        let player = self.player();
+        // The seat that fills a range lobby draws its size from SlotHashes
+        let join = instruction::with_slot_hashes(instruction::join_game(&self.config.program_id, &player, &game));
+        match self.send(vec![join], ActionGuard::Join { game, player }).await {
+            Ok(()) => {
+                self.games.insert(game, None);
+                BotEvent::Joined { game }
+            }
+            Err(error) => BotEvent::Failed { game: Some(game), error },
+        }
+    }
+
+    async fn advance(&mut self, game: Pubkey) -> Option<BotEvent> {
+        let state = match fetch_game(&self.rpc, &game).await {
+            Ok(state) => state,
+            Err(RpsClientError::AccountNotFound(_)) => {
+                self.games.remove(&game);
+                return Some(BotEvent::Left { game });
+            }
+            Err(error) => return Some(BotEvent::Failed { game: Some(game), error }),
+        };
+        let player = self.player();
+        let program_id = self.config.program_id;
+
+        let result = match next_step(&state, &player, now()) {
+            Step::Wait => return None,
+            Step::Join | Step::Done => {
+                self.games.remove(&game);
+                return Some(BotEvent::Left { game });
+            }
+            Step::Commit => self.commit(game, &state).await,
+            Step::Reveal => {
+                // A salt lost to a restart cannot be recovered; the round
+                // times out for this seat like any silent player's
+                let commitment = self.games.get(&game).cloned().flatten()?;
+                if !commitment.is_committed_in(&state) {
+                    return None;
+                }
+                let guard = ActionGuard::Reveal { game, player };
+                self.send(vec![commitment.reveal(&program_id)], guard)
+                    .await
+                    .map(|()| BotEvent::Revealed {
+                        game,
+                        round: commitment.round(),
+                    })
+            }
+            Step::Claim => {
+                let claim = instruction::claim_winnings(&program_id, &player, &game);
+                self.send(vec![claim], ActionGuard::Claim { game, player })
+                    .await
+                    .map(|()| BotEvent::Claimed { game })
+            }
+            Step::Refund => {
+                let refund = instruction::refund_entry(&program_id, &player, &game, &player);
+                self.send(vec![refund], ActionGuard::Claim { game, player })
+                    .await
+                    .map(|()| BotEvent::Refunded { game })
+            }
+            Step::ResolveTimeout { phase_nonce } => {
+                let resolve = instruction::resolve_timeout(&program_id, &player, &game, phase_nonce);
+                self.send(vec![resolve], ActionGuard::ResolveTimeout { game, phase_nonce })
+                    .await
+                    .map(|()| BotEvent::TimedOut { game, phase_nonce })
+            }
+        };
+        Some(result.unwrap_or_else(|error| BotEvent::Failed { game: Some(game), error }))
+    }
+
+    async fn commit(&mut self, game: Pubkey, state: &Game) -> Result<BotEvent> {
+        let player = self.player();
+        let seat = state
+            .players
+            .iter()
+            .position(|p| p.pubkey == player)
+            .ok_or_else(|| RpsClientError::InvalidAccountData(game.to_string()))?;
+        // A commit from an earlier tick may still land; resending the same
+        // one keeps the stored salt the one on-chain
+        let pending = self.games.get(&game).cloned().flatten();
+        let commitment = match pending {
+            Some(commitment) if commitment.round() == state.current_round => commitment,
+            _ => {
+                let choice = self.strategy.choose(&game, state, seat);
+                Commitment::for_game(choice, &game, state, &player)?
+            }
+        };
+        // Kept before sending, so a commit that lands unobserved can still
+        // be revealed
+        self.games.insert(game, Some(commitment.clone()));
+        let commit = commitment.commit(&self.config.program_id);
+        self.send(vec![commit], ActionGuard::Commit { game, player }).await?;
+        Ok(BotEvent::Committed {
+            game,
+            round: commitment.round(),
+        })
+    }
+
+    async fn send(&self, instructions: Vec<Instruction>, guard: ActionGuard) -> Result<()> {
+        let payer = self.wallet.pubkey();
+        send_and_confirm(&self.rpc, &self.config.send, Some(guard), |blockhash| {
+            let tx = Transaction::new_signed_with_payer(&instructions, Some(&payer), &[&self.wallet], blockhash);
+            Ok(VersionedTransaction::from(tx))
+        })
+        .await?;
+        Ok(())
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs())
+}
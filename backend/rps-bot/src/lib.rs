@@ -0,0 +1,17 @@
+//! Autonomous players for the Solana RPS program.
+//!
+//! A [`Bot`] wraps the client SDK in a polling loop: it discovers open SOL
+//! lobbies whose entry fee is within its [`StakeLimits`], joins them, commits
+//! the hand its [`Strategy`] picks, reveals it, claims what it wins and
+//! cranks timeouts when other seats stall. Everything it does in a game is
+//! decided by [`next_step`] from the game account alone, so a bot restarted
+//! mid-game picks up where it left off (short of the salts of commitments
+//! it had not revealed yet).
+
+pub mod bot;
+pub mod plan;
+pub mod strategy;
+
+pub use bot::{Bot, BotConfig, BotEvent};
+pub use plan::{next_step, StakeLimits, Step};
+pub use strategy::{hands, Strategy, Uniform};
@@ -0,0 +1,80 @@
+//! What a bot does next, worked out from a game account alone.
+
+use solana_sdk::pubkey::Pubkey;
+use solana_rps::{CurrencyMode, Game, GameState};
+
+/// Entry fees, in lamports, a bot is willing to pay.
+#[derive(Debug, Clone, Copy)]
+pub struct StakeLimits {
+    pub min_entry_fee: u64,
+    pub max_entry_fee: u64,
+}
+
+impl StakeLimits {
+    /// True for a lobby a bot can simply sit down in: a current-layout SOL
+    /// game still taking players, with a free seat and an entry fee within
+    /// the limits. Tournament matches, sealed fees and multi-currency pots
+    /// are left to people.
+    pub fn accepts(&self, state: &Game) -> bool {
+        matches!(state.state, GameState::WaitingForPlayers)
+            && matches!(state.currency_mode, CurrencyMode::SOL)
+            && !state.is_legacy()
+            && state.tournament.is_none()
+            && state.hidden_fee.is_none()
+            && state.stake_vaults.is_empty()
+            && state.players.len() < state.max_players as usize
+            && (self.min_entry_fee..=self.max_entry_fee).contains(&state.entry_fee)
+    }
+}
+
+/// The next thing a player does in a game.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Step {
+    /// Take a seat in the open lobby.
+    Join,
+    /// Commit a hand for the current round.
+    Commit,
+    /// Reveal the hand committed this round.
+    Reveal,
+    /// Collect this seat's share of the pot.
+    Claim,
+    /// Take this seat's entry back from a cancelled game.
+    Refund,
+    /// End the phase the other seats let expire.
+    ResolveTimeout { phase_nonce: u64 },
+    /// Nothing to do until someone else acts.
+    Wait,
+    /// The game needs nothing more from this player.
+    Done,
+}
+
+/// What `player` should do next in the game `state`, at unix time `now`.
+pub fn next_step(state: &Game, player: &Pubkey, now: u64) -> Step {
+    let Some(seat) = state.players.iter().position(|p| p.pubkey == *player) else {
+        return match state.state {
+            GameState::WaitingForPlayers => Step::Join,
+            _ => Step::Done,
+        };
+    };
+    let me = &state.players[seat];
+    match state.state {
+        GameState::Finished if state.cancelled => {
+            return if state.refundable(player).is_ok() { Step::Refund } else { Step::Done };
+        }
+        GameState::Finished => {
+            let owed = state.winner_bitmap() & !state.claimed_bitmap & (1 << seat) != 0;
+            return if owed { Step::Claim } else { Step::Done };
+        }
+        GameState::CommitPhase if me.committed_choice == [0; 64] => return Step::Commit,
+        GameState::RevealPhase if me.committed_choice != [0; 64] && !me.revealed => return Step::Reveal,
+        _ => {}
+    }
+    // A game without a timeout can only wait for its players
+    if state.phase_timeout(state.state) > 0 && now >= state.timeout_deadline() {
+        Step::ResolveTimeout {
+            phase_nonce: state.phase_nonce,
+        }
+    } else {
+        Step::Wait
+    }
+}
@@ -0,0 +1,45 @@
+//! How a bot picks its hands.
+
+use rand::{rngs::OsRng, Rng};
+use solana_sdk::pubkey::Pubkey;
+use solana_rps::{Choice, Game, RuleSet};
+
+/// Picks the hand a bot commits each round.
+///
+/// Closures of the same shape are strategies too, so a one-off rule needs
+/// no type of its own.
+pub trait Strategy {
+    /// The hand to commit from `seat` of `game`, whose account is `state`,
+    /// this round. It must be one of [`hands`] for the game's rule set, or
+    /// the reveal is refused.
+    fn choose(&mut self, game: &Pubkey, state: &Game, seat: usize) -> Choice;
+}
+
+impl<F> Strategy for F
+where
+    F: FnMut(&Pubkey, &Game, usize) -> Choice,
+{
+    fn choose(&mut self, game: &Pubkey, state: &Game, seat: usize) -> Choice {
+        self(game, state, seat)
+    }
+}
+
+/// Hands a game under `rule_set` accepts.
+pub fn hands(rule_set: RuleSet) -> &'static [Choice] {
+    match rule_set {
+        RuleSet::Classic => &[Choice::Rock, Choice::Paper, Choice::Scissors],
+        RuleSet::LizardSpock => &[Choice::Rock, Choice::Paper, Choice::Scissors, Choice::Lizard, Choice::Spock],
+    }
+}
+
+/// Every allowed hand with equal odds, drawn from the OS: the strategy
+/// nobody can exploit.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Uniform;
+
+impl Strategy for Uniform {
+    fn choose(&mut self, _game: &Pubkey, state: &Game, _seat: usize) -> Choice {
+        let hands = hands(state.rule_set);
+        hands[OsRng.gen_range(0..hands.len())].clone()
+    }
+}
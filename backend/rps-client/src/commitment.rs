@@ -0,0 +1,142 @@
+//! Commitments built and kept together with the reveal they promise.
+//!
+//! A reveal only opens a commitment when the hand, salt, game, round, player
+//! and hash scheme are exactly the ones it was made with. Integrations that
+//! track those separately tend to lose one of them between the commit and
+//! the reveal: a salt regenerated after a restart, the round read after it
+//! advanced, the default scheme used on a SHA-256 game. [`Commitment`] draws
+//! the salt itself, hashes everything the program hashes, and serializes as
+//! one value that can be stored until the reveal.
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use rand::{rngs::OsRng, RngCore};
+use solana_sdk::{instruction::Instruction, pubkey::Pubkey};
+use solana_rps::{compute_commitment_with, Choice, CommitmentScheme, Game};
+
+use crate::{
+    error::{Result, RpsClientError},
+    instruction,
+};
+
+/// A hand committed for one player, game and round, with its salt.
+///
+/// The salt is secret until the reveal: store the whole value (see
+/// [`Commitment::to_bytes`]) somewhere only the player can read.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct Commitment {
+    game: Pubkey,
+    round: u8,
+    player: Pubkey,
+    scheme: CommitmentScheme,
+    choice: Choice,
+    salt: [u8; 32],
+    hash: [u8; 64],
+}
+
+impl Commitment {
+    /// Commits `choice` for `player` in `round` of `game` under the default
+    /// HMAC-SHA512 scheme, with a fresh random salt.
+    pub fn new(choice: Choice, game: &Pubkey, round: u8, player: &Pubkey) -> Result<Self> {
+        Self::with_scheme(CommitmentScheme::HmacSha512, choice, game, round, player)
+    }
+
+    /// [`Commitment::new`] under a game's own `scheme`.
+    pub fn with_scheme(
+        scheme: CommitmentScheme,
+        choice: Choice,
+        game: &Pubkey,
+        round: u8,
+        player: &Pubkey,
+    ) -> Result<Self> {
+        let mut salt = [0u8; 32];
+        OsRng.fill_bytes(&mut salt);
+        let hash = compute_commitment_with(scheme, game, round, player, &salt, &choice)
+            .map_err(|_| RpsClientError::InvalidChoice(choice.clone()))?;
+        Ok(Self {
+            game: *game,
+            round,
+            player: *player,
+            scheme,
+            choice,
+            salt,
+            hash,
+        })
+    }
+
+    /// Commits `choice` for the round `state`, the decoded `game` account,
+    /// is playing now, under its scheme.
+    pub fn for_game(choice: Choice, game: &Pubkey, state: &Game, player: &Pubkey) -> Result<Self> {
+        Self::with_scheme(state.commitment_scheme, choice, game, state.current_round, player)
+    }
+
+    pub fn game(&self) -> &Pubkey {
+        &self.game
+    }
+
+    pub fn round(&self) -> u8 {
+        self.round
+    }
+
+    pub fn player(&self) -> &Pubkey {
+        &self.player
+    }
+
+    pub fn choice(&self) -> &Choice {
+        &self.choice
+    }
+
+    /// The hash sent with the commit.
+    pub fn hash(&self) -> [u8; 64] {
+        self.hash
+    }
+
+    /// `CommitChoice` for this commitment.
+    pub fn commit(&self, program_id: &Pubkey) -> Instruction {
+        instruction::commit_choice(program_id, &self.player, &self.game, self.hash)
+    }
+
+    /// `RecommitChoice` replacing the player's earlier commitment this round.
+    pub fn recommit(&self, program_id: &Pubkey) -> Instruction {
+        instruction::recommit_choice(program_id, &self.player, &self.game, self.hash)
+    }
+
+    /// `RevealChoice` opening this commitment.
+    pub fn reveal(&self, program_id: &Pubkey) -> Instruction {
+        instruction::reveal_choice(program_id, &self.player, &self.game, self.choice.clone(), self.salt)
+    }
+
+    /// True if `state` is still in this commitment's round and holds it as
+    /// the player's commitment, i.e. the reveal will open it.
+    pub fn is_committed_in(&self, state: &Game) -> bool {
+        state.current_round == self.round
+            && state
+                .players
+                .iter()
+                .any(|p| p.pubkey == self.player && p.committed_choice == self.hash)
+    }
+
+    /// Serializes the commitment, salt included, for storage until the
+    /// reveal.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.try_to_vec().expect("commitment serializes")
+    }
+
+    /// Restores a stored commitment, refusing one whose hash no longer
+    /// matches the values it holds.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let commitment = Self::try_from_slice(bytes).map_err(|_| RpsClientError::InvalidPendingReveal)?;
+        let hash = compute_commitment_with(
+            commitment.scheme,
+            &commitment.game,
+            commitment.round,
+            &commitment.player,
+            &commitment.salt,
+            &commitment.choice,
+        )
+        .map_err(|_| RpsClientError::InvalidPendingReveal)?;
+        if hash != commitment.hash {
+            return Err(RpsClientError::InvalidPendingReveal);
+        }
+        Ok(commitment)
+    }
+}
@@ -0,0 +1,234 @@
+//! Compute-budget and priority-fee helpers.
+//!
+//! Every RPS transaction is sent with an explicit compute-unit limit sized
+//! from the instructions it carries, plus an optional priority fee derived
+//! from recently landed fees on the accounts it writes. This keeps games
+//! playable when the cluster is congested without overpaying when it isn't.
+
+use solana_client::{client_error::ClientError, nonblocking::rpc_client::RpcClient};
+use solana_sdk::{
+    compute_budget::{self, ComputeBudgetInstruction},
+    ed25519_program,
+    instruction::Instruction,
+    pubkey::Pubkey,
+};
+
+/// Budget used for instructions we have no estimate for.
+pub const DEFAULT_COMPUTE_UNITS: u32 = 200_000;
+
+/// Hard cap the runtime enforces per transaction.
+pub const MAX_COMPUTE_UNITS: u32 = 1_400_000;
+
+/// Base cost of each compute-budget instruction we prepend.
+const COMPUTE_BUDGET_IX_UNITS: u32 = 150;
+
+/// Headroom applied on top of the table below, in percent.
+const COMPUTE_UNIT_MARGIN_PERCENT: u32 = 20;
+
+/// Compute units per `RPSInstruction`, indexed by its Borsh discriminant.
+///
+/// No row has been measured yet: each is a conservative estimate from the
+/// work its handler does (account creation, CPIs, hashing). The in-process
+/// test harness runs handlers natively and meters no compute, so it cannot
+/// measure them. Measured values come from an SBF build on
+/// `solana-test-validator`:
+///
+/// - InitializeGame, JoinGame, CommitChoice, RevealChoice and
+///   DistributeWinnings: the "CU max" column of an `rps-loadtest` report
+///   with `--players 8`, the seat count that costs the most.
+/// - Every other row: `unitsConsumed` from simulating the instruction.
+///
+/// When a row is replaced with a measurement, note next to it the program
+/// commit and validator version it was taken with.
+const INSTRUCTION_COMPUTE_UNITS: &[u32] = &[
+    45_000,  // InitializeGame (create_account + transfers)
+    30_000,  // JoinGame
+    12_000,  // CommitChoice
+    25_000,  // RevealChoice (HMAC-SHA512 + round scoring)
+    25_000,  // ResolveTimeout
+    30_000,  // ClaimWinnings
+    30_000,  // RejoinGame
+    10_000,  // StartNewGameRound
+    10_000,  // AutoPlayNextRound
+    40_000,  // AddBotPlayers
+    20_000,  // CollectFees
+    25_000,  // CreateTournament
+    20_000,  // JoinTournament
+    400_000, // JoinGameWithSwap (Jupiter route CPI)
+    60_000,  // ConfigureStakeMints
+    70_000,  // JoinGameWithMint (two Pyth reads + token transfer)
+    30_000,  // StartTournament (bracket seeding)
+    15_000,  // ReportMatchResult
+    25_000,  // FinishTournament
+    10_000,  // ClaimTournamentPrize
+    15_000,  // OpenTournamentMatch
+    25_000,  // DisqualifyPlayer (walkover pass)
+    10_000,  // SetTournamentAdmins
+    20_000,  // InitializeConfig
+    30_000,  // BanPlayer (creates the chunk on first use)
+    10_000,  // UnbanPlayer
+    10_000,  // SetCompliance
+    10_000,  // GetClaimable (view)
+    25_000,  // CreateChallenge
+    20_000,  // AcceptChallenge
+    8_000,   // CommitChallengeChoice
+    25_000,  // RevealChallengeChoice (HMAC-SHA512 + settlement)
+    15_000,  // ExpireChallenge
+    12_000,  // RecommitChoice
+    25_000,  // RecordGameResult (create_account)
+    10_000,  // AuditGame (view)
+    25_000,  // CreateAutoTournament (create_account)
+    60_000,  // CrankAutoTournament (rethrows draws)
+    30_000,  // RevealChoiceSigned (instruction introspection + round scoring)
+    25_000,  // CreateClan (PDA create_account)
+    12_000,  // JoinTournamentAsClan
+    30_000,  // OpenTeamMatch (create_account)
+    25_000,  // ClaimTeamPrize (one payout per member)
+    20_000,  // InitializeStats (PDA create_account)
+    5_000,   // SetTimeoutPolicy
+    8_000,   // RefreshDefaultTimeout
+    20_000,  // RecordParticipation (PDA create_account on first use)
+    5_000,   // SetCollusionPolicy
+    8_000,   // ClearGameFlag
+    5_000,   // SetClaimPolicy
+    10_000,  // InitiateClaim
+    30_000,  // FinalizeClaim
+    25_000,  // RegisterLobbyName (PDA create_account)
+    20_000,  // CreateRecurringLobby (create_account)
+    30_000,  // SpawnScheduledLobby (create_account)
+    25_000,  // JoinWaitlist (PDA create_account)
+    15_000,  // LeaveGame
+    15_000,  // KickPlayer
+    15_000,  // RefundWaitlist
+    15_000,  // RefundExpiredEscrow
+    5_000,   // SetPotGuarantee
+    15_000,  // ApplyPotSubsidy
+    5_000,   // GetFeeQuote (view)
+    5_000,   // SetPausedInstructions
+    20_000,  // FinalizeLegacyGame (payouts + close)
+    15_000,  // OfferDoubleOrNothing
+    30_000,  // AcceptDoubleOrNothing (create_account + close)
+    5_000,   // SealEntryFee
+    25_000,  // RevealEntryFee (per-seat refunds)
+    30_000,  // SpawnTournamentMatch (create_account)
+    40_000,  // ArchiveGameResults (per-result fold and close)
+    5_000,   // SetNicknamePolicy
+    35_000,  // SetNickname (create_account x2 + fee transfer)
+    10_000,  // GetSpectatorView (view)
+    10_000,  // CloseGame
+    5_000,   // SetFeePolicy
+    5_000,   // SetPayoutGuard
+    20_000,  // InitializeGovernance (create_account)
+    35_000,  // StakeGovernanceTokens (create_account + token transfer)
+    25_000,  // UnstakeGovernanceTokens (PDA-signed token transfer)
+    25_000,  // ProposeParameterChange (create_account)
+    20_000,  // CastVote (create_account)
+    10_000,  // ExecuteProposal
+    20_000,  // SetLimits (create_account)
+    20_000,  // SelfExclude (create_account)
+    20_000,  // InitializeColdTreasury (create_account)
+    15_000,  // SweepTreasury (PDA-signed transfers)
+    20_000,  // ProposeColdAction (create_account)
+    5_000,   // ApproveColdAction
+    10_000,  // ExecuteColdAction
+    30_000,  // DistributeWinnings (one payout per winner)
+    20_000,  // ConfigureInsuranceFund (create_account)
+    20_000,  // FileInsuranceClaim (create_account)
+    5_000,   // ResolveInsuranceClaim
+    15_000,  // RefundEntry (token CPI for RPSToken games)
+    65_000,  // ClaimDemoTokens (create_account x2 + mint init + associated token account CPI + mint_to)
+    30_000,  // CreateGameTokenVault (associated token account CPI)
+    20_000,  // BotAct (a hash per bot seat; may score the round)
+];
+
+/// Lower/upper bounds on the suggested priority fee, in micro-lamports per CU.
+pub const MIN_PRIORITY_FEE: u64 = 1_000;
+pub const MAX_PRIORITY_FEE: u64 = 2_000_000;
+
+/// Estimates the compute units an RPS program instruction needs.
+///
+/// The ed25519 precompile is free; instructions addressed to other programs
+/// fall back to [`DEFAULT_COMPUTE_UNITS`].
+pub fn estimate_instruction_units(program_id: &Pubkey, ix: &Instruction) -> u32 {
+    if ix.program_id == ed25519_program::id() {
+        return 0;
+    }
+    if ix.program_id != *program_id {
+        return DEFAULT_COMPUTE_UNITS;
+    }
+    ix.data
+        .first()
+        .and_then(|tag| INSTRUCTION_COMPUTE_UNITS.get(*tag as usize))
+        .copied()
+        .unwrap_or(DEFAULT_COMPUTE_UNITS)
+}
+
+/// Estimates the compute-unit limit for a whole transaction, including
+/// margin, clamped to the runtime maximum.
+pub fn estimate_transaction_units(program_id: &Pubkey, ixs: &[Instruction]) -> u32 {
+    let raw: u32 = ixs
+        .iter()
+        .map(|ix| estimate_instruction_units(program_id, ix))
+        .fold(2 * COMPUTE_BUDGET_IX_UNITS, u32::saturating_add);
+    let with_margin = raw.saturating_add(raw / 100 * COMPUTE_UNIT_MARGIN_PERCENT);
+    with_margin.min(MAX_COMPUTE_UNITS)
+}
+
+/// Prepends compute-budget instructions to `ixs`.
+///
+/// Any compute-budget instructions already present are replaced, since the
+/// runtime rejects transactions carrying duplicates.
+pub fn with_compute_budget(
+    ixs: Vec<Instruction>,
+    unit_limit: u32,
+    micro_lamports_per_unit: Option<u64>,
+) -> Vec<Instruction> {
+    let mut out = Vec::with_capacity(ixs.len() + 2);
+    out.push(ComputeBudgetInstruction::set_compute_unit_limit(unit_limit));
+    if let Some(price) = micro_lamports_per_unit {
+        out.push(ComputeBudgetInstruction::set_compute_unit_price(price));
+    }
+    out.extend(ixs.into_iter().filter(|ix| ix.program_id != compute_budget::id()));
+    out
+}
+
+/// Suggests a priority fee from fees recently paid by transactions that
+/// locked any of `writable_accounts` (typically the game account).
+///
+/// `percentile` selects how aggressive the suggestion is (50 = median,
+/// 90 = outbid most recent traffic). Returns micro-lamports per CU.
+pub async fn suggest_priority_fee(
+    rpc: &RpcClient,
+    writable_accounts: &[Pubkey],
+    percentile: u8,
+) -> Result<u64, ClientError> {
+    let mut fees: Vec<u64> = rpc
+        .get_recent_prioritization_fees(writable_accounts)
+        .await?
+        .into_iter()
+        .map(|f| f.prioritization_fee)
+        .filter(|fee| *fee > 0)
+        .collect();
+
+    if fees.is_empty() {
+        return Ok(MIN_PRIORITY_FEE);
+    }
+
+    fees.sort_unstable();
+    let rank = (fees.len() - 1) * percentile.min(100) as usize / 100;
+    Ok(fees[rank].clamp(MIN_PRIORITY_FEE, MAX_PRIORITY_FEE))
+}
+
+/// Sizes the compute budget for `ixs` and attaches a priority fee suggested
+/// from recent traffic on `writable_accounts`.
+pub async fn prepare_instructions(
+    rpc: &RpcClient,
+    program_id: &Pubkey,
+    ixs: Vec<Instruction>,
+    writable_accounts: &[Pubkey],
+    percentile: u8,
+) -> Result<Vec<Instruction>, ClientError> {
+    let units = estimate_transaction_units(program_id, &ixs);
+    let price = suggest_priority_fee(rpc, writable_accounts, percentile).await?;
+    Ok(with_compute_budget(ixs, units, Some(price)))
+}
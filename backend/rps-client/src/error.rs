@@ -0,0 +1,75 @@
+//! Error type shared by the client SDK.
+
+use solana_client::{client_error::ClientError, pubsub_client::PubsubClientError};
+use solana_sdk::{message::CompileError, signer::SignerError, transaction::TransactionError};
+use solana_rps::{Choice, RPSError};
+use thiserror::Error;
+
+use crate::preflight::ErrorContext;
+
+#[derive(Error, Debug)]
+pub enum RpsClientError {
+    // Boxed, like Pubsub: RPC errors carry the whole request and dwarf
+    // every other variant
+    #[error("RPC error: {0}")]
+    Rpc(Box<ClientError>),
+
+    #[error("Websocket error: {0}")]
+    Pubsub(Box<PubsubClientError>),
+
+    #[error("Failed to compile transaction message: {0}")]
+    Compile(#[from] CompileError),
+
+    #[error("Signing failed: {0}")]
+    Signer(#[from] SignerError),
+
+    #[error("Account {0} not found")]
+    AccountNotFound(String),
+
+    #[error("Account {0} could not be decoded")]
+    InvalidAccountData(String),
+
+    #[error("Transaction failed: {0}")]
+    TransactionFailed(TransactionError),
+
+    #[error("Transaction not confirmed after {0} attempts")]
+    RetriesExhausted(u32),
+
+    #[error("Instruction {instruction_index} rejected: {message}")]
+    Program {
+        instruction_index: u8,
+        error: RPSError,
+        message: String,
+        context: Option<Box<ErrorContext>>,
+        logs: Vec<String>,
+    },
+
+    #[error("Simulation failed: {err}")]
+    Simulation {
+        err: TransactionError,
+        logs: Vec<String>,
+    },
+
+    #[error("View instruction returned no data")]
+    MissingReturnData,
+
+    #[error("Cannot commit to {0:?}")]
+    InvalidChoice(Choice),
+
+    #[error("Stored commitment is corrupt or does not match its hash")]
+    InvalidPendingReveal,
+}
+
+impl From<ClientError> for RpsClientError {
+    fn from(err: ClientError) -> Self {
+        RpsClientError::Rpc(Box::new(err))
+    }
+}
+
+impl From<PubsubClientError> for RpsClientError {
+    fn from(err: PubsubClientError) -> Self {
+        RpsClientError::Pubsub(Box::new(err))
+    }
+}
+
+pub type Result<T> = std::result::Result<T, RpsClientError>;
@@ -0,0 +1,2068 @@
+//! Instruction builders for the RPS program.
+//!
+//! Account order mirrors the handlers in the program crate; optional
+//! trailing accounts (credentials, token accounts) are appended by the
+//! caller when needed.
+
+use borsh::BorshSerialize;
+use solana_sdk::{
+    ed25519_program,
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    system_program, sysvar,
+};
+use solana_rps::{
+    ban_list_address, clan_address, cold_proposal_address, cold_treasury_address, config_address, demo_claim_address,
+    demo_mint_address, demo_token_address, exclusion_address, game_authority_address, game_result_address,
+    game_vault_address, governance_address, governance_vault_address, insurance_claim_address, insurance_fund_address,
+    limits_address, lobby_name_address, nickname_address, participation_address, pot_vault_address, profile_address,
+    proposal_address, result_archive_address, stats_address, treasury_address, vote_record_address, voter_stake_address,
+    waitlist_address, Choice, ColdAction, LobbyTemplate, ParameterChange, RPSInstruction, ASSOCIATED_TOKEN_PROGRAM_ID,
+};
+
+pub use solana_rps::accounts::{instruction_accounts, AccountMismatch, AccountSpec};
+
+/// Parameters for [`initialize_game`].
+#[derive(Debug, Clone)]
+pub struct GameConfig {
+    pub min_players: u8,
+    pub max_players: u8,
+    pub total_rounds: u8,
+    pub entry_fee: u64,
+    /// 0 inherits the program's adaptive default (see `set_timeout_policy`).
+    pub timeout_seconds: u64,
+    pub losers_can_rejoin: bool,
+    pub game_mode: u8,
+    pub currency_mode: u8,
+    pub auto_round_delay: u64,
+    pub max_auto_rounds: u64,
+    pub token_mint: Option<Pubkey>,
+    /// Let anyone add bots; otherwise only the host can.
+    pub allow_public_bots: bool,
+    /// 0 = HMAC-SHA512, 1 = SHA-256 (see `solana_rps::compute_commitment_with`).
+    pub commitment_scheme: u8,
+    /// Client skin to render the game with, e.g. a holiday event; 0 = default.
+    pub theme_id: u16,
+    /// 0 = rock-paper-scissors, 1 = with lizard and Spock.
+    pub rule_set: u8,
+    /// Lobby countdown; 0 uses `timeout_seconds`.
+    pub join_timeout: u64,
+    /// Commit phase countdown; 0 uses `timeout_seconds`.
+    pub commit_timeout: u64,
+    /// Reveal phase countdown; 0 uses `timeout_seconds`.
+    pub reveal_timeout: u64,
+    /// Lamports paid to each caller of `resolve_timeout`, at most
+    /// `solana_rps::MAX_CRANK_REWARD_BPS` of the entry fee; SOL games only.
+    pub crank_reward_lamports: u64,
+    /// Lamports the host deposits to pay crank rewards before the pot does.
+    pub crank_bounty: u64,
+    /// Seats that commit but never reveal forfeit their entry to the seats
+    /// that revealed.
+    pub slash_non_revealers: bool,
+}
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        Self {
+            min_players: 3,
+            max_players: 3,
+            total_rounds: 1,
+            entry_fee: 0,
+            timeout_seconds: 300,
+            losers_can_rejoin: false,
+            game_mode: 0,
+            currency_mode: 0,
+            auto_round_delay: 0,
+            max_auto_rounds: 0,
+            token_mint: None,
+            allow_public_bots: false,
+            commitment_scheme: 0,
+            theme_id: 0,
+            rule_set: 0,
+            join_timeout: 0,
+            commit_timeout: 0,
+            reveal_timeout: 0,
+            crank_reward_lamports: 0,
+            crank_bounty: 0,
+            slash_non_revealers: false,
+        }
+    }
+}
+
+/// Parameters for [`create_tournament`].
+#[derive(Debug, Clone)]
+pub struct TournamentConfig {
+    pub max_players: u8,
+    pub entry_fee: u64,
+    pub currency_mode: u8,
+    pub token_mint: Option<Pubkey>,
+    /// Finished tournament entrants must have placed in, if any.
+    pub qualifier_of: Option<Pubkey>,
+    pub qualifier_top_n: u8,
+    /// Entrants are clans playing 2v2 team matches.
+    pub team_entries: bool,
+}
+
+impl Default for TournamentConfig {
+    fn default() -> Self {
+        Self {
+            max_players: 4,
+            entry_fee: 0,
+            currency_mode: 0,
+            token_mint: None,
+            qualifier_of: None,
+            qualifier_top_n: 0,
+            team_entries: false,
+        }
+    }
+}
+
+/// A compliance credential: the player's frozen token account and its mint.
+/// Paid entries need one while the operator has compliance mode on.
+#[derive(Debug, Clone, Copy)]
+pub struct Credential {
+    pub account: Pubkey,
+    pub mint: Pubkey,
+}
+
+impl Credential {
+    fn metas(&self) -> [AccountMeta; 2] {
+        [
+            AccountMeta::new_readonly(self.account, false),
+            AccountMeta::new_readonly(self.mint, false),
+        ]
+    }
+}
+
+// Token program, `wallet_tokens` and the game vault: the token tail of an
+// RPSToken entry
+fn token_entry_metas(program_id: &Pubkey, game: &Pubkey, mint: &Pubkey, wallet_tokens: &Pubkey) -> [AccountMeta; 3] {
+    [
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new(*wallet_tokens, false),
+        AccountMeta::new(game_vault_address(program_id, game, mint), false),
+    ]
+}
+
+// The entry tail plus the game authority that signs RPSToken payouts
+fn token_payout_metas(program_id: &Pubkey, game: &Pubkey, mint: &Pubkey, wallet_tokens: &Pubkey) -> Vec<AccountMeta> {
+    let mut metas = token_entry_metas(program_id, game, mint, wallet_tokens).to_vec();
+    metas.push(AccountMeta::new_readonly(game_authority_address(program_id, game).0, false));
+    metas
+}
+
+// The game's pot vault, which holds a SOL game's pot
+fn pot_vault_meta(program_id: &Pubkey, game: &Pubkey) -> AccountMeta {
+    AccountMeta::new(pot_vault_address(program_id, game).0, false)
+}
+
+fn build(program_id: &Pubkey, data: RPSInstruction, accounts: Vec<AccountMeta>) -> Instruction {
+    let ix = Instruction {
+        program_id: *program_id,
+        accounts,
+        data: data.try_to_vec().expect("instruction serializes"),
+    };
+    debug_assert_eq!(account_mismatch(&ix), None, "builder disagrees with the program's account specs");
+    ix
+}
+
+/// Checks `ix`'s leading accounts against the program's spec for it, the
+/// same check the program makes before running the instruction. Useful for
+/// instructions assembled or edited by hand.
+pub fn account_mismatch(ix: &Instruction) -> Option<AccountMismatch> {
+    let tag = *ix.data.first()?;
+    solana_rps::accounts::find_mismatch(tag, ix.accounts.iter().map(|meta| (meta.is_signer, meta.is_writable)))
+}
+
+/// Creates a game; `game` must be a fresh keypair that signs the transaction.
+pub fn initialize_game(
+    program_id: &Pubkey,
+    host: &Pubkey,
+    game: &Pubkey,
+    config: &GameConfig,
+) -> Instruction {
+    // The config PDA fixes the game's fee rate, and its adaptive default
+    // timeout when `timeout_seconds` is 0; a self-excluded host is refused
+    let accounts = vec![
+        AccountMeta::new(*host, true),
+        AccountMeta::new(*game, true),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new(treasury_address(program_id).0, false),
+        pot_vault_meta(program_id, game),
+        AccountMeta::new_readonly(config_address(program_id).0, false),
+        AccountMeta::new_readonly(exclusion_address(program_id, host).0, false),
+    ];
+    build(
+        program_id,
+        RPSInstruction::InitializeGame {
+            min_players: config.min_players,
+            max_players: config.max_players,
+            total_rounds: config.total_rounds,
+            entry_fee: config.entry_fee,
+            timeout_seconds: config.timeout_seconds,
+            losers_can_rejoin: config.losers_can_rejoin,
+            game_mode: config.game_mode,
+            currency_mode: config.currency_mode,
+            auto_round_delay: config.auto_round_delay,
+            max_auto_rounds: config.max_auto_rounds,
+            token_mint: config.token_mint,
+            allow_public_bots: config.allow_public_bots,
+            commitment_scheme: config.commitment_scheme,
+            theme_id: config.theme_id,
+            rule_set: config.rule_set,
+            join_timeout: config.join_timeout,
+            commit_timeout: config.commit_timeout,
+            reveal_timeout: config.reveal_timeout,
+            crank_reward_lamports: config.crank_reward_lamports,
+            crank_bounty: config.crank_bounty,
+            slash_non_revealers: config.slash_non_revealers,
+        },
+        accounts,
+    )
+}
+
+/// [`initialize_game`] for an RPSToken game (`config.token_mint` set). A
+/// paid entry moves from `host_tokens` into the game vault, which
+/// [`create_game_token_vault`] must have created.
+pub fn initialize_game_with_tokens(
+    program_id: &Pubkey,
+    host: &Pubkey,
+    game: &Pubkey,
+    config: &GameConfig,
+    host_tokens: &Pubkey,
+) -> Instruction {
+    let mint = config.token_mint.expect("RPSToken games name their mint");
+    let mut tail = vec![
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new_readonly(mint, false),
+    ];
+    if config.entry_fee > 0 {
+        tail.push(AccountMeta::new(*host_tokens, false));
+        tail.push(AccountMeta::new(game_vault_address(program_id, game, &mint), false));
+    }
+    let mut ix = initialize_game(program_id, host, game, config);
+    // Ahead of the config PDA
+    ix.accounts.splice(5..5, tail);
+    ix
+}
+
+pub fn join_game(program_id: &Pubkey, player: &Pubkey, game: &Pubkey) -> Instruction {
+    build(
+        program_id,
+        RPSInstruction::JoinGame,
+        vec![
+            AccountMeta::new(*player, true),
+            AccountMeta::new(*game, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(ban_list_address(program_id, player).0, false),
+            AccountMeta::new_readonly(config_address(program_id).0, false),
+            AccountMeta::new(treasury_address(program_id).0, false),
+            pot_vault_meta(program_id, game),
+            AccountMeta::new(limits_address(program_id, player).0, false),
+            AccountMeta::new_readonly(exclusion_address(program_id, player).0, false),
+        ],
+    )
+}
+
+/// [`join_game`] for a paid entry under compliance mode.
+pub fn join_game_with_credential(
+    program_id: &Pubkey,
+    player: &Pubkey,
+    game: &Pubkey,
+    credential: &Credential,
+) -> Instruction {
+    let mut ix = join_game(program_id, player, game);
+    ix.accounts.extend(credential.metas());
+    ix
+}
+
+/// [`join_game`] for an RPSToken game: the entry moves from
+/// `player_tokens` into the game vault.
+pub fn join_game_with_tokens(
+    program_id: &Pubkey,
+    player: &Pubkey,
+    game: &Pubkey,
+    mint: &Pubkey,
+    player_tokens: &Pubkey,
+) -> Instruction {
+    let mut ix = join_game(program_id, player, game);
+    ix.accounts.extend(token_entry_metas(program_id, game, mint, player_tokens));
+    ix
+}
+
+/// Joins staking the game's approved stake mint `mint_index`, priced
+/// against the game currency: `player_tokens` holds that mint and `vault`
+/// is the game's vault for it.
+#[allow(clippy::too_many_arguments)]
+pub fn join_game_with_mint(
+    program_id: &Pubkey,
+    player: &Pubkey,
+    game: &Pubkey,
+    base_price_feed: &Pubkey,
+    mint_price_feed: &Pubkey,
+    player_tokens: &Pubkey,
+    vault: &Pubkey,
+    mint_index: u8,
+) -> Instruction {
+    build(
+        program_id,
+        RPSInstruction::JoinGameWithMint { mint_index },
+        vec![
+            AccountMeta::new(*player, true),
+            AccountMeta::new(*game, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(*base_price_feed, false),
+            AccountMeta::new_readonly(*mint_price_feed, false),
+            AccountMeta::new(*player_tokens, false),
+            AccountMeta::new(*vault, false),
+            AccountMeta::new_readonly(ban_list_address(program_id, player).0, false),
+            AccountMeta::new_readonly(exclusion_address(program_id, player).0, false),
+            AccountMeta::new_readonly(config_address(program_id).0, false),
+            AccountMeta::new(limits_address(program_id, player).0, false),
+        ],
+    )
+}
+
+/// Commits to a hand. Only the commitment is sent; keep the salt for
+/// [`reveal_choice`]. Hash it with `solana_rps::compute_commitment` for this
+/// game, its current round and `player`, or the reveal will not match.
+pub fn commit_choice(program_id: &Pubkey, player: &Pubkey, game: &Pubkey, committed_choice: [u8; 64]) -> Instruction {
+    build(
+        program_id,
+        RPSInstruction::CommitChoice { committed_choice },
+        vec![
+            AccountMeta::new(*player, true),
+            AccountMeta::new(*game, false),
+            AccountMeta::new_readonly(config_address(program_id).0, false),
+        ],
+    )
+}
+
+/// Replaces `player`'s commitment; only allowed before anyone else in the
+/// game has committed this round.
+pub fn recommit_choice(program_id: &Pubkey, player: &Pubkey, game: &Pubkey, committed_choice: [u8; 64]) -> Instruction {
+    build(
+        program_id,
+        RPSInstruction::RecommitChoice { committed_choice },
+        vec![
+            AccountMeta::new(*player, true),
+            AccountMeta::new(*game, false),
+            AccountMeta::new_readonly(config_address(program_id).0, false),
+        ],
+    )
+}
+
+/// Reveals `choice` with the salt it was committed under.
+pub fn reveal_choice(program_id: &Pubkey, player: &Pubkey, game: &Pubkey, choice: Choice, salt: [u8; 32]) -> Instruction {
+    build(
+        program_id,
+        RPSInstruction::RevealChoice { choice, salt },
+        vec![
+            AccountMeta::new(*player, true),
+            AccountMeta::new(*game, false),
+            AccountMeta::new_readonly(config_address(program_id).0, false),
+        ],
+    )
+}
+
+/// Reveals with a wallet signature instead of a raw salt. `signature`
+/// is `player`'s ed25519 signature over `solana_rps::reveal_message` for the
+/// current round, raw or as an off-chain message; the commitment must have
+/// been made with `solana_rps::signed_reveal_salt(signature)` as its salt.
+///
+/// Returns the ed25519 verify instruction followed by the reveal; send both
+/// in one transaction, in this order.
+pub fn reveal_choice_signed(
+    program_id: &Pubkey,
+    player: &Pubkey,
+    game: &Pubkey,
+    choice: Choice,
+    signed_message: &[u8],
+    signature: &[u8; 64],
+) -> Vec<Instruction> {
+    vec![
+        ed25519_verify(player, signed_message, signature),
+        build(
+            program_id,
+            RPSInstruction::RevealChoiceSigned { choice },
+            vec![
+                AccountMeta::new(*player, true),
+                AccountMeta::new(*game, false),
+                AccountMeta::new_readonly(config_address(program_id).0, false),
+                AccountMeta::new_readonly(sysvar::instructions::id(), false),
+            ],
+        ),
+    ]
+}
+
+/// Ed25519 precompile instruction checking one signature, with the key,
+/// signature and message all carried in its own data.
+pub fn ed25519_verify(signer: &Pubkey, message: &[u8], signature: &[u8; 64]) -> Instruction {
+    const HEADER: usize = 2 + 14;
+    let key_offset = HEADER;
+    let signature_offset = key_offset + 32;
+    let message_offset = signature_offset + 64;
+
+    let mut data = Vec::with_capacity(message_offset + message.len());
+    data.extend_from_slice(&[1, 0]);
+    for offset in [
+        signature_offset as u16,
+        u16::MAX,
+        key_offset as u16,
+        u16::MAX,
+        message_offset as u16,
+        message.len() as u16,
+        u16::MAX,
+    ] {
+        data.extend_from_slice(&offset.to_le_bytes());
+    }
+    data.extend_from_slice(signer.as_ref());
+    data.extend_from_slice(signature);
+    data.extend_from_slice(message);
+
+    Instruction {
+        program_id: ed25519_program::id(),
+        accounts: vec![],
+        data,
+    }
+}
+
+/// `phase_nonce` is the game's `phase_nonce` as last observed; the call
+/// fails with `StalePhase` if the game has moved on since.
+pub fn resolve_timeout(
+    program_id: &Pubkey,
+    caller: &Pubkey,
+    game: &Pubkey,
+    phase_nonce: u64,
+) -> Instruction {
+    build(
+        program_id,
+        RPSInstruction::ResolveTimeout { phase_nonce },
+        vec![
+            AccountMeta::new(*caller, true),
+            AccountMeta::new(*game, false),
+            pot_vault_meta(program_id, game),
+        ],
+    )
+}
+
+pub fn claim_winnings(program_id: &Pubkey, winner: &Pubkey, game: &Pubkey) -> Instruction {
+    build(
+        program_id,
+        RPSInstruction::ClaimWinnings,
+        vec![
+            AccountMeta::new(*winner, true),
+            AccountMeta::new(*game, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(config_address(program_id).0, false),
+            pot_vault_meta(program_id, game),
+            AccountMeta::new_readonly(sysvar::instructions::id(), false),
+        ],
+    )
+}
+
+/// [`claim_winnings`] for an RPSToken game, paid from the game vault into
+/// `winner_tokens`.
+pub fn claim_winnings_with_tokens(
+    program_id: &Pubkey,
+    winner: &Pubkey,
+    game: &Pubkey,
+    mint: &Pubkey,
+    winner_tokens: &Pubkey,
+) -> Instruction {
+    let mut ix = claim_winnings(program_id, winner, game);
+    ix.accounts.extend(token_payout_metas(program_id, game, mint, winner_tokens));
+    ix
+}
+
+/// First step of claiming a pot above the operator's large payout
+/// threshold; [`finalize_claim`] pays out once the delay has passed.
+pub fn initiate_claim(program_id: &Pubkey, winner: &Pubkey, game: &Pubkey) -> Instruction {
+    build(
+        program_id,
+        RPSInstruction::InitiateClaim,
+        vec![
+            AccountMeta::new(*winner, true),
+            AccountMeta::new(*game, false),
+            AccountMeta::new_readonly(config_address(program_id).0, false),
+        ],
+    )
+}
+
+pub fn finalize_claim(program_id: &Pubkey, winner: &Pubkey, game: &Pubkey) -> Instruction {
+    build(
+        program_id,
+        RPSInstruction::FinalizeClaim,
+        vec![
+            AccountMeta::new(*winner, true),
+            AccountMeta::new(*game, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(config_address(program_id).0, false),
+            pot_vault_meta(program_id, game),
+            AccountMeta::new_readonly(sysvar::instructions::id(), false),
+        ],
+    )
+}
+
+/// [`finalize_claim`] for an RPSToken game.
+pub fn finalize_claim_with_tokens(
+    program_id: &Pubkey,
+    winner: &Pubkey,
+    game: &Pubkey,
+    mint: &Pubkey,
+    winner_tokens: &Pubkey,
+) -> Instruction {
+    let mut ix = finalize_claim(program_id, winner, game);
+    ix.accounts.extend(token_payout_metas(program_id, game, mint, winner_tokens));
+    ix
+}
+
+pub fn rejoin_game(program_id: &Pubkey, player: &Pubkey, game: &Pubkey) -> Instruction {
+    build(
+        program_id,
+        RPSInstruction::RejoinGame,
+        vec![
+            AccountMeta::new(*player, true),
+            AccountMeta::new(*game, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new(treasury_address(program_id).0, false),
+            pot_vault_meta(program_id, game),
+        ],
+    )
+}
+
+/// [`rejoin_game`] for an RPSToken game.
+pub fn rejoin_game_with_tokens(
+    program_id: &Pubkey,
+    player: &Pubkey,
+    game: &Pubkey,
+    mint: &Pubkey,
+    player_tokens: &Pubkey,
+) -> Instruction {
+    let mut ix = rejoin_game(program_id, player, game);
+    ix.accounts.extend(token_entry_metas(program_id, game, mint, player_tokens));
+    ix
+}
+
+pub fn start_new_game_round(program_id: &Pubkey, initiator: &Pubkey, game: &Pubkey) -> Instruction {
+    build(
+        program_id,
+        RPSInstruction::StartNewGameRound,
+        vec![AccountMeta::new(*initiator, true), AccountMeta::new(*game, false)],
+    )
+}
+
+pub fn auto_play_next_round(program_id: &Pubkey, initiator: &Pubkey, game: &Pubkey) -> Instruction {
+    build(
+        program_id,
+        RPSInstruction::AutoPlayNextRound,
+        vec![AccountMeta::new(*initiator, true), AccountMeta::new(*game, false)],
+    )
+}
+
+/// In paid games `initiator` pays every bot's entry fee. `strategy` is a
+/// `solana_rps::BotStrategy` byte: how `BotAct` plays the new bots.
+pub fn add_bot_players(program_id: &Pubkey, initiator: &Pubkey, game: &Pubkey, count: u8, strategy: u8) -> Instruction {
+    build(
+        program_id,
+        RPSInstruction::AddBotPlayers { count, strategy },
+        vec![
+            AccountMeta::new(*initiator, true),
+            AccountMeta::new(*game, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new(treasury_address(program_id).0, false),
+            pot_vault_meta(program_id, game),
+            AccountMeta::new_readonly(config_address(program_id).0, false),
+        ],
+    )
+}
+
+/// [`add_bot_players`] for a paid RPSToken game: `initiator_tokens` pays
+/// `entry_fee * count` into `game_tokens`, which must be the game vault
+/// (`solana_rps::game_vault_address`).
+pub fn add_bot_players_with_tokens(
+    program_id: &Pubkey,
+    initiator: &Pubkey,
+    game: &Pubkey,
+    count: u8,
+    strategy: u8,
+    initiator_tokens: &Pubkey,
+    game_tokens: &Pubkey,
+) -> Instruction {
+    let mut ix = add_bot_players(program_id, initiator, game, count, strategy);
+    ix.accounts.extend([
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new(*initiator_tokens, false),
+        AccountMeta::new(*game_tokens, false),
+    ]);
+    ix
+}
+
+/// Commits or reveals for every bot seat of `game` that is waiting on one;
+/// anyone can send it.
+pub fn bot_act(program_id: &Pubkey, caller: &Pubkey, game: &Pubkey) -> Instruction {
+    build(
+        program_id,
+        RPSInstruction::BotAct,
+        vec![
+            AccountMeta::new_readonly(*caller, true),
+            AccountMeta::new(*game, false),
+            AccountMeta::new_readonly(sysvar::slot_hashes::id(), false),
+        ],
+    )
+}
+
+pub fn collect_fees(program_id: &Pubkey, fee_collector: &Pubkey, game: &Pubkey) -> Instruction {
+    build(
+        program_id,
+        RPSInstruction::CollectFees,
+        vec![
+            AccountMeta::new(*fee_collector, true),
+            AccountMeta::new(*game, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            pot_vault_meta(program_id, game),
+            AccountMeta::new_readonly(config_address(program_id).0, false),
+            AccountMeta::new_readonly(sysvar::instructions::id(), false),
+        ],
+    )
+}
+
+/// [`collect_fees`] for an RPSToken game, paid from the game vault into
+/// `collector_tokens`.
+pub fn collect_fees_with_tokens(
+    program_id: &Pubkey,
+    fee_collector: &Pubkey,
+    game: &Pubkey,
+    mint: &Pubkey,
+    collector_tokens: &Pubkey,
+) -> Instruction {
+    let mut ix = collect_fees(program_id, fee_collector, game);
+    ix.accounts.extend(token_payout_metas(program_id, game, mint, collector_tokens));
+    ix
+}
+
+pub fn create_tournament(
+    program_id: &Pubkey,
+    host: &Pubkey,
+    tournament: &Pubkey,
+    config: &TournamentConfig,
+) -> Instruction {
+    build(
+        program_id,
+        RPSInstruction::CreateTournament {
+            max_players: config.max_players,
+            entry_fee: config.entry_fee,
+            currency_mode: config.currency_mode,
+            token_mint: config.token_mint,
+            qualifier_of: config.qualifier_of,
+            qualifier_top_n: config.qualifier_top_n,
+            team_entries: config.team_entries,
+        },
+        vec![
+            AccountMeta::new(*host, true),
+            AccountMeta::new(*tournament, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(config_address(program_id).0, false),
+        ],
+    )
+}
+
+/// Joins a tournament; pass a credential for a paid entry under compliance
+/// mode, and the qualifier when the tournament has one.
+pub fn join_tournament(
+    program_id: &Pubkey,
+    player: &Pubkey,
+    tournament: &Pubkey,
+    credential: Option<&Credential>,
+    qualifier: Option<&Pubkey>,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(*player, true),
+        AccountMeta::new(*tournament, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(ban_list_address(program_id, player).0, false),
+        AccountMeta::new_readonly(exclusion_address(program_id, player).0, false),
+        AccountMeta::new_readonly(config_address(program_id).0, false),
+    ];
+    if let Some(credential) = credential {
+        accounts.extend(credential.metas());
+    }
+    if let Some(qualifier) = qualifier {
+        accounts.push(AccountMeta::new_readonly(*qualifier, false));
+    }
+    build(program_id, RPSInstruction::JoinTournament, accounts)
+}
+
+pub fn start_tournament(program_id: &Pubkey, host: &Pubkey, tournament: &Pubkey) -> Instruction {
+    build(
+        program_id,
+        RPSInstruction::StartTournament,
+        vec![AccountMeta::new(*host, true), AccountMeta::new(*tournament, false)],
+    )
+}
+
+pub fn report_match_result(
+    program_id: &Pubkey,
+    caller: &Pubkey,
+    tournament: &Pubkey,
+    match_index: u8,
+    game: &Pubkey,
+) -> Instruction {
+    build(
+        program_id,
+        RPSInstruction::ReportMatchResult { match_index },
+        vec![
+            AccountMeta::new(*caller, true),
+            AccountMeta::new(*tournament, false),
+            AccountMeta::new_readonly(*game, false),
+        ],
+    )
+}
+
+pub fn finish_tournament(program_id: &Pubkey, caller: &Pubkey, tournament: &Pubkey) -> Instruction {
+    build(
+        program_id,
+        RPSInstruction::FinishTournament,
+        vec![
+            AccountMeta::new(*caller, true),
+            AccountMeta::new(*tournament, false),
+            AccountMeta::new(treasury_address(program_id).0, false),
+        ],
+    )
+}
+
+/// [`finish_tournament`] that also pays the prizes of `placed`, the top
+/// placings' wallets in standings order (champion first).
+pub fn finish_tournament_with_payouts(
+    program_id: &Pubkey,
+    caller: &Pubkey,
+    tournament: &Pubkey,
+    placed: &[Pubkey],
+) -> Instruction {
+    let mut ix = finish_tournament(program_id, caller, tournament);
+    ix.accounts.extend(placed.iter().map(|wallet| AccountMeta::new(*wallet, false)));
+    ix
+}
+
+pub fn claim_tournament_prize(program_id: &Pubkey, player: &Pubkey, tournament: &Pubkey) -> Instruction {
+    build(
+        program_id,
+        RPSInstruction::ClaimTournamentPrize,
+        vec![AccountMeta::new(*player, true), AccountMeta::new(*tournament, false)],
+    )
+}
+
+pub fn open_tournament_match(
+    program_id: &Pubkey,
+    host: &Pubkey,
+    tournament: &Pubkey,
+    match_index: u8,
+    game: &Pubkey,
+) -> Instruction {
+    build(
+        program_id,
+        RPSInstruction::OpenTournamentMatch { match_index },
+        vec![
+            AccountMeta::new(*host, true),
+            AccountMeta::new(*tournament, false),
+            AccountMeta::new(*game, false),
+        ],
+    )
+}
+
+pub fn disqualify_player(program_id: &Pubkey, host: &Pubkey, tournament: &Pubkey, player: &Pubkey) -> Instruction {
+    build(
+        program_id,
+        RPSInstruction::DisqualifyPlayer { player: *player },
+        vec![AccountMeta::new(*host, true), AccountMeta::new(*tournament, false)],
+    )
+}
+
+pub fn set_tournament_admins(program_id: &Pubkey, host: &Pubkey, tournament: &Pubkey, admins: Vec<Pubkey>) -> Instruction {
+    build(
+        program_id,
+        RPSInstruction::SetTournamentAdmins { admins },
+        vec![AccountMeta::new(*host, true), AccountMeta::new(*tournament, false)],
+    )
+}
+
+/// Creates the program config; the signer becomes its authority.
+pub fn initialize_config(program_id: &Pubkey, authority: &Pubkey) -> Instruction {
+    build(
+        program_id,
+        RPSInstruction::InitializeConfig,
+        vec![
+            AccountMeta::new(*authority, true),
+            AccountMeta::new(config_address(program_id).0, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+pub fn ban_player(program_id: &Pubkey, authority: &Pubkey, wallet: &Pubkey) -> Instruction {
+    build(
+        program_id,
+        RPSInstruction::BanPlayer { wallet: *wallet },
+        vec![
+            AccountMeta::new(*authority, true),
+            AccountMeta::new_readonly(config_address(program_id).0, false),
+            AccountMeta::new(ban_list_address(program_id, wallet).0, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+pub fn unban_player(program_id: &Pubkey, authority: &Pubkey, wallet: &Pubkey) -> Instruction {
+    build(
+        program_id,
+        RPSInstruction::UnbanPlayer { wallet: *wallet },
+        vec![
+            AccountMeta::new(*authority, true),
+            AccountMeta::new_readonly(config_address(program_id).0, false),
+            AccountMeta::new(ban_list_address(program_id, wallet).0, false),
+        ],
+    )
+}
+
+pub fn set_compliance(program_id: &Pubkey, authority: &Pubkey, enabled: bool, attestors: Vec<Pubkey>) -> Instruction {
+    build(
+        program_id,
+        RPSInstruction::SetCompliance { enabled, attestors },
+        vec![
+            AccountMeta::new(*authority, true),
+            AccountMeta::new(config_address(program_id).0, false),
+        ],
+    )
+}
+
+/// Sets how the adaptive default timeout is derived; a zero multiplier
+/// turns it off.
+pub fn set_timeout_policy(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    multiplier_pct: u16,
+    min_secs: u64,
+    max_secs: u64,
+) -> Instruction {
+    build(
+        program_id,
+        RPSInstruction::SetTimeoutPolicy {
+            multiplier_pct,
+            min_secs,
+            max_secs,
+        },
+        vec![
+            AccountMeta::new(*authority, true),
+            AccountMeta::new(config_address(program_id).0, false),
+        ],
+    )
+}
+
+pub fn refresh_default_timeout(program_id: &Pubkey, caller: &Pubkey) -> Instruction {
+    build(
+        program_id,
+        RPSInstruction::RefreshDefaultTimeout,
+        vec![
+            AccountMeta::new(*caller, true),
+            AccountMeta::new(config_address(program_id).0, false),
+            AccountMeta::new_readonly(stats_address(program_id).0, false),
+        ],
+    )
+}
+
+/// Registers `name` for `lobby` (a game or recurring lobby), or re-points a
+/// name `registrant` already owns; resolve it again with
+/// [`crate::send::resolve_lobby_name`].
+pub fn register_lobby_name(program_id: &Pubkey, registrant: &Pubkey, name: &str, lobby: &Pubkey) -> Instruction {
+    build(
+        program_id,
+        RPSInstruction::RegisterLobbyName { name: name.to_string() },
+        vec![
+            AccountMeta::new(*registrant, true),
+            AccountMeta::new(lobby_name_address(program_id, name).0, false),
+            AccountMeta::new_readonly(*lobby, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+/// Creates a standing lobby; `recurring` must be a fresh keypair that signs
+/// the transaction.
+pub fn create_recurring_lobby(
+    program_id: &Pubkey,
+    host: &Pubkey,
+    recurring: &Pubkey,
+    template: LobbyTemplate,
+    interval_secs: u64,
+    first_spawn_at: u64,
+) -> Instruction {
+    build(
+        program_id,
+        RPSInstruction::CreateRecurringLobby {
+            template,
+            interval_secs,
+            first_spawn_at,
+        },
+        vec![
+            AccountMeta::new(*host, true),
+            AccountMeta::new(*recurring, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+/// Spawns the recurring lobby's next game into `game`, a fresh keypair that
+/// signs the transaction; `caller` pays its rent.
+pub fn spawn_scheduled_lobby(program_id: &Pubkey, caller: &Pubkey, recurring: &Pubkey, game: &Pubkey) -> Instruction {
+    build(
+        program_id,
+        RPSInstruction::SpawnScheduledLobby,
+        vec![
+            AccountMeta::new(*caller, true),
+            AccountMeta::new(*recurring, false),
+            AccountMeta::new(*game, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+            pot_vault_meta(program_id, game),
+            AccountMeta::new_readonly(config_address(program_id).0, false),
+        ],
+    )
+}
+
+/// Queues `player` for a seat in a full lobby, escrowing the entry fee.
+pub fn join_waitlist(program_id: &Pubkey, player: &Pubkey, game: &Pubkey) -> Instruction {
+    build(
+        program_id,
+        RPSInstruction::JoinWaitlist,
+        vec![
+            AccountMeta::new(*player, true),
+            AccountMeta::new_readonly(*game, false),
+            AccountMeta::new(waitlist_address(program_id, game).0, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(ban_list_address(program_id, player).0, false),
+            AccountMeta::new_readonly(exclusion_address(program_id, player).0, false),
+            AccountMeta::new_readonly(config_address(program_id).0, false),
+        ],
+    )
+}
+
+/// Gives up `player`'s seat before play starts.
+pub fn leave_game(program_id: &Pubkey, player: &Pubkey, game: &Pubkey) -> Instruction {
+    build(
+        program_id,
+        RPSInstruction::LeaveGame,
+        vec![
+            AccountMeta::new(*player, true),
+            AccountMeta::new(*game, false),
+            AccountMeta::new(waitlist_address(program_id, game).0, false),
+            AccountMeta::new(treasury_address(program_id).0, false),
+            pot_vault_meta(program_id, game),
+        ],
+    )
+}
+
+/// Removes `player` from `host`'s lobby before play starts.
+pub fn kick_player(program_id: &Pubkey, host: &Pubkey, game: &Pubkey, player: &Pubkey) -> Instruction {
+    build(
+        program_id,
+        RPSInstruction::KickPlayer,
+        vec![
+            AccountMeta::new_readonly(*host, true),
+            AccountMeta::new(*game, false),
+            AccountMeta::new(waitlist_address(program_id, game).0, false),
+            AccountMeta::new(treasury_address(program_id).0, false),
+            pot_vault_meta(program_id, game),
+            AccountMeta::new(*player, false),
+        ],
+    )
+}
+
+/// Refunds the waitlist of a started game; `queued` must list the waiting
+/// wallets in queue order.
+pub fn refund_waitlist(program_id: &Pubkey, caller: &Pubkey, game: &Pubkey, queued: &[Pubkey]) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*caller, true),
+        AccountMeta::new_readonly(*game, false),
+        AccountMeta::new(waitlist_address(program_id, game).0, false),
+    ];
+    accounts.extend(queued.iter().map(|wallet| AccountMeta::new(*wallet, false)));
+    build(program_id, RPSInstruction::RefundWaitlist, accounts)
+}
+
+/// Returns `player`'s escrowed entry from a lobby that never filled, once
+/// `solana_rps::ENTRY_REFUND_SECS` have passed.
+pub fn refund_expired_escrow(program_id: &Pubkey, caller: &Pubkey, game: &Pubkey, player: &Pubkey) -> Instruction {
+    build(
+        program_id,
+        RPSInstruction::RefundExpiredEscrow,
+        vec![
+            AccountMeta::new_readonly(*caller, true),
+            AccountMeta::new(*game, false),
+            AccountMeta::new(*player, false),
+            pot_vault_meta(program_id, game),
+        ],
+    )
+}
+
+/// Holds (or stops holding) payouts of games flagged for collusion review.
+pub fn set_collusion_policy(program_id: &Pubkey, authority: &Pubkey, hold_flagged_payouts: bool) -> Instruction {
+    build(
+        program_id,
+        RPSInstruction::SetCollusionPolicy { hold_flagged_payouts },
+        vec![
+            AccountMeta::new(*authority, true),
+            AccountMeta::new(config_address(program_id).0, false),
+        ],
+    )
+}
+
+/// Pots above `large_payout_threshold` must be claimed in two steps,
+/// `claim_delay_secs` apart; a zero threshold turns this off.
+pub fn set_claim_policy(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    large_payout_threshold: u64,
+    claim_delay_secs: u64,
+) -> Instruction {
+    build(
+        program_id,
+        RPSInstruction::SetClaimPolicy {
+            large_payout_threshold,
+            claim_delay_secs,
+        },
+        vec![
+            AccountMeta::new(*authority, true),
+            AccountMeta::new(config_address(program_id).0, false),
+        ],
+    )
+}
+
+/// Tops the pots of SOL games entering at `subsidy_entry_floor` or more up
+/// to `guaranteed_min_pot` from the treasury; a zero minimum turns this off.
+pub fn set_pot_guarantee(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    guaranteed_min_pot: u64,
+    subsidy_entry_floor: u64,
+) -> Instruction {
+    build(
+        program_id,
+        RPSInstruction::SetPotGuarantee {
+            guaranteed_min_pot,
+            subsidy_entry_floor,
+        },
+        vec![
+            AccountMeta::new(*authority, true),
+            AccountMeta::new(config_address(program_id).0, false),
+        ],
+    )
+}
+
+/// Applies the pot guarantee to a started game.
+pub fn apply_pot_subsidy(program_id: &Pubkey, caller: &Pubkey, game: &Pubkey) -> Instruction {
+    build(
+        program_id,
+        RPSInstruction::ApplyPotSubsidy,
+        vec![
+            AccountMeta::new_readonly(*caller, true),
+            AccountMeta::new(*game, false),
+            AccountMeta::new_readonly(config_address(program_id).0, false),
+            AccountMeta::new(treasury_address(program_id).0, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            pot_vault_meta(program_id, game),
+        ],
+    )
+}
+
+pub fn clear_game_flag(program_id: &Pubkey, authority: &Pubkey, game: &Pubkey) -> Instruction {
+    build(
+        program_id,
+        RPSInstruction::ClearGameFlag,
+        vec![
+            AccountMeta::new(*authority, true),
+            AccountMeta::new_readonly(config_address(program_id).0, false),
+            AccountMeta::new(*game, false),
+        ],
+    )
+}
+
+/// Credits `player` with finished `game` in the `Participation` PDA of
+/// `epoch`, which must be the epoch the transaction lands in.
+pub fn record_participation(program_id: &Pubkey, player: &Pubkey, game: &Pubkey, epoch: u64) -> Instruction {
+    build(
+        program_id,
+        RPSInstruction::RecordParticipation,
+        vec![
+            AccountMeta::new(*player, true),
+            AccountMeta::new(*game, false),
+            AccountMeta::new(participation_address(program_id, player, epoch).0, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+/// View instruction; simulate it and decode the return data as
+/// `solana_rps::Claimable` (see [`crate::view::claimable`]).
+pub fn get_claimable(program_id: &Pubkey, game: &Pubkey, player: &Pubkey) -> Instruction {
+    build(
+        program_id,
+        RPSInstruction::GetClaimable { player: *player },
+        vec![AccountMeta::new_readonly(*game, false)],
+    )
+}
+
+/// View instruction; simulate it and decode the return data as
+/// `solana_rps::FeeQuote` (see [`crate::view::fee_quote`]).
+pub fn get_fee_quote(program_id: &Pubkey, entry_fee: u64, currency_mode: u8) -> Instruction {
+    build(
+        program_id,
+        RPSInstruction::GetFeeQuote { entry_fee, currency_mode },
+        vec![AccountMeta::new_readonly(config_address(program_id).0, false)],
+    )
+}
+
+/// Sets the operator kill switch: bit n of `paused_instructions` pauses
+/// discriminant n, `token_entries_paused` pauses RPSToken entries. Only
+/// bits in `solana_rps::PAUSABLE_INSTRUCTIONS` are accepted. Pass 0 and
+/// `false` to lift every pause.
+pub fn set_paused_instructions(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    paused_instructions: u128,
+    token_entries_paused: bool,
+) -> Instruction {
+    build(
+        program_id,
+        RPSInstruction::SetPausedInstructions {
+            paused_instructions,
+            token_entries_paused,
+        },
+        vec![
+            AccountMeta::new(*authority, true),
+            AccountMeta::new(config_address(program_id).0, false),
+        ],
+    )
+}
+
+/// Makes every nickname change cost `fee` of `fee_mint`, paid to the
+/// treasury; a fee of 0 makes them free.
+pub fn set_nickname_policy(program_id: &Pubkey, authority: &Pubkey, fee: u64, fee_mint: Option<Pubkey>) -> Instruction {
+    build(
+        program_id,
+        RPSInstruction::SetNicknamePolicy { fee, fee_mint },
+        vec![
+            AccountMeta::new(*authority, true),
+            AccountMeta::new(config_address(program_id).0, false),
+        ],
+    )
+}
+
+/// Sets `player`'s nickname. `current` is the nickname they hold now, if
+/// any, so its registry entry can be freed; add the fee accounts with
+/// [`with_nickname_fee`] when the operator charges one.
+pub fn set_nickname(program_id: &Pubkey, player: &Pubkey, nickname: &str, current: Option<&str>) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(*player, true),
+        AccountMeta::new(profile_address(program_id, player).0, false),
+        AccountMeta::new(nickname_address(program_id, nickname).0, false),
+        AccountMeta::new_readonly(config_address(program_id).0, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    if let Some(current) = current {
+        accounts.push(AccountMeta::new(nickname_address(program_id, current).0, false));
+    }
+    build(
+        program_id,
+        RPSInstruction::SetNickname {
+            nickname: nickname.to_string(),
+        },
+        accounts,
+    )
+}
+
+/// Appends the token accounts a `set_nickname` pays the operator's fee
+/// from and to: the player's and the treasury's accounts for the fee mint.
+pub fn with_nickname_fee(mut instruction: Instruction, player_tokens: &Pubkey, treasury_tokens: &Pubkey) -> Instruction {
+    instruction.accounts.extend([
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new(*player_tokens, false),
+        AccountMeta::new(*treasury_tokens, false),
+    ]);
+    instruction
+}
+
+/// Settles and closes a game created under an older layout. `seats` lists
+/// the seated wallets in seat order; `host` gets the rent back.
+pub fn finalize_legacy_game(
+    program_id: &Pubkey,
+    caller: &Pubkey,
+    game: &Pubkey,
+    host: &Pubkey,
+    seats: &[Pubkey],
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*caller, true),
+        AccountMeta::new(*game, false),
+        AccountMeta::new(*host, false),
+    ];
+    accounts.extend(seats.iter().map(|wallet| AccountMeta::new(*wallet, false)));
+    build(program_id, RPSInstruction::FinalizeLegacyGame, accounts)
+}
+
+/// Challenges `opponent` to a one-throw match for `stake` lamports each;
+/// `challenge` must be a fresh keypair that signs the transaction.
+pub fn create_challenge(
+    program_id: &Pubkey,
+    challenger: &Pubkey,
+    challenge: &Pubkey,
+    opponent: &Pubkey,
+    stake: u64,
+    credential: Option<&Credential>,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(*challenger, true),
+        AccountMeta::new(*challenge, true),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(ban_list_address(program_id, challenger).0, false),
+        AccountMeta::new_readonly(exclusion_address(program_id, challenger).0, false),
+        AccountMeta::new_readonly(config_address(program_id).0, false),
+    ];
+    if let Some(credential) = credential {
+        accounts.extend(credential.metas());
+    }
+    build(
+        program_id,
+        RPSInstruction::CreateChallenge {
+            opponent: *opponent,
+            stake,
+        },
+        accounts,
+    )
+}
+
+pub fn accept_challenge(
+    program_id: &Pubkey,
+    opponent: &Pubkey,
+    challenge: &Pubkey,
+    credential: Option<&Credential>,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(*opponent, true),
+        AccountMeta::new(*challenge, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(ban_list_address(program_id, opponent).0, false),
+        AccountMeta::new_readonly(exclusion_address(program_id, opponent).0, false),
+        AccountMeta::new_readonly(config_address(program_id).0, false),
+    ];
+    if let Some(credential) = credential {
+        accounts.extend(credential.metas());
+    }
+    build(program_id, RPSInstruction::AcceptChallenge, accounts)
+}
+
+/// Commits to a challenge throw, hashed with `solana_rps::compute_commitment`
+/// for the challenge account, round 1 and `player`.
+pub fn commit_challenge_choice(
+    program_id: &Pubkey,
+    player: &Pubkey,
+    challenge: &Pubkey,
+    commitment: [u8; 64],
+) -> Instruction {
+    build(
+        program_id,
+        RPSInstruction::CommitChallengeChoice { commitment },
+        vec![AccountMeta::new(*player, true), AccountMeta::new(*challenge, false)],
+    )
+}
+
+/// `challenger` and `opponent` are the challenge's two sides, which the
+/// final reveal pays out.
+pub fn reveal_challenge_choice(
+    program_id: &Pubkey,
+    player: &Pubkey,
+    challenge: &Pubkey,
+    challenger: &Pubkey,
+    opponent: &Pubkey,
+    choice: Choice,
+    salt: [u8; 32],
+) -> Instruction {
+    build(
+        program_id,
+        RPSInstruction::RevealChallengeChoice { choice, salt },
+        vec![
+            AccountMeta::new(*player, true),
+            AccountMeta::new(*challenge, false),
+            AccountMeta::new(*challenger, false),
+            AccountMeta::new(*opponent, false),
+        ],
+    )
+}
+
+pub fn expire_challenge(
+    program_id: &Pubkey,
+    caller: &Pubkey,
+    challenge: &Pubkey,
+    challenger: &Pubkey,
+    opponent: &Pubkey,
+) -> Instruction {
+    build(
+        program_id,
+        RPSInstruction::ExpireChallenge,
+        vec![
+            AccountMeta::new(*caller, true),
+            AccountMeta::new(*challenge, false),
+            AccountMeta::new(*challenger, false),
+            AccountMeta::new(*opponent, false),
+        ],
+    )
+}
+
+/// The loser of a decided challenge offers the winner double or nothing.
+pub fn offer_double_or_nothing(
+    program_id: &Pubkey,
+    loser: &Pubkey,
+    challenge: &Pubkey,
+    credential: Option<&Credential>,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(*loser, true),
+        AccountMeta::new(*challenge, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(ban_list_address(program_id, loser).0, false),
+        AccountMeta::new_readonly(exclusion_address(program_id, loser).0, false),
+        AccountMeta::new_readonly(config_address(program_id).0, false),
+    ];
+    if let Some(credential) = credential {
+        accounts.extend(credential.metas());
+    }
+    build(program_id, RPSInstruction::OfferDoubleOrNothing, accounts)
+}
+
+/// The winner takes up a double-or-nothing offer; `rematch` must be a fresh
+/// keypair that signs the transaction, and `challenger` is side 0 of the
+/// settled `challenge`.
+pub fn accept_double_or_nothing(
+    program_id: &Pubkey,
+    winner: &Pubkey,
+    challenge: &Pubkey,
+    rematch: &Pubkey,
+    challenger: &Pubkey,
+    credential: Option<&Credential>,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(*winner, true),
+        AccountMeta::new(*challenge, false),
+        AccountMeta::new(*rematch, true),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new(*challenger, false),
+        AccountMeta::new_readonly(ban_list_address(program_id, winner).0, false),
+        AccountMeta::new_readonly(exclusion_address(program_id, winner).0, false),
+        AccountMeta::new_readonly(config_address(program_id).0, false),
+    ];
+    if let Some(credential) = credential {
+        accounts.extend(credential.metas());
+    }
+    build(program_id, RPSInstruction::AcceptDoubleOrNothing, accounts)
+}
+
+/// Seals `host`'s fresh lobby as a mystery-stake game; `commitment` is
+/// `solana_rps::hidden_fee_commitment(fee, salt)` for a fee between
+/// `min_fee` and the lobby's entry fee. Send it alongside
+/// [`initialize_game`].
+pub fn seal_entry_fee(program_id: &Pubkey, host: &Pubkey, game: &Pubkey, commitment: [u8; 32], min_fee: u64) -> Instruction {
+    build(
+        program_id,
+        RPSInstruction::SealEntryFee { commitment, min_fee },
+        vec![AccountMeta::new_readonly(*host, true), AccountMeta::new(*game, false)],
+    )
+}
+
+/// Reveals a full mystery-stake lobby's entry fee and refunds the
+/// difference to `seats`, the seated wallets in seat order. Past the commit
+/// deadline anyone may call, and `fee` and `salt` are ignored.
+pub fn reveal_entry_fee(
+    program_id: &Pubkey,
+    caller: &Pubkey,
+    game: &Pubkey,
+    fee: u64,
+    salt: [u8; 32],
+    seats: &[Pubkey],
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*caller, true),
+        AccountMeta::new(*game, false),
+        pot_vault_meta(program_id, game),
+        AccountMeta::new(treasury_address(program_id).0, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    accounts.extend(seats.iter().map(|wallet| AccountMeta::new(*wallet, false)));
+    build(program_id, RPSInstruction::RevealEntryFee { fee, salt }, accounts)
+}
+
+/// Snapshots a finished game's outcome into its `GameResult` PDA.
+pub fn record_game_result(program_id: &Pubkey, payer: &Pubkey, game: &Pubkey) -> Instruction {
+    build(
+        program_id,
+        RPSInstruction::RecordGameResult,
+        vec![
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(*game, false),
+            AccountMeta::new(game_result_address(program_id, game).0, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+/// Folds the recorded results of `games`, all finished in archive `epoch`
+/// and already closed, into that epoch's archive and closes them, refunding
+/// their rent to `cranker`.
+pub fn archive_game_results(program_id: &Pubkey, cranker: &Pubkey, epoch: u64, games: &[Pubkey]) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(*cranker, true),
+        AccountMeta::new(result_archive_address(program_id, epoch).0, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    accounts.extend(games.iter().flat_map(|game| {
+        [
+            AccountMeta::new(game_result_address(program_id, game).0, false),
+            AccountMeta::new_readonly(*game, false),
+        ]
+    }));
+    build(program_id, RPSInstruction::ArchiveGameResults { epoch }, accounts)
+}
+
+/// View instruction; simulate it and decode the return data as
+/// `solana_rps::GameAudit` (see [`crate::view::audit_game`]).
+pub fn audit_game(program_id: &Pubkey, game: &Pubkey) -> Instruction {
+    build(
+        program_id,
+        RPSInstruction::AuditGame,
+        vec![
+            AccountMeta::new_readonly(*game, false),
+            AccountMeta::new_readonly(pot_vault_address(program_id, game).0, false),
+        ],
+    )
+}
+
+/// View instruction; simulate it and decode the return data as
+/// `solana_rps::SpectatorView` (see [`crate::view::spectator_view`]).
+pub fn get_spectator_view(program_id: &Pubkey, game: &Pubkey) -> Instruction {
+    build(
+        program_id,
+        RPSInstruction::GetSpectatorView,
+        vec![AccountMeta::new_readonly(*game, false)],
+    )
+}
+
+/// Closes a finished, fully paid-out game and its pot vault, sending the
+/// rent to `recipient`. Anyone may close to the host; only the host may pick
+/// another recipient. The game's result must be recorded first (see
+/// [`record_game_result`]). Pass the tournament of a tournament match game.
+pub fn close_game(
+    program_id: &Pubkey,
+    caller: &Pubkey,
+    game: &Pubkey,
+    recipient: &Pubkey,
+    tournament: Option<&Pubkey>,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*caller, true),
+        AccountMeta::new(*game, false),
+        AccountMeta::new(pot_vault_address(program_id, game).0, false),
+        AccountMeta::new(*recipient, false),
+        AccountMeta::new_readonly(game_result_address(program_id, game).0, false),
+    ];
+    if let Some(tournament) = tournament {
+        accounts.push(AccountMeta::new_readonly(*tournament, false));
+    }
+    build(program_id, RPSInstruction::CloseGame, accounts)
+}
+
+/// Sets the fee rate of games and tournaments created from now on, in
+/// basis points, and the wallet that alone may collect held fees; `None`
+/// restores the default rate, or open collection from finished games.
+pub fn set_fee_policy(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    fee_collector: Option<Pubkey>,
+    fee_bps: Option<u16>,
+) -> Instruction {
+    build(
+        program_id,
+        RPSInstruction::SetFeePolicy { fee_collector, fee_bps },
+        vec![
+            AccountMeta::new(*authority, true),
+            AccountMeta::new(config_address(program_id).0, false),
+        ],
+    )
+}
+
+/// Turns the payout guard on or off: while on, claims and fee collection
+/// must be sent on their own, with at most compute budget instructions and
+/// other instructions of this program alongside.
+pub fn set_payout_guard(program_id: &Pubkey, authority: &Pubkey, enabled: bool) -> Instruction {
+    build(
+        program_id,
+        RPSInstruction::SetPayoutGuard { enabled },
+        vec![
+            AccountMeta::new(*authority, true),
+            AccountMeta::new(config_address(program_id).0, false),
+        ],
+    )
+}
+
+/// Hands the fee and payout settings over to stakers of `stake_mint`. The
+/// governance vault (`governance_vault_address`) must exist before anyone
+/// stakes.
+pub fn initialize_governance(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    stake_mint: &Pubkey,
+    quorum: u64,
+    voting_secs: u64,
+) -> Instruction {
+    build(
+        program_id,
+        RPSInstruction::InitializeGovernance {
+            stake_mint: *stake_mint,
+            quorum,
+            voting_secs,
+        },
+        vec![
+            AccountMeta::new(*authority, true),
+            AccountMeta::new(config_address(program_id).0, false),
+            AccountMeta::new(governance_address(program_id).0, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+/// Stakes `amount` of `stake_mint` from `staker_tokens`.
+pub fn stake_governance_tokens(
+    program_id: &Pubkey,
+    staker: &Pubkey,
+    stake_mint: &Pubkey,
+    staker_tokens: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    build(
+        program_id,
+        RPSInstruction::StakeGovernanceTokens { amount },
+        vec![
+            AccountMeta::new(*staker, true),
+            AccountMeta::new(voter_stake_address(program_id, staker).0, false),
+            AccountMeta::new(governance_address(program_id).0, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new(*staker_tokens, false),
+            AccountMeta::new(governance_vault_address(program_id, stake_mint), false),
+        ],
+    )
+}
+
+/// Withdraws `amount` of stake to `staker_tokens`; fails while a vote the
+/// stake was cast in is still open.
+pub fn unstake_governance_tokens(
+    program_id: &Pubkey,
+    staker: &Pubkey,
+    stake_mint: &Pubkey,
+    staker_tokens: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    build(
+        program_id,
+        RPSInstruction::UnstakeGovernanceTokens { amount },
+        vec![
+            AccountMeta::new_readonly(*staker, true),
+            AccountMeta::new(voter_stake_address(program_id, staker).0, false),
+            AccountMeta::new(governance_address(program_id).0, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new(*staker_tokens, false),
+            AccountMeta::new(governance_vault_address(program_id, stake_mint), false),
+        ],
+    )
+}
+
+/// Opens proposal `index`, which must be the governance's `proposal_count`.
+pub fn propose_parameter_change(
+    program_id: &Pubkey,
+    proposer: &Pubkey,
+    index: u64,
+    change: ParameterChange,
+) -> Instruction {
+    build(
+        program_id,
+        RPSInstruction::ProposeParameterChange { change },
+        vec![
+            AccountMeta::new(*proposer, true),
+            AccountMeta::new_readonly(voter_stake_address(program_id, proposer).0, false),
+            AccountMeta::new(governance_address(program_id).0, false),
+            AccountMeta::new_readonly(config_address(program_id).0, false),
+            AccountMeta::new(proposal_address(program_id, index).0, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+pub fn cast_vote(program_id: &Pubkey, voter: &Pubkey, index: u64, approve: bool) -> Instruction {
+    let proposal = proposal_address(program_id, index).0;
+    build(
+        program_id,
+        RPSInstruction::CastVote { approve },
+        vec![
+            AccountMeta::new(*voter, true),
+            AccountMeta::new(voter_stake_address(program_id, voter).0, false),
+            AccountMeta::new(proposal, false),
+            AccountMeta::new(vote_record_address(program_id, &proposal, voter).0, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+pub fn execute_proposal(program_id: &Pubkey, caller: &Pubkey, index: u64) -> Instruction {
+    build(
+        program_id,
+        RPSInstruction::ExecuteProposal,
+        vec![
+            AccountMeta::new_readonly(*caller, true),
+            AccountMeta::new(proposal_address(program_id, index).0, false),
+            AccountMeta::new_readonly(governance_address(program_id).0, false),
+            AccountMeta::new(config_address(program_id).0, false),
+        ],
+    )
+}
+
+/// Sets `player`'s own wager limit and, with a non-zero `cool_off_secs`, a
+/// cool-off. Pass the current limit to keep it.
+pub fn set_limits(program_id: &Pubkey, player: &Pubkey, daily_limit: u64, cool_off_secs: u64) -> Instruction {
+    build(
+        program_id,
+        RPSInstruction::SetLimits { daily_limit, cool_off_secs },
+        vec![
+            AccountMeta::new(*player, true),
+            AccountMeta::new(limits_address(program_id, player).0, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+/// Excludes `player` from every game, tournament and challenge entry until
+/// the unix time `until`. It can be extended later but never shortened.
+pub fn self_exclude(program_id: &Pubkey, player: &Pubkey, until: u64) -> Instruction {
+    build(
+        program_id,
+        RPSInstruction::SelfExclude { until },
+        vec![
+            AccountMeta::new(*player, true),
+            AccountMeta::new(exclusion_address(program_id, player).0, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+/// Sets up the cold treasury; `threshold` of `signers` must approve each of
+/// its actions, which wait `timelock_secs` after being proposed.
+pub fn initialize_cold_treasury(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    signers: Vec<Pubkey>,
+    threshold: u8,
+    timelock_secs: u64,
+    hot_cap: u64,
+) -> Instruction {
+    build(
+        program_id,
+        RPSInstruction::InitializeColdTreasury {
+            signers,
+            threshold,
+            timelock_secs,
+            hot_cap,
+        },
+        vec![
+            AccountMeta::new(*authority, true),
+            AccountMeta::new_readonly(config_address(program_id).0, false),
+            AccountMeta::new(cold_treasury_address(program_id).0, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+/// Always lists the insurance fund; the program skips it until the fund
+/// is configured.
+pub fn sweep_treasury(program_id: &Pubkey, caller: &Pubkey) -> Instruction {
+    build(
+        program_id,
+        RPSInstruction::SweepTreasury,
+        vec![
+            AccountMeta::new_readonly(*caller, true),
+            AccountMeta::new(treasury_address(program_id).0, false),
+            AccountMeta::new(cold_treasury_address(program_id).0, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new(insurance_fund_address(program_id).0, false),
+        ],
+    )
+}
+
+/// `index` must be the cold treasury's current `proposal_count`.
+pub fn propose_cold_action(program_id: &Pubkey, signer: &Pubkey, index: u64, action: ColdAction) -> Instruction {
+    build(
+        program_id,
+        RPSInstruction::ProposeColdAction { action },
+        vec![
+            AccountMeta::new(*signer, true),
+            AccountMeta::new(cold_treasury_address(program_id).0, false),
+            AccountMeta::new(cold_proposal_address(program_id, index).0, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+pub fn approve_cold_action(program_id: &Pubkey, signer: &Pubkey, index: u64) -> Instruction {
+    build(
+        program_id,
+        RPSInstruction::ApproveColdAction,
+        vec![
+            AccountMeta::new_readonly(*signer, true),
+            AccountMeta::new_readonly(cold_treasury_address(program_id).0, false),
+            AccountMeta::new(cold_proposal_address(program_id, index).0, false),
+        ],
+    )
+}
+
+/// Pass the withdrawal's `recipient` when the proposal is a `Withdraw`.
+pub fn execute_cold_action(
+    program_id: &Pubkey,
+    caller: &Pubkey,
+    index: u64,
+    recipient: Option<&Pubkey>,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*caller, true),
+        AccountMeta::new(cold_treasury_address(program_id).0, false),
+        AccountMeta::new(cold_proposal_address(program_id, index).0, false),
+    ];
+    if let Some(recipient) = recipient {
+        accounts.push(AccountMeta::new(*recipient, false));
+    }
+    build(program_id, RPSInstruction::ExecuteColdAction, accounts)
+}
+
+/// Pays every unpaid winner of a finished SOL game at once. `winners` are
+/// the unpaid winners' payees (`Game::payee`) in seat order; anyone can
+/// send it.
+pub fn distribute_winnings(program_id: &Pubkey, caller: &Pubkey, game: &Pubkey, winners: &[Pubkey]) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*caller, true),
+        AccountMeta::new(*game, false),
+        AccountMeta::new_readonly(config_address(program_id).0, false),
+        pot_vault_meta(program_id, game),
+        AccountMeta::new_readonly(sysvar::instructions::id(), false),
+    ];
+    accounts.extend(winners.iter().map(|winner| AccountMeta::new(*winner, false)));
+    build(program_id, RPSInstruction::DistributeWinnings, accounts)
+}
+
+pub fn configure_insurance_fund(program_id: &Pubkey, authority: &Pubkey, share_bps: u16) -> Instruction {
+    build(
+        program_id,
+        RPSInstruction::ConfigureInsuranceFund { share_bps },
+        vec![
+            AccountMeta::new(*authority, true),
+            AccountMeta::new_readonly(config_address(program_id).0, false),
+            AccountMeta::new(insurance_fund_address(program_id).0, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+/// `index` must be the insurance fund's current `claim_count`.
+pub fn file_insurance_claim(
+    program_id: &Pubkey,
+    claimant: &Pubkey,
+    index: u64,
+    requested: u64,
+    evidence: [u8; 32],
+) -> Instruction {
+    build(
+        program_id,
+        RPSInstruction::FileInsuranceClaim { requested, evidence },
+        vec![
+            AccountMeta::new(*claimant, true),
+            AccountMeta::new(insurance_fund_address(program_id).0, false),
+            AccountMeta::new(insurance_claim_address(program_id, index).0, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+/// Pays `amount` on claim `index`, or rejects it with 0.
+pub fn resolve_insurance_claim(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    index: u64,
+    claimant: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    build(
+        program_id,
+        RPSInstruction::ResolveInsuranceClaim { amount },
+        vec![
+            AccountMeta::new_readonly(*authority, true),
+            AccountMeta::new_readonly(config_address(program_id).0, false),
+            AccountMeta::new(insurance_fund_address(program_id).0, false),
+            AccountMeta::new(insurance_claim_address(program_id, index).0, false),
+            AccountMeta::new(*claimant, false),
+        ],
+    )
+}
+
+/// Returns `player`'s entry from a game cancelled at a timeout; a bot
+/// seat's entry is returned to the wallet that paid for it, passed as
+/// `player`.
+pub fn refund_entry(program_id: &Pubkey, caller: &Pubkey, game: &Pubkey, player: &Pubkey) -> Instruction {
+    build(
+        program_id,
+        RPSInstruction::RefundEntry,
+        vec![
+            AccountMeta::new_readonly(*caller, true),
+            AccountMeta::new(*game, false),
+            AccountMeta::new(*player, false),
+            pot_vault_meta(program_id, game),
+            AccountMeta::new(treasury_address(program_id).0, false),
+        ],
+    )
+}
+
+/// [`refund_entry`] for an RPSToken game, paid from the game vault into
+/// `player_tokens`.
+pub fn refund_entry_with_tokens(
+    program_id: &Pubkey,
+    caller: &Pubkey,
+    game: &Pubkey,
+    player: &Pubkey,
+    mint: &Pubkey,
+    player_tokens: &Pubkey,
+) -> Instruction {
+    let mut ix = refund_entry(program_id, caller, game, player);
+    ix.accounts.extend(token_payout_metas(program_id, game, mint, player_tokens));
+    ix
+}
+
+/// Mints devnet test tokens of the demo mint (`demo_mint_address`) into
+/// `player`'s associated token account for it (`demo_token_address`),
+/// which the claim creates when missing; `player` needs a profile and may
+/// claim once a day. Only programs built with the `devnet` feature accept
+/// it.
+pub fn claim_demo_tokens(program_id: &Pubkey, player: &Pubkey) -> Instruction {
+    build(
+        program_id,
+        RPSInstruction::ClaimDemoTokens,
+        vec![
+            AccountMeta::new(*player, true),
+            AccountMeta::new_readonly(profile_address(program_id, player).0, false),
+            AccountMeta::new(demo_claim_address(program_id, player).0, false),
+            AccountMeta::new(demo_mint_address(program_id).0, false),
+            AccountMeta::new(demo_token_address(program_id, player), false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(ASSOCIATED_TOKEN_PROGRAM_ID, false),
+        ],
+    )
+}
+
+/// Creates the token vault of `game` for `mint`, ahead of
+/// [`initialize_game_with_tokens`]; `game` may still be a fresh keypair.
+/// Harmless when the vault already exists.
+pub fn create_game_token_vault(program_id: &Pubkey, payer: &Pubkey, game: &Pubkey, mint: &Pubkey) -> Instruction {
+    build(
+        program_id,
+        RPSInstruction::CreateGameTokenVault,
+        vec![
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(*game, false),
+            AccountMeta::new_readonly(game_authority_address(program_id, game).0, false),
+            AccountMeta::new(game_vault_address(program_id, game, mint), false),
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(ASSOCIATED_TOKEN_PROGRAM_ID, false),
+        ],
+    )
+}
+
+/// Creates a bot-only tournament; `tournament` is a new keypair that signs.
+pub fn create_auto_tournament(
+    program_id: &Pubkey,
+    host: &Pubkey,
+    tournament: &Pubkey,
+    bots: u8,
+    rounds_per_match: u8,
+) -> Instruction {
+    build(
+        program_id,
+        RPSInstruction::CreateAutoTournament { bots, rounds_per_match },
+        vec![
+            AccountMeta::new(*host, true),
+            AccountMeta::new(*tournament, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+pub fn crank_auto_tournament(program_id: &Pubkey, caller: &Pubkey, tournament: &Pubkey) -> Instruction {
+    build(
+        program_id,
+        RPSInstruction::CrankAutoTournament,
+        vec![AccountMeta::new(*caller, true), AccountMeta::new(*tournament, false)],
+    )
+}
+
+/// Registers a clan at `clan_address(name)`; `split_bps` is each member's
+/// prize share in basis points, in roster order, summing to 10_000.
+pub fn create_clan(
+    program_id: &Pubkey,
+    leader: &Pubkey,
+    name: &str,
+    members: Vec<Pubkey>,
+    split_bps: Vec<u16>,
+) -> Instruction {
+    build(
+        program_id,
+        RPSInstruction::CreateClan {
+            name: name.to_string(),
+            members,
+            split_bps,
+        },
+        vec![
+            AccountMeta::new(*leader, true),
+            AccountMeta::new(clan_address(program_id, name).0, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+/// Enters `clan` into a team tournament; `leader` pays the entry fee.
+pub fn join_tournament_as_clan(program_id: &Pubkey, leader: &Pubkey, tournament: &Pubkey, clan: &Pubkey) -> Instruction {
+    build(
+        program_id,
+        RPSInstruction::JoinTournamentAsClan,
+        vec![
+            AccountMeta::new(*leader, true),
+            AccountMeta::new(*tournament, false),
+            AccountMeta::new_readonly(*clan, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+/// Spawns the team game for bracket match `match_index`; `game` must be a
+/// fresh keypair that signs. `clan_a` and `clan_b` are the match's sides in
+/// bracket order.
+#[allow(clippy::too_many_arguments)]
+pub fn open_team_match(
+    program_id: &Pubkey,
+    caller: &Pubkey,
+    tournament: &Pubkey,
+    game: &Pubkey,
+    clan_a: &Pubkey,
+    clan_b: &Pubkey,
+    match_index: u8,
+    total_rounds: u8,
+    timeout_seconds: u64,
+) -> Instruction {
+    build(
+        program_id,
+        RPSInstruction::OpenTeamMatch {
+            match_index,
+            total_rounds,
+            timeout_seconds,
+        },
+        vec![
+            AccountMeta::new(*caller, true),
+            AccountMeta::new(*tournament, false),
+            AccountMeta::new(*game, true),
+            AccountMeta::new_readonly(*clan_a, false),
+            AccountMeta::new_readonly(*clan_b, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+/// Spawns the two-seat game for solo bracket match `match_index`, reserved
+/// for the match's players; `game` must be a fresh keypair that signs.
+pub fn spawn_tournament_match(
+    program_id: &Pubkey,
+    caller: &Pubkey,
+    tournament: &Pubkey,
+    match_index: u8,
+    game: &Pubkey,
+    total_rounds: u8,
+    timeout_seconds: u64,
+) -> Instruction {
+    build(
+        program_id,
+        RPSInstruction::SpawnTournamentMatch {
+            match_index,
+            total_rounds,
+            timeout_seconds,
+        },
+        vec![
+            AccountMeta::new(*caller, true),
+            AccountMeta::new(*tournament, false),
+            AccountMeta::new(*game, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+/// Pays a placed clan's prize to `members`, which must be the clan's full
+/// roster in order.
+pub fn claim_team_prize(
+    program_id: &Pubkey,
+    caller: &Pubkey,
+    tournament: &Pubkey,
+    clan: &Pubkey,
+    members: &[Pubkey],
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(*caller, true),
+        AccountMeta::new(*tournament, false),
+        AccountMeta::new_readonly(*clan, false),
+    ];
+    accounts.extend(members.iter().map(|member| AccountMeta::new(*member, false)));
+    build(program_id, RPSInstruction::ClaimTeamPrize, accounts)
+}
+
+/// Creates the program-wide `GlobalStats` PDA, paid for by `payer`.
+pub fn initialize_stats(program_id: &Pubkey, payer: &Pubkey) -> Instruction {
+    build(
+        program_id,
+        RPSInstruction::InitializeStats,
+        vec![
+            AccountMeta::new(*payer, true),
+            AccountMeta::new(stats_address(program_id).0, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+/// Appends the stats PDA to a commit or reveal so its timing is folded into
+/// `GlobalStats`. Every such transaction then write-locks the one stats
+/// account, so this is for the clients that report, not every player.
+pub fn with_stats(program_id: &Pubkey, mut instruction: Instruction) -> Instruction {
+    instruction
+        .accounts
+        .push(AccountMeta::new(stats_address(program_id).0, false));
+    instruction
+}
+
+/// Appends the SlotHashes sysvar to a join or `AddBotPlayers`, where a range
+/// lobby draws its seat count from it on the seat that reaches
+/// `min_players`, or to a commit or `resolve_timeout`, where it seeds the
+/// reveal order when the commit phase closes. Apply it last, after any
+/// credential, token or stats accounts.
+pub fn with_slot_hashes(mut instruction: Instruction) -> Instruction {
+    instruction
+        .accounts
+        .push(AccountMeta::new_readonly(sysvar::slot_hashes::id(), false));
+    instruction
+}
@@ -0,0 +1,20 @@
+//! Rust client SDK for the Solana RPS program.
+//!
+//! Builds on the program crate's own instruction and state types so the
+//! client can never disagree with the on-chain layout, and adds the
+//! transaction plumbing integrations need around them.
+
+pub mod commitment;
+pub mod compute_budget;
+pub mod error;
+pub mod instruction;
+pub mod lookup_tables;
+pub mod nonce;
+pub mod preflight;
+pub mod send;
+pub mod subscribe;
+pub mod view;
+
+pub use commitment::Commitment;
+pub use error::{Result, RpsClientError};
+pub use solana_rps as program;
@@ -0,0 +1,149 @@
+//! Address-lookup-table management and v0 transaction builders.
+//!
+//! Token-mode claims, multi-mint settlements and tournament instructions
+//! carry more accounts than fit in a legacy transaction. Games keep a lookup
+//! table of their static accounts (programs, vaults, mints) so those
+//! instructions compile into a v0 message with one-byte account indexes.
+
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{
+    address_lookup_table::{
+        instruction as alt_instruction, state::AddressLookupTable, AddressLookupTableAccount,
+    },
+    commitment_config::CommitmentConfig,
+    hash::Hash,
+    instruction::Instruction,
+    message::{v0, VersionedMessage},
+    pubkey::Pubkey,
+    signer::Signer,
+    system_program,
+    transaction::VersionedTransaction,
+};
+use solana_rps::{CurrencyMode, Game};
+
+use crate::error::{Result, RpsClientError};
+
+/// Maximum addresses a single extend instruction can carry and still fit
+/// in a legacy transaction alongside its signers.
+pub const MAX_ADDRESSES_PER_EXTEND: usize = 30;
+
+/// Builds the instruction creating a new lookup table owned by `authority`.
+///
+/// Returns the instruction together with the table address it will create.
+pub async fn create_lookup_table(
+    rpc: &RpcClient,
+    authority: &Pubkey,
+    payer: &Pubkey,
+) -> Result<(Instruction, Pubkey)> {
+    // Tables are derived from a recent slot; use a finalized one so the
+    // instruction doesn't fail when the RPC node is slightly ahead
+    let recent_slot = rpc
+        .get_slot_with_commitment(CommitmentConfig::finalized())
+        .await?;
+    Ok(alt_instruction::create_lookup_table(*authority, *payer, recent_slot))
+}
+
+/// Builds the instructions appending `addresses` to `table`, split into
+/// chunks small enough to land one per transaction.
+pub fn extend_lookup_table(
+    table: &Pubkey,
+    authority: &Pubkey,
+    payer: &Pubkey,
+    addresses: &[Pubkey],
+) -> Vec<Instruction> {
+    addresses
+        .chunks(MAX_ADDRESSES_PER_EXTEND)
+        .map(|chunk| {
+            alt_instruction::extend_lookup_table(*table, *authority, Some(*payer), chunk.to_vec())
+        })
+        .collect()
+}
+
+/// Builds the instructions retiring a table: deactivate now, close once the
+/// deactivation cooldown (~513 slots) has elapsed.
+pub fn deactivate_lookup_table(table: &Pubkey, authority: &Pubkey) -> Instruction {
+    alt_instruction::deactivate_lookup_table(*table, *authority)
+}
+
+pub fn close_lookup_table(table: &Pubkey, authority: &Pubkey, recipient: &Pubkey) -> Instruction {
+    alt_instruction::close_lookup_table(*table, *authority, *recipient)
+}
+
+/// Fetches and decodes a lookup table so it can be used to compile messages.
+pub async fn fetch_lookup_table(rpc: &RpcClient, table: &Pubkey) -> Result<AddressLookupTableAccount> {
+    let account = rpc.get_account(table).await?;
+    let state = AddressLookupTable::deserialize(&account.data)
+        .map_err(|_| RpsClientError::InvalidAccountData(table.to_string()))?;
+    Ok(AddressLookupTableAccount {
+        key: *table,
+        addresses: state.addresses.to_vec(),
+    })
+}
+
+/// Returns the addresses of `wanted` that are not already in `table`, so
+/// callers can extend a shared table idempotently.
+pub fn missing_addresses(table: &AddressLookupTableAccount, wanted: &[Pubkey]) -> Vec<Pubkey> {
+    let mut missing = Vec::new();
+    for address in wanted {
+        if !table.addresses.contains(address) && !missing.contains(address) {
+            missing.push(*address);
+        }
+    }
+    missing
+}
+
+/// Collects the static accounts worth caching for a game: programs, the
+/// game account itself, its token mint and any stake vaults and price feeds.
+///
+/// Player wallets are left out on purpose; they sign and therefore must be
+/// in the static account list anyway.
+pub fn game_lookup_addresses(program_id: &Pubkey, game_key: &Pubkey, game: &Game) -> Vec<Pubkey> {
+    let mut addresses = vec![*program_id, *game_key, system_program::id()];
+
+    let needs_token_program =
+        matches!(game.currency_mode, CurrencyMode::RPSToken) || !game.stake_vaults.is_empty();
+    if needs_token_program {
+        addresses.push(spl_token::id());
+    }
+    if let Some(mint) = game.token_mint {
+        addresses.push(mint);
+    }
+    if let Some(feed) = game.base_price_feed {
+        addresses.push(feed);
+    }
+    for vault in &game.stake_vaults {
+        addresses.extend([vault.mint, vault.vault, vault.price_feed]);
+    }
+
+    let mut unique = Vec::with_capacity(addresses.len());
+    for address in addresses {
+        if !unique.contains(&address) {
+            unique.push(address);
+        }
+    }
+    unique
+}
+
+/// Compiles and signs a v0 transaction resolving accounts through `tables`.
+pub fn build_v0_transaction(
+    payer: &Pubkey,
+    ixs: &[Instruction],
+    tables: &[AddressLookupTableAccount],
+    recent_blockhash: Hash,
+    signers: &[&dyn Signer],
+) -> Result<VersionedTransaction> {
+    let message = v0::Message::try_compile(payer, ixs, tables, recent_blockhash)?;
+    Ok(VersionedTransaction::try_new(VersionedMessage::V0(message), signers)?)
+}
+
+/// Same as [`build_v0_transaction`] but fetches the latest blockhash.
+pub async fn build_v0_transaction_latest(
+    rpc: &RpcClient,
+    payer: &Pubkey,
+    ixs: &[Instruction],
+    tables: &[AddressLookupTableAccount],
+    signers: &[&dyn Signer],
+) -> Result<VersionedTransaction> {
+    let blockhash = rpc.get_latest_blockhash().await?;
+    build_v0_transaction(payer, ixs, tables, blockhash, signers)
+}
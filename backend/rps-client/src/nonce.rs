@@ -0,0 +1,120 @@
+//! Durable-nonce transactions for commits and reveals signed ahead of time.
+//!
+//! A transaction built on a recent blockhash expires after ~150 slots, which
+//! is too short for players on flaky connections who want to sign their
+//! commit and reveal up front. Using a nonce account as the blockhash source
+//! keeps a signed transaction valid until the nonce is advanced, which
+//! happens exactly once: when that transaction (or another using the same
+//! nonce) lands. A commit and its reveal therefore need one nonce account
+//! each, otherwise landing the commit invalidates the pre-signed reveal.
+
+use solana_client::{
+    nonblocking::rpc_client::RpcClient,
+    nonce_utils::nonblocking::{data_from_account, get_account_with_commitment},
+};
+use solana_sdk::{
+    commitment_config::CommitmentConfig,
+    hash::Hash,
+    instruction::Instruction,
+    message::Message,
+    nonce::State as NonceState,
+    pubkey::Pubkey,
+    signer::Signer,
+    system_instruction,
+    transaction::Transaction,
+};
+
+use crate::error::{Result, RpsClientError};
+
+/// Builds the instructions creating and initializing a nonce account.
+///
+/// `nonce_account` must sign the resulting transaction, and `lamports`
+/// must cover rent exemption for [`NonceState::size`] bytes.
+pub fn create_nonce_account(
+    payer: &Pubkey,
+    nonce_account: &Pubkey,
+    authority: &Pubkey,
+    lamports: u64,
+) -> Vec<Instruction> {
+    system_instruction::create_nonce_account(payer, nonce_account, authority, lamports)
+}
+
+/// Rent-exempt balance needed for a nonce account.
+pub async fn nonce_account_rent(rpc: &RpcClient) -> Result<u64> {
+    Ok(rpc
+        .get_minimum_balance_for_rent_exemption(NonceState::size())
+        .await?)
+}
+
+/// Reads the current durable nonce stored in `nonce_account`.
+pub async fn fetch_nonce(rpc: &RpcClient, nonce_account: &Pubkey) -> Result<Hash> {
+    let account =
+        get_account_with_commitment(rpc, nonce_account, CommitmentConfig::confirmed())
+            .await
+            .map_err(|_| RpsClientError::AccountNotFound(nonce_account.to_string()))?;
+    let data = data_from_account(&account)
+        .map_err(|_| RpsClientError::InvalidAccountData(nonce_account.to_string()))?;
+    Ok(data.blockhash())
+}
+
+/// Builds and signs a transaction that uses `nonce` instead of a recent
+/// blockhash. The advance-nonce instruction is placed first, as the
+/// runtime requires.
+pub fn build_nonce_transaction(
+    payer: &Pubkey,
+    nonce_account: &Pubkey,
+    nonce_authority: &Pubkey,
+    ixs: &[Instruction],
+    nonce: Hash,
+    signers: &[&dyn Signer],
+) -> Result<Transaction> {
+    let message = Message::new_with_nonce(ixs.to_vec(), Some(payer), nonce_account, nonce_authority);
+    let mut tx = Transaction::new_unsigned(message);
+    tx.try_sign(signers, nonce)?;
+    Ok(tx)
+}
+
+/// Fetches the current nonce and builds a signed transaction on it.
+pub async fn build_nonce_transaction_latest(
+    rpc: &RpcClient,
+    payer: &Pubkey,
+    nonce_account: &Pubkey,
+    nonce_authority: &Pubkey,
+    ixs: &[Instruction],
+    signers: &[&dyn Signer],
+) -> Result<Transaction> {
+    let nonce = fetch_nonce(rpc, nonce_account).await?;
+    build_nonce_transaction(payer, nonce_account, nonce_authority, ixs, nonce, signers)
+}
+
+/// A transaction signed offline, ready to be broadcast whenever the player
+/// comes back online.
+#[derive(Debug, Clone)]
+pub struct PresignedTransaction {
+    pub nonce_account: Pubkey,
+    pub transaction: Transaction,
+}
+
+impl PresignedTransaction {
+    /// Serializes for local storage.
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        bincode::serialize(&(self.nonce_account, &self.transaction))
+            .map_err(|_| RpsClientError::InvalidAccountData("presigned transaction".to_string()))
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let (nonce_account, transaction) = bincode::deserialize(bytes)
+            .map_err(|_| RpsClientError::InvalidAccountData("presigned transaction".to_string()))?;
+        Ok(Self {
+            nonce_account,
+            transaction,
+        })
+    }
+
+    /// Whether the nonce this transaction was signed against is still the
+    /// current one, i.e. the transaction can still land.
+    pub async fn is_still_valid(&self, rpc: &RpcClient) -> Result<bool> {
+        let current = fetch_nonce(rpc, &self.nonce_account).await?;
+        Ok(current == self.transaction.message.recent_blockhash)
+    }
+}
@@ -0,0 +1,172 @@
+//! Pre-flight simulation with typed program errors.
+//!
+//! Simulating before sending catches most failures without paying fees,
+//! but the raw result is a `Custom(6)` buried in an `InstructionError`.
+//! [`preflight`] maps those codes back to [`RPSError`] and pairs them with
+//! the program's last log line and a hint the user can act on.
+//!
+//! The program also logs one structured `rps-error` line per failure,
+//! parsed into [`ErrorContext`] for support tooling and monitoring.
+
+use base64::Engine;
+use num_traits::FromPrimitive;
+use solana_client::{nonblocking::rpc_client::RpcClient, rpc_config::RpcSimulateTransactionConfig};
+use solana_sdk::{
+    commitment_config::CommitmentConfig,
+    instruction::InstructionError,
+    transaction::{TransactionError, VersionedTransaction},
+};
+use solana_rps::RPSError;
+
+use crate::error::{Result, RpsClientError};
+
+/// Outcome of a successful simulation.
+#[derive(Debug, Clone)]
+pub struct SimulationReport {
+    pub units_consumed: Option<u64>,
+    pub logs: Vec<String>,
+    /// Return data set by the last instruction, for view instructions.
+    pub return_data: Option<Vec<u8>>,
+}
+
+/// Context from the program's `rps-error` log line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ErrorContext {
+    /// Custom error code, or the name of a builtin `ProgramError`.
+    pub code: String,
+    /// Discriminant of the failing `RPSInstruction`.
+    pub instruction: Option<u8>,
+    /// Game state when the instruction failed, e.g. `RevealPhase`.
+    pub state: Option<String>,
+    /// Index of the offending account within the instruction.
+    pub account: Option<usize>,
+}
+
+const ERROR_CONTEXT_PREFIX: &str = "rps-error ";
+
+/// Finds and parses the program's `rps-error` line in `logs`.
+pub fn error_context(logs: &[String]) -> Option<ErrorContext> {
+    let line = logs
+        .iter()
+        .rev()
+        .filter_map(|line| line.strip_prefix("Program log: "))
+        .find_map(|message| message.strip_prefix(ERROR_CONTEXT_PREFIX))?;
+
+    let mut context = ErrorContext {
+        code: String::new(),
+        instruction: None,
+        state: None,
+        account: None,
+    };
+    for field in line.split_whitespace() {
+        let Some((key, value)) = field.split_once('=') else { continue };
+        if value == "-" {
+            continue;
+        }
+        match key {
+            "code" => context.code = value.to_string(),
+            "ix" => context.instruction = value.parse().ok(),
+            "state" => context.state = Some(value.to_string()),
+            "account" => context.account = value.parse().ok(),
+            _ => {}
+        }
+    }
+    Some(context)
+}
+
+/// Simulates `tx` and returns a typed error if the program would reject it.
+///
+/// The blockhash is replaced during simulation so a transaction built ahead
+/// of time is still checked against current state.
+pub async fn preflight(rpc: &RpcClient, tx: &VersionedTransaction) -> Result<SimulationReport> {
+    let config = RpcSimulateTransactionConfig {
+        sig_verify: false,
+        replace_recent_blockhash: true,
+        commitment: Some(CommitmentConfig::confirmed()),
+        ..RpcSimulateTransactionConfig::default()
+    };
+    let result = rpc.simulate_transaction_with_config(tx, config).await?.value;
+    let logs = result.logs.unwrap_or_default();
+
+    match result.err {
+        None => Ok(SimulationReport {
+            units_consumed: result.units_consumed,
+            logs,
+            return_data: result
+                .return_data
+                .and_then(|data| base64::engine::general_purpose::STANDARD.decode(data.data.0).ok()),
+        }),
+        Some(err) => Err(map_transaction_error(err, logs)),
+    }
+}
+
+/// Converts a transaction error into the most specific client error.
+pub fn map_transaction_error(err: TransactionError, logs: Vec<String>) -> RpsClientError {
+    if let TransactionError::InstructionError(index, InstructionError::Custom(code)) = &err {
+        if let Some(error) = RPSError::from_u32(*code) {
+            let detail = last_program_log(&logs);
+            return RpsClientError::Program {
+                instruction_index: *index,
+                error,
+                message: actionable_message(error, detail.as_deref()),
+                context: error_context(&logs).map(Box::new),
+                logs,
+            };
+        }
+    }
+    RpsClientError::Simulation { err, logs }
+}
+
+/// Human-readable explanation of `error`, using the program's own log line
+/// for the specifics when there is one.
+pub fn actionable_message(error: RPSError, detail: Option<&str>) -> String {
+    let hint = match error {
+        RPSError::InvalidGameState => "The game is not in the right phase for this action; refresh the game and try again.",
+        RPSError::InvalidPlayerState => "Your seat in this game does not allow this action.",
+        RPSError::PlayerNotFound => "This wallet is not a player in the game.",
+        RPSError::GameFull => "The lobby is already full; pick another game.",
+        RPSError::PlayerAlreadyJoined => "This wallet has already joined the game.",
+        RPSError::InvalidChoice => "Choose rock, paper or scissors; lizard and Spock only count in lizard-Spock games.",
+        RPSError::InvalidHash => "The revealed choice or salt does not match your commitment; reveal with the exact values you committed.",
+        RPSError::TimeoutNotReached => "The timeout has not elapsed yet; wait before resolving.",
+        RPSError::NotWinner => "Only winners of a finished game can claim.",
+        RPSError::InvalidParameter => "One of the instruction parameters is out of range.",
+        RPSError::InsufficientFunds => "There is nothing left to pay out, or the wallet cannot cover the entry.",
+        RPSError::NotAuthorized => "This wallet is not allowed to perform the action.",
+        RPSError::FeeCalculationError => "The fee or price conversion overflowed; try a smaller amount.",
+        RPSError::TokenTransferError => "The token transfer failed; check token accounts and balances.",
+        RPSError::SlippageExceeded => "The swap moved more than your slippage limit; requote and retry.",
+        RPSError::InvalidTokenAccount => "A token account has the wrong mint or owner.",
+        RPSError::MintNotApproved => "That mint is not accepted for this game.",
+        RPSError::StalePriceFeed => "The price feed is stale; retry in a few seconds.",
+        RPSError::StalePhase => "The game moved on since you loaded it; refresh the game and try again.",
+        RPSError::AlreadyClaimed => "Your winnings from this game were already paid out.",
+        RPSError::NotQualified => "This tournament is only open to players who placed in its qualifier.",
+        RPSError::PlayerBanned => "This wallet is banned from rated play.",
+        RPSError::MissingCredential => "Paid entries need a valid compliance credential in this wallet.",
+        RPSError::AlreadyCommitted => "You already committed this round; a commitment can only be replaced before anyone else commits.",
+        RPSError::InvalidRevealSignature => "A signed reveal must directly follow an ed25519 verify of your wallet's signature over this round's reveal message.",
+        RPSError::PayoutHeld => "This game was flagged for collusion review and the operator holds flagged payouts; claim again once the flag is cleared.",
+        RPSError::ClaimNotReady => "This pot is above the large payout threshold: send InitiateClaim, wait out the delay, then FinalizeClaim.",
+        RPSError::InstructionPaused => "The operator has paused this instruction during an incident; try again once it is lifted.",
+        RPSError::UnexpectedInstruction => "The operator requires payouts to be sent on their own; resend the claim without other programs' instructions.",
+        RPSError::SelfLimitReached => "This wallet's own wager limit or cool-off blocks the entry; wait for it to pass. Raised limits apply after a delay.",
+        RPSError::SelfExcluded => "This wallet excluded itself from play; entries reopen when the exclusion ends, and it cannot be shortened.",
+        RPSError::AccountNotWritable => "An account the instruction writes to was passed read-only; build it with the SDK's instruction builder.",
+    };
+    match detail {
+        Some(detail) => format!("{} ({})", hint, detail),
+        None => hint.to_string(),
+    }
+}
+
+/// The last `Program log:` message before the failure, which is where the
+/// program explains which check tripped. The structured `rps-error` line
+/// that follows it is skipped.
+fn last_program_log(logs: &[String]) -> Option<String> {
+    logs.iter()
+        .rev()
+        .filter_map(|line| line.strip_prefix("Program log: "))
+        .find(|message| !message.starts_with(ERROR_CONTEXT_PREFIX))
+        .map(str::to_string)
+}
@@ -0,0 +1,224 @@
+//! Retrying send-and-confirm pipeline.
+//!
+//! RPC nodes drop transactions, time out on confirmation and occasionally
+//! report stale state. Naively re-sending a `JoinGame` or `ClaimWinnings`
+//! after such a failure risks doing it twice. This pipeline
+//!
+//! - rebuilds the transaction on a fresh blockhash only once the previous
+//!   one has provably expired,
+//! - polls the status of every signature it has sent, so a late landing of
+//!   an earlier attempt is still recognised, and
+//! - before every attempt asks an [`ActionGuard`] whether the action's effect
+//!   is already visible on-chain, in which case nothing is re-sent.
+
+use std::time::Duration;
+
+use solana_client::{nonblocking::rpc_client::RpcClient, rpc_config::RpcSendTransactionConfig};
+use solana_sdk::{
+    commitment_config::CommitmentConfig, hash::Hash, pubkey::Pubkey, signature::Signature,
+    transaction::VersionedTransaction,
+};
+use solana_rps::{game_result_address, lobby_name_address, Game, GameResult, GameState, LobbyName};
+
+use crate::error::{Result, RpsClientError};
+
+#[derive(Debug, Clone)]
+pub struct SendConfig {
+    /// How many distinct blockhashes to try before giving up.
+    pub max_attempts: u32,
+    /// Delay between signature status polls.
+    pub poll_interval: Duration,
+    /// Commitment at which a landed transaction counts as confirmed.
+    pub commitment: CommitmentConfig,
+}
+
+impl Default for SendConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            poll_interval: Duration::from_millis(500),
+            commitment: CommitmentConfig::confirmed(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SendOutcome {
+    /// One of the sent transactions landed with this signature.
+    Confirmed(Signature),
+    /// The guard found the action already applied; nothing (more) was sent.
+    AlreadyApplied,
+}
+
+/// On-chain check that tells whether an action already took effect, used to
+/// make re-sends idempotent.
+#[derive(Debug, Clone, Copy)]
+pub enum ActionGuard {
+    /// The player holds a seat in the game.
+    Join { game: Pubkey, player: Pubkey },
+    /// The player's commitment for the current round is recorded.
+    Commit { game: Pubkey, player: Pubkey },
+    /// The player's choice for the current round is revealed.
+    Reveal { game: Pubkey, player: Pubkey },
+    /// The player has nothing left to claim, or to be refunded, from a
+    /// finished game.
+    Claim { game: Pubkey, player: Pubkey },
+    /// The phase the timeout targeted is over, whoever ended it.
+    ResolveTimeout { game: Pubkey, phase_nonce: u64 },
+}
+
+impl ActionGuard {
+    pub async fn already_applied(&self, rpc: &RpcClient) -> Result<bool> {
+        let (game_key, player) = match *self {
+            ActionGuard::Join { game, player }
+            | ActionGuard::Commit { game, player }
+            | ActionGuard::Reveal { game, player }
+            | ActionGuard::Claim { game, player } => (game, player),
+            ActionGuard::ResolveTimeout { game, .. } => (game, Pubkey::default()),
+        };
+        let game = fetch_game(rpc, &game_key).await?;
+        let seat = game.players.iter().find(|p| p.pubkey == player);
+
+        Ok(match self {
+            ActionGuard::Join { .. } => seat.is_some(),
+            ActionGuard::Commit { .. } => seat.is_some_and(|p| p.committed_choice != [0; 64]),
+            ActionGuard::Reveal { .. } => seat.is_some_and(|p| p.revealed),
+            ActionGuard::Claim { .. } => {
+                let index = game.players.iter().position(|p| p.pubkey == player);
+                // A cancelled game owes every seat its entry instead
+                let owed = if game.cancelled { u8::MAX } else { game.winner_bitmap() };
+                matches!(game.state, GameState::Finished)
+                    && index.is_none_or(|i| owed & (1 << i) == 0 || game.claimed_bitmap & (1 << i) != 0)
+            }
+            ActionGuard::ResolveTimeout { phase_nonce, .. } => game.phase_nonce != *phase_nonce,
+        })
+    }
+}
+
+/// Sends the transaction produced by `build` until it confirms, the guard
+/// reports the action as applied, or `config.max_attempts` blockhashes have
+/// expired.
+///
+/// `build` is called with a fresh blockhash for every attempt and must
+/// return a fully signed transaction.
+pub async fn send_and_confirm<F>(
+    rpc: &RpcClient,
+    config: &SendConfig,
+    guard: Option<ActionGuard>,
+    mut build: F,
+) -> Result<SendOutcome>
+where
+    F: FnMut(Hash) -> Result<VersionedTransaction>,
+{
+    let mut sent: Vec<Signature> = Vec::new();
+
+    for attempt in 0..config.max_attempts {
+        // Any earlier attempt may have landed after its blockhash check
+        if let Some(signature) = find_landed(rpc, &sent, config).await? {
+            return Ok(SendOutcome::Confirmed(signature));
+        }
+        if let Some(guard) = &guard {
+            if guard.already_applied(rpc).await? {
+                return Ok(SendOutcome::AlreadyApplied);
+            }
+        }
+
+        let blockhash = rpc.get_latest_blockhash().await?;
+        let tx = build(blockhash)?;
+        let signature = tx.signatures[0];
+
+        let send_config = RpcSendTransactionConfig {
+            // Preflight already ran on the first attempt; re-sends may race
+            // a landing we haven't observed yet and would fail simulation
+            skip_preflight: attempt > 0,
+            max_retries: Some(0),
+            ..RpcSendTransactionConfig::default()
+        };
+        if let Err(err) = rpc.send_transaction_with_config(&tx, send_config).await {
+            if let Some(tx_err) = err.get_transaction_error() {
+                return Err(RpsClientError::TransactionFailed(tx_err));
+            }
+            // Transport hiccup: fall through and keep polling what we have
+        } else if !sent.contains(&signature) {
+            sent.push(signature);
+        }
+
+        // Poll until something lands or this blockhash can no longer land
+        loop {
+            if let Some(signature) = find_landed(rpc, &sent, config).await? {
+                return Ok(SendOutcome::Confirmed(signature));
+            }
+            if !rpc
+                .is_blockhash_valid(&blockhash, CommitmentConfig::processed())
+                .await?
+            {
+                break;
+            }
+            tokio::time::sleep(config.poll_interval).await;
+        }
+    }
+
+    Err(RpsClientError::RetriesExhausted(config.max_attempts))
+}
+
+/// Returns the first of `signatures` that reached the configured commitment,
+/// or the error of one that landed but failed.
+async fn find_landed(
+    rpc: &RpcClient,
+    signatures: &[Signature],
+    config: &SendConfig,
+) -> Result<Option<Signature>> {
+    if signatures.is_empty() {
+        return Ok(None);
+    }
+    let statuses = rpc.get_signature_statuses(signatures).await?.value;
+    for (signature, status) in signatures.iter().zip(statuses) {
+        let Some(status) = status else { continue };
+        if let Some(err) = status.err {
+            return Err(RpsClientError::TransactionFailed(err));
+        }
+        if status.satisfies_commitment(config.commitment) {
+            return Ok(Some(*signature));
+        }
+    }
+    Ok(None)
+}
+
+/// Fetches and decodes a game account.
+pub async fn fetch_game(rpc: &RpcClient, game: &Pubkey) -> Result<Game> {
+    let data = rpc
+        .get_account_data(game)
+        .await
+        .map_err(|_| RpsClientError::AccountNotFound(game.to_string()))?;
+    decode_game(game, &data)
+}
+
+/// Fetches the `GameResult` snapshot recorded for `game`, which is still
+/// available after the game account itself is closed.
+pub async fn fetch_game_result(rpc: &RpcClient, program_id: &Pubkey, game: &Pubkey) -> Result<GameResult> {
+    let address = game_result_address(program_id, game).0;
+    let data = rpc
+        .get_account_data(&address)
+        .await
+        .map_err(|_| RpsClientError::AccountNotFound(address.to_string()))?;
+    GameResult::unpack_from(&data).map_err(|_| RpsClientError::InvalidAccountData(address.to_string()))
+}
+
+/// Looks up the lobby a registered name currently points at: a game, or a
+/// recurring lobby whose `last_game` is the one to join.
+pub async fn resolve_lobby_name(rpc: &RpcClient, program_id: &Pubkey, name: &str) -> Result<Pubkey> {
+    let address = lobby_name_address(program_id, name).0;
+    let data = rpc
+        .get_account_data(&address)
+        .await
+        .map_err(|_| RpsClientError::AccountNotFound(name.to_string()))?;
+    let entry = LobbyName::unpack_from(&data).map_err(|_| RpsClientError::InvalidAccountData(address.to_string()))?;
+    Ok(entry.target)
+}
+
+/// Decodes game account data, tolerating the zero padding after the
+/// serialized state and accounts of older layouts (see
+/// `Game::is_legacy`).
+pub fn decode_game(game: &Pubkey, data: &[u8]) -> Result<Game> {
+    Game::unpack_from(data).map_err(|_| RpsClientError::InvalidAccountData(game.to_string()))
+}
@@ -0,0 +1,301 @@
+//! Push-based game updates over the RPC websocket.
+//!
+//! [`subscribe_game`] combines an `accountSubscribe` on the game account
+//! (decoded into [`Game`] snapshots) with a `logsSubscribe` on transactions
+//! mentioning it (parsed into [`GameEvent`]s), so bots and UIs can react to
+//! phase changes without polling.
+//!
+//! Production builds of the program report progress as Borsh-encoded
+//! [`RPSEvent`]s in `Program data:` lines; builds with the `debug-logs`
+//! feature also emit the verbose `Program log:` messages. Both are parsed.
+
+use base64::Engine;
+use borsh::BorshDeserialize;
+use futures::StreamExt;
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::{
+    nonblocking::pubsub_client::PubsubClient,
+    rpc_config::{RpcAccountInfoConfig, RpcTransactionLogsConfig, RpcTransactionLogsFilter},
+};
+use solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey};
+use solana_rps::{Game, GameState, RPSEvent};
+use tokio::{sync::mpsc, task::JoinHandle};
+
+use crate::{error::Result, send::decode_game};
+
+/// Program activity recognised in transaction logs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GameEvent {
+    /// `theme_id` is the client skin the host picked; `None` when only the
+    /// plain-text log line was seen.
+    GameInitialized { theme_id: Option<u16> },
+    PlayerJoined { player: String },
+    ChoiceCommitted { player: String },
+    ChoiceRevealed { player: String },
+    RevealPhaseStarted,
+    RoundResolved { round: u8, scores: Vec<u8> },
+    RoundStarted { round: u8, total_rounds: u8 },
+    GameFinished,
+    TimeoutResolved,
+    WinningsClaimed { player: String },
+    /// `committed` of `total` seated players have committed this round.
+    CommitProgress { committed: u8, total: u8 },
+    /// `revealed` of `total` seated players have revealed this round.
+    RevealProgress { revealed: u8, total: u8 },
+    /// A new phase or round began; it can be timed out from `deadline`
+    /// (unix seconds, 0 once the game is finished).
+    PhaseStarted { phase_nonce: u64, deadline: u64 },
+    ChoiceRecommitted { player: String },
+    /// An auto tournament crank played bracket match `match_index`.
+    AutoMatchPlayed {
+        match_index: u8,
+        winner: String,
+        wins_a: u8,
+        wins_b: u8,
+    },
+    /// Phase `phase_nonce` (in state `state`) lasted `seconds`.
+    PhaseEnded {
+        phase_nonce: u64,
+        state: GameState,
+        seconds: u64,
+    },
+    /// Settlement heuristics flagged the game; `reasons` holds the
+    /// `solana_rps::FLAG_*` bits that fired.
+    CollusionSuspected { reasons: u8 },
+    /// A large payout was initiated; it can be finalized from `ready_at`.
+    ClaimInitiated { player: String, ready_at: u64 },
+    /// A player gave up (or was kicked from) their seat before play started.
+    PlayerLeft { player: String },
+    /// The treasury added `amount` to guarantee the pot, now `pot`.
+    PotSubsidized { amount: u64, pot: u64 },
+    /// Seats, by index, in the order round `round`'s reveals are staged.
+    RevealOrderDrawn { round: u8, order: Vec<u8> },
+    /// A mystery-stake game's fee opened at `entry_fee`; every seat got
+    /// `refund` back.
+    EntryFeeRevealed { entry_fee: u64, refund: u64 },
+    /// The settled game was closed and `lamports` of rent went to
+    /// `recipient`.
+    GameClosed { recipient: String, lamports: u64 },
+    /// `amount` of platform fee left the game for `recipient`: the treasury
+    /// on entry, or the fee collector for fees a token game held.
+    FeeCollected { recipient: String, amount: u64 },
+    /// Tournament, challenge, waitlist or governance activity, as decoded.
+    Other(RPSEvent),
+}
+
+impl From<RPSEvent> for GameEvent {
+    fn from(event: RPSEvent) -> Self {
+        match event {
+            RPSEvent::GameCreated { theme_id, .. } => GameEvent::GameInitialized {
+                theme_id: Some(theme_id),
+            },
+            RPSEvent::PlayerJoined { player, .. } => GameEvent::PlayerJoined {
+                player: player.to_string(),
+            },
+            RPSEvent::ChoiceCommitted { player, .. } => GameEvent::ChoiceCommitted {
+                player: player.to_string(),
+            },
+            RPSEvent::RevealPhaseStarted { .. } => GameEvent::RevealPhaseStarted,
+            RPSEvent::ChoiceRevealed { player, .. } => GameEvent::ChoiceRevealed {
+                player: player.to_string(),
+            },
+            RPSEvent::RoundResolved { round, scores, .. } => GameEvent::RoundResolved { round, scores },
+            RPSEvent::RoundStarted {
+                round, total_rounds, ..
+            } => GameEvent::RoundStarted { round, total_rounds },
+            RPSEvent::GameFinished { .. } => GameEvent::GameFinished,
+            RPSEvent::TimeoutResolved { .. } => GameEvent::TimeoutResolved,
+            RPSEvent::WinningsClaimed { winner, .. } => GameEvent::WinningsClaimed {
+                player: winner.to_string(),
+            },
+            RPSEvent::CommitProgress { committed, total, .. } => GameEvent::CommitProgress { committed, total },
+            RPSEvent::RevealProgress { revealed, total, .. } => GameEvent::RevealProgress { revealed, total },
+            RPSEvent::PhaseStarted {
+                phase_nonce, deadline, ..
+            } => GameEvent::PhaseStarted { phase_nonce, deadline },
+            RPSEvent::ChoiceRecommitted { player, .. } => GameEvent::ChoiceRecommitted {
+                player: player.to_string(),
+            },
+            RPSEvent::AutoMatchPlayed {
+                match_index,
+                winner,
+                wins_a,
+                wins_b,
+                ..
+            } => GameEvent::AutoMatchPlayed {
+                match_index,
+                winner: winner.to_string(),
+                wins_a,
+                wins_b,
+            },
+            RPSEvent::PhaseEnded {
+                phase_nonce,
+                state,
+                seconds,
+                ..
+            } => GameEvent::PhaseEnded {
+                phase_nonce,
+                state,
+                seconds,
+            },
+            RPSEvent::CollusionSuspected { reasons, .. } => GameEvent::CollusionSuspected { reasons },
+            RPSEvent::ClaimInitiated { winner, ready_at, .. } => GameEvent::ClaimInitiated {
+                player: winner.to_string(),
+                ready_at,
+            },
+            RPSEvent::PlayerLeft { player, .. } => GameEvent::PlayerLeft {
+                player: player.to_string(),
+            },
+            RPSEvent::PotSubsidized { amount, pot, .. } => GameEvent::PotSubsidized { amount, pot },
+            RPSEvent::RevealOrderDrawn { round, order, .. } => GameEvent::RevealOrderDrawn { round, order },
+            RPSEvent::EntryFeeRevealed { entry_fee, refund, .. } => GameEvent::EntryFeeRevealed { entry_fee, refund },
+            RPSEvent::GameClosed { recipient, lamports, .. } => GameEvent::GameClosed {
+                recipient: recipient.to_string(),
+                lamports,
+            },
+            RPSEvent::FeeCollected { recipient, amount, .. } => GameEvent::FeeCollected {
+                recipient: recipient.to_string(),
+                amount,
+            },
+            other => GameEvent::Other(other),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum GameUpdate {
+    /// The game account changed; `game` is its decoded state at `slot`.
+    Snapshot { slot: u64, game: Box<Game> },
+    /// A transaction touching the game logged `event`.
+    Event { signature: String, event: GameEvent },
+    /// A transaction touching the game failed.
+    Failed { signature: String, logs: Vec<String> },
+}
+
+/// Live subscription; dropping it (or calling [`GameSubscription::close`])
+/// tears down both websocket subscriptions.
+pub struct GameSubscription {
+    pub updates: mpsc::UnboundedReceiver<GameUpdate>,
+    task: JoinHandle<()>,
+}
+
+impl GameSubscription {
+    pub async fn next(&mut self) -> Option<GameUpdate> {
+        self.updates.recv().await
+    }
+
+    pub fn close(self) {
+        self.task.abort();
+    }
+}
+
+impl Drop for GameSubscription {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// Subscribes to account changes and logs for `game` on `ws_url`.
+pub async fn subscribe_game(
+    ws_url: &str,
+    game: Pubkey,
+    commitment: CommitmentConfig,
+) -> Result<GameSubscription> {
+    let client = PubsubClient::new(ws_url).await?;
+    let (sender, updates) = mpsc::unbounded_channel();
+
+    let task = tokio::spawn(async move {
+        let account_config = RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            commitment: Some(commitment),
+            ..RpcAccountInfoConfig::default()
+        };
+        let logs_filter = RpcTransactionLogsFilter::Mentions(vec![game.to_string()]);
+        let logs_config = RpcTransactionLogsConfig {
+            commitment: Some(commitment),
+        };
+
+        let Ok((mut accounts, _unsub_accounts)) =
+            client.account_subscribe(&game, Some(account_config)).await
+        else {
+            return;
+        };
+        let Ok((mut logs, _unsub_logs)) = client.logs_subscribe(logs_filter, logs_config).await
+        else {
+            return;
+        };
+
+        loop {
+            let update = tokio::select! {
+                Some(response) = accounts.next() => {
+                    let Some(data) = response.value.data.decode() else { continue };
+                    // Skip writes that don't decode (e.g. account being closed)
+                    let Ok(state) = decode_game(&game, &data) else { continue };
+                    vec![GameUpdate::Snapshot { slot: response.context.slot, game: Box::new(state) }]
+                }
+                Some(response) = logs.next() => {
+                    let logs = response.value;
+                    if logs.err.is_some() {
+                        vec![GameUpdate::Failed { signature: logs.signature, logs: logs.logs }]
+                    } else {
+                        logs.logs
+                            .iter()
+                            .filter_map(|line| parse_log_event(line))
+                            .map(|event| GameUpdate::Event { signature: logs.signature.clone(), event })
+                            .collect()
+                    }
+                }
+                else => break,
+            };
+            for update in update {
+                if sender.send(update).is_err() {
+                    return;
+                }
+            }
+        }
+    });
+
+    Ok(GameSubscription { updates, task })
+}
+
+/// Decodes the payload of a `Program data:` line into an [`RPSEvent`].
+pub fn decode_program_event(payload: &str) -> Option<RPSEvent> {
+    let field = payload.split_whitespace().next()?;
+    let data = base64::engine::general_purpose::STANDARD.decode(field).ok()?;
+    RPSEvent::try_from_slice(&data).ok()
+}
+
+/// Parses one `Program data:` or `Program log:` line emitted by the RPS
+/// program.
+pub fn parse_log_event(line: &str) -> Option<GameEvent> {
+    if let Some(payload) = line.strip_prefix("Program data: ") {
+        return decode_program_event(payload).map(GameEvent::from);
+    }
+
+    let message = line.strip_prefix("Program log: ")?;
+    let player_after = |prefix: &str| message.strip_prefix(prefix).map(|p| p.trim().to_string());
+
+    if message.starts_with("Game initialized with ID:") {
+        Some(GameEvent::GameInitialized { theme_id: None })
+    } else if let Some(player) = player_after("Player joined game:") {
+        Some(GameEvent::PlayerJoined { player })
+    } else if let Some(player) = player_after("Player committed choice:") {
+        Some(GameEvent::ChoiceCommitted { player })
+    } else if let Some(player) = player_after("Player revealed choice:") {
+        Some(GameEvent::ChoiceRevealed { player })
+    } else if message.starts_with("All players have committed") {
+        Some(GameEvent::RevealPhaseStarted)
+    } else if let Some(rest) = message.strip_prefix("Starting round ") {
+        let (round, total) = rest.split_once(" of ")?;
+        Some(GameEvent::RoundStarted {
+            round: round.trim().parse().ok()?,
+            total_rounds: total.trim().parse().ok()?,
+        })
+    } else if message.starts_with("Game finished after") {
+        Some(GameEvent::GameFinished)
+    } else if message == "Timeout resolved" {
+        Some(GameEvent::TimeoutResolved)
+    } else {
+        player_after("Winnings claimed by:").map(|player| GameEvent::WinningsClaimed { player })
+    }
+}
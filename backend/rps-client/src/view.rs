@@ -0,0 +1,75 @@
+//! Read-only program views.
+//!
+//! View instructions change nothing and answer through return data, so
+//! they are simulated rather than sent. The program computes the answer
+//! with the same code the real instruction uses, which keeps UIs from
+//! drifting out of sync with on-chain rules.
+
+use borsh::BorshDeserialize;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{instruction::Instruction, message::Message, pubkey::Pubkey, transaction::Transaction};
+use solana_rps::{Claimable, FeeQuote, GameAudit, SpectatorView};
+
+use crate::{
+    error::{Result, RpsClientError},
+    instruction,
+    preflight::preflight,
+};
+
+/// What `player` would receive from claiming `game` right now: the share
+/// settled on them when it finished, rounding dust included. All zero if
+/// they cannot claim. `player` pays for the simulation, so it must be a
+/// funded wallet.
+pub async fn claimable(rpc: &RpcClient, program_id: &Pubkey, game: &Pubkey, player: &Pubkey) -> Result<Claimable> {
+    let ix = instruction::get_claimable(program_id, game, player);
+    simulate_view(rpc, ix, player, game).await
+}
+
+/// The game account's lamports against what its state says it should hold.
+/// A nonzero `discrepancy` points at an accounting bug; monitoring bots can
+/// poll this for every live game. `payer` only pays for the simulation.
+pub async fn audit_game(rpc: &RpcClient, program_id: &Pubkey, game: &Pubkey, payer: &Pubkey) -> Result<GameAudit> {
+    let ix = instruction::audit_game(program_id, game);
+    simulate_view(rpc, ix, payer, game).await
+}
+
+/// A spectator's picture of `game`: phase, pot and each seat's committed
+/// and revealed flags and score, with no commitments or salts. `payer` only
+/// pays for the simulation.
+pub async fn spectator_view(
+    rpc: &RpcClient,
+    program_id: &Pubkey,
+    game: &Pubkey,
+    payer: &Pubkey,
+) -> Result<SpectatorView> {
+    let ix = instruction::get_spectator_view(program_id, game);
+    simulate_view(rpc, ix, payer, game).await
+}
+
+/// The platform fee and pot contribution of an entry of `entry_fee` in
+/// `currency_mode` (0 = SOL, 1 = RPSToken), as the program computes them at
+/// the rate a game created now is charged.
+/// `payer` only pays for the simulation.
+pub async fn fee_quote(
+    rpc: &RpcClient,
+    program_id: &Pubkey,
+    entry_fee: u64,
+    currency_mode: u8,
+    payer: &Pubkey,
+) -> Result<FeeQuote> {
+    let ix = instruction::get_fee_quote(program_id, entry_fee, currency_mode);
+    simulate_view(rpc, ix, payer, program_id).await
+}
+
+// `source` names the account the answer is about in decode errors
+async fn simulate_view<T: BorshDeserialize>(
+    rpc: &RpcClient,
+    ix: Instruction,
+    payer: &Pubkey,
+    source: &Pubkey,
+) -> Result<T> {
+    let tx = Transaction::new_unsigned(Message::new(&[ix], Some(payer)));
+    let report = preflight(rpc, &tx.into()).await?;
+    let data = report.return_data.ok_or(RpsClientError::MissingReturnData)?;
+    T::try_from_slice(&data).map_err(|_| RpsClientError::InvalidAccountData(source.to_string()))
+}
@@ -0,0 +1,749 @@
+//! In-process runtime for fast scenario tests.
+//!
+//! [`Harness`] runs instructions by calling `solana_rps::process_instruction`
+//! directly, serving the program's syscalls (clock, rent, logs, return data
+//! and CPIs) from memory. There is no validator, bank or BPF load, so a full
+//! game takes microseconds and whole scenario matrices fit in one test.
+//!
+//! The checks the real runtime applies around an instruction are kept:
+//! transactions are atomic, read-only accounts must not change, a program
+//! may only debit and write accounts it owns, and lamports are conserved.
+//! CPIs into the system program and SPL Token execute natively with the same
+//! rules, and the associated token account program's `Create` and
+//! `CreateIdempotent` are emulated on top of them; any other CPI target
+//! fails with `IncorrectProgramId`.
+//!
+//! Transaction signatures are not verified: an account is a signer when its
+//! `AccountMeta` says so, which lets tests forge missing signatures freely.
+//! Ed25519 precompile instructions are verified like the runtime does, and
+//! the instructions sysvar is served to instructions that list it. So is a
+//! one-entry SlotHashes sysvar, whose hash is derived from the clock's slot.
+//!
+//! Syscall stubs are a process-wide hook, so executions are serialized
+//! behind a global lock and harnesses can still be used from parallel tests.
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use base64::Engine;
+use num_traits::FromPrimitive;
+use solana_rps::{Game, RPSError, TournamentState, ASSOCIATED_TOKEN_PROGRAM_ID};
+use solana_sdk::{
+    account::Account,
+    account_info::AccountInfo,
+    clock::Clock,
+    ed25519_instruction, ed25519_program,
+    entrypoint::{ProgramResult, MAX_PERMITTED_DATA_INCREASE, SUCCESS},
+    feature_set::FeatureSet,
+    hash::hashv,
+    instruction::{Instruction, InstructionError},
+    program_error::ProgramError,
+    program_pack::Pack,
+    program_stubs::{self, SyscallStubs},
+    pubkey::Pubkey,
+    rent::Rent,
+    system_instruction::{self, SystemError, SystemInstruction},
+    system_program, sysvar,
+    sysvar::instructions::{construct_instructions_data, store_current_index, BorrowedAccountMeta, BorrowedInstruction},
+};
+
+/// Unix timestamp the harness clock starts at.
+pub const GENESIS_TIMESTAMP: i64 = 1_700_000_000;
+
+static EXECUTION: Mutex<()> = Mutex::new(());
+
+/// A failed transaction.
+#[derive(Debug, Clone)]
+pub struct Failure {
+    pub instruction_index: usize,
+    pub error: InstructionError,
+    pub logs: Vec<String>,
+}
+
+impl Failure {
+    /// The program error behind the failure, if it was an `RPSError`.
+    pub fn rps_error(&self) -> Option<RPSError> {
+        match self.error {
+            InstructionError::Custom(code) => RPSError::from_u32(code),
+            _ => None,
+        }
+    }
+}
+
+pub struct Harness {
+    program_id: Pubkey,
+    accounts: HashMap<Pubkey, Account>,
+    clock: Clock,
+    logs: Vec<String>,
+    return_data: Option<(Pubkey, Vec<u8>)>,
+}
+
+impl Harness {
+    pub fn new(program_id: Pubkey) -> Self {
+        Self {
+            program_id,
+            accounts: HashMap::new(),
+            clock: Clock {
+                slot: 1,
+                unix_timestamp: GENESIS_TIMESTAMP,
+                ..Clock::default()
+            },
+            logs: Vec::new(),
+            return_data: None,
+        }
+    }
+
+    pub fn program_id(&self) -> Pubkey {
+        self.program_id
+    }
+
+    pub fn set_account(&mut self, pubkey: Pubkey, account: Account) {
+        self.accounts.insert(pubkey, account);
+    }
+
+    pub fn account(&self, pubkey: &Pubkey) -> Option<&Account> {
+        self.accounts.get(pubkey)
+    }
+
+    /// Creates a system-owned wallet holding `lamports`.
+    pub fn new_wallet(&mut self, lamports: u64) -> Pubkey {
+        let pubkey = Pubkey::new_unique();
+        self.set_account(pubkey, Account::new(lamports, 0, &system_program::id()));
+        pubkey
+    }
+
+    pub fn lamports(&self, pubkey: &Pubkey) -> u64 {
+        self.accounts.get(pubkey).map_or(0, |a| a.lamports)
+    }
+
+    /// Sum of lamports across every account, for conservation checks.
+    pub fn total_lamports(&self) -> u128 {
+        self.accounts.values().map(|a| a.lamports as u128).sum()
+    }
+
+    /// Decodes a game account, `None` if it is missing or not a game.
+    pub fn game(&self, pubkey: &Pubkey) -> Option<Game> {
+        let account = self.accounts.get(pubkey)?;
+        Game::unpack_from(&account.data).ok()
+    }
+
+    /// Decodes a tournament account, `None` if it is missing or not a
+    /// tournament.
+    pub fn tournament(&self, pubkey: &Pubkey) -> Option<TournamentState> {
+        let account = self.accounts.get(pubkey)?;
+        TournamentState::unpack_from(&account.data).ok()
+    }
+
+    pub fn clock(&self) -> &Clock {
+        &self.clock
+    }
+
+    /// Hash the SlotHashes sysvar reports for the slot before the current
+    /// one; it changes whenever the clock moves.
+    pub fn recent_slot_hash(&self) -> [u8; 32] {
+        hashv(&[b"harness-slot", &self.clock.slot.saturating_sub(1).to_le_bytes()]).to_bytes()
+    }
+
+    /// Moves the clock forward by `seconds`, advancing the slot alongside.
+    pub fn warp_seconds(&mut self, seconds: i64) {
+        self.clock.unix_timestamp += seconds;
+        self.clock.slot += (seconds.max(0) as u64 * 5) / 2;
+    }
+
+    /// Moves the clock into a later epoch.
+    pub fn warp_epochs(&mut self, epochs: u64) {
+        self.clock.epoch += epochs;
+    }
+
+    /// Logs of the last transaction, in validator format.
+    pub fn logs(&self) -> &[String] {
+        &self.logs
+    }
+
+    /// Return data left by the last transaction.
+    pub fn return_data(&self) -> Option<&(Pubkey, Vec<u8>)> {
+        self.return_data.as_ref()
+    }
+
+    /// Runs `instructions` as one atomic transaction.
+    pub fn process(&mut self, instructions: &[Instruction]) -> Result<(), Failure> {
+        let _guard = EXECUTION.lock().unwrap_or_else(|e| e.into_inner());
+
+        self.logs.clear();
+        self.return_data = None;
+
+        let mut working = self.accounts.clone();
+        let introspection = instructions_sysvar_data(instructions);
+        let lists_slot_hashes = instructions
+            .iter()
+            .flat_map(|instruction| &instruction.accounts)
+            .any(|meta| meta.pubkey == sysvar::slot_hashes::id());
+        if lists_slot_hashes {
+            let slot = self.clock.slot.saturating_sub(1);
+            working.insert(
+                sysvar::slot_hashes::id(),
+                Account {
+                    lamports: 0,
+                    data: slot_hashes_data(slot, &self.recent_slot_hash()),
+                    owner: sysvar::id(),
+                    executable: false,
+                    rent_epoch: 0,
+                },
+            );
+        }
+        for (index, instruction) in instructions.iter().enumerate() {
+            let result = if instruction.program_id == ed25519_program::id() {
+                verify_ed25519(instruction)
+            } else {
+                if instruction.accounts.iter().any(|meta| meta.pubkey == sysvar::instructions::id()) {
+                    let mut data = introspection.clone();
+                    store_current_index(&mut data, index as u16);
+                    working.insert(
+                        sysvar::instructions::id(),
+                        Account {
+                            lamports: 0,
+                            data,
+                            owner: sysvar::id(),
+                            executable: false,
+                            rent_epoch: 0,
+                        },
+                    );
+                }
+                self.execute(&mut working, instruction)
+            };
+            if let Err(error) = result {
+                return Err(Failure {
+                    instruction_index: index,
+                    error,
+                    logs: self.logs.clone(),
+                });
+            }
+        }
+
+        // Accounts drained to zero lamports are garbage collected
+        working.retain(|_, account| account.lamports > 0);
+        self.accounts = working;
+        Ok(())
+    }
+
+    fn execute(
+        &mut self,
+        working: &mut HashMap<Pubkey, Account>,
+        instruction: &Instruction,
+    ) -> Result<(), InstructionError> {
+        if instruction.program_id != self.program_id {
+            return Err(InstructionError::IncorrectProgramId);
+        }
+
+        let mut slots = load_slots(working, instruction);
+        let state = Arc::new(Mutex::new(Invocation {
+            program_id: self.program_id,
+            depth: 1,
+            clock: self.clock.clone(),
+            logs: vec![format!("Program {} invoke [1]", self.program_id)],
+            return_data: None,
+            allocated: slots
+                .iter()
+                .filter(|s| s.buffered)
+                .map(|s| (s.key, 0))
+                .collect(),
+            snapshots: slots.iter().map(|s| (s.key, Snapshot::of_slot(s))).collect(),
+            aborted: None,
+        }));
+        program_stubs::set_syscall_stubs(Box::new(Runtime {
+            state: Arc::clone(&state),
+        }));
+
+        let lamports_before: u128 = slots.iter().map(|s| s.lamports as u128).sum();
+        let result = {
+            let infos: Vec<AccountInfo> = slots.iter_mut().map(Slot::account_info).collect();
+            let ordered: Vec<AccountInfo> = instruction
+                .accounts
+                .iter()
+                .map(|meta| {
+                    let index = position_of(&infos, &meta.pubkey);
+                    infos[index].clone()
+                })
+                .collect();
+
+            let result = solana_rps::process_instruction(&self.program_id, &ordered, &instruction.data)
+                .map_err(|e| InstructionError::from(u64::from(e)));
+
+            let mut state = state.lock().unwrap();
+            let result = match state.aborted.take() {
+                Some(error) => Err(error),
+                None => result.and_then(|()| state.verify(&self.program_id, &infos)),
+            };
+            result.and_then(|()| {
+                let lamports_after: u128 = infos.iter().map(|i| i.lamports() as u128).sum();
+                if lamports_after != lamports_before {
+                    Err(InstructionError::UnbalancedInstruction)
+                } else {
+                    Ok(())
+                }
+            })
+        };
+
+        let mut state = state.lock().unwrap();
+        state.logs.push(match &result {
+            Ok(()) => format!("Program {} success", self.program_id),
+            Err(error) => format!("Program {} failed: {}", self.program_id, error),
+        });
+        self.logs.append(&mut state.logs);
+        if state.return_data.is_some() {
+            self.return_data = state.return_data.take();
+        }
+        result?;
+
+        for slot in slots {
+            let mut data = slot.data;
+            if let Some(len) = state.allocated.get(&slot.key) {
+                data.truncate(*len);
+            }
+            working.insert(
+                slot.key,
+                Account {
+                    lamports: slot.lamports,
+                    data,
+                    owner: slot.owner,
+                    executable: slot.executable,
+                    rent_epoch: slot.rent_epoch,
+                },
+            );
+        }
+        Ok(())
+    }
+}
+
+// Backing storage for one account during an instruction
+struct Slot {
+    key: Pubkey,
+    lamports: u64,
+    data: Vec<u8>,
+    owner: Pubkey,
+    executable: bool,
+    rent_epoch: u64,
+    is_signer: bool,
+    is_writable: bool,
+    // Nonexistent account given spare capacity so CreateAccount can size it
+    buffered: bool,
+}
+
+impl Slot {
+    fn account_info(&mut self) -> AccountInfo<'_> {
+        AccountInfo::new(
+            &self.key,
+            self.is_signer,
+            self.is_writable,
+            &mut self.lamports,
+            &mut self.data,
+            &self.owner,
+            self.executable,
+            self.rent_epoch,
+        )
+    }
+}
+
+// Serialized SlotHashes sysvar holding the one entry `(slot, hash)`
+fn slot_hashes_data(slot: u64, hash: &[u8; 32]) -> Vec<u8> {
+    let mut data = Vec::with_capacity(48);
+    data.extend_from_slice(&1u64.to_le_bytes());
+    data.extend_from_slice(&slot.to_le_bytes());
+    data.extend_from_slice(hash);
+    data
+}
+
+// Serialized instructions sysvar for `instructions`; the current index is
+// patched in per instruction
+fn instructions_sysvar_data(instructions: &[Instruction]) -> Vec<u8> {
+    let borrowed: Vec<BorrowedInstruction> = instructions
+        .iter()
+        .map(|instruction| BorrowedInstruction {
+            program_id: &instruction.program_id,
+            accounts: instruction
+                .accounts
+                .iter()
+                .map(|meta| BorrowedAccountMeta {
+                    pubkey: &meta.pubkey,
+                    is_signer: meta.is_signer,
+                    is_writable: meta.is_writable,
+                })
+                .collect(),
+            data: &instruction.data,
+        })
+        .collect();
+    construct_instructions_data(&borrowed)
+}
+
+// Runs the ed25519 precompile. Only self-contained instructions are
+// supported, which is all the RPS client builds.
+fn verify_ed25519(instruction: &Instruction) -> Result<(), InstructionError> {
+    ed25519_instruction::verify(&instruction.data, &[&instruction.data], &FeatureSet::all_enabled())
+        .map_err(|_| InstructionError::InvalidInstructionData)
+}
+
+fn load_slots(working: &HashMap<Pubkey, Account>, instruction: &Instruction) -> Vec<Slot> {
+    let mut slots: Vec<Slot> = Vec::new();
+    for meta in &instruction.accounts {
+        if let Some(slot) = slots.iter_mut().find(|s| s.key == meta.pubkey) {
+            slot.is_signer |= meta.is_signer;
+            slot.is_writable |= meta.is_writable;
+            continue;
+        }
+        let account = working
+            .get(&meta.pubkey)
+            .cloned()
+            .unwrap_or_else(|| Account::new(0, 0, &system_program::id()));
+        let buffered = account.lamports == 0 && account.data.is_empty();
+        slots.push(Slot {
+            key: meta.pubkey,
+            lamports: account.lamports,
+            data: if buffered {
+                vec![0; MAX_PERMITTED_DATA_INCREASE]
+            } else {
+                account.data
+            },
+            owner: account.owner,
+            executable: account.executable,
+            rent_epoch: account.rent_epoch,
+            is_signer: meta.is_signer,
+            is_writable: meta.is_writable,
+            buffered,
+        });
+    }
+    slots
+}
+
+fn position_of(infos: &[AccountInfo], key: &Pubkey) -> usize {
+    infos
+        .iter()
+        .position(|info| info.key == key)
+        .expect("every meta has a slot")
+}
+
+#[derive(Clone)]
+struct Snapshot {
+    lamports: u64,
+    data: Vec<u8>,
+    owner: Pubkey,
+}
+
+impl Snapshot {
+    fn of_slot(slot: &Slot) -> Self {
+        Self {
+            lamports: slot.lamports,
+            data: slot.data.clone(),
+            owner: slot.owner,
+        }
+    }
+
+    fn of_info(info: &AccountInfo) -> Self {
+        Self {
+            lamports: info.lamports(),
+            data: info.data.borrow().to_vec(),
+            owner: *info.owner,
+        }
+    }
+}
+
+// Execution state shared with the syscall stubs
+struct Invocation {
+    program_id: Pubkey,
+    depth: usize,
+    clock: Clock,
+    logs: Vec<String>,
+    return_data: Option<(Pubkey, Vec<u8>)>,
+    // Logical data length of buffered accounts
+    allocated: HashMap<Pubkey, usize>,
+    // Account state at the last program boundary
+    snapshots: HashMap<Pubkey, Snapshot>,
+    // A CPI failure aborts the transaction even if the caller swallows it
+    aborted: Option<InstructionError>,
+}
+
+impl Invocation {
+    // Checks the changes `program_id` made since the last boundary and
+    // starts a new one
+    fn verify(&mut self, program_id: &Pubkey, infos: &[AccountInfo]) -> Result<(), InstructionError> {
+        for info in infos {
+            let current = Snapshot::of_info(info);
+            let Some(before) = self.snapshots.insert(*info.key, current.clone()) else {
+                continue;
+            };
+            let owned = before.owner == *program_id;
+
+            if !info.is_writable {
+                if current.lamports != before.lamports {
+                    return Err(InstructionError::ReadonlyLamportChange);
+                }
+                if current.data != before.data {
+                    return Err(InstructionError::ReadonlyDataModified);
+                }
+            }
+            if current.owner != before.owner && !owned {
+                return Err(InstructionError::ModifiedProgramId);
+            }
+            if current.lamports < before.lamports && !owned {
+                return Err(InstructionError::ExternalAccountLamportSpend);
+            }
+            if current.data != before.data && !owned {
+                return Err(InstructionError::ExternalAccountDataModified);
+            }
+        }
+        Ok(())
+    }
+
+    fn data_len(&self, info: &AccountInfo) -> usize {
+        self.allocated
+            .get(info.key)
+            .copied()
+            .unwrap_or_else(|| info.data_len())
+    }
+}
+
+struct Runtime {
+    state: Arc<Mutex<Invocation>>,
+}
+
+impl Runtime {
+    fn fail(&self, error: InstructionError) -> ProgramError {
+        let mut state = self.state.lock().unwrap();
+        state.aborted.get_or_insert(error.clone());
+        ProgramError::try_from(error).unwrap_or(ProgramError::InvalidArgument)
+    }
+
+    fn invoke(
+        &self,
+        instruction: &Instruction,
+        account_infos: &[AccountInfo],
+        signers_seeds: &[&[&[u8]]],
+    ) -> Result<(), InstructionError> {
+        let caller = self.state.lock().unwrap().program_id;
+
+        let signers = signers_seeds
+            .iter()
+            .map(|seeds| Pubkey::create_program_address(seeds, &caller))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| InstructionError::InvalidSeeds)?;
+
+        // Resolve the callee's accounts from the caller's, checking privileges
+        let mut infos = Vec::with_capacity(instruction.accounts.len());
+        for meta in &instruction.accounts {
+            let info = account_infos
+                .iter()
+                .find(|info| *info.key == meta.pubkey)
+                .ok_or(InstructionError::MissingAccount)?;
+            if meta.is_signer && !info.is_signer && !signers.contains(&meta.pubkey) {
+                return Err(InstructionError::PrivilegeEscalation);
+            }
+            if meta.is_writable && !info.is_writable {
+                return Err(InstructionError::PrivilegeEscalation);
+            }
+            let mut info = info.clone();
+            info.is_signer = meta.is_signer;
+            info.is_writable = meta.is_writable;
+            infos.push(info);
+        }
+
+        self.state.lock().unwrap().verify(&caller, account_infos)?;
+
+        let callee = instruction.program_id;
+        {
+            let mut state = self.state.lock().unwrap();
+            state.depth += 1;
+            let depth = state.depth;
+            state.logs.push(format!("Program {} invoke [{}]", callee, depth));
+            state.program_id = callee;
+        }
+
+        let result = if callee == system_program::id() {
+            self.process_system(&infos, &instruction.data)
+        } else if callee == spl_token::id() {
+            spl_token::processor::Processor::process(&callee, &infos, &instruction.data)
+                .map_err(|e| InstructionError::from(u64::from(e)))
+        } else if callee == ASSOCIATED_TOKEN_PROGRAM_ID {
+            self.process_associated_token(&infos, &instruction.data)
+        } else {
+            Err(InstructionError::IncorrectProgramId)
+        };
+
+        let mut state = self.state.lock().unwrap();
+        let result = result.and_then(|()| state.verify(&callee, account_infos));
+        state.logs.push(match &result {
+            Ok(()) => format!("Program {} success", callee),
+            Err(error) => format!("Program {} failed: {}", callee, error),
+        });
+        state.depth -= 1;
+        state.program_id = caller;
+        result
+    }
+
+    fn process_system(&self, infos: &[AccountInfo], data: &[u8]) -> Result<(), InstructionError> {
+        let instruction: SystemInstruction =
+            bincode::deserialize(data).map_err(|_| InstructionError::InvalidInstructionData)?;
+        let account = |i: usize| infos.get(i).ok_or(InstructionError::NotEnoughAccountKeys);
+
+        match instruction {
+            SystemInstruction::Transfer { lamports } => {
+                self.transfer(account(0)?, account(1)?, lamports)
+            }
+            SystemInstruction::CreateAccount {
+                lamports,
+                space,
+                owner,
+            } => {
+                let from = account(0)?;
+                let to = account(1)?;
+                if !to.is_signer {
+                    return Err(InstructionError::MissingRequiredSignature);
+                }
+                {
+                    let state = self.state.lock().unwrap();
+                    if to.lamports() > 0 || state.data_len(to) > 0 || *to.owner != system_program::id() {
+                        return Err(InstructionError::Custom(SystemError::AccountAlreadyInUse as u32));
+                    }
+                }
+                if space as usize > MAX_PERMITTED_DATA_INCREASE {
+                    return Err(InstructionError::InvalidRealloc);
+                }
+                self.transfer(from, to, lamports)?;
+                to.assign(&owner);
+                self.state.lock().unwrap().allocated.insert(*to.key, space as usize);
+                // The new account's data is exactly `space` bytes long, which
+                // SPL Token checks when it initializes one
+                let mut data = to.try_borrow_mut_data().map_err(|_| InstructionError::AccountBorrowFailed)?;
+                let buffer = std::mem::take(&mut *data);
+                *data = &mut buffer[..space as usize];
+                Ok(())
+            }
+            SystemInstruction::Assign { owner } => {
+                let target = account(0)?;
+                if !target.is_signer {
+                    return Err(InstructionError::MissingRequiredSignature);
+                }
+                if *target.owner != system_program::id() {
+                    return Err(InstructionError::ModifiedProgramId);
+                }
+                target.assign(&owner);
+                Ok(())
+            }
+            _ => Err(InstructionError::InvalidInstructionData),
+        }
+    }
+
+    // The associated token account program's `Create` and `CreateIdempotent`,
+    // which create the token account through the same CPIs it makes
+    fn process_associated_token(&self, infos: &[AccountInfo], data: &[u8]) -> Result<(), InstructionError> {
+        let idempotent = match data {
+            [] | [0] => false,
+            [1] => true,
+            _ => return Err(InstructionError::InvalidInstructionData),
+        };
+        let account = |i: usize| infos.get(i).ok_or(InstructionError::NotEnoughAccountKeys);
+        let (payer, address, wallet, mint) = (account(0)?, account(1)?, account(2)?, account(3)?);
+        let token_program = account(5)?.key;
+
+        let (expected, bump) = Pubkey::find_program_address(
+            &[wallet.key.as_ref(), token_program.as_ref(), mint.key.as_ref()],
+            &ASSOCIATED_TOKEN_PROGRAM_ID,
+        );
+        if *address.key != expected {
+            return Err(InstructionError::InvalidSeeds);
+        }
+        if address.owner == token_program {
+            let existing = spl_token::state::Account::unpack(&address.data.borrow())
+                .map_err(|_| InstructionError::InvalidAccountData)?;
+            if idempotent && existing.owner == *wallet.key && existing.mint == *mint.key {
+                return Ok(());
+            }
+            return Err(InstructionError::Custom(SystemError::AccountAlreadyInUse as u32));
+        }
+
+        let space = spl_token::state::Account::LEN;
+        let create = system_instruction::create_account(
+            payer.key,
+            address.key,
+            Rent::default().minimum_balance(space),
+            space as u64,
+            token_program,
+        );
+        let bump = [bump];
+        let seeds: &[&[u8]] = &[wallet.key.as_ref(), token_program.as_ref(), mint.key.as_ref(), &bump];
+        self.invoke(&create, infos, &[seeds])?;
+        let initialize = spl_token::instruction::initialize_account3(token_program, address.key, mint.key, wallet.key)
+            .map_err(|e| InstructionError::from(u64::from(e)))?;
+        self.invoke(&initialize, infos, &[])
+    }
+
+    fn transfer(&self, from: &AccountInfo, to: &AccountInfo, lamports: u64) -> Result<(), InstructionError> {
+        if !from.is_signer {
+            return Err(InstructionError::MissingRequiredSignature);
+        }
+        // The system program can only debit plain wallets
+        if self.state.lock().unwrap().data_len(from) > 0 || *from.owner != system_program::id() {
+            return Err(InstructionError::InvalidArgument);
+        }
+        if from.lamports() < lamports {
+            return Err(InstructionError::Custom(
+                SystemError::ResultWithNegativeLamports as u32,
+            ));
+        }
+        **from.try_borrow_mut_lamports().map_err(|_| InstructionError::AccountBorrowFailed)? -= lamports;
+        **to.try_borrow_mut_lamports().map_err(|_| InstructionError::AccountBorrowFailed)? += lamports;
+        Ok(())
+    }
+}
+
+impl SyscallStubs for Runtime {
+    fn sol_log(&self, message: &str) {
+        self.state.lock().unwrap().logs.push(format!("Program log: {}", message));
+    }
+
+    fn sol_log_data(&self, data: &[&[u8]]) {
+        let encoded: Vec<String> = data
+            .iter()
+            .map(|field| base64::engine::general_purpose::STANDARD.encode(field))
+            .collect();
+        self.state
+            .lock()
+            .unwrap()
+            .logs
+            .push(format!("Program data: {}", encoded.join(" ")));
+    }
+
+    fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
+        let clock = self.state.lock().unwrap().clock.clone();
+        unsafe { *(var_addr as *mut Clock) = clock };
+        SUCCESS
+    }
+
+    fn sol_get_rent_sysvar(&self, var_addr: *mut u8) -> u64 {
+        unsafe { *(var_addr as *mut Rent) = Rent::default() };
+        SUCCESS
+    }
+
+    fn sol_set_return_data(&self, data: &[u8]) {
+        let mut state = self.state.lock().unwrap();
+        state.return_data = if data.is_empty() {
+            None
+        } else {
+            Some((state.program_id, data.to_vec()))
+        };
+    }
+
+    fn sol_get_return_data(&self) -> Option<(Pubkey, Vec<u8>)> {
+        self.state.lock().unwrap().return_data.clone()
+    }
+
+    fn sol_invoke_signed(
+        &self,
+        instruction: &Instruction,
+        account_infos: &[AccountInfo],
+        signers_seeds: &[&[&[u8]]],
+    ) -> ProgramResult {
+        self.invoke(instruction, account_infos, signers_seeds)
+            .map_err(|error| self.fail(error))
+    }
+}
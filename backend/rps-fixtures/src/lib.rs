@@ -0,0 +1,433 @@
+//! Seeded fixtures for testing against the RPS program.
+//!
+//! [`FixtureBuilder::start`] boots a `solana-program-test` context with the
+//! program loaded, a set of funded player wallets, an RPS token mint with a
+//! funded token account per wallet, and one lobby in every game phase:
+//!
+//! - `waiting`:  host seated, waiting for the rest of the players
+//! - `commit`:   full lobby, every player but the last has committed
+//! - `reveal`:   everyone committed, every player but the last has revealed
+//! - `finished`: all rounds played, winners yet to claim
+//!
+//! The choices and salts behind every commitment are returned in
+//! [`Scenario::secrets`], so tests can reveal on behalf of the seeded players.
+//! [`Scenario::export_accounts`] writes the same accounts as JSON files that
+//! `solana-test-validator --account <pubkey> <file>` accepts, for frontend
+//! and bot developers who want a real validator.
+
+pub mod harness;
+pub mod setup;
+
+use std::{fs, io, path::Path};
+
+use base64::Engine;
+use borsh::BorshSerialize;
+use solana_program_test::{processor, ProgramTest, ProgramTestContext};
+use solana_rps::{
+    compute_commitment, Choice, CommitmentScheme, CurrencyMode, Game, GameMode, GameState, Player, RuleSet,
+    Settlement, GAME_LAYOUT_VERSION,
+};
+use solana_sdk::{
+    account::Account,
+    clock::Clock,
+    program_option::COption,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    rent::Rent,
+    signature::{Keypair, Signer},
+    system_program,
+};
+
+/// Default lamports given to each wallet.
+pub const DEFAULT_WALLET_LAMPORTS: u64 = 10_000_000_000;
+
+/// Default token balance minted to each wallet's token account.
+pub const DEFAULT_WALLET_TOKENS: u64 = 1_000_000_000;
+
+/// Decimals of the fixture RPS token mint.
+pub const TOKEN_DECIMALS: u8 = 6;
+
+/// Choices cycled through when seeding commitments.
+const SEED_CHOICES: [Choice; 3] = [Choice::Rock, Choice::Paper, Choice::Scissors];
+
+#[derive(Debug, Clone)]
+pub struct FixtureBuilder {
+    program_id: Pubkey,
+    players: u8,
+    entry_fee: u64,
+    total_rounds: u8,
+    timeout_seconds: u64,
+    wallet_lamports: u64,
+}
+
+impl Default for FixtureBuilder {
+    fn default() -> Self {
+        Self {
+            program_id: Pubkey::new_unique(),
+            players: 3,
+            entry_fee: 100_000_000,
+            total_rounds: 1,
+            timeout_seconds: 300,
+            wallet_lamports: DEFAULT_WALLET_LAMPORTS,
+        }
+    }
+}
+
+impl FixtureBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn program_id(mut self, program_id: Pubkey) -> Self {
+        self.program_id = program_id;
+        self
+    }
+
+    /// Players per lobby (2 to 8, matching program validation).
+    pub fn players(mut self, players: u8) -> Self {
+        self.players = players;
+        self
+    }
+
+    pub fn entry_fee(mut self, entry_fee: u64) -> Self {
+        self.entry_fee = entry_fee;
+        self
+    }
+
+    pub fn total_rounds(mut self, total_rounds: u8) -> Self {
+        self.total_rounds = total_rounds;
+        self
+    }
+
+    pub fn timeout_seconds(mut self, timeout_seconds: u64) -> Self {
+        self.timeout_seconds = timeout_seconds;
+        self
+    }
+
+    pub fn wallet_lamports(mut self, lamports: u64) -> Self {
+        self.wallet_lamports = lamports;
+        self
+    }
+
+    /// Boots the program-test context and seeds every fixture account.
+    pub async fn start(self) -> Scenario {
+        let mut program_test = ProgramTest::new(
+            "solana_rps",
+            self.program_id,
+            processor!(solana_rps::process_instruction),
+        );
+
+        let wallets: Vec<Keypair> = (0..self.players).map(|_| Keypair::new()).collect();
+        let mint_authority = Keypair::new();
+        let token_mint = Pubkey::new_unique();
+        let mut seeded = Vec::new();
+
+        for wallet in &wallets {
+            let account = Account::new(self.wallet_lamports, 0, &system_program::id());
+            program_test.add_account(wallet.pubkey(), account.clone());
+            seeded.push((wallet.pubkey(), account));
+        }
+
+        let mint_account = token_mint_account(&mint_authority.pubkey(), self.players);
+        program_test.add_account(token_mint, mint_account.clone());
+        seeded.push((token_mint, mint_account));
+
+        let mut token_accounts = Vec::new();
+        for wallet in &wallets {
+            let key = Pubkey::new_unique();
+            let account = token_account(&token_mint, &wallet.pubkey(), DEFAULT_WALLET_TOKENS);
+            program_test.add_account(key, account.clone());
+            seeded.push((key, account));
+            token_accounts.push(key);
+        }
+
+        let mut context = program_test.start_with_context().await;
+
+        // Lobbies are written after boot so their timestamps match the clock
+        let clock: Clock = context
+            .banks_client
+            .get_sysvar()
+            .await
+            .expect("clock sysvar");
+        let now = clock.unix_timestamp as u64;
+
+        let players: Vec<Pubkey> = wallets.iter().map(|w| w.pubkey()).collect();
+        let secrets = seed_secrets(&players);
+
+        let lobbies = Lobbies {
+            waiting: Pubkey::new_unique(),
+            commit: Pubkey::new_unique(),
+            reveal: Pubkey::new_unique(),
+            finished: Pubkey::new_unique(),
+        };
+
+        let phases = [
+            (lobbies.waiting, GameState::WaitingForPlayers),
+            (lobbies.commit, GameState::CommitPhase),
+            (lobbies.reveal, GameState::RevealPhase),
+            (lobbies.finished, GameState::Finished),
+        ];
+        for (key, state) in phases {
+            let game = self.game_in_phase(&key, state, &players, &secrets, now);
+            let account = game_account(&self.program_id, &game);
+            context.set_account(&key, &account.clone().into());
+            seeded.push((key, account));
+        }
+
+        Scenario {
+            context,
+            program_id: self.program_id,
+            wallets,
+            mint_authority,
+            token_mint,
+            token_accounts,
+            lobbies,
+            secrets,
+            seeded,
+        }
+    }
+
+    fn game_in_phase(
+        &self,
+        key: &Pubkey,
+        state: GameState,
+        players: &[Pubkey],
+        secrets: &[RevealSecret],
+        now: u64,
+    ) -> Game {
+        let seated = match state {
+            GameState::WaitingForPlayers => &players[..1],
+            _ => players,
+        };
+        let last = seated.len() - 1;
+        let current_round = match state {
+            GameState::Finished => self.total_rounds,
+            _ => 1,
+        };
+
+        let seats = seated
+            .iter()
+            .enumerate()
+            .map(|(i, pubkey)| {
+                let secret = &secrets[i];
+                let (committed, revealed) = match state {
+                    GameState::WaitingForPlayers => (false, false),
+                    GameState::CommitPhase => (i < last, false),
+                    GameState::RevealPhase => (true, i < last),
+                    GameState::Finished => (true, true),
+                };
+                Player {
+                    pubkey: *pubkey,
+                    choice: if revealed { secret.choice.clone() } else { Choice::None },
+                    committed_choice: if committed { secret.commitment(key, current_round) } else { [0; 64] },
+                    salt: [0; 32],
+                    revealed,
+                    // Finished lobbies have a single winner: the first player
+                    score: if matches!(state, GameState::Finished) && i == 0 { 1 } else { 0 },
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let fee = self.entry_fee / 100;
+        let phase_deadline = match state {
+            GameState::Finished => 0,
+            _ => now + self.timeout_seconds,
+        };
+
+        Game {
+            host: players[0],
+            players: seats,
+            min_players: self.players,
+            max_players: self.players,
+            state,
+            current_round,
+            total_rounds: self.total_rounds,
+            entry_fee: self.entry_fee,
+            game_pot: (self.entry_fee - fee) * seated.len() as u64,
+            required_timeout: self.timeout_seconds,
+            last_action_timestamp: now,
+            player_count: self.players,
+            losers_can_rejoin: false,
+            game_mode: GameMode::Manual,
+            auto_round_delay: 0,
+            max_auto_rounds: 0,
+            current_auto_round: 0,
+            currency_mode: CurrencyMode::SOL,
+            fee_collected: 0, // SOL fees are paid to the treasury at entry
+            token_mint: None,
+            base_price_feed: None,
+            base_decimals: 0,
+            stake_vaults: Vec::new(),
+            phase_nonce: 0,
+            claimed_bitmap: 0,
+            pot_snapshot: match state {
+                GameState::Finished => (self.entry_fee - fee) * seated.len() as u64,
+                _ => 0,
+            },
+            tournament: None,
+            tournament_match: 0,
+            phase_deadline,
+            allow_public_bots: false,
+            commitment_scheme: CommitmentScheme::HmacSha512,
+            team_lineup: Vec::new(),
+            phase_started_at: now,
+            participation_bitmap: 0,
+            flagged: false,
+            flag_reasons: 0,
+            seat_watch: Vec::new(),
+            bot_funders: Vec::new(),
+            pending_claims: Vec::new(),
+            escrows: Vec::new(),
+            pot_subsidy: 0,
+            layout_version: GAME_LAYOUT_VERSION,
+            player_count_drawn: true,
+            reveal_order: Vec::new(),
+            hidden_fee: None,
+            theme_id: 0,
+            fee_bps: None,
+            rule_set: RuleSet::Classic,
+            winners: 0,
+            cancelled: false,
+            join_timeout: 0,
+            commit_timeout: 0,
+            reveal_timeout: 0,
+            crank_reward_lamports: 0,
+            crank_bounty: 0,
+            slash_non_revealers: false,
+            slashed: Vec::new(),
+            bot_config: Vec::new(),
+            settlement: Settlement::default(),
+        }
+    }
+}
+
+/// Seeded lobby addresses, one per phase.
+#[derive(Debug, Clone, Copy)]
+pub struct Lobbies {
+    pub waiting: Pubkey,
+    pub commit: Pubkey,
+    pub reveal: Pubkey,
+    pub finished: Pubkey,
+}
+
+/// The preimage behind a seeded commitment.
+#[derive(Debug, Clone)]
+pub struct RevealSecret {
+    pub player: Pubkey,
+    pub choice: Choice,
+    pub salt: [u8; 32],
+}
+
+impl RevealSecret {
+    /// The commitment as seeded into `round` of lobby `game`.
+    pub fn commitment(&self, game: &Pubkey, round: u8) -> [u8; 64] {
+        compute_commitment(game, round, &self.player, &self.salt, &self.choice).expect("seed choices are valid")
+    }
+}
+
+pub struct Scenario {
+    pub context: ProgramTestContext,
+    pub program_id: Pubkey,
+    /// Player wallets; `wallets[0]` hosts every lobby.
+    pub wallets: Vec<Keypair>,
+    pub mint_authority: Keypair,
+    pub token_mint: Pubkey,
+    /// RPS token account per wallet, same order as `wallets`.
+    pub token_accounts: Vec<Pubkey>,
+    pub lobbies: Lobbies,
+    /// Commitment preimages per wallet, same order as `wallets`.
+    pub secrets: Vec<RevealSecret>,
+    seeded: Vec<(Pubkey, Account)>,
+}
+
+impl Scenario {
+    /// Writes every seeded account as `<pubkey>.json` under `dir`, in the
+    /// format `solana account --output json` produces.
+    pub fn export_accounts(&self, dir: &Path) -> io::Result<()> {
+        fs::create_dir_all(dir)?;
+        for (pubkey, account) in &self.seeded {
+            let json = serde_json::json!({
+                "pubkey": pubkey.to_string(),
+                "account": {
+                    "lamports": account.lamports,
+                    "data": [base64::engine::general_purpose::STANDARD.encode(&account.data), "base64"],
+                    "owner": account.owner.to_string(),
+                    "executable": account.executable,
+                    "rentEpoch": account.rent_epoch,
+                    "space": account.data.len(),
+                }
+            });
+            fs::write(dir.join(format!("{}.json", pubkey)), json.to_string())?;
+        }
+        Ok(())
+    }
+}
+
+fn seed_secrets(players: &[Pubkey]) -> Vec<RevealSecret> {
+    players
+        .iter()
+        .enumerate()
+        .map(|(i, player)| {
+            let choice = SEED_CHOICES[i % SEED_CHOICES.len()].clone();
+            let mut salt = [0u8; 32];
+            salt[..32].copy_from_slice(&player.to_bytes());
+            salt[0] ^= i as u8;
+            RevealSecret {
+                player: *player,
+                choice,
+                salt,
+            }
+        })
+        .collect()
+}
+
+fn game_account(program_id: &Pubkey, game: &Game) -> Account {
+    let mut data = game.try_to_vec().expect("game serializes");
+    data.resize(Game::get_max_size(game.max_players).max(data.len()), 0);
+    let lamports = Rent::default().minimum_balance(data.len()) + game.game_pot + game.fee_collected;
+    Account {
+        lamports,
+        data,
+        owner: *program_id,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+fn token_mint_account(authority: &Pubkey, holders: u8) -> Account {
+    let mint = spl_token::state::Mint {
+        mint_authority: COption::Some(*authority),
+        supply: DEFAULT_WALLET_TOKENS * holders as u64,
+        decimals: TOKEN_DECIMALS,
+        is_initialized: true,
+        freeze_authority: COption::None,
+    };
+    let mut data = vec![0; spl_token::state::Mint::LEN];
+    mint.pack_into_slice(&mut data);
+    Account {
+        lamports: Rent::default().minimum_balance(data.len()),
+        data,
+        owner: spl_token::id(),
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+fn token_account(mint: &Pubkey, owner: &Pubkey, amount: u64) -> Account {
+    let account = spl_token::state::Account {
+        mint: *mint,
+        owner: *owner,
+        amount,
+        state: spl_token::state::AccountState::Initialized,
+        ..spl_token::state::Account::default()
+    };
+    let mut data = vec![0; spl_token::state::Account::LEN];
+    account.pack_into_slice(&mut data);
+    Account {
+        lamports: Rent::default().minimum_balance(data.len()),
+        data,
+        owner: spl_token::id(),
+        executable: false,
+        rent_epoch: 0,
+    }
+}
@@ -0,0 +1,64 @@
+//! Setup shared by the [`Harness`] scenario tests: funded wallets, SPL
+//! Token state written straight into the harness, and fresh lobbies.
+
+use rps_client::instruction::{self, GameConfig};
+use solana_sdk::{account::Account, program_pack::Pack, pubkey::Pubkey, rent::Rent};
+
+use crate::harness::Harness;
+
+/// Lamports scenario tests fund each wallet with.
+pub const WALLET_LAMPORTS: u64 = crate::DEFAULT_WALLET_LAMPORTS;
+
+/// A rent-exempt account owned by `owner` holding `state`.
+pub fn packed<T: Pack>(state: T, owner: Pubkey) -> Account {
+    let mut data = vec![0; T::LEN];
+    state.pack_into_slice(&mut data);
+    Account {
+        lamports: Rent::default().minimum_balance(data.len()),
+        data,
+        owner,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+/// Writes an initialized token account of `owner`'s for `mint` at a new
+/// address.
+pub fn token_account(harness: &mut Harness, mint: Pubkey, owner: Pubkey, amount: u64) -> Pubkey {
+    token_account_at(harness, Pubkey::new_unique(), mint, owner, amount)
+}
+
+/// [`token_account`] at `address`, for accounts the program derives.
+pub fn token_account_at(harness: &mut Harness, address: Pubkey, mint: Pubkey, owner: Pubkey, amount: u64) -> Pubkey {
+    let state = spl_token::state::Account {
+        mint,
+        owner,
+        amount,
+        state: spl_token::state::AccountState::Initialized,
+        ..spl_token::state::Account::default()
+    };
+    harness.set_account(address, packed(state, spl_token::id()));
+    address
+}
+
+/// Balance of the token account at `address`.
+pub fn token_balance(harness: &Harness, address: &Pubkey) -> u64 {
+    let account = harness.account(address).expect("token account");
+    spl_token::state::Account::unpack(&account.data).expect("token state").amount
+}
+
+/// A lobby hosted by `players[0]` under `config`, which the rest of
+/// `players` have joined.
+pub fn open_lobby(harness: &mut Harness, players: &[Pubkey], config: &GameConfig) -> Pubkey {
+    let program_id = harness.program_id();
+    let game = Pubkey::new_unique();
+    harness
+        .process(&[instruction::initialize_game(&program_id, &players[0], &game, config)])
+        .expect("initialize game");
+    for player in &players[1..] {
+        harness
+            .process(&[instruction::join_game(&program_id, player, &game)])
+            .expect("join game");
+    }
+    game
+}
@@ -0,0 +1,75 @@
+//! Nothing in a game account narrows down a hand before it is revealed:
+//! lobbies that differ only in their hidden hands differ only in the
+//! commitments, and no bytes of the account open a commitment.
+
+use borsh::BorshSerialize;
+use rps_client::instruction::{self, GameConfig};
+use rps_fixtures::harness::Harness;
+use rps_fixtures::setup::{open_lobby, WALLET_LAMPORTS};
+use solana_rps::{compute_commitment, Choice};
+use solana_sdk::pubkey::Pubkey;
+
+const ENTRY_FEE: u64 = 100_000_000;
+const HANDS: [Choice; 3] = [Choice::Rock, Choice::Paper, Choice::Scissors];
+
+// Opens a full lobby and has the first seats commit to `hands`, leaving it
+// in the commit phase
+fn lobby_committed_to(harness: &mut Harness, players: &[Pubkey], hands: &[Choice], salt_base: u8) -> Pubkey {
+    let program_id = harness.program_id();
+    let config = GameConfig {
+        entry_fee: ENTRY_FEE,
+        ..GameConfig::default()
+    };
+    let game = open_lobby(harness, players, &config);
+    for (i, (player, hand)) in players.iter().zip(hands).enumerate() {
+        let salt = [salt_base + i as u8; 32];
+        let commitment = compute_commitment(&game, 1, player, &salt, hand).expect("valid choice");
+        harness
+            .process(&[instruction::commit_choice(&program_id, player, &game, commitment)])
+            .expect("commit");
+    }
+    game
+}
+
+#[test]
+fn hidden_hands_leave_no_trace_but_the_commitment() {
+    let mut harness = Harness::new(Pubkey::new_unique());
+    let players: Vec<Pubkey> = (0..3).map(|_| harness.new_wallet(WALLET_LAMPORTS)).collect();
+
+    let rocks = lobby_committed_to(&mut harness, &players, &[Choice::Rock, Choice::Rock], 1);
+    let mixed = lobby_committed_to(&mut harness, &players, &[Choice::Scissors, Choice::Paper], 11);
+
+    let redacted = |game: &Pubkey| {
+        let mut state = harness.game(game).expect("game");
+        assert!(state.players.iter().all(|p| p.salt == [0; 32] && matches!(p.choice, Choice::None)));
+        for player in &mut state.players {
+            player.committed_choice = [0; 64];
+        }
+        state.try_to_vec().expect("game serializes")
+    };
+    assert_eq!(redacted(&rocks), redacted(&mixed));
+    assert_eq!(
+        harness.account(&rocks).expect("game account").data.len(),
+        harness.account(&mixed).expect("game account").data.len()
+    );
+}
+
+#[test]
+fn no_account_bytes_open_a_commitment() {
+    let mut harness = Harness::new(Pubkey::new_unique());
+    let players: Vec<Pubkey> = (0..3).map(|_| harness.new_wallet(WALLET_LAMPORTS)).collect();
+    let game = lobby_committed_to(&mut harness, &players, &[Choice::Paper, Choice::Scissors], 1);
+
+    // Try every 32-byte run of the account as the salt, against every hand
+    let data = harness.account(&game).expect("game account").data.clone();
+    let state = harness.game(&game).expect("game");
+    for seat in &state.players[..2] {
+        for salt in data.windows(32) {
+            let salt: [u8; 32] = salt.try_into().expect("32-byte window");
+            for hand in &HANDS {
+                let guess = compute_commitment(&game, 1, &seat.pubkey, &salt, hand).expect("valid choice");
+                assert_ne!(guess, seat.committed_choice);
+            }
+        }
+    }
+}
@@ -0,0 +1,49 @@
+//! Account specs: every instruction's leading accounts are checked against
+//! `solana_rps::accounts` before it runs, and the client SDK reads the same
+//! table, so a hand-built instruction that drops a signature or a writable
+//! flag is refused in both places.
+
+use rps_client::instruction::{self, AccountMismatch, GameConfig};
+use rps_fixtures::harness::Harness;
+use rps_fixtures::setup::WALLET_LAMPORTS;
+use solana_rps::{accounts::INSTRUCTION_ACCOUNTS, RPSError};
+use solana_sdk::{instruction::InstructionError, pubkey::Pubkey};
+
+#[test]
+fn every_instruction_has_a_spec() {
+    let program_id = Pubkey::new_unique();
+    let last = instruction::bot_act(&program_id, &Pubkey::new_unique(), &Pubkey::new_unique());
+    assert_eq!(last.data[0] as usize, INSTRUCTION_ACCOUNTS.len() - 1);
+    assert_eq!(instruction::account_mismatch(&last), None);
+}
+
+#[test]
+fn hand_edited_accounts_are_refused_at_dispatch() {
+    let mut harness = Harness::new(Pubkey::new_unique());
+    let program_id = harness.program_id();
+    let host = harness.new_wallet(WALLET_LAMPORTS);
+    let player = harness.new_wallet(WALLET_LAMPORTS);
+    let game = Pubkey::new_unique();
+    harness
+        .process(&[instruction::initialize_game(&program_id, &host, &game, &GameConfig::default())])
+        .expect("initialize game");
+    let join = instruction::join_game(&program_id, &player, &game);
+    assert_eq!(instruction::account_mismatch(&join), None);
+
+    let mut unsigned = join.clone();
+    unsigned.accounts[0].is_signer = false;
+    assert_eq!(instruction::account_mismatch(&unsigned), Some(AccountMismatch::Unsigned(0)));
+    assert_eq!(harness.process(&[unsigned]).unwrap_err().rps_error(), Some(RPSError::NotAuthorized));
+
+    let mut read_only = join.clone();
+    read_only.accounts[1].is_writable = false;
+    assert_eq!(instruction::account_mismatch(&read_only), Some(AccountMismatch::ReadOnly(1)));
+    assert_eq!(harness.process(&[read_only]).unwrap_err().rps_error(), Some(RPSError::AccountNotWritable));
+
+    let mut short = join.clone();
+    short.accounts.truncate(8);
+    assert_eq!(instruction::account_mismatch(&short), Some(AccountMismatch::Missing(8)));
+    assert_eq!(harness.process(&[short]).unwrap_err().error, InstructionError::NotEnoughAccountKeys);
+
+    harness.process(&[join]).expect("join game");
+}
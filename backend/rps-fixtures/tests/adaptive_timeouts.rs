@@ -0,0 +1,98 @@
+//! Adaptive default timeouts: games created without a timeout inherit one
+//! a crank derives from recent commit and reveal latency.
+
+use borsh::BorshSerialize;
+use rps_client::instruction::{self, GameConfig};
+use rps_fixtures::harness::Harness;
+use rps_fixtures::setup::WALLET_LAMPORTS;
+use solana_rps::{stats_address, GlobalStats, RPSError};
+use solana_sdk::{account::Account, pubkey::Pubkey, rent::Rent};
+
+// Overwrites the stats PDA with the given running totals
+fn set_stats(harness: &mut Harness, commits: u64, commit_latency_secs: u64, reveals: u64, reveal_latency_secs: u64) {
+    let stats = GlobalStats {
+        commits,
+        commit_latency_secs,
+        reveals,
+        reveal_latency_secs,
+        ..GlobalStats::default()
+    };
+    let data = stats.try_to_vec().expect("stats serialize");
+    let account = Account {
+        lamports: Rent::default().minimum_balance(data.len()),
+        data,
+        owner: harness.program_id(),
+        executable: false,
+        rent_epoch: 0,
+    };
+    harness.set_account(stats_address(&harness.program_id()).0, account);
+}
+
+// Creates a game that inherits the default timeout and returns it
+fn inherited_timeout(harness: &mut Harness, host: &Pubkey) -> Result<u64, Option<RPSError>> {
+    let program_id = harness.program_id();
+    let game = Pubkey::new_unique();
+    let config = GameConfig {
+        timeout_seconds: 0,
+        ..GameConfig::default()
+    };
+    harness
+        .process(&[instruction::initialize_game(&program_id, host, &game, &config)])
+        .map_err(|failure| failure.rps_error())?;
+    Ok(harness.game(&game).expect("game").required_timeout)
+}
+
+#[test]
+fn games_inherit_a_default_derived_from_recent_latency() {
+    let mut harness = Harness::new(Pubkey::new_unique());
+    let program_id = harness.program_id();
+    let operator = harness.new_wallet(WALLET_LAMPORTS);
+    let host = harness.new_wallet(WALLET_LAMPORTS);
+
+    harness
+        .process(&[instruction::initialize_config(&program_id, &operator)])
+        .expect("initialize config");
+    harness
+        .process(&[instruction::initialize_stats(&program_id, &operator)])
+        .expect("initialize stats");
+    assert_eq!(inherited_timeout(&mut harness, &host), Err(Some(RPSError::InvalidParameter)));
+
+    let forged = harness.process(&[instruction::set_timeout_policy(&program_id, &host, 200, 30, 600)]);
+    assert_eq!(forged.unwrap_err().rps_error(), Some(RPSError::NotAuthorized));
+    let inverted = harness.process(&[instruction::set_timeout_policy(&program_id, &operator, 200, 600, 30)]);
+    assert_eq!(inverted.unwrap_err().rps_error(), Some(RPSError::InvalidParameter));
+    harness
+        .process(&[instruction::set_timeout_policy(&program_id, &operator, 200, 30, 600)])
+        .expect("set timeout policy");
+
+    // Until the first refresh games get the upper bound
+    assert_eq!(inherited_timeout(&mut harness, &host), Ok(600));
+
+    set_stats(&mut harness, 5, 100, 5, 100);
+    let thin = harness.process(&[instruction::refresh_default_timeout(&program_id, &host)]);
+    assert_eq!(thin.unwrap_err().rps_error(), Some(RPSError::InvalidGameState));
+
+    // 24 actions averaging 30s: the default is twice that
+    set_stats(&mut harness, 12, 480, 12, 240);
+    harness
+        .process(&[instruction::refresh_default_timeout(&program_id, &host)])
+        .expect("refresh");
+    assert_eq!(inherited_timeout(&mut harness, &host), Ok(60));
+
+    let stale = harness.process(&[instruction::refresh_default_timeout(&program_id, &host)]);
+    assert_eq!(stale.unwrap_err().rps_error(), Some(RPSError::InvalidGameState));
+
+    // Only the 20 actions since the last refresh count, averaging 200s
+    set_stats(&mut harness, 22, 2_480, 22, 2_240);
+    harness
+        .process(&[instruction::refresh_default_timeout(&program_id, &host)])
+        .expect("refresh");
+    assert_eq!(inherited_timeout(&mut harness, &host), Ok(400));
+
+    // An explicit timeout still wins
+    let game = Pubkey::new_unique();
+    harness
+        .process(&[instruction::initialize_game(&program_id, &host, &game, &GameConfig::default())])
+        .expect("initialize game");
+    assert_eq!(harness.game(&game).expect("game").required_timeout, 300);
+}
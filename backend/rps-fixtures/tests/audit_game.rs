@@ -0,0 +1,56 @@
+//! `AuditGame` reconciles the pot vault's lamports with the game's state, so
+//! a monitoring bot sees any lamports the accounting lost track of.
+
+use borsh::BorshDeserialize;
+use rps_client::instruction::{self, GameConfig};
+use rps_fixtures::harness::Harness;
+use rps_fixtures::setup::WALLET_LAMPORTS;
+use solana_rps::{pot_vault_address, GameAudit};
+use solana_sdk::pubkey::Pubkey;
+
+fn audit(harness: &mut Harness, game: &Pubkey) -> GameAudit {
+    let program_id = harness.program_id();
+    harness
+        .process(&[instruction::audit_game(&program_id, game)])
+        .expect("audit game");
+    let (program, data) = harness.return_data().expect("return data");
+    assert_eq!(*program, program_id);
+    GameAudit::try_from_slice(data).expect("decode audit")
+}
+
+#[test]
+fn audit_balances_until_lamports_go_missing() {
+    let mut harness = Harness::new(Pubkey::new_unique());
+    let program_id = harness.program_id();
+    let players: Vec<Pubkey> = (0..3).map(|_| harness.new_wallet(WALLET_LAMPORTS)).collect();
+    let game = Pubkey::new_unique();
+
+    let config = GameConfig {
+        entry_fee: 100_000_000,
+        ..GameConfig::default()
+    };
+    harness
+        .process(&[instruction::initialize_game(&program_id, &players[0], &game, &config)])
+        .expect("initialize game");
+    for player in &players[1..] {
+        harness
+            .process(&[instruction::join_game(&program_id, player, &game)])
+            .expect("join game");
+    }
+
+    let vault = pot_vault_address(&program_id, &game).0;
+    let clean = audit(&mut harness, &game);
+    assert_eq!(clean.discrepancy, 0, "{:?}", clean);
+    assert_eq!(clean.actual_lamports, harness.lamports(&vault));
+
+    // Lamports leaking out of the vault show up as a shortfall
+    let mut account = harness.account(&vault).expect("pot vault").clone();
+    account.lamports -= 1_000;
+    harness.set_account(vault, account.clone());
+    assert_eq!(audit(&mut harness, &game).discrepancy, -1_000);
+
+    // and stray deposits as a surplus
+    account.lamports += 3_000;
+    harness.set_account(vault, account);
+    assert_eq!(audit(&mut harness, &game).discrepancy, 2_000);
+}
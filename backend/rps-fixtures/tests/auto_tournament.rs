@@ -0,0 +1,72 @@
+//! Bot-only auto tournaments run from creation to champion on nothing but
+//! permissionless cranks.
+
+use rps_client::{
+    instruction::{self, TournamentConfig},
+    subscribe::{parse_log_event, GameEvent},
+};
+use rps_fixtures::harness::Harness;
+use rps_fixtures::setup::WALLET_LAMPORTS;
+use solana_rps::RPSError;
+use solana_sdk::pubkey::Pubkey;
+
+#[test]
+fn cranks_play_every_match_to_a_champion() {
+    let mut harness = Harness::new(Pubkey::new_unique());
+    let program_id = harness.program_id();
+    let host = harness.new_wallet(WALLET_LAMPORTS);
+    let cranker = harness.new_wallet(WALLET_LAMPORTS);
+    let tournament = Pubkey::new_unique();
+
+    let invalid = harness.process(&[instruction::create_auto_tournament(&program_id, &host, &tournament, 5, 0)]);
+    assert_eq!(invalid.unwrap_err().rps_error(), Some(RPSError::InvalidParameter));
+    harness
+        .process(&[instruction::create_auto_tournament(&program_id, &host, &tournament, 5, 3)])
+        .expect("create auto tournament");
+
+    let state = harness.tournament(&tournament).expect("tournament account");
+    assert!(state.is_started);
+    assert_eq!(state.players.len(), 5);
+
+    // Five bots in an eight-slot bracket: three byes, four matches to play
+    let mut played = Vec::new();
+    while !harness.tournament(&tournament).expect("tournament account").is_finished {
+        harness.warp_seconds(1);
+        harness
+            .process(&[instruction::crank_auto_tournament(&program_id, &cranker, &tournament)])
+            .expect("crank");
+        let event = harness.logs().iter().find_map(|line| match parse_log_event(line) {
+            Some(GameEvent::AutoMatchPlayed { match_index, winner, wins_a, wins_b }) => {
+                Some((match_index, winner, wins_a, wins_b))
+            }
+            _ => None,
+        });
+        let (match_index, winner, wins_a, wins_b) = event.expect("match played event");
+        assert_eq!(wins_a.max(wins_b), 2, "best of three");
+        played.push((match_index, winner));
+    }
+    assert_eq!(played.len(), 4);
+
+    let state = harness.tournament(&tournament).expect("tournament account");
+    let champion = state.champion.expect("champion");
+    assert_eq!(played.last().unwrap().1, champion.to_string());
+    assert_eq!(state.standings.len(), 5);
+    assert_eq!(state.standings[0], champion);
+
+    let again = harness.process(&[instruction::crank_auto_tournament(&program_id, &cranker, &tournament)]);
+    assert_eq!(again.unwrap_err().rps_error(), Some(RPSError::InvalidGameState));
+}
+
+#[test]
+fn regular_tournaments_cannot_be_cranked() {
+    let mut harness = Harness::new(Pubkey::new_unique());
+    let program_id = harness.program_id();
+    let host = harness.new_wallet(WALLET_LAMPORTS);
+    let tournament = Pubkey::new_unique();
+
+    harness
+        .process(&[instruction::create_tournament(&program_id, &host, &tournament, &TournamentConfig::default())])
+        .expect("create tournament");
+    let crank = harness.process(&[instruction::crank_auto_tournament(&program_id, &host, &tournament)]);
+    assert_eq!(crank.unwrap_err().rps_error(), Some(RPSError::InvalidGameState));
+}
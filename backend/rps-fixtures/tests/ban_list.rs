@@ -0,0 +1,53 @@
+//! Operator ban list: banned wallets are turned away from games and
+//! tournaments until they are unbanned.
+
+use rps_client::instruction::{self, GameConfig, TournamentConfig};
+use rps_fixtures::harness::Harness;
+use rps_fixtures::setup::WALLET_LAMPORTS;
+use solana_rps::RPSError;
+use solana_sdk::pubkey::Pubkey;
+
+#[test]
+fn banned_wallet_cannot_join_until_unbanned() {
+    let mut harness = Harness::new(Pubkey::new_unique());
+    let program_id = harness.program_id();
+    let operator = harness.new_wallet(WALLET_LAMPORTS);
+    let host = harness.new_wallet(WALLET_LAMPORTS);
+    let cheater = harness.new_wallet(WALLET_LAMPORTS);
+
+    harness
+        .process(&[instruction::initialize_config(&program_id, &operator)])
+        .expect("initialize config");
+    let again = harness.process(&[instruction::initialize_config(&program_id, &host)]);
+    assert_eq!(again.unwrap_err().rps_error(), Some(RPSError::InvalidGameState));
+
+    let forged = harness.process(&[instruction::ban_player(&program_id, &host, &cheater)]);
+    assert_eq!(forged.unwrap_err().rps_error(), Some(RPSError::NotAuthorized));
+    harness
+        .process(&[instruction::ban_player(&program_id, &operator, &cheater)])
+        .expect("ban");
+
+    let game = Pubkey::new_unique();
+    harness
+        .process(&[instruction::initialize_game(&program_id, &host, &game, &GameConfig::default())])
+        .expect("initialize game");
+    let tournament = Pubkey::new_unique();
+    harness
+        .process(&[instruction::create_tournament(&program_id, &host, &tournament, &TournamentConfig::default())])
+        .expect("create tournament");
+
+    let join = harness.process(&[instruction::join_game(&program_id, &cheater, &game)]);
+    assert_eq!(join.unwrap_err().rps_error(), Some(RPSError::PlayerBanned));
+    let enter = harness.process(&[instruction::join_tournament(&program_id, &cheater, &tournament, None, None)]);
+    assert_eq!(enter.unwrap_err().rps_error(), Some(RPSError::PlayerBanned));
+
+    harness
+        .process(&[instruction::unban_player(&program_id, &operator, &cheater)])
+        .expect("unban");
+    harness
+        .process(&[instruction::join_game(&program_id, &cheater, &game)])
+        .expect("join after unban");
+    harness
+        .process(&[instruction::join_tournament(&program_id, &cheater, &tournament, None, None)])
+        .expect("enter after unban");
+}
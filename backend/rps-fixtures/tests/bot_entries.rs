@@ -0,0 +1,314 @@
+//! Bots in paid games: the initiator buys their seats, so the pot is always
+//! backed by lamports that were actually deposited, and is paid whatever
+//! those seats win or are refunded. Only the host may add them unless the
+//! game opts into public bots.
+
+use borsh::BorshDeserialize;
+use rps_client::instruction::{self, GameConfig};
+use rps_fixtures::harness::Harness;
+use rps_fixtures::setup::{packed, token_account, token_account_at, token_balance, WALLET_LAMPORTS};
+use solana_rps::{
+    compute_commitment, game_authority_address, game_vault_address, treasury_address, Choice, GameAudit, GameState,
+    RPSError,
+};
+use solana_sdk::{program_option::COption, pubkey::Pubkey};
+
+const ENTRY_FEE: u64 = 100_000_000;
+
+// A paid game where `funder` bought two bot seats against a host who
+// commits and never reveals, left finished with only bots winning
+fn lost_to_bots(harness: &mut Harness, host: &Pubkey, funder: &Pubkey) -> Pubkey {
+    let program_id = harness.program_id();
+    let game = Pubkey::new_unique();
+    let config = GameConfig {
+        entry_fee: ENTRY_FEE,
+        allow_public_bots: true,
+        ..GameConfig::default()
+    };
+    harness
+        .process(&[instruction::initialize_game(&program_id, host, &game, &config)])
+        .expect("initialize game");
+    harness
+        .process(&[instruction::add_bot_players(&program_id, funder, &game, 2, 0)])
+        .expect("add bots");
+
+    let commitment = compute_commitment(&game, 1, host, &[1; 32], &Choice::Rock).expect("valid choice");
+    harness
+        .process(&[instruction::commit_choice(&program_id, host, &game, commitment)])
+        .expect("commit");
+    let act = instruction::bot_act(&program_id, funder, &game);
+    harness.process(std::slice::from_ref(&act)).expect("bots commit");
+    harness.process(&[act]).expect("bots reveal");
+    let phase_nonce = harness.game(&game).expect("game").phase_nonce;
+    harness.warp_seconds(config.timeout_seconds as i64);
+    harness
+        .process(&[instruction::resolve_timeout(&program_id, host, &game, phase_nonce)])
+        .expect("resolve timeout");
+
+    let state = harness.game(&game).expect("game");
+    assert!(matches!(state.state, GameState::Finished));
+    assert_eq!(state.winner_bitmap() & 1, 0);
+    assert_ne!(state.winner_bitmap(), 0);
+    game
+}
+
+#[test]
+fn initiator_pays_for_bot_seats() {
+    let mut harness = Harness::new(Pubkey::new_unique());
+    let program_id = harness.program_id();
+    let host = harness.new_wallet(WALLET_LAMPORTS);
+    let game = Pubkey::new_unique();
+    let treasury = treasury_address(&program_id).0;
+
+    let config = GameConfig {
+        entry_fee: ENTRY_FEE,
+        ..GameConfig::default()
+    };
+    harness
+        .process(&[instruction::initialize_game(&program_id, &host, &game, &config)])
+        .expect("initialize game");
+    let host_before = harness.lamports(&host);
+    let treasury_before = harness.lamports(&treasury);
+
+    harness
+        .process(&[instruction::add_bot_players(&program_id, &host, &game, 2, 0)])
+        .expect("add bots");
+
+    let fee = ENTRY_FEE / 100;
+    let state = harness.game(&game).expect("game account");
+    assert!(matches!(state.state, GameState::CommitPhase));
+    assert_eq!(state.game_pot, 3 * (ENTRY_FEE - fee));
+    assert_eq!(host_before - harness.lamports(&host), 2 * ENTRY_FEE);
+    assert_eq!(harness.lamports(&treasury) - treasury_before, 2 * fee);
+
+    harness
+        .process(&[instruction::audit_game(&program_id, &game)])
+        .expect("audit game");
+    let (_, data) = harness.return_data().expect("return data");
+    assert_eq!(GameAudit::try_from_slice(data).expect("decode audit").discrepancy, 0);
+}
+
+#[test]
+fn broke_initiator_cannot_add_paid_bots() {
+    let mut harness = Harness::new(Pubkey::new_unique());
+    let program_id = harness.program_id();
+    let host = harness.new_wallet(WALLET_LAMPORTS);
+    let game = Pubkey::new_unique();
+
+    let config = GameConfig {
+        entry_fee: ENTRY_FEE,
+        allow_public_bots: true,
+        ..GameConfig::default()
+    };
+    harness
+        .process(&[instruction::initialize_game(&program_id, &host, &game, &config)])
+        .expect("initialize game");
+
+    let broke = harness.new_wallet(ENTRY_FEE / 2);
+    assert!(harness
+        .process(&[instruction::add_bot_players(&program_id, &broke, &game, 1, 0)])
+        .is_err());
+    assert_eq!(harness.game(&game).expect("game account").players.len(), 1);
+}
+
+#[test]
+fn token_bots_are_funded_in_one_transfer() {
+    let mut harness = Harness::new(Pubkey::new_unique());
+    let program_id = harness.program_id();
+    let host = harness.new_wallet(WALLET_LAMPORTS);
+    let game = Pubkey::new_unique();
+
+    let mint = Pubkey::new_unique();
+    harness.set_account(
+        mint,
+        packed(
+            spl_token::state::Mint {
+                mint_authority: COption::None,
+                supply: 10 * ENTRY_FEE,
+                decimals: 9,
+                is_initialized: true,
+                freeze_authority: COption::None,
+            },
+            spl_token::id(),
+        ),
+    );
+    let host_tokens = token_account(&mut harness, mint, host, 10 * ENTRY_FEE);
+    let game_tokens = token_account_at(
+        &mut harness,
+        game_vault_address(&program_id, &game, &mint),
+        mint,
+        game_authority_address(&program_id, &game).0,
+        0,
+    );
+
+    let config = GameConfig {
+        entry_fee: ENTRY_FEE,
+        currency_mode: 1,
+        token_mint: Some(mint),
+        ..GameConfig::default()
+    };
+    harness
+        .process(&[instruction::initialize_game_with_tokens(&program_id, &host, &game, &config, &host_tokens)])
+        .expect("initialize game");
+
+    // Without token accounts there is nothing to fund the bots with
+    let unfunded = harness.process(&[instruction::add_bot_players(&program_id, &host, &game, 2, 0)]);
+    assert_eq!(unfunded.unwrap_err().rps_error(), Some(solana_rps::RPSError::InvalidParameter));
+
+    // The game's token account must be the game vault
+    let stray = token_account(&mut harness, mint, host, 0);
+    let diverted = harness.process(&[instruction::add_bot_players_with_tokens(
+        &program_id, &host, &game, 2, 0, &host_tokens, &stray,
+    )]);
+    assert_eq!(diverted.unwrap_err().rps_error(), Some(solana_rps::RPSError::InvalidTokenAccount));
+
+    harness
+        .process(&[instruction::add_bot_players_with_tokens(
+            &program_id, &host, &game, 2, 0, &host_tokens, &game_tokens,
+        )])
+        .expect("add funded bots");
+
+    let fee = ENTRY_FEE / 100;
+    assert_eq!(token_balance(&harness, &game_tokens), 3 * ENTRY_FEE);
+    assert_eq!(token_balance(&harness, &host_tokens), 7 * ENTRY_FEE);
+    let state = harness.game(&game).expect("game account");
+    assert!(matches!(state.state, GameState::CommitPhase));
+    assert_eq!(state.fee_collected, 3 * fee);
+}
+
+#[test]
+fn only_the_host_adds_bots_unless_public() {
+    let mut harness = Harness::new(Pubkey::new_unique());
+    let program_id = harness.program_id();
+    let host = harness.new_wallet(WALLET_LAMPORTS);
+    let stranger = harness.new_wallet(WALLET_LAMPORTS);
+
+    let private = Pubkey::new_unique();
+    harness
+        .process(&[instruction::initialize_game(&program_id, &host, &private, &GameConfig::default())])
+        .expect("initialize private game");
+    let hijack = harness.process(&[instruction::add_bot_players(&program_id, &stranger, &private, 2, 0)]);
+    assert_eq!(hijack.unwrap_err().rps_error(), Some(solana_rps::RPSError::NotAuthorized));
+    harness
+        .process(&[instruction::add_bot_players(&program_id, &host, &private, 1, 0)])
+        .expect("host adds bot");
+
+    let public = Pubkey::new_unique();
+    let config = GameConfig {
+        allow_public_bots: true,
+        ..GameConfig::default()
+    };
+    harness
+        .process(&[instruction::initialize_game(&program_id, &host, &public, &config)])
+        .expect("initialize public game");
+    harness
+        .process(&[instruction::add_bot_players(&program_id, &stranger, &public, 1, 0)])
+        .expect("anyone adds bot");
+}
+
+#[test]
+fn bot_winnings_go_to_their_funder() {
+    let mut harness = Harness::new(Pubkey::new_unique());
+    let program_id = harness.program_id();
+    let host = harness.new_wallet(WALLET_LAMPORTS);
+    let funder = harness.new_wallet(WALLET_LAMPORTS);
+    let game = lost_to_bots(&mut harness, &host, &funder);
+    let state = harness.game(&game).expect("game");
+    let winners = state.winner_bitmap().count_ones();
+    assert!((1..3).all(|seat| state.payee(seat) == funder));
+    assert_eq!(state.payee(0), host);
+
+    // The funder claims for each winning bot seat in turn
+    let pot = state.game_pot;
+    let before = harness.lamports(&funder);
+    for _ in 0..winners {
+        harness
+            .process(&[instruction::claim_winnings(&program_id, &funder, &game)])
+            .expect("claim for a bot seat");
+    }
+    assert_eq!(harness.lamports(&funder) - before, pot);
+    let again = harness.process(&[instruction::claim_winnings(&program_id, &funder, &game)]);
+    assert!(matches!(again.unwrap_err().rps_error(), Some(RPSError::AlreadyClaimed | RPSError::NotWinner)));
+
+    // With the pot paid out the game can be closed
+    harness
+        .process(&[instruction::record_game_result(&program_id, &host, &game)])
+        .expect("record result");
+    harness
+        .process(&[instruction::close_game(&program_id, &host, &game, &host, None)])
+        .expect("close game");
+    assert!(harness.account(&game).is_none());
+}
+
+#[test]
+fn distributed_bot_winnings_go_to_their_funder() {
+    let mut harness = Harness::new(Pubkey::new_unique());
+    let program_id = harness.program_id();
+    let host = harness.new_wallet(WALLET_LAMPORTS);
+    let funder = harness.new_wallet(WALLET_LAMPORTS);
+    let game = lost_to_bots(&mut harness, &host, &funder);
+    let state = harness.game(&game).expect("game");
+    let seats: Vec<Pubkey> = (0..3)
+        .filter(|seat| state.winner_bitmap() & (1 << seat) != 0)
+        .map(|seat| state.players[seat].pubkey)
+        .collect();
+
+    // The bot seats' own keys cannot be paid
+    let keyless = instruction::distribute_winnings(&program_id, &host, &game, &seats);
+    assert_eq!(harness.process(&[keyless]).unwrap_err().rps_error(), Some(RPSError::InvalidParameter));
+
+    let before = harness.lamports(&funder);
+    let payees = vec![funder; seats.len()];
+    harness
+        .process(&[instruction::distribute_winnings(&program_id, &host, &game, &payees)])
+        .expect("distribute winnings");
+    assert_eq!(harness.lamports(&funder) - before, state.game_pot);
+    assert!(harness.game(&game).expect("game").all_claimed());
+}
+
+#[test]
+fn cancelled_bot_seats_are_refunded_to_their_funder() {
+    let mut harness = Harness::new(Pubkey::new_unique());
+    let program_id = harness.program_id();
+    let host = harness.new_wallet(WALLET_LAMPORTS);
+    let funder = harness.new_wallet(WALLET_LAMPORTS);
+    let game = Pubkey::new_unique();
+    let config = GameConfig {
+        min_players: 4,
+        max_players: 4,
+        entry_fee: ENTRY_FEE,
+        allow_public_bots: true,
+        ..GameConfig::default()
+    };
+    harness
+        .process(&[instruction::initialize_game(&program_id, &host, &game, &config)])
+        .expect("initialize game");
+    harness
+        .process(&[instruction::add_bot_players(&program_id, &funder, &game, 2, 0)])
+        .expect("add bots");
+
+    let phase_nonce = harness.game(&game).expect("game").phase_nonce;
+    harness.warp_seconds(config.timeout_seconds as i64);
+    harness
+        .process(&[instruction::resolve_timeout(&program_id, &host, &game, phase_nonce)])
+        .expect("resolve timeout");
+    let state = harness.game(&game).expect("game");
+    assert!(state.cancelled);
+    let share = state.pot_snapshot / 3;
+
+    // Each bot seat is refunded to the funder, once
+    let before = harness.lamports(&funder);
+    for _ in 0..2 {
+        harness
+            .process(&[instruction::refund_entry(&program_id, &host, &game, &funder)])
+            .expect("refund a bot seat");
+    }
+    assert_eq!(harness.lamports(&funder) - before, 2 * share);
+    let again = harness.process(&[instruction::refund_entry(&program_id, &host, &game, &funder)]);
+    assert_eq!(again.unwrap_err().rps_error(), Some(RPSError::AlreadyClaimed));
+
+    harness
+        .process(&[instruction::refund_entry(&program_id, &host, &game, &host)])
+        .expect("refund the host");
+    assert_eq!(harness.game(&game).expect("game").game_pot, 0);
+}
@@ -0,0 +1,159 @@
+//! Bots decide every move from the game account: played step by step
+//! against the program, their plan carries a lobby from joining to claims.
+
+use std::collections::HashMap;
+
+use rps_bot::{hands, next_step, StakeLimits, Step, Strategy, Uniform};
+use rps_client::{
+    instruction::{self, GameConfig},
+    Commitment,
+};
+use rps_fixtures::harness::Harness;
+use rps_fixtures::setup::{open_lobby, WALLET_LAMPORTS};
+use rps_state::simulate::Hand;
+use solana_rps::{Choice, Game, GameState, RuleSet};
+use solana_sdk::pubkey::Pubkey;
+
+const ENTRY_FEE: u64 = 100_000_000;
+const TIMEOUT_SECS: u64 = 300;
+
+const LIMITS: StakeLimits = StakeLimits {
+    min_entry_fee: 0,
+    max_entry_fee: 2 * ENTRY_FEE,
+};
+
+fn lobby_config() -> GameConfig {
+    GameConfig {
+        entry_fee: ENTRY_FEE,
+        timeout_seconds: TIMEOUT_SECS,
+        ..GameConfig::default()
+    }
+}
+
+// Takes `player`'s next step the way a bot would and returns it
+fn act(
+    harness: &mut Harness,
+    game: &Pubkey,
+    player: &Pubkey,
+    strategy: &mut dyn Strategy,
+    pending: &mut HashMap<Pubkey, Commitment>,
+) -> Step {
+    let program_id = harness.program_id();
+    let state: Game = harness.game(game).expect("game");
+    let step = next_step(&state, player, harness.clock().unix_timestamp as u64);
+    let ix = match step {
+        Step::Join => instruction::with_slot_hashes(instruction::join_game(&program_id, player, game)),
+        Step::Commit => {
+            let seat = state.players.iter().position(|p| p.pubkey == *player).expect("seated");
+            let choice = strategy.choose(game, &state, seat);
+            let commitment = Commitment::for_game(choice, game, &state, player).expect("valid choice");
+            let ix = commitment.commit(&program_id);
+            pending.insert(*player, commitment);
+            ix
+        }
+        Step::Reveal => pending[player].reveal(&program_id),
+        Step::Claim => instruction::claim_winnings(&program_id, player, game),
+        Step::Refund => instruction::refund_entry(&program_id, player, game, player),
+        Step::ResolveTimeout { phase_nonce } => instruction::resolve_timeout(&program_id, player, game, phase_nonce),
+        Step::Wait | Step::Done => return step,
+    };
+    harness.process(&[ix]).expect("bot step");
+    step
+}
+
+#[test]
+fn bots_play_a_lobby_through_to_the_claims() {
+    let mut harness = Harness::new(Pubkey::new_unique());
+    let host = harness.new_wallet(WALLET_LAMPORTS);
+    let bots: Vec<Pubkey> = (0..2).map(|_| harness.new_wallet(WALLET_LAMPORTS)).collect();
+    let game = open_lobby(&mut harness, &[host], &lobby_config());
+
+    let state = harness.game(&game).expect("game");
+    assert!(LIMITS.accepts(&state));
+    let rich = StakeLimits {
+        min_entry_fee: 2 * ENTRY_FEE,
+        max_entry_fee: u64::MAX,
+    };
+    assert!(!rich.accepts(&state));
+
+    let mut pending = HashMap::new();
+    let mut uniform = Uniform;
+    let mut host_hand = |_: &Pubkey, _: &Game, _: usize| Choice::Rock;
+    assert_eq!(act(&mut harness, &game, &bots[0], &mut uniform, &mut pending), Step::Join);
+    assert_eq!(act(&mut harness, &game, &bots[0], &mut uniform, &mut pending), Step::Wait);
+    assert_eq!(act(&mut harness, &game, &bots[1], &mut uniform, &mut pending), Step::Join);
+    assert!(!LIMITS.accepts(&harness.game(&game).expect("game")), "a full lobby is not open");
+
+    for step in [Step::Commit, Step::Reveal] {
+        assert_eq!(act(&mut harness, &game, &host, &mut host_hand, &mut pending), step);
+        for bot in &bots {
+            assert_eq!(act(&mut harness, &game, bot, &mut uniform, &mut pending), step);
+        }
+    }
+    let state = harness.game(&game).expect("game");
+    assert!(matches!(state.state, GameState::Finished));
+
+    for (seat, player) in [host, bots[0], bots[1]].iter().enumerate() {
+        let strategy: &mut dyn Strategy = if seat == 0 { &mut host_hand } else { &mut uniform };
+        let won = state.winner_bitmap() & (1 << seat) != 0;
+        let expected = if won { Step::Claim } else { Step::Done };
+        assert_eq!(act(&mut harness, &game, player, strategy, &mut pending), expected);
+        assert_eq!(act(&mut harness, &game, player, strategy, &mut pending), Step::Done);
+    }
+    assert!(harness.game(&game).expect("game").all_claimed());
+}
+
+#[test]
+fn bots_crank_the_timeout_on_a_stalled_seat() {
+    let mut harness = Harness::new(Pubkey::new_unique());
+    let host = harness.new_wallet(WALLET_LAMPORTS);
+    let bots: Vec<Pubkey> = (0..2).map(|_| harness.new_wallet(WALLET_LAMPORTS)).collect();
+    let game = open_lobby(&mut harness, &[host], &lobby_config());
+    let mut pending = HashMap::new();
+    let mut uniform = Uniform;
+    for bot in &bots {
+        assert_eq!(act(&mut harness, &game, bot, &mut uniform, &mut pending), Step::Join);
+    }
+
+    // The host never commits
+    for bot in &bots {
+        assert_eq!(act(&mut harness, &game, bot, &mut uniform, &mut pending), Step::Commit);
+    }
+    assert_eq!(act(&mut harness, &game, &bots[0], &mut uniform, &mut pending), Step::Wait);
+    harness.warp_seconds(TIMEOUT_SECS as i64);
+    let phase_nonce = harness.game(&game).expect("game").phase_nonce;
+    assert_eq!(
+        act(&mut harness, &game, &bots[0], &mut uniform, &mut pending),
+        Step::ResolveTimeout { phase_nonce }
+    );
+    assert_ne!(harness.game(&game).expect("game").phase_nonce, phase_nonce);
+}
+
+#[test]
+fn uniform_throws_every_allowed_hand_and_nothing_else() {
+    let mut harness = Harness::new(Pubkey::new_unique());
+    let program_id = harness.program_id();
+    let host = harness.new_wallet(WALLET_LAMPORTS);
+    let game = Pubkey::new_unique();
+    let config = GameConfig {
+        rule_set: 1,
+        ..GameConfig::default()
+    };
+    harness
+        .process(&[instruction::initialize_game(&program_id, &host, &game, &config)])
+        .expect("initialize game");
+    let state = harness.game(&game).expect("game");
+    assert_eq!(state.rule_set, RuleSet::LizardSpock);
+
+    let allowed: Vec<Hand> = hands(state.rule_set).iter().filter_map(Choice::hand).collect();
+    assert_eq!(allowed.len(), 5);
+    let mut seen: Vec<Hand> = Vec::new();
+    for _ in 0..200 {
+        let hand = Uniform.choose(&game, &state, 0).hand().expect("a hand");
+        assert!(allowed.contains(&hand));
+        if !seen.contains(&hand) {
+            seen.push(hand);
+        }
+    }
+    assert_eq!(seen.len(), allowed.len());
+}
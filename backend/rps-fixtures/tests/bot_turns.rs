@@ -0,0 +1,156 @@
+//! Bot turns: bot seats have no key to commit or reveal with, so `BotAct`
+//! plays them. Each hand is drawn as the bot's strategy directs when the
+//! commit phase closes, so the slot the reveal lands in makes no difference.
+
+use rps_client::instruction::{self, GameConfig};
+use rps_fixtures::harness::Harness;
+use rps_fixtures::setup::WALLET_LAMPORTS;
+use solana_rps::{compute_commitment, BotStrategy, Choice, GameState, RPSError};
+use solana_sdk::pubkey::Pubkey;
+
+const SALT: [u8; 32] = [7; 32];
+
+// A host sitting with two bots
+fn bot_table(harness: &mut Harness, total_rounds: u8, strategy: BotStrategy) -> (Pubkey, Pubkey) {
+    let program_id = harness.program_id();
+    let host = harness.new_wallet(WALLET_LAMPORTS);
+    let game = Pubkey::new_unique();
+    let config = GameConfig {
+        total_rounds,
+        ..GameConfig::default()
+    };
+    harness
+        .process(&[instruction::initialize_game(&program_id, &host, &game, &config)])
+        .expect("initialize game");
+    harness
+        .process(&[instruction::add_bot_players(&program_id, &host, &game, 2, strategy as u8)])
+        .expect("add bots");
+    (host, game)
+}
+
+fn commit(harness: &mut Harness, host: &Pubkey, game: &Pubkey) {
+    let program_id = harness.program_id();
+    let round = harness.game(game).expect("game").current_round;
+    let commitment = compute_commitment(game, round, host, &SALT, &Choice::Rock).expect("valid choice");
+    harness
+        .process(&[instruction::commit_choice(&program_id, host, game, commitment)])
+        .expect("commit");
+}
+
+fn reveal(harness: &mut Harness, host: &Pubkey, game: &Pubkey) {
+    let program_id = harness.program_id();
+    harness
+        .process(&[instruction::reveal_choice(&program_id, host, game, Choice::Rock, SALT)])
+        .expect("reveal");
+}
+
+#[test]
+fn bots_commit_and_reveal_through_bot_act() {
+    let mut harness = Harness::new(Pubkey::new_unique());
+    let program_id = harness.program_id();
+    let keeper = harness.new_wallet(WALLET_LAMPORTS);
+    let (host, game) = bot_table(&mut harness, 2, BotStrategy::Random);
+    assert_eq!(harness.game(&game).expect("game").bot_config.len(), 2);
+    let act = instruction::bot_act(&program_id, &keeper, &game);
+
+    // The host commits first; the bots' commits close the phase
+    commit(&mut harness, &host, &game);
+    harness.process(std::slice::from_ref(&act)).expect("bots commit");
+    let state = harness.game(&game).expect("game");
+    assert!(matches!(state.state, GameState::RevealPhase));
+    let drawn: Vec<Choice> = state.players[1..].iter().map(|p| p.choice.clone()).collect();
+    assert!(drawn.iter().all(|choice| choice.hand().is_some()));
+
+    // Bots reveal without waiting for the host, and only once; a later slot
+    // reveals the same hands
+    harness.warp_seconds(10);
+    harness.process(std::slice::from_ref(&act)).expect("bots reveal");
+    let state = harness.game(&game).expect("game");
+    for (player, choice) in state.players[1..].iter().zip(&drawn) {
+        assert!(player.revealed);
+        assert_eq!(player.choice.hand(), choice.hand());
+    }
+    let idle = harness.process(std::slice::from_ref(&act)).unwrap_err();
+    assert_eq!(idle.rps_error(), Some(RPSError::InvalidGameState));
+    reveal(&mut harness, &host, &game);
+    let state = harness.game(&game).expect("game");
+    assert!(matches!(state.state, GameState::CommitPhase));
+    assert_eq!(state.current_round, 2);
+
+    // Bots may also go first, and their reveal can end the game
+    harness.process(std::slice::from_ref(&act)).expect("bots commit");
+    commit(&mut harness, &host, &game);
+    reveal(&mut harness, &host, &game);
+    harness.process(&[act]).expect("bots reveal");
+    assert!(matches!(harness.game(&game).expect("game").state, GameState::Finished));
+}
+
+#[test]
+fn bot_act_needs_bots_and_the_slot_hashes() {
+    let mut harness = Harness::new(Pubkey::new_unique());
+    let program_id = harness.program_id();
+    let players: Vec<Pubkey> = (0..3).map(|_| harness.new_wallet(WALLET_LAMPORTS)).collect();
+
+    // A table of people has nothing for it to do
+    let game = Pubkey::new_unique();
+    harness
+        .process(&[instruction::initialize_game(&program_id, &players[0], &game, &GameConfig::default())])
+        .expect("initialize game");
+    for player in &players[1..] {
+        harness
+            .process(&[instruction::join_game(&program_id, player, &game)])
+            .expect("join game");
+    }
+    let humans = harness.process(&[instruction::bot_act(&program_id, &players[0], &game)]);
+    assert_eq!(humans.unwrap_err().rps_error(), Some(RPSError::InvalidGameState));
+
+    let (_, game) = bot_table(&mut harness, 1, BotStrategy::Random);
+    let mut blind = instruction::bot_act(&program_id, &players[0], &game);
+    blind.accounts.pop();
+    assert_eq!(harness.process(&[blind]).unwrap_err().rps_error(), Some(RPSError::InvalidParameter));
+}
+
+#[test]
+fn countering_bots_beat_the_last_round_favourite() {
+    let mut harness = Harness::new(Pubkey::new_unique());
+    let program_id = harness.program_id();
+    let keeper = harness.new_wallet(WALLET_LAMPORTS);
+    let (host, game) = bot_table(&mut harness, 2, BotStrategy::CounterLastMove);
+    let state = harness.game(&game).expect("game");
+    assert!(state.bot_config.iter().all(|bot| bot.strategy == BotStrategy::CounterLastMove));
+    let act = instruction::bot_act(&program_id, &keeper, &game);
+
+    // Round one has nothing to counter. After it, rock is what each bot
+    // saw most: the host's, tied with or matched by the other bot's.
+    for _ in 0..2 {
+        commit(&mut harness, &host, &game);
+        harness.process(std::slice::from_ref(&act)).expect("bots commit");
+        reveal(&mut harness, &host, &game);
+        harness.process(std::slice::from_ref(&act)).expect("bots reveal");
+    }
+    let state = harness.game(&game).expect("game");
+    assert!(matches!(state.state, GameState::Finished));
+    for player in &state.players[1..] {
+        assert!(matches!(player.choice, Choice::Paper));
+    }
+}
+
+#[test]
+fn unknown_strategies_are_refused() {
+    let mut harness = Harness::new(Pubkey::new_unique());
+    let program_id = harness.program_id();
+    let host = harness.new_wallet(WALLET_LAMPORTS);
+    let game = Pubkey::new_unique();
+    harness
+        .process(&[instruction::initialize_game(&program_id, &host, &game, &GameConfig::default())])
+        .expect("initialize game");
+    let refused = harness.process(&[instruction::add_bot_players(&program_id, &host, &game, 1, 3)]);
+    assert_eq!(refused.unwrap_err().rps_error(), Some(RPSError::InvalidParameter));
+
+    harness
+        .process(&[instruction::add_bot_players(&program_id, &host, &game, 1, BotStrategy::Weighted as u8)])
+        .expect("add bot");
+    let state = harness.game(&game).expect("game");
+    assert_eq!(state.bot_config[0].pubkey, state.players[1].pubkey);
+    assert_eq!(state.bot_config[0].strategy, BotStrategy::Weighted);
+}
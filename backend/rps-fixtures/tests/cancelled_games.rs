@@ -0,0 +1,216 @@
+//! Cancelled games: a lobby or commit phase that times out before the game
+//! can be played finishes with no winners, and `RefundEntry` hands every
+//! seat its entry back from the pot.
+
+use rps_client::instruction::{self, GameConfig};
+use rps_fixtures::harness::Harness;
+use rps_fixtures::setup::{open_lobby, packed, token_account_at, token_balance, WALLET_LAMPORTS};
+use solana_rps::{
+    compute_commitment, game_authority_address, game_vault_address, treasury_address, Choice, GameState, RPSError,
+};
+use solana_sdk::{account::Account, program_option::COption, pubkey::Pubkey, system_program};
+
+const ENTRY_FEE: u64 = 100_000_000;
+const TIMEOUT_SECS: u64 = 300;
+
+fn sol_config() -> GameConfig {
+    GameConfig {
+        entry_fee: ENTRY_FEE,
+        timeout_seconds: TIMEOUT_SECS,
+        ..GameConfig::default()
+    }
+}
+
+fn time_out(harness: &mut Harness, caller: &Pubkey, game: &Pubkey) {
+    let program_id = harness.program_id();
+    let phase_nonce = harness.game(game).expect("game").phase_nonce;
+    harness.warp_seconds(TIMEOUT_SECS as i64);
+    harness
+        .process(&[instruction::resolve_timeout(&program_id, caller, game, phase_nonce)])
+        .expect("resolve timeout");
+}
+
+#[test]
+fn a_lobby_that_never_fills_refunds_every_seat() {
+    let mut harness = Harness::new(Pubkey::new_unique());
+    let program_id = harness.program_id();
+    let players: Vec<Pubkey> = (0..2).map(|_| harness.new_wallet(WALLET_LAMPORTS)).collect();
+    let cranker = harness.new_wallet(WALLET_LAMPORTS);
+    let game = open_lobby(&mut harness, &players, &sol_config());
+
+    time_out(&mut harness, &cranker, &game);
+    let state = harness.game(&game).expect("game");
+    assert!(matches!(state.state, GameState::Finished));
+    assert!(state.cancelled);
+    assert_eq!(state.winner_bitmap(), 0);
+    let share = state.pot_snapshot / 2;
+
+    // Nobody won, so there is nothing to claim, and only seats are refunded
+    let claim = instruction::claim_winnings(&program_id, &players[0], &game);
+    assert_eq!(harness.process(&[claim]).unwrap_err().rps_error(), Some(RPSError::NotWinner));
+    let stranger = harness.new_wallet(WALLET_LAMPORTS);
+    let outsider = instruction::refund_entry(&program_id, &cranker, &game, &stranger);
+    assert_eq!(harness.process(&[outsider]).unwrap_err().rps_error(), Some(RPSError::PlayerNotFound));
+
+    let before: Vec<u64> = players.iter().map(|p| harness.lamports(p)).collect();
+    for player in &players {
+        harness
+            .process(&[instruction::refund_entry(&program_id, &cranker, &game, player)])
+            .expect("refund entry");
+    }
+    let refunds: Vec<u64> = players.iter().zip(&before).map(|(p, b)| harness.lamports(p) - b).collect();
+    assert_eq!(refunds, vec![share; 2]);
+    assert_eq!(harness.game(&game).expect("game").game_pot, 0);
+
+    let again = instruction::refund_entry(&program_id, &cranker, &game, &players[0]);
+    assert_eq!(harness.process(&[again]).unwrap_err().rps_error(), Some(RPSError::AlreadyClaimed));
+}
+
+#[test]
+fn a_commit_phase_without_quorum_is_cancelled() {
+    let mut harness = Harness::new(Pubkey::new_unique());
+    let program_id = harness.program_id();
+    let players: Vec<Pubkey> = (0..3).map(|_| harness.new_wallet(WALLET_LAMPORTS)).collect();
+    let cranker = harness.new_wallet(WALLET_LAMPORTS);
+    let game = open_lobby(&mut harness, &players, &sol_config());
+
+    let commitment = compute_commitment(&game, 1, &players[0], &[1; 32], &Choice::Rock).expect("valid choice");
+    harness
+        .process(&[instruction::commit_choice(&program_id, &players[0], &game, commitment)])
+        .expect("commit");
+    let early = instruction::refund_entry(&program_id, &cranker, &game, &players[0]);
+    assert_eq!(harness.process(&[early]).unwrap_err().rps_error(), Some(RPSError::InvalidGameState));
+
+    // One commitment of the three needed
+    time_out(&mut harness, &cranker, &game);
+    let state = harness.game(&game).expect("game");
+    assert!(state.cancelled);
+    assert_eq!(state.players.len(), 3);
+    let distribute = instruction::distribute_winnings(&program_id, &cranker, &game, &players);
+    assert_eq!(harness.process(&[distribute]).unwrap_err().rps_error(), Some(RPSError::InvalidGameState));
+
+    let before: Vec<u64> = players.iter().map(|p| harness.lamports(p)).collect();
+    for player in &players {
+        harness
+            .process(&[instruction::refund_entry(&program_id, &cranker, &game, player)])
+            .expect("refund entry");
+    }
+    let refunds: Vec<u64> = players.iter().zip(&before).map(|(p, b)| harness.lamports(p) - b).collect();
+    assert_eq!(refunds, vec![state.pot_snapshot / 3; 3]);
+    assert_eq!(harness.game(&game).expect("game").game_pot, 0);
+}
+
+#[test]
+fn a_pot_subsidy_goes_back_to_the_treasury_not_the_seats() {
+    let mut harness = Harness::new(Pubkey::new_unique());
+    let program_id = harness.program_id();
+    let operator = harness.new_wallet(WALLET_LAMPORTS);
+    let players: Vec<Pubkey> = (0..3).map(|_| harness.new_wallet(WALLET_LAMPORTS)).collect();
+    let cranker = harness.new_wallet(WALLET_LAMPORTS);
+    let treasury = treasury_address(&program_id).0;
+    let min_pot = 10 * ENTRY_FEE;
+
+    harness
+        .process(&[instruction::initialize_config(&program_id, &operator)])
+        .expect("initialize config");
+    harness
+        .process(&[instruction::set_pot_guarantee(&program_id, &operator, min_pot, ENTRY_FEE)])
+        .expect("set pot guarantee");
+    harness.set_account(treasury, Account::new(WALLET_LAMPORTS, 0, &system_program::id()));
+    let game = open_lobby(&mut harness, &players, &sol_config());
+    let entries = harness.game(&game).expect("game").escrows;
+
+    let commitment = compute_commitment(&game, 1, &players[0], &[1; 32], &Choice::Rock).expect("valid choice");
+    harness
+        .process(&[instruction::commit_choice(&program_id, &players[0], &game, commitment)])
+        .expect("commit");
+    harness
+        .process(&[instruction::apply_pot_subsidy(&program_id, &cranker, &game)])
+        .expect("apply pot subsidy");
+    let subsidy = harness.game(&game).expect("game").pot_subsidy;
+    assert!(subsidy > 0);
+    time_out(&mut harness, &cranker, &game);
+    assert!(harness.game(&game).expect("game").cancelled);
+
+    // Each seat gets back what it paid in, and the top-up returns to the
+    // treasury with the last refund
+    let treasury_before = harness.lamports(&treasury);
+    let before: Vec<u64> = players.iter().map(|p| harness.lamports(p)).collect();
+    for player in &players {
+        harness
+            .process(&[instruction::refund_entry(&program_id, &cranker, &game, player)])
+            .expect("refund entry");
+    }
+    let refunds: Vec<u64> = players.iter().zip(&before).map(|(p, b)| harness.lamports(p) - b).collect();
+    let paid_in: Vec<u64> = players
+        .iter()
+        .map(|p| entries.iter().find(|e| e.player == *p).expect("escrow").amount)
+        .collect();
+    assert_eq!(refunds, paid_in);
+    assert_eq!(harness.lamports(&treasury) - treasury_before, subsidy);
+    assert_eq!(harness.game(&game).expect("game").game_pot, 0);
+}
+
+#[test]
+fn token_entries_are_refunded_from_the_game_vault() {
+    let mut harness = Harness::new(Pubkey::new_unique());
+    let program_id = harness.program_id();
+    let players: Vec<Pubkey> = (0..2).map(|_| harness.new_wallet(WALLET_LAMPORTS)).collect();
+    let cranker = harness.new_wallet(WALLET_LAMPORTS);
+    let game = Pubkey::new_unique();
+
+    let mint = Pubkey::new_unique();
+    harness.set_account(
+        mint,
+        packed(
+            spl_token::state::Mint {
+                mint_authority: COption::None,
+                supply: 20 * ENTRY_FEE,
+                decimals: 9,
+                is_initialized: true,
+                freeze_authority: COption::None,
+            },
+            spl_token::id(),
+        ),
+    );
+    let wallets: Vec<Pubkey> = players
+        .iter()
+        .map(|p| token_account_at(&mut harness, Pubkey::new_unique(), mint, *p, 10 * ENTRY_FEE))
+        .collect();
+    let vault = token_account_at(
+        &mut harness,
+        game_vault_address(&program_id, &game, &mint),
+        mint,
+        game_authority_address(&program_id, &game).0,
+        0,
+    );
+
+    let config = GameConfig {
+        currency_mode: 1,
+        token_mint: Some(mint),
+        ..sol_config()
+    };
+    harness
+        .process(&[instruction::initialize_game_with_tokens(&program_id, &players[0], &game, &config, &wallets[0])])
+        .expect("initialize game");
+    harness
+        .process(&[instruction::join_game_with_tokens(&program_id, &players[1], &game, &mint, &wallets[1])])
+        .expect("join game");
+    time_out(&mut harness, &cranker, &game);
+    let state = harness.game(&game).expect("game");
+    assert!(state.cancelled);
+
+    // The entry goes back to the seat's own token account only
+    let diverted = instruction::refund_entry_with_tokens(&program_id, &cranker, &game, &players[0], &mint, &wallets[1]);
+    assert_eq!(harness.process(&[diverted]).unwrap_err().rps_error(), Some(RPSError::InvalidTokenAccount));
+
+    for (player, tokens) in players.iter().zip(&wallets) {
+        harness
+            .process(&[instruction::refund_entry_with_tokens(&program_id, &cranker, &game, player, &mint, tokens)])
+            .expect("refund entry");
+    }
+    for tokens in &wallets {
+        assert_eq!(token_balance(&harness, tokens), 9 * ENTRY_FEE + state.pot_snapshot / 2);
+    }
+    assert_eq!(token_balance(&harness, &vault), state.fee_collected);
+}
@@ -0,0 +1,239 @@
+//! Call-out challenges: a staked one-throw match that only goes live once
+//! the named opponent accepts, refunded if they never do. The loser of a
+//! decided challenge can offer the winner double or nothing.
+
+use rps_client::instruction;
+use rps_fixtures::harness::Harness;
+use rps_fixtures::setup::WALLET_LAMPORTS;
+use solana_rps::{compute_commitment, ChallengeState, Choice, RPSError};
+use solana_sdk::pubkey::Pubkey;
+
+const STAKE: u64 = 1_000_000_000;
+const DOUBLE_OR_NOTHING_SECS: i64 = 10 * 60;
+
+struct Duel {
+    harness: Harness,
+    challenge: Pubkey,
+    challenger: Pubkey,
+    opponent: Pubkey,
+}
+
+impl Duel {
+    fn create() -> Self {
+        let mut harness = Harness::new(Pubkey::new_unique());
+        let program_id = harness.program_id();
+        let challenger = harness.new_wallet(WALLET_LAMPORTS);
+        let opponent = harness.new_wallet(WALLET_LAMPORTS);
+        let challenge = Pubkey::new_unique();
+        harness
+            .process(&[instruction::create_challenge(&program_id, &challenger, &challenge, &opponent, STAKE, None)])
+            .expect("create challenge");
+        Self {
+            harness,
+            challenge,
+            challenger,
+            opponent,
+        }
+    }
+
+    fn accept(&mut self) {
+        let program_id = self.harness.program_id();
+        self.harness
+            .process(&[instruction::accept_challenge(&program_id, &self.opponent, &self.challenge, None)])
+            .expect("accept challenge");
+    }
+
+    fn commit(&mut self, player: Pubkey, choice: &Choice) {
+        let program_id = self.harness.program_id();
+        let commitment =
+            compute_commitment(&self.challenge, 1, &player, &salt_of(&player), choice).expect("valid choice");
+        self.harness
+            .process(&[instruction::commit_challenge_choice(&program_id, &player, &self.challenge, commitment)])
+            .expect("commit");
+    }
+
+    fn reveal(&mut self, player: Pubkey, choice: Choice) {
+        let program_id = self.harness.program_id();
+        self.harness
+            .process(&[instruction::reveal_challenge_choice(
+                &program_id,
+                &player,
+                &self.challenge,
+                &self.challenger,
+                &self.opponent,
+                choice,
+                salt_of(&player),
+            )])
+            .expect("reveal");
+    }
+
+    fn expire(&mut self) -> Result<(), Option<RPSError>> {
+        let program_id = self.harness.program_id();
+        let caller = self.harness.new_wallet(WALLET_LAMPORTS);
+        self.harness
+            .process(&[instruction::expire_challenge(
+                &program_id,
+                &caller,
+                &self.challenge,
+                &self.challenger,
+                &self.opponent,
+            )])
+            .map_err(|failure| failure.rps_error())
+    }
+
+    // Plays an accepted challenge out: the challenger's paper beats rock
+    fn play_to_a_win(&mut self) {
+        let (challenger, opponent) = (self.challenger, self.opponent);
+        self.commit(challenger, &Choice::Paper);
+        self.commit(opponent, &Choice::Rock);
+        self.reveal(opponent, Choice::Rock);
+        self.reveal(challenger, Choice::Paper);
+    }
+
+    fn offer_double_or_nothing(&mut self, loser: Pubkey) -> Result<(), Option<RPSError>> {
+        let program_id = self.harness.program_id();
+        self.harness
+            .process(&[instruction::offer_double_or_nothing(&program_id, &loser, &self.challenge, None)])
+            .map_err(|failure| failure.rps_error())
+    }
+}
+
+fn salt_of(player: &Pubkey) -> [u8; 32] {
+    player.to_bytes()
+}
+
+#[test]
+fn accepted_challenge_pays_the_winner_and_closes() {
+    let mut duel = Duel::create();
+    let program_id = duel.harness.program_id();
+
+    let stranger = duel.harness.new_wallet(WALLET_LAMPORTS);
+    let hijack = duel
+        .harness
+        .process(&[instruction::accept_challenge(&program_id, &stranger, &duel.challenge, None)]);
+    assert_eq!(hijack.unwrap_err().rps_error(), Some(RPSError::NotAuthorized));
+
+    duel.accept();
+    let (challenger, opponent) = (duel.challenger, duel.opponent);
+    let challenger_before = duel.harness.lamports(&challenger);
+    let opponent_before = duel.harness.lamports(&opponent);
+    let escrow = duel.harness.lamports(&duel.challenge);
+
+    duel.commit(challenger, &Choice::Paper);
+    duel.commit(opponent, &Choice::Rock);
+    duel.reveal(opponent, Choice::Rock);
+    assert_eq!(duel.expire(), Err(Some(RPSError::TimeoutNotReached)));
+    duel.reveal(challenger, Choice::Paper);
+    assert_eq!(duel.harness.lamports(&challenger), challenger_before + 2 * STAKE);
+
+    // The settled account closes once the double-or-nothing window passes
+    assert_eq!(duel.expire(), Err(Some(RPSError::TimeoutNotReached)));
+    duel.harness.warp_seconds(DOUBLE_OR_NOTHING_SECS + 1);
+    duel.expire().expect("close settled challenge");
+
+    // Winner takes both stakes; the rent comes back to the challenger
+    assert!(duel.harness.account(&duel.challenge).is_none());
+    assert_eq!(duel.harness.lamports(&challenger), challenger_before + escrow);
+    assert_eq!(duel.harness.lamports(&opponent), opponent_before);
+}
+
+#[test]
+fn unaccepted_challenge_refunds_after_the_deadline() {
+    let mut duel = Duel::create();
+    assert_eq!(duel.expire(), Err(Some(RPSError::TimeoutNotReached)));
+
+    duel.harness.warp_seconds(24 * 60 * 60 + 1);
+    let program_id = duel.harness.program_id();
+    let late = duel
+        .harness
+        .process(&[instruction::accept_challenge(&program_id, &duel.opponent, &duel.challenge, None)]);
+    assert_eq!(late.unwrap_err().rps_error(), Some(RPSError::InvalidGameState));
+
+    duel.expire().expect("expire challenge");
+    assert!(duel.harness.account(&duel.challenge).is_none());
+    assert_eq!(duel.harness.lamports(&duel.challenger), WALLET_LAMPORTS);
+    assert_eq!(duel.harness.lamports(&duel.opponent), WALLET_LAMPORTS);
+}
+
+#[test]
+fn revealing_side_takes_the_pot_from_a_no_show() {
+    let mut duel = Duel::create();
+    duel.accept();
+    let (challenger, opponent) = (duel.challenger, duel.opponent);
+    duel.commit(challenger, &Choice::Scissors);
+    duel.commit(opponent, &Choice::Rock);
+    duel.reveal(challenger, Choice::Scissors);
+
+    duel.harness.warp_seconds(60 * 60 + 1);
+    duel.expire().expect("expire challenge");
+    duel.harness.warp_seconds(DOUBLE_OR_NOTHING_SECS + 1);
+    duel.expire().expect("close settled challenge");
+    assert_eq!(duel.harness.lamports(&challenger), WALLET_LAMPORTS + STAKE);
+    assert_eq!(duel.harness.lamports(&opponent), WALLET_LAMPORTS - STAKE);
+}
+
+#[test]
+fn double_or_nothing_rematches_for_the_combined_pot() {
+    let mut duel = Duel::create();
+    let program_id = duel.harness.program_id();
+    duel.accept();
+    duel.play_to_a_win();
+    let (winner, loser) = (duel.challenger, duel.opponent);
+
+    assert_eq!(duel.offer_double_or_nothing(winner), Err(Some(RPSError::NotAuthorized)));
+    duel.offer_double_or_nothing(loser).expect("offer double or nothing");
+    assert_eq!(duel.harness.lamports(&loser), WALLET_LAMPORTS - 3 * STAKE);
+    assert_eq!(duel.offer_double_or_nothing(loser), Err(Some(RPSError::InvalidGameState)));
+
+    let rematch = Pubkey::new_unique();
+    let sniped = duel.harness.process(&[instruction::accept_double_or_nothing(
+        &program_id, &loser, &duel.challenge, &rematch, &winner, None,
+    )]);
+    assert_eq!(sniped.unwrap_err().rps_error(), Some(RPSError::NotAuthorized));
+    duel
+        .harness
+        .process(&[instruction::accept_double_or_nothing(
+            &program_id, &winner, &duel.challenge, &rematch, &winner, None,
+        )])
+        .expect("accept double or nothing");
+
+    // The settled challenge closes and the rematch starts with both stakes in
+    assert!(duel.harness.account(&duel.challenge).is_none());
+    let account = duel.harness.account(&rematch).expect("rematch account");
+    let state = ChallengeState::unpack_from(&account.data).expect("rematch state");
+    assert!(state.accepted);
+    assert_eq!(state.stake, 2 * STAKE);
+    assert_eq!(
+        state.sides.iter().map(|side| side.player).collect::<Vec<_>>(),
+        vec![winner, loser]
+    );
+
+    // Nothing: the winner loses the rematch and the loser is back to even
+    duel.challenge = rematch;
+    duel.commit(winner, &Choice::Scissors);
+    duel.commit(loser, &Choice::Rock);
+    duel.reveal(winner, Choice::Scissors);
+    duel.reveal(loser, Choice::Rock);
+    assert_eq!(duel.harness.lamports(&loser), WALLET_LAMPORTS + STAKE);
+}
+
+#[test]
+fn unanswered_double_or_nothing_is_refunded() {
+    let mut duel = Duel::create();
+    let program_id = duel.harness.program_id();
+    duel.accept();
+    duel.play_to_a_win();
+    let (winner, loser) = (duel.challenger, duel.opponent);
+    duel.offer_double_or_nothing(loser).expect("offer double or nothing");
+
+    duel.harness.warp_seconds(DOUBLE_OR_NOTHING_SECS + 1);
+    let late = duel.harness.process(&[instruction::accept_double_or_nothing(
+        &program_id, &winner, &duel.challenge, &Pubkey::new_unique(), &winner, None,
+    )]);
+    assert_eq!(late.unwrap_err().rps_error(), Some(RPSError::InvalidGameState));
+
+    duel.expire().expect("expire offer");
+    assert!(duel.harness.account(&duel.challenge).is_none());
+    assert_eq!(duel.harness.lamports(&loser), WALLET_LAMPORTS - STAKE);
+    assert_eq!(duel.harness.lamports(&winner), WALLET_LAMPORTS + STAKE);
+}
@@ -0,0 +1,240 @@
+//! `GetClaimable` answers with exactly what a claim would pay: the share
+//! settled on each winner when the game finished, the last winner in seat
+//! order taking the rounding dust.
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use rps_client::instruction::{self, GameConfig};
+use rps_fixtures::harness::Harness;
+use rps_fixtures::setup::{open_lobby, WALLET_LAMPORTS};
+use solana_rps::{compute_commitment, Choice, Claimable, Game, GameState, Payout, RPSError, Settlement};
+use solana_sdk::pubkey::Pubkey;
+
+fn claimable(harness: &mut Harness, game: &Pubkey, player: &Pubkey) -> Claimable {
+    let program_id = harness.program_id();
+    harness
+        .process(&[instruction::get_claimable(&program_id, game, player)])
+        .expect("get claimable");
+    let (program, data) = harness.return_data().expect("return data");
+    assert_eq!(*program, program_id);
+    Claimable::try_from_slice(data).expect("decode claimable")
+}
+
+// Rewrites the game account with `state`
+fn store(harness: &mut Harness, game: &Pubkey, state: &Game) {
+    let mut account = harness.account(game).expect("game account").clone();
+    let bytes = state.try_to_vec().expect("serialize game");
+    account.data[..bytes.len()].copy_from_slice(&bytes);
+    harness.set_account(*game, account);
+}
+
+// Plays one round of `choices`, host first
+fn play(harness: &mut Harness, game: &Pubkey, players: &[Pubkey], choices: &[Choice]) {
+    let program_id = harness.program_id();
+    let round = harness.game(game).expect("game").current_round;
+    for (i, (player, choice)) in players.iter().zip(choices).enumerate() {
+        let salt = [i as u8 + 1; 32];
+        let commitment = compute_commitment(game, round, player, &salt, choice).expect("valid choice");
+        harness
+            .process(&[instruction::commit_choice(&program_id, player, game, commitment)])
+            .expect("commit");
+    }
+    for (i, (player, choice)) in players.iter().zip(choices).enumerate() {
+        let salt = [i as u8 + 1; 32];
+        harness
+            .process(&[instruction::reveal_choice(&program_id, player, game, choice.clone(), salt)])
+            .expect("reveal");
+    }
+}
+
+#[test]
+fn winners_split_the_pot_and_the_last_seat_takes_the_dust() {
+    let mut harness = Harness::new(Pubkey::new_unique());
+    let program_id = harness.program_id();
+    let players: Vec<Pubkey> = (0..3).map(|_| harness.new_wallet(WALLET_LAMPORTS)).collect();
+    let game = Pubkey::new_unique();
+
+    let config = GameConfig {
+        entry_fee: 100_000_001,
+        ..GameConfig::default()
+    };
+    harness
+        .process(&[instruction::initialize_game(&program_id, &players[0], &game, &config)])
+        .expect("initialize game");
+    for player in &players[1..] {
+        harness
+            .process(&[instruction::join_game(&program_id, player, &game)])
+            .expect("join game");
+    }
+    assert_eq!(claimable(&mut harness, &game, &players[0]), Claimable::default());
+
+    // Both rocks beat the scissors and share the win
+    play(&mut harness, &game, &players, &[Choice::Rock, Choice::Rock, Choice::Scissors]);
+
+    let mut state = harness.game(&game).expect("game account");
+    assert!(matches!(state.state, GameState::Finished));
+    let pot = state.pot_snapshot;
+    assert_eq!(pot % 2, 1, "pot should leave dust to exercise the split");
+
+    assert_eq!(claimable(&mut harness, &game, &players[0]).amount, pot / 2);
+    assert_eq!(claimable(&mut harness, &game, &players[1]).amount, pot - pot / 2);
+    assert_eq!(claimable(&mut harness, &game, &players[2]), Claimable::default());
+    let shares: Vec<u64> = state.settlement.payouts.iter().map(|p| p.amount).collect();
+    assert_eq!(shares, vec![pot / 2, pot - pot / 2]);
+
+    // The dust stays with its seat whoever claims first
+    state.claimed_bitmap = 0b10;
+    state.game_pot -= pot - pot / 2;
+    store(&mut harness, &game, &state);
+
+    assert_eq!(claimable(&mut harness, &game, &players[1]), Claimable::default());
+    assert_eq!(claimable(&mut harness, &game, &players[0]).amount, pot / 2);
+}
+
+#[test]
+fn claims_pay_the_settlement_not_the_scores() {
+    let mut harness = Harness::new(Pubkey::new_unique());
+    let program_id = harness.program_id();
+    let players: Vec<Pubkey> = (0..3).map(|_| harness.new_wallet(WALLET_LAMPORTS)).collect();
+    let game = Pubkey::new_unique();
+    let config = GameConfig {
+        entry_fee: 100_000_000,
+        ..GameConfig::default()
+    };
+    harness
+        .process(&[instruction::initialize_game(&program_id, &players[0], &game, &config)])
+        .expect("initialize game");
+    for player in &players[1..] {
+        harness
+            .process(&[instruction::join_game(&program_id, player, &game)])
+            .expect("join game");
+    }
+    play(&mut harness, &game, &players, &[Choice::Paper, Choice::Rock, Choice::Rock]);
+    let mut state = harness.game(&game).expect("game");
+    let pot = state.pot_snapshot;
+    assert_eq!(state.settlement.payouts, vec![Payout { player: players[0], amount: pot }]);
+
+    // Scores and the snapshot edited after the finish change nothing
+    state.players[1].score = 9;
+    state.pot_snapshot = 0;
+    store(&mut harness, &game, &state);
+    assert_eq!(claimable(&mut harness, &game, &players[0]).amount, pot);
+
+    // A game that finished before settlements were recorded still pays out
+    // from its winners and snapshot
+    state.pot_snapshot = pot;
+    state.settlement = Settlement::default();
+    store(&mut harness, &game, &state);
+    assert_eq!(claimable(&mut harness, &game, &players[0]).amount, pot);
+
+    let before = harness.lamports(&players[0]);
+    harness
+        .process(&[instruction::claim_winnings(&program_id, &players[0], &game)])
+        .expect("claim winnings");
+    assert_eq!(harness.lamports(&players[0]) - before, pot);
+}
+
+#[test]
+fn shares_follow_the_winners_recorded_at_finish() {
+    let mut harness = Harness::new(Pubkey::new_unique());
+    let program_id = harness.program_id();
+    let players: Vec<Pubkey> = (0..2).map(|_| harness.new_wallet(WALLET_LAMPORTS)).collect();
+    let game = Pubkey::new_unique();
+    let config = GameConfig {
+        min_players: 2,
+        max_players: 2,
+        entry_fee: 100_000_000,
+        ..GameConfig::default()
+    };
+    harness
+        .process(&[instruction::initialize_game(&program_id, &players[0], &game, &config)])
+        .expect("initialize game");
+    harness
+        .process(&[instruction::join_game(&program_id, &players[1], &game)])
+        .expect("join game");
+    play(&mut harness, &game, &players, &[Choice::Paper, Choice::Rock]);
+
+    // A claim leaves the scores the result was decided by
+    harness
+        .process(&[instruction::claim_winnings(&program_id, &players[0], &game)])
+        .expect("claim winnings");
+    let mut state = harness.game(&game).expect("game");
+    assert_eq!(state.winners, 0b01);
+    assert_eq!((state.players[0].score, state.players[1].score), (1, 0));
+    assert_eq!(claimable(&mut harness, &game, &players[0]), Claimable::default());
+
+    // Whatever later happens to the scores, the paid winner stays the winner
+    state.players[1].score = 5;
+    store(&mut harness, &game, &state);
+    assert_eq!(claimable(&mut harness, &game, &players[1]), Claimable::default());
+
+    // A replay forgets the old winners and records the new ones
+    harness
+        .process(&[instruction::start_new_game_round(&program_id, &players[0], &game)])
+        .expect("start new round");
+    assert_eq!(harness.game(&game).expect("game").winners, 0);
+    play(&mut harness, &game, &players, &[Choice::Rock, Choice::Paper]);
+    assert_eq!(harness.game(&game).expect("game").winners, 0b10);
+}
+
+#[test]
+fn nobody_replays_a_game_that_still_owes_its_winner() {
+    let mut harness = Harness::new(Pubkey::new_unique());
+    let program_id = harness.program_id();
+    let players: Vec<Pubkey> = (0..2).map(|_| harness.new_wallet(WALLET_LAMPORTS)).collect();
+    let game = Pubkey::new_unique();
+    let config = GameConfig {
+        min_players: 2,
+        max_players: 2,
+        entry_fee: 100_000_000,
+        game_mode: 1,
+        max_auto_rounds: 3,
+        ..GameConfig::default()
+    };
+    harness
+        .process(&[instruction::initialize_game(&program_id, &players[0], &game, &config)])
+        .expect("initialize game");
+    harness
+        .process(&[instruction::join_game(&program_id, &players[1], &game)])
+        .expect("join game");
+    play(&mut harness, &game, &players, &[Choice::Paper, Choice::Rock]);
+
+    // The loser cannot wipe the result before the winner is paid
+    let replay = harness.process(&[instruction::start_new_game_round(&program_id, &players[1], &game)]);
+    assert_eq!(replay.unwrap_err().rps_error(), Some(RPSError::InvalidGameState));
+    let auto = harness.process(&[instruction::auto_play_next_round(&program_id, &players[1], &game)]);
+    assert_eq!(auto.unwrap_err().rps_error(), Some(RPSError::InvalidGameState));
+    assert_eq!(harness.game(&game).expect("game").winners, 0b01);
+
+    harness
+        .process(&[instruction::claim_winnings(&program_id, &players[0], &game)])
+        .expect("claim winnings");
+    harness
+        .process(&[instruction::auto_play_next_round(&program_id, &players[1], &game)])
+        .expect("replay once paid");
+    assert!(matches!(harness.game(&game).expect("game").state, GameState::CommitPhase));
+}
+
+#[test]
+fn a_cancelled_game_replays_only_once_every_seat_is_refunded() {
+    let mut harness = Harness::new(Pubkey::new_unique());
+    let program_id = harness.program_id();
+    let players: Vec<Pubkey> = (0..2).map(|_| harness.new_wallet(WALLET_LAMPORTS)).collect();
+    let config = GameConfig {
+        entry_fee: 100_000_000,
+        ..GameConfig::default()
+    };
+    let game = open_lobby(&mut harness, &players, &config);
+    let phase_nonce = harness.game(&game).expect("game").phase_nonce;
+    harness.warp_seconds(config.timeout_seconds as i64);
+    harness
+        .process(&[instruction::resolve_timeout(&program_id, &players[0], &game, phase_nonce)])
+        .expect("resolve timeout");
+    assert!(harness.game(&game).expect("game").cancelled);
+
+    harness
+        .process(&[instruction::refund_entry(&program_id, &players[0], &game, &players[0])])
+        .expect("refund entry");
+    let replay = harness.process(&[instruction::start_new_game_round(&program_id, &players[0], &game)]);
+    assert_eq!(replay.unwrap_err().rps_error(), Some(RPSError::InvalidGameState));
+    assert!(harness.game(&game).expect("game").cancelled);
+}
@@ -0,0 +1,87 @@
+//! `CloseGame` returns a settled game's rent, and its pot vault's, once
+//! every winner has been paid and the game's result is recorded.
+
+use rps_client::{
+    instruction::{self, GameConfig},
+    subscribe::{parse_log_event, GameEvent},
+};
+use rps_fixtures::harness::Harness;
+use rps_fixtures::setup::WALLET_LAMPORTS;
+use solana_rps::{compute_commitment, game_result_address, pot_vault_address, Choice, RPSError};
+use solana_sdk::pubkey::Pubkey;
+
+const ENTRY_FEE: u64 = 100_000_000;
+
+#[test]
+fn paid_out_game_closes_to_the_host() {
+    let mut harness = Harness::new(Pubkey::new_unique());
+    let program_id = harness.program_id();
+    let players: Vec<Pubkey> = (0..3).map(|_| harness.new_wallet(WALLET_LAMPORTS)).collect();
+    let stranger = harness.new_wallet(WALLET_LAMPORTS);
+    let game = Pubkey::new_unique();
+    let vault = pot_vault_address(&program_id, &game).0;
+
+    let config = GameConfig {
+        entry_fee: ENTRY_FEE,
+        ..GameConfig::default()
+    };
+    harness
+        .process(&[instruction::initialize_game(&program_id, &players[0], &game, &config)])
+        .expect("initialize game");
+    for player in &players[1..] {
+        harness
+            .process(&[instruction::join_game(&program_id, player, &game)])
+            .expect("join game");
+    }
+    let early = harness.process(&[instruction::close_game(&program_id, &players[0], &game, &players[0], None)]);
+    assert_eq!(early.unwrap_err().rps_error(), Some(RPSError::InvalidGameState));
+
+    let hands = [Choice::Scissors, Choice::Rock, Choice::Scissors];
+    for (i, (player, hand)) in players.iter().zip(&hands).enumerate() {
+        let salt = [i as u8 + 1; 32];
+        let commitment = compute_commitment(&game, 1, player, &salt, hand).expect("valid choice");
+        harness
+            .process(&[instruction::commit_choice(&program_id, player, &game, commitment)])
+            .expect("commit");
+    }
+    for (i, (player, hand)) in players.iter().zip(&hands).enumerate() {
+        let salt = [i as u8 + 1; 32];
+        harness
+            .process(&[instruction::reveal_choice(&program_id, player, &game, hand.clone(), salt)])
+            .expect("reveal");
+    }
+
+    // The winner has not claimed yet
+    let unclaimed = harness.process(&[instruction::close_game(&program_id, &stranger, &game, &players[0], None)]);
+    assert_eq!(unclaimed.unwrap_err().rps_error(), Some(RPSError::InvalidGameState));
+    harness
+        .process(&[instruction::claim_winnings(&program_id, &players[1], &game)])
+        .expect("claim winnings");
+
+    // Only the host may send the rent elsewhere
+    let diverted = harness.process(&[instruction::close_game(&program_id, &stranger, &game, &stranger, None)]);
+    assert_eq!(diverted.unwrap_err().rps_error(), Some(RPSError::NotAuthorized));
+
+    // Nor may the game go before its result is on record
+    let unrecorded = harness.process(&[instruction::close_game(&program_id, &stranger, &game, &players[0], None)]);
+    assert_eq!(unrecorded.unwrap_err().rps_error(), Some(RPSError::InvalidGameState));
+    harness
+        .process(&[instruction::record_game_result(&program_id, &stranger, &game)])
+        .expect("record result");
+
+    let rent = harness.lamports(&game) + harness.lamports(&vault);
+    let before = harness.lamports(&players[0]);
+    harness
+        .process(&[instruction::close_game(&program_id, &stranger, &game, &players[0], None)])
+        .expect("close game");
+    assert_eq!(harness.lamports(&players[0]), before + rent);
+    assert!(harness.account(&game).is_none());
+    assert!(harness.account(&vault).is_none());
+    assert!(harness.account(&game_result_address(&program_id, &game).0).is_some());
+
+    let events: Vec<GameEvent> = harness.logs().iter().filter_map(|line| parse_log_event(line)).collect();
+    assert!(events.contains(&GameEvent::GameClosed {
+        recipient: players[0].to_string(),
+        lamports: rent,
+    }));
+}
@@ -0,0 +1,175 @@
+//! Hot/cold treasury: fees above the hot treasury's cap are swept into the
+//! cold treasury, which only pays out on a timelocked multisig proposal.
+
+use rps_client::instruction;
+use rps_fixtures::harness::Harness;
+use rps_fixtures::setup::WALLET_LAMPORTS;
+use solana_rps::{cold_treasury_address, treasury_address, ColdAction, ColdTreasury, RPSError};
+use solana_sdk::{account::Account, pubkey::Pubkey, rent::Rent, system_program};
+
+const SOL: u64 = 1_000_000_000;
+const TIMELOCK_SECS: u64 = 2 * 24 * 60 * 60;
+const HOT_CAP: u64 = SOL;
+
+struct Vaults {
+    harness: Harness,
+    signers: Vec<Pubkey>,
+}
+
+impl Vaults {
+    // A cold treasury run by two of three signers, and a hot treasury
+    // holding `hot` lamports of fees
+    fn open(hot: u64) -> Self {
+        let mut harness = Harness::new(Pubkey::new_unique());
+        let program_id = harness.program_id();
+        let operator = harness.new_wallet(WALLET_LAMPORTS);
+        let signers: Vec<Pubkey> = (0..3).map(|_| harness.new_wallet(WALLET_LAMPORTS)).collect();
+        harness
+            .process(&[instruction::initialize_config(&program_id, &operator)])
+            .expect("initialize config");
+
+        let lone = instruction::initialize_cold_treasury(&program_id, &operator, signers.clone(), 1, TIMELOCK_SECS, 0);
+        assert_eq!(harness.process(&[lone]).unwrap_err().rps_error(), Some(RPSError::InvalidParameter));
+        let hasty = instruction::initialize_cold_treasury(&program_id, &operator, signers.clone(), 2, 60, 0);
+        assert_eq!(harness.process(&[hasty]).unwrap_err().rps_error(), Some(RPSError::InvalidParameter));
+        harness
+            .process(&[instruction::initialize_cold_treasury(
+                &program_id,
+                &operator,
+                signers.clone(),
+                2,
+                TIMELOCK_SECS,
+                HOT_CAP,
+            )])
+            .expect("initialize cold treasury");
+
+        let treasury = treasury_address(&program_id).0;
+        harness.set_account(treasury, Account::new(hot, 0, &system_program::id()));
+        Vaults { harness, signers }
+    }
+
+    fn cold_balance(&self) -> u64 {
+        let cold = cold_treasury_address(&self.harness.program_id()).0;
+        self.harness.lamports(&cold) - Rent::default().minimum_balance(ColdTreasury::SPACE)
+    }
+
+    fn propose(&mut self, signer: usize, index: u64, action: ColdAction) {
+        let program_id = self.harness.program_id();
+        self.harness
+            .process(&[instruction::propose_cold_action(&program_id, &self.signers[signer], index, action)])
+            .expect("propose cold action");
+    }
+
+    fn approve(&mut self, signer: usize, index: u64) -> Option<RPSError> {
+        let program_id = self.harness.program_id();
+        let approve = instruction::approve_cold_action(&program_id, &self.signers[signer], index);
+        self.harness.process(&[approve]).err().map(|failure| failure.rps_error().expect("program error"))
+    }
+
+    fn execute(&mut self, index: u64, recipient: Option<&Pubkey>) -> Option<RPSError> {
+        let program_id = self.harness.program_id();
+        let caller = self.harness.new_wallet(WALLET_LAMPORTS);
+        let execute = instruction::execute_cold_action(&program_id, &caller, index, recipient);
+        self.harness.process(&[execute]).err().map(|failure| failure.rps_error().expect("program error"))
+    }
+}
+
+#[test]
+fn sweeps_keep_the_hot_treasury_at_its_cap() {
+    let mut vaults = Vaults::open(5 * SOL);
+    let program_id = vaults.harness.program_id();
+    let cranker = vaults.harness.new_wallet(WALLET_LAMPORTS);
+
+    vaults
+        .harness
+        .process(&[instruction::sweep_treasury(&program_id, &cranker)])
+        .expect("sweep");
+    assert_eq!(vaults.harness.lamports(&treasury_address(&program_id).0), HOT_CAP);
+    assert_eq!(vaults.cold_balance(), 4 * SOL);
+
+    let empty = vaults.harness.process(&[instruction::sweep_treasury(&program_id, &cranker)]);
+    assert_eq!(empty.unwrap_err().rps_error(), Some(RPSError::InsufficientFunds));
+}
+
+#[test]
+fn withdrawals_need_the_timelock_and_the_threshold() {
+    let mut vaults = Vaults::open(5 * SOL);
+    let program_id = vaults.harness.program_id();
+    let cranker = vaults.harness.new_wallet(WALLET_LAMPORTS);
+    vaults
+        .harness
+        .process(&[instruction::sweep_treasury(&program_id, &cranker)])
+        .expect("sweep");
+
+    // A leaked operational key is not a cold signer
+    let stranger = vaults.harness.new_wallet(WALLET_LAMPORTS);
+    let withdraw = ColdAction::Withdraw {
+        recipient: stranger,
+        amount: 3 * SOL,
+    };
+    let outsider = instruction::propose_cold_action(&program_id, &stranger, 0, withdraw);
+    assert_eq!(vaults.harness.process(&[outsider]).unwrap_err().rps_error(), Some(RPSError::NotAuthorized));
+
+    let recipient = vaults.harness.new_wallet(0);
+    vaults.propose(
+        0,
+        0,
+        ColdAction::Withdraw {
+            recipient,
+            amount: 3 * SOL,
+        },
+    );
+    assert_eq!(vaults.approve(0, 0), Some(RPSError::InvalidGameState));
+    assert_eq!(vaults.execute(0, Some(&recipient)), Some(RPSError::TimeoutNotReached));
+
+    vaults.harness.warp_seconds(TIMELOCK_SECS as i64);
+    assert_eq!(vaults.execute(0, Some(&recipient)), Some(RPSError::NotAuthorized));
+    assert_eq!(vaults.approve(1, 0), None);
+    assert_eq!(vaults.execute(0, Some(&stranger)), Some(RPSError::InvalidParameter));
+    assert_eq!(vaults.execute(0, Some(&recipient)), None);
+    assert_eq!(vaults.harness.lamports(&recipient), 3 * SOL);
+    assert_eq!(vaults.cold_balance(), SOL);
+    assert_eq!(vaults.execute(0, Some(&recipient)), Some(RPSError::InvalidGameState));
+}
+
+#[test]
+fn rotating_signers_lapses_pending_proposals() {
+    let mut vaults = Vaults::open(HOT_CAP);
+    let replacement: Vec<Pubkey> = (0..2).map(|_| vaults.harness.new_wallet(WALLET_LAMPORTS)).collect();
+
+    vaults.propose(2, 0, ColdAction::SetHotCap { hot_cap: 0 });
+    vaults.propose(
+        0,
+        1,
+        ColdAction::SetSigners {
+            signers: replacement.clone(),
+            threshold: 2,
+        },
+    );
+    assert_eq!(vaults.approve(1, 1), None);
+    vaults.harness.warp_seconds(TIMELOCK_SECS as i64);
+    assert_eq!(vaults.execute(1, None), None);
+
+    // The old signers are out, and the hot cap change they proposed lapsed
+    assert_eq!(vaults.approve(2, 0), Some(RPSError::NotAuthorized));
+    vaults.signers = replacement;
+    assert_eq!(vaults.approve(0, 0), Some(RPSError::InvalidGameState));
+    assert_eq!(vaults.approve(1, 0), Some(RPSError::InvalidGameState));
+    assert_eq!(vaults.execute(0, None), Some(RPSError::InvalidGameState));
+
+    vaults.propose(0, 2, ColdAction::SetHotCap { hot_cap: 0 });
+    assert_eq!(vaults.approve(1, 2), None);
+    vaults.harness.warp_seconds(TIMELOCK_SECS as i64);
+    assert_eq!(vaults.execute(2, None), None);
+
+    let program_id = vaults.harness.program_id();
+    let cranker = vaults.harness.new_wallet(WALLET_LAMPORTS);
+    vaults
+        .harness
+        .process(&[instruction::sweep_treasury(&program_id, &cranker)])
+        .expect("sweep");
+    assert_eq!(
+        vaults.harness.lamports(&treasury_address(&program_id).0),
+        Rent::default().minimum_balance(0)
+    );
+}
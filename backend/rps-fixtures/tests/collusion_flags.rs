@@ -0,0 +1,163 @@
+//! Collusion heuristics: games that look rigged are flagged at settlement
+//! for off-chain review, and only stop paying out if the operator says so.
+
+use rps_client::{
+    instruction::{self, GameConfig},
+    subscribe::{parse_log_event, GameEvent},
+};
+use rps_fixtures::harness::Harness;
+use rps_fixtures::setup::WALLET_LAMPORTS;
+use solana_rps::{
+    compute_commitment, Choice, RPSError, FLAG_DUMP_LOSSES, FLAG_HAND_PATTERN, FLAG_SHARED_FUNDING,
+};
+use solana_sdk::pubkey::Pubkey;
+
+const ENTRY_FEE: u64 = 100_000_000;
+
+// Plays a free game of `rounds[i]` hands per seat, host first, and returns it
+fn play(harness: &mut Harness, players: &[Pubkey], rounds: &[[Choice; 3]]) -> Pubkey {
+    let program_id = harness.program_id();
+    let game = Pubkey::new_unique();
+    let config = GameConfig {
+        total_rounds: rounds.len() as u8,
+        ..GameConfig::default()
+    };
+    harness
+        .process(&[instruction::initialize_game(&program_id, &players[0], &game, &config)])
+        .expect("initialize game");
+    for player in &players[1..] {
+        harness
+            .process(&[instruction::join_game(&program_id, player, &game)])
+            .expect("join game");
+    }
+    for (round, hands) in rounds.iter().enumerate() {
+        for (i, (player, hand)) in players.iter().zip(hands).enumerate() {
+            let salt = [(round * 3 + i) as u8 + 1; 32];
+            let commitment = compute_commitment(&game, round as u8 + 1, player, &salt, hand).expect("valid choice");
+            harness
+                .process(&[instruction::commit_choice(&program_id, player, &game, commitment)])
+                .expect("commit");
+        }
+        for (i, (player, hand)) in players.iter().zip(hands).enumerate() {
+            let salt = [(round * 3 + i) as u8 + 1; 32];
+            harness
+                .process(&[instruction::reveal_choice(&program_id, player, &game, hand.clone(), salt)])
+                .expect("reveal");
+        }
+    }
+    game
+}
+
+fn alerts(harness: &Harness) -> Vec<u8> {
+    harness
+        .logs()
+        .iter()
+        .filter_map(|line| match parse_log_event(line) {
+            Some(GameEvent::CollusionSuspected { reasons }) => Some(reasons),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn suspicious_games_are_flagged_at_settlement() {
+    use Choice::{Paper, Rock, Scissors};
+    let mut harness = Harness::new(Pubkey::new_unique());
+    let players: Vec<Pubkey> = (0..3).map(|_| harness.new_wallet(WALLET_LAMPORTS)).collect();
+
+    // Mixed play finishes clean
+    let clean = play(&mut harness, &players, &[[Rock, Paper, Scissors], [Paper, Paper, Rock]]);
+    assert!(alerts(&harness).is_empty());
+    assert!(!harness.game(&clean).expect("game").flagged);
+
+    // The last two seats throw whatever the host beats, three rounds running
+    let dumped = play(
+        &mut harness,
+        &players,
+        &[[Rock, Scissors, Scissors], [Paper, Rock, Rock], [Scissors, Paper, Paper]],
+    );
+    assert_eq!(alerts(&harness), vec![FLAG_DUMP_LOSSES]);
+    let state = harness.game(&dumped).expect("game");
+    assert!(state.flagged);
+    assert!(state.seat_watch.is_empty());
+
+    // Nobody throws anything but rock for five rounds
+    let streak = play(&mut harness, &players, &vec![[Rock, Rock, Rock]; 5]);
+    assert_eq!(alerts(&harness), vec![FLAG_HAND_PATTERN]);
+    assert_eq!(harness.game(&streak).expect("game").flag_reasons, FLAG_HAND_PATTERN);
+}
+
+#[test]
+fn a_player_buying_opponent_seats_is_flagged() {
+    let mut harness = Harness::new(Pubkey::new_unique());
+    let program_id = harness.program_id();
+    let host = harness.new_wallet(WALLET_LAMPORTS);
+    let guest = harness.new_wallet(WALLET_LAMPORTS);
+    let game = Pubkey::new_unique();
+
+    let config = GameConfig {
+        entry_fee: ENTRY_FEE,
+        ..GameConfig::default()
+    };
+    harness
+        .process(&[instruction::initialize_game(&program_id, &host, &game, &config)])
+        .expect("initialize game");
+    harness
+        .process(&[instruction::add_bot_players(&program_id, &host, &game, 1, 0)])
+        .expect("add bot");
+    harness
+        .process(&[instruction::join_game(&program_id, &guest, &game)])
+        .expect("join game");
+
+    // Nobody commits; the timeout settles the game
+    let state = harness.game(&game).expect("game");
+    harness.warp_seconds(state.required_timeout as i64 + 1);
+    harness
+        .process(&[instruction::resolve_timeout(&program_id, &guest, &game, state.phase_nonce)])
+        .expect("resolve timeout");
+    assert_eq!(alerts(&harness), vec![FLAG_SHARED_FUNDING]);
+    assert_eq!(harness.game(&game).expect("game").bot_funders, vec![host]);
+}
+
+#[test]
+fn operator_can_hold_flagged_payouts_until_cleared() {
+    use Choice::{Paper, Rock, Scissors};
+    let mut harness = Harness::new(Pubkey::new_unique());
+    let program_id = harness.program_id();
+    let operator = harness.new_wallet(WALLET_LAMPORTS);
+    let players: Vec<Pubkey> = (0..3).map(|_| harness.new_wallet(WALLET_LAMPORTS)).collect();
+
+    harness
+        .process(&[instruction::initialize_config(&program_id, &operator)])
+        .expect("initialize config");
+    let game = play(
+        &mut harness,
+        &players,
+        &[[Rock, Scissors, Scissors], [Paper, Rock, Rock], [Scissors, Paper, Paper]],
+    );
+
+    // By default a flag does not stand between the winner and the pot; this
+    // free game simply has nothing to pay
+    let unheld = harness.process(&[instruction::claim_winnings(&program_id, &players[0], &game)]);
+    assert_eq!(unheld.unwrap_err().rps_error(), Some(RPSError::InsufficientFunds));
+
+    let forged = harness.process(&[instruction::set_collusion_policy(&program_id, &players[0], true)]);
+    assert_eq!(forged.unwrap_err().rps_error(), Some(RPSError::NotAuthorized));
+    harness
+        .process(&[instruction::set_collusion_policy(&program_id, &operator, true)])
+        .expect("hold flagged payouts");
+    let held = harness.process(&[instruction::claim_winnings(&program_id, &players[0], &game)]);
+    assert_eq!(held.unwrap_err().rps_error(), Some(RPSError::PayoutHeld));
+
+    let self_cleared = harness.process(&[instruction::clear_game_flag(&program_id, &players[0], &game)]);
+    assert_eq!(self_cleared.unwrap_err().rps_error(), Some(RPSError::NotAuthorized));
+    harness
+        .process(&[instruction::clear_game_flag(&program_id, &operator, &game)])
+        .expect("clear flag");
+    let state = harness.game(&game).expect("game");
+    assert!(!state.flagged);
+    assert_eq!(state.flag_reasons, FLAG_DUMP_LOSSES);
+
+    let released = harness.process(&[instruction::claim_winnings(&program_id, &players[0], &game)]);
+    assert_eq!(released.unwrap_err().rps_error(), Some(RPSError::InsufficientFunds));
+}
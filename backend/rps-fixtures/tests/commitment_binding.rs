@@ -0,0 +1,59 @@
+//! Commitments are bound to the game, the round and the committing player,
+//! so copying someone else's commitment and replaying their revealed salt
+//! does not open it.
+
+use rps_client::instruction::{self, GameConfig};
+use rps_fixtures::harness::Harness;
+use rps_fixtures::setup::WALLET_LAMPORTS;
+use solana_rps::{compute_commitment, Choice, RPSError};
+use solana_sdk::pubkey::Pubkey;
+
+#[test]
+fn copied_commitment_cannot_be_revealed() {
+    let mut harness = Harness::new(Pubkey::new_unique());
+    let program_id = harness.program_id();
+    let players: Vec<Pubkey> = (0..3).map(|_| harness.new_wallet(WALLET_LAMPORTS)).collect();
+    let game = Pubkey::new_unique();
+
+    harness
+        .process(&[instruction::initialize_game(&program_id, &players[0], &game, &GameConfig::default())])
+        .expect("initialize game");
+    for player in &players[1..] {
+        harness
+            .process(&[instruction::join_game(&program_id, player, &game)])
+            .expect("join game");
+    }
+
+    let salt = [1; 32];
+    let commitment = compute_commitment(&game, 1, &players[0], &salt, &Choice::Rock).expect("valid choice");
+    harness
+        .process(&[instruction::commit_choice(&program_id, &players[0], &game, commitment)])
+        .expect("commit");
+
+    // The copier lifts the host's commitment straight off the game account
+    let copied = harness.game(&game).expect("game").players[0].committed_choice;
+    harness
+        .process(&[instruction::commit_choice(&program_id, &players[1], &game, copied)])
+        .expect("copied commit");
+    let honest_salt = [3; 32];
+    let honest = compute_commitment(&game, 1, &players[2], &honest_salt, &Choice::Paper).expect("valid choice");
+    harness
+        .process(&[instruction::commit_choice(&program_id, &players[2], &game, honest)])
+        .expect("commit");
+
+    harness
+        .process(&[instruction::reveal_choice(&program_id, &players[0], &game, Choice::Rock, salt)])
+        .expect("reveal");
+    // The host's salt is public now, but it opens only the host's seat
+    let replayed = harness.process(&[instruction::reveal_choice(&program_id, &players[1], &game, Choice::Rock, salt)]);
+    assert_eq!(replayed.unwrap_err().rps_error(), Some(RPSError::InvalidHash));
+
+    // A commitment made for another game or round does not open here either
+    let elsewhere = compute_commitment(&Pubkey::new_unique(), 1, &players[2], &honest_salt, &Choice::Paper);
+    let next_round = compute_commitment(&game, 2, &players[2], &honest_salt, &Choice::Paper);
+    assert_ne!(elsewhere.expect("valid choice"), honest);
+    assert_ne!(next_round.expect("valid choice"), honest);
+    harness
+        .process(&[instruction::reveal_choice(&program_id, &players[2], &game, Choice::Paper, honest_salt)])
+        .expect("reveal");
+}
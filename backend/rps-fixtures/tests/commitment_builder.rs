@@ -0,0 +1,69 @@
+//! `Commitment` keeps everything a reveal needs: stored between the commit
+//! and the reveal, it opens on-chain under the game's own scheme.
+
+use rps_client::{
+    instruction::{self, GameConfig},
+    Commitment, RpsClientError,
+};
+use rps_fixtures::harness::Harness;
+use rps_fixtures::setup::WALLET_LAMPORTS;
+use solana_rps::{Choice, GameState};
+use solana_sdk::pubkey::Pubkey;
+
+#[test]
+fn stored_commitments_reveal_under_the_game_scheme() {
+    let mut harness = Harness::new(Pubkey::new_unique());
+    let program_id = harness.program_id();
+    let players: Vec<Pubkey> = (0..3).map(|_| harness.new_wallet(WALLET_LAMPORTS)).collect();
+    let game = Pubkey::new_unique();
+
+    // SHA-256, so the default scheme would not open
+    let config = GameConfig {
+        commitment_scheme: 1,
+        ..GameConfig::default()
+    };
+    harness
+        .process(&[instruction::initialize_game(&program_id, &players[0], &game, &config)])
+        .expect("initialize game");
+    for player in &players[1..] {
+        harness
+            .process(&[instruction::join_game(&program_id, player, &game)])
+            .expect("join game");
+    }
+
+    let hands = [Choice::Rock, Choice::Paper, Choice::Paper];
+    let mut stored = Vec::new();
+    for (player, hand) in players.iter().zip(hands) {
+        let state = harness.game(&game).expect("game");
+        let commitment = Commitment::for_game(hand, &game, &state, player).expect("valid choice");
+        harness.process(&[commitment.commit(&program_id)]).expect("commit");
+        assert!(commitment.is_committed_in(&harness.game(&game).expect("game")));
+        stored.push(commitment.to_bytes());
+    }
+
+    for bytes in &stored {
+        let commitment = Commitment::from_bytes(bytes).expect("stored commitment");
+        harness.process(&[commitment.reveal(&program_id)]).expect("reveal");
+    }
+    let state = harness.game(&game).expect("game");
+    assert!(matches!(state.state, GameState::Finished));
+    assert_eq!(state.winner_bitmap(), 0b110);
+}
+
+#[test]
+fn salts_are_fresh_and_tampering_is_caught() {
+    let game = Pubkey::new_unique();
+    let player = Pubkey::new_unique();
+    let first = Commitment::new(Choice::Rock, &game, 1, &player).expect("valid choice");
+    let second = Commitment::new(Choice::Rock, &game, 1, &player).expect("valid choice");
+    assert_ne!(first.hash(), second.hash());
+
+    let none = Commitment::new(Choice::None, &game, 1, &player);
+    assert!(matches!(none, Err(RpsClientError::InvalidChoice(Choice::None))));
+
+    // Flipping the stored round no longer matches the hash
+    let mut bytes = first.to_bytes();
+    bytes[32] ^= 1;
+    assert!(matches!(Commitment::from_bytes(&bytes), Err(RpsClientError::InvalidPendingReveal)));
+    assert!(matches!(Commitment::from_bytes(&bytes[1..]), Err(RpsClientError::InvalidPendingReveal)));
+}
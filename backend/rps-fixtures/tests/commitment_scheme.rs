@@ -0,0 +1,61 @@
+//! Games pick their commitment hash at init; reveals are checked against the
+//! game's scheme only.
+
+use rps_client::instruction::{self, GameConfig};
+use rps_fixtures::harness::Harness;
+use rps_fixtures::setup::WALLET_LAMPORTS;
+use solana_rps::{compute_commitment, compute_commitment_with, Choice, CommitmentScheme, GameState, RPSError};
+use solana_sdk::pubkey::Pubkey;
+
+#[test]
+fn sha256_games_reveal_against_sha256_commitments() {
+    let mut harness = Harness::new(Pubkey::new_unique());
+    let program_id = harness.program_id();
+    let players: Vec<Pubkey> = (0..3).map(|_| harness.new_wallet(WALLET_LAMPORTS)).collect();
+
+    let unknown = GameConfig {
+        commitment_scheme: 2,
+        ..GameConfig::default()
+    };
+    let rejected = harness.process(&[instruction::initialize_game(&program_id, &players[0], &Pubkey::new_unique(), &unknown)]);
+    assert_eq!(rejected.unwrap_err().rps_error(), Some(RPSError::InvalidParameter));
+
+    let game = Pubkey::new_unique();
+    let config = GameConfig {
+        commitment_scheme: 1,
+        ..GameConfig::default()
+    };
+    harness
+        .process(&[instruction::initialize_game(&program_id, &players[0], &game, &config)])
+        .expect("initialize game");
+    for player in &players[1..] {
+        harness
+            .process(&[instruction::join_game(&program_id, player, &game)])
+            .expect("join game");
+    }
+    assert_eq!(harness.game(&game).expect("game account").commitment_scheme, CommitmentScheme::Sha256);
+
+    // The last player commits with the default scheme by mistake
+    for (i, player) in players.iter().enumerate() {
+        let salt = [i as u8 + 1; 32];
+        let commitment = if i == 2 {
+            compute_commitment(&game, 1, player, &salt, &Choice::Paper).expect("valid choice")
+        } else {
+            compute_commitment_with(CommitmentScheme::Sha256, &game, 1, player, &salt, &Choice::Paper)
+                .expect("valid choice")
+        };
+        harness
+            .process(&[instruction::commit_choice(&program_id, player, &game, commitment)])
+            .expect("commit");
+    }
+
+    for (i, player) in players[..2].iter().enumerate() {
+        let salt = [i as u8 + 1; 32];
+        harness
+            .process(&[instruction::reveal_choice(&program_id, player, &game, Choice::Paper, salt)])
+            .expect("reveal");
+    }
+    let mismatched = harness.process(&[instruction::reveal_choice(&program_id, &players[2], &game, Choice::Paper, [3; 32])]);
+    assert_eq!(mismatched.unwrap_err().rps_error(), Some(RPSError::InvalidHash));
+    assert!(matches!(harness.game(&game).expect("game account").state, GameState::RevealPhase));
+}
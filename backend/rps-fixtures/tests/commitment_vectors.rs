@@ -0,0 +1,91 @@
+//! The golden commitment vectors in rps-state are what the program computes,
+//! and the JSON copy shipped to non-Rust clients says the same thing.
+
+use std::str::FromStr;
+
+use rps_state::{
+    simulate::{CommitBinding, Hand},
+    vectors::{CommitmentVector, COMMITMENT_VECTORS, SHA256_COMMITMENT_VECTORS},
+};
+use solana_rps::{compute_commitment, compute_commitment_with, Choice, CommitmentScheme};
+use solana_sdk::pubkey::Pubkey;
+
+const VECTORS_JSON: &str = include_str!("../../rps-state/vectors/commitments.json");
+
+fn unhex(hex: &str) -> Vec<u8> {
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).expect("hex byte"))
+        .collect()
+}
+
+fn choice(byte: u8) -> Choice {
+    match byte {
+        1 => Choice::Rock,
+        2 => Choice::Paper,
+        3 => Choice::Scissors,
+        _ => panic!("vector choice {} out of range", byte),
+    }
+}
+
+fn keys(vector: &CommitmentVector) -> (Pubkey, Pubkey) {
+    (
+        Pubkey::from_str(vector.game).expect("game address"),
+        Pubkey::from_str(vector.player).expect("player address"),
+    )
+}
+
+#[test]
+fn program_reproduces_every_vector() {
+    for vector in COMMITMENT_VECTORS {
+        let salt: [u8; 32] = unhex(vector.salt).try_into().expect("32-byte salt");
+        let expected = unhex(vector.commitment);
+        let (game, player) = keys(vector);
+
+        let commitment =
+            compute_commitment(&game, vector.round, &player, &salt, &choice(vector.choice)).expect("valid choice");
+        assert_eq!(commitment.to_vec(), expected, "{:?}", vector);
+        let binding = CommitBinding {
+            game: game.to_bytes(),
+            round: vector.round,
+            player: player.to_bytes(),
+        };
+        let hand = Hand::from_byte(vector.choice).expect("valid hand");
+        assert_eq!(rps_state::simulate::commitment(&binding, &salt, hand).to_vec(), expected);
+    }
+
+    for vector in SHA256_COMMITMENT_VECTORS {
+        let salt: [u8; 32] = unhex(vector.salt).try_into().expect("32-byte salt");
+        let (game, player) = keys(vector);
+        let commitment = compute_commitment_with(
+            CommitmentScheme::Sha256,
+            &game,
+            vector.round,
+            &player,
+            &salt,
+            &choice(vector.choice),
+        )
+        .expect("valid choice");
+        assert_eq!(commitment.to_vec(), unhex(vector.commitment), "{:?}", vector);
+    }
+}
+
+fn assert_json_matches(entries: &serde_json::Value, constants: &[CommitmentVector]) {
+    let entries = entries.as_array().expect("vectors array");
+    assert_eq!(entries.len(), constants.len());
+    for (entry, vector) in entries.iter().zip(constants) {
+        assert_eq!(entry["choice"].as_u64(), Some(vector.choice as u64));
+        assert_eq!(entry["salt"].as_str(), Some(vector.salt));
+        assert_eq!(entry["game"].as_str(), Some(vector.game));
+        assert_eq!(entry["round"].as_u64(), Some(vector.round as u64));
+        assert_eq!(entry["player"].as_str(), Some(vector.player));
+        assert_eq!(entry["commitment"].as_str(), Some(vector.commitment));
+    }
+}
+
+#[test]
+fn json_vectors_match_the_constants() {
+    let json: serde_json::Value = serde_json::from_str(VECTORS_JSON).expect("vectors json");
+    assert_json_matches(&json["vectors"], COMMITMENT_VECTORS);
+    assert_json_matches(&json["sha256_vectors"], SHA256_COMMITMENT_VECTORS);
+}
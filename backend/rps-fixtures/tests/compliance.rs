@@ -0,0 +1,98 @@
+//! Compliance mode: paid entries need a frozen credential token issued by an
+//! approved attestor, free games stay open to everyone.
+
+use rps_client::instruction::{self, Credential, GameConfig};
+use rps_fixtures::harness::Harness;
+use rps_fixtures::setup::{packed, WALLET_LAMPORTS};
+use solana_rps::RPSError;
+use solana_sdk::{program_option::COption, pubkey::Pubkey};
+
+// Issues a credential for `holder` from a new mint controlled by `attestor`
+fn issue_credential(harness: &mut Harness, attestor: &Pubkey, holder: &Pubkey, frozen: bool) -> Credential {
+    let mint = Pubkey::new_unique();
+    let account = Pubkey::new_unique();
+    harness.set_account(
+        mint,
+        packed(
+            spl_token::state::Mint {
+                mint_authority: COption::Some(*attestor),
+                supply: 1,
+                decimals: 0,
+                is_initialized: true,
+                freeze_authority: COption::Some(*attestor),
+            },
+            spl_token::id(),
+        ),
+    );
+    harness.set_account(
+        account,
+        packed(
+            spl_token::state::Account {
+                mint,
+                owner: *holder,
+                amount: 1,
+                state: if frozen {
+                    spl_token::state::AccountState::Frozen
+                } else {
+                    spl_token::state::AccountState::Initialized
+                },
+                ..spl_token::state::Account::default()
+            },
+            spl_token::id(),
+        ),
+    );
+    Credential { account, mint }
+}
+
+#[test]
+fn paid_entries_need_a_frozen_attested_credential() {
+    let mut harness = Harness::new(Pubkey::new_unique());
+    let program_id = harness.program_id();
+    let operator = harness.new_wallet(WALLET_LAMPORTS);
+    let attestor = Pubkey::new_unique();
+    let host = harness.new_wallet(WALLET_LAMPORTS);
+    let player = harness.new_wallet(WALLET_LAMPORTS);
+
+    harness
+        .process(&[instruction::initialize_config(&program_id, &operator)])
+        .expect("initialize config");
+    harness
+        .process(&[instruction::set_compliance(&program_id, &operator, true, vec![attestor])])
+        .expect("enable compliance");
+
+    // Free games need no credential
+    let free_game = Pubkey::new_unique();
+    harness
+        .process(&[instruction::initialize_game(&program_id, &host, &free_game, &GameConfig::default())])
+        .expect("initialize free game");
+    harness
+        .process(&[instruction::join_game(&program_id, &player, &free_game)])
+        .expect("join free game");
+
+    let paid_game = Pubkey::new_unique();
+    let config = GameConfig {
+        entry_fee: 100_000_000,
+        ..GameConfig::default()
+    };
+    harness
+        .process(&[instruction::initialize_game(&program_id, &host, &paid_game, &config)])
+        .expect("initialize paid game");
+
+    let rogue = issue_credential(&mut harness, &Pubkey::new_unique(), &player, true);
+    let unattested = harness.process(&[instruction::join_game_with_credential(&program_id, &player, &paid_game, &rogue)]);
+    assert_eq!(unattested.unwrap_err().rps_error(), Some(RPSError::MissingCredential));
+
+    let transferable = issue_credential(&mut harness, &attestor, &player, false);
+    let unfrozen =
+        harness.process(&[instruction::join_game_with_credential(&program_id, &player, &paid_game, &transferable)]);
+    assert_eq!(unfrozen.unwrap_err().rps_error(), Some(RPSError::MissingCredential));
+
+    let borrowed = issue_credential(&mut harness, &attestor, &host, true);
+    let not_theirs = harness.process(&[instruction::join_game_with_credential(&program_id, &player, &paid_game, &borrowed)]);
+    assert_eq!(not_theirs.unwrap_err().rps_error(), Some(RPSError::MissingCredential));
+
+    let credential = issue_credential(&mut harness, &attestor, &player, true);
+    harness
+        .process(&[instruction::join_game_with_credential(&program_id, &player, &paid_game, &credential)])
+        .expect("join with credential");
+}
@@ -0,0 +1,135 @@
+//! Crank rewards: a SOL game can pay whoever resolves its timeouts, first
+//! from a bounty the host deposits into the pot vault, then from the pot.
+
+use rps_client::instruction::{self, GameConfig};
+use rps_fixtures::harness::Harness;
+use rps_fixtures::setup::WALLET_LAMPORTS;
+use solana_rps::{compute_commitment, pot_vault_address, Choice, GameState, RPSError, MAX_CRANK_REWARD_BPS};
+use solana_sdk::{pubkey::Pubkey, rent::Rent};
+
+const ENTRY_FEE: u64 = 100_000_000;
+const TIMEOUT_SECS: u64 = 300;
+const REWARD: u64 = 1_000_000;
+const HANDS: [Choice; 3] = [Choice::Rock, Choice::Scissors, Choice::Scissors];
+
+fn rewarded_config(bounty: u64) -> GameConfig {
+    GameConfig {
+        entry_fee: ENTRY_FEE,
+        timeout_seconds: TIMEOUT_SECS,
+        total_rounds: 2,
+        crank_reward_lamports: REWARD,
+        crank_bounty: bounty,
+        ..GameConfig::default()
+    }
+}
+
+fn salt(seat: usize) -> [u8; 32] {
+    [seat as u8 + 1; 32]
+}
+
+// Every seat commits and all but the last reveal, then a keeper times the
+// reveal phase out
+fn play_round_to_timeout(harness: &mut Harness, players: &[Pubkey], game: &Pubkey, keeper: &Pubkey) {
+    let program_id = harness.program_id();
+    let round = harness.game(game).expect("game").current_round;
+    for (seat, player) in players.iter().enumerate() {
+        let commitment = compute_commitment(game, round, player, &salt(seat), &HANDS[seat]).expect("valid choice");
+        harness
+            .process(&[instruction::commit_choice(&program_id, player, game, commitment)])
+            .expect("commit");
+    }
+    for (seat, player) in players.iter().enumerate().take(players.len() - 1) {
+        harness
+            .process(&[instruction::reveal_choice(&program_id, player, game, HANDS[seat].clone(), salt(seat))])
+            .expect("reveal");
+    }
+    let phase_nonce = harness.game(game).expect("game").phase_nonce;
+    harness.warp_seconds(TIMEOUT_SECS as i64);
+    harness
+        .process(&[instruction::resolve_timeout(&program_id, keeper, game, phase_nonce)])
+        .expect("resolve timeout");
+}
+
+#[test]
+fn keepers_are_paid_from_the_bounty_then_the_pot() {
+    let mut harness = Harness::new(Pubkey::new_unique());
+    let program_id = harness.program_id();
+    let players: Vec<Pubkey> = (0..3).map(|_| harness.new_wallet(WALLET_LAMPORTS)).collect();
+    let keeper = harness.new_wallet(WALLET_LAMPORTS);
+    let game = Pubkey::new_unique();
+    let vault = pot_vault_address(&program_id, &game).0;
+
+    // Enough bounty for one and a half rewards
+    let bounty = REWARD + REWARD / 2;
+    harness
+        .process(&[instruction::initialize_game(&program_id, &players[0], &game, &rewarded_config(bounty))])
+        .expect("initialize game");
+    for player in &players[1..] {
+        harness
+            .process(&[instruction::join_game(&program_id, player, &game)])
+            .expect("join game");
+    }
+    let pot = harness.game(&game).expect("game").game_pot;
+
+    let before = harness.lamports(&keeper);
+    play_round_to_timeout(&mut harness, &players, &game, &keeper);
+    let state = harness.game(&game).expect("game");
+    assert_eq!(harness.lamports(&keeper) - before, REWARD);
+    assert_eq!(state.crank_bounty, REWARD / 2);
+    assert_eq!(state.game_pot, pot);
+
+    // The bounty runs out part way, and the pot makes up the rest before
+    // it is split
+    let vault_before = harness.lamports(&vault);
+    play_round_to_timeout(&mut harness, &players, &game, &keeper);
+    let state = harness.game(&game).expect("game");
+    assert!(matches!(state.state, GameState::Finished));
+    assert_eq!(harness.lamports(&keeper) - before, 2 * REWARD);
+    assert_eq!(harness.lamports(&vault), vault_before - REWARD);
+    assert_eq!(state.crank_bounty, 0);
+    assert_eq!(state.pot_snapshot, pot - REWARD / 2);
+}
+
+#[test]
+fn crank_bounties_are_deposited_with_the_game() {
+    let mut harness = Harness::new(Pubkey::new_unique());
+    let program_id = harness.program_id();
+    let host = harness.new_wallet(WALLET_LAMPORTS);
+    let game = Pubkey::new_unique();
+    let vault = pot_vault_address(&program_id, &game).0;
+
+    // A bounty with no reward to pay would be stranded
+    let stranded = GameConfig {
+        crank_reward_lamports: 0,
+        ..rewarded_config(REWARD)
+    };
+    let refused = harness.process(&[instruction::initialize_game(&program_id, &host, &game, &stranded)]);
+    assert_eq!(refused.unwrap_err().rps_error(), Some(RPSError::InvalidParameter));
+
+    // A reward above the cap could empty the pot over a few timeouts, even
+    // one the bounty would cover at first
+    let max_reward = ENTRY_FEE * MAX_CRANK_REWARD_BPS as u64 / 10_000;
+    for bounty in [0, 10 * max_reward] {
+        let greedy = GameConfig {
+            crank_reward_lamports: max_reward + 1,
+            ..rewarded_config(bounty)
+        };
+        let refused = harness.process(&[instruction::initialize_game(&program_id, &host, &game, &greedy)]);
+        assert_eq!(refused.unwrap_err().rps_error(), Some(RPSError::InvalidParameter));
+    }
+    let free = GameConfig {
+        entry_fee: 0,
+        ..rewarded_config(REWARD)
+    };
+    let refused = harness.process(&[instruction::initialize_game(&program_id, &host, &game, &free)]);
+    assert_eq!(refused.unwrap_err().rps_error(), Some(RPSError::InvalidParameter));
+
+    harness
+        .process(&[instruction::initialize_game(&program_id, &host, &game, &rewarded_config(5 * REWARD))])
+        .expect("initialize game");
+    let state = harness.game(&game).expect("game");
+    assert_eq!(state.crank_reward_lamports, REWARD);
+    assert_eq!(state.crank_bounty, 5 * REWARD);
+    let rent = harness.lamports(&vault) - state.game_pot - 5 * REWARD;
+    assert_eq!(rent, Rent::default().minimum_balance(0));
+}
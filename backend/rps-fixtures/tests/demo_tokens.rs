@@ -0,0 +1,89 @@
+//! Devnet faucet: `ClaimDemoTokens` mints test tokens to wallets with a
+//! profile, once per cooldown, and only in builds with the `devnet`
+//! feature (`cargo test -p rps-fixtures --features devnet`). The claim opens
+//! the wallet's associated token account for the demo mint when it is missing.
+
+use rps_client::instruction;
+use rps_fixtures::harness::Harness;
+use rps_fixtures::setup::WALLET_LAMPORTS;
+use solana_rps::{demo_mint_address, demo_token_address};
+use solana_sdk::pubkey::Pubkey;
+
+#[cfg(feature = "devnet")]
+#[test]
+fn profiles_claim_demo_tokens_once_a_day() {
+    use rps_fixtures::setup::token_balance;
+    use solana_rps::{
+        demo_claim_address, DemoClaim, RPSError, DEMO_CLAIM_AMOUNT, DEMO_CLAIM_COOLDOWN_SECS, DEMO_TOKEN_DECIMALS,
+    };
+    use solana_sdk::program_pack::Pack;
+
+    let mut harness = Harness::new(Pubkey::new_unique());
+    let program_id = harness.program_id();
+    let player = harness.new_wallet(WALLET_LAMPORTS);
+    let tokens = demo_token_address(&program_id, &player);
+    let claim = instruction::claim_demo_tokens(&program_id, &player);
+
+    // The faucet only serves players who have set up a profile
+    let anonymous = harness.process(std::slice::from_ref(&claim)).unwrap_err();
+    assert_eq!(anonymous.rps_error(), Some(RPSError::PlayerNotFound));
+    harness
+        .process(&[instruction::set_nickname(&program_id, &player, "newcomer", None)])
+        .expect("set nickname");
+
+    // The first claim creates the mint, which only the program can mint from,
+    // and the player's token account for it
+    assert!(harness.account(&tokens).is_none());
+    harness.process(std::slice::from_ref(&claim)).expect("claim demo tokens");
+    assert_eq!(token_balance(&harness, &tokens), DEMO_CLAIM_AMOUNT);
+    let mint_key = demo_mint_address(&program_id).0;
+    let mint = spl_token::state::Mint::unpack(&harness.account(&mint_key).expect("demo mint").data).expect("mint");
+    assert_eq!(mint.mint_authority, Some(mint_key).into());
+    assert_eq!(mint.decimals, DEMO_TOKEN_DECIMALS);
+
+    harness.warp_seconds(DEMO_CLAIM_COOLDOWN_SECS as i64 - 1);
+    let early = harness.process(std::slice::from_ref(&claim)).unwrap_err();
+    assert_eq!(early.rps_error(), Some(RPSError::InvalidGameState));
+    harness.warp_seconds(1);
+    harness.process(&[claim]).expect("claim after the cooldown");
+    assert_eq!(token_balance(&harness, &tokens), 2 * DEMO_CLAIM_AMOUNT);
+
+    let record = demo_claim_address(&program_id, &player).0;
+    let record = DemoClaim::unpack_from(&harness.account(&record).expect("claim record").data).expect("claim");
+    assert_eq!(record.total_claimed, 2 * DEMO_CLAIM_AMOUNT);
+
+    // Each wallet has its own allowance, paid only into its own account
+    let newcomer = harness.new_wallet(WALLET_LAMPORTS);
+    harness
+        .process(&[instruction::set_nickname(&program_id, &newcomer, "latecomer", None)])
+        .expect("set nickname");
+    let mut diverted = instruction::claim_demo_tokens(&program_id, &newcomer);
+    diverted.accounts[4].pubkey = tokens;
+    assert_eq!(harness.process(&[diverted]).unwrap_err().rps_error(), Some(RPSError::InvalidTokenAccount));
+    let mut stray = instruction::claim_demo_tokens(&program_id, &newcomer);
+    stray.accounts[4].pubkey = Pubkey::new_unique();
+    assert_eq!(harness.process(&[stray]).unwrap_err().rps_error(), Some(RPSError::InvalidTokenAccount));
+    harness
+        .process(&[instruction::claim_demo_tokens(&program_id, &newcomer)])
+        .expect("claim demo tokens");
+    let own = demo_token_address(&program_id, &newcomer);
+    assert_eq!(token_balance(&harness, &own), DEMO_CLAIM_AMOUNT);
+}
+
+#[cfg(not(feature = "devnet"))]
+#[test]
+fn other_builds_reject_demo_claims() {
+    use solana_sdk::instruction::InstructionError;
+
+    let mut harness = Harness::new(Pubkey::new_unique());
+    let program_id = harness.program_id();
+    let player = harness.new_wallet(WALLET_LAMPORTS);
+    harness
+        .process(&[instruction::set_nickname(&program_id, &player, "newcomer", None)])
+        .expect("set nickname");
+
+    let claim = harness.process(&[instruction::claim_demo_tokens(&program_id, &player)]);
+    assert_eq!(claim.unwrap_err().error, InstructionError::InvalidInstructionData);
+    assert!(harness.account(&demo_mint_address(&program_id).0).is_none());
+    assert!(harness.account(&demo_token_address(&program_id, &player)).is_none());
+}
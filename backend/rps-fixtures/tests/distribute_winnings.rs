@@ -0,0 +1,92 @@
+//! Push payouts: one `DistributeWinnings` crank pays every unpaid winner,
+//! with the same shares and dust as separate claims would.
+
+use rps_client::instruction::{self, GameConfig};
+use rps_fixtures::harness::Harness;
+use rps_fixtures::setup::WALLET_LAMPORTS;
+use solana_rps::{compute_commitment, Choice, RPSError};
+use solana_sdk::pubkey::Pubkey;
+
+const ENTRY_FEE: u64 = 100_000_001;
+
+// A three-seat game where both rocks beat the scissors, left finished
+fn shared_win(harness: &mut Harness, players: &[Pubkey]) -> Pubkey {
+    let program_id = harness.program_id();
+    let game = Pubkey::new_unique();
+    let config = GameConfig {
+        entry_fee: ENTRY_FEE,
+        ..GameConfig::default()
+    };
+    harness
+        .process(&[instruction::initialize_game(&program_id, &players[0], &game, &config)])
+        .expect("initialize game");
+    for player in &players[1..] {
+        harness
+            .process(&[instruction::join_game(&program_id, player, &game)])
+            .expect("join game");
+    }
+
+    let choices = [Choice::Rock, Choice::Rock, Choice::Scissors];
+    for (i, (player, choice)) in players.iter().zip(&choices).enumerate() {
+        let commitment = compute_commitment(&game, 1, player, &[i as u8 + 1; 32], choice).expect("valid choice");
+        harness
+            .process(&[instruction::commit_choice(&program_id, player, &game, commitment)])
+            .expect("commit");
+    }
+    let early = instruction::distribute_winnings(&program_id, &players[2], &game, &players[..2]);
+    assert_eq!(harness.process(&[early]).unwrap_err().rps_error(), Some(RPSError::InvalidGameState));
+    for (i, (player, choice)) in players.iter().zip(&choices).enumerate() {
+        harness
+            .process(&[instruction::reveal_choice(&program_id, player, &game, choice.clone(), [i as u8 + 1; 32])])
+            .expect("reveal");
+    }
+    game
+}
+
+#[test]
+fn one_crank_pays_every_winner() {
+    let mut harness = Harness::new(Pubkey::new_unique());
+    let program_id = harness.program_id();
+    let players: Vec<Pubkey> = (0..3).map(|_| harness.new_wallet(WALLET_LAMPORTS)).collect();
+    let game = shared_win(&mut harness, &players);
+    let pot = harness.game(&game).expect("game").pot_snapshot;
+    let cranker = harness.new_wallet(WALLET_LAMPORTS);
+
+    let swapped = instruction::distribute_winnings(&program_id, &cranker, &game, &[players[1], players[0]]);
+    assert_eq!(harness.process(&[swapped]).unwrap_err().rps_error(), Some(RPSError::InvalidParameter));
+
+    let before: Vec<u64> = players.iter().map(|p| harness.lamports(p)).collect();
+    harness
+        .process(&[instruction::distribute_winnings(&program_id, &cranker, &game, &players[..2])])
+        .expect("distribute winnings");
+    assert_eq!(harness.lamports(&players[0]) - before[0], pot / 2);
+    assert_eq!(harness.lamports(&players[1]) - before[1], pot - pot / 2);
+    assert_eq!(harness.lamports(&players[2]), before[2]);
+
+    let state = harness.game(&game).expect("game");
+    assert_eq!(state.game_pot, 0);
+    assert!(state.all_claimed());
+    let again = harness.process(&[instruction::distribute_winnings(&program_id, &cranker, &game, &[])]);
+    assert_eq!(again.unwrap_err().rps_error(), Some(RPSError::AlreadyClaimed));
+    let claim = harness.process(&[instruction::claim_winnings(&program_id, &players[0], &game)]);
+    assert_eq!(claim.unwrap_err().rps_error(), Some(RPSError::AlreadyClaimed));
+}
+
+#[test]
+fn winners_who_already_claimed_are_skipped() {
+    let mut harness = Harness::new(Pubkey::new_unique());
+    let program_id = harness.program_id();
+    let players: Vec<Pubkey> = (0..3).map(|_| harness.new_wallet(WALLET_LAMPORTS)).collect();
+    let game = shared_win(&mut harness, &players);
+    let pot = harness.game(&game).expect("game").pot_snapshot;
+
+    harness
+        .process(&[instruction::claim_winnings(&program_id, &players[0], &game)])
+        .expect("claim winnings");
+    let before = harness.lamports(&players[1]);
+    harness
+        .process(&[instruction::distribute_winnings(&program_id, &players[2], &game, &players[1..2])])
+        .expect("distribute winnings");
+    assert_eq!(harness.lamports(&players[1]) - before, pot - pot / 2);
+    assert_eq!(harness.game(&game).expect("game").game_pot, 0);
+}
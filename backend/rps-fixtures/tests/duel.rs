@@ -0,0 +1,102 @@
+//! Two-seat games: a duel is scored like any other game, and a player who
+//! goes silent in the reveal phase loses the round instead of tying it.
+
+use rps_client::instruction::{self, GameConfig};
+use rps_fixtures::harness::Harness;
+use rps_fixtures::setup::WALLET_LAMPORTS;
+use solana_rps::{compute_commitment, Choice, GameState, RPSError};
+use solana_sdk::pubkey::Pubkey;
+
+const ENTRY_FEE: u64 = 100_000_000;
+
+fn duel_config() -> GameConfig {
+    GameConfig {
+        min_players: 2,
+        max_players: 2,
+        entry_fee: ENTRY_FEE,
+        ..GameConfig::default()
+    }
+}
+
+// Opens a duel between `players` with both hands committed
+fn committed_duel(harness: &mut Harness, players: &[Pubkey], hands: &[Choice; 2]) -> Pubkey {
+    let program_id = harness.program_id();
+    let game = Pubkey::new_unique();
+    harness
+        .process(&[instruction::initialize_game(&program_id, &players[0], &game, &duel_config())])
+        .expect("initialize game");
+    harness
+        .process(&[instruction::join_game(&program_id, &players[1], &game)])
+        .expect("join game");
+    assert_eq!(harness.game(&game).expect("game").state, GameState::CommitPhase);
+    for (i, (player, hand)) in players.iter().zip(hands).enumerate() {
+        let salt = [i as u8 + 1; 32];
+        let commitment = compute_commitment(&game, 1, player, &salt, hand).expect("valid choice");
+        harness
+            .process(&[instruction::commit_choice(&program_id, player, &game, commitment)])
+            .expect("commit");
+    }
+    game
+}
+
+#[test]
+fn duel_pays_the_winner() {
+    let mut harness = Harness::new(Pubkey::new_unique());
+    let program_id = harness.program_id();
+    let players: Vec<Pubkey> = (0..2).map(|_| harness.new_wallet(WALLET_LAMPORTS)).collect();
+
+    // One seat is still not a game
+    let solo = GameConfig {
+        min_players: 1,
+        ..duel_config()
+    };
+    let rejected = harness.process(&[instruction::initialize_game(
+        &program_id,
+        &players[0],
+        &Pubkey::new_unique(),
+        &solo,
+    )]);
+    assert_eq!(rejected.unwrap_err().rps_error(), Some(RPSError::InvalidParameter));
+
+    let hands = [Choice::Paper, Choice::Rock];
+    let game = committed_duel(&mut harness, &players, &hands);
+    for (i, (player, hand)) in players.iter().zip(&hands).enumerate() {
+        harness
+            .process(&[instruction::reveal_choice(&program_id, player, &game, hand.clone(), [i as u8 + 1; 32])])
+            .expect("reveal");
+    }
+    let state = harness.game(&game).expect("game");
+    assert_eq!(state.state, GameState::Finished);
+    assert_eq!(state.winner_bitmap(), 0b01);
+
+    let before = harness.lamports(&players[0]);
+    harness
+        .process(&[instruction::claim_winnings(&program_id, &players[0], &game)])
+        .expect("claim winnings");
+    assert_eq!(harness.lamports(&players[0]) - before, state.game_pot);
+    let loser = harness.process(&[instruction::claim_winnings(&program_id, &players[1], &game)]);
+    assert_eq!(loser.unwrap_err().rps_error(), Some(RPSError::NotWinner));
+}
+
+#[test]
+fn silent_duelist_forfeits_the_round() {
+    let mut harness = Harness::new(Pubkey::new_unique());
+    let program_id = harness.program_id();
+    let players: Vec<Pubkey> = (0..2).map(|_| harness.new_wallet(WALLET_LAMPORTS)).collect();
+
+    // The second seat sees it has lost and never reveals
+    let game = committed_duel(&mut harness, &players, &[Choice::Rock, Choice::Scissors]);
+    harness
+        .process(&[instruction::reveal_choice(&program_id, &players[0], &game, Choice::Rock, [1; 32])])
+        .expect("reveal");
+
+    let state = harness.game(&game).expect("game");
+    harness.warp_seconds(state.required_timeout as i64 + 1);
+    harness
+        .process(&[instruction::resolve_timeout(&program_id, &players[0], &game, state.phase_nonce)])
+        .expect("resolve timeout");
+    let state = harness.game(&game).expect("game");
+    assert_eq!(state.state, GameState::Finished);
+    assert_eq!(state.players.iter().map(|p| p.score).collect::<Vec<_>>(), vec![1, 0]);
+    assert_eq!(state.winner_bitmap(), 0b01);
+}
@@ -0,0 +1,60 @@
+//! Entry escrow: SOL entries of a lobby that never fills can be returned by
+//! anyone once their refund deadline passes, without the host.
+
+use rps_client::instruction::{self, GameConfig};
+use rps_fixtures::harness::Harness;
+use rps_fixtures::setup::WALLET_LAMPORTS;
+use solana_rps::{RPSError, ENTRY_REFUND_SECS};
+use solana_sdk::pubkey::Pubkey;
+
+const ENTRY_FEE: u64 = 100_000_000;
+
+#[test]
+fn dead_lobbies_refund_after_the_deadline() {
+    let mut harness = Harness::new(Pubkey::new_unique());
+    let program_id = harness.program_id();
+    let players: Vec<Pubkey> = (0..3).map(|_| harness.new_wallet(WALLET_LAMPORTS)).collect();
+    let cranker = harness.new_wallet(WALLET_LAMPORTS);
+    let (dead, full) = (Pubkey::new_unique(), Pubkey::new_unique());
+
+    let config = GameConfig {
+        entry_fee: ENTRY_FEE,
+        ..GameConfig::default()
+    };
+    for (game, seated) in [(dead, &players[..2]), (full, &players[..])] {
+        harness
+            .process(&[instruction::initialize_game(&program_id, &seated[0], &game, &config)])
+            .expect("initialize game");
+        for player in &seated[1..] {
+            harness
+                .process(&[instruction::join_game(&program_id, player, &game)])
+                .expect("join game");
+        }
+    }
+    let state = harness.game(&dead).expect("game");
+    assert_eq!(state.escrows.len(), 2);
+    let share = state.escrows[1].amount;
+
+    let early = harness.process(&[instruction::refund_expired_escrow(&program_id, &cranker, &dead, &players[1])]);
+    assert_eq!(early.unwrap_err().rps_error(), Some(RPSError::TimeoutNotReached));
+
+    harness.warp_seconds(ENTRY_REFUND_SECS as i64);
+    let stranger = harness.process(&[instruction::refund_expired_escrow(&program_id, &cranker, &dead, &players[2])]);
+    assert_eq!(stranger.unwrap_err().rps_error(), Some(RPSError::PlayerNotFound));
+    let started = harness.process(&[instruction::refund_expired_escrow(&program_id, &cranker, &full, &players[1])]);
+    assert_eq!(started.unwrap_err().rps_error(), Some(RPSError::InvalidGameState));
+
+    for player in &players[..2] {
+        let before = harness.lamports(player);
+        harness
+            .process(&[instruction::refund_expired_escrow(&program_id, &cranker, &dead, player)])
+            .expect("refund expired escrow");
+        assert_eq!(harness.lamports(player) - before, share);
+    }
+    let state = harness.game(&dead).expect("game");
+    assert!(state.players.is_empty() && state.escrows.is_empty());
+    assert_eq!(state.game_pot, 0);
+
+    let again = harness.process(&[instruction::refund_expired_escrow(&program_id, &cranker, &dead, &players[1])]);
+    assert_eq!(again.unwrap_err().rps_error(), Some(RPSError::PlayerNotFound));
+}
@@ -0,0 +1,128 @@
+//! Fee policy: the operator sets the platform fee rate and the fee collector
+//! in the config PDA. Games keep the rate they were created with, and only
+//! the configured collector may collect held fees.
+
+use borsh::BorshDeserialize;
+use rps_client::instruction::{self, GameConfig};
+use rps_fixtures::harness::Harness;
+use rps_fixtures::setup::{open_lobby, packed, token_account, token_account_at, token_balance, WALLET_LAMPORTS};
+use solana_rps::{game_authority_address, game_vault_address, treasury_address, FeeQuote, RPSError};
+use solana_sdk::{program_option::COption, pubkey::Pubkey};
+
+const ENTRY_FEE: u64 = 100_000_000;
+const FEE_BPS: u16 = 250;
+
+// What the treasury takes from `player` joining `game`
+fn join_fee(harness: &mut Harness, player: &Pubkey, game: &Pubkey) -> u64 {
+    let program_id = harness.program_id();
+    let treasury = treasury_address(&program_id).0;
+    let before = harness.lamports(&treasury);
+    harness
+        .process(&[instruction::join_game(&program_id, player, game)])
+        .expect("join game");
+    harness.lamports(&treasury) - before
+}
+
+#[test]
+fn new_games_take_the_configured_rate() {
+    let mut harness = Harness::new(Pubkey::new_unique());
+    let program_id = harness.program_id();
+    let operator = harness.new_wallet(WALLET_LAMPORTS);
+    let players: Vec<Pubkey> = (0..3).map(|_| harness.new_wallet(WALLET_LAMPORTS)).collect();
+    let config = GameConfig {
+        entry_fee: ENTRY_FEE,
+        ..GameConfig::default()
+    };
+
+    // Without a config every game pays the default 1%
+    let before_config = open_lobby(&mut harness, &players[..1], &config);
+    assert_eq!(harness.game(&before_config).expect("game").fee_bps, None);
+
+    harness
+        .process(&[instruction::initialize_config(&program_id, &operator)])
+        .expect("initialize config");
+    let forged = harness.process(&[instruction::set_fee_policy(&program_id, &players[0], None, Some(FEE_BPS))]);
+    assert_eq!(forged.unwrap_err().rps_error(), Some(RPSError::NotAuthorized));
+    let greedy = harness.process(&[instruction::set_fee_policy(&program_id, &operator, None, Some(1_001))]);
+    assert_eq!(greedy.unwrap_err().rps_error(), Some(RPSError::InvalidParameter));
+    harness
+        .process(&[instruction::set_fee_policy(&program_id, &operator, None, Some(FEE_BPS))])
+        .expect("set fee policy");
+
+    harness
+        .process(&[instruction::get_fee_quote(&program_id, ENTRY_FEE, 0)])
+        .expect("fee quote");
+    let (_, data) = harness.return_data().expect("return data");
+    let quote = FeeQuote::try_from_slice(data).expect("decode quote");
+    assert_eq!(quote.fee, ENTRY_FEE * FEE_BPS as u64 / 10_000);
+
+    let configured = open_lobby(&mut harness, &players[..1], &config);
+    assert_eq!(harness.game(&configured).expect("game").fee_bps, Some(FEE_BPS));
+    assert_eq!(join_fee(&mut harness, &players[1], &configured), quote.fee);
+
+    // A game opened earlier keeps charging the rate it was created with
+    assert_eq!(join_fee(&mut harness, &players[1], &before_config), ENTRY_FEE / 100);
+}
+
+#[test]
+fn only_the_configured_collector_collects() {
+    let mut harness = Harness::new(Pubkey::new_unique());
+    let program_id = harness.program_id();
+    let operator = harness.new_wallet(WALLET_LAMPORTS);
+    let collector = harness.new_wallet(WALLET_LAMPORTS);
+    let host = harness.new_wallet(WALLET_LAMPORTS);
+    let game = Pubkey::new_unique();
+
+    harness
+        .process(&[instruction::initialize_config(&program_id, &operator)])
+        .expect("initialize config");
+    harness
+        .process(&[instruction::set_fee_policy(&program_id, &operator, Some(collector), Some(FEE_BPS))])
+        .expect("set fee policy");
+
+    let mint = Pubkey::new_unique();
+    harness.set_account(
+        mint,
+        packed(
+            spl_token::state::Mint {
+                mint_authority: COption::None,
+                supply: 30 * ENTRY_FEE,
+                decimals: 9,
+                is_initialized: true,
+                freeze_authority: COption::None,
+            },
+            spl_token::id(),
+        ),
+    );
+    let host_tokens = token_account(&mut harness, mint, host, 10 * ENTRY_FEE);
+    token_account_at(
+        &mut harness,
+        game_vault_address(&program_id, &game, &mint),
+        mint,
+        game_authority_address(&program_id, &game).0,
+        10 * ENTRY_FEE,
+    );
+    let config = GameConfig {
+        entry_fee: ENTRY_FEE,
+        currency_mode: 1,
+        token_mint: Some(mint),
+        ..GameConfig::default()
+    };
+    harness
+        .process(&[instruction::initialize_game_with_tokens(&program_id, &host, &game, &config, &host_tokens)])
+        .expect("initialize game");
+    let fees = harness.game(&game).expect("game").fee_collected;
+    assert_eq!(fees, ENTRY_FEE * FEE_BPS as u64 / 10_000);
+
+    let host_payout = token_account(&mut harness, mint, host, 10 * ENTRY_FEE);
+    let stolen = harness.process(&[instruction::collect_fees_with_tokens(&program_id, &host, &game, &mint, &host_payout)]);
+    assert_eq!(stolen.unwrap_err().rps_error(), Some(RPSError::NotAuthorized));
+
+    // The collector need not wait for the game to finish
+    let collector_tokens = token_account(&mut harness, mint, collector, 10 * ENTRY_FEE);
+    harness
+        .process(&[instruction::collect_fees_with_tokens(&program_id, &collector, &game, &mint, &collector_tokens)])
+        .expect("collect fees");
+    assert_eq!(token_balance(&harness, &collector_tokens), 10 * ENTRY_FEE + fees);
+    assert_eq!(harness.game(&game).expect("game").fee_collected, 0);
+}
@@ -0,0 +1,50 @@
+//! `GetFeeQuote` answers with the fee split a real entry is charged, so
+//! clients can show exact numbers before sending anything.
+
+use borsh::BorshDeserialize;
+use rps_client::instruction::{self, GameConfig};
+use rps_fixtures::harness::Harness;
+use rps_fixtures::setup::WALLET_LAMPORTS;
+use solana_rps::{treasury_address, FeeQuote, RPSError};
+use solana_sdk::pubkey::Pubkey;
+
+const ENTRY_FEE: u64 = 123_456_789;
+
+fn quote(harness: &mut Harness, entry_fee: u64, currency_mode: u8) -> FeeQuote {
+    let program_id = harness.program_id();
+    harness
+        .process(&[instruction::get_fee_quote(&program_id, entry_fee, currency_mode)])
+        .expect("fee quote");
+    let (_, data) = harness.return_data().expect("return data");
+    FeeQuote::try_from_slice(data).expect("decode quote")
+}
+
+#[test]
+fn quote_matches_what_an_entry_is_charged() {
+    let mut harness = Harness::new(Pubkey::new_unique());
+    let program_id = harness.program_id();
+    let players: Vec<Pubkey> = (0..2).map(|_| harness.new_wallet(WALLET_LAMPORTS)).collect();
+    let game = Pubkey::new_unique();
+
+    let sol = quote(&mut harness, ENTRY_FEE, 0);
+    assert_eq!(sol.fee + sol.pot_contribution, ENTRY_FEE);
+    assert_eq!(quote(&mut harness, ENTRY_FEE, 1), sol);
+    assert_eq!(quote(&mut harness, 0, 0), FeeQuote::default());
+    let unknown = harness.process(&[instruction::get_fee_quote(&program_id, ENTRY_FEE, 2)]);
+    assert_eq!(unknown.unwrap_err().rps_error(), Some(RPSError::InvalidParameter));
+
+    let config = GameConfig {
+        entry_fee: ENTRY_FEE,
+        ..GameConfig::default()
+    };
+    harness
+        .process(&[instruction::initialize_game(&program_id, &players[0], &game, &config)])
+        .expect("initialize game");
+    let treasury = treasury_address(&program_id).0;
+    let (treasury_before, pot_before) = (harness.lamports(&treasury), harness.game(&game).expect("game").game_pot);
+    harness
+        .process(&[instruction::join_game(&program_id, &players[1], &game)])
+        .expect("join game");
+    assert_eq!(harness.lamports(&treasury) - treasury_before, sol.fee);
+    assert_eq!(harness.game(&game).expect("game").game_pot - pot_before, sol.pot_contribution);
+}
@@ -0,0 +1,57 @@
+//! `RecordGameResult` writes a finished game's outcome to a write-once PDA.
+
+use rps_client::instruction::{self, GameConfig};
+use rps_fixtures::harness::Harness;
+use rps_fixtures::setup::WALLET_LAMPORTS;
+use solana_rps::{compute_commitment, game_result_address, Choice, GameResult, RPSError};
+use solana_sdk::pubkey::Pubkey;
+
+#[test]
+fn finished_game_result_is_recorded_once() {
+    let mut harness = Harness::new(Pubkey::new_unique());
+    let program_id = harness.program_id();
+    let players: Vec<Pubkey> = (0..3).map(|_| harness.new_wallet(WALLET_LAMPORTS)).collect();
+    let game = Pubkey::new_unique();
+    let payer = harness.new_wallet(WALLET_LAMPORTS);
+
+    harness
+        .process(&[instruction::initialize_game(&program_id, &players[0], &game, &GameConfig::default())])
+        .expect("initialize game");
+    for player in &players[1..] {
+        harness
+            .process(&[instruction::join_game(&program_id, player, &game)])
+            .expect("join game");
+    }
+
+    let unfinished = harness.process(&[instruction::record_game_result(&program_id, &payer, &game)]);
+    assert_eq!(unfinished.unwrap_err().rps_error(), Some(RPSError::InvalidGameState));
+
+    let choices = [Choice::Paper, Choice::Rock, Choice::Rock];
+    for (i, (player, choice)) in players.iter().zip(&choices).enumerate() {
+        let salt = [i as u8 + 1; 32];
+        let commitment = compute_commitment(&game, 1, player, &salt, choice).expect("valid choice");
+        harness
+            .process(&[instruction::commit_choice(&program_id, player, &game, commitment)])
+            .expect("commit");
+    }
+    for (i, (player, choice)) in players.iter().zip(&choices).enumerate() {
+        let salt = [i as u8 + 1; 32];
+        harness
+            .process(&[instruction::reveal_choice(&program_id, player, &game, choice.clone(), salt)])
+            .expect("reveal");
+    }
+
+    harness
+        .process(&[instruction::record_game_result(&program_id, &payer, &game)])
+        .expect("record result");
+    let address = game_result_address(&program_id, &game).0;
+    let result = GameResult::unpack_from(&harness.account(&address).expect("result account").data).unwrap();
+    assert_eq!(result.game, game);
+    assert_eq!(result.players, players);
+    assert_eq!(result.scores, vec![2, 0, 0]);
+    assert_eq!(result.winners, 0b001);
+    assert_eq!(result.recorded_at, harness.clock().unix_timestamp as u64);
+
+    let again = harness.process(&[instruction::record_game_result(&program_id, &payer, &game)]);
+    assert_eq!(again.unwrap_err().rps_error(), Some(RPSError::InvalidGameState));
+}
@@ -0,0 +1,38 @@
+//! Game rules: every variant is a fair cycle over its own hands, and the
+//! lizard-Spock rules extend the classic ones without changing them.
+
+use std::cmp::Ordering;
+
+use rps_state::rules::{Classic, GameRules, LizardSpock, RuleSet};
+use rps_state::simulate::Hand;
+
+#[test]
+fn every_rule_set_is_balanced() {
+    for byte in 0..=u8::MAX {
+        let Some(rule_set) = RuleSet::from_byte(byte) else {
+            continue;
+        };
+        let rules = rule_set.rules();
+        let hands = rules.valid_choices();
+        assert_eq!(hands.len() % 2, 1, "{:?} needs an odd number of hands to have no dead draws", rule_set);
+        for &a in hands {
+            assert_eq!(rules.beats(a, a), Ordering::Equal);
+            for &b in hands {
+                assert_eq!(rules.beats(a, b), rules.beats(b, a).reverse());
+            }
+            let wins = hands.iter().filter(|&&b| rules.beats(a, b).is_gt()).count();
+            assert_eq!(wins, hands.len() / 2, "{:?} beats too many or too few under {:?}", a, rule_set);
+        }
+    }
+}
+
+#[test]
+fn lizard_spock_keeps_the_classic_outcomes() {
+    let classic = Classic.valid_choices();
+    assert!(!Classic.allows(Hand::Lizard) && !Classic.allows(Hand::Spock));
+    for &a in classic {
+        for &b in classic {
+            assert_eq!(LizardSpock.beats(a, b), Classic.beats(a, b));
+        }
+    }
+}
@@ -0,0 +1,165 @@
+//! Governance: once stakers govern, fee and payout settings change only
+//! through proposals that reach quorum and win a majority of the stake.
+
+use borsh::BorshDeserialize;
+use rps_client::instruction;
+use rps_fixtures::harness::Harness;
+use rps_fixtures::setup::{packed, token_account_at, token_balance, WALLET_LAMPORTS};
+use solana_rps::{governance_address, governance_vault_address, FeeQuote, ParameterChange, RPSError};
+use solana_sdk::{program_option::COption, pubkey::Pubkey};
+
+const QUORUM: u64 = 150;
+const VOTING_SECS: u64 = 3_600;
+const NEW_FEE_BPS: u16 = 250;
+
+struct Dao {
+    harness: Harness,
+    operator: Pubkey,
+    mint: Pubkey,
+}
+
+impl Dao {
+    // Config and governance over a fresh mint, with its vault in place
+    fn open() -> Self {
+        let mut harness = Harness::new(Pubkey::new_unique());
+        let program_id = harness.program_id();
+        let operator = harness.new_wallet(WALLET_LAMPORTS);
+        let mint = Pubkey::new_unique();
+        harness.set_account(
+            mint,
+            packed(
+                spl_token::state::Mint {
+                    mint_authority: COption::None,
+                    supply: 1_000,
+                    decimals: 0,
+                    is_initialized: true,
+                    freeze_authority: COption::None,
+                },
+                spl_token::id(),
+            ),
+        );
+        token_account_at(
+            &mut harness,
+            governance_vault_address(&program_id, &mint),
+            mint,
+            governance_address(&program_id).0,
+            0,
+        );
+        harness
+            .process(&[instruction::initialize_config(&program_id, &operator)])
+            .expect("initialize config");
+        harness
+            .process(&[instruction::initialize_governance(&program_id, &operator, &mint, QUORUM, VOTING_SECS)])
+            .expect("initialize governance");
+        Dao { harness, operator, mint }
+    }
+
+    // A wallet that has staked `amount`, and its token account
+    fn staker(&mut self, amount: u64) -> (Pubkey, Pubkey) {
+        let program_id = self.harness.program_id();
+        let wallet = self.harness.new_wallet(WALLET_LAMPORTS);
+        let tokens = token_account_at(&mut self.harness, Pubkey::new_unique(), self.mint, wallet, amount);
+        self.harness
+            .process(&[instruction::stake_governance_tokens(&program_id, &wallet, &self.mint, &tokens, amount)])
+            .expect("stake");
+        (wallet, tokens)
+    }
+
+    // Fee quoted on an entry of 10_000, which is the rate in bps
+    fn quoted_rate(&mut self) -> u64 {
+        let program_id = self.harness.program_id();
+        self.harness
+            .process(&[instruction::get_fee_quote(&program_id, 10_000, 0)])
+            .expect("fee quote");
+        let (_, data) = self.harness.return_data().expect("return data");
+        FeeQuote::try_from_slice(data).expect("decode quote").fee
+    }
+}
+
+fn fee_change() -> ParameterChange {
+    ParameterChange::FeePolicy {
+        fee_collector: None,
+        fee_bps: Some(NEW_FEE_BPS),
+    }
+}
+
+#[test]
+fn stakers_vote_in_a_fee_change() {
+    let mut dao = Dao::open();
+    let program_id = dao.harness.program_id();
+    let (whale, whale_tokens) = dao.staker(100);
+    let (minnow, _) = dao.staker(60);
+
+    // The operator no longer sets fees alone
+    let unilateral = dao.harness.process(&[instruction::set_fee_policy(&program_id, &dao.operator, None, Some(1))]);
+    assert_eq!(unilateral.unwrap_err().rps_error(), Some(RPSError::NotAuthorized));
+    let outsider = dao.harness.new_wallet(WALLET_LAMPORTS);
+    let proposal = instruction::propose_parameter_change(&program_id, &outsider, 0, fee_change());
+    let unstaked = dao.harness.process(&[proposal]);
+    assert_eq!(unstaked.unwrap_err().rps_error(), Some(RPSError::NotAuthorized));
+
+    dao.harness
+        .process(&[instruction::propose_parameter_change(&program_id, &whale, 0, fee_change())])
+        .expect("propose");
+    dao.harness
+        .process(&[instruction::cast_vote(&program_id, &whale, 0, true)])
+        .expect("vote for");
+    dao.harness
+        .process(&[instruction::cast_vote(&program_id, &minnow, 0, false)])
+        .expect("vote against");
+    let twice = dao.harness.process(&[instruction::cast_vote(&program_id, &whale, 0, true)]);
+    assert_eq!(twice.unwrap_err().rps_error(), Some(RPSError::InvalidGameState));
+
+    // Votes lock the stake and the result until voting closes
+    let early = dao.harness.process(&[instruction::execute_proposal(&program_id, &minnow, 0)]);
+    assert_eq!(early.unwrap_err().rps_error(), Some(RPSError::TimeoutNotReached));
+    let unstake = instruction::unstake_governance_tokens(&program_id, &whale, &dao.mint, &whale_tokens, 100);
+    let locked = dao.harness.process(std::slice::from_ref(&unstake));
+    assert_eq!(locked.unwrap_err().rps_error(), Some(RPSError::InvalidGameState));
+
+    dao.harness.warp_seconds(VOTING_SECS as i64);
+    assert_eq!(dao.quoted_rate(), 100);
+    dao.harness
+        .process(&[instruction::execute_proposal(&program_id, &minnow, 0)])
+        .expect("execute");
+    assert_eq!(dao.quoted_rate(), NEW_FEE_BPS as u64);
+    let again = dao.harness.process(&[instruction::execute_proposal(&program_id, &minnow, 0)]);
+    assert_eq!(again.unwrap_err().rps_error(), Some(RPSError::InvalidGameState));
+
+    dao.harness.process(&[unstake]).expect("unstake");
+    assert_eq!(token_balance(&dao.harness, &whale_tokens), 100);
+}
+
+#[test]
+fn proposals_short_of_quorum_or_majority_fail() {
+    let mut dao = Dao::open();
+    let program_id = dao.harness.program_id();
+    let (small, _) = dao.staker(50);
+    let (large, _) = dao.staker(120);
+
+    // 50 for: no quorum
+    dao.harness
+        .process(&[instruction::propose_parameter_change(&program_id, &small, 0, fee_change())])
+        .expect("propose");
+    dao.harness
+        .process(&[instruction::cast_vote(&program_id, &small, 0, true)])
+        .expect("vote");
+
+    // 50 for, 120 against: quorum but no majority
+    dao.harness
+        .process(&[instruction::propose_parameter_change(&program_id, &small, 1, fee_change())])
+        .expect("propose");
+    dao.harness
+        .process(&[instruction::cast_vote(&program_id, &small, 1, true)])
+        .expect("vote for");
+    dao.harness
+        .process(&[instruction::cast_vote(&program_id, &large, 1, false)])
+        .expect("vote against");
+
+    dao.harness.warp_seconds(VOTING_SECS as i64);
+    for index in 0..2 {
+        let failed = dao.harness.process(&[instruction::execute_proposal(&program_id, &large, index)]);
+        assert_eq!(failed.unwrap_err().rps_error(), Some(RPSError::InvalidGameState));
+    }
+    assert_eq!(dao.quoted_rate(), 100);
+}
@@ -0,0 +1,121 @@
+//! Insurance fund: it takes a share of every treasury sweep, and pays
+//! claims for funds lost to program faults only as the operator rules.
+
+use rps_client::instruction;
+use rps_fixtures::harness::Harness;
+use rps_fixtures::setup::WALLET_LAMPORTS;
+use solana_rps::{
+    insurance_claim_address, insurance_fund_address, treasury_address, InsuranceClaim, InsuranceFund, RPSError,
+    MAX_INSURANCE_SHARE_BPS,
+};
+use solana_sdk::{account::Account, pubkey::Pubkey, rent::Rent, system_program};
+
+const SOL: u64 = 1_000_000_000;
+const SHARE_BPS: u16 = 1_000;
+
+struct Fund {
+    harness: Harness,
+    operator: Pubkey,
+}
+
+impl Fund {
+    // An insurance fund holding a tenth of a 10 SOL sweep
+    fn open() -> Self {
+        let mut harness = Harness::new(Pubkey::new_unique());
+        let program_id = harness.program_id();
+        let operator = harness.new_wallet(WALLET_LAMPORTS);
+        let signers: Vec<Pubkey> = (0..2).map(|_| harness.new_wallet(WALLET_LAMPORTS)).collect();
+        harness
+            .process(&[instruction::initialize_config(&program_id, &operator)])
+            .expect("initialize config");
+        harness
+            .process(&[instruction::initialize_cold_treasury(&program_id, &operator, signers, 2, 24 * 60 * 60, SOL)])
+            .expect("initialize cold treasury");
+
+        let greedy = instruction::configure_insurance_fund(&program_id, &operator, MAX_INSURANCE_SHARE_BPS + 1);
+        assert_eq!(harness.process(&[greedy]).unwrap_err().rps_error(), Some(RPSError::InvalidParameter));
+        let stranger = harness.new_wallet(WALLET_LAMPORTS);
+        let outsider = instruction::configure_insurance_fund(&program_id, &stranger, SHARE_BPS);
+        assert_eq!(harness.process(&[outsider]).unwrap_err().rps_error(), Some(RPSError::NotAuthorized));
+        harness
+            .process(&[instruction::configure_insurance_fund(&program_id, &operator, SHARE_BPS)])
+            .expect("configure insurance fund");
+
+        let treasury = treasury_address(&program_id).0;
+        harness.set_account(treasury, Account::new(11 * SOL, 0, &system_program::id()));
+        harness
+            .process(&[instruction::sweep_treasury(&program_id, &stranger)])
+            .expect("sweep");
+        Fund { harness, operator }
+    }
+
+    fn state(&self) -> InsuranceFund {
+        let fund = insurance_fund_address(&self.harness.program_id()).0;
+        InsuranceFund::unpack_from(&self.harness.account(&fund).expect("fund account").data).expect("decode fund")
+    }
+
+    fn claim(&self, index: u64) -> InsuranceClaim {
+        let claim = insurance_claim_address(&self.harness.program_id(), index).0;
+        InsuranceClaim::unpack_from(&self.harness.account(&claim).expect("claim account").data).expect("decode claim")
+    }
+
+    fn file(&mut self, claimant: &Pubkey, index: u64, requested: u64) {
+        let program_id = self.harness.program_id();
+        self.harness
+            .process(&[instruction::file_insurance_claim(&program_id, claimant, index, requested, [index as u8; 32])])
+            .expect("file claim");
+    }
+
+    fn resolve(&mut self, index: u64, claimant: &Pubkey, amount: u64) -> Option<RPSError> {
+        let program_id = self.harness.program_id();
+        let resolve = instruction::resolve_insurance_claim(&program_id, &self.operator, index, claimant, amount);
+        self.harness.process(&[resolve]).err().map(|failure| failure.rps_error().expect("program error"))
+    }
+}
+
+#[test]
+fn sweeps_pay_the_fund_its_share() {
+    let fund = Fund::open();
+    let program_id = fund.harness.program_id();
+    let address = insurance_fund_address(&program_id).0;
+    let rent = Rent::default().minimum_balance(InsuranceFund::SPACE);
+
+    assert_eq!(fund.state().contributed, SOL);
+    assert_eq!(fund.harness.lamports(&address) - rent, SOL);
+    assert_eq!(fund.harness.lamports(&treasury_address(&program_id).0), SOL);
+}
+
+#[test]
+fn claims_pay_only_what_the_operator_allows() {
+    let mut fund = Fund::open();
+    let program_id = fund.harness.program_id();
+    let claimant = fund.harness.new_wallet(WALLET_LAMPORTS);
+
+    let nothing = instruction::file_insurance_claim(&program_id, &claimant, 0, 0, [0; 32]);
+    assert_eq!(fund.harness.process(&[nothing]).unwrap_err().rps_error(), Some(RPSError::InvalidParameter));
+    fund.file(&claimant, 0, SOL / 2);
+    let claim = fund.claim(0);
+    assert_eq!((claim.claimant, claim.requested, claim.resolved), (claimant, SOL / 2, false));
+
+    // The claimant cannot rule on their own claim, nor get more than asked
+    let own = instruction::resolve_insurance_claim(&program_id, &claimant, 0, &claimant, SOL / 2);
+    assert_eq!(fund.harness.process(&[own]).unwrap_err().rps_error(), Some(RPSError::NotAuthorized));
+    assert_eq!(fund.resolve(0, &claimant, SOL), Some(RPSError::InvalidParameter));
+
+    let before = fund.harness.lamports(&claimant);
+    assert_eq!(fund.resolve(0, &claimant, SOL / 4), None);
+    assert_eq!(fund.harness.lamports(&claimant) - before, SOL / 4);
+    assert_eq!((fund.claim(0).resolved, fund.claim(0).paid), (true, SOL / 4));
+    assert_eq!(fund.state().paid_out, SOL / 4);
+    assert_eq!(fund.resolve(0, &claimant, 0), Some(RPSError::InvalidGameState));
+
+    // A rejection is recorded as a ruling that paid nothing
+    fund.file(&claimant, 1, SOL);
+    assert_eq!(fund.resolve(1, &claimant, 0), None);
+    assert_eq!((fund.claim(1).resolved, fund.claim(1).paid), (true, 0));
+
+    // The fund never pays out more than it holds
+    fund.file(&claimant, 2, 2 * SOL);
+    assert_eq!(fund.resolve(2, &claimant, 2 * SOL), Some(RPSError::InsufficientFunds));
+    assert_eq!(fund.state().claim_count, 3);
+}
@@ -0,0 +1,175 @@
+//! Kill switch: the operator can pause single instructions, or just entries
+//! into token-mode games, while the rest of the program stays live.
+
+use borsh::BorshSerialize;
+use rps_client::instruction::{self, GameConfig};
+use rps_fixtures::harness::Harness;
+use rps_fixtures::setup::{open_lobby, WALLET_LAMPORTS};
+use solana_rps::{compute_commitment, Choice, CurrencyMode, RPSError, RPSInstruction, PAUSABLE_INSTRUCTIONS};
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::pubkey::Pubkey;
+
+const JOIN_GAME: u32 = 1;
+const COMMIT_CHOICE: u32 = 2;
+const ADD_BOT_PLAYERS: u32 = 9;
+const RESOLVE_TIMEOUT: u32 = 4;
+
+// A lobby hosted by `host`, switched to token mode after the fact so it
+// joins without token accounts
+fn token_lobby(harness: &mut Harness, host: &Pubkey) -> Pubkey {
+    let game = open_lobby(harness, &[*host], &GameConfig::default());
+    let mut state = harness.game(&game).expect("game");
+    state.currency_mode = CurrencyMode::RPSToken;
+    let mut account = harness.account(&game).expect("game account").clone();
+    let bytes = state.try_to_vec().expect("game serialize");
+    account.data[..bytes.len()].copy_from_slice(&bytes);
+    harness.set_account(game, account);
+    game
+}
+
+// JoinGameWithSwap with an empty route: the swap accounts, then the
+// JoinGame tail. Only reaches the pause check here.
+fn swap_join(program_id: &Pubkey, player: &Pubkey, game: &Pubkey) -> Instruction {
+    let join = instruction::join_game(program_id, player, game);
+    let mut accounts = join.accounts[..3].to_vec();
+    accounts.extend([
+        AccountMeta::new_readonly(Pubkey::new_unique(), false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new(Pubkey::new_unique(), false),
+        AccountMeta::new(Pubkey::new_unique(), false),
+    ]);
+    accounts.extend_from_slice(&join.accounts[3..]);
+    let data = RPSInstruction::JoinGameWithSwap {
+        usdc_amount_in: 1,
+        max_slippage_bps: 0,
+        route_accounts_len: 0,
+        route_data: Vec::new(),
+    };
+    Instruction::new_with_bytes(*program_id, &data.try_to_vec().expect("serialize"), accounts)
+}
+
+// JoinGameWithMint staking a mint the game never approved
+fn mint_join(program_id: &Pubkey, player: &Pubkey, game: &Pubkey) -> Instruction {
+    let [base_feed, mint_feed, tokens, vault] = [(); 4].map(|_| Pubkey::new_unique());
+    instruction::join_game_with_mint(program_id, player, game, &base_feed, &mint_feed, &tokens, &vault, 0)
+}
+
+#[test]
+fn paused_instructions_fail_until_lifted() {
+    let mut harness = Harness::new(Pubkey::new_unique());
+    let program_id = harness.program_id();
+    let operator = harness.new_wallet(WALLET_LAMPORTS);
+    let players: Vec<Pubkey> = (0..2).map(|_| harness.new_wallet(WALLET_LAMPORTS)).collect();
+
+    harness
+        .process(&[instruction::initialize_config(&program_id, &operator)])
+        .expect("initialize config");
+    let game = open_lobby(&mut harness, &players[..1], &GameConfig::default());
+    let token_game = token_lobby(&mut harness, &players[0]);
+
+    let forged = harness.process(&[instruction::set_paused_instructions(&program_id, &players[0], 1 << ADD_BOT_PLAYERS, false)]);
+    assert_eq!(forged.unwrap_err().rps_error(), Some(RPSError::NotAuthorized));
+
+    // Only AddBotPlayers is off
+    harness
+        .process(&[instruction::set_paused_instructions(&program_id, &operator, 1 << ADD_BOT_PLAYERS, false)])
+        .expect("pause bots");
+    let bots = harness.process(&[instruction::add_bot_players(&program_id, &players[0], &game, 1, 0)]);
+    assert_eq!(bots.unwrap_err().rps_error(), Some(RPSError::InstructionPaused));
+    harness
+        .process(&[instruction::join_game(&program_id, &players[1], &game)])
+        .expect("join game");
+
+    // Only token-mode entries are off
+    harness
+        .process(&[instruction::set_paused_instructions(&program_id, &operator, 0, true)])
+        .expect("pause token entries");
+    let token_join = harness.process(&[instruction::join_game(&program_id, &players[1], &token_game)]);
+    assert_eq!(token_join.unwrap_err().rps_error(), Some(RPSError::InstructionPaused));
+    harness
+        .process(&[instruction::add_bot_players(&program_id, &players[0], &game, 1, 0)])
+        .expect("add bots to a SOL game");
+
+    // Pausing everything still leaves the switch itself usable
+    harness
+        .process(&[instruction::set_paused_instructions(&program_id, &operator, PAUSABLE_INSTRUCTIONS, false)])
+        .expect("pause everything");
+    let joins = harness.process(&[instruction::join_game(&program_id, &players[1], &token_game)]);
+    assert_eq!(joins.unwrap_err().rps_error(), Some(RPSError::InstructionPaused));
+    harness
+        .process(&[instruction::set_paused_instructions(&program_id, &operator, 0, false)])
+        .expect("lift pauses");
+    harness
+        .process(&[instruction::join_game(&program_id, &players[1], &token_game)])
+        .expect("join token game");
+}
+
+#[test]
+fn every_token_entry_path_honours_the_switch() {
+    let mut harness = Harness::new(Pubkey::new_unique());
+    let program_id = harness.program_id();
+    let operator = harness.new_wallet(WALLET_LAMPORTS);
+    let host = harness.new_wallet(WALLET_LAMPORTS);
+    let player = harness.new_wallet(WALLET_LAMPORTS);
+    harness
+        .process(&[instruction::initialize_config(&program_id, &operator)])
+        .expect("initialize config");
+    let token_game = token_lobby(&mut harness, &host);
+
+    // Open, the swap and mint joins get past the switch to their own checks
+    let swap = harness.process(&[swap_join(&program_id, &player, &token_game)]).unwrap_err();
+    assert_ne!(swap.rps_error(), Some(RPSError::InstructionPaused));
+    let mint = harness.process(&[mint_join(&program_id, &player, &token_game)]).unwrap_err();
+    assert_ne!(mint.rps_error(), Some(RPSError::InstructionPaused));
+
+    harness
+        .process(&[instruction::set_paused_instructions(&program_id, &operator, 0, true)])
+        .expect("pause token entries");
+    for join in [swap_join(&program_id, &player, &token_game), mint_join(&program_id, &player, &token_game)] {
+        assert_eq!(harness.process(&[join]).unwrap_err().rps_error(), Some(RPSError::InstructionPaused));
+    }
+
+    // A swap join seats the player through JoinGame, so pausing JoinGame
+    // stops it as well
+    harness
+        .process(&[instruction::set_paused_instructions(&program_id, &operator, 1 << JOIN_GAME, false)])
+        .expect("pause joins");
+    let swap = harness.process(&[swap_join(&program_id, &player, &token_game)]).unwrap_err();
+    assert_eq!(swap.rps_error(), Some(RPSError::InstructionPaused));
+}
+
+#[test]
+fn pauses_reach_gameplay_and_only_what_carries_the_config() {
+    let mut harness = Harness::new(Pubkey::new_unique());
+    let program_id = harness.program_id();
+    let operator = harness.new_wallet(WALLET_LAMPORTS);
+    let players: Vec<Pubkey> = (0..3).map(|_| harness.new_wallet(WALLET_LAMPORTS)).collect();
+    harness
+        .process(&[instruction::initialize_config(&program_id, &operator)])
+        .expect("initialize config");
+    let game = open_lobby(&mut harness, &players, &GameConfig::default());
+    let commitment = compute_commitment(&game, 1, &players[0], &[7; 32], &Choice::Rock).expect("valid choice");
+    let commit = instruction::commit_choice(&program_id, &players[0], &game, commitment);
+
+    // ResolveTimeout does not carry the config, so a pause could never be
+    // enforced on it
+    let unreachable = instruction::set_paused_instructions(&program_id, &operator, 1 << RESOLVE_TIMEOUT, false);
+    assert_eq!(harness.process(&[unreachable]).unwrap_err().rps_error(), Some(RPSError::InvalidParameter));
+
+    harness
+        .process(&[instruction::set_paused_instructions(&program_id, &operator, 1 << COMMIT_CHOICE, false)])
+        .expect("pause commits");
+    let paused = harness.process(std::slice::from_ref(&commit)).unwrap_err();
+    assert_eq!(paused.rps_error(), Some(RPSError::InstructionPaused));
+
+    // Leaving the config out does not slip past the pause
+    let mut without_config = commit.clone();
+    without_config.accounts[2].pubkey = Pubkey::new_unique();
+    let dodged = harness.process(&[without_config]).unwrap_err();
+    assert_eq!(dodged.rps_error(), Some(RPSError::InvalidParameter));
+
+    harness
+        .process(&[instruction::set_paused_instructions(&program_id, &operator, 0, false)])
+        .expect("lift pauses");
+    harness.process(&[commit]).expect("commit");
+}
@@ -0,0 +1,158 @@
+//! Lobbies of up to eight seats: they play out like small ones, and their
+//! accounts are sized from the real Borsh layout of a full game.
+
+use borsh::BorshSerialize;
+use rps_client::instruction::{self, GameConfig};
+use rps_fixtures::harness::Harness;
+use rps_fixtures::setup::WALLET_LAMPORTS;
+use solana_rps::{
+    compute_commitment, BotSeat, BotStrategy, Choice, EntryEscrow, Game, GameState, HiddenFee, Payout, PendingClaim,
+    RPSError, SeatWatch, StakeVault,
+};
+use solana_sdk::pubkey::Pubkey;
+
+const SEATS: u8 = 8;
+const HANDS: [Choice; 3] = [Choice::Rock, Choice::Paper, Choice::Scissors];
+
+fn beats(a: &Choice, b: &Choice) -> bool {
+    matches!(
+        (a, b),
+        (Choice::Rock, Choice::Scissors) | (Choice::Paper, Choice::Rock) | (Choice::Scissors, Choice::Paper)
+    )
+}
+
+// Plays one round of a full eight-seat game, seat i throwing `hands[i]`
+fn play_eight_seats(harness: &mut Harness, hands: &[Choice]) -> Pubkey {
+    let program_id = harness.program_id();
+    let players: Vec<Pubkey> = (0..SEATS).map(|_| harness.new_wallet(WALLET_LAMPORTS)).collect();
+    let game = Pubkey::new_unique();
+    let config = GameConfig {
+        min_players: SEATS,
+        max_players: SEATS,
+        entry_fee: 10_000_000,
+        ..GameConfig::default()
+    };
+    harness
+        .process(&[instruction::initialize_game(&program_id, &players[0], &game, &config)])
+        .expect("initialize game");
+    for player in &players[1..] {
+        harness
+            .process(&[instruction::join_game(&program_id, player, &game)])
+            .expect("join game");
+    }
+    for (i, (player, hand)) in players.iter().zip(hands).enumerate() {
+        let salt = [i as u8 + 1; 32];
+        let commitment = compute_commitment(&game, 1, player, &salt, hand).expect("valid choice");
+        harness
+            .process(&[instruction::commit_choice(&program_id, player, &game, commitment)])
+            .expect("commit");
+    }
+    for (i, (player, hand)) in players.iter().zip(hands).enumerate() {
+        harness
+            .process(&[instruction::reveal_choice(&program_id, player, &game, hand.clone(), [i as u8 + 1; 32])])
+            .expect("reveal");
+    }
+    game
+}
+
+#[test]
+fn eight_seats_score_every_pair() {
+    let mut harness = Harness::new(Pubkey::new_unique());
+    let hands: Vec<Choice> = (0..SEATS as usize).map(|i| HANDS[i % 3].clone()).collect();
+    let game = play_eight_seats(&mut harness, &hands);
+
+    let state = harness.game(&game).expect("game");
+    assert_eq!(state.state, GameState::Finished);
+    let expected: Vec<u8> = hands
+        .iter()
+        .map(|hand| hands.iter().filter(|other| beats(hand, other)).count() as u8)
+        .collect();
+    assert_eq!(state.players.iter().map(|p| p.score).collect::<Vec<_>>(), expected);
+
+    // Nine seats is past what the per-seat bitmaps can hold
+    let program_id = harness.program_id();
+    let host = harness.new_wallet(WALLET_LAMPORTS);
+    let config = GameConfig {
+        min_players: SEATS + 1,
+        max_players: SEATS + 1,
+        ..GameConfig::default()
+    };
+    let too_big = harness.process(&[instruction::initialize_game(&program_id, &host, &Pubkey::new_unique(), &config)]);
+    assert_eq!(too_big.unwrap_err().rps_error(), Some(RPSError::InvalidParameter));
+}
+
+#[test]
+fn max_size_is_the_size_of_a_full_game() {
+    let mut harness = Harness::new(Pubkey::new_unique());
+    let hands: Vec<Choice> = (0..SEATS as usize).map(|i| HANDS[i % 3].clone()).collect();
+    let game = play_eight_seats(&mut harness, &hands);
+    assert_eq!(harness.account(&game).expect("game account").data.len(), Game::get_max_size(SEATS));
+
+    // Fill every vector and option the program bounds by the seat count
+    let mut full = harness.game(&game).expect("game");
+    let seats: Vec<Pubkey> = full.players.iter().map(|p| p.pubkey).collect();
+    full.token_mint = Some(Pubkey::new_unique());
+    full.base_price_feed = Some(Pubkey::new_unique());
+    full.stake_vaults = (0..3)
+        .map(|_| StakeVault {
+            mint: Pubkey::new_unique(),
+            price_feed: Pubkey::new_unique(),
+            vault: Pubkey::new_unique(),
+            decimals: 6,
+            balance: 1,
+            fees: 1,
+        })
+        .collect();
+    full.tournament = Some(Pubkey::new_unique());
+    full.team_lineup = seats[..4].to_vec();
+    full.seat_watch = seats
+        .iter()
+        .map(|player| SeatWatch {
+            player: *player,
+            last_hand: 1,
+            hand_streak: 1,
+            lost_to: Some(seats[0]),
+            loss_streak: 1,
+        })
+        .collect();
+    full.bot_funders = seats.clone();
+    full.pending_claims = seats
+        .iter()
+        .map(|player| PendingClaim {
+            player: *player,
+            ready_at: 1,
+        })
+        .collect();
+    full.escrows = seats
+        .iter()
+        .map(|player| EntryEscrow {
+            player: *player,
+            amount: 1,
+            refund_after: 1,
+        })
+        .collect();
+    full.reveal_order = (0..SEATS).collect();
+    full.hidden_fee = Some(HiddenFee {
+        commitment: [1; 32],
+        min_fee: 1,
+    });
+    full.fee_bps = Some(100);
+    full.slashed = seats.clone();
+    full.bot_config = seats
+        .iter()
+        .map(|player| BotSeat {
+            pubkey: *player,
+            strategy: BotStrategy::Weighted,
+            funder: *player,
+        })
+        .collect();
+    full.settlement.payouts = seats
+        .iter()
+        .map(|player| Payout {
+            player: *player,
+            amount: 1,
+        })
+        .collect();
+
+    assert_eq!(full.try_to_vec().expect("game serializes").len(), Game::get_max_size(SEATS));
+}
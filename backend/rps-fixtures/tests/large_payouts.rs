@@ -0,0 +1,144 @@
+//! Two-step payouts: pots above the operator's threshold are claimed with
+//! InitiateClaim and, after a delay, FinalizeClaim, so a flag raised in
+//! between stops the money.
+
+use rps_client::{
+    instruction::{self, GameConfig},
+    subscribe::{parse_log_event, GameEvent},
+};
+use rps_fixtures::harness::Harness;
+use rps_fixtures::setup::WALLET_LAMPORTS;
+use solana_rps::{compute_commitment, Choice, RPSError};
+use solana_sdk::pubkey::Pubkey;
+
+const ENTRY_FEE: u64 = 100_000_000;
+const CLAIM_DELAY: u64 = 3_600;
+
+// Plays a paid game of `rounds[i]` hands per seat, host first
+fn play(harness: &mut Harness, players: &[Pubkey], rounds: &[[Choice; 3]]) -> Pubkey {
+    let program_id = harness.program_id();
+    let game = Pubkey::new_unique();
+    let config = GameConfig {
+        entry_fee: ENTRY_FEE,
+        total_rounds: rounds.len() as u8,
+        ..GameConfig::default()
+    };
+    harness
+        .process(&[instruction::initialize_game(&program_id, &players[0], &game, &config)])
+        .expect("initialize game");
+    for player in &players[1..] {
+        harness
+            .process(&[instruction::join_game(&program_id, player, &game)])
+            .expect("join game");
+    }
+    for (round, hands) in rounds.iter().enumerate() {
+        for (i, (player, hand)) in players.iter().zip(hands).enumerate() {
+            let salt = [(round * 3 + i) as u8 + 1; 32];
+            let commitment = compute_commitment(&game, round as u8 + 1, player, &salt, hand).expect("valid choice");
+            harness
+                .process(&[instruction::commit_choice(&program_id, player, &game, commitment)])
+                .expect("commit");
+        }
+        for (i, (player, hand)) in players.iter().zip(hands).enumerate() {
+            let salt = [(round * 3 + i) as u8 + 1; 32];
+            harness
+                .process(&[instruction::reveal_choice(&program_id, player, &game, hand.clone(), salt)])
+                .expect("reveal");
+        }
+    }
+    game
+}
+
+#[test]
+fn large_pots_pay_out_after_a_delay() {
+    use Choice::{Rock, Scissors};
+    let mut harness = Harness::new(Pubkey::new_unique());
+    let program_id = harness.program_id();
+    let operator = harness.new_wallet(WALLET_LAMPORTS);
+    let players: Vec<Pubkey> = (0..3).map(|_| harness.new_wallet(WALLET_LAMPORTS)).collect();
+
+    harness
+        .process(&[instruction::initialize_config(&program_id, &operator)])
+        .expect("initialize config");
+    let no_delay = harness.process(&[instruction::set_claim_policy(&program_id, &operator, ENTRY_FEE, 0)]);
+    assert_eq!(no_delay.unwrap_err().rps_error(), Some(RPSError::InvalidParameter));
+    harness
+        .process(&[instruction::set_claim_policy(&program_id, &operator, ENTRY_FEE, CLAIM_DELAY)])
+        .expect("set claim policy");
+
+    let game = play(&mut harness, &players, &[[Rock, Scissors, Scissors]]);
+    let host = players[0];
+
+    let direct = harness.process(&[instruction::claim_winnings(&program_id, &host, &game)]);
+    assert_eq!(direct.unwrap_err().rps_error(), Some(RPSError::ClaimNotReady));
+    let uninitiated = harness.process(&[instruction::finalize_claim(&program_id, &host, &game)]);
+    assert_eq!(uninitiated.unwrap_err().rps_error(), Some(RPSError::ClaimNotReady));
+    let loser = harness.process(&[instruction::initiate_claim(&program_id, &players[1], &game)]);
+    assert_eq!(loser.unwrap_err().rps_error(), Some(RPSError::NotWinner));
+
+    harness
+        .process(&[instruction::initiate_claim(&program_id, &host, &game)])
+        .expect("initiate claim");
+    let ready_at = harness.clock().unix_timestamp as u64 + CLAIM_DELAY;
+    let events: Vec<GameEvent> = harness.logs().iter().filter_map(|line| parse_log_event(line)).collect();
+    assert!(events.contains(&GameEvent::ClaimInitiated {
+        player: host.to_string(),
+        ready_at,
+    }));
+    let twice = harness.process(&[instruction::initiate_claim(&program_id, &host, &game)]);
+    assert_eq!(twice.unwrap_err().rps_error(), Some(RPSError::InvalidGameState));
+
+    harness.warp_seconds(CLAIM_DELAY as i64 - 1);
+    let early = harness.process(&[instruction::finalize_claim(&program_id, &host, &game)]);
+    assert_eq!(early.unwrap_err().rps_error(), Some(RPSError::ClaimNotReady));
+    harness.warp_seconds(1);
+
+    let pot = harness.game(&game).expect("game").game_pot;
+    let before = harness.lamports(&host);
+    harness
+        .process(&[instruction::finalize_claim(&program_id, &host, &game)])
+        .expect("finalize claim");
+    let state = harness.game(&game).expect("game");
+    assert_eq!(state.game_pot, 0);
+    assert!(state.pending_claims.is_empty());
+    assert_eq!(harness.lamports(&host) - before, pot);
+}
+
+#[test]
+fn flagged_large_pots_wait_for_review() {
+    use Choice::{Paper, Rock, Scissors};
+    let mut harness = Harness::new(Pubkey::new_unique());
+    let program_id = harness.program_id();
+    let operator = harness.new_wallet(WALLET_LAMPORTS);
+    let players: Vec<Pubkey> = (0..3).map(|_| harness.new_wallet(WALLET_LAMPORTS)).collect();
+
+    harness
+        .process(&[instruction::initialize_config(&program_id, &operator)])
+        .expect("initialize config");
+    harness
+        .process(&[instruction::set_claim_policy(&program_id, &operator, ENTRY_FEE, CLAIM_DELAY)])
+        .expect("set claim policy");
+
+    // Both guests throw whatever the host beats: flagged at settlement
+    let game = play(
+        &mut harness,
+        &players,
+        &[[Rock, Scissors, Scissors], [Paper, Rock, Rock], [Scissors, Paper, Paper]],
+    );
+    assert!(harness.game(&game).expect("game").flagged);
+
+    // Without a general hold, the flag still stops a large payout
+    harness
+        .process(&[instruction::initiate_claim(&program_id, &players[0], &game)])
+        .expect("initiate claim");
+    harness.warp_seconds(CLAIM_DELAY as i64);
+    let held = harness.process(&[instruction::finalize_claim(&program_id, &players[0], &game)]);
+    assert_eq!(held.unwrap_err().rps_error(), Some(RPSError::PayoutHeld));
+
+    harness
+        .process(&[instruction::clear_game_flag(&program_id, &operator, &game)])
+        .expect("clear flag");
+    harness
+        .process(&[instruction::finalize_claim(&program_id, &players[0], &game)])
+        .expect("finalize claim");
+}
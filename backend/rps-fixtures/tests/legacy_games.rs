@@ -0,0 +1,102 @@
+//! Legacy games: accounts written under an older layout still decode, and
+//! `FinalizeLegacyGame` settles and closes them instead of leaving them
+//! stuck.
+
+use borsh::BorshSerialize;
+use rps_client::instruction::{self, GameConfig};
+use rps_fixtures::harness::Harness;
+use rps_fixtures::setup::{open_lobby, WALLET_LAMPORTS};
+use solana_rps::{compute_commitment, pot_vault_address, Choice, GameState, RPSError};
+use solana_sdk::{account::Account, pubkey::Pubkey};
+
+const ENTRY_FEE: u64 = 100_000_000;
+// `escrows`, `pot_subsidy`, `layout_version`, `player_count_drawn`,
+// `reveal_order`, `hidden_fee`, `theme_id`, `fee_bps`, `rule_set`,
+// `winners`, `cancelled`, the three phase timeouts, `crank_reward_lamports`,
+// `crank_bounty`, `slash_non_revealers`, `slashed`, `bot_config` and
+// `settlement`, all empty
+const APPENDED_TAIL: usize = 4 + 8 + 1 + 1 + 4 + 1 + 2 + 1 + 1 + 1 + 1 + 8 + 8 + 8 + 8 + 8 + 1 + 4 + 4 + 4;
+
+// Rewrites the game as an account from before the appended fields existed,
+// sized exactly to its state and holding its own pot
+fn make_legacy(harness: &mut Harness, game: &Pubkey) {
+    let vault = pot_vault_address(&harness.program_id(), game).0;
+    let pot = harness.lamports(&vault);
+    harness.set_account(vault, Account::default());
+
+    let mut state = harness.game(game).expect("game");
+    state.escrows.clear();
+    state.reveal_order.clear();
+    state.layout_version = 0;
+    let mut bytes = state.try_to_vec().expect("game serialize");
+    bytes.truncate(bytes.len() - APPENDED_TAIL);
+    let mut account = harness.account(game).expect("game account").clone();
+    account.data = bytes;
+    account.lamports += pot;
+    harness.set_account(*game, account);
+
+    let decoded = harness.game(game).expect("legacy game decodes");
+    assert!(decoded.is_legacy());
+    assert!(decoded.escrows.is_empty());
+}
+
+#[test]
+fn legacy_games_settle_and_close() {
+    let mut harness = Harness::new(Pubkey::new_unique());
+    let program_id = harness.program_id();
+    let players: Vec<Pubkey> = (0..3).map(|_| harness.new_wallet(WALLET_LAMPORTS)).collect();
+    let cranker = harness.new_wallet(WALLET_LAMPORTS);
+    let host = players[0];
+    let config = GameConfig {
+        entry_fee: ENTRY_FEE,
+        ..GameConfig::default()
+    };
+
+    let current = open_lobby(&mut harness, &players, &config);
+    let upgraded = harness.process(&[instruction::finalize_legacy_game(&program_id, &cranker, &current, &host, &players)]);
+    assert_eq!(upgraded.unwrap_err().rps_error(), Some(RPSError::InvalidGameState));
+
+    // A game caught mid-play refunds the pot in equal shares
+    make_legacy(&mut harness, &current);
+    let shuffled = [players[1], players[0], players[2]];
+    let unordered = harness.process(&[instruction::finalize_legacy_game(&program_id, &cranker, &current, &host, &shuffled)]);
+    assert_eq!(unordered.unwrap_err().rps_error(), Some(RPSError::InvalidParameter));
+
+    let share = harness.game(&current).expect("game").game_pot / 3;
+    let held = harness.lamports(&current);
+    let before: Vec<u64> = players.iter().map(|p| harness.lamports(p)).collect();
+    harness
+        .process(&[instruction::finalize_legacy_game(&program_id, &cranker, &current, &host, &players)])
+        .expect("finalize legacy game");
+    let gains: Vec<u64> = players.iter().zip(&before).map(|(p, b)| harness.lamports(p) - b).collect();
+    assert_eq!(gains, vec![held - 2 * share, share, share]);
+    assert!(harness.account(&current).is_none());
+
+    // A finished game pays its unclaimed winner
+    let finished = open_lobby(&mut harness, &players, &config);
+    let hands = [Choice::Rock, Choice::Scissors, Choice::Scissors];
+    for (i, (player, hand)) in players.iter().zip(&hands).enumerate() {
+        let salt = [i as u8 + 1; 32];
+        let commitment = compute_commitment(&finished, 1, player, &salt, hand).expect("valid choice");
+        harness
+            .process(&[instruction::commit_choice(&program_id, player, &finished, commitment)])
+            .expect("commit");
+    }
+    for (i, (player, hand)) in players.iter().zip(&hands).enumerate() {
+        let salt = [i as u8 + 1; 32];
+        harness
+            .process(&[instruction::reveal_choice(&program_id, player, &finished, hand.clone(), salt)])
+            .expect("reveal");
+    }
+    assert!(matches!(harness.game(&finished).expect("game").state, GameState::Finished));
+
+    make_legacy(&mut harness, &finished);
+    let held = harness.lamports(&finished);
+    let before: Vec<u64> = players.iter().map(|p| harness.lamports(p)).collect();
+    harness
+        .process(&[instruction::finalize_legacy_game(&program_id, &cranker, &finished, &host, &players)])
+        .expect("finalize legacy game");
+    let gains: Vec<u64> = players.iter().zip(&before).map(|(p, b)| harness.lamports(p) - b).collect();
+    assert_eq!(gains, vec![held, 0, 0]);
+    assert!(harness.account(&finished).is_none());
+}
@@ -0,0 +1,89 @@
+//! Lizard-Spock games: the host can opt into the extended hands, which then
+//! score by the five-hand matrix; classic games never count them.
+
+use rps_client::instruction::{self, GameConfig};
+use rps_fixtures::harness::Harness;
+use rps_fixtures::setup::WALLET_LAMPORTS;
+use solana_rps::{compute_commitment, Choice, GameState, RPSError, RuleSet};
+use solana_sdk::pubkey::Pubkey;
+
+// Opens a full three-seat game under `rule_set` with every hand committed
+fn committed_game(harness: &mut Harness, players: &[Pubkey], rule_set: u8, hands: &[Choice]) -> Pubkey {
+    let program_id = harness.program_id();
+    let game = Pubkey::new_unique();
+    let config = GameConfig {
+        rule_set,
+        ..GameConfig::default()
+    };
+    harness
+        .process(&[instruction::initialize_game(&program_id, &players[0], &game, &config)])
+        .expect("initialize game");
+    for player in &players[1..] {
+        harness
+            .process(&[instruction::join_game(&program_id, player, &game)])
+            .expect("join game");
+    }
+    for (i, (player, hand)) in players.iter().zip(hands).enumerate() {
+        let salt = [i as u8 + 1; 32];
+        let commitment = compute_commitment(&game, 1, player, &salt, hand).expect("valid choice");
+        harness
+            .process(&[instruction::commit_choice(&program_id, player, &game, commitment)])
+            .expect("commit");
+    }
+    game
+}
+
+#[test]
+fn spock_smashes_scissors_and_vaporizes_rock() {
+    let mut harness = Harness::new(Pubkey::new_unique());
+    let program_id = harness.program_id();
+    let players: Vec<Pubkey> = (0..3).map(|_| harness.new_wallet(WALLET_LAMPORTS)).collect();
+
+    let unknown = GameConfig {
+        rule_set: 2,
+        ..GameConfig::default()
+    };
+    let rejected = harness.process(&[instruction::initialize_game(
+        &program_id,
+        &players[0],
+        &Pubkey::new_unique(),
+        &unknown,
+    )]);
+    assert_eq!(rejected.unwrap_err().rps_error(), Some(RPSError::InvalidParameter));
+
+    let hands = [Choice::Spock, Choice::Rock, Choice::Scissors];
+    let game = committed_game(&mut harness, &players, 1, &hands);
+    assert_eq!(harness.game(&game).expect("game").rule_set, RuleSet::LizardSpock);
+    for (i, (player, hand)) in players.iter().zip(&hands).enumerate() {
+        harness
+            .process(&[instruction::reveal_choice(&program_id, player, &game, hand.clone(), [i as u8 + 1; 32])])
+            .expect("reveal");
+    }
+
+    let state = harness.game(&game).expect("game");
+    assert_eq!(state.state, GameState::Finished);
+    assert_eq!(state.players.iter().map(|p| p.score).collect::<Vec<_>>(), vec![2, 1, 0]);
+    assert_eq!(state.winner_bitmap(), 0b001);
+}
+
+#[test]
+fn classic_games_refuse_lizard_reveals() {
+    let mut harness = Harness::new(Pubkey::new_unique());
+    let program_id = harness.program_id();
+    let players: Vec<Pubkey> = (0..3).map(|_| harness.new_wallet(WALLET_LAMPORTS)).collect();
+
+    // The commitment hides the hand, so only the reveal can catch it
+    let hands = [Choice::Lizard, Choice::Paper, Choice::Rock];
+    let game = committed_game(&mut harness, &players, 0, &hands);
+    assert_eq!(harness.game(&game).expect("game").rule_set, RuleSet::Classic);
+    let reveal = instruction::reveal_choice(&program_id, &players[0], &game, Choice::Lizard, [1; 32]);
+    let lizard = harness.process(&[reveal]);
+    assert_eq!(lizard.unwrap_err().rps_error(), Some(RPSError::InvalidChoice));
+
+    for (i, (player, hand)) in players.iter().zip(&hands).enumerate().skip(1) {
+        harness
+            .process(&[instruction::reveal_choice(&program_id, player, &game, hand.clone(), [i as u8 + 1; 32])])
+            .expect("reveal");
+    }
+    assert!(!harness.game(&game).expect("game").players[0].revealed);
+}
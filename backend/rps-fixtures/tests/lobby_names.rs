@@ -0,0 +1,50 @@
+//! Lobby names: hosts register short names for their lobbies so players
+//! can join "friday-night" instead of a base58 address.
+
+use rps_client::instruction::{self, GameConfig};
+use rps_fixtures::harness::Harness;
+use rps_fixtures::setup::WALLET_LAMPORTS;
+use solana_rps::{lobby_name_address, LobbyName, RPSError};
+use solana_sdk::pubkey::Pubkey;
+
+fn resolve(harness: &Harness, name: &str) -> Option<Pubkey> {
+    let account = harness.account(&lobby_name_address(&harness.program_id(), name).0)?;
+    Some(LobbyName::unpack_from(&account.data).expect("lobby name").target)
+}
+
+#[test]
+fn hosts_name_their_lobbies() {
+    let mut harness = Harness::new(Pubkey::new_unique());
+    let program_id = harness.program_id();
+    let host = harness.new_wallet(WALLET_LAMPORTS);
+    let rival = harness.new_wallet(WALLET_LAMPORTS);
+    let (friday, next_friday, rival_game) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+
+    for (owner, game) in [(host, friday), (host, next_friday), (rival, rival_game)] {
+        harness
+            .process(&[instruction::initialize_game(&program_id, &owner, &game, &GameConfig::default())])
+            .expect("initialize game");
+    }
+
+    let long = "x".repeat(33);
+    for bad in ["fn", "Friday-Night", "friday night", long.as_str()] {
+        let rejected = harness.process(&[instruction::register_lobby_name(&program_id, &host, bad, &friday)]);
+        assert_eq!(rejected.unwrap_err().rps_error(), Some(RPSError::InvalidParameter), "{bad}");
+    }
+    let not_host = harness.process(&[instruction::register_lobby_name(&program_id, &rival, "friday-night", &friday)]);
+    assert_eq!(not_host.unwrap_err().rps_error(), Some(RPSError::NotAuthorized));
+
+    harness
+        .process(&[instruction::register_lobby_name(&program_id, &host, "friday-night", &friday)])
+        .expect("register name");
+    assert_eq!(resolve(&harness, "friday-night"), Some(friday));
+
+    let squat = harness.process(&[instruction::register_lobby_name(&program_id, &rival, "friday-night", &rival_game)]);
+    assert_eq!(squat.unwrap_err().rps_error(), Some(RPSError::InvalidGameState));
+
+    // Next week the same name moves to the new lobby
+    harness
+        .process(&[instruction::register_lobby_name(&program_id, &host, "friday-night", &next_friday)])
+        .expect("re-point name");
+    assert_eq!(resolve(&harness, "friday-night"), Some(next_friday));
+}
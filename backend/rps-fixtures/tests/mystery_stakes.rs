@@ -0,0 +1,101 @@
+//! Mystery stakes: the host seals the entry fee under a cap, every seat
+//! escrows the cap, and revealing the fee once the lobby fills refunds the
+//! difference.
+
+use rps_client::instruction::{self, GameConfig};
+use rps_fixtures::harness::Harness;
+use rps_fixtures::setup::WALLET_LAMPORTS;
+use solana_rps::{compute_commitment, hidden_fee_commitment, pot_vault_address, Choice, FeeQuote, RPSError};
+use solana_sdk::{pubkey::Pubkey, rent::Rent};
+
+const FEE_CAP: u64 = 100_000_000;
+const MIN_FEE: u64 = 10_000_000;
+const SEALED_FEE: u64 = 40_000_000;
+const SALT: [u8; 32] = [7; 32];
+
+// Opens a sealed lobby hosted by `players[0]` and fills it
+fn sealed_lobby(harness: &mut Harness, players: &[Pubkey]) -> Pubkey {
+    let program_id = harness.program_id();
+    let game = Pubkey::new_unique();
+    let config = GameConfig {
+        entry_fee: FEE_CAP,
+        ..GameConfig::default()
+    };
+    let commitment = hidden_fee_commitment(SEALED_FEE, &SALT);
+    harness
+        .process(&[
+            instruction::initialize_game(&program_id, &players[0], &game, &config),
+            instruction::seal_entry_fee(&program_id, &players[0], &game, commitment, MIN_FEE),
+        ])
+        .expect("open sealed lobby");
+
+    let bots = harness.process(&[instruction::add_bot_players(&program_id, &players[0], &game, 1, 0)]);
+    assert_eq!(bots.unwrap_err().rps_error(), Some(RPSError::InvalidGameState));
+    for player in &players[1..] {
+        harness
+            .process(&[instruction::join_game(&program_id, player, &game)])
+            .expect("join game");
+    }
+    game
+}
+
+#[test]
+fn host_reveal_refunds_the_difference() {
+    let mut harness = Harness::new(Pubkey::new_unique());
+    let program_id = harness.program_id();
+    let players: Vec<Pubkey> = (0..3).map(|_| harness.new_wallet(WALLET_LAMPORTS)).collect();
+    let game = sealed_lobby(&mut harness, &players);
+    assert_eq!(harness.lamports(&players[1]), WALLET_LAMPORTS - FEE_CAP);
+
+    // Commits wait for the fee
+    let commitment = compute_commitment(&game, 1, &players[1], &[1; 32], &Choice::Rock).expect("valid choice");
+    let early = harness.process(&[instruction::commit_choice(&program_id, &players[1], &game, commitment)]);
+    assert_eq!(early.unwrap_err().rps_error(), Some(RPSError::InvalidGameState));
+
+    let guest = harness.process(&[instruction::reveal_entry_fee(&program_id, &players[1], &game, 0, [0; 32], &players)]);
+    assert_eq!(guest.unwrap_err().rps_error(), Some(RPSError::NotAuthorized));
+    let bluff = harness.process(&[instruction::reveal_entry_fee(&program_id, &players[0], &game, MIN_FEE, SALT, &players)]);
+    assert_eq!(bluff.unwrap_err().rps_error(), Some(RPSError::InvalidHash));
+
+    let before: Vec<u64> = players.iter().map(|p| harness.lamports(p)).collect();
+    harness
+        .process(&[instruction::reveal_entry_fee(&program_id, &players[0], &game, SEALED_FEE, SALT, &players)])
+        .expect("reveal entry fee");
+    let refunds: Vec<u64> = players.iter().zip(&before).map(|(p, b)| harness.lamports(p) - b).collect();
+    assert_eq!(refunds, vec![FEE_CAP - SEALED_FEE; 3]);
+
+    let state = harness.game(&game).expect("game");
+    let pot = 3 * FeeQuote::new(SEALED_FEE, None).pot_contribution;
+    assert_eq!((state.entry_fee, state.game_pot), (SEALED_FEE, pot));
+    assert!(state.hidden_fee.is_none());
+    let vault = pot_vault_address(&program_id, &game).0;
+    assert_eq!(harness.lamports(&vault), Rent::default().minimum_balance(0) + pot);
+
+    harness
+        .process(&[instruction::commit_choice(&program_id, &players[1], &game, commitment)])
+        .expect("commit");
+}
+
+#[test]
+fn an_unrevealed_fee_falls_to_the_minimum() {
+    let mut harness = Harness::new(Pubkey::new_unique());
+    let program_id = harness.program_id();
+    let players: Vec<Pubkey> = (0..3).map(|_| harness.new_wallet(WALLET_LAMPORTS)).collect();
+    let cranker = harness.new_wallet(WALLET_LAMPORTS);
+    let game = sealed_lobby(&mut harness, &players);
+
+    let state = harness.game(&game).expect("game");
+    harness.warp_seconds(state.required_timeout as i64);
+    let stalled = harness.process(&[instruction::resolve_timeout(&program_id, &cranker, &game, state.phase_nonce)]);
+    assert_eq!(stalled.unwrap_err().rps_error(), Some(RPSError::InvalidGameState));
+
+    let before: Vec<u64> = players.iter().map(|p| harness.lamports(p)).collect();
+    harness
+        .process(&[instruction::reveal_entry_fee(&program_id, &cranker, &game, 0, [0; 32], &players)])
+        .expect("crank reveal");
+    let refunds: Vec<u64> = players.iter().zip(&before).map(|(p, b)| harness.lamports(p) - b).collect();
+    assert_eq!(refunds, vec![FEE_CAP - MIN_FEE; 3]);
+    let state = harness.game(&game).expect("game");
+    assert_eq!(state.entry_fee, MIN_FEE);
+    assert!(state.phase_deadline > harness.clock().unix_timestamp as u64);
+}
@@ -0,0 +1,93 @@
+//! Nicknames: unique regardless of case, changeable once per cooldown, and
+//! optionally charged a token fee by the operator.
+
+use rps_client::instruction;
+use rps_fixtures::harness::Harness;
+use rps_fixtures::setup::{packed, token_account, token_balance, WALLET_LAMPORTS};
+use solana_rps::{
+    nickname_address, profile_address, treasury_address, PlayerProfile, RPSError, NICKNAME_COOLDOWN_SECS,
+};
+use solana_sdk::{program_option::COption, pubkey::Pubkey};
+
+const NICKNAME_FEE: u64 = 5_000;
+
+fn nickname_of(harness: &Harness, player: &Pubkey) -> String {
+    let profile = harness.account(&profile_address(&harness.program_id(), player).0).expect("profile");
+    PlayerProfile::unpack_from(&profile.data).expect("profile state").nickname
+}
+
+#[test]
+fn nicknames_are_unique_and_change_on_a_cooldown() {
+    let mut harness = Harness::new(Pubkey::new_unique());
+    let program_id = harness.program_id();
+    let ace = harness.new_wallet(WALLET_LAMPORTS);
+    let rival = harness.new_wallet(WALLET_LAMPORTS);
+
+    let invalid = harness.process(&[instruction::set_nickname(&program_id, &ace, "ace of spades", None)]);
+    assert_eq!(invalid.unwrap_err().rps_error(), Some(RPSError::InvalidParameter));
+    harness
+        .process(&[instruction::set_nickname(&program_id, &ace, "Ace", None)])
+        .expect("set nickname");
+    assert_eq!(nickname_of(&harness, &ace), "Ace");
+
+    let lookalike = harness.process(&[instruction::set_nickname(&program_id, &rival, "ACE", None)]);
+    assert_eq!(lookalike.unwrap_err().rps_error(), Some(RPSError::InvalidGameState));
+
+    let early = harness.process(&[instruction::set_nickname(&program_id, &ace, "Spades", Some("Ace"))]);
+    assert_eq!(early.unwrap_err().rps_error(), Some(RPSError::InvalidGameState));
+    harness.warp_seconds(NICKNAME_COOLDOWN_SECS as i64);
+    harness
+        .process(&[instruction::set_nickname(&program_id, &ace, "Spades", Some("Ace"))])
+        .expect("rename");
+    assert_eq!(nickname_of(&harness, &ace), "Spades");
+    assert!(harness.account(&nickname_address(&program_id, "Ace").0).is_none());
+
+    // The old name is free again
+    harness
+        .process(&[instruction::set_nickname(&program_id, &rival, "ace", None)])
+        .expect("take freed nickname");
+    assert_eq!(nickname_of(&harness, &rival), "ace");
+}
+
+#[test]
+fn operator_fee_is_paid_to_the_treasury() {
+    let mut harness = Harness::new(Pubkey::new_unique());
+    let program_id = harness.program_id();
+    let authority = harness.new_wallet(WALLET_LAMPORTS);
+    let player = harness.new_wallet(WALLET_LAMPORTS);
+
+    let mint = Pubkey::new_unique();
+    harness.set_account(
+        mint,
+        packed(
+            spl_token::state::Mint {
+                mint_authority: COption::None,
+                supply: NICKNAME_FEE,
+                decimals: 6,
+                is_initialized: true,
+                freeze_authority: COption::None,
+            },
+            spl_token::id(),
+        ),
+    );
+    let player_tokens = token_account(&mut harness, mint, player, NICKNAME_FEE);
+    let treasury_tokens = token_account(&mut harness, mint, treasury_address(&program_id).0, 0);
+
+    harness
+        .process(&[
+            instruction::initialize_config(&program_id, &authority),
+            instruction::set_nickname_policy(&program_id, &authority, NICKNAME_FEE, Some(mint)),
+        ])
+        .expect("configure nickname fee");
+
+    let unpaid = harness.process(&[instruction::set_nickname(&program_id, &player, "Payer", None)]);
+    assert_eq!(unpaid.unwrap_err().rps_error(), Some(RPSError::InvalidParameter));
+    let paid = instruction::with_nickname_fee(
+        instruction::set_nickname(&program_id, &player, "Payer", None),
+        &player_tokens,
+        &treasury_tokens,
+    );
+    harness.process(&[paid]).expect("set nickname");
+    assert_eq!(token_balance(&harness, &player_tokens), 0);
+    assert_eq!(token_balance(&harness, &treasury_tokens), NICKNAME_FEE);
+}
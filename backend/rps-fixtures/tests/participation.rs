@@ -0,0 +1,88 @@
+//! Participation records: players credit finished games to a compact
+//! per-epoch PDA that other programs can read.
+
+use rps_client::instruction::{self, GameConfig};
+use rps_fixtures::harness::Harness;
+use rps_fixtures::setup::WALLET_LAMPORTS;
+use solana_rps::{compute_commitment, participation_address, Choice, Participation, RPSError};
+use solana_sdk::pubkey::Pubkey;
+
+// Plays one single-round game between `players` to the finish
+fn play_game(harness: &mut Harness, players: &[Pubkey]) -> Pubkey {
+    let program_id = harness.program_id();
+    let game = Pubkey::new_unique();
+    harness
+        .process(&[instruction::initialize_game(&program_id, &players[0], &game, &GameConfig::default())])
+        .expect("initialize game");
+    for player in &players[1..] {
+        harness
+            .process(&[instruction::join_game(&program_id, player, &game)])
+            .expect("join game");
+    }
+    for (i, player) in players.iter().enumerate() {
+        let salt = [i as u8 + 1; 32];
+        let commitment = compute_commitment(&game, 1, player, &salt, &Choice::Rock).expect("valid choice");
+        harness
+            .process(&[instruction::commit_choice(&program_id, player, &game, commitment)])
+            .expect("commit");
+    }
+    for (i, player) in players.iter().enumerate() {
+        let salt = [i as u8 + 1; 32];
+        harness
+            .process(&[instruction::reveal_choice(&program_id, player, &game, Choice::Rock, salt)])
+            .expect("reveal");
+    }
+    game
+}
+
+fn games_in(harness: &Harness, player: &Pubkey, epoch: u64) -> Option<u32> {
+    let address = participation_address(&harness.program_id(), player, epoch).0;
+    let account = harness.account(&address)?;
+    let record = Participation::unpack_from(&account.data).expect("participation");
+    assert_eq!((record.player, record.epoch), (*player, epoch));
+    Some(record.games)
+}
+
+#[test]
+fn finished_games_are_counted_once_per_epoch() {
+    let mut harness = Harness::new(Pubkey::new_unique());
+    let program_id = harness.program_id();
+    let players: Vec<Pubkey> = (0..3).map(|_| harness.new_wallet(WALLET_LAMPORTS)).collect();
+    let outsider = harness.new_wallet(WALLET_LAMPORTS);
+    let epoch = harness.clock().epoch;
+
+    let open = Pubkey::new_unique();
+    harness
+        .process(&[instruction::initialize_game(&program_id, &players[0], &open, &GameConfig::default())])
+        .expect("initialize game");
+    let early = harness.process(&[instruction::record_participation(&program_id, &players[0], &open, epoch)]);
+    assert_eq!(early.unwrap_err().rps_error(), Some(RPSError::InvalidGameState));
+
+    let game = play_game(&mut harness, &players);
+    let stranger = harness.process(&[instruction::record_participation(&program_id, &outsider, &game, epoch)]);
+    assert_eq!(stranger.unwrap_err().rps_error(), Some(RPSError::PlayerNotFound));
+    let future = harness.process(&[instruction::record_participation(&program_id, &players[0], &game, epoch + 1)]);
+    assert_eq!(future.unwrap_err().rps_error(), Some(RPSError::InvalidParameter));
+
+    harness
+        .process(&[instruction::record_participation(&program_id, &players[0], &game, epoch)])
+        .expect("record participation");
+    let again = harness.process(&[instruction::record_participation(&program_id, &players[0], &game, epoch)]);
+    assert_eq!(again.unwrap_err().rps_error(), Some(RPSError::AlreadyClaimed));
+    assert_eq!(games_in(&harness, &players[0], epoch), Some(1));
+    assert_eq!(games_in(&harness, &players[1], epoch), None);
+
+    let second = play_game(&mut harness, &players);
+    harness
+        .process(&[instruction::record_participation(&program_id, &players[0], &second, epoch)])
+        .expect("record second game");
+    assert_eq!(games_in(&harness, &players[0], epoch), Some(2));
+
+    // A new epoch starts a fresh counter; old games can still be credited
+    harness.warp_epochs(1);
+    harness
+        .process(&[instruction::record_participation(&program_id, &players[1], &game, epoch + 1)])
+        .expect("record in next epoch");
+    assert_eq!(games_in(&harness, &players[1], epoch + 1), Some(1));
+    assert_eq!(games_in(&harness, &players[0], epoch), Some(2));
+}
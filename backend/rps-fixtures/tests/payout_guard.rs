@@ -0,0 +1,75 @@
+//! Payout guard: with the guard on, a claim only goes through when nothing
+//! but this program's instructions share its transaction.
+
+use rps_client::instruction::{self, GameConfig};
+use rps_fixtures::harness::Harness;
+use rps_fixtures::setup::WALLET_LAMPORTS;
+use solana_rps::{compute_commitment, Choice, RPSError};
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+
+const ENTRY_FEE: u64 = 100_000_000;
+
+#[test]
+fn guarded_claim_must_run_alone() {
+    let mut harness = Harness::new(Pubkey::new_unique());
+    let program_id = harness.program_id();
+    let operator = harness.new_wallet(WALLET_LAMPORTS);
+    let players: Vec<Pubkey> = (0..3).map(|_| harness.new_wallet(WALLET_LAMPORTS)).collect();
+    let game = Pubkey::new_unique();
+
+    harness
+        .process(&[instruction::initialize_config(&program_id, &operator)])
+        .expect("initialize config");
+    let forged = harness.process(&[instruction::set_payout_guard(&program_id, &players[0], true)]);
+    assert_eq!(forged.unwrap_err().rps_error(), Some(RPSError::NotAuthorized));
+    harness
+        .process(&[instruction::set_payout_guard(&program_id, &operator, true)])
+        .expect("set payout guard");
+
+    let config = GameConfig {
+        entry_fee: ENTRY_FEE,
+        ..GameConfig::default()
+    };
+    harness
+        .process(&[instruction::initialize_game(&program_id, &players[0], &game, &config)])
+        .expect("initialize game");
+    for player in &players[1..] {
+        harness
+            .process(&[instruction::join_game(&program_id, player, &game)])
+            .expect("join game");
+    }
+    let hands = [Choice::Scissors, Choice::Rock, Choice::Scissors];
+    for (i, (player, hand)) in players.iter().zip(&hands).enumerate() {
+        let salt = [i as u8 + 1; 32];
+        let commitment = compute_commitment(&game, 1, player, &salt, hand).expect("valid choice");
+        harness
+            .process(&[instruction::commit_choice(&program_id, player, &game, commitment)])
+            .expect("commit");
+    }
+    for (i, (player, hand)) in players.iter().zip(&hands).enumerate() {
+        let salt = [i as u8 + 1; 32];
+        harness
+            .process(&[instruction::reveal_choice(&program_id, player, &game, hand.clone(), salt)])
+            .expect("reveal");
+    }
+
+    // Another program's instruction after the claim is refused, even a
+    // harmless one
+    let signer = Keypair::new();
+    let message = b"unrelated";
+    let signature: [u8; 64] = signer.sign_message(message).into();
+    let claim = instruction::claim_winnings(&program_id, &players[1], &game);
+    let unrelated = instruction::ed25519_verify(&signer.pubkey(), message, &signature);
+    let sandwiched = harness.process(&[claim.clone(), unrelated]);
+    assert_eq!(sandwiched.unwrap_err().rps_error(), Some(RPSError::UnexpectedInstruction));
+
+    // This program's own instructions may ride along
+    let before = harness.lamports(&players[1]);
+    harness
+        .process(&[claim, instruction::get_spectator_view(&program_id, &game)])
+        .expect("claim winnings");
+    assert!(harness.lamports(&players[1]) > before);
+}
@@ -0,0 +1,75 @@
+//! `phase_deadline` is set once per phase, announced in `PhaseStarted`, and
+//! is exactly when a timeout becomes resolvable.
+
+use rps_client::{
+    instruction::{self, GameConfig},
+    subscribe::{parse_log_event, GameEvent},
+};
+use rps_fixtures::harness::Harness;
+use rps_fixtures::setup::WALLET_LAMPORTS;
+use solana_rps::{compute_commitment, Choice, GameState, RPSError};
+use solana_sdk::pubkey::Pubkey;
+
+const TIMEOUT_SECONDS: u64 = 300;
+
+fn phase_started(harness: &Harness) -> Option<GameEvent> {
+    harness
+        .logs()
+        .iter()
+        .filter_map(|line| parse_log_event(line))
+        .find(|event| matches!(event, GameEvent::PhaseStarted { .. }))
+}
+
+#[test]
+fn deadline_is_fixed_per_phase_and_gates_timeouts() {
+    let mut harness = Harness::new(Pubkey::new_unique());
+    let program_id = harness.program_id();
+    let players: Vec<Pubkey> = (0..3).map(|_| harness.new_wallet(WALLET_LAMPORTS)).collect();
+    let game = Pubkey::new_unique();
+
+    let config = GameConfig {
+        timeout_seconds: TIMEOUT_SECONDS,
+        ..GameConfig::default()
+    };
+    harness
+        .process(&[instruction::initialize_game(&program_id, &players[0], &game, &config)])
+        .expect("initialize game");
+    for player in &players[1..] {
+        harness
+            .process(&[instruction::join_game(&program_id, player, &game)])
+            .expect("join game");
+    }
+
+    let commit_started = harness.clock().unix_timestamp as u64;
+    let state = harness.game(&game).expect("game account");
+    assert!(matches!(state.state, GameState::CommitPhase));
+    assert_eq!(state.phase_deadline, commit_started + TIMEOUT_SECONDS);
+    assert_eq!(
+        phase_started(&harness),
+        Some(GameEvent::PhaseStarted {
+            phase_nonce: state.phase_nonce,
+            deadline: state.phase_deadline,
+        })
+    );
+
+    // A commit late in the phase does not push the countdown back
+    harness.warp_seconds(TIMEOUT_SECONDS as i64 - 10);
+    let salt = [7; 32];
+    let commitment = compute_commitment(&game, 1, &players[0], &salt, &Choice::Rock).expect("valid choice");
+    harness
+        .process(&[instruction::commit_choice(&program_id, &players[0], &game, commitment)])
+        .expect("commit");
+    assert_eq!(harness.game(&game).unwrap().phase_deadline, commit_started + TIMEOUT_SECONDS);
+
+    let caller = harness.new_wallet(WALLET_LAMPORTS);
+    let early = harness.process(&[instruction::resolve_timeout(&program_id, &caller, &game, state.phase_nonce)]);
+    assert_eq!(early.unwrap_err().rps_error(), Some(RPSError::TimeoutNotReached));
+
+    harness.warp_seconds(10);
+    harness
+        .process(&[instruction::resolve_timeout(&program_id, &caller, &game, state.phase_nonce)])
+        .expect("resolve timeout at the deadline");
+    let state = harness.game(&game).unwrap();
+    assert!(matches!(state.state, GameState::Finished));
+    assert_eq!(state.phase_deadline, 0);
+}
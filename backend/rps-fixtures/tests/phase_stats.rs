@@ -0,0 +1,77 @@
+//! Phase timing: every phase change reports how long the phase lasted, and
+//! commits and reveals that pass the stats PDA feed the global averages.
+
+use rps_client::{
+    instruction::{self, GameConfig},
+    subscribe::{parse_log_event, GameEvent},
+};
+use rps_fixtures::harness::Harness;
+use rps_fixtures::setup::WALLET_LAMPORTS;
+use solana_rps::{compute_commitment, stats_address, Choice, GameState, GlobalStats, RPSError};
+use solana_sdk::pubkey::Pubkey;
+
+fn phase_ends(harness: &Harness) -> Vec<(GameState, u64)> {
+    harness
+        .logs()
+        .iter()
+        .filter_map(|line| match parse_log_event(line) {
+            Some(GameEvent::PhaseEnded { state, seconds, .. }) => Some((state, seconds)),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn commit_and_reveal_timings_feed_global_stats() {
+    let mut harness = Harness::new(Pubkey::new_unique());
+    let program_id = harness.program_id();
+    let players: Vec<Pubkey> = (0..3).map(|_| harness.new_wallet(WALLET_LAMPORTS)).collect();
+    let game = Pubkey::new_unique();
+
+    harness
+        .process(&[instruction::initialize_stats(&program_id, &players[0])])
+        .expect("initialize stats");
+    let again = harness.process(&[instruction::initialize_stats(&program_id, &players[1])]);
+    assert_eq!(again.unwrap_err().rps_error(), Some(RPSError::InvalidGameState));
+
+    harness
+        .process(&[instruction::initialize_game(&program_id, &players[0], &game, &GameConfig::default())])
+        .expect("initialize game");
+    harness.warp_seconds(3);
+    for player in &players[1..] {
+        harness
+            .process(&[instruction::join_game(&program_id, player, &game)])
+            .expect("join game");
+    }
+    assert_eq!(phase_ends(&harness), vec![(GameState::WaitingForPlayers, 3)]);
+
+    for (i, (player, wait)) in players.iter().zip([10, 20, 5]).enumerate() {
+        harness.warp_seconds(wait);
+        let salt = [i as u8 + 1; 32];
+        let commitment = compute_commitment(&game, 1, player, &salt, &Choice::Rock).expect("valid choice");
+        let commit = instruction::commit_choice(&program_id, player, &game, commitment);
+        harness
+            .process(&[instruction::with_stats(&program_id, commit)])
+            .expect("commit");
+    }
+    assert_eq!(phase_ends(&harness), vec![(GameState::CommitPhase, 35)]);
+
+    for (i, (player, wait)) in players.iter().zip([4, 0, 6]).enumerate() {
+        harness.warp_seconds(wait);
+        let salt = [i as u8 + 1; 32];
+        let reveal = instruction::reveal_choice(&program_id, player, &game, Choice::Rock, salt);
+        harness
+            .process(&[instruction::with_stats(&program_id, reveal)])
+            .expect("reveal");
+    }
+    assert_eq!(phase_ends(&harness), vec![(GameState::RevealPhase, 10)]);
+
+    let account = harness.account(&stats_address(&program_id).0).expect("stats account");
+    let stats = GlobalStats::unpack_from(&account.data).expect("stats");
+    assert_eq!((stats.commits, stats.commit_latency_secs), (3, 10 + 30 + 35));
+    assert_eq!((stats.reveals, stats.reveal_latency_secs), (3, 4 + 4 + 10));
+    assert_eq!(stats.average_commit_latency(), Some(25));
+    assert_eq!(stats.average_reveal_latency(), Some(6));
+    assert_eq!(stats.average_commit_phase(), Some(35));
+    assert_eq!(stats.average_reveal_phase(), Some(10));
+}
@@ -0,0 +1,146 @@
+//! Per-phase timeouts: the lobby, commit and reveal phases each count down
+//! from their own timeout when the host sets one, and from
+//! `timeout_seconds` otherwise.
+
+use rps_client::instruction::{self, GameConfig};
+use rps_fixtures::harness::Harness;
+use rps_fixtures::setup::WALLET_LAMPORTS;
+use solana_rps::{compute_commitment, Choice, GameState, RPSError};
+use solana_sdk::pubkey::Pubkey;
+
+const TIMEOUT_SECS: u64 = 300;
+const JOIN_SECS: u64 = 600;
+const COMMIT_SECS: u64 = 60;
+const REVEAL_SECS: u64 = 120;
+const HANDS: [Choice; 3] = [Choice::Rock, Choice::Paper, Choice::Scissors];
+
+fn split_config() -> GameConfig {
+    GameConfig {
+        timeout_seconds: TIMEOUT_SECS,
+        join_timeout: JOIN_SECS,
+        commit_timeout: COMMIT_SECS,
+        reveal_timeout: REVEAL_SECS,
+        ..GameConfig::default()
+    }
+}
+
+fn create(harness: &mut Harness, host: &Pubkey, config: &GameConfig) -> Pubkey {
+    let program_id = harness.program_id();
+    let game = Pubkey::new_unique();
+    harness
+        .process(&[instruction::initialize_game(&program_id, host, &game, config)])
+        .expect("initialize game");
+    game
+}
+
+fn join(harness: &mut Harness, players: &[Pubkey], game: &Pubkey) {
+    let program_id = harness.program_id();
+    for player in players {
+        harness
+            .process(&[instruction::join_game(&program_id, player, game)])
+            .expect("join game");
+    }
+}
+
+fn commit(harness: &mut Harness, players: &[Pubkey], game: &Pubkey) {
+    let program_id = harness.program_id();
+    for (seat, (player, hand)) in players.iter().zip(&HANDS).enumerate() {
+        let commitment = compute_commitment(game, 1, player, &salt(seat), hand).expect("valid choice");
+        harness
+            .process(&[instruction::commit_choice(&program_id, player, game, commitment)])
+            .expect("commit");
+    }
+}
+
+fn salt(seat: usize) -> [u8; 32] {
+    [seat as u8 + 1; 32]
+}
+
+fn now(harness: &Harness) -> u64 {
+    harness.clock().unix_timestamp as u64
+}
+
+// Times out the current phase exactly at its deadline, checking it cannot
+// be timed out a second earlier
+fn time_out_after(harness: &mut Harness, caller: &Pubkey, game: &Pubkey, secs: u64) {
+    let program_id = harness.program_id();
+    let phase_nonce = harness.game(game).expect("game").phase_nonce;
+    let timeout = instruction::resolve_timeout(&program_id, caller, game, phase_nonce);
+    harness.warp_seconds(secs as i64 - 1);
+    let early = harness.process(std::slice::from_ref(&timeout)).unwrap_err();
+    assert_eq!(early.rps_error(), Some(RPSError::TimeoutNotReached));
+    harness.warp_seconds(1);
+    harness.process(&[timeout]).expect("resolve timeout");
+}
+
+#[test]
+fn each_phase_counts_down_from_its_own_timeout() {
+    let mut harness = Harness::new(Pubkey::new_unique());
+    let program_id = harness.program_id();
+    let players: Vec<Pubkey> = (0..3).map(|_| harness.new_wallet(WALLET_LAMPORTS)).collect();
+    let cranker = harness.new_wallet(WALLET_LAMPORTS);
+
+    let game = create(&mut harness, &players[0], &split_config());
+    let state = harness.game(&game).expect("game");
+    assert_eq!(state.phase_deadline, now(&harness) + JOIN_SECS);
+    assert_eq!(state.phase_timeout(GameState::WaitingForPlayers), JOIN_SECS);
+
+    join(&mut harness, &players[1..], &game);
+    assert_eq!(harness.game(&game).expect("game").phase_deadline, now(&harness) + COMMIT_SECS);
+
+    commit(&mut harness, &players, &game);
+    let state = harness.game(&game).expect("game");
+    assert!(matches!(state.state, GameState::RevealPhase));
+    assert_eq!(state.phase_deadline, now(&harness) + REVEAL_SECS);
+
+    // Two reveals, then the silent seat is timed out on the reveal clock
+    for (seat, player) in players.iter().enumerate().take(2) {
+        harness
+            .process(&[instruction::reveal_choice(&program_id, player, &game, HANDS[seat].clone(), salt(seat))])
+            .expect("reveal");
+    }
+    time_out_after(&mut harness, &cranker, &game, REVEAL_SECS);
+    assert!(matches!(harness.game(&game).expect("game").state, GameState::Finished));
+}
+
+#[test]
+fn the_lobby_and_commit_phase_time_out_on_their_own_clocks() {
+    let mut harness = Harness::new(Pubkey::new_unique());
+    let players: Vec<Pubkey> = (0..3).map(|_| harness.new_wallet(WALLET_LAMPORTS)).collect();
+    let cranker = harness.new_wallet(WALLET_LAMPORTS);
+
+    // A lobby outlives the shared timeout, and is cancelled at its own
+    let lobby = create(&mut harness, &players[0], &split_config());
+    join(&mut harness, &players[1..2], &lobby);
+    time_out_after(&mut harness, &cranker, &lobby, JOIN_SECS);
+    assert!(harness.game(&lobby).expect("game").cancelled);
+
+    // A commit phase is cut short well before it
+    let game = create(&mut harness, &players[0], &split_config());
+    join(&mut harness, &players[1..], &game);
+    commit(&mut harness, &players[..1], &game);
+    time_out_after(&mut harness, &cranker, &game, COMMIT_SECS);
+    let state = harness.game(&game).expect("game");
+    assert!(matches!(state.state, GameState::Finished));
+    assert!(state.cancelled);
+}
+
+#[test]
+fn unset_phase_timeouts_use_timeout_seconds() {
+    let mut harness = Harness::new(Pubkey::new_unique());
+    let players: Vec<Pubkey> = (0..3).map(|_| harness.new_wallet(WALLET_LAMPORTS)).collect();
+    let config = GameConfig {
+        timeout_seconds: TIMEOUT_SECS,
+        reveal_timeout: REVEAL_SECS,
+        ..GameConfig::default()
+    };
+
+    let game = create(&mut harness, &players[0], &config);
+    assert_eq!(harness.game(&game).expect("game").phase_deadline, now(&harness) + TIMEOUT_SECS);
+    join(&mut harness, &players[1..], &game);
+    assert_eq!(harness.game(&game).expect("game").phase_deadline, now(&harness) + TIMEOUT_SECS);
+    commit(&mut harness, &players, &game);
+    let state = harness.game(&game).expect("game");
+    assert_eq!(state.phase_deadline, now(&harness) + REVEAL_SECS);
+    assert_eq!(state.phase_timeout(GameState::CommitPhase), TIMEOUT_SECS);
+}
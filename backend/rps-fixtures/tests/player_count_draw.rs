@@ -0,0 +1,86 @@
+//! 3-4 player lobbies draw their seat count from the SlotHashes sysvar when
+//! they reach three players, not when they are created.
+
+use rps_client::instruction::{self, GameConfig};
+use rps_fixtures::harness::Harness;
+use rps_fixtures::setup::{open_lobby, WALLET_LAMPORTS};
+use solana_rps::{draw_player_count, GameState, RPSError};
+use solana_sdk::pubkey::Pubkey;
+
+// A 3-4 seat lobby whose seat count is still to be drawn
+fn undrawn_lobby(harness: &mut Harness, host: &Pubkey) -> Pubkey {
+    let config = GameConfig {
+        min_players: 3,
+        max_players: 4,
+        ..GameConfig::default()
+    };
+    let game = open_lobby(harness, &[*host], &config);
+    let state = harness.game(&game).expect("game");
+    assert_eq!(state.player_count, 4);
+    assert!(!state.player_count_drawn);
+    game
+}
+
+#[test]
+fn seat_count_is_drawn_when_the_lobby_reaches_three() {
+    let mut harness = Harness::new(Pubkey::new_unique());
+    let program_id = harness.program_id();
+    let players: Vec<Pubkey> = (0..4).map(|_| harness.new_wallet(WALLET_LAMPORTS)).collect();
+    let game = undrawn_lobby(&mut harness, &players[0]);
+
+    harness
+        .process(&[instruction::join_game(&program_id, &players[1], &game)])
+        .expect("join game");
+    let blind = harness.process(&[instruction::join_game(&program_id, &players[2], &game)]);
+    assert_eq!(blind.unwrap_err().rps_error(), Some(RPSError::InvalidParameter));
+
+    let drawn = draw_player_count(&harness.recent_slot_hash(), &game, 3, 4);
+    harness
+        .process(&[instruction::with_slot_hashes(instruction::join_game(&program_id, &players[2], &game))])
+        .expect("join game");
+    let state = harness.game(&game).expect("game");
+    assert!(state.player_count_drawn);
+    assert_eq!(state.player_count, drawn);
+
+    // A four-seat draw leaves one seat open, and the last join needs no sysvar
+    if drawn == 4 {
+        assert!(matches!(state.state, GameState::WaitingForPlayers));
+        harness
+            .process(&[instruction::join_game(&program_id, &players[3], &game)])
+            .expect("join game");
+    } else {
+        let full = harness.process(&[instruction::join_game(&program_id, &players[3], &game)]);
+        assert_eq!(full.unwrap_err().rps_error(), Some(RPSError::InvalidGameState));
+    }
+    let state = harness.game(&game).expect("game");
+    assert!(matches!(state.state, GameState::CommitPhase));
+    assert_eq!(state.players.len(), drawn as usize);
+}
+
+#[test]
+fn bots_stop_at_the_drawn_seat_count() {
+    let mut harness = Harness::new(Pubkey::new_unique());
+    let program_id = harness.program_id();
+    let host = harness.new_wallet(WALLET_LAMPORTS);
+    let game = undrawn_lobby(&mut harness, &host);
+
+    let drawn = draw_player_count(&harness.recent_slot_hash(), &game, 3, 4);
+    harness
+        .process(&[instruction::with_slot_hashes(instruction::add_bot_players(&program_id, &host, &game, 3, 0))])
+        .expect("add bots");
+    let state = harness.game(&game).expect("game");
+    assert_eq!((state.player_count, state.players.len()), (drawn, drawn as usize));
+    assert!(matches!(state.state, GameState::CommitPhase));
+}
+
+#[test]
+fn draws_depend_on_the_slot_and_the_game() {
+    let games: Vec<Pubkey> = (0..64).map(|_| Pubkey::new_unique()).collect();
+    let counts = |hash: [u8; 32]| -> Vec<u8> { games.iter().map(|g| draw_player_count(&hash, g, 3, 4)).collect() };
+
+    let early = counts([1; 32]);
+    assert!(early.contains(&3) && early.contains(&4));
+    assert_ne!(early, counts([2; 32]));
+    assert_eq!(counts([1; 32]), early);
+    assert!(games.iter().all(|g| draw_player_count(&[1; 32], g, 3, 3) == 3));
+}
@@ -0,0 +1,83 @@
+//! Pot guarantee: the operator can have the treasury top small SOL pots up
+//! to a minimum, recorded on the game and announced in an event.
+
+use rps_client::{
+    instruction::{self, GameConfig},
+    subscribe::{parse_log_event, GameEvent},
+};
+use rps_fixtures::harness::Harness;
+use rps_fixtures::setup::{open_lobby, WALLET_LAMPORTS};
+use solana_rps::{compute_commitment, treasury_address, Choice, RPSError};
+use solana_sdk::{account::Account, pubkey::Pubkey, system_program};
+
+const ENTRY_FEE: u64 = 100_000_000;
+const MIN_POT: u64 = 1_000_000_000;
+
+#[test]
+fn treasury_tops_small_pots_up_once() {
+    let mut harness = Harness::new(Pubkey::new_unique());
+    let program_id = harness.program_id();
+    let operator = harness.new_wallet(WALLET_LAMPORTS);
+    let players: Vec<Pubkey> = (0..3).map(|_| harness.new_wallet(WALLET_LAMPORTS)).collect();
+
+    harness
+        .process(&[instruction::initialize_config(&program_id, &operator)])
+        .expect("initialize config");
+    let forged = harness.process(&[instruction::set_pot_guarantee(&program_id, &players[0], MIN_POT, ENTRY_FEE)]);
+    assert_eq!(forged.unwrap_err().rps_error(), Some(RPSError::NotAuthorized));
+    let free = harness.process(&[instruction::set_pot_guarantee(&program_id, &operator, MIN_POT, 0)]);
+    assert_eq!(free.unwrap_err().rps_error(), Some(RPSError::InvalidParameter));
+    harness
+        .process(&[instruction::set_pot_guarantee(&program_id, &operator, MIN_POT, ENTRY_FEE)])
+        .expect("set pot guarantee");
+
+    let config = GameConfig {
+        entry_fee: ENTRY_FEE,
+        ..GameConfig::default()
+    };
+    let game = open_lobby(&mut harness, &players, &config);
+    let unstarted = harness.process(&[instruction::apply_pot_subsidy(&program_id, &players[0], &game)]);
+    assert_eq!(unstarted.unwrap_err().rps_error(), Some(RPSError::InvalidGameState));
+
+    // The host's commit starts play
+    let commitment = compute_commitment(&game, 1, &players[0], &[1; 32], &Choice::Rock).expect("valid choice");
+    harness
+        .process(&[instruction::commit_choice(&program_id, &players[0], &game, commitment)])
+        .expect("commit");
+    let dry = harness.process(&[instruction::apply_pot_subsidy(&program_id, &players[1], &game)]);
+    assert_eq!(dry.unwrap_err().rps_error(), Some(RPSError::InsufficientFunds));
+
+    let treasury = treasury_address(&program_id).0;
+    harness.set_account(treasury, Account::new(WALLET_LAMPORTS, 0, &system_program::id()));
+    let pot = harness.game(&game).expect("game").game_pot;
+    harness
+        .process(&[instruction::apply_pot_subsidy(&program_id, &players[1], &game)])
+        .expect("apply pot subsidy");
+    let state = harness.game(&game).expect("game");
+    assert_eq!((state.game_pot, state.pot_subsidy), (MIN_POT, MIN_POT - pot));
+    assert_eq!(harness.lamports(&treasury), WALLET_LAMPORTS - (MIN_POT - pot));
+    let announced = harness.logs().iter().any(|line| {
+        parse_log_event(line)
+            == Some(GameEvent::PotSubsidized {
+                amount: MIN_POT - pot,
+                pot: MIN_POT,
+            })
+    });
+    assert!(announced);
+
+    let again = harness.process(&[instruction::apply_pot_subsidy(&program_id, &players[1], &game)]);
+    assert_eq!(again.unwrap_err().rps_error(), Some(RPSError::InvalidGameState));
+
+    // Games below the entry floor are not subsidized
+    let cheap_config = GameConfig {
+        entry_fee: ENTRY_FEE / 2,
+        ..config
+    };
+    let cheap = open_lobby(&mut harness, &players, &cheap_config);
+    let commitment = compute_commitment(&cheap, 1, &players[0], &[1; 32], &Choice::Rock).expect("valid choice");
+    harness
+        .process(&[instruction::commit_choice(&program_id, &players[0], &cheap, commitment)])
+        .expect("commit");
+    let below = harness.process(&[instruction::apply_pot_subsidy(&program_id, &players[1], &cheap)]);
+    assert_eq!(below.unwrap_err().rps_error(), Some(RPSError::InvalidGameState));
+}
@@ -0,0 +1,71 @@
+//! Pot vault: a SOL game's entries land in its `rps_vault` PDA rather than
+//! the game account, and the payout comes back out of it.
+
+use rps_client::instruction::{self, GameConfig};
+use rps_fixtures::harness::Harness;
+use rps_fixtures::setup::WALLET_LAMPORTS;
+use solana_rps::{compute_commitment, pot_vault_address, Choice, GameState, RPSError};
+use solana_sdk::{pubkey::Pubkey, rent::Rent};
+
+const ENTRY_FEE: u64 = 100_000_000;
+
+#[test]
+fn entries_and_payout_move_through_the_vault() {
+    let mut harness = Harness::new(Pubkey::new_unique());
+    let program_id = harness.program_id();
+    let players: Vec<Pubkey> = (0..3).map(|_| harness.new_wallet(WALLET_LAMPORTS)).collect();
+    let game = Pubkey::new_unique();
+    let vault = pot_vault_address(&program_id, &game).0;
+
+    let config = GameConfig {
+        entry_fee: ENTRY_FEE,
+        ..GameConfig::default()
+    };
+    harness
+        .process(&[instruction::initialize_game(&program_id, &players[0], &game, &config)])
+        .expect("initialize game");
+    assert_eq!(harness.account(&vault).expect("pot vault").owner, program_id);
+
+    // Entries only land in the game's own vault
+    let mut diverted = instruction::join_game(&program_id, &players[1], &game);
+    let vault_index = diverted.accounts.iter().position(|m| m.pubkey == vault).expect("vault meta");
+    diverted.accounts[vault_index].pubkey = Pubkey::new_unique();
+    let diverted = harness.process(&[diverted]);
+    assert_eq!(diverted.unwrap_err().rps_error(), Some(RPSError::InvalidParameter));
+
+    for player in &players[1..] {
+        harness
+            .process(&[instruction::join_game(&program_id, player, &game)])
+            .expect("join game");
+    }
+    let pot = harness.game(&game).expect("game").game_pot;
+    let vault_rent = Rent::default().minimum_balance(0);
+    assert_eq!(harness.lamports(&vault), vault_rent + pot);
+    let account = harness.account(&game).expect("game account");
+    assert_eq!(account.lamports, Rent::default().minimum_balance(account.data.len()));
+
+    let hands = [Choice::Rock, Choice::Scissors, Choice::Scissors];
+    for (i, (player, hand)) in players.iter().zip(&hands).enumerate() {
+        let salt = [i as u8 + 1; 32];
+        let commitment = compute_commitment(&game, 1, player, &salt, hand).expect("valid choice");
+        harness
+            .process(&[instruction::commit_choice(&program_id, player, &game, commitment)])
+            .expect("commit");
+    }
+    for (i, (player, hand)) in players.iter().zip(&hands).enumerate() {
+        let salt = [i as u8 + 1; 32];
+        harness
+            .process(&[instruction::reveal_choice(&program_id, player, &game, hand.clone(), salt)])
+            .expect("reveal");
+    }
+    let state = harness.game(&game).expect("game");
+    assert!(matches!(state.state, GameState::Finished));
+    assert_eq!(state.game_pot, pot);
+
+    let before = harness.lamports(&players[0]);
+    harness
+        .process(&[instruction::claim_winnings(&program_id, &players[0], &game)])
+        .expect("claim winnings");
+    assert_eq!(harness.lamports(&players[0]) - before, pot);
+    assert_eq!(harness.lamports(&vault), vault_rent);
+}
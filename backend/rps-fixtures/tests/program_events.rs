@@ -0,0 +1,155 @@
+//! Structured events beyond the game loop: fees, tournaments and challenges
+//! all announce their transitions in `Program data:` lines.
+
+use rps_client::{
+    instruction::{self, GameConfig, TournamentConfig},
+    subscribe::{decode_program_event, parse_log_event, GameEvent},
+};
+use rps_fixtures::harness::Harness;
+use rps_fixtures::setup::WALLET_LAMPORTS;
+use solana_rps::{compute_commitment, treasury_address, Choice, RPSEvent};
+use solana_sdk::pubkey::Pubkey;
+
+const ENTRY_FEE: u64 = 100_000_000;
+const STAKE: u64 = 1_000_000_000;
+
+fn events(harness: &Harness) -> Vec<RPSEvent> {
+    harness
+        .logs()
+        .iter()
+        .filter_map(|line| line.strip_prefix("Program data: "))
+        .filter_map(decode_program_event)
+        .collect()
+}
+
+#[test]
+fn entry_fees_and_tournament_steps_are_announced() {
+    let mut harness = Harness::new(Pubkey::new_unique());
+    let program_id = harness.program_id();
+    let players: Vec<Pubkey> = (0..3).map(|_| harness.new_wallet(WALLET_LAMPORTS)).collect();
+    let treasury = treasury_address(&program_id).0;
+
+    let game = Pubkey::new_unique();
+    let config = GameConfig {
+        entry_fee: ENTRY_FEE,
+        ..GameConfig::default()
+    };
+    harness
+        .process(&[instruction::initialize_game(&program_id, &players[0], &game, &config)])
+        .expect("initialize game");
+    harness
+        .process(&[instruction::join_game(&program_id, &players[1], &game)])
+        .expect("join game");
+    let fee = GameEvent::FeeCollected {
+        recipient: treasury.to_string(),
+        amount: ENTRY_FEE / 100,
+    };
+    let seen: Vec<GameEvent> = harness.logs().iter().filter_map(|line| parse_log_event(line)).collect();
+    assert!(seen.contains(&fee), "{:?}", seen);
+
+    let tournament = Pubkey::new_unique();
+    harness
+        .process(&[instruction::create_tournament(
+            &program_id,
+            &players[0],
+            &tournament,
+            &TournamentConfig::default(),
+        )])
+        .expect("create tournament");
+    assert_eq!(
+        events(&harness),
+        vec![RPSEvent::TournamentCreated {
+            tournament,
+            host: players[0],
+            max_players: 4,
+            entry_fee: 0,
+        }]
+    );
+    for player in &players {
+        harness
+            .process(&[instruction::join_tournament(&program_id, player, &tournament, None, None)])
+            .expect("join tournament");
+        assert_eq!(
+            events(&harness),
+            vec![RPSEvent::TournamentJoined {
+                tournament,
+                entrant: *player,
+            }]
+        );
+    }
+    harness
+        .process(&[instruction::start_tournament(&program_id, &players[0], &tournament)])
+        .expect("start tournament");
+    assert_eq!(
+        events(&harness),
+        vec![RPSEvent::TournamentStarted { tournament, entrants: 3 }]
+    );
+}
+
+#[test]
+fn a_challenge_reports_every_step() {
+    let mut harness = Harness::new(Pubkey::new_unique());
+    let program_id = harness.program_id();
+    let challenger = harness.new_wallet(WALLET_LAMPORTS);
+    let opponent = harness.new_wallet(WALLET_LAMPORTS);
+    let challenge = Pubkey::new_unique();
+
+    harness
+        .process(&[instruction::create_challenge(&program_id, &challenger, &challenge, &opponent, STAKE, None)])
+        .expect("create challenge");
+    assert_eq!(
+        events(&harness),
+        vec![RPSEvent::ChallengeCreated {
+            challenge,
+            challenger,
+            opponent,
+            stake: STAKE,
+        }]
+    );
+    harness
+        .process(&[instruction::accept_challenge(&program_id, &opponent, &challenge, None)])
+        .expect("accept challenge");
+    assert_eq!(events(&harness), vec![RPSEvent::ChallengeAccepted { challenge, opponent }]);
+
+    let throws = [(challenger, Choice::Rock), (opponent, Choice::Scissors)];
+    for (player, choice) in &throws {
+        let commitment = compute_commitment(&challenge, 1, player, &[7; 32], choice).expect("valid choice");
+        harness
+            .process(&[instruction::commit_challenge_choice(&program_id, player, &challenge, commitment)])
+            .expect("commit");
+        // Throws are reported like game hands, keyed by the challenge
+        assert_eq!(
+            events(&harness),
+            vec![RPSEvent::ChoiceCommitted {
+                game: challenge,
+                player: *player,
+            }]
+        );
+    }
+    for (player, choice) in throws {
+        harness
+            .process(&[instruction::reveal_challenge_choice(
+                &program_id,
+                &player,
+                &challenge,
+                &challenger,
+                &opponent,
+                choice,
+                [7; 32],
+            )])
+            .expect("reveal");
+    }
+    assert_eq!(
+        events(&harness),
+        vec![
+            RPSEvent::ChoiceRevealed {
+                game: challenge,
+                player: opponent,
+            },
+            RPSEvent::ChallengeSettled {
+                challenge,
+                payouts: [2 * STAKE, 0],
+            },
+        ]
+    );
+}
@@ -0,0 +1,41 @@
+//! Program slots are pinned: a spoofed system or token program is refused
+//! before anything is invoked.
+
+use rps_client::instruction::{self, GameConfig};
+use rps_fixtures::harness::Harness;
+use rps_fixtures::setup::WALLET_LAMPORTS;
+use solana_sdk::{instruction::InstructionError, pubkey::Pubkey, system_program};
+
+#[test]
+fn spoofed_system_program_is_rejected() {
+    let mut harness = Harness::new(Pubkey::new_unique());
+    let program_id = harness.program_id();
+    let host = harness.new_wallet(WALLET_LAMPORTS);
+    let player = harness.new_wallet(WALLET_LAMPORTS);
+    let game = Pubkey::new_unique();
+
+    let config = GameConfig {
+        entry_fee: 100_000_000,
+        ..GameConfig::default()
+    };
+    let mut init = instruction::initialize_game(&program_id, &host, &game, &config);
+    assert_eq!(init.accounts[2].pubkey, system_program::id());
+    init.accounts[2].pubkey = Pubkey::new_unique();
+    let spoofed = harness.process(&[init]).unwrap_err();
+    assert_eq!(spoofed.error, InstructionError::IncorrectProgramId);
+
+    harness
+        .process(&[instruction::initialize_game(&program_id, &host, &game, &config)])
+        .expect("initialize game");
+
+    let mut join = instruction::join_game(&program_id, &player, &game);
+    join.accounts[2].pubkey = Pubkey::new_unique();
+    let spoofed = harness.process(&[join]).unwrap_err();
+    assert_eq!(spoofed.error, InstructionError::IncorrectProgramId);
+    let before = harness.lamports(&player);
+
+    harness
+        .process(&[instruction::join_game(&program_id, &player, &game)])
+        .expect("join game");
+    assert!(harness.lamports(&player) < before);
+}
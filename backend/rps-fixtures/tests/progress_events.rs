@@ -0,0 +1,56 @@
+//! Commit and reveal progress events, as a lobby UI would consume them.
+
+use rps_client::{
+    instruction::{self, GameConfig},
+    subscribe::{parse_log_event, GameEvent},
+};
+use rps_fixtures::harness::Harness;
+use rps_fixtures::setup::WALLET_LAMPORTS;
+use solana_rps::{compute_commitment, Choice};
+use solana_sdk::pubkey::Pubkey;
+
+fn events(harness: &Harness) -> Vec<GameEvent> {
+    harness.logs().iter().filter_map(|line| parse_log_event(line)).collect()
+}
+
+#[test]
+fn every_commit_and_reveal_reports_progress() {
+    let mut harness = Harness::new(Pubkey::new_unique());
+    let program_id = harness.program_id();
+    let players: Vec<Pubkey> = (0..3).map(|_| harness.new_wallet(WALLET_LAMPORTS)).collect();
+    let game = Pubkey::new_unique();
+
+    harness
+        .process(&[instruction::initialize_game(&program_id, &players[0], &game, &GameConfig::default())])
+        .expect("initialize game");
+    for player in &players[1..] {
+        harness
+            .process(&[instruction::join_game(&program_id, player, &game)])
+            .expect("join game");
+    }
+
+    for (i, player) in players.iter().enumerate() {
+        let salt = [i as u8 + 1; 32];
+        let commitment = compute_commitment(&game, 1, player, &salt, &Choice::Rock).expect("valid choice");
+        harness
+            .process(&[instruction::commit_choice(&program_id, player, &game, commitment)])
+            .expect("commit");
+        let progress = GameEvent::CommitProgress {
+            committed: i as u8 + 1,
+            total: 3,
+        };
+        assert!(events(&harness).contains(&progress), "{:?}", events(&harness));
+    }
+
+    for (i, player) in players.iter().enumerate() {
+        let salt = [i as u8 + 1; 32];
+        harness
+            .process(&[instruction::reveal_choice(&program_id, player, &game, Choice::Rock, salt)])
+            .expect("reveal");
+        let progress = GameEvent::RevealProgress {
+            revealed: i as u8 + 1,
+            total: 3,
+        };
+        assert!(events(&harness).contains(&progress), "{:?}", events(&harness));
+    }
+}
@@ -0,0 +1,185 @@
+//! Racing transactions. The runtime runs transactions that write the same
+//! game one after the other, so each race here is played out in both
+//! orders: the loser must fail with the same error every time and leave no
+//! trace, and a transaction failing part way must undo the instructions
+//! before the failure.
+
+use rps_client::instruction::{self, GameConfig};
+use rps_fixtures::harness::Harness;
+use rps_fixtures::setup::WALLET_LAMPORTS;
+use solana_rps::{compute_commitment, pot_vault_address, treasury_address, Choice, GameState, RPSError};
+use solana_sdk::{instruction::Instruction, pubkey::Pubkey};
+
+const ENTRY_FEE: u64 = 100_000_000;
+const TIMEOUT_SECS: u64 = 300;
+// The rock beats both scissors and wins alone
+const ROCK_WINS: [Choice; 3] = [Choice::Rock, Choice::Scissors, Choice::Scissors];
+
+struct Race {
+    harness: Harness,
+    players: Vec<Pubkey>,
+    game: Pubkey,
+}
+
+impl Race {
+    // A three-seat SOL game with `seated` of `wallets` players seated
+    fn lobby(wallets: usize, seated: usize) -> Self {
+        let mut harness = Harness::new(Pubkey::new_unique());
+        let program_id = harness.program_id();
+        let players: Vec<Pubkey> = (0..wallets).map(|_| harness.new_wallet(WALLET_LAMPORTS)).collect();
+        let game = Pubkey::new_unique();
+        let config = GameConfig {
+            entry_fee: ENTRY_FEE,
+            timeout_seconds: TIMEOUT_SECS,
+            ..GameConfig::default()
+        };
+        harness
+            .process(&[instruction::initialize_game(&program_id, &players[0], &game, &config)])
+            .expect("initialize game");
+        for player in &players[1..seated] {
+            harness
+                .process(&[instruction::join_game(&program_id, player, &game)])
+                .expect("join game");
+        }
+        Race { harness, players, game }
+    }
+
+    // A full game where every seat has committed its hand in `hands`
+    fn committed(hands: &[Choice; 3]) -> Self {
+        let mut race = Race::lobby(3, 3);
+        let program_id = race.harness.program_id();
+        for (seat, hand) in hands.iter().enumerate() {
+            let player = &race.players[seat];
+            let commitment = compute_commitment(&race.game, 1, player, &salt(seat), hand).expect("valid choice");
+            let commit = instruction::commit_choice(&program_id, player, &race.game, commitment);
+            race.harness.process(&[commit]).expect("commit");
+        }
+        race
+    }
+
+    fn reveal(&self, seat: usize, hand: &Choice) -> Instruction {
+        let program_id = self.harness.program_id();
+        instruction::reveal_choice(&program_id, &self.players[seat], &self.game, hand.clone(), salt(seat))
+    }
+
+    fn reveal_all(&mut self, hands: &[Choice]) {
+        for (seat, hand) in hands.iter().enumerate() {
+            let reveal = self.reveal(seat, hand);
+            self.harness.process(&[reveal]).expect("reveal");
+        }
+    }
+
+    // Everything the losing side of a race must leave untouched: the game
+    // account and the lamports of every wallet, the treasury and the vault
+    fn snapshot(&self) -> (Vec<u8>, Vec<u64>) {
+        let program_id = self.harness.program_id();
+        let data = self.harness.account(&self.game).expect("game account").data.clone();
+        let mut holders = self.players.clone();
+        holders.push(treasury_address(&program_id).0);
+        holders.push(pot_vault_address(&program_id, &self.game).0);
+        (data, holders.iter().map(|h| self.harness.lamports(h)).collect())
+    }
+}
+
+fn salt(seat: usize) -> [u8; 32] {
+    [seat as u8 + 1; 32]
+}
+
+#[test]
+fn the_last_seat_goes_to_exactly_one_join() {
+    for (first, second) in [(2, 3), (3, 2)] {
+        let mut race = Race::lobby(4, 2);
+        let program_id = race.harness.program_id();
+        let game = race.game;
+        let join = |player: &Pubkey| instruction::with_slot_hashes(instruction::join_game(&program_id, player, &game));
+        let (winner, loser) = (join(&race.players[first]), join(&race.players[second]));
+
+        race.harness.process(&[winner]).expect("join the last seat");
+        let before = race.snapshot();
+        let late = race.harness.process(&[loser]).unwrap_err();
+        assert_eq!(late.rps_error(), Some(RPSError::InvalidGameState));
+        assert_eq!(race.snapshot(), before);
+
+        let state = race.harness.game(&race.game).expect("game");
+        assert!(matches!(state.state, GameState::CommitPhase));
+        assert_eq!(state.players[2].pubkey, race.players[first]);
+    }
+}
+
+#[test]
+fn a_failing_instruction_undoes_the_whole_transaction() {
+    let mut race = Race::lobby(3, 2);
+    let program_id = race.harness.program_id();
+    let before = race.snapshot();
+
+    // The second join fails, so the first one never happened either
+    let join = instruction::join_game(&program_id, &race.players[2], &race.game);
+    let failure = race.harness.process(&[join.clone(), join]).unwrap_err();
+    assert_eq!(failure.instruction_index, 1);
+    assert_eq!(failure.rps_error(), Some(RPSError::InvalidGameState));
+    assert_eq!(race.snapshot(), before);
+
+    // A claim paid out before a failing instruction is clawed back too
+    let mut race = Race::committed(&ROCK_WINS);
+    race.reveal_all(&ROCK_WINS);
+    let program_id = race.harness.program_id();
+    let before = race.snapshot();
+    let claim = instruction::claim_winnings(&program_id, &race.players[0], &race.game);
+    let failure = race.harness.process(&[claim.clone(), claim]).unwrap_err();
+    assert_eq!(failure.instruction_index, 1);
+    assert_eq!(failure.rps_error(), Some(RPSError::AlreadyClaimed));
+    assert_eq!(race.snapshot(), before);
+}
+
+#[test]
+fn a_winner_is_paid_once_whoever_settles_first() {
+    for claim_first in [true, false] {
+        let mut race = Race::committed(&ROCK_WINS);
+        race.reveal_all(&ROCK_WINS);
+        let program_id = race.harness.program_id();
+        let cranker = race.harness.new_wallet(WALLET_LAMPORTS);
+        let pot = race.harness.game(&race.game).expect("game").pot_snapshot;
+        let claim = instruction::claim_winnings(&program_id, &race.players[0], &race.game);
+        let distribute = instruction::distribute_winnings(&program_id, &cranker, &race.game, &race.players[..1]);
+        let (first, second) = if claim_first { (claim, distribute) } else { (distribute, claim) };
+
+        let balance = race.harness.lamports(&race.players[0]);
+        race.harness.process(&[first]).expect("settle");
+        let before = race.snapshot();
+        let late = race.harness.process(&[second]).unwrap_err();
+        assert_eq!(late.rps_error(), Some(RPSError::AlreadyClaimed));
+        assert_eq!(race.snapshot(), before);
+        assert_eq!(race.harness.lamports(&race.players[0]) - balance, pot);
+    }
+}
+
+#[test]
+fn the_last_reveal_and_the_timeout_settle_one_way() {
+    // All three revealed, every hand beats one other; with the paper
+    // silent, the rock alone beats a hand
+    let hands = [Choice::Rock, Choice::Scissors, Choice::Paper];
+    for reveal_first in [true, false] {
+        let mut race = Race::committed(&hands);
+        race.reveal_all(&hands[..2]);
+        race.harness.warp_seconds(TIMEOUT_SECS as i64);
+        let program_id = race.harness.program_id();
+        let nonce = race.harness.game(&race.game).expect("game").phase_nonce;
+        let timeout = instruction::resolve_timeout(&program_id, &race.players[0], &race.game, nonce);
+        let reveal = race.reveal(2, &hands[2]);
+        let (first, second, error) = if reveal_first {
+            (reveal, timeout, RPSError::StalePhase)
+        } else {
+            (timeout, reveal, RPSError::InvalidGameState)
+        };
+
+        race.harness.process(&[first]).expect("settle the reveal phase");
+        let before = race.snapshot();
+        let late = race.harness.process(&[second]).unwrap_err();
+        assert_eq!(late.rps_error(), Some(error));
+        assert_eq!(race.snapshot(), before);
+
+        let state = race.harness.game(&race.game).expect("game");
+        assert!(matches!(state.state, GameState::Finished));
+        assert_eq!(state.winner_bitmap(), if reveal_first { 0b111 } else { 0b001 });
+    }
+}
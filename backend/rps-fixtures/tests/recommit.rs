@@ -0,0 +1,51 @@
+//! Commitments are never silently overwritten: a second `CommitChoice`
+//! fails, and `RecommitChoice` only works while nobody else has committed.
+
+use rps_client::instruction::{self, GameConfig};
+use rps_fixtures::harness::Harness;
+use rps_fixtures::setup::WALLET_LAMPORTS;
+use solana_rps::{compute_commitment, Choice, RPSError};
+use solana_sdk::pubkey::Pubkey;
+
+#[test]
+fn commitments_lock_once_another_player_commits() {
+    let mut harness = Harness::new(Pubkey::new_unique());
+    let program_id = harness.program_id();
+    let players: Vec<Pubkey> = (0..3).map(|_| harness.new_wallet(WALLET_LAMPORTS)).collect();
+    let game = Pubkey::new_unique();
+
+    harness
+        .process(&[instruction::initialize_game(&program_id, &players[0], &game, &GameConfig::default())])
+        .expect("initialize game");
+    for player in &players[1..] {
+        harness
+            .process(&[instruction::join_game(&program_id, player, &game)])
+            .expect("join game");
+    }
+
+    let salt = [1; 32];
+    let rock = compute_commitment(&game, 1, &players[0], &salt, &Choice::Rock).expect("valid choice");
+    let paper = compute_commitment(&game, 1, &players[0], &salt, &Choice::Paper).expect("valid choice");
+
+    let early = harness.process(&[instruction::recommit_choice(&program_id, &players[0], &game, paper)]);
+    assert_eq!(early.unwrap_err().rps_error(), Some(RPSError::InvalidPlayerState));
+
+    harness
+        .process(&[instruction::commit_choice(&program_id, &players[0], &game, rock)])
+        .expect("commit");
+    // Only the hash goes on-chain; the salt waits for the reveal
+    assert_eq!(harness.game(&game).unwrap().players[0].salt, [0; 32]);
+    let overwrite = harness.process(&[instruction::commit_choice(&program_id, &players[0], &game, paper)]);
+    assert_eq!(overwrite.unwrap_err().rps_error(), Some(RPSError::AlreadyCommitted));
+
+    harness
+        .process(&[instruction::recommit_choice(&program_id, &players[0], &game, paper)])
+        .expect("recommit while alone");
+    assert_eq!(harness.game(&game).unwrap().players[0].committed_choice, paper);
+
+    harness
+        .process(&[instruction::commit_choice(&program_id, &players[1], &game, rock)])
+        .expect("second commit");
+    let locked = harness.process(&[instruction::recommit_choice(&program_id, &players[0], &game, rock)]);
+    assert_eq!(locked.unwrap_err().rps_error(), Some(RPSError::AlreadyCommitted));
+}
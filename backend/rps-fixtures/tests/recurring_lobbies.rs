@@ -0,0 +1,86 @@
+//! Recurring lobbies: a template plus an interval, cranked into a fresh
+//! game whenever the next slot on the schedule comes around.
+
+use rps_client::instruction;
+use rps_fixtures::harness::Harness;
+use rps_fixtures::setup::WALLET_LAMPORTS;
+use solana_rps::{lobby_name_address, CommitmentScheme, GameState, LobbyName, LobbyTemplate, RPSError, RecurringLobby};
+use solana_sdk::pubkey::Pubkey;
+
+const DAY: u64 = 24 * 60 * 60;
+const ENTRY_FEE: u64 = 50_000_000;
+
+fn template() -> LobbyTemplate {
+    LobbyTemplate {
+        min_players: 3,
+        max_players: 3,
+        total_rounds: 3,
+        entry_fee: ENTRY_FEE,
+        timeout_seconds: 600,
+        losers_can_rejoin: false,
+        allow_public_bots: false,
+        commitment_scheme: CommitmentScheme::HmacSha512,
+    }
+}
+
+fn recurring(harness: &Harness, address: &Pubkey) -> RecurringLobby {
+    RecurringLobby::unpack_from(&harness.account(address).expect("recurring lobby").data).expect("decode")
+}
+
+#[test]
+fn daily_lobby_spawns_on_schedule() {
+    let mut harness = Harness::new(Pubkey::new_unique());
+    let program_id = harness.program_id();
+    let host = harness.new_wallet(WALLET_LAMPORTS);
+    let cranker = harness.new_wallet(WALLET_LAMPORTS);
+    let daily = Pubkey::new_unique();
+    let start = harness.clock().unix_timestamp as u64 + 60;
+
+    let never = harness.process(&[instruction::create_recurring_lobby(&program_id, &host, &daily, template(), 0, start)]);
+    assert_eq!(never.unwrap_err().rps_error(), Some(RPSError::InvalidParameter));
+    harness
+        .process(&[instruction::create_recurring_lobby(&program_id, &host, &daily, template(), DAY, start)])
+        .expect("create recurring lobby");
+    harness
+        .process(&[instruction::register_lobby_name(&program_id, &host, "daily-duel", &daily)])
+        .expect("name the recurring lobby");
+    let entry = harness.account(&lobby_name_address(&program_id, "daily-duel").0).expect("name");
+    assert_eq!(LobbyName::unpack_from(&entry.data).expect("lobby name").target, daily);
+
+    let early = harness.process(&[instruction::spawn_scheduled_lobby(&program_id, &cranker, &daily, &Pubkey::new_unique())]);
+    assert_eq!(early.unwrap_err().rps_error(), Some(RPSError::TimeoutNotReached));
+
+    harness.warp_seconds(60);
+    let first = Pubkey::new_unique();
+    harness
+        .process(&[instruction::spawn_scheduled_lobby(&program_id, &cranker, &daily, &first)])
+        .expect("spawn first game");
+    let game = harness.game(&first).expect("game");
+    assert_eq!((game.host, game.entry_fee, game.total_rounds), (host, ENTRY_FEE, 3));
+    assert!(matches!(game.state, GameState::WaitingForPlayers));
+    assert!(game.players.is_empty());
+    let state = recurring(&harness, &daily);
+    assert_eq!((state.spawned, state.last_game, state.next_spawn_at), (1, Some(first), start + DAY));
+
+    let again = harness.process(&[instruction::spawn_scheduled_lobby(&program_id, &cranker, &daily, &Pubkey::new_unique())]);
+    assert_eq!(again.unwrap_err().rps_error(), Some(RPSError::TimeoutNotReached));
+
+    // Nobody cranked for three days: one game spawns and the schedule keeps
+    // its time of day
+    harness.warp_seconds((3 * DAY + 5) as i64);
+    let late = Pubkey::new_unique();
+    harness
+        .process(&[instruction::spawn_scheduled_lobby(&program_id, &cranker, &daily, &late)])
+        .expect("spawn late game");
+    let state = recurring(&harness, &daily);
+    assert_eq!((state.spawned, state.last_game, state.next_spawn_at), (2, Some(late), start + 4 * DAY));
+
+    // Players join the spawned game and pay as they sit down
+    let before = harness.lamports(&host);
+    let guest = harness.new_wallet(WALLET_LAMPORTS);
+    harness
+        .process(&[instruction::join_game(&program_id, &guest, &late)])
+        .expect("join spawned game");
+    assert_eq!(harness.lamports(&host), before);
+    assert_eq!(harness.game(&late).expect("game").players.len(), 1);
+}
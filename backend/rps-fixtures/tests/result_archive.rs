@@ -0,0 +1,94 @@
+//! `ArchiveGameResults` folds a past epoch's recorded results into the
+//! epoch's archive and closes them, once their games are closed.
+
+use rps_client::instruction::{self, GameConfig};
+use rps_fixtures::harness::Harness;
+use rps_fixtures::setup::WALLET_LAMPORTS;
+use solana_rps::{
+    compute_commitment, game_result_address, result_archive_address, ArchivedWin, Choice, GameResult, RPSError,
+    ResultArchive, ARCHIVE_EPOCH_SECONDS,
+};
+use solana_sdk::{pubkey::Pubkey, rent::Rent};
+
+const ENTRY_FEE: u64 = 100_000_000;
+
+#[test]
+fn past_results_fold_into_the_epoch_archive() {
+    let mut harness = Harness::new(Pubkey::new_unique());
+    let program_id = harness.program_id();
+    let players: Vec<Pubkey> = (0..3).map(|_| harness.new_wallet(WALLET_LAMPORTS)).collect();
+    let cranker = harness.new_wallet(WALLET_LAMPORTS);
+    let game = Pubkey::new_unique();
+
+    let config = GameConfig {
+        entry_fee: ENTRY_FEE,
+        ..GameConfig::default()
+    };
+    harness
+        .process(&[instruction::initialize_game(&program_id, &players[0], &game, &config)])
+        .expect("initialize game");
+    for player in &players[1..] {
+        harness
+            .process(&[instruction::join_game(&program_id, player, &game)])
+            .expect("join game");
+    }
+    let choices = [Choice::Paper, Choice::Rock, Choice::Rock];
+    for (i, (player, choice)) in players.iter().zip(&choices).enumerate() {
+        let salt = [i as u8 + 1; 32];
+        let commitment = compute_commitment(&game, 1, player, &salt, choice).expect("valid choice");
+        harness
+            .process(&[instruction::commit_choice(&program_id, player, &game, commitment)])
+            .expect("commit");
+    }
+    for (i, (player, choice)) in players.iter().zip(&choices).enumerate() {
+        let salt = [i as u8 + 1; 32];
+        harness
+            .process(&[instruction::reveal_choice(&program_id, player, &game, choice.clone(), salt)])
+            .expect("reveal");
+    }
+    harness
+        .process(&[instruction::record_game_result(&program_id, &players[0], &game)])
+        .expect("record result");
+
+    let address = game_result_address(&program_id, &game).0;
+    let result_account = harness.account(&address).expect("result account").clone();
+    let result = GameResult::unpack_from(&result_account.data).expect("result");
+    let epoch = result.finished_at / ARCHIVE_EPOCH_SECONDS;
+
+    // The epoch has to be over first
+    let early = harness.process(&[instruction::archive_game_results(&program_id, &cranker, epoch, &[game])]);
+    assert_eq!(early.unwrap_err().rps_error(), Some(RPSError::InvalidGameState));
+    harness.warp_seconds(ARCHIVE_EPOCH_SECONDS as i64);
+    let wrong_epoch = harness.process(&[instruction::archive_game_results(&program_id, &cranker, epoch - 1, &[game])]);
+    assert_eq!(wrong_epoch.unwrap_err().rps_error(), Some(RPSError::InvalidParameter));
+
+    // CloseGame needs the result, so the game has to go first
+    let open = harness.process(&[instruction::archive_game_results(&program_id, &cranker, epoch, &[game])]);
+    assert_eq!(open.unwrap_err().rps_error(), Some(RPSError::InvalidGameState));
+    harness
+        .process(&[instruction::claim_winnings(&program_id, &players[0], &game)])
+        .expect("claim winnings");
+    harness
+        .process(&[instruction::close_game(&program_id, &players[0], &game, &players[0], None)])
+        .expect("close game");
+
+    let before = harness.lamports(&cranker);
+    harness
+        .process(&[instruction::archive_game_results(&program_id, &cranker, epoch, &[game])])
+        .expect("archive results");
+    assert!(harness.account(&address).is_none());
+    let archive_rent = Rent::default().minimum_balance(ResultArchive::SPACE);
+    assert_eq!(harness.lamports(&cranker) + archive_rent, before + result_account.lamports);
+
+    let archive_account = harness.account(&result_archive_address(&program_id, epoch).0).expect("archive");
+    let archive = ResultArchive::unpack_from(&archive_account.data).expect("archive state");
+    assert_eq!((archive.epoch, archive.games, archive.token_games), (epoch, 1, 0));
+    assert_eq!(archive.sol_volume, result.pot);
+    assert_eq!(
+        archive.top_winners,
+        vec![ArchivedWin {
+            player: players[0],
+            amount: result.pot,
+        }]
+    );
+}
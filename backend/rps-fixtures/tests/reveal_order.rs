@@ -0,0 +1,61 @@
+//! Reveal order: closing the commit phase shuffles the seats into the order
+//! reveals are staged in, stores it on the game and announces it.
+
+use rps_client::{
+    instruction::{self, GameConfig},
+    subscribe::{parse_log_event, GameEvent},
+};
+use rps_fixtures::harness::Harness;
+use rps_fixtures::setup::WALLET_LAMPORTS;
+use solana_rps::{compute_commitment, stats_address, Choice, GameState, GlobalStats};
+use solana_sdk::pubkey::Pubkey;
+
+fn announced_order(harness: &Harness) -> Option<(u8, Vec<u8>)> {
+    harness.logs().iter().find_map(|line| match parse_log_event(line) {
+        Some(GameEvent::RevealOrderDrawn { round, order }) => Some((round, order)),
+        _ => None,
+    })
+}
+
+#[test]
+fn closing_commits_draws_a_reveal_order() {
+    let mut harness = Harness::new(Pubkey::new_unique());
+    let program_id = harness.program_id();
+    let players: Vec<Pubkey> = (0..3).map(|_| harness.new_wallet(WALLET_LAMPORTS)).collect();
+    let game = Pubkey::new_unique();
+
+    harness
+        .process(&[instruction::initialize_stats(&program_id, &players[0])])
+        .expect("initialize stats");
+    harness
+        .process(&[instruction::initialize_game(&program_id, &players[0], &game, &GameConfig::default())])
+        .expect("initialize game");
+    for player in &players[1..] {
+        harness
+            .process(&[instruction::join_game(&program_id, player, &game)])
+            .expect("join game");
+    }
+
+    for (i, player) in players.iter().enumerate() {
+        let salt = [i as u8 + 1; 32];
+        let commitment = compute_commitment(&game, 1, player, &salt, &Choice::Paper).expect("valid choice");
+        let commit = instruction::commit_choice(&program_id, player, &game, commitment);
+        // The sysvar trails the stats PDA without displacing it
+        let commit = instruction::with_slot_hashes(instruction::with_stats(&program_id, commit));
+        harness.process(&[commit]).expect("commit");
+        if i < 2 {
+            assert_eq!(announced_order(&harness), None);
+        }
+    }
+
+    let state = harness.game(&game).expect("game");
+    assert!(matches!(state.state, GameState::RevealPhase));
+    let mut seats = state.reveal_order.clone();
+    seats.sort_unstable();
+    assert_eq!(seats, vec![0, 1, 2]);
+    assert_eq!(announced_order(&harness), Some((1, state.reveal_order.clone())));
+
+    let stats_account = harness.account(&stats_address(&program_id).0).expect("stats account");
+    let stats = GlobalStats::unpack_from(&stats_account.data).expect("stats");
+    assert_eq!(stats.commits, 3);
+}
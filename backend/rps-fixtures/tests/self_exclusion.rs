@@ -0,0 +1,81 @@
+//! Self-exclusion: a wallet that excludes itself is refused by every entry
+//! path until the exclusion ends, and it cannot undo that early.
+
+use rps_client::instruction::{self, GameConfig};
+use rps_fixtures::harness::Harness;
+use rps_fixtures::setup::{open_lobby, WALLET_LAMPORTS};
+use solana_rps::RPSError;
+use solana_sdk::pubkey::Pubkey;
+
+const STAKE: u64 = 1_000_000_000;
+const WEEK: u64 = 7 * 24 * 60 * 60;
+
+// Opens a lobby for `host`, or returns the program error that refused it
+fn try_open_lobby(harness: &mut Harness, host: &Pubkey) -> Result<Pubkey, Option<RPSError>> {
+    let program_id = harness.program_id();
+    let game = Pubkey::new_unique();
+    harness
+        .process(&[instruction::initialize_game(&program_id, host, &game, &GameConfig::default())])
+        .map(|_| game)
+        .map_err(|failure| failure.rps_error())
+}
+
+#[test]
+fn excluded_wallets_cannot_enter_anything() {
+    let mut harness = Harness::new(Pubkey::new_unique());
+    let program_id = harness.program_id();
+    let host = harness.new_wallet(WALLET_LAMPORTS);
+    let player = harness.new_wallet(WALLET_LAMPORTS);
+    let game = open_lobby(&mut harness, &[host], &GameConfig::default());
+
+    let until = harness.clock().unix_timestamp as u64 + WEEK;
+    harness
+        .process(&[instruction::self_exclude(&program_id, &player, until)])
+        .expect("self exclude");
+
+    let join = harness.process(&[instruction::join_game(&program_id, &player, &game)]);
+    assert_eq!(join.unwrap_err().rps_error(), Some(RPSError::SelfExcluded));
+    let [base_feed, mint_feed, tokens, vault] = [(); 4].map(|_| Pubkey::new_unique());
+    let staked = instruction::join_game_with_mint(&program_id, &player, &game, &base_feed, &mint_feed, &tokens, &vault, 0);
+    assert_eq!(harness.process(&[staked]).unwrap_err().rps_error(), Some(RPSError::SelfExcluded));
+    assert_eq!(try_open_lobby(&mut harness, &player), Err(Some(RPSError::SelfExcluded)));
+    let challenge = instruction::create_challenge(&program_id, &player, &Pubkey::new_unique(), &host, STAKE, None);
+    assert_eq!(harness.process(&[challenge]).unwrap_err().rps_error(), Some(RPSError::SelfExcluded));
+
+    // Other wallets were never excluded
+    let other = harness.new_wallet(WALLET_LAMPORTS);
+    harness
+        .process(&[instruction::join_game(&program_id, &other, &game)])
+        .expect("join game");
+
+    harness.warp_seconds(WEEK as i64);
+    let game = open_lobby(&mut harness, &[host], &GameConfig::default());
+    harness
+        .process(&[instruction::join_game(&program_id, &player, &game)])
+        .expect("join after the exclusion");
+}
+
+#[test]
+fn exclusions_extend_but_never_shorten() {
+    let mut harness = Harness::new(Pubkey::new_unique());
+    let program_id = harness.program_id();
+    let player = harness.new_wallet(WALLET_LAMPORTS);
+    let now = harness.clock().unix_timestamp as u64;
+
+    let past = harness.process(&[instruction::self_exclude(&program_id, &player, now)]);
+    assert_eq!(past.unwrap_err().rps_error(), Some(RPSError::InvalidParameter));
+
+    harness
+        .process(&[instruction::self_exclude(&program_id, &player, now + WEEK)])
+        .expect("self exclude");
+    let shorter = harness.process(&[instruction::self_exclude(&program_id, &player, now + 60)]);
+    assert_eq!(shorter.unwrap_err().rps_error(), Some(RPSError::SelfExcluded));
+    harness
+        .process(&[instruction::self_exclude(&program_id, &player, now + 2 * WEEK)])
+        .expect("extend exclusion");
+
+    harness.warp_seconds(WEEK as i64 + 1);
+    assert_eq!(try_open_lobby(&mut harness, &player), Err(Some(RPSError::SelfExcluded)));
+    harness.warp_seconds(WEEK as i64);
+    assert!(try_open_lobby(&mut harness, &player).is_ok());
+}
@@ -0,0 +1,93 @@
+//! Self-limits: a player can cap their daily SOL entries and take a
+//! cool-off, and neither can be loosened on the spot.
+
+use rps_client::instruction::{self, GameConfig};
+use rps_fixtures::harness::Harness;
+use rps_fixtures::setup::{open_lobby, WALLET_LAMPORTS};
+use solana_rps::{RPSError, LIMIT_RAISE_DELAY_SECS, WAGER_WINDOW_SECS};
+use solana_sdk::pubkey::Pubkey;
+
+const ENTRY_FEE: u64 = 100_000_000;
+
+// Opens a paid three-seat lobby for `player` to join
+fn join(harness: &mut Harness, host: &Pubkey, player: &Pubkey) -> Option<RPSError> {
+    let program_id = harness.program_id();
+    let config = GameConfig {
+        entry_fee: ENTRY_FEE,
+        ..GameConfig::default()
+    };
+    let game = open_lobby(harness, &[*host], &config);
+    harness
+        .process(&[instruction::join_game(&program_id, player, &game)])
+        .err()
+        .map(|failure| failure.rps_error().expect("program error"))
+}
+
+#[test]
+fn daily_limit_caps_entries_and_raises_wait() {
+    let mut harness = Harness::new(Pubkey::new_unique());
+    let program_id = harness.program_id();
+    let host = harness.new_wallet(WALLET_LAMPORTS);
+    let player = harness.new_wallet(WALLET_LAMPORTS);
+
+    harness
+        .process(&[instruction::set_limits(&program_id, &player, 2 * ENTRY_FEE, 0)])
+        .expect("set limits");
+    assert_eq!(join(&mut harness, &host, &player), None);
+    assert_eq!(join(&mut harness, &host, &player), None);
+    assert_eq!(join(&mut harness, &host, &player), Some(RPSError::SelfLimitReached));
+
+    // Lifting the limit waits out the delay; the next window opens first
+    harness
+        .process(&[instruction::set_limits(&program_id, &player, 0, 0)])
+        .expect("lift limit");
+    assert_eq!(join(&mut harness, &host, &player), Some(RPSError::SelfLimitReached));
+    harness.warp_seconds(WAGER_WINDOW_SECS as i64);
+    assert_eq!(join(&mut harness, &host, &player), None);
+    assert_eq!(join(&mut harness, &host, &player), None);
+    assert_eq!(join(&mut harness, &host, &player), Some(RPSError::SelfLimitReached));
+
+    harness.warp_seconds((LIMIT_RAISE_DELAY_SECS - WAGER_WINDOW_SECS) as i64);
+    for _ in 0..3 {
+        assert_eq!(join(&mut harness, &host, &player), None);
+    }
+
+    // Tightening needs no wait
+    harness
+        .process(&[instruction::set_limits(&program_id, &player, ENTRY_FEE / 2, 0)])
+        .expect("tighten limit");
+    assert_eq!(join(&mut harness, &host, &player), Some(RPSError::SelfLimitReached));
+}
+
+#[test]
+fn cool_off_blocks_joins_and_cannot_be_cut_short() {
+    let mut harness = Harness::new(Pubkey::new_unique());
+    let program_id = harness.program_id();
+    let host = harness.new_wallet(WALLET_LAMPORTS);
+    let player = harness.new_wallet(WALLET_LAMPORTS);
+    let week = 7 * 24 * 60 * 60;
+
+    harness
+        .process(&[instruction::set_limits(&program_id, &player, 0, week)])
+        .expect("cool off");
+    assert_eq!(join(&mut harness, &host, &player), Some(RPSError::SelfLimitReached));
+
+    // Staking another mint is no way around it
+    let game = open_lobby(&mut harness, &[host], &GameConfig::default());
+    let [base_feed, mint_feed, tokens, vault] = [(); 4].map(|_| Pubkey::new_unique());
+    let staked = instruction::join_game_with_mint(&program_id, &player, &game, &base_feed, &mint_feed, &tokens, &vault, 0);
+    assert_eq!(harness.process(&[staked]).unwrap_err().rps_error(), Some(RPSError::SelfLimitReached));
+
+    harness
+        .process(&[instruction::set_limits(&program_id, &player, 0, 60)])
+        .expect("shorter cool-off");
+    harness.warp_seconds(61);
+    assert_eq!(join(&mut harness, &host, &player), Some(RPSError::SelfLimitReached));
+
+    harness.warp_seconds(week as i64);
+    assert_eq!(join(&mut harness, &host, &player), None);
+
+    // Other wallets were never limited
+    let other = harness.new_wallet(WALLET_LAMPORTS);
+    assert_eq!(join(&mut harness, &host, &other), None);
+}
@@ -0,0 +1,78 @@
+//! Signed reveals: a wallet that can only sign text reveals by signing the
+//! human-readable reveal message, whose signature doubles as the salt.
+
+use rps_client::instruction::{self, GameConfig};
+use rps_fixtures::harness::Harness;
+use rps_fixtures::setup::WALLET_LAMPORTS;
+use solana_rps::{compute_commitment, reveal_message, signed_reveal_salt, Choice, RPSError};
+use solana_sdk::{
+    account::Account,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    system_program,
+};
+
+#[test]
+fn reveal_with_a_signed_message() {
+    let mut harness = Harness::new(Pubkey::new_unique());
+    let program_id = harness.program_id();
+    let host = harness.new_wallet(WALLET_LAMPORTS);
+    let guest = harness.new_wallet(WALLET_LAMPORTS);
+    let ledger = Keypair::new();
+    harness.set_account(ledger.pubkey(), Account::new(WALLET_LAMPORTS, 0, &system_program::id()));
+    let game = Pubkey::new_unique();
+
+    harness
+        .process(&[instruction::initialize_game(&program_id, &host, &game, &GameConfig::default())])
+        .expect("initialize game");
+    for player in [guest, ledger.pubkey()] {
+        harness
+            .process(&[instruction::join_game(&program_id, &player, &game)])
+            .expect("join game");
+    }
+
+    // Sign the reveal up front; the signature stays secret until the reveal
+    let round = harness.game(&game).expect("game").current_round;
+    let message = reveal_message(&game, round, &Choice::Rock);
+    let signature: [u8; 64] = ledger.sign_message(&message).into();
+    let signed_salt = signed_reveal_salt(&signature);
+    let commitment = compute_commitment(&game, round, &ledger.pubkey(), &signed_salt, &Choice::Rock).expect("valid choice");
+
+    for (i, player) in [host, guest].iter().enumerate() {
+        let salt = [i as u8 + 1; 32];
+        let commitment = compute_commitment(&game, 1, player, &salt, &Choice::Scissors).expect("valid choice");
+        harness
+            .process(&[instruction::commit_choice(&program_id, player, &game, commitment)])
+            .expect("commit");
+    }
+    harness
+        .process(&[instruction::commit_choice(&program_id, &ledger.pubkey(), &game, commitment)])
+        .expect("commit signed");
+
+    let signed = instruction::reveal_choice_signed(&program_id, &ledger.pubkey(), &game, Choice::Rock, &message, &signature);
+
+    // The verify instruction must come right before the reveal
+    let unverified = harness.process(&signed[1..]);
+    assert_eq!(unverified.unwrap_err().rps_error(), Some(RPSError::InvalidRevealSignature));
+
+    // A signature for one choice cannot reveal another
+    let paper = instruction::reveal_choice_signed(&program_id, &ledger.pubkey(), &game, Choice::Paper, &message, &signature);
+    let swapped = harness.process(&paper);
+    assert_eq!(swapped.unwrap_err().rps_error(), Some(RPSError::InvalidRevealSignature));
+
+    // Somebody else's reveal signature is rejected
+    let mut stolen = instruction::reveal_choice_signed(&program_id, &host, &game, Choice::Rock, &message, &signature);
+    stolen[0] = instruction::ed25519_verify(&ledger.pubkey(), &message, &signature);
+    let stolen = harness.process(&stolen);
+    assert_eq!(stolen.unwrap_err().rps_error(), Some(RPSError::InvalidRevealSignature));
+
+    // The precompile rejects a forged signature before the program runs
+    let forged = instruction::reveal_choice_signed(&program_id, &ledger.pubkey(), &game, Choice::Rock, &message, &[7; 64]);
+    assert_eq!(harness.process(&forged).unwrap_err().instruction_index, 0);
+
+    harness.process(&signed).expect("signed reveal");
+    let state = harness.game(&game).expect("game");
+    let seat = state.players.iter().find(|p| p.pubkey == ledger.pubkey()).expect("seated");
+    assert!(seat.revealed);
+    assert!(matches!(seat.choice, Choice::Rock));
+}
@@ -0,0 +1,134 @@
+//! The off-chain simulation in rps-state tracks the program step for step:
+//! same phases, scores, pot and payouts, and the same rejections.
+
+use borsh::BorshDeserialize;
+use rps_client::instruction::{self, GameConfig};
+use rps_fixtures::harness::Harness;
+use rps_fixtures::setup::WALLET_LAMPORTS;
+use rps_state::simulate::{self, CommitBinding, Hand, Phase, SimError, Simulation};
+use solana_rps::{Choice, Claimable, GameState, RPSError};
+use solana_sdk::pubkey::Pubkey;
+
+const ENTRY_FEE: u64 = 100_000_001;
+
+fn choice(hand: Hand) -> Choice {
+    match hand {
+        Hand::Rock => Choice::Rock,
+        Hand::Paper => Choice::Paper,
+        Hand::Scissors => Choice::Scissors,
+        Hand::Lizard => Choice::Lizard,
+        Hand::Spock => Choice::Spock,
+    }
+}
+
+fn assert_in_step(harness: &Harness, game: &Pubkey, sim: &Simulation) {
+    let state = harness.game(game).expect("game account");
+    let phase = match state.state {
+        GameState::WaitingForPlayers => Phase::WaitingForPlayers,
+        GameState::CommitPhase => Phase::CommitPhase,
+        GameState::RevealPhase => Phase::RevealPhase,
+        GameState::Finished => Phase::Finished,
+    };
+    assert_eq!(phase, sim.phase);
+    assert_eq!(state.current_round, sim.current_round);
+    assert_eq!(state.game_pot, sim.pot);
+    assert_eq!(state.pot_snapshot, sim.pot_snapshot);
+    assert_eq!(state.claimed_bitmap, sim.claimed);
+    let scores: Vec<u8> = state.players.iter().map(|p| p.score).collect();
+    let sim_scores: Vec<u8> = sim.seats.iter().map(|s| s.score).collect();
+    assert_eq!(scores, sim_scores);
+}
+
+#[test]
+fn simulation_matches_the_program() {
+    let mut harness = Harness::new(Pubkey::new_unique());
+    let program_id = harness.program_id();
+    let players: Vec<Pubkey> = (0..3).map(|_| harness.new_wallet(WALLET_LAMPORTS)).collect();
+    let game = Pubkey::new_unique();
+
+    let config = GameConfig {
+        entry_fee: ENTRY_FEE,
+        total_rounds: 2,
+        ..GameConfig::default()
+    };
+    harness
+        .process(&[instruction::initialize_game(&program_id, &players[0], &game, &config)])
+        .expect("initialize game");
+    let mut sim = Simulation::new(game.to_bytes(), players[0].to_bytes(), 3, 2, ENTRY_FEE);
+    assert_in_step(&harness, &game, &sim);
+
+    for player in &players[1..] {
+        harness
+            .process(&[instruction::join_game(&program_id, player, &game)])
+            .expect("join game");
+        sim.join(player.to_bytes()).expect("simulated join");
+        assert_in_step(&harness, &game, &sim);
+    }
+
+    let rounds = [
+        [Hand::Rock, Hand::Scissors, Hand::Scissors],
+        [Hand::Paper, Hand::Scissors, Hand::Rock],
+    ];
+    for (round, hands) in rounds.iter().enumerate() {
+        for (i, (player, hand)) in players.iter().zip(hands).enumerate() {
+            let salt = [(round * 3 + i) as u8 + 1; 32];
+            let binding = CommitBinding {
+                game: game.to_bytes(),
+                round: round as u8 + 1,
+                player: player.to_bytes(),
+            };
+            let commitment = simulate::commitment(&binding, &salt, *hand);
+            harness
+                .process(&[instruction::commit_choice(&program_id, player, &game, commitment)])
+                .expect("commit");
+            sim.commit(player.to_bytes(), commitment, false).expect("simulated commit");
+            assert_in_step(&harness, &game, &sim);
+
+            // Both refuse a second plain commit
+            let again = harness.process(&[instruction::commit_choice(&program_id, player, &game, commitment)]);
+            let sim_again = sim.commit(player.to_bytes(), commitment, false);
+            match sim_again {
+                Err(SimError::AlreadyCommitted) => {
+                    assert_eq!(again.unwrap_err().rps_error(), Some(RPSError::AlreadyCommitted))
+                }
+                Err(SimError::InvalidGameState) => {
+                    assert_eq!(again.unwrap_err().rps_error(), Some(RPSError::InvalidGameState))
+                }
+                other => panic!("unexpected {:?}", other),
+            }
+        }
+
+        // A wrong hand fails the hash check in both
+        let wrong = match hands[0] {
+            Hand::Rock => Hand::Paper,
+            _ => Hand::Rock,
+        };
+        let first_salt = [(round * 3) as u8 + 1; 32];
+        let bad = harness.process(&[instruction::reveal_choice(&program_id, &players[0], &game, choice(wrong), first_salt)]);
+        assert_eq!(bad.unwrap_err().rps_error(), Some(RPSError::InvalidHash));
+        assert_eq!(sim.reveal(players[0].to_bytes(), wrong, first_salt), Err(SimError::InvalidHash));
+
+        for (i, (player, hand)) in players.iter().zip(hands).enumerate() {
+            let salt = [(round * 3 + i) as u8 + 1; 32];
+            harness
+                .process(&[instruction::reveal_choice(&program_id, player, &game, choice(*hand), salt)])
+                .expect("reveal");
+            sim.reveal(player.to_bytes(), *hand, salt).expect("simulated reveal");
+            assert_in_step(&harness, &game, &sim);
+        }
+    }
+    assert_eq!(sim.phase, Phase::Finished);
+
+    for player in &players {
+        harness
+            .process(&[instruction::get_claimable(&program_id, &game, player)])
+            .expect("get claimable");
+        let (_, data) = harness.return_data().expect("return data");
+        let claimable = Claimable::try_from_slice(data).expect("decode claimable");
+        match sim.claimable(&player.to_bytes()) {
+            Ok(amount) => assert_eq!(claimable.amount, amount),
+            Err(SimError::NotWinner) => assert_eq!(claimable, Claimable::default()),
+            Err(other) => panic!("unexpected {:?}", other),
+        }
+    }
+}
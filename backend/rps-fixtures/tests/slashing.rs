@@ -0,0 +1,121 @@
+//! Non-reveal slashing: in a game created with `slash_non_revealers`, a seat
+//! that commits but is timed out of a reveal phase can no longer win, so
+//! its entry goes to the seats that revealed.
+
+use rps_client::instruction::{self, GameConfig};
+use rps_fixtures::harness::Harness;
+use rps_fixtures::setup::WALLET_LAMPORTS;
+use solana_rps::{compute_commitment, Choice, GameState, RPSError};
+use solana_sdk::pubkey::Pubkey;
+
+const ENTRY_FEE: u64 = 100_000_000;
+const TIMEOUT_SECS: u64 = 300;
+
+struct Table {
+    harness: Harness,
+    players: Vec<Pubkey>,
+    game: Pubkey,
+}
+
+impl Table {
+    fn open(total_rounds: u8, slash_non_revealers: bool) -> Self {
+        let mut harness = Harness::new(Pubkey::new_unique());
+        let program_id = harness.program_id();
+        let players: Vec<Pubkey> = (0..3).map(|_| harness.new_wallet(WALLET_LAMPORTS)).collect();
+        let game = Pubkey::new_unique();
+        let config = GameConfig {
+            entry_fee: ENTRY_FEE,
+            timeout_seconds: TIMEOUT_SECS,
+            total_rounds,
+            slash_non_revealers,
+            ..GameConfig::default()
+        };
+        harness
+            .process(&[instruction::initialize_game(&program_id, &players[0], &game, &config)])
+            .expect("initialize game");
+        for player in &players[1..] {
+            harness
+                .process(&[instruction::join_game(&program_id, player, &game)])
+                .expect("join game");
+        }
+        Table { harness, players, game }
+    }
+
+    // Every seat commits its hand, the seats in `revealing` reveal, and the
+    // reveal phase is timed out if anyone stayed silent
+    fn play_round(&mut self, hands: &[Choice; 3], revealing: &[usize]) {
+        let program_id = self.harness.program_id();
+        let round = self.harness.game(&self.game).expect("game").current_round;
+        for (seat, player) in self.players.iter().enumerate() {
+            let commitment =
+                compute_commitment(&self.game, round, player, &salt(seat), &hands[seat]).expect("valid choice");
+            let commit = instruction::commit_choice(&program_id, player, &self.game, commitment);
+            self.harness.process(&[commit]).expect("commit");
+        }
+        for &seat in revealing {
+            let player = &self.players[seat];
+            let reveal = instruction::reveal_choice(&program_id, player, &self.game, hands[seat].clone(), salt(seat));
+            self.harness.process(&[reveal]).expect("reveal");
+        }
+        if revealing.len() < self.players.len() {
+            let phase_nonce = self.harness.game(&self.game).expect("game").phase_nonce;
+            self.harness.warp_seconds(TIMEOUT_SECS as i64);
+            let timeout = instruction::resolve_timeout(&program_id, &self.players[0], &self.game, phase_nonce);
+            self.harness.process(&[timeout]).expect("resolve timeout");
+        }
+    }
+}
+
+fn salt(seat: usize) -> [u8; 32] {
+    [seat as u8 + 1; 32]
+}
+
+#[test]
+fn a_silent_seat_no_longer_shares_a_tied_pot() {
+    // Two rocks tie, and the silent seat scores as little as they do
+    let hands = [Choice::Rock, Choice::Rock, Choice::Scissors];
+    for slash in [false, true] {
+        let mut table = Table::open(1, slash);
+        table.play_round(&hands, &[0, 1]);
+        let state = table.harness.game(&table.game).expect("game");
+        assert!(matches!(state.state, GameState::Finished));
+        assert_eq!(state.winner_bitmap(), if slash { 0b011 } else { 0b111 });
+        assert_eq!(state.slashed, if slash { vec![table.players[2]] } else { Vec::new() });
+    }
+
+    let mut table = Table::open(1, true);
+    table.play_round(&hands, &[0, 1]);
+    let program_id = table.harness.program_id();
+    let pot = table.harness.game(&table.game).expect("game").pot_snapshot;
+    let silent = instruction::claim_winnings(&program_id, &table.players[2], &table.game);
+    assert_eq!(table.harness.process(&[silent]).unwrap_err().rps_error(), Some(RPSError::NotWinner));
+    let balance = table.harness.lamports(&table.players[0]);
+    let claim = instruction::claim_winnings(&program_id, &table.players[0], &table.game);
+    table.harness.process(&[claim]).expect("claim");
+    assert_eq!(table.harness.lamports(&table.players[0]) - balance, pot / 2);
+}
+
+#[test]
+fn a_slashed_leader_forfeits_to_the_best_revealer() {
+    // The third seat sweeps round one, then goes silent in round two
+    let first = [Choice::Scissors, Choice::Scissors, Choice::Rock];
+    let second = [Choice::Rock, Choice::Scissors, Choice::Paper];
+    for slash in [false, true] {
+        let mut table = Table::open(2, slash);
+        table.play_round(&first, &[0, 1, 2]);
+        table.play_round(&second, &[0, 1]);
+        let state = table.harness.game(&table.game).expect("game");
+        assert!(matches!(state.state, GameState::Finished));
+        assert_eq!(state.winner_bitmap(), if slash { 0b001 } else { 0b100 });
+    }
+}
+
+#[test]
+fn slashing_stands_aside_when_nobody_revealed() {
+    let hands = [Choice::Rock, Choice::Paper, Choice::Scissors];
+    let mut table = Table::open(1, true);
+    table.play_round(&hands, &[]);
+    let state = table.harness.game(&table.game).expect("game");
+    assert_eq!(state.slashed.len(), 3);
+    assert_eq!(state.winner_bitmap(), 0b111);
+}
@@ -0,0 +1,79 @@
+//! `GetSpectatorView` answers with the phase, pot and per-seat progress of a
+//! game, and never with a commitment or a hand that is still hidden.
+
+use borsh::BorshDeserialize;
+use rps_client::instruction::{self, GameConfig};
+use rps_fixtures::harness::Harness;
+use rps_fixtures::setup::WALLET_LAMPORTS;
+use solana_rps::{compute_commitment, Choice, GameState, SpectatorView};
+use solana_sdk::pubkey::Pubkey;
+
+const ENTRY_FEE: u64 = 100_000_000;
+
+fn spectate(harness: &mut Harness, game: &Pubkey) -> (SpectatorView, Vec<u8>) {
+    let program_id = harness.program_id();
+    harness
+        .process(&[instruction::get_spectator_view(&program_id, game)])
+        .expect("spectator view");
+    let (program, data) = harness.return_data().expect("return data");
+    assert_eq!(*program, program_id);
+    (SpectatorView::try_from_slice(data).expect("decode view"), data.clone())
+}
+
+#[test]
+fn view_follows_the_game_without_its_secrets() {
+    let mut harness = Harness::new(Pubkey::new_unique());
+    let program_id = harness.program_id();
+    let players: Vec<Pubkey> = (0..3).map(|_| harness.new_wallet(WALLET_LAMPORTS)).collect();
+    let game = Pubkey::new_unique();
+
+    let config = GameConfig {
+        entry_fee: ENTRY_FEE,
+        ..GameConfig::default()
+    };
+    harness
+        .process(&[instruction::initialize_game(&program_id, &players[0], &game, &config)])
+        .expect("initialize game");
+    for player in &players[1..] {
+        harness
+            .process(&[instruction::join_game(&program_id, player, &game)])
+            .expect("join game");
+    }
+
+    let choices = [Choice::Rock, Choice::Paper, Choice::Paper];
+    let mut commitments = Vec::new();
+    for (i, (player, choice)) in players.iter().zip(&choices).enumerate().take(2) {
+        let salt = [i as u8 + 1; 32];
+        let commitment = compute_commitment(&game, 1, player, &salt, choice).expect("valid choice");
+        harness
+            .process(&[instruction::commit_choice(&program_id, player, &game, commitment)])
+            .expect("commit");
+        commitments.push(commitment);
+    }
+
+    let (view, data) = spectate(&mut harness, &game);
+    assert!(matches!(view.state, GameState::CommitPhase));
+    assert_eq!((view.current_round, view.entry_fee), (1, ENTRY_FEE));
+    assert_eq!(view.pot, harness.game(&game).expect("game").game_pot);
+    let committed: Vec<bool> = view.seats.iter().map(|s| s.committed).collect();
+    assert_eq!(committed, vec![true, true, false]);
+    for commitment in &commitments {
+        assert!(!data.windows(64).any(|w| w == commitment));
+    }
+
+    let salt = [3; 32];
+    let commitment = compute_commitment(&game, 1, &players[2], &salt, &choices[2]).expect("valid choice");
+    harness
+        .process(&[
+            instruction::commit_choice(&program_id, &players[2], &game, commitment),
+            instruction::reveal_choice(&program_id, &players[0], &game, Choice::Rock, [1; 32]),
+        ])
+        .expect("commit and reveal");
+
+    // Only the revealed hand shows
+    let (view, _) = spectate(&mut harness, &game);
+    assert!(matches!(view.state, GameState::RevealPhase));
+    assert!(view.seats[0].revealed && matches!(view.seats[0].choice, Choice::Rock));
+    assert!(!view.seats[1].revealed && matches!(view.seats[1].choice, Choice::None));
+    assert_eq!(view.seats.iter().map(|s| s.player).collect::<Vec<_>>(), players);
+}
@@ -0,0 +1,82 @@
+//! The phase table: games only reach `Finished` by finishing, only leave it
+//! by being replayed, and pay out only once finished.
+
+use solana_rps::state_machine::{self, Action, Phase, Transition};
+use solana_rps::GameState;
+
+const STATES: [GameState; 4] = [
+    GameState::WaitingForPlayers,
+    GameState::CommitPhase,
+    GameState::RevealPhase,
+    GameState::Finished,
+];
+const TRANSITIONS: [Transition; 7] = [
+    Transition::Fill,
+    Transition::Reopen,
+    Transition::RestartCommits,
+    Transition::CloseCommits,
+    Transition::NextRound,
+    Transition::Finish,
+    Transition::Replay,
+];
+
+fn phases() -> Vec<Phase> {
+    let mut phases = Vec::new();
+    for state in STATES {
+        for started in [false, true] {
+            for last_round in [false, true] {
+                phases.push(Phase {
+                    state,
+                    started,
+                    last_round,
+                });
+            }
+        }
+    }
+    phases
+}
+
+#[test]
+fn finished_is_entered_by_finishing_and_left_by_replaying() {
+    for phase in phases() {
+        for transition in TRANSITIONS {
+            let Ok(next) = state_machine::next_state(phase, transition) else {
+                continue;
+            };
+            assert_eq!(next == GameState::Finished, transition == Transition::Finish, "{phase:?} {transition:?}");
+            assert_eq!(
+                phase.state == GameState::Finished,
+                transition == Transition::Replay,
+                "{phase:?} {transition:?}"
+            );
+            // Seats only change hands before play starts, and rounds only
+            // continue while one is left
+            if matches!(transition, Transition::Fill | Transition::Reopen | Transition::RestartCommits) {
+                assert!(phase.state == GameState::WaitingForPlayers || !phase.started);
+            }
+            if transition == Transition::NextRound {
+                assert!(!phase.last_round);
+            }
+        }
+    }
+
+    // A reveal phase finishes only on the last round
+    let mid_game = Phase {
+        state: GameState::RevealPhase,
+        started: true,
+        last_round: false,
+    };
+    assert!(state_machine::next_state(mid_game, Transition::Finish).is_err());
+}
+
+#[test]
+fn each_action_belongs_to_its_phases() {
+    for state in STATES {
+        assert_eq!(state_machine::allows(state, Action::Settle), state == GameState::Finished);
+        assert_eq!(state_machine::allows(state, Action::Seat), state == GameState::WaitingForPlayers);
+        assert_eq!(
+            state_machine::allows(state, Action::Play),
+            state_machine::allows(state, Action::Commit) || state_machine::allows(state, Action::Reveal)
+        );
+    }
+}
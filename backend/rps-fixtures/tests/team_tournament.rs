@@ -0,0 +1,114 @@
+//! Team tournaments: clans enter instead of players, each match is a 2v2
+//! game between the clans' lineups, and prizes are split by clan rules.
+
+use rps_client::instruction::{self, TournamentConfig};
+use rps_fixtures::harness::Harness;
+use rps_fixtures::setup::WALLET_LAMPORTS;
+use solana_rps::{clan_address, compute_commitment, Choice, RPSError};
+use solana_sdk::pubkey::Pubkey;
+
+const ENTRY_FEE: u64 = 100_000_000;
+
+#[test]
+fn clans_play_team_matches_and_split_prizes() {
+    let mut harness = Harness::new(Pubkey::new_unique());
+    let program_id = harness.program_id();
+    let host = harness.new_wallet(WALLET_LAMPORTS);
+    let rooks: Vec<Pubkey> = (0..2).map(|_| harness.new_wallet(WALLET_LAMPORTS)).collect();
+    let blades: Vec<Pubkey> = (0..3).map(|_| harness.new_wallet(WALLET_LAMPORTS)).collect();
+
+    let lopsided = harness.process(&[instruction::create_clan(&program_id, &rooks[0], "Rooks", rooks.clone(), vec![7_000, 2_000])]);
+    assert_eq!(lopsided.unwrap_err().rps_error(), Some(RPSError::InvalidParameter));
+    harness
+        .process(&[instruction::create_clan(&program_id, &rooks[0], "Rooks", rooks.clone(), vec![7_000, 3_000])])
+        .expect("create rooks");
+    let taken = harness.process(&[instruction::create_clan(&program_id, &blades[0], "Rooks", blades.clone(), vec![3_334, 3_333, 3_333])]);
+    assert_eq!(taken.unwrap_err().rps_error(), Some(RPSError::InvalidGameState));
+    harness
+        .process(&[instruction::create_clan(&program_id, &blades[0], "Blades", blades.clone(), vec![3_334, 3_333, 3_333])])
+        .expect("create blades");
+    let (rooks_clan, blades_clan) = (clan_address(&program_id, "Rooks").0, clan_address(&program_id, "Blades").0);
+
+    let tournament = Pubkey::new_unique();
+    let config = TournamentConfig {
+        entry_fee: ENTRY_FEE,
+        team_entries: true,
+        ..TournamentConfig::default()
+    };
+    harness
+        .process(&[instruction::create_tournament(&program_id, &host, &tournament, &config)])
+        .expect("create team tournament");
+
+    let solo = harness.process(&[instruction::join_tournament(&program_id, &rooks[0], &tournament, None, None)]);
+    assert_eq!(solo.unwrap_err().rps_error(), Some(RPSError::InvalidGameState));
+    let not_leader = harness.process(&[instruction::join_tournament_as_clan(&program_id, &rooks[1], &tournament, &rooks_clan)]);
+    assert_eq!(not_leader.unwrap_err().rps_error(), Some(RPSError::NotAuthorized));
+    for (leader, clan) in [(rooks[0], rooks_clan), (blades[0], blades_clan)] {
+        harness
+            .process(&[instruction::join_tournament_as_clan(&program_id, &leader, &tournament, &clan)])
+            .expect("enter clan");
+    }
+    harness
+        .process(&[instruction::start_tournament(&program_id, &host, &tournament)])
+        .expect("start tournament");
+
+    // The final spawns a 2v2 game reserved for the clans' lineups
+    let game = Pubkey::new_unique();
+    harness
+        .process(&[instruction::open_team_match(&program_id, &rooks[0], &tournament, &game, &rooks_clan, &blades_clan, 0, 1, 300)])
+        .expect("open team match");
+    let bench = harness.process(&[instruction::join_game(&program_id, &blades[2], &game)]);
+    assert_eq!(bench.unwrap_err().rps_error(), Some(RPSError::NotAuthorized));
+
+    let lineup = [(rooks[0], Choice::Rock), (rooks[1], Choice::Rock), (blades[0], Choice::Scissors), (blades[1], Choice::Scissors)];
+    for (player, _) in &lineup {
+        harness
+            .process(&[instruction::join_game(&program_id, player, &game)])
+            .expect("join team game");
+    }
+    for (i, (player, choice)) in lineup.iter().enumerate() {
+        let salt = [i as u8 + 1; 32];
+        let commitment = compute_commitment(&game, 1, player, &salt, choice).expect("valid choice");
+        harness
+            .process(&[instruction::commit_choice(&program_id, player, &game, commitment)])
+            .expect("commit");
+    }
+    for (i, (player, choice)) in lineup.iter().enumerate() {
+        let salt = [i as u8 + 1; 32];
+        harness
+            .process(&[instruction::reveal_choice(&program_id, player, &game, choice.clone(), salt)])
+            .expect("reveal");
+    }
+
+    harness
+        .process(&[instruction::report_match_result(&program_id, &host, &tournament, 0, &game)])
+        .expect("report match");
+    harness
+        .process(&[instruction::finish_tournament(&program_id, &host, &tournament)])
+        .expect("finish tournament");
+    let state = harness.tournament(&tournament).expect("tournament");
+    assert_eq!(state.standings, vec![rooks_clan, blades_clan]);
+
+    let pool = state.prize_pool;
+    let runner_up = pool * 3 / 10;
+    let champion = pool - runner_up;
+
+    let out_of_order = [rooks[1], rooks[0]];
+    let shuffled = harness.process(&[instruction::claim_team_prize(&program_id, &host, &tournament, &rooks_clan, &out_of_order)]);
+    assert_eq!(shuffled.unwrap_err().rps_error(), Some(RPSError::InvalidParameter));
+
+    for (clan, members, prize) in [(rooks_clan, &rooks, champion), (blades_clan, &blades, runner_up)] {
+        let before: Vec<u64> = members.iter().map(|m| harness.lamports(m)).collect();
+        harness
+            .process(&[instruction::claim_team_prize(&program_id, &host, &tournament, &clan, members)])
+            .expect("claim team prize");
+        let gains: Vec<u64> = members.iter().zip(&before).map(|(m, b)| harness.lamports(m) - b).collect();
+        assert_eq!(gains.iter().sum::<u64>(), prize);
+        if clan == rooks_clan {
+            assert_eq!(gains[1], prize * 3 / 10);
+        }
+    }
+
+    let again = harness.process(&[instruction::claim_team_prize(&program_id, &host, &tournament, &rooks_clan, &rooks)]);
+    assert_eq!(again.unwrap_err().rps_error(), Some(RPSError::AlreadyClaimed));
+}
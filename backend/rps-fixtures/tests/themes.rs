@@ -0,0 +1,34 @@
+//! Game themes: the host picks a client skin at init, stored on the game and
+//! announced with the game's creation.
+
+use rps_client::{
+    instruction::{self, GameConfig},
+    subscribe::{parse_log_event, GameEvent},
+};
+use rps_fixtures::harness::Harness;
+use rps_fixtures::setup::WALLET_LAMPORTS;
+use solana_sdk::pubkey::Pubkey;
+
+const HOLIDAY_THEME: u16 = 12;
+
+#[test]
+fn theme_is_stored_and_announced() {
+    let mut harness = Harness::new(Pubkey::new_unique());
+    let program_id = harness.program_id();
+    let host = harness.new_wallet(WALLET_LAMPORTS);
+    let game = Pubkey::new_unique();
+
+    let config = GameConfig {
+        theme_id: HOLIDAY_THEME,
+        ..GameConfig::default()
+    };
+    harness
+        .process(&[instruction::initialize_game(&program_id, &host, &game, &config)])
+        .expect("initialize game");
+
+    assert_eq!(harness.game(&game).expect("game").theme_id, HOLIDAY_THEME);
+    let events: Vec<GameEvent> = harness.logs().iter().filter_map(|line| parse_log_event(line)).collect();
+    assert!(events.contains(&GameEvent::GameInitialized {
+        theme_id: Some(HOLIDAY_THEME),
+    }));
+}
@@ -0,0 +1,199 @@
+//! Plays every combination of three-player choices through the in-process
+//! harness and checks the on-chain scores against a reference scorer.
+
+use rps_client::instruction::{self, GameConfig};
+use rps_fixtures::harness::Harness;
+use rps_fixtures::setup::WALLET_LAMPORTS;
+use solana_rps::{compute_commitment, Choice, GameState, RPSError};
+use solana_sdk::pubkey::Pubkey;
+
+const CHOICES: [Choice; 3] = [Choice::Rock, Choice::Paper, Choice::Scissors];
+
+fn beats(a: &Choice, b: &Choice) -> bool {
+    matches!(
+        (a, b),
+        (Choice::Rock, Choice::Scissors) | (Choice::Paper, Choice::Rock) | (Choice::Scissors, Choice::Paper)
+    )
+}
+
+// Pairwise points per player, mirroring the rules rather than the program
+fn reference_scores(choices: &[Choice]) -> Vec<u8> {
+    (0..choices.len())
+        .map(|i| {
+            (0..choices.len())
+                .filter(|&j| j != i && beats(&choices[i], &choices[j]))
+                .count() as u8
+        })
+        .collect()
+}
+
+fn combinations() -> Vec<[Choice; 3]> {
+    let mut combos = Vec::new();
+    for a in &CHOICES {
+        for b in &CHOICES {
+            for c in &CHOICES {
+                combos.push([a.clone(), b.clone(), c.clone()]);
+            }
+        }
+    }
+    combos
+}
+
+struct Table {
+    harness: Harness,
+    game: Pubkey,
+    players: Vec<Pubkey>,
+}
+
+impl Table {
+    fn open(total_rounds: u8) -> Self {
+        let mut harness = Harness::new(Pubkey::new_unique());
+        let program_id = harness.program_id();
+        let players: Vec<Pubkey> = (0..3).map(|_| harness.new_wallet(WALLET_LAMPORTS)).collect();
+        let game = Pubkey::new_unique();
+
+        let config = GameConfig {
+            total_rounds,
+            ..GameConfig::default()
+        };
+        harness
+            .process(&[instruction::initialize_game(&program_id, &players[0], &game, &config)])
+            .expect("initialize game");
+        for player in &players[1..] {
+            harness
+                .process(&[instruction::join_game(&program_id, player, &game)])
+                .expect("join game");
+        }
+
+        Self { harness, game, players }
+    }
+
+    fn commit(&mut self, seat: usize, choice: &Choice, round: u8) {
+        let program_id = self.harness.program_id();
+        let salt = salt_for(seat, round);
+        let commitment =
+            compute_commitment(&self.game, round, &self.players[seat], &salt, choice).expect("valid choice");
+        self.harness
+            .process(&[instruction::commit_choice(
+                &program_id,
+                &self.players[seat],
+                &self.game,
+                commitment,
+            )])
+            .expect("commit");
+    }
+
+    fn reveal(&mut self, seat: usize, choice: &Choice, round: u8) {
+        let program_id = self.harness.program_id();
+        self.harness
+            .process(&[instruction::reveal_choice(
+                &program_id,
+                &self.players[seat],
+                &self.game,
+                choice.clone(),
+                salt_for(seat, round),
+            )])
+            .expect("reveal");
+    }
+
+    fn play_round(&mut self, choices: &[Choice], round: u8) {
+        for (seat, choice) in choices.iter().enumerate() {
+            self.commit(seat, choice, round);
+        }
+        for (seat, choice) in choices.iter().enumerate() {
+            self.reveal(seat, choice, round);
+        }
+    }
+
+    fn scores(&self) -> Vec<u8> {
+        let game = self.harness.game(&self.game).expect("game account");
+        game.players.iter().map(|p| p.score).collect()
+    }
+}
+
+fn salt_for(seat: usize, round: u8) -> [u8; 32] {
+    let mut salt = [0x5a; 32];
+    salt[0] = seat as u8;
+    salt[1] = round;
+    salt
+}
+
+#[test]
+fn every_combination_scores_pairwise() {
+    for combo in combinations() {
+        // Second round rotates the seats so each player meets each outcome
+        let rotated = [combo[1].clone(), combo[2].clone(), combo[0].clone()];
+
+        let mut table = Table::open(2);
+        table.play_round(&combo, 1);
+
+        let game = table.harness.game(&table.game).unwrap();
+        assert!(matches!(game.state, GameState::CommitPhase), "{:?}", combo);
+        assert_eq!(game.current_round, 2);
+        assert_eq!(table.scores(), reference_scores(&combo), "round 1 of {:?}", combo);
+
+        table.play_round(&rotated, 2);
+
+        let game = table.harness.game(&table.game).unwrap();
+        assert!(matches!(game.state, GameState::Finished), "{:?}", combo);
+        let expected: Vec<u8> = reference_scores(&combo)
+            .iter()
+            .zip(reference_scores(&rotated))
+            .map(|(a, b)| a + b)
+            .collect();
+        assert_eq!(table.scores(), expected, "{:?} then {:?}", combo, rotated);
+    }
+}
+
+#[test]
+fn unrevealed_player_forfeits_at_timeout() {
+    for combo in combinations() {
+        let mut table = Table::open(1);
+        for (seat, choice) in combo.iter().enumerate() {
+            table.commit(seat, choice, 1);
+        }
+        table.reveal(0, &combo[0], 1);
+        table.reveal(1, &combo[1], 1);
+
+        let program_id = table.harness.program_id();
+        let caller = table.players[0];
+        let nonce = table.harness.game(&table.game).unwrap().phase_nonce;
+        let early = table
+            .harness
+            .process(&[instruction::resolve_timeout(&program_id, &caller, &table.game, nonce)]);
+        assert_eq!(
+            early.unwrap_err().rps_error(),
+            Some(RPSError::TimeoutNotReached)
+        );
+
+        table.harness.warp_seconds(GameConfig::default().timeout_seconds as i64);
+        table
+            .harness
+            .process(&[instruction::resolve_timeout(&program_id, &caller, &table.game, nonce)])
+            .expect("resolve timeout");
+
+        // Replaying the same call cannot fire the timeout again
+        table.harness.warp_seconds(GameConfig::default().timeout_seconds as i64);
+        let replay = table
+            .harness
+            .process(&[instruction::resolve_timeout(&program_id, &caller, &table.game, nonce)]);
+        assert_eq!(replay.unwrap_err().rps_error(), Some(RPSError::StalePhase));
+
+        // The silent seat scores as if it had played nothing
+        let played = [combo[0].clone(), combo[1].clone(), Choice::None];
+        let game = table.harness.game(&table.game).unwrap();
+        assert!(matches!(game.state, GameState::Finished));
+        assert_eq!(table.scores(), reference_scores(&played), "{:?}", combo);
+    }
+}
+
+#[test]
+fn initialize_conserves_lamports() {
+    let table = Table::open(1);
+    let total = table.harness.total_lamports();
+    assert_eq!(total, 3 * WALLET_LAMPORTS as u128);
+
+    let game_account = table.harness.account(&table.game).expect("game account");
+    assert_eq!(game_account.owner, table.harness.program_id());
+    assert_eq!(game_account.data.len(), solana_rps::Game::get_max_size(3));
+}
@@ -0,0 +1,171 @@
+//! RPSToken games: entries move into the game vault, the authority's
+//! associated token account, and the payout and fees are paid back out of
+//! it under the game authority's signature.
+
+use rps_client::instruction::{self, GameConfig};
+use rps_fixtures::harness::Harness;
+use rps_fixtures::setup::{packed, token_account_at, token_balance, WALLET_LAMPORTS};
+use solana_rps::{compute_commitment, game_authority_address, game_vault_address, Choice, GameState, RPSError};
+use solana_sdk::{program_option::COption, program_pack::Pack, pubkey::Pubkey, rent::Rent};
+
+const ENTRY_FEE: u64 = 100_000_000;
+
+#[test]
+fn entries_payout_and_fees_move_through_the_vault() {
+    let mut harness = Harness::new(Pubkey::new_unique());
+    let program_id = harness.program_id();
+    let players: Vec<Pubkey> = (0..3).map(|_| harness.new_wallet(WALLET_LAMPORTS)).collect();
+    let collector = harness.new_wallet(WALLET_LAMPORTS);
+    let game = Pubkey::new_unique();
+
+    let mint = Pubkey::new_unique();
+    harness.set_account(
+        mint,
+        packed(
+            spl_token::state::Mint {
+                mint_authority: COption::None,
+                supply: 30 * ENTRY_FEE,
+                decimals: 9,
+                is_initialized: true,
+                freeze_authority: COption::None,
+            },
+            spl_token::id(),
+        ),
+    );
+    let wallets: Vec<Pubkey> = players
+        .iter()
+        .map(|p| token_account_at(&mut harness, Pubkey::new_unique(), mint, *p, 10 * ENTRY_FEE))
+        .collect();
+    let vault = token_account_at(
+        &mut harness,
+        game_vault_address(&program_id, &game, &mint),
+        mint,
+        game_authority_address(&program_id, &game).0,
+        0,
+    );
+
+    let config = GameConfig {
+        entry_fee: ENTRY_FEE,
+        currency_mode: 1,
+        token_mint: Some(mint),
+        ..GameConfig::default()
+    };
+    harness
+        .process(&[instruction::initialize_game_with_tokens(&program_id, &players[0], &game, &config, &wallets[0])])
+        .expect("initialize game");
+    assert_eq!(token_balance(&harness, &vault), ENTRY_FEE);
+
+    // Entries only land in the game vault
+    let stray = token_account_at(&mut harness, Pubkey::new_unique(), mint, players[1], 0);
+    let mut diverted = instruction::join_game_with_tokens(&program_id, &players[1], &game, &mint, &wallets[1]);
+    let vault_index = diverted.accounts.iter().position(|m| m.pubkey == vault).expect("vault meta");
+    diverted.accounts[vault_index].pubkey = stray;
+    let diverted = harness.process(&[diverted]);
+    assert_eq!(diverted.unwrap_err().rps_error(), Some(RPSError::InvalidTokenAccount));
+
+    for (player, tokens) in players.iter().zip(&wallets).skip(1) {
+        harness
+            .process(&[instruction::join_game_with_tokens(&program_id, player, &game, &mint, tokens)])
+            .expect("join game");
+    }
+    assert_eq!(token_balance(&harness, &vault), 3 * ENTRY_FEE);
+
+    let hands = [Choice::Rock, Choice::Scissors, Choice::Scissors];
+    for (i, (player, hand)) in players.iter().zip(&hands).enumerate() {
+        let salt = [i as u8 + 1; 32];
+        let commitment = compute_commitment(&game, 1, player, &salt, hand).expect("valid choice");
+        harness
+            .process(&[instruction::commit_choice(&program_id, player, &game, commitment)])
+            .expect("commit");
+    }
+    for (i, (player, hand)) in players.iter().zip(&hands).enumerate() {
+        let salt = [i as u8 + 1; 32];
+        harness
+            .process(&[instruction::reveal_choice(&program_id, player, &game, hand.clone(), salt)])
+            .expect("reveal");
+    }
+    let state = harness.game(&game).expect("game");
+    assert!(matches!(state.state, GameState::Finished));
+    let (pot, fees) = (state.game_pot, state.fee_collected);
+    assert_eq!(pot + fees, 3 * ENTRY_FEE);
+
+    // The payout must go to the winner's own token account
+    let hijack = harness.process(&[instruction::claim_winnings_with_tokens(
+        &program_id, &players[0], &game, &mint, &wallets[1],
+    )]);
+    assert_eq!(hijack.unwrap_err().rps_error(), Some(RPSError::InvalidTokenAccount));
+    harness
+        .process(&[instruction::claim_winnings_with_tokens(&program_id, &players[0], &game, &mint, &wallets[0])])
+        .expect("claim winnings");
+    assert_eq!(token_balance(&harness, &wallets[0]), 9 * ENTRY_FEE + pot);
+    assert_eq!(token_balance(&harness, &vault), fees);
+
+    let collector_tokens = token_account_at(&mut harness, Pubkey::new_unique(), mint, collector, 0);
+    harness
+        .process(&[instruction::collect_fees_with_tokens(&program_id, &collector, &game, &mint, &collector_tokens)])
+        .expect("collect fees");
+    assert_eq!(token_balance(&harness, &collector_tokens), fees);
+    assert_eq!(token_balance(&harness, &vault), 0);
+    assert_eq!(harness.game(&game).expect("game").fee_collected, 0);
+}
+
+#[test]
+fn the_game_vault_is_created_ahead_of_the_game() {
+    let mut harness = Harness::new(Pubkey::new_unique());
+    let program_id = harness.program_id();
+    let host = harness.new_wallet(WALLET_LAMPORTS);
+    let game = Pubkey::new_unique();
+
+    let mint = Pubkey::new_unique();
+    harness.set_account(
+        mint,
+        packed(
+            spl_token::state::Mint {
+                mint_authority: COption::None,
+                supply: 10 * ENTRY_FEE,
+                decimals: 9,
+                is_initialized: true,
+                freeze_authority: COption::None,
+            },
+            spl_token::id(),
+        ),
+    );
+    let host_tokens = token_account_at(&mut harness, Pubkey::new_unique(), mint, host, 10 * ENTRY_FEE);
+    let vault = game_vault_address(&program_id, &game, &mint);
+    let config = GameConfig {
+        entry_fee: ENTRY_FEE,
+        currency_mode: 1,
+        token_mint: Some(mint),
+        ..GameConfig::default()
+    };
+
+    // The game account does not exist yet, and the vault is made for it
+    // in the same transaction
+    let create_vault = instruction::create_game_token_vault(&program_id, &host, &game, &mint);
+    let before = harness.lamports(&host);
+    harness
+        .process(&[
+            create_vault.clone(),
+            instruction::initialize_game_with_tokens(&program_id, &host, &game, &config, &host_tokens),
+        ])
+        .expect("create vault and initialize game");
+    let account = harness.account(&vault).expect("game vault");
+    assert_eq!(account.owner, spl_token::id());
+    assert_eq!(account.lamports, Rent::default().minimum_balance(spl_token::state::Account::LEN));
+    let state = spl_token::state::Account::unpack(&account.data).expect("token state");
+    assert_eq!(state.owner, game_authority_address(&program_id, &game).0);
+    assert_eq!(state.mint, mint);
+    assert_eq!(state.amount, ENTRY_FEE);
+    assert!(before - harness.lamports(&host) >= account.lamports);
+
+    // Sending it again changes nothing
+    let before = harness.lamports(&host);
+    harness.process(&[create_vault]).expect("create vault again");
+    assert_eq!(harness.lamports(&host), before);
+    assert_eq!(token_balance(&harness, &vault), ENTRY_FEE);
+
+    // A token account that is not the game's vault is refused
+    let mut stray = instruction::create_game_token_vault(&program_id, &host, &game, &mint);
+    stray.accounts[3].pubkey = host_tokens;
+    assert_eq!(harness.process(&[stray]).unwrap_err().rps_error(), Some(RPSError::InvalidTokenAccount));
+}
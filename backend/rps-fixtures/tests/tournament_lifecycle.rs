@@ -0,0 +1,357 @@
+//! Runs a three-player SOL tournament from creation to prize claims through
+//! the in-process harness, deciding every match with a linked game.
+
+use rps_client::instruction::{self, GameConfig, TournamentConfig};
+use rps_fixtures::harness::Harness;
+use rps_fixtures::setup::WALLET_LAMPORTS;
+use solana_rps::{compute_commitment, treasury_address, Choice, RPSError};
+use solana_sdk::pubkey::Pubkey;
+
+const ENTRY_FEE: u64 = 100_000_000;
+
+// Plays a one-round game for bracket match `match_index`, hosted by
+// `seats[0]` and linked to the match before the other seats join. Games
+// need three seats, so `seats[2]` fills in. Returns the game account.
+fn play_match(
+    harness: &mut Harness,
+    tournament: &Pubkey,
+    match_index: u8,
+    seats: [Pubkey; 3],
+    choices: [Choice; 3],
+) -> Pubkey {
+    let program_id = harness.program_id();
+    let game = Pubkey::new_unique();
+    harness
+        .process(&[instruction::initialize_game(&program_id, &seats[0], &game, &GameConfig::default())])
+        .expect("initialize match game");
+    harness
+        .process(&[instruction::open_tournament_match(&program_id, &seats[0], tournament, match_index, &game)])
+        .expect("open match");
+    for seat in &seats[1..] {
+        harness
+            .process(&[instruction::join_game(&program_id, seat, &game)])
+            .expect("join match game");
+    }
+    for (i, (seat, choice)) in seats.iter().zip(&choices).enumerate() {
+        let salt = [i as u8 + 1; 32];
+        let commitment = compute_commitment(&game, 1, seat, &salt, choice).expect("valid choice");
+        harness
+            .process(&[instruction::commit_choice(&program_id, seat, &game, commitment)])
+            .expect("commit");
+    }
+    for (i, (seat, choice)) in seats.iter().zip(&choices).enumerate() {
+        let salt = [i as u8 + 1; 32];
+        harness
+            .process(&[instruction::reveal_choice(&program_id, seat, &game, choice.clone(), salt)])
+            .expect("reveal");
+    }
+    game
+}
+
+// Creates a four-seat tournament, registers `players` and starts it
+fn start_tournament(harness: &mut Harness, host: &Pubkey, players: &[Pubkey], entry_fee: u64) -> Pubkey {
+    let program_id = harness.program_id();
+    let tournament = Pubkey::new_unique();
+    harness
+        .process(&[instruction::create_tournament(
+            &program_id,
+            host,
+            &tournament,
+            &TournamentConfig {
+                entry_fee,
+                ..TournamentConfig::default()
+            },
+        )])
+        .expect("create tournament");
+    for player in players {
+        harness
+            .process(&[instruction::join_tournament(&program_id, player, &tournament, None, None)])
+            .expect("join tournament");
+    }
+    harness
+        .process(&[instruction::start_tournament(&program_id, host, &tournament)])
+        .expect("start tournament");
+    tournament
+}
+
+#[test]
+fn bracket_runs_to_champion_and_pays_placings() {
+    let mut harness = Harness::new(Pubkey::new_unique());
+    let program_id = harness.program_id();
+    let host = harness.new_wallet(WALLET_LAMPORTS);
+    let players: Vec<Pubkey> = (0..3).map(|_| harness.new_wallet(WALLET_LAMPORTS)).collect();
+    let treasury = treasury_address(&program_id).0;
+    let tournament = start_tournament(&mut harness, &host, &players, ENTRY_FEE);
+
+    // Four seats for three players: players[0] meets players[2] and
+    // players[1] takes the bye straight into the final
+    let state = harness.tournament(&tournament).expect("tournament");
+    assert_eq!(state.bracket.len(), 3);
+    assert_eq!(state.bracket[1].winner, Some(players[1]));
+    assert_eq!(state.bracket[2].player_b, Some(players[1]));
+
+    let early = harness.process(&[instruction::finish_tournament(&program_id, &players[0], &tournament)]);
+    assert_eq!(early.unwrap_err().rps_error(), Some(RPSError::InvalidGameState));
+
+    // A drawn game decides nothing and frees the slot for a replay
+    let drawn = play_match(
+        &mut harness,
+        &tournament,
+        0,
+        [players[0], players[2], host],
+        [Choice::Rock, Choice::Rock, Choice::Rock],
+    );
+    harness
+        .process(&[instruction::report_match_result(&program_id, &host, &tournament, 0, &drawn)])
+        .expect("report draw");
+    let state = harness.tournament(&tournament).expect("tournament");
+    assert_eq!(state.bracket[0].winner, None);
+    assert_eq!(state.bracket[0].game, None);
+
+    // Results can only come from the game linked to the slot
+    let stale = harness.process(&[instruction::report_match_result(&program_id, &host, &tournament, 0, &drawn)]);
+    assert_eq!(stale.unwrap_err().rps_error(), Some(RPSError::InvalidParameter));
+
+    let semi = play_match(
+        &mut harness,
+        &tournament,
+        0,
+        [players[2], players[0], host],
+        [Choice::Paper, Choice::Rock, Choice::Rock],
+    );
+    harness
+        .process(&[instruction::report_match_result(&program_id, &players[2], &tournament, 0, &semi)])
+        .expect("report semi-final");
+
+    let final_game = play_match(
+        &mut harness,
+        &tournament,
+        2,
+        [players[1], players[2], host],
+        [Choice::Scissors, Choice::Paper, Choice::Paper],
+    );
+    let wrong_slot = harness.process(&[instruction::report_match_result(&program_id, &host, &tournament, 0, &final_game)]);
+    assert_eq!(wrong_slot.unwrap_err().rps_error(), Some(RPSError::InvalidGameState));
+    harness
+        .process(&[instruction::report_match_result(&program_id, &host, &tournament, 2, &final_game)])
+        .expect("report final");
+    harness
+        .process(&[instruction::finish_tournament(&program_id, &players[0], &tournament)])
+        .expect("finish tournament");
+
+    let state = harness.tournament(&tournament).expect("tournament");
+    let pool = 3 * ENTRY_FEE;
+    let fee = pool / 100;
+    assert!(state.is_finished);
+    assert_eq!(state.champion, Some(players[1]));
+    assert_eq!(state.standings, vec![players[1], players[2], players[0]]);
+    assert_eq!(state.prize_pool, pool - fee);
+    assert_eq!(harness.lamports(&treasury), fee);
+
+    let runner_up = (pool - fee) * 3 / 10;
+    let champion = pool - fee - runner_up;
+    for (player, prize) in [(players[1], champion), (players[2], runner_up)] {
+        let before = harness.lamports(&player);
+        harness
+            .process(&[instruction::claim_tournament_prize(&program_id, &player, &tournament)])
+            .expect("claim prize");
+        assert_eq!(harness.lamports(&player) - before, prize);
+    }
+
+    let again = harness.process(&[instruction::claim_tournament_prize(&program_id, &players[1], &tournament)]);
+    assert_eq!(again.unwrap_err().rps_error(), Some(RPSError::AlreadyClaimed));
+    let unplaced = harness.process(&[instruction::claim_tournament_prize(&program_id, &players[0], &tournament)]);
+    assert_eq!(unplaced.unwrap_err().rps_error(), Some(RPSError::NotWinner));
+}
+
+#[test]
+fn absent_and_disqualified_players_are_walked_over() {
+    let mut harness = Harness::new(Pubkey::new_unique());
+    let program_id = harness.program_id();
+    let host = harness.new_wallet(WALLET_LAMPORTS);
+    let players: Vec<Pubkey> = (0..4).map(|_| harness.new_wallet(WALLET_LAMPORTS)).collect();
+    let tournament = start_tournament(&mut harness, &host, &players, 0);
+
+    // players[2] never shows up for match 0; the game is cancelled at the
+    // timeout and players[0], still seated, takes the walkover
+    let game = Pubkey::new_unique();
+    harness
+        .process(&[instruction::initialize_game(&program_id, &players[0], &game, &GameConfig::default())])
+        .expect("initialize match game");
+    harness
+        .process(&[instruction::open_tournament_match(&program_id, &players[0], &tournament, 0, &game)])
+        .expect("open match");
+    harness
+        .process(&[instruction::join_game(&program_id, &host, &game)])
+        .expect("join match game");
+    harness.warp_seconds(301);
+    let nonce = harness.game(&game).expect("game").phase_nonce;
+    harness
+        .process(&[instruction::resolve_timeout(&program_id, &host, &game, nonce)])
+        .expect("resolve timeout");
+    harness
+        .process(&[instruction::report_match_result(&program_id, &host, &tournament, 0, &game)])
+        .expect("report walkover");
+    assert_eq!(harness.tournament(&tournament).expect("tournament").bracket[0].winner, Some(players[0]));
+
+    let forged = harness.process(&[instruction::disqualify_player(&program_id, &players[1], &tournament, &players[3])]);
+    assert_eq!(forged.unwrap_err().rps_error(), Some(RPSError::NotAuthorized));
+
+    // The host delegates to a co-admin, who cannot appoint others
+    let admin = harness.new_wallet(WALLET_LAMPORTS);
+    harness
+        .process(&[instruction::set_tournament_admins(&program_id, &host, &tournament, vec![admin])])
+        .expect("set co-admins");
+    let appointed = harness.process(&[instruction::set_tournament_admins(&program_id, &admin, &tournament, vec![players[0]])]);
+    assert_eq!(appointed.unwrap_err().rps_error(), Some(RPSError::NotAuthorized));
+
+    // Disqualifying players[3] hands match 1 to players[1]; disqualifying
+    // them in turn hands the final to players[0]
+    for player in [players[3], players[1]] {
+        harness
+            .process(&[instruction::disqualify_player(&program_id, &admin, &tournament, &player)])
+            .expect("disqualify");
+    }
+    let state = harness.tournament(&tournament).expect("tournament");
+    assert_eq!(state.bracket[1].winner, Some(players[1]));
+    assert_eq!(state.bracket[2].winner, Some(players[0]));
+
+    harness
+        .process(&[instruction::finish_tournament(&program_id, &host, &tournament)])
+        .expect("finish tournament");
+    let state = harness.tournament(&tournament).expect("tournament");
+    assert_eq!(state.champion, Some(players[0]));
+    assert_eq!(state.standings, vec![players[0], players[2], players[1], players[3]]);
+}
+
+#[test]
+fn finishing_can_pay_the_placings_directly() {
+    let mut harness = Harness::new(Pubkey::new_unique());
+    let program_id = harness.program_id();
+    let host = harness.new_wallet(WALLET_LAMPORTS);
+    let players: Vec<Pubkey> = (0..2).map(|_| harness.new_wallet(WALLET_LAMPORTS)).collect();
+    let tournament = start_tournament(&mut harness, &host, &players, ENTRY_FEE);
+
+    // A two-player bracket is just the final; a disqualification decides it
+    harness
+        .process(&[instruction::disqualify_player(&program_id, &host, &tournament, &players[1])])
+        .expect("disqualify");
+    let reversed = harness.process(&[instruction::finish_tournament_with_payouts(
+        &program_id,
+        &host,
+        &tournament,
+        &[players[1], players[0]],
+    )]);
+    assert_eq!(reversed.unwrap_err().rps_error(), Some(RPSError::InvalidParameter));
+
+    let before: Vec<u64> = players.iter().map(|p| harness.lamports(p)).collect();
+    harness
+        .process(&[instruction::finish_tournament_with_payouts(&program_id, &host, &tournament, &players)])
+        .expect("finish tournament");
+    let pool = 2 * ENTRY_FEE - 2 * ENTRY_FEE / 100;
+    let runner_up = pool * 3 / 10;
+    let gains: Vec<u64> = players.iter().zip(&before).map(|(p, b)| harness.lamports(p) - b).collect();
+    assert_eq!(gains, vec![pool - runner_up, runner_up]);
+
+    let again = harness.process(&[instruction::claim_tournament_prize(&program_id, &players[0], &tournament)]);
+    assert_eq!(again.unwrap_err().rps_error(), Some(RPSError::AlreadyClaimed));
+}
+
+#[test]
+fn spawned_match_game_decides_the_final() {
+    let mut harness = Harness::new(Pubkey::new_unique());
+    let program_id = harness.program_id();
+    let host = harness.new_wallet(WALLET_LAMPORTS);
+    let outsider = harness.new_wallet(WALLET_LAMPORTS);
+    let players: Vec<Pubkey> = (0..2).map(|_| harness.new_wallet(WALLET_LAMPORTS)).collect();
+    let tournament = start_tournament(&mut harness, &host, &players, 0);
+
+    let game = Pubkey::new_unique();
+    let stranger = harness.process(&[instruction::spawn_tournament_match(&program_id, &outsider, &tournament, 0, &game, 1, 300)]);
+    assert_eq!(stranger.unwrap_err().rps_error(), Some(RPSError::NotAuthorized));
+    harness
+        .process(&[instruction::spawn_tournament_match(&program_id, &players[1], &tournament, 0, &game, 1, 300)])
+        .expect("spawn match game");
+
+    let state = harness.game(&game).expect("match game");
+    assert_eq!((state.tournament, state.tournament_match), (Some(tournament), 0));
+    assert_eq!(state.team_lineup, players);
+    assert_eq!(harness.tournament(&tournament).expect("tournament").bracket[0].game, Some(game));
+
+    // Only the two finalists can take the seats
+    let gatecrash = harness.process(&[instruction::join_game(&program_id, &outsider, &game)]);
+    assert_eq!(gatecrash.unwrap_err().rps_error(), Some(RPSError::NotAuthorized));
+    for player in &players {
+        harness
+            .process(&[instruction::join_game(&program_id, player, &game)])
+            .expect("join match game");
+    }
+
+    let hands = [Choice::Scissors, Choice::Rock];
+    for (i, (player, hand)) in players.iter().zip(&hands).enumerate() {
+        let salt = [i as u8 + 1; 32];
+        let commitment = compute_commitment(&game, 1, player, &salt, hand).expect("valid choice");
+        harness
+            .process(&[instruction::commit_choice(&program_id, player, &game, commitment)])
+            .expect("commit");
+    }
+    for (i, (player, hand)) in players.iter().zip(&hands).enumerate() {
+        let salt = [i as u8 + 1; 32];
+        harness
+            .process(&[instruction::reveal_choice(&program_id, player, &game, hand.clone(), salt)])
+            .expect("reveal");
+    }
+
+    harness
+        .process(&[instruction::report_match_result(&program_id, &outsider, &tournament, 0, &game)])
+        .expect("report result");
+    let state = harness.tournament(&tournament).expect("tournament");
+    assert_eq!(state.bracket[0].winner, Some(players[1]));
+}
+
+#[test]
+fn qualified_tournament_admits_only_top_placings() {
+    let mut harness = Harness::new(Pubkey::new_unique());
+    let program_id = harness.program_id();
+    let host = harness.new_wallet(WALLET_LAMPORTS);
+    let players: Vec<Pubkey> = (0..2).map(|_| harness.new_wallet(WALLET_LAMPORTS)).collect();
+
+    // A two-player qualifier settled by walkover: players[0] places first
+    let qualifier = start_tournament(&mut harness, &host, &players, 0);
+    harness
+        .process(&[instruction::disqualify_player(&program_id, &host, &qualifier, &players[1])])
+        .expect("disqualify");
+
+    let main_event = Pubkey::new_unique();
+    harness
+        .process(&[instruction::create_tournament(
+            &program_id,
+            &host,
+            &main_event,
+            &TournamentConfig {
+                qualifier_of: Some(qualifier),
+                qualifier_top_n: 1,
+                ..TournamentConfig::default()
+            },
+        )])
+        .expect("create main event");
+
+    let unfinished = harness.process(&[instruction::join_tournament(&program_id, &players[0], &main_event, None, Some(&qualifier))]);
+    assert_eq!(unfinished.unwrap_err().rps_error(), Some(RPSError::NotQualified));
+
+    harness
+        .process(&[instruction::finish_tournament(&program_id, &host, &qualifier)])
+        .expect("finish qualifier");
+
+    let elsewhere = start_tournament(&mut harness, &host, &players, 0);
+    let wrong = harness.process(&[instruction::join_tournament(&program_id, &players[0], &main_event, None, Some(&elsewhere))]);
+    assert_eq!(wrong.unwrap_err().rps_error(), Some(RPSError::InvalidParameter));
+
+    let unplaced = harness.process(&[instruction::join_tournament(&program_id, &players[1], &main_event, None, Some(&qualifier))]);
+    assert_eq!(unplaced.unwrap_err().rps_error(), Some(RPSError::NotQualified));
+
+    harness
+        .process(&[instruction::join_tournament(&program_id, &players[0], &main_event, None, Some(&qualifier))])
+        .expect("qualified join");
+    assert_eq!(harness.tournament(&main_event).expect("tournament").players, vec![players[0]]);
+}
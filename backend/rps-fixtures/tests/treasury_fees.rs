@@ -0,0 +1,58 @@
+//! SOL entry fees reach the treasury in one hop from the player; the pot
+//! vault only ever holds the pot.
+
+use rps_client::instruction::{self, GameConfig};
+use rps_fixtures::harness::Harness;
+use rps_fixtures::setup::WALLET_LAMPORTS;
+use solana_rps::{pot_vault_address, treasury_address};
+use solana_sdk::{pubkey::Pubkey, rent::Rent};
+
+const ENTRY_FEE: u64 = 100_000_000;
+
+#[test]
+fn platform_fee_goes_straight_to_the_treasury() {
+    let mut harness = Harness::new(Pubkey::new_unique());
+    let program_id = harness.program_id();
+    let players: Vec<Pubkey> = (0..3).map(|_| harness.new_wallet(WALLET_LAMPORTS)).collect();
+    let game = Pubkey::new_unique();
+    let treasury = treasury_address(&program_id).0;
+
+    let config = GameConfig {
+        entry_fee: ENTRY_FEE,
+        ..GameConfig::default()
+    };
+    harness
+        .process(&[instruction::initialize_game(&program_id, &players[0], &game, &config)])
+        .expect("initialize game");
+    for player in &players[1..] {
+        harness
+            .process(&[instruction::join_game(&program_id, player, &game)])
+            .expect("join game");
+    }
+
+    let fee = ENTRY_FEE / 100;
+    let state = harness.game(&game).expect("game account");
+    assert_eq!(harness.lamports(&treasury), 3 * fee);
+    assert_eq!(state.fee_collected, 0);
+    assert_eq!(state.game_pot, 3 * (ENTRY_FEE - fee));
+
+    let vault = harness.lamports(&pot_vault_address(&program_id, &game).0);
+    assert_eq!(vault - Rent::default().minimum_balance(0), state.game_pot);
+}
+
+#[test]
+fn entries_must_name_the_real_treasury() {
+    let mut harness = Harness::new(Pubkey::new_unique());
+    let program_id = harness.program_id();
+    let host = harness.new_wallet(WALLET_LAMPORTS);
+    let game = Pubkey::new_unique();
+
+    let config = GameConfig {
+        entry_fee: ENTRY_FEE,
+        ..GameConfig::default()
+    };
+    let mut ix = instruction::initialize_game(&program_id, &host, &game, &config);
+    ix.accounts[3].pubkey = Pubkey::new_unique();
+    let diverted = harness.process(&[ix]);
+    assert_eq!(diverted.unwrap_err().rps_error(), Some(solana_rps::RPSError::InvalidParameter));
+}
@@ -0,0 +1,106 @@
+//! Waitlists: full lobbies queue extra players with escrowed entries, hand
+//! them seats that open up before play starts, and refund the rest after.
+
+use rps_client::instruction::{self, GameConfig};
+use rps_fixtures::harness::Harness;
+use rps_fixtures::setup::WALLET_LAMPORTS;
+use solana_rps::{compute_commitment, waitlist_address, Choice, GameState, RPSError};
+use solana_sdk::pubkey::Pubkey;
+
+const ENTRY_FEE: u64 = 100_000_000;
+
+#[test]
+fn waitlisted_players_take_vacated_seats() {
+    let mut harness = Harness::new(Pubkey::new_unique());
+    let program_id = harness.program_id();
+    let players: Vec<Pubkey> = (0..3).map(|_| harness.new_wallet(WALLET_LAMPORTS)).collect();
+    let queued: Vec<Pubkey> = (0..3).map(|_| harness.new_wallet(WALLET_LAMPORTS)).collect();
+    let (host, game) = (players[0], Pubkey::new_unique());
+
+    let config = GameConfig {
+        entry_fee: ENTRY_FEE,
+        ..GameConfig::default()
+    };
+    harness
+        .process(&[instruction::initialize_game(&program_id, &host, &game, &config)])
+        .expect("initialize game");
+    harness
+        .process(&[instruction::join_game(&program_id, &players[1], &game)])
+        .expect("join game");
+    let open = harness.process(&[instruction::join_waitlist(&program_id, &queued[0], &game)]);
+    assert_eq!(open.unwrap_err().rps_error(), Some(RPSError::InvalidGameState));
+    harness
+        .process(&[instruction::join_game(&program_id, &players[2], &game)])
+        .expect("join game");
+
+    // With nobody queued a departure reopens the lobby
+    let share = harness.game(&game).expect("game").game_pot / 3;
+    let before = harness.lamports(&players[2]);
+    harness
+        .process(&[instruction::leave_game(&program_id, &players[2], &game)])
+        .expect("leave game");
+    assert_eq!(harness.lamports(&players[2]) - before, share);
+    let state = harness.game(&game).expect("game");
+    assert!(matches!(state.state, GameState::WaitingForPlayers));
+    assert_eq!(state.game_pot, share * 2);
+    harness
+        .process(&[instruction::join_game(&program_id, &players[2], &game)])
+        .expect("rejoin game");
+
+    for wallet in &queued[..2] {
+        harness
+            .process(&[instruction::join_waitlist(&program_id, wallet, &game)])
+            .expect("join waitlist");
+    }
+    let seated = harness.process(&[instruction::join_waitlist(&program_id, &players[1], &game)]);
+    assert_eq!(seated.unwrap_err().rps_error(), Some(RPSError::PlayerAlreadyJoined));
+    let twice = harness.process(&[instruction::join_waitlist(&program_id, &queued[1], &game)]);
+    assert_eq!(twice.unwrap_err().rps_error(), Some(RPSError::PlayerAlreadyJoined));
+
+    let host_leaves = harness.process(&[instruction::leave_game(&program_id, &host, &game)]);
+    assert_eq!(host_leaves.unwrap_err().rps_error(), Some(RPSError::InvalidPlayerState));
+    let forged = harness.process(&[instruction::kick_player(&program_id, &players[1], &game, &players[2])]);
+    assert_eq!(forged.unwrap_err().rps_error(), Some(RPSError::NotAuthorized));
+
+    // A leaver's seat goes to the head of the queue, a kicked one's next
+    harness
+        .process(&[instruction::leave_game(&program_id, &players[1], &game)])
+        .expect("leave game");
+    harness
+        .process(&[instruction::kick_player(&program_id, &host, &game, &players[2])])
+        .expect("kick player");
+    let state = harness.game(&game).expect("game");
+    let seats: Vec<Pubkey> = state.players.iter().map(|p| p.pubkey).collect();
+    assert_eq!(seats, vec![host, queued[0], queued[1]]);
+    assert!(matches!(state.state, GameState::CommitPhase));
+    assert_eq!(state.game_pot, share * 3);
+
+    harness
+        .process(&[instruction::join_waitlist(&program_id, &queued[2], &game)])
+        .expect("join waitlist");
+    let early = harness.process(&[instruction::refund_waitlist(&program_id, &host, &game, &queued[2..])]);
+    assert_eq!(early.unwrap_err().rps_error(), Some(RPSError::InvalidGameState));
+
+    // The first commit starts play: seats are locked and the queue refunds
+    let salt = [1; 32];
+    let commitment = compute_commitment(&game, 1, &host, &salt, &Choice::Rock).expect("valid choice");
+    harness
+        .process(&[instruction::commit_choice(&program_id, &host, &game, commitment)])
+        .expect("commit");
+    let late = harness.process(&[instruction::leave_game(&program_id, &queued[0], &game)]);
+    assert_eq!(late.unwrap_err().rps_error(), Some(RPSError::InvalidGameState));
+    let closed = harness.process(&[instruction::join_waitlist(&program_id, &players[1], &game)]);
+    assert_eq!(closed.unwrap_err().rps_error(), Some(RPSError::InvalidGameState));
+
+    let wrong = harness.process(&[instruction::refund_waitlist(&program_id, &host, &game, &players[1..2])]);
+    assert_eq!(wrong.unwrap_err().rps_error(), Some(RPSError::InvalidParameter));
+    let before = harness.lamports(&queued[2]);
+    harness
+        .process(&[instruction::refund_waitlist(&program_id, &host, &game, &queued[2..])])
+        .expect("refund waitlist");
+    assert_eq!(harness.lamports(&queued[2]) - before, ENTRY_FEE);
+    assert!(harness.account(&waitlist_address(&program_id, &game).0).is_some());
+
+    let again = harness.process(&[instruction::refund_waitlist(&program_id, &host, &game, &queued[2..])]);
+    assert_eq!(again.unwrap_err().rps_error(), Some(RPSError::AlreadyClaimed));
+}
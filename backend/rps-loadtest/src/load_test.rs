@@ -0,0 +1,181 @@
+//! Funding the wallets and playing one game through, timing every
+//! transaction.
+
+use std::{sync::Mutex, time::Instant};
+
+use futures::future::join_all;
+use rps_bot::{Strategy, Uniform};
+use rps_client::{
+    compute_budget::{estimate_transaction_units, with_compute_budget},
+    instruction::{self, GameConfig},
+    send::{fetch_game, send_and_confirm, SendConfig, SendOutcome},
+    Commitment, RpsClientError,
+};
+use solana_client::{nonblocking::rpc_client::RpcClient, rpc_config::RpcTransactionConfig};
+use solana_sdk::{
+    commitment_config::CommitmentConfig,
+    instruction::Instruction,
+    pubkey::Pubkey,
+    signature::{Keypair, Signature},
+    signer::Signer,
+    system_instruction,
+    transaction::{Transaction, VersionedTransaction},
+};
+use solana_rps::Game;
+use solana_transaction_status::UiTransactionEncoding;
+
+use crate::metrics::{Landed, Metrics, Sample};
+
+// Transfers per funding transaction, well within the size limit
+const FUNDING_BATCH: usize = 20;
+
+pub struct LoadTest {
+    pub rpc: RpcClient,
+    pub payer: Keypair,
+    pub program_id: Pubkey,
+    pub config: GameConfig,
+    pub send: SendConfig,
+    pub metrics: Mutex<Metrics>,
+}
+
+impl LoadTest {
+    /// Sends `lamports` to every wallet in `wallets` from the payer. Every
+    /// batch locks the payer, so funding is kept out of the measured games.
+    pub async fn fund(&self, wallets: &[Pubkey], lamports: u64) -> Result<(), RpsClientError> {
+        let payer = self.payer.pubkey();
+        for batch in wallets.chunks(FUNDING_BATCH) {
+            let transfers: Vec<Instruction> = batch
+                .iter()
+                .map(|wallet| system_instruction::transfer(&payer, wallet, lamports))
+                .collect();
+            send_and_confirm(&self.rpc, &self.send, None, |blockhash| {
+                let tx = Transaction::new_signed_with_payer(&transfers, Some(&payer), &[&self.payer], blockhash);
+                Ok(VersionedTransaction::from(tx))
+            })
+            .await?;
+        }
+        Ok(())
+    }
+
+    /// Plays one game between `wallets`, the first of them hosting: every
+    /// seat joins, commits and reveals at once, then the host distributes
+    /// the pot. A failed step is recorded and abandons the game.
+    pub async fn play(&self, wallets: &[Keypair]) {
+        if self.play_steps(wallets).await.is_ok() {
+            self.metrics.lock().expect("metrics").finish_game();
+        }
+    }
+
+    async fn play_steps(&self, wallets: &[Keypair]) -> Result<(), ()> {
+        let program_id = self.program_id;
+        let game = Keypair::new();
+        let game_key = game.pubkey();
+        let host = &wallets[0];
+
+        let create = instruction::initialize_game(&program_id, &host.pubkey(), &game_key, &self.config);
+        self.send("InitializeGame", vec![create], vec![host, &game]).await?;
+
+        // Seats fill concurrently, as they would in a busy lobby; whichever
+        // join fills it draws the size from SlotHashes
+        let joins = wallets[1..].iter().map(|player| {
+            let join = instruction::join_game(&program_id, &player.pubkey(), &game_key);
+            self.send("JoinGame", vec![instruction::with_slot_hashes(join)], vec![player])
+        });
+        join_all(joins).await.into_iter().collect::<Result<Vec<()>, ()>>()?;
+
+        let state = self.fetch(&game_key).await?;
+        let mut commitments = Vec::with_capacity(wallets.len());
+        for player in wallets {
+            let seat = state.players.iter().position(|p| p.pubkey == player.pubkey()).unwrap_or(0);
+            let choice = Uniform.choose(&game_key, &state, seat);
+            let commitment = Commitment::for_game(choice, &game_key, &state, &player.pubkey())
+                .map_err(|error| self.fail("CommitChoice", &error))?;
+            commitments.push(commitment);
+        }
+        let commits = wallets
+            .iter()
+            .zip(&commitments)
+            .map(|(player, commitment)| self.send("CommitChoice", vec![commitment.commit(&program_id)], vec![player]));
+        join_all(commits).await.into_iter().collect::<Result<Vec<()>, ()>>()?;
+        let reveals = wallets
+            .iter()
+            .zip(&commitments)
+            .map(|(player, commitment)| self.send("RevealChoice", vec![commitment.reveal(&program_id)], vec![player]));
+        join_all(reveals).await.into_iter().collect::<Result<Vec<()>, ()>>()?;
+
+        let state = self.fetch(&game_key).await?;
+        if state.game_pot > 0 {
+            let winners: Vec<Pubkey> = state
+                .players
+                .iter()
+                .enumerate()
+                .filter(|(seat, _)| state.winner_bitmap() & (1 << seat) != 0)
+                .map(|(seat, _)| state.payee(seat))
+                .collect();
+            let distribute = instruction::distribute_winnings(&program_id, &host.pubkey(), &game_key, &winners);
+            self.send("DistributeWinnings", vec![distribute], vec![host]).await?;
+        }
+        Ok(())
+    }
+
+    // Sends `instructions` paid by the first signer with the SDK's compute
+    // budget, recording the outcome under `action`
+    async fn send(
+        &self,
+        action: &'static str,
+        instructions: Vec<Instruction>,
+        signers: Vec<&Keypair>,
+    ) -> Result<(), ()> {
+        let budget = estimate_transaction_units(&self.program_id, &instructions);
+        let instructions = with_compute_budget(instructions, budget, None);
+        let payer = signers[0].pubkey();
+        let started = Instant::now();
+        let outcome = send_and_confirm(&self.rpc, &self.send, None, |blockhash| {
+            let tx = Transaction::new_signed_with_payer(&instructions, Some(&payer), signers.as_slice(), blockhash);
+            Ok(VersionedTransaction::from(tx))
+        })
+        .await;
+        let latency = started.elapsed();
+
+        let landed = match outcome {
+            Ok(SendOutcome::Confirmed(signature)) => self.landed(&signature).await,
+            // Only reported by a guard, and none is passed
+            Ok(SendOutcome::AlreadyApplied) => None,
+            Err(error) => {
+                self.fail(action, &error);
+                return Err(());
+            }
+        };
+        let sample = Sample {
+            latency,
+            budget,
+            landed,
+        };
+        self.metrics.lock().expect("metrics").record(action, sample);
+        Ok(())
+    }
+
+    // The slot and compute units of a confirmed transaction
+    async fn landed(&self, signature: &Signature) -> Option<Landed> {
+        let config = RpcTransactionConfig {
+            encoding: Some(UiTransactionEncoding::Base64),
+            commitment: Some(CommitmentConfig::confirmed()),
+            max_supported_transaction_version: Some(0),
+        };
+        let tx = self.rpc.get_transaction_with_config(signature, config).await.ok()?;
+        Some(Landed {
+            slot: tx.slot,
+            compute_units: tx.transaction.meta.and_then(|meta| meta.compute_units_consumed.into()),
+        })
+    }
+
+    async fn fetch(&self, game: &Pubkey) -> Result<Game, ()> {
+        fetch_game(&self.rpc, game)
+            .await
+            .map_err(|error| self.fail("FetchGame", &error))
+    }
+
+    fn fail(&self, action: &'static str, error: &RpsClientError) {
+        self.metrics.lock().expect("metrics").fail(action, error);
+    }
+}
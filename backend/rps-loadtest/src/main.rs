@@ -0,0 +1,160 @@
+//! Stress test for a Solana RPS deployment on localnet or devnet.
+//!
+//! Funds `--games` x `--players` fresh wallets from `--keypair`, then plays
+//! every game through with at most `--concurrency` in flight: initialize,
+//! joins, commits and reveals (each seat sending at once), then a
+//! `DistributeWinnings` crank. Each game writes its own game account, pot
+//! vault and wallets, so games can run in parallel; the SOL entry fee that
+//! every join pays into the shared treasury PDA is the one lock they all
+//! take. The report printed at the end gives latency and compute units per
+//! instruction, how many of the test's transactions landed per slot, and
+//! every failure by cause.
+//!
+//! The wallets are throwaway and keep what they are not charged; fund the
+//! run from a keypair made for it.
+
+mod load_test;
+mod metrics;
+
+use std::{env, process, str::FromStr, sync::Mutex, time::Instant};
+
+use futures::{stream, StreamExt};
+use rps_client::{instruction::GameConfig, send::SendConfig};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{
+    commitment_config::CommitmentConfig,
+    pubkey::Pubkey,
+    signature::{read_keypair_file, Keypair},
+    signer::Signer,
+};
+use solana_rps::Game;
+
+use crate::{load_test::LoadTest, metrics::Metrics};
+
+const USAGE: &str = "\
+Usage: rps-loadtest --program-id <PUBKEY> [options]
+
+  --url <URL>             RPC endpoint (default http://127.0.0.1:8899)
+  --keypair <PATH>        Wallet that funds every player (default ~/.config/solana/id.json)
+  --games <N>             Games to play (default 100)
+  --players <N>           Seats per game, 2 to 8 (default 3)
+  --concurrency <N>       Games in flight at once (default 50)
+  --entry-fee <LAMPORTS>  Entry fee of every game (default 1000000)";
+
+// The program's seat limit
+const MAX_PLAYERS: u8 = 8;
+
+// Transaction fees and the pot vault's rent, on top of what each wallet
+// pays into its game
+const FEE_MARGIN: u64 = 10_000_000;
+
+struct Options {
+    url: String,
+    program_id: Pubkey,
+    keypair: String,
+    games: usize,
+    players: u8,
+    concurrency: usize,
+    entry_fee: u64,
+}
+
+impl Options {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut options = Options {
+            url: "http://127.0.0.1:8899".to_string(),
+            program_id: Pubkey::default(),
+            keypair: format!("{}/.config/solana/id.json", env::var("HOME").unwrap_or_default()),
+            games: 100,
+            players: 3,
+            concurrency: 50,
+            entry_fee: 1_000_000,
+        };
+        let mut program_id = None;
+        while let Some(flag) = args.next() {
+            let value = args.next().ok_or_else(|| format!("{} needs a value", flag))?;
+            match flag.as_str() {
+                "--url" => options.url = value,
+                "--program-id" => program_id = Some(parse(&flag, &value)?),
+                "--keypair" => options.keypair = value,
+                "--games" => options.games = parse(&flag, &value)?,
+                "--players" => options.players = parse(&flag, &value)?,
+                "--concurrency" => options.concurrency = parse(&flag, &value)?,
+                "--entry-fee" => options.entry_fee = parse(&flag, &value)?,
+                _ => return Err(format!("Unknown option {}", flag)),
+            }
+        }
+        options.program_id = program_id.ok_or("--program-id is required")?;
+        if !(2..=MAX_PLAYERS).contains(&options.players) {
+            return Err(format!("--players must be between 2 and {}", MAX_PLAYERS));
+        }
+        if options.concurrency == 0 {
+            return Err("--concurrency must be at least 1".to_string());
+        }
+        Ok(options)
+    }
+}
+
+fn parse<T: FromStr>(flag: &str, value: &str) -> Result<T, String> {
+    value.parse().map_err(|_| format!("Invalid value {} for {}", value, flag))
+}
+
+fn exit_with(message: &str) -> ! {
+    eprintln!("{}", message);
+    process::exit(2)
+}
+
+#[tokio::main]
+async fn main() {
+    let options = Options::parse(env::args().skip(1)).unwrap_or_else(|e| exit_with(&format!("{}\n\n{}", e, USAGE)));
+    let payer = read_keypair_file(&options.keypair)
+        .unwrap_or_else(|e| exit_with(&format!("Cannot read keypair {}: {}", options.keypair, e)));
+
+    let test = LoadTest {
+        rpc: RpcClient::new_with_commitment(options.url.clone(), CommitmentConfig::confirmed()),
+        payer,
+        program_id: options.program_id,
+        config: GameConfig {
+            min_players: options.players,
+            max_players: options.players,
+            entry_fee: options.entry_fee,
+            ..GameConfig::default()
+        },
+        send: SendConfig::default(),
+        metrics: Mutex::new(Metrics::default()),
+    };
+
+    // The host also pays the game account's rent
+    let rent = test
+        .rpc
+        .get_minimum_balance_for_rent_exemption(Game::get_max_size(options.players))
+        .await
+        .unwrap_or_else(|e| exit_with(&format!("Cannot reach {}: {}", options.url, e)));
+    let tables: Vec<Vec<Keypair>> = (0..options.games)
+        .map(|_| (0..options.players).map(|_| Keypair::new()).collect())
+        .collect();
+    let hosts: Vec<Pubkey> = tables.iter().map(|wallets| wallets[0].pubkey()).collect();
+    let guests: Vec<Pubkey> = tables.iter().flat_map(|wallets| wallets[1..].iter().map(Signer::pubkey)).collect();
+    println!("Funding {} wallets from {}", hosts.len() + guests.len(), test.payer.pubkey());
+    let funded = async {
+        test.fund(&hosts, rent + options.entry_fee + FEE_MARGIN).await?;
+        test.fund(&guests, options.entry_fee + FEE_MARGIN).await
+    };
+    if let Err(e) = funded.await {
+        exit_with(&format!("Funding failed: {}", e));
+    }
+
+    println!("Playing {} games of {} players, {} at once", options.games, options.players, options.concurrency);
+    let started = Instant::now();
+    stream::iter(&tables)
+        .for_each_concurrent(options.concurrency, |wallets| {
+            let test = &test;
+            async move {
+                test.play(wallets).await;
+            }
+        })
+        .await;
+    let elapsed = started.elapsed();
+
+    let metrics = test.metrics.lock().expect("metrics");
+    print!("{}", metrics.report(options.games, elapsed));
+}
@@ -0,0 +1,122 @@
+//! Samples and failures collected while the load test runs, and the report
+//! printed at the end.
+
+use std::{collections::BTreeMap, fmt::Write, time::Duration};
+
+use rps_client::RpsClientError;
+
+/// One confirmed transaction.
+#[derive(Debug, Clone, Copy)]
+pub struct Sample {
+    /// From the first send to confirmation, retries included.
+    pub latency: Duration,
+    /// Compute-unit limit the transaction was sent with.
+    pub budget: u32,
+    /// Where and at what cost it landed; `None` if the transaction could
+    /// not be fetched back.
+    pub landed: Option<Landed>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Landed {
+    pub slot: u64,
+    pub compute_units: Option<u64>,
+}
+
+#[derive(Debug, Default)]
+pub struct Metrics {
+    samples: BTreeMap<&'static str, Vec<Sample>>,
+    // Failures per action and error message
+    failures: BTreeMap<(&'static str, String), u64>,
+    games_finished: usize,
+}
+
+impl Metrics {
+    pub fn record(&mut self, action: &'static str, sample: Sample) {
+        self.samples.entry(action).or_default().push(sample);
+    }
+
+    pub fn fail(&mut self, action: &'static str, error: &RpsClientError) {
+        *self.failures.entry((action, error.to_string())).or_default() += 1;
+    }
+
+    pub fn finish_game(&mut self) {
+        self.games_finished += 1;
+    }
+
+    /// Throughput, then per action the latency percentiles and compute
+    /// units used against the budget sent, then how many of the test's
+    /// transactions shared a slot, then every failure.
+    pub fn report(&self, games: usize, elapsed: Duration) -> String {
+        let mut out = String::new();
+        let seconds = elapsed.as_secs_f64();
+        let _ = writeln!(
+            out,
+            "games: {}/{} finished in {:.1}s ({:.2} games/s)",
+            self.games_finished,
+            games,
+            seconds,
+            self.games_finished as f64 / seconds.max(f64::EPSILON),
+        );
+
+        let _ = writeln!(
+            out,
+            "\n{:<20} {:>7} {:>8} {:>8} {:>8} {:>8} {:>9} {:>8} {:>8}",
+            "action", "count", "p50 ms", "p90 ms", "p99 ms", "max ms", "CU mean", "CU max", "budget"
+        );
+        for (action, samples) in &self.samples {
+            let mut latencies: Vec<u128> = samples.iter().map(|s| s.latency.as_millis()).collect();
+            latencies.sort_unstable();
+            let units: Vec<u64> = samples.iter().filter_map(|s| s.landed?.compute_units).collect();
+            let mean_units = units.iter().sum::<u64>() / (units.len() as u64).max(1);
+            let _ = writeln!(
+                out,
+                "{:<20} {:>7} {:>8} {:>8} {:>8} {:>8} {:>9} {:>8} {:>8}",
+                action,
+                samples.len(),
+                percentile(&latencies, 50),
+                percentile(&latencies, 90),
+                percentile(&latencies, 99),
+                latencies.last().copied().unwrap_or(0),
+                mean_units,
+                units.iter().max().copied().unwrap_or(0),
+                samples.iter().map(|s| s.budget).max().unwrap_or(0),
+            );
+        }
+
+        // Independent games only land together if they lock disjoint
+        // accounts, so a busy slot is the parallelism actually achieved
+        let mut per_slot: BTreeMap<u64, u64> = BTreeMap::new();
+        for sample in self.samples.values().flatten() {
+            if let Some(landed) = sample.landed {
+                *per_slot.entry(landed.slot).or_default() += 1;
+            }
+        }
+        if !per_slot.is_empty() {
+            let landed: u64 = per_slot.values().sum();
+            let _ = writeln!(
+                out,
+                "\nlanded per slot: mean {:.1}, max {} over {} slots",
+                landed as f64 / per_slot.len() as f64,
+                per_slot.values().max().copied().unwrap_or(0),
+                per_slot.len(),
+            );
+        }
+
+        if !self.failures.is_empty() {
+            let _ = writeln!(out, "\nfailures:");
+            for ((action, error), count) in &self.failures {
+                let _ = writeln!(out, "  {:>6} x {}: {}", count, action, error);
+            }
+        }
+        out
+    }
+}
+
+// Nearest-rank percentile of sorted values
+fn percentile(sorted: &[u128], p: usize) -> u128 {
+    if sorted.is_empty() {
+        return 0;
+    }
+    sorted[(sorted.len() - 1) * p / 100]
+}
@@ -0,0 +1,10 @@
+//! Game rules of the Solana RPS program with no Solana dependencies.
+//!
+//! The program hashes commitments, takes its fee and scores rounds through
+//! this crate, so anything built on [`simulate`] (clients predicting an
+//! outcome, bots, tests) computes exactly what the chain will. What each
+//! hand beats lives in [`rules`].
+
+pub mod rules;
+pub mod simulate;
+pub mod vectors;
@@ -0,0 +1,100 @@
+//! What a throw is worth, behind one trait. A new hand-game variant is a
+//! [`GameRules`] implementation plus a [`RuleSet`] byte for games to store;
+//! seating, commit, reveal and payouts never look at the hands themselves.
+
+use core::cmp::Ordering;
+
+use crate::simulate::Hand;
+
+/// The rules of one variant.
+pub trait GameRules {
+    /// `Greater` when `a` beats `b`, `Less` when `b` beats `a` and `Equal`
+    /// when neither does.
+    fn beats(&self, a: Hand, b: Hand) -> Ordering;
+
+    /// Hands a player may reveal; any other is refused.
+    fn valid_choices(&self) -> &'static [Hand];
+
+    fn allows(&self, hand: Hand) -> bool {
+        self.valid_choices().contains(&hand)
+    }
+}
+
+/// Rock crushes scissors, scissors cut paper, paper covers rock.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Classic;
+
+impl GameRules for Classic {
+    fn beats(&self, a: Hand, b: Hand) -> Ordering {
+        let wins = |x, y| {
+            matches!(
+                (x, y),
+                (Hand::Rock, Hand::Scissors) | (Hand::Paper, Hand::Rock) | (Hand::Scissors, Hand::Paper)
+            )
+        };
+        ordering(wins(a, b), wins(b, a))
+    }
+
+    fn valid_choices(&self) -> &'static [Hand] {
+        &[Hand::Rock, Hand::Paper, Hand::Scissors]
+    }
+}
+
+/// Rock-Paper-Scissors-Lizard-Spock: every hand beats two others and loses
+/// to the remaining two. Between rock, paper and scissors it is the classic
+/// game.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LizardSpock;
+
+impl GameRules for LizardSpock {
+    fn beats(&self, a: Hand, b: Hand) -> Ordering {
+        let wins = |x, y| {
+            matches!(
+                (x, y),
+                (Hand::Rock, Hand::Scissors | Hand::Lizard)
+                    | (Hand::Paper, Hand::Rock | Hand::Spock)
+                    | (Hand::Scissors, Hand::Paper | Hand::Lizard)
+                    | (Hand::Lizard, Hand::Paper | Hand::Spock)
+                    | (Hand::Spock, Hand::Rock | Hand::Scissors)
+            )
+        };
+        ordering(wins(a, b), wins(b, a))
+    }
+
+    fn valid_choices(&self) -> &'static [Hand] {
+        &[Hand::Rock, Hand::Paper, Hand::Scissors, Hand::Lizard, Hand::Spock]
+    }
+}
+
+fn ordering(a_wins: bool, b_wins: bool) -> Ordering {
+    match (a_wins, b_wins) {
+        (true, _) => Ordering::Greater,
+        (_, true) => Ordering::Less,
+        _ => Ordering::Equal,
+    }
+}
+
+/// Which [`GameRules`] a game plays by, as stored with it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RuleSet {
+    #[default]
+    Classic,
+    LizardSpock,
+}
+
+impl RuleSet {
+    pub fn from_byte(byte: u8) -> Option<Self> {
+        match byte {
+            0 => Some(RuleSet::Classic),
+            1 => Some(RuleSet::LizardSpock),
+            _ => None,
+        }
+    }
+
+    pub fn rules(self) -> &'static dyn GameRules {
+        match self {
+            RuleSet::Classic => &Classic,
+            RuleSet::LizardSpock => &LizardSpock,
+        }
+    }
+}
@@ -0,0 +1,390 @@
+//! Deterministic simulation of a game: seating, commit, reveal, scoring and
+//! payouts, step for step as the program runs them.
+//!
+//! A [`Simulation`] is driven with the same actions players send on-chain
+//! and rejects them in the same cases, with the [`SimError`] named after the
+//! program error it stands for. Timeouts, rejoins, bots, stake mints and
+//! tournaments are not modelled.
+
+use core::cmp::Ordering;
+
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256, Sha512};
+
+use crate::rules::{GameRules, RuleSet};
+
+/// A wallet address, as raw bytes.
+pub type Key = [u8; 32];
+
+/// Platform fee rate, in basis points of an entry, of games created while
+/// the operator has not set one.
+pub const DEFAULT_FEE_BPS: u16 = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Hand {
+    Rock,
+    Paper,
+    Scissors,
+    Lizard,
+    Spock,
+}
+
+impl Hand {
+    /// The byte committed to: 1 rock, 2 paper, 3 scissors, 4 lizard,
+    /// 5 spock.
+    pub fn byte(self) -> u8 {
+        match self {
+            Hand::Rock => 1,
+            Hand::Paper => 2,
+            Hand::Scissors => 3,
+            Hand::Lizard => 4,
+            Hand::Spock => 5,
+        }
+    }
+
+    pub fn from_byte(byte: u8) -> Option<Self> {
+        match byte {
+            1 => Some(Hand::Rock),
+            2 => Some(Hand::Paper),
+            3 => Some(Hand::Scissors),
+            4 => Some(Hand::Lizard),
+            5 => Some(Hand::Spock),
+            _ => None,
+        }
+    }
+}
+
+/// How a game's commitments are hashed, chosen by the host at init.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CommitmentScheme {
+    /// HMAC-SHA512 keyed by the salt over the binding and the hand byte.
+    #[default]
+    HmacSha512,
+    /// SHA-256 over the salt, the binding and the hand byte, zero-padded to
+    /// 64 bytes. For clients without a fast SHA-512.
+    Sha256,
+}
+
+impl CommitmentScheme {
+    pub fn from_byte(byte: u8) -> Option<Self> {
+        match byte {
+            0 => Some(CommitmentScheme::HmacSha512),
+            1 => Some(CommitmentScheme::Sha256),
+            _ => None,
+        }
+    }
+}
+
+/// What a commitment is bound to, hashed in as the game key, the round byte
+/// and the player key. A commitment replayed in another game, round or seat
+/// never verifies.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CommitBinding {
+    pub game: Key,
+    pub round: u8,
+    pub player: Key,
+}
+
+impl CommitBinding {
+    fn bytes(&self) -> [u8; 65] {
+        let mut out = [0u8; 65];
+        out[..32].copy_from_slice(&self.game);
+        out[32] = self.round;
+        out[33..].copy_from_slice(&self.player);
+        out
+    }
+}
+
+/// Commitment under the default HMAC-SHA512 scheme.
+pub fn commitment(binding: &CommitBinding, salt: &[u8; 32], hand: Hand) -> [u8; 64] {
+    commitment_with(CommitmentScheme::HmacSha512, binding, salt, hand)
+}
+
+pub fn commitment_with(scheme: CommitmentScheme, binding: &CommitBinding, salt: &[u8; 32], hand: Hand) -> [u8; 64] {
+    match scheme {
+        CommitmentScheme::HmacSha512 => {
+            let mut mac = Hmac::<Sha512>::new_from_slice(salt).expect("HMAC takes keys of any length");
+            mac.update(&binding.bytes());
+            mac.update(&[hand.byte()]);
+            mac.finalize().into_bytes().into()
+        }
+        CommitmentScheme::Sha256 => {
+            let digest = Sha256::new()
+                .chain_update(salt)
+                .chain_update(binding.bytes())
+                .chain_update([hand.byte()])
+                .finalize();
+            let mut out = [0u8; 64];
+            out[..32].copy_from_slice(&digest);
+            out
+        }
+    }
+}
+
+/// Platform fee taken from an entry of `amount` at [`DEFAULT_FEE_BPS`].
+pub fn fee(amount: u64) -> u64 {
+    fee_at(amount, DEFAULT_FEE_BPS)
+}
+
+/// Platform fee taken from an entry of `amount` at `fee_bps` basis points,
+/// rounded down.
+pub fn fee_at(amount: u64, fee_bps: u16) -> u64 {
+    (amount as u128 * fee_bps as u128 / 10_000) as u64
+}
+
+/// Points each seat earns in a round: one per opponent its hand beats under
+/// `rules`. A seat without a hand neither wins nor loses, except in a duel:
+/// there the only opponent of a missing hand takes the point, so a loser
+/// cannot force a tie by not revealing.
+pub fn round_points(rules: &dyn GameRules, hands: &[Option<Hand>]) -> Vec<u8> {
+    let mut points = vec![0u8; hands.len()];
+    if let [a, b] = hands {
+        match (a, b) {
+            (Some(_), None) => points[0] = 1,
+            (None, Some(_)) => points[1] = 1,
+            _ => {}
+        }
+    }
+    for i in 0..hands.len() {
+        for j in (i + 1)..hands.len() {
+            if let (Some(a), Some(b)) = (hands[i], hands[j]) {
+                match rules.beats(a, b) {
+                    Ordering::Greater => points[i] += 1,
+                    Ordering::Less => points[j] += 1,
+                    Ordering::Equal => {}
+                }
+            }
+        }
+    }
+    points
+}
+
+/// Seats holding the top score, as a bitmap over seat indices.
+pub fn winner_bitmap(scores: &[u8]) -> u8 {
+    let max_score = scores.iter().copied().max().unwrap_or(0);
+    scores
+        .iter()
+        .enumerate()
+        .filter(|(_, s)| **s == max_score)
+        .fold(0u8, |bits, (i, _)| bits | (1 << i))
+}
+
+/// Each winner's share of `pot` as `(seat, amount)`, in seat order. Shares
+/// are equal and the last winner's also takes the rounding dust, so they
+/// add up to the pot exactly.
+pub fn pot_shares(pot: u64, winners: u8) -> Vec<(usize, u64)> {
+    let seats: Vec<usize> = (0..8).filter(|i| winners & (1 << i) != 0).collect();
+    let share = pot / seats.len().max(1) as u64;
+    let dust = pot - share * seats.len() as u64;
+    seats
+        .iter()
+        .enumerate()
+        .map(|(n, &seat)| (seat, if n + 1 == seats.len() { share + dust } else { share }))
+        .collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    WaitingForPlayers,
+    CommitPhase,
+    RevealPhase,
+    Finished,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SimError {
+    InvalidGameState,
+    PlayerNotFound,
+    GameFull,
+    PlayerAlreadyJoined,
+    AlreadyCommitted,
+    InvalidPlayerState,
+    InvalidHash,
+    InvalidChoice,
+    NotWinner,
+    AlreadyClaimed,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Seat {
+    pub player: Key,
+    pub commitment: Option<[u8; 64]>,
+    pub hand: Option<Hand>,
+    pub revealed: bool,
+    pub score: u8,
+}
+
+impl Seat {
+    fn new(player: Key) -> Self {
+        Self {
+            player,
+            commitment: None,
+            hand: None,
+            revealed: false,
+            score: 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Simulation {
+    /// The game's account key, which commitments are bound to.
+    pub game: Key,
+    pub seats: Vec<Seat>,
+    pub player_count: u8,
+    pub total_rounds: u8,
+    pub current_round: u8,
+    pub phase: Phase,
+    pub entry_fee: u64,
+    pub scheme: CommitmentScheme,
+    pub rule_set: RuleSet,
+    /// Pot still held for winners.
+    pub pot: u64,
+    /// Pot when the game finished, which winners split.
+    pub pot_snapshot: u64,
+    /// Bit per seat index, set once that winner is paid.
+    pub claimed: u8,
+}
+
+impl Simulation {
+    /// Lobby `game` created by `host`, who takes the first seat and pays in.
+    /// Commitments use the default scheme and hands the classic rules; set
+    /// `scheme` and `rule_set` to match the game.
+    pub fn new(game: Key, host: Key, player_count: u8, total_rounds: u8, entry_fee: u64) -> Self {
+        Self {
+            game,
+            seats: vec![Seat::new(host)],
+            player_count,
+            total_rounds,
+            current_round: 1,
+            phase: Phase::WaitingForPlayers,
+            entry_fee,
+            scheme: CommitmentScheme::default(),
+            rule_set: RuleSet::default(),
+            pot: entry_fee - fee(entry_fee),
+            pot_snapshot: 0,
+            claimed: 0,
+        }
+    }
+
+    pub fn join(&mut self, player: Key) -> Result<(), SimError> {
+        if self.phase != Phase::WaitingForPlayers {
+            return Err(SimError::InvalidGameState);
+        }
+        if self.seat(&player).is_some() {
+            return Err(SimError::PlayerAlreadyJoined);
+        }
+        if self.seats.len() >= self.player_count as usize {
+            return Err(SimError::GameFull);
+        }
+        self.seats.push(Seat::new(player));
+        self.pot += self.entry_fee - fee(self.entry_fee);
+        if self.seats.len() >= self.player_count as usize {
+            self.phase = Phase::CommitPhase;
+        }
+        Ok(())
+    }
+
+    /// `CommitChoice`, or `RecommitChoice` when `recommit` is set.
+    pub fn commit(&mut self, player: Key, commitment: [u8; 64], recommit: bool) -> Result<(), SimError> {
+        if self.phase != Phase::CommitPhase {
+            return Err(SimError::InvalidGameState);
+        }
+        let seat = self.seat(&player).ok_or(SimError::PlayerNotFound)?;
+        let has_committed = self.seats[seat].commitment.is_some();
+        if recommit {
+            if !has_committed {
+                return Err(SimError::InvalidPlayerState);
+            }
+            let others_committed = self.seats.iter().enumerate().any(|(i, s)| i != seat && s.commitment.is_some());
+            if others_committed {
+                return Err(SimError::AlreadyCommitted);
+            }
+        } else if has_committed {
+            return Err(SimError::AlreadyCommitted);
+        }
+        self.seats[seat].commitment = Some(commitment);
+
+        if self.seats.iter().all(|s| s.commitment.is_some()) {
+            self.phase = Phase::RevealPhase;
+        }
+        Ok(())
+    }
+
+    /// `RevealChoice` with the salt `hand` was committed under.
+    pub fn reveal(&mut self, player: Key, hand: Hand, salt: [u8; 32]) -> Result<(), SimError> {
+        if self.phase != Phase::RevealPhase {
+            return Err(SimError::InvalidGameState);
+        }
+        let seat = self.seat(&player).ok_or(SimError::PlayerNotFound)?;
+        let binding = CommitBinding {
+            game: self.game,
+            round: self.current_round,
+            player,
+        };
+        if self.seats[seat].commitment != Some(commitment_with(self.scheme, &binding, &salt, hand)) {
+            return Err(SimError::InvalidHash);
+        }
+        if !self.rule_set.rules().allows(hand) {
+            return Err(SimError::InvalidChoice);
+        }
+        self.seats[seat].hand = Some(hand);
+        self.seats[seat].revealed = true;
+
+        if self.seats.iter().all(|s| s.revealed) {
+            let hands: Vec<Option<Hand>> = self.seats.iter().map(|s| s.hand).collect();
+            for (seat, points) in self.seats.iter_mut().zip(round_points(self.rule_set.rules(), &hands)) {
+                seat.score += points;
+            }
+            if self.current_round >= self.total_rounds {
+                self.phase = Phase::Finished;
+                self.pot_snapshot = self.pot;
+                self.claimed = 0;
+            } else {
+                self.current_round += 1;
+                self.phase = Phase::CommitPhase;
+                for seat in &mut self.seats {
+                    seat.commitment = None;
+                    seat.hand = None;
+                    seat.revealed = false;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn winners(&self) -> u8 {
+        let scores: Vec<u8> = self.seats.iter().map(|s| s.score).collect();
+        winner_bitmap(&scores)
+    }
+
+    /// What a claim by `player` would pay now: their share of the finishing
+    /// pot as `pot_shares` splits it, whoever claims first.
+    pub fn claimable(&self, player: &Key) -> Result<u64, SimError> {
+        if self.phase != Phase::Finished {
+            return Err(SimError::InvalidGameState);
+        }
+        let winners = self.winners();
+        let seat = match self.seat(player) {
+            Some(i) if winners & (1 << i) != 0 => i,
+            _ => return Err(SimError::NotWinner),
+        };
+        if self.claimed & (1 << seat) != 0 {
+            return Err(SimError::AlreadyClaimed);
+        }
+        let shares = pot_shares(self.pot_snapshot, winners);
+        Ok(shares.iter().find(|(s, _)| *s == seat).map_or(0, |&(_, amount)| amount))
+    }
+
+    pub fn claim(&mut self, player: &Key) -> Result<u64, SimError> {
+        let amount = self.claimable(player)?;
+        let seat = self.seat(player).ok_or(SimError::PlayerNotFound)?;
+        self.claimed |= 1 << seat;
+        self.pot -= amount;
+        Ok(amount)
+    }
+
+    fn seat(&self, player: &Key) -> Option<usize> {
+        self.seats.iter().position(|s| s.player == *player)
+    }
+}
@@ -0,0 +1,273 @@
+//! Golden vectors for commitment hashing.
+//!
+//! Every other implementation of the commitment (the TypeScript client in
+//! particular) should reproduce these before it is trusted with a reveal: a
+//! mismatch means players commit to hashes they can never open. The same
+//! vectors ship as `vectors/commitments.json` for non-Rust clients, and a
+//! test checks both against the program's `compute_commitment`.
+//!
+//! [`COMMITMENT_VECTORS`] cover the default HMAC-SHA512 scheme and
+//! [`SHA256_COMMITMENT_VECTORS`] the SHA-256 one, over the same inputs.
+//!
+//! Each commitment is bound to `game`, `round` and `player`, hashed in that
+//! order between the salt and the choice (see `simulate::CommitBinding`).
+
+/// One expected commitment. Byte strings are lowercase hex; `game` and
+/// `player` are base58 addresses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CommitmentVector {
+    /// 1 rock, 2 paper, 3 scissors.
+    pub choice: u8,
+    pub salt: &'static str,
+    pub game: &'static str,
+    pub round: u8,
+    pub player: &'static str,
+    pub commitment: &'static str,
+}
+
+pub const COMMITMENT_VECTORS: &[CommitmentVector] = &[
+    CommitmentVector {
+        choice: 1,
+        salt: "0000000000000000000000000000000000000000000000000000000000000000",
+        game: "H4DzxGYmebbRcut6wztSPQcN5adV5zSd6E416zCprZ1p",
+        round: 2,
+        player: "29d2S7vB453rNYFdR5Ycwt7y9haRT5fwVwL9zTmBhfV2",
+        commitment: "0b4b44cf2d3bebbf2ba96881c1142516b62262d85d08d0b66c92235a0e14d36582174226b92166fc58d88c43b38b8af41e842b1ae5e2a6a799daeb08bf8dd89b",
+    },
+    CommitmentVector {
+        choice: 2,
+        salt: "0000000000000000000000000000000000000000000000000000000000000000",
+        game: "US517G5965aydkZ46HS38QLi7UQiSojurfbQfKCELFx",
+        round: 3,
+        player: "29d2S7vB453rNYFdR5Ycwt7y9haRT5fwVwL9zTmBhfV2",
+        commitment: "ca905fe4eeb74468438c3630da6b310994a5c37d4ed1cf4070a3605ab959bcb8748c6ac464cf8b7759bb6785d97a4a85758e31d105ee2a3a708271b4ab780c5e",
+    },
+    CommitmentVector {
+        choice: 3,
+        salt: "0000000000000000000000000000000000000000000000000000000000000000",
+        game: "H4DzxGYmebbRcut6wztSPQcN5adV5zSd6E416zCprZ1p",
+        round: 1,
+        player: "3JF3sEqM796hk5WFqA6EtmEwJQ9quALszsfJyvXNQKy3",
+        commitment: "53f59a5c4bae8c35e39b49d39c20c730a7dbd9ac1b333e6b8feeea52a654c95d666c2f2f91a255484e2b6cb3e09c4e03bb8cf255c74a095a37d61e113ee18022",
+    },
+    CommitmentVector {
+        choice: 1,
+        salt: "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+        game: "US517G5965aydkZ46HS38QLi7UQiSojurfbQfKCELFx",
+        round: 3,
+        player: "3JF3sEqM796hk5WFqA6EtmEwJQ9quALszsfJyvXNQKy3",
+        commitment: "d6dc3333b47027ef109cbdef00eb17f1f11687e51a76fcc236ac34e4267e342914cf60785f78e2379b2df64f6a3624d80ce5930bc800b8b63e952cd77c591ec0",
+    },
+    CommitmentVector {
+        choice: 2,
+        salt: "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+        game: "H4DzxGYmebbRcut6wztSPQcN5adV5zSd6E416zCprZ1p",
+        round: 1,
+        player: "29d2S7vB453rNYFdR5Ycwt7y9haRT5fwVwL9zTmBhfV2",
+        commitment: "b5c364a4f4697a9c559cd433d852e05c03bb0b52f53dae6bb3e1d938abd8da87e77260cfa22bef87316cfd5b342b94e26d5f35efec1e704383bc4eb284825220",
+    },
+    CommitmentVector {
+        choice: 3,
+        salt: "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+        game: "US517G5965aydkZ46HS38QLi7UQiSojurfbQfKCELFx",
+        round: 2,
+        player: "29d2S7vB453rNYFdR5Ycwt7y9haRT5fwVwL9zTmBhfV2",
+        commitment: "55387b0a664b226505100ad5b75504a687f85cbea42db71ff6b894c243b8954af1ff55c58fe55fcfeeaaa2cfb941ebf8f1f8fc4a8c79f596fa18e325cc2f361c",
+    },
+    CommitmentVector {
+        choice: 1,
+        salt: "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f",
+        game: "H4DzxGYmebbRcut6wztSPQcN5adV5zSd6E416zCprZ1p",
+        round: 1,
+        player: "3JF3sEqM796hk5WFqA6EtmEwJQ9quALszsfJyvXNQKy3",
+        commitment: "715e30c10f65162c0c451e353c2363c6c4a16a50872645fa794f34cc9a2d4957639e79aa818f8b0a30812583c3e6e077b5c7b0f43edc8530ae2fa9460a1fe6b7",
+    },
+    CommitmentVector {
+        choice: 2,
+        salt: "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f",
+        game: "US517G5965aydkZ46HS38QLi7UQiSojurfbQfKCELFx",
+        round: 2,
+        player: "3JF3sEqM796hk5WFqA6EtmEwJQ9quALszsfJyvXNQKy3",
+        commitment: "a689e374d12e869bc1b15d2223591cc50442de5a5f70625ce40a712f51ab392d9fc40f0f4dd58237a20f5f524121f6ec63b63af6b72a3cf19013484ad145cb1d",
+    },
+    CommitmentVector {
+        choice: 3,
+        salt: "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f",
+        game: "H4DzxGYmebbRcut6wztSPQcN5adV5zSd6E416zCprZ1p",
+        round: 3,
+        player: "29d2S7vB453rNYFdR5Ycwt7y9haRT5fwVwL9zTmBhfV2",
+        commitment: "479655c4aa5df7fdac113a6a254d653ba8af8761a1a1cb1b4833449e1c66395aba22213bc0c3cea2ad185e55ed861a7c716c951cc3e4da5b026a9f59eee98f35",
+    },
+    CommitmentVector {
+        choice: 1,
+        salt: "201f1e1d1c1b1a191817161514131211100f0e0d0c0b0a090807060504030201",
+        game: "US517G5965aydkZ46HS38QLi7UQiSojurfbQfKCELFx",
+        round: 2,
+        player: "29d2S7vB453rNYFdR5Ycwt7y9haRT5fwVwL9zTmBhfV2",
+        commitment: "f3619345c22a32aedc3c0f42a5a46c3b700793643b0b3de36217980ec8aceb4900b92d9fb358210bd2e9044098fb33e69400ef2149571700c61184de46e80c99",
+    },
+    CommitmentVector {
+        choice: 2,
+        salt: "201f1e1d1c1b1a191817161514131211100f0e0d0c0b0a090807060504030201",
+        game: "H4DzxGYmebbRcut6wztSPQcN5adV5zSd6E416zCprZ1p",
+        round: 3,
+        player: "3JF3sEqM796hk5WFqA6EtmEwJQ9quALszsfJyvXNQKy3",
+        commitment: "ba2641503ccea0b7012c09608e0a437487ec2b82784874359c06240e785091ab2990e5224fa3e46ea05aad585bcd8c2445c1f6ad17f153961ece82eeaeb78d56",
+    },
+    CommitmentVector {
+        choice: 3,
+        salt: "201f1e1d1c1b1a191817161514131211100f0e0d0c0b0a090807060504030201",
+        game: "US517G5965aydkZ46HS38QLi7UQiSojurfbQfKCELFx",
+        round: 1,
+        player: "3JF3sEqM796hk5WFqA6EtmEwJQ9quALszsfJyvXNQKy3",
+        commitment: "349fcf8e527c95d8ff41ce52660dfd58740890747cc3ef76b74071d4ef180a49045403080f32cdf19865ec915df7e4dd8eaacfa621c48e29dca35ed79421cbaf",
+    },
+    CommitmentVector {
+        choice: 1,
+        salt: "2457ba0cc1060f56a952ea716d3609a3844f972a83f939ab4c1f9da124c1c775",
+        game: "H4DzxGYmebbRcut6wztSPQcN5adV5zSd6E416zCprZ1p",
+        round: 3,
+        player: "29d2S7vB453rNYFdR5Ycwt7y9haRT5fwVwL9zTmBhfV2",
+        commitment: "0e3c0385bc48dded2313641281cd8f42061b869e6eff0cb25d2d5ad0432fc6201a08a3ba4141aef3ed547f3d6b3c472c1a3e9797b6c7819b128a7a99d1e646e8",
+    },
+    CommitmentVector {
+        choice: 2,
+        salt: "2457ba0cc1060f56a952ea716d3609a3844f972a83f939ab4c1f9da124c1c775",
+        game: "US517G5965aydkZ46HS38QLi7UQiSojurfbQfKCELFx",
+        round: 1,
+        player: "29d2S7vB453rNYFdR5Ycwt7y9haRT5fwVwL9zTmBhfV2",
+        commitment: "11fc21fb1263c9281c13d91e4d80c3a5aab33f91b1cf4e69ef2cc9dbc8f04d570d5a488faf500dc84b6d3a4bd9068dfdcc9dce24c2ad0d68cfebc8faeaa1ceb7",
+    },
+    CommitmentVector {
+        choice: 3,
+        salt: "2457ba0cc1060f56a952ea716d3609a3844f972a83f939ab4c1f9da124c1c775",
+        game: "H4DzxGYmebbRcut6wztSPQcN5adV5zSd6E416zCprZ1p",
+        round: 2,
+        player: "3JF3sEqM796hk5WFqA6EtmEwJQ9quALszsfJyvXNQKy3",
+        commitment: "7a3df2bc35ca968d0fe350f492e1f6decd946495bc4be4f59128c62dca7eca661217ec477455258412d95fbe56312deec60f52f8346c6419d898b44c894a81f3",
+    },
+];
+
+/// The same inputs under `CommitmentScheme::Sha256`.
+pub const SHA256_COMMITMENT_VECTORS: &[CommitmentVector] = &[
+    CommitmentVector {
+        choice: 1,
+        salt: "0000000000000000000000000000000000000000000000000000000000000000",
+        game: "H4DzxGYmebbRcut6wztSPQcN5adV5zSd6E416zCprZ1p",
+        round: 2,
+        player: "29d2S7vB453rNYFdR5Ycwt7y9haRT5fwVwL9zTmBhfV2",
+        commitment: "631ddb32641df43799a844b8c4939dc20ce1d326ee6819298b00382c2bb5cbc30000000000000000000000000000000000000000000000000000000000000000",
+    },
+    CommitmentVector {
+        choice: 2,
+        salt: "0000000000000000000000000000000000000000000000000000000000000000",
+        game: "US517G5965aydkZ46HS38QLi7UQiSojurfbQfKCELFx",
+        round: 3,
+        player: "29d2S7vB453rNYFdR5Ycwt7y9haRT5fwVwL9zTmBhfV2",
+        commitment: "9ea0aee9033ba33880cceb2bc5093da2e91e8541e994e573ae82eaa7af457a8c0000000000000000000000000000000000000000000000000000000000000000",
+    },
+    CommitmentVector {
+        choice: 3,
+        salt: "0000000000000000000000000000000000000000000000000000000000000000",
+        game: "H4DzxGYmebbRcut6wztSPQcN5adV5zSd6E416zCprZ1p",
+        round: 1,
+        player: "3JF3sEqM796hk5WFqA6EtmEwJQ9quALszsfJyvXNQKy3",
+        commitment: "3e8c6ce2ec140e7771e5828556141718a071ce5f4f4f661da476d5c44df319f30000000000000000000000000000000000000000000000000000000000000000",
+    },
+    CommitmentVector {
+        choice: 1,
+        salt: "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+        game: "US517G5965aydkZ46HS38QLi7UQiSojurfbQfKCELFx",
+        round: 3,
+        player: "3JF3sEqM796hk5WFqA6EtmEwJQ9quALszsfJyvXNQKy3",
+        commitment: "d8c1157354b528c6ed6de6fa275aa2c10f44a9a138b68448405b38909b28217e0000000000000000000000000000000000000000000000000000000000000000",
+    },
+    CommitmentVector {
+        choice: 2,
+        salt: "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+        game: "H4DzxGYmebbRcut6wztSPQcN5adV5zSd6E416zCprZ1p",
+        round: 1,
+        player: "29d2S7vB453rNYFdR5Ycwt7y9haRT5fwVwL9zTmBhfV2",
+        commitment: "130cd361f43efc9d997651ebcf9087a3f1c6e201158e9e8ec59ff15aa68d247c0000000000000000000000000000000000000000000000000000000000000000",
+    },
+    CommitmentVector {
+        choice: 3,
+        salt: "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+        game: "US517G5965aydkZ46HS38QLi7UQiSojurfbQfKCELFx",
+        round: 2,
+        player: "29d2S7vB453rNYFdR5Ycwt7y9haRT5fwVwL9zTmBhfV2",
+        commitment: "df80365f764b98b8791476ae318471a71f94a98bccd301e8fd890d8acd05ca430000000000000000000000000000000000000000000000000000000000000000",
+    },
+    CommitmentVector {
+        choice: 1,
+        salt: "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f",
+        game: "H4DzxGYmebbRcut6wztSPQcN5adV5zSd6E416zCprZ1p",
+        round: 1,
+        player: "3JF3sEqM796hk5WFqA6EtmEwJQ9quALszsfJyvXNQKy3",
+        commitment: "0167316e0075d131e89748f0dce1a1de8a470c7dccc400d0350e65ef175d57be0000000000000000000000000000000000000000000000000000000000000000",
+    },
+    CommitmentVector {
+        choice: 2,
+        salt: "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f",
+        game: "US517G5965aydkZ46HS38QLi7UQiSojurfbQfKCELFx",
+        round: 2,
+        player: "3JF3sEqM796hk5WFqA6EtmEwJQ9quALszsfJyvXNQKy3",
+        commitment: "3ca85a2a01379622da09796d3c3c068429b3d0bbc96f210622a1b33325aab12c0000000000000000000000000000000000000000000000000000000000000000",
+    },
+    CommitmentVector {
+        choice: 3,
+        salt: "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f",
+        game: "H4DzxGYmebbRcut6wztSPQcN5adV5zSd6E416zCprZ1p",
+        round: 3,
+        player: "29d2S7vB453rNYFdR5Ycwt7y9haRT5fwVwL9zTmBhfV2",
+        commitment: "0c6114024f25be2a87ee967665faeebc709051ae94ef7f97f340ffb9859ff4bb0000000000000000000000000000000000000000000000000000000000000000",
+    },
+    CommitmentVector {
+        choice: 1,
+        salt: "201f1e1d1c1b1a191817161514131211100f0e0d0c0b0a090807060504030201",
+        game: "US517G5965aydkZ46HS38QLi7UQiSojurfbQfKCELFx",
+        round: 2,
+        player: "29d2S7vB453rNYFdR5Ycwt7y9haRT5fwVwL9zTmBhfV2",
+        commitment: "7c6881423025c48f41a8ed18a871d07c08f9b8e0778acc6c5499e871d865d8420000000000000000000000000000000000000000000000000000000000000000",
+    },
+    CommitmentVector {
+        choice: 2,
+        salt: "201f1e1d1c1b1a191817161514131211100f0e0d0c0b0a090807060504030201",
+        game: "H4DzxGYmebbRcut6wztSPQcN5adV5zSd6E416zCprZ1p",
+        round: 3,
+        player: "3JF3sEqM796hk5WFqA6EtmEwJQ9quALszsfJyvXNQKy3",
+        commitment: "ffe5a3c90dfa4ec74f81175a64b3ae430c4b202392d27c675094982e466279c10000000000000000000000000000000000000000000000000000000000000000",
+    },
+    CommitmentVector {
+        choice: 3,
+        salt: "201f1e1d1c1b1a191817161514131211100f0e0d0c0b0a090807060504030201",
+        game: "US517G5965aydkZ46HS38QLi7UQiSojurfbQfKCELFx",
+        round: 1,
+        player: "3JF3sEqM796hk5WFqA6EtmEwJQ9quALszsfJyvXNQKy3",
+        commitment: "5a10b11189db103d897383665f82ef668f37706791d531c7c0f395bf9c9a5e5f0000000000000000000000000000000000000000000000000000000000000000",
+    },
+    CommitmentVector {
+        choice: 1,
+        salt: "2457ba0cc1060f56a952ea716d3609a3844f972a83f939ab4c1f9da124c1c775",
+        game: "H4DzxGYmebbRcut6wztSPQcN5adV5zSd6E416zCprZ1p",
+        round: 3,
+        player: "29d2S7vB453rNYFdR5Ycwt7y9haRT5fwVwL9zTmBhfV2",
+        commitment: "29d7a7c4689bba0b40088927127d7e32385416b8cb07041291b6fa7a82074af80000000000000000000000000000000000000000000000000000000000000000",
+    },
+    CommitmentVector {
+        choice: 2,
+        salt: "2457ba0cc1060f56a952ea716d3609a3844f972a83f939ab4c1f9da124c1c775",
+        game: "US517G5965aydkZ46HS38QLi7UQiSojurfbQfKCELFx",
+        round: 1,
+        player: "29d2S7vB453rNYFdR5Ycwt7y9haRT5fwVwL9zTmBhfV2",
+        commitment: "367f47ba640748aee2329c207f2921401df5320588f3f9368ffdb8e64b46ba1e0000000000000000000000000000000000000000000000000000000000000000",
+    },
+    CommitmentVector {
+        choice: 3,
+        salt: "2457ba0cc1060f56a952ea716d3609a3844f972a83f939ab4c1f9da124c1c775",
+        game: "H4DzxGYmebbRcut6wztSPQcN5adV5zSd6E416zCprZ1p",
+        round: 2,
+        player: "3JF3sEqM796hk5WFqA6EtmEwJQ9quALszsfJyvXNQKy3",
+        commitment: "5c549c66edeabbeaa35ef4d77ac3404855f2103759bf94f6d4aa4b53b5e1c86b0000000000000000000000000000000000000000000000000000000000000000",
+    },
+];
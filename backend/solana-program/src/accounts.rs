@@ -0,0 +1,190 @@
+// Account expectations of every instruction, as constants. A row lists the
+// leading accounts an instruction always takes, in order, and whether each
+// must sign and be writable. Optional and variable trailing accounts (token
+// tails, credentials, payees, sysvars listed last) are left to the handlers.
+// Every instruction is held to its row before it is dispatched, and the
+// client SDK checks its builders against the same table, so the two cannot
+// drift apart. In the comments, `bans` and `exclusion` are the entrant's
+// ban list chunk and self-exclusion PDA, and `(new)` a fresh keypair.
+
+use solana_program::{account_info::AccountInfo, msg, program_error::ProgramError};
+
+use crate::RPSError;
+
+// What an instruction needs of one of its accounts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AccountSpec {
+    pub signer: bool,
+    pub writable: bool,
+}
+
+impl AccountSpec {
+    // Whether an account passed with these flags meets the spec
+    pub fn admits(&self, is_signer: bool, is_writable: bool) -> bool {
+        (is_signer || !self.signer) && (is_writable || !self.writable)
+    }
+}
+
+const R: AccountSpec = AccountSpec { signer: false, writable: false };
+const W: AccountSpec = AccountSpec { signer: false, writable: true };
+const S: AccountSpec = AccountSpec { signer: true, writable: false };
+const WS: AccountSpec = AccountSpec { signer: true, writable: true };
+
+// Leading accounts of each `RPSInstruction`, indexed by its Borsh
+// discriminant. Append a row with every new instruction.
+pub const INSTRUCTION_ACCOUNTS: &[&[AccountSpec]] = &[
+    &[WS, WS, R, W, W],              // InitializeGame: host, game (new), system, treasury, pot vault
+    &[WS, W, R, R, R, W, W, W, R],   // JoinGame: player, game, system, bans, config, treasury, vault, limits, exclusion
+    &[S, W, R],                      // CommitChoice: player, game, config
+    &[S, W, R],                      // RevealChoice: player, game, config
+    &[WS, W],                        // ResolveTimeout: caller (takes the crank reward), game
+    &[WS, W, R, R, W, R],            // ClaimWinnings: winner, game, system, config, pot vault, instructions sysvar
+    &[WS, W, R, W, W],               // RejoinGame: player, game, system, treasury, pot vault
+    &[S, W],                         // StartNewGameRound: initiator, game
+    &[S, W],                         // AutoPlayNextRound: initiator, game
+    &[WS, W, R, W, W, R],            // AddBotPlayers: initiator, game, system, treasury, pot vault, config
+    &[WS, W, R, W, R, R],            // CollectFees: collector, game, system, pot vault, config, instructions sysvar
+    &[WS, WS, R, R],                 // CreateTournament: host, tournament (new), system, config
+    &[WS, W, R, R, R, R],            // JoinTournament: player, tournament, system, bans, exclusion, config
+    &[WS, W, R, R, R, W, W],         // JoinGameWithSwap: player, game, system, Jupiter, token, USDC, destination
+    &[S, W, R],                      // ConfigureStakeMints: host, game, base price feed
+    // JoinGameWithMint: player, game, token program, both price feeds, source,
+    // vault, bans, exclusion, config, limits
+    &[S, W, R, R, R, W, W, R, R, R, W],
+    &[S, W],                         // StartTournament: host or co-admin, tournament
+    &[S, W, R],                      // ReportMatchResult: caller, tournament, match game
+    &[S, W, W],                      // FinishTournament: caller, tournament, treasury
+    &[WS, W],                        // ClaimTournamentPrize: player, tournament
+    &[S, W, W],                      // OpenTournamentMatch: host, tournament, match game
+    &[S, W],                         // DisqualifyPlayer: host or co-admin, tournament
+    &[S, W],                         // SetTournamentAdmins: host, tournament
+    &[WS, W, R],                     // InitializeConfig: authority, config, system
+    &[WS, R, W, R],                  // BanPlayer: authority, config, ban list chunk, system
+    &[S, R, W],                      // UnbanPlayer: authority, config, ban list chunk
+    &[S, W],                         // SetCompliance: authority, config
+    &[R],                            // GetClaimable: game
+    &[WS, WS, R, R, R, R],           // CreateChallenge: challenger, challenge (new), system, bans, exclusion, config
+    &[WS, W, R, R, R, R],            // AcceptChallenge: opponent, challenge, system, bans, exclusion, config
+    &[S, W],                         // CommitChallengeChoice: player, challenge
+    &[S, W, W, W],                   // RevealChallengeChoice: player, challenge, challenger, opponent
+    &[S, W, W, W],                   // ExpireChallenge: caller, challenge, challenger, opponent
+    &[S, W, R],                      // RecommitChoice: player, game, config
+    &[WS, R, W, R],                  // RecordGameResult: payer, game, result, system
+    &[R, R],                         // AuditGame: game, pot vault
+    &[WS, WS, R],                    // CreateAutoTournament: host, tournament (new), system
+    &[S, W],                         // CrankAutoTournament: caller, tournament
+    &[S, W, R, R],                   // RevealChoiceSigned: player, game, config, instructions sysvar
+    &[WS, W, R],                     // CreateClan: leader, clan, system
+    &[WS, W, R, R],                  // JoinTournamentAsClan: leader, tournament, clan, system
+    &[WS, W, WS, R, R, R],           // OpenTeamMatch: caller, tournament, game (new), clan A, clan B, system
+    &[S, W, R],                      // ClaimTeamPrize: caller, tournament, clan
+    &[WS, W, R],                     // InitializeStats: payer, stats, system
+    &[S, W],                         // SetTimeoutPolicy: authority, config
+    &[S, W, R],                      // RefreshDefaultTimeout: caller, config, stats
+    &[WS, W, W, R],                  // RecordParticipation: player, game, participation, system
+    &[S, W],                         // SetCollusionPolicy: authority, config
+    &[S, R, W],                      // ClearGameFlag: authority, config, game
+    &[S, W],                         // SetClaimPolicy: authority, config
+    &[S, W, R],                      // InitiateClaim: winner, game, config
+    &[WS, W, R, R, W, R],            // FinalizeClaim: as ClaimWinnings
+    &[WS, W, R, R],                  // RegisterLobbyName: registrant, name, target lobby, system
+    &[WS, WS, R],                    // CreateRecurringLobby: host, recurring lobby (new), system
+    &[WS, W, WS, R, W, R],           // SpawnScheduledLobby: caller, recurring lobby, game (new), system, vault, config
+    &[WS, R, W, R, R, R, R],         // JoinWaitlist: player, game, waitlist, system, bans, exclusion, config
+    &[WS, W, W, W, W],               // LeaveGame: player, game, waitlist, treasury, pot vault
+    &[S, W, W, W, W, W],             // KickPlayer: host, game, waitlist, treasury, pot vault, kicked wallet
+    &[S, R, W],                      // RefundWaitlist: caller, game, waitlist
+    &[S, W, W, W],                   // RefundExpiredEscrow: caller, game, wallet, pot vault
+    &[S, W],                         // SetPotGuarantee: authority, config
+    &[S, W, R, W, R, W],             // ApplyPotSubsidy: caller, game, config, treasury, system, pot vault
+    &[R],                            // GetFeeQuote: config
+    &[S, W],                         // SetPausedInstructions: authority, config
+    &[S, W, W],                      // FinalizeLegacyGame: caller, game, host
+    &[WS, W, R, R, R, R],            // OfferDoubleOrNothing: loser, challenge, system, bans, exclusion, config
+    // AcceptDoubleOrNothing: winner, challenge, rematch (new), system,
+    // challenger, bans, exclusion, config
+    &[WS, W, WS, R, W, R, R, R],
+    &[S, W],                         // SealEntryFee: host, game
+    &[S, W, W, W, R],                // RevealEntryFee: caller, game, pot vault, treasury, system
+    &[WS, W, WS, R],                 // SpawnTournamentMatch: caller, tournament, game (new), system
+    &[WS, W, R],                     // ArchiveGameResults: cranker, archive, system
+    &[S, W],                         // SetNicknamePolicy: authority, config
+    &[WS, W, W, R, R],               // SetNickname: player, profile, nickname, config, system
+    &[R],                            // GetSpectatorView: game
+    &[S, W, W, W, R],                // CloseGame: caller, game, pot vault, recipient, result
+    &[S, W],                         // SetFeePolicy: authority, config
+    &[S, W],                         // SetPayoutGuard: authority, config
+    &[WS, W, W, R],                  // InitializeGovernance: authority, config, governance, system
+    &[WS, W, W, R, R, W, W],         // StakeGovernanceTokens: staker, stake, governance, system, token, source, vault
+    &[S, W, W, R, W, W],             // UnstakeGovernanceTokens: staker, stake, governance, token, destination, vault
+    &[WS, R, W, R, W, R],            // ProposeParameterChange: proposer, stake, governance, config, proposal, system
+    &[WS, W, W, W, R],               // CastVote: voter, stake, proposal, vote record, system
+    &[S, W, R, W],                   // ExecuteProposal: caller, proposal, governance, config
+    &[WS, W, R],                     // SetLimits: player, limits, system
+    &[WS, W, R],                     // SelfExclude: player, exclusion, system
+    &[WS, R, W, R],                  // InitializeColdTreasury: authority, config, cold treasury, system
+    &[S, W, W, R],                   // SweepTreasury: caller, treasury, cold treasury, system
+    &[WS, W, W, R],                  // ProposeColdAction: cold signer, cold treasury, proposal, system
+    &[S, R, W],                      // ApproveColdAction: cold signer, cold treasury, proposal
+    &[S, W, W],                      // ExecuteColdAction: caller, cold treasury, proposal
+    &[S, W, R, W, R],                // DistributeWinnings: caller, game, config, pot vault, instructions sysvar
+    &[WS, R, W, R],                  // ConfigureInsuranceFund: authority, config, fund, system
+    &[WS, W, W, R],                  // FileInsuranceClaim: claimant, fund, claim, system
+    &[S, R, W, W, W],                // ResolveInsuranceClaim: authority, config, fund, claim, claimant
+    &[S, W, W, W, W],                // RefundEntry: caller, game, wallet, pot vault, treasury
+    // ClaimDemoTokens: player, profile, claim, mint, player tokens, token, system, associated token
+    &[WS, R, W, W, W, R, R, R],
+    &[WS, R, R, W, R, R, R, R],      // CreateGameTokenVault: payer, game, authority, vault, mint, system, token, ATA
+    &[S, W],                         // BotAct: caller, game; the handler checks the SlotHashes sysvar
+];
+
+// The row of instruction `tag`; empty for a tag the table does not know
+pub fn instruction_accounts(tag: u8) -> &'static [AccountSpec] {
+    INSTRUCTION_ACCOUNTS.get(tag as usize).copied().unwrap_or(&[])
+}
+
+// Where the accounts passed to an instruction first fall short of its row
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccountMismatch {
+    Missing(usize),  // Fewer accounts than the row lists; the first absent index
+    Unsigned(usize), // The account at this index must sign
+    ReadOnly(usize), // The account at this index must be writable
+}
+
+// Checks the (is_signer, is_writable) flags of the accounts passed to
+// instruction `tag`, in order, against its row
+pub fn find_mismatch(tag: u8, flags: impl IntoIterator<Item = (bool, bool)>) -> Option<AccountMismatch> {
+    let mut flags = flags.into_iter();
+    for (index, spec) in instruction_accounts(tag).iter().enumerate() {
+        let Some((is_signer, is_writable)) = flags.next() else {
+            return Some(AccountMismatch::Missing(index));
+        };
+        if spec.signer && !is_signer {
+            return Some(AccountMismatch::Unsigned(index));
+        }
+        if spec.writable && !is_writable {
+            return Some(AccountMismatch::ReadOnly(index));
+        }
+    }
+    None
+}
+
+// Fails an instruction whose accounts do not meet its row, as a handler
+// would: a missing signature is NotAuthorized
+pub fn check_accounts(tag: u8, accounts: &[AccountInfo]) -> Result<(), ProgramError> {
+    match find_mismatch(tag, accounts.iter().map(|acc| (acc.is_signer, acc.is_writable))) {
+        None => Ok(()),
+        Some(AccountMismatch::Missing(index)) => {
+            msg!("Instruction {} takes at least {} accounts, got {}", tag, instruction_accounts(tag).len(), index);
+            Err(ProgramError::NotEnoughAccountKeys)
+        }
+        Some(AccountMismatch::Unsigned(index)) => {
+            msg!("Account {} must sign instruction {}", index, tag);
+            Err(RPSError::NotAuthorized.into())
+        }
+        Some(AccountMismatch::ReadOnly(index)) => {
+            msg!("Account {} must be writable for instruction {}", index, tag);
+            Err(RPSError::AccountNotWritable.into())
+        }
+    }
+}
@@ -7,24 +7,34 @@ use solana_program::{
     program_error::ProgramError,
     pubkey::Pubkey,
     system_instruction,
-    program::{invoke, invoke_signed},
+    program::{invoke, invoke_signed, set_return_data},
     sysvar::{rent::Rent, Sysvar},
     clock::Clock,
-    hash::{hashv, Hash},
+    hash::hashv,
     instruction::{AccountMeta, Instruction},
     program_pack::Pack,
+    program_option::COption,
+    decode_error::DecodeError,
+    log::sol_log_data,
+    ed25519_program,
+    sysvar::instructions::{load_current_index_checked, load_instruction_at_checked},
+    sysvar::slot_hashes,
 };
-use std::collections::HashMap;
+use num_derive::FromPrimitive;
+use std::cmp::Ordering;
 use std::convert::TryInto;
 use thiserror::Error;
 
-// Import SPL Token program for RPS token support
-use anchor_spl::token::{self, Token, TokenAccount, Mint, Transfer};
-use sha2::{Digest, Sha512};
-use hmac::{Hmac, Mac}; // NEW: proper HMAC support
+use pyth_sdk_solana::state::SolanaPriceAccount;
+use rps_state::rules::{self, GameRules};
+use rps_state::simulate::{self, Hand};
+
+pub mod accounts;
+pub mod state_machine;
+use state_machine::{Action, Phase, Transition};
 
 // Define custom errors for better error handling
-#[derive(Error, Debug, Copy, Clone)]
+#[derive(Error, Debug, Copy, Clone, PartialEq, Eq, FromPrimitive)]
 pub enum RPSError {
     #[error("Invalid player state")]
     InvalidPlayerState,
@@ -67,6 +77,60 @@ pub enum RPSError {
     
     #[error("Token transfer error")]
     TokenTransferError,
+
+    #[error("Swap slippage exceeded")]
+    SlippageExceeded,
+
+    #[error("Invalid token account")]
+    InvalidTokenAccount,
+
+    #[error("Mint not approved for staking")]
+    MintNotApproved,
+
+    #[error("Price feed unavailable or stale")]
+    StalePriceFeed,
+
+    #[error("Game phase changed since the request was built")]
+    StalePhase,
+
+    #[error("Winnings already claimed")]
+    AlreadyClaimed,
+
+    #[error("Player did not qualify")]
+    NotQualified,
+
+    #[error("Player is banned")]
+    PlayerBanned,
+
+    #[error("Compliance credential missing or invalid")]
+    MissingCredential,
+
+    #[error("Choice already committed")]
+    AlreadyCommitted,
+
+    #[error("Reveal signature missing or invalid")]
+    InvalidRevealSignature,
+
+    #[error("Payouts are held while the game is flagged for review")]
+    PayoutHeld,
+
+    #[error("Large payout must be initiated and wait out its delay")]
+    ClaimNotReady,
+
+    #[error("Instruction is paused by the operator")]
+    InstructionPaused,
+
+    #[error("Transaction holds instructions a guarded payout does not allow")]
+    UnexpectedInstruction,
+
+    #[error("Player's own wager limit or cool-off is in effect")]
+    SelfLimitReached,
+
+    #[error("Player has excluded themselves from play")]
+    SelfExcluded,
+
+    #[error("Account must be writable")]
+    AccountNotWritable,
 }
 
 // Map custom errors to ProgramError
@@ -76,6 +140,94 @@ impl From<RPSError> for ProgramError {
     }
 }
 
+// Lets clients decode ProgramError::Custom codes back into RPSError
+impl<T> DecodeError<T> for RPSError {
+    fn type_of() -> &'static str {
+        "RPSError"
+    }
+}
+
+// Verbose success-path logging, compiled in only with the `debug-logs`
+// feature. Production builds report progress through RPSEvent instead.
+macro_rules! debug_msg {
+    ($($arg:tt)*) => {
+        if cfg!(feature = "debug-logs") {
+            msg!($($arg)*);
+        }
+    };
+}
+
+// Compact events emitted via sol_log_data; clients decode them from the
+// base64 payload of `Program data:` log lines
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub enum RPSEvent {
+    GameCreated { game: Pubkey, host: Pubkey, theme_id: u16 },
+    PlayerJoined { game: Pubkey, player: Pubkey },
+    ChoiceCommitted { game: Pubkey, player: Pubkey },
+    RevealPhaseStarted { game: Pubkey },
+    ChoiceRevealed { game: Pubkey, player: Pubkey },
+    RoundResolved { game: Pubkey, round: u8, scores: Vec<u8> },
+    RoundStarted { game: Pubkey, round: u8, total_rounds: u8 },
+    GameFinished { game: Pubkey },
+    TimeoutResolved { game: Pubkey },
+    WinningsClaimed { game: Pubkey, winner: Pubkey, amount: u64 },
+    CommitProgress { game: Pubkey, committed: u8, total: u8 },
+    RevealProgress { game: Pubkey, revealed: u8, total: u8 },
+    PhaseStarted { game: Pubkey, phase_nonce: u64, deadline: u64 },
+    ChoiceRecommitted { game: Pubkey, player: Pubkey },
+    AutoMatchPlayed { tournament: Pubkey, match_index: u8, winner: Pubkey, wins_a: u8, wins_b: u8 },
+    PhaseEnded { game: Pubkey, phase_nonce: u64, state: GameState, seconds: u64 },
+    CollusionSuspected { game: Pubkey, reasons: u8 },
+    ClaimInitiated { game: Pubkey, winner: Pubkey, ready_at: u64 },
+    PlayerLeft { game: Pubkey, player: Pubkey },
+    PotSubsidized { game: Pubkey, amount: u64, pot: u64 },
+    RevealOrderDrawn { game: Pubkey, round: u8, order: Vec<u8> },
+    EntryFeeRevealed { game: Pubkey, entry_fee: u64, refund: u64 },
+    GameClosed { game: Pubkey, recipient: Pubkey, lamports: u64 },
+    // `source` is the game or tournament the fee was taken from
+    FeeCollected { source: Pubkey, recipient: Pubkey, amount: u64 },
+    TournamentCreated { tournament: Pubkey, host: Pubkey, max_players: u8, entry_fee: u64 },
+    // `entrant` is the player, or the clan account in team tournaments
+    TournamentJoined { tournament: Pubkey, entrant: Pubkey },
+    TournamentStarted { tournament: Pubkey, entrants: u8 },
+    MatchOpened { tournament: Pubkey, match_index: u8, game: Pubkey },
+    // No winner means a draw, and the match is replayed
+    MatchDecided { tournament: Pubkey, match_index: u8, winner: Option<Pubkey> },
+    EntrantDisqualified { tournament: Pubkey, entrant: Pubkey },
+    TournamentFinished { tournament: Pubkey, champion: Pubkey, prize_pool: u64 },
+    PrizePaid { tournament: Pubkey, recipient: Pubkey, placing: u8, amount: u64 },
+    ChallengeCreated { challenge: Pubkey, challenger: Pubkey, opponent: Pubkey, stake: u64 },
+    ChallengeAccepted { challenge: Pubkey, opponent: Pubkey },
+    // What each side, challenger first, was paid
+    ChallengeSettled { challenge: Pubkey, payouts: [u64; 2] },
+    DoubleOrNothingOffered { challenge: Pubkey, offer: u64 },
+    DoubleOrNothingAccepted { challenge: Pubkey, rematch: Pubkey },
+    WaitlistJoined { game: Pubkey, player: Pubkey, position: u8 },
+    WaitlistRefunded { game: Pubkey, entries: u8 },
+    EntryFeeSealed { game: Pubkey, min_fee: u64, max_fee: u64 },
+    ProposalCreated { proposal: Pubkey, index: u64, change: ParameterChange, voting_ends_at: u64 },
+    VoteCast { proposal: Pubkey, voter: Pubkey, approve: bool, weight: u64 },
+    ProposalExecuted { proposal: Pubkey, index: u64 },
+    TreasurySwept { amount: u64, hot_balance: u64 },
+    ColdActionProposed { proposal: Pubkey, index: u64, action: ColdAction, ready_at: u64 },
+    ColdActionApproved { proposal: Pubkey, signer: Pubkey, approvals: u8 },
+    ColdActionExecuted { proposal: Pubkey, index: u64 },
+    InsuranceContributed { amount: u64, balance: u64 },
+    InsuranceClaimFiled { claim: Pubkey, index: u64, claimant: Pubkey, requested: u64 },
+    InsuranceClaimResolved { claim: Pubkey, index: u64, claimant: Pubkey, paid: u64 },
+    EntryRefunded { game: Pubkey, player: Pubkey, amount: u64 },
+    DemoTokensClaimed { player: Pubkey, amount: u64 },
+    CrankRewarded { game: Pubkey, keeper: Pubkey, amount: u64 },
+    PlayerSlashed { game: Pubkey, player: Pubkey, round: u8 },
+}
+
+// Helper to emit an event as a single Borsh-encoded log_data field
+fn emit_event(event: &RPSEvent) {
+    if let Ok(data) = event.try_to_vec() {
+        sol_log_data(&[&data]);
+    }
+}
+
 // Helper to assert ownership of an account
 #[inline(always)]
 fn assert_owned_by(acc: &AccountInfo, owner: &Pubkey) -> ProgramResult {
@@ -86,6 +238,34 @@ fn assert_owned_by(acc: &AccountInfo, owner: &Pubkey) -> ProgramResult {
     Ok(())
 }
 
+// Helpers to pin the program slots. Invoking whatever account the caller put
+// there would let a spoofed "system program" or "token program" fake a
+// transfer. Only the legacy Token program is accepted: every token CPI here
+// is built against its instruction layout and id.
+fn check_system_program(acc: &AccountInfo) -> ProgramResult {
+    if *acc.key != solana_program::system_program::id() {
+        msg!("Expected the system program, got {}", acc.key);
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    Ok(())
+}
+
+fn check_token_program(acc: &AccountInfo) -> ProgramResult {
+    if *acc.key != spl_token::id() {
+        msg!("Expected the token program, got {}", acc.key);
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    Ok(())
+}
+
+fn check_associated_token_program(acc: &AccountInfo) -> ProgramResult {
+    if *acc.key != ASSOCIATED_TOKEN_PROGRAM_ID {
+        msg!("Expected the associated token account program, got {}", acc.key);
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    Ok(())
+}
+
 // Helper to build PDA seed slice (without bump)
 #[inline(always)]
 fn rps_pda_seeds(game_key: &Pubkey) -> [&[u8]; 2] {
@@ -99,6 +279,31 @@ pub enum Choice {
     Rock,
     Paper,
     Scissors,
+    Lizard, // Lizard-Spock games only
+    Spock,  // Lizard-Spock games only
+}
+
+impl Choice {
+    pub fn hand(&self) -> Option<Hand> {
+        match self {
+            Choice::None => None,
+            Choice::Rock => Some(Hand::Rock),
+            Choice::Paper => Some(Hand::Paper),
+            Choice::Scissors => Some(Hand::Scissors),
+            Choice::Lizard => Some(Hand::Lizard),
+            Choice::Spock => Some(Hand::Spock),
+        }
+    }
+
+    pub fn from_hand(hand: Hand) -> Self {
+        match hand {
+            Hand::Rock => Choice::Rock,
+            Hand::Paper => Choice::Paper,
+            Hand::Scissors => Choice::Scissors,
+            Hand::Lizard => Choice::Lizard,
+            Hand::Spock => Choice::Spock,
+        }
+    }
 }
 
 #[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
@@ -107,29 +312,520 @@ pub enum GameMode {
     Automated,
 }
 
-#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy)]
 pub enum CurrencyMode {
     SOL,
     RPSToken,
 }
 
-// Constants for fee calculation
-const FEE_PERCENTAGE: u64 = 10; // 1% (10/1000)
-const FEE_DENOMINATOR: u64 = 1000;
-// Public key of the fee collector account (should be updated to actual account)
-const FEE_COLLECTOR: &str = "FeeCoLLeCToRyouNEEDtoUPDATEthiswithREALaccount111";
+// How a game's commitments are hashed; see rps_state::simulate::CommitmentScheme
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommitmentScheme {
+    HmacSha512,
+    Sha256, // For clients without a fast SHA-512
+}
 
-#[derive(BorshSerialize, BorshDeserialize, Debug)]
+impl CommitmentScheme {
+    fn rules(self) -> simulate::CommitmentScheme {
+        match self {
+            CommitmentScheme::HmacSha512 => simulate::CommitmentScheme::HmacSha512,
+            CommitmentScheme::Sha256 => simulate::CommitmentScheme::Sha256,
+        }
+    }
+}
+
+// Rules a game plays by; see rps_state::rules. Classic is the zero byte, so
+// games from before rule sets read as classic.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RuleSet {
+    Classic,
+    LizardSpock,
+}
+
+impl RuleSet {
+    fn rules(self) -> &'static dyn GameRules {
+        match self {
+            RuleSet::Classic => rules::RuleSet::Classic,
+            RuleSet::LizardSpock => rules::RuleSet::LizardSpock,
+        }
+        .rules()
+    }
+}
+
+// The platform fee rate is set in the config PDA (`SetFeePolicy`); games
+// created without one use simulate::DEFAULT_FEE_BPS
+const MAX_FEE_BPS: u16 = 1_000; // 10% upper bound on the configured rate
+
+// The only other program a guarded payout may share its transaction with
+const COMPUTE_BUDGET_PROGRAM_ID: Pubkey = solana_program::pubkey!("ComputeBudget111111111111111111111111111111");
+
+// Swap-on-join support: entry fees paid in USDC are routed through Jupiter
+const USDC_MINT: Pubkey = solana_program::pubkey!("EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v");
+const JUPITER_PROGRAM_ID: Pubkey = solana_program::pubkey!("JUP6LkbZbjS1jKKwapdHNy74zcZ3tLUZoi5QNyVTaV4");
+const MAX_SWAP_SLIPPAGE_BPS: u16 = 300; // 3% upper bound on caller-supplied slippage
+const BPS_DENOMINATOR: u64 = 10_000;
+
+// Multi-currency pots: extra mints players may stake, valued via Pyth
+const USDT_MINT: Pubkey = solana_program::pubkey!("Es9vMFrzaCERmJfrF4H2FYD4KCoNkY11McCe8BenwNYB");
+const APPROVED_STAKE_MINTS: [Pubkey; 3] = [USDC_MINT, USDT_MINT, spl_token::native_mint::ID];
+const MAX_STAKE_MINTS: usize = 3;
+const MAX_PRICE_AGE_SECS: u64 = 60;
+
+// Tournament prizes in basis points of the prize pool net of the platform
+// fee, by final placing (champion first)
+const TOURNAMENT_PRIZE_SPLIT_BPS: [u64; 2] = [7_000, 3_000];
+
+// Co-admins a tournament host can delegate administration to
+const MAX_TOURNAMENT_ADMINS: usize = 4;
+
+// Longest best-of an auto tournament match can be, keeping a crank's
+// rethrown draws well inside the compute budget
+const MAX_AUTO_MATCH_ROUNDS: u8 = 15;
+
+// Seats a lobby may have; two makes a head-to-head duel. The per-seat
+// bitmaps (`claimed_bitmap`, `participation_bitmap`, winners) are u8, so
+// eight is as large as a lobby can get without a layout change.
+const MIN_SEATS: u8 = 2;
+const MAX_SEATS: u8 = 8;
+
+// Seats each clan fills in a team match (2v2)
+const TEAM_MATCH_SIZE: usize = 2;
+const MAX_CLAN_MEMBERS: usize = 8;
+const MAX_CLAN_NAME_LEN: usize = 32;
+
+// Lobby names are short lowercase slugs, so lookalikes can't be registered
+// next to a popular name
+const MIN_LOBBY_NAME_LEN: usize = 3;
+const MAX_LOBBY_NAME_LEN: usize = 32;
+
+// Nicknames are display names, so case is kept, but uniqueness ignores it.
+// A player can change theirs once per cooldown.
+const MIN_NICKNAME_LEN: usize = 3;
+const MAX_NICKNAME_LEN: usize = 20;
+pub const NICKNAME_COOLDOWN_SECS: u64 = 7 * 24 * 60 * 60;
+
+// Self-limits: wagers count against day-long windows, and loosening a limit
+// only takes effect after a delay, so it can't be lifted mid-session
+pub const WAGER_WINDOW_SECS: u64 = 24 * 60 * 60;
+pub const LIMIT_RAISE_DELAY_SECS: u64 = 3 * 24 * 60 * 60;
+
+// Queued players a full lobby will escrow entries for
+pub const MAX_WAITLIST: usize = 8;
+
+// Cold treasury: a multisig of up to five keys, at least two of which must
+// approve, and a timelock of at least a day on everything it does
+pub const MAX_COLD_SIGNERS: usize = 5;
+pub const MIN_COLD_THRESHOLD: u8 = 2;
+pub const MIN_COLD_TIMELOCK_SECS: u64 = 24 * 60 * 60;
+
+// Largest share of each treasury sweep the insurance fund can take
+pub const MAX_INSURANCE_SHARE_BPS: u16 = 1_000;
+
+// Largest crank reward a game can offer, as a share of its entry fee:
+// enough to keep keepers cranking, too little for a host to drain the pot
+// by timing out their own game
+pub const MAX_CRANK_REWARD_BPS: u16 = 100;
+
+// Devnet faucet: a wallet with a profile can take a hundred test tokens
+// once a day
+pub const DEMO_TOKEN_DECIMALS: u8 = 9;
+pub const DEMO_CLAIM_AMOUNT: u64 = 100 * 1_000_000_000;
+pub const DEMO_CLAIM_COOLDOWN_SECS: u64 = 24 * 60 * 60;
+
+// Seed of the program treasury PDA that receives platform fees
+pub const TREASURY_SEED: &[u8] = b"rps_treasury";
+
+pub fn treasury_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[TREASURY_SEED], program_id)
+}
+
+// Seed of the `ColdTreasury` PDA, which holds the fees swept out of the
+// (hot) treasury above its cap
+pub const COLD_TREASURY_SEED: &[u8] = b"rps_cold_treasury";
+
+pub fn cold_treasury_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[COLD_TREASURY_SEED], program_id)
+}
+
+// Seed of a `ColdProposal` PDA, numbered from 0 by its little-endian index
+pub const COLD_PROPOSAL_SEED: &[u8] = b"rps_cold_proposal";
+
+pub fn cold_proposal_address(program_id: &Pubkey, index: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[COLD_PROPOSAL_SEED, &index.to_le_bytes()], program_id)
+}
+
+// Seed of the `InsuranceFund` PDA, which reimburses losses to program faults
+pub const INSURANCE_FUND_SEED: &[u8] = b"rps_insurance_fund";
+
+pub fn insurance_fund_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[INSURANCE_FUND_SEED], program_id)
+}
+
+// Seed of an `InsuranceClaim` PDA, numbered from 0 by its little-endian index
+pub const INSURANCE_CLAIM_SEED: &[u8] = b"rps_insurance_claim";
+
+pub fn insurance_claim_address(program_id: &Pubkey, index: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[INSURANCE_CLAIM_SEED, &index.to_le_bytes()], program_id)
+}
+
+// Seed of the program-wide configuration PDA
+pub const CONFIG_SEED: &[u8] = b"rps_config";
+
+pub fn config_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[CONFIG_SEED], program_id)
+}
+
+// Ban list chunks are sharded by the first byte of the wallet, so a join
+// only ever needs the one chunk that could hold the joiner
+pub const BAN_LIST_SEED: &[u8] = b"rps_ban_list";
+const BAN_LIST_CHUNK_CAPACITY: usize = 64;
+
+// Credential issuers the operator can approve for compliance mode
+const MAX_ATTESTORS: usize = 8;
+
+// New commits and reveals a default timeout refresh must have to average
+// over, so a handful of fast or slow games can't swing it
+const MIN_TIMEOUT_SAMPLE: u64 = 20;
+
+// Settlement-time collusion heuristics: one hand played this many rounds
+// running, or this many rounds in a row lost to the same opponent without
+// beating anybody, is improbable enough to flag
+const SUSPICIOUS_HAND_STREAK: u8 = 5;
+const DUMP_LOSS_ROUNDS: u8 = 3;
+
+// How long a SOL entry stays escrowed in a lobby that never starts before
+// anyone can return it with `RefundExpiredEscrow`
+pub const ENTRY_REFUND_SECS: u64 = 24 * 60 * 60;
+
+// Layout written into `Game::layout_version`. Games from before versioning
+// read as 0; their accounts may also be too small for the fields appended
+// since, which decode from up to this much zero padding. Layout 2 moved the
+// SOL pot out of the game account into its pot vault.
+pub const GAME_LAYOUT_VERSION: u8 = 2;
+const LEGACY_TAIL_PADDING: usize = 256;
+
+// Reasons a game was flagged, as bits of `Game::flag_reasons`
+pub const FLAG_SHARED_FUNDING: u8 = 1 << 0; // A seated wallet paid for opponents' seats
+pub const FLAG_HAND_PATTERN: u8 = 1 << 1;   // A seat repeated one hand improbably often
+pub const FLAG_DUMP_LOSSES: u8 = 1 << 2;    // A seat kept losing to the same wallet
+
+// How long a named opponent has to accept a challenge, and then how long
+// both sides have to commit and reveal once it is accepted
+const CHALLENGE_ACCEPT_SECS: i64 = 24 * 60 * 60;
+const CHALLENGE_PLAY_SECS: i64 = 60 * 60;
+// How long the loser of a decided challenge has to offer double or nothing,
+// and then how long the winner has to take it up
+const DOUBLE_OR_NOTHING_SECS: i64 = 10 * 60;
+
+pub fn ban_list_address(program_id: &Pubkey, wallet: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[BAN_LIST_SEED, &wallet.as_ref()[..1]], program_id)
+}
+
+// Authority PDA that owns a game's token accounts and signs its payouts
+pub fn game_authority_address(program_id: &Pubkey, game: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&rps_pda_seeds(game), program_id)
+}
+
+pub const ASSOCIATED_TOKEN_PROGRAM_ID: Pubkey =
+    solana_program::pubkey!("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL");
+
+// Token vault of an RPSToken game: the game authority's associated token
+// account for the game mint, so each game has exactly one and clients can
+// derive it
+pub fn game_vault_address(program_id: &Pubkey, game: &Pubkey, mint: &Pubkey) -> Pubkey {
+    let authority = game_authority_address(program_id, game).0;
+    Pubkey::find_program_address(
+        &[authority.as_ref(), spl_token::id().as_ref(), mint.as_ref()],
+        &ASSOCIATED_TOKEN_PROGRAM_ID,
+    )
+    .0
+}
+
+// Seed of the per-game `GameResult` PDA that outlives the game account
+pub const GAME_RESULT_SEED: &[u8] = b"rps_result";
+
+pub fn game_result_address(program_id: &Pubkey, game: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[GAME_RESULT_SEED, game.as_ref()], program_id)
+}
+
+// Seed of the per-epoch `ResultArchive` PDAs finished results are folded
+// into. Archive epochs are fixed weeks of unix time, not Solana epochs,
+// since a result only carries timestamps.
+pub const RESULT_ARCHIVE_SEED: &[u8] = b"rps_archive";
+pub const ARCHIVE_EPOCH_SECONDS: u64 = 7 * 24 * 60 * 60;
+
+pub fn result_archive_address(program_id: &Pubkey, epoch: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[RESULT_ARCHIVE_SEED, &epoch.to_le_bytes()], program_id)
+}
+
+// Seed of the program-wide `GlobalStats` PDA
+pub const STATS_SEED: &[u8] = b"rps_stats";
+
+pub fn stats_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[STATS_SEED], program_id)
+}
+
+// Seed of the per-player, per-epoch `Participation` PDAs
+pub const PARTICIPATION_SEED: &[u8] = b"rps_participation";
+
+pub fn participation_address(program_id: &Pubkey, player: &Pubkey, epoch: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[PARTICIPATION_SEED, player.as_ref(), &epoch.to_le_bytes()], program_id)
+}
+
+// Seed of the clan PDAs team tournaments are entered with; clan names are
+// unique program-wide
+pub const CLAN_SEED: &[u8] = b"rps_clan";
+
+pub fn clan_address(program_id: &Pubkey, name: &str) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[CLAN_SEED, name.as_bytes()], program_id)
+}
+
+// Seed of the lobby name registry. Each name's PDA is keyed by the name's
+// hash, so clients resolve "friday-night" without knowing any address.
+pub const LOBBY_NAME_SEED: &[u8] = b"rps_lobby_name";
+
+pub fn lobby_name_hash(name: &str) -> [u8; 32] {
+    hashv(&[name.as_bytes()]).to_bytes()
+}
+
+pub fn lobby_name_address(program_id: &Pubkey, name: &str) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[LOBBY_NAME_SEED, &lobby_name_hash(name)], program_id)
+}
+
+// Seed of the per-player `PlayerProfile` PDA
+pub const PROFILE_SEED: &[u8] = b"rps_profile";
+
+pub fn profile_address(program_id: &Pubkey, player: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[PROFILE_SEED, player.as_ref()], program_id)
+}
+
+// Seed of the per-player `PlayerLimits` PDA
+pub const LIMITS_SEED: &[u8] = b"rps_limits";
+
+pub fn limits_address(program_id: &Pubkey, player: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[LIMITS_SEED, player.as_ref()], program_id)
+}
+
+// Seed of the per-player `SelfExclusion` PDA
+pub const EXCLUSION_SEED: &[u8] = b"rps_exclusion";
+
+pub fn exclusion_address(program_id: &Pubkey, player: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[EXCLUSION_SEED, player.as_ref()], program_id)
+}
+
+// Seed of the nickname registry. Like lobby names, each nickname's PDA is
+// keyed by a hash, here of the lowercased name, so "Ace" and "ACE" collide.
+pub const NICKNAME_SEED: &[u8] = b"rps_nickname";
+
+pub fn nickname_hash(nickname: &str) -> [u8; 32] {
+    hashv(&[nickname.to_ascii_lowercase().as_bytes()]).to_bytes()
+}
+
+pub fn nickname_address(program_id: &Pubkey, nickname: &str) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[NICKNAME_SEED, &nickname_hash(nickname)], program_id)
+}
+
+// Seed of the per-game waitlist PDA that escrows queued entries
+pub const WAITLIST_SEED: &[u8] = b"rps_waitlist";
+
+pub fn waitlist_address(program_id: &Pubkey, game: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[WAITLIST_SEED, game.as_ref()], program_id)
+}
+
+// Seed of the per-game pot vault PDA, a data-less account that holds a SOL
+// game's pot apart from the game state
+pub const POT_VAULT_SEED: &[u8] = b"rps_vault";
+
+pub fn pot_vault_address(program_id: &Pubkey, game: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[POT_VAULT_SEED, game.as_ref()], program_id)
+}
+
+// Seed of the `Governance` PDA, which also owns the staked tokens
+pub const GOVERNANCE_SEED: &[u8] = b"rps_governance";
+
+pub fn governance_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[GOVERNANCE_SEED], program_id)
+}
+
+// Token account holding the staked tokens: the governance PDA's associated
+// token account for the stake mint
+pub fn governance_vault_address(program_id: &Pubkey, mint: &Pubkey) -> Pubkey {
+    let authority = governance_address(program_id).0;
+    Pubkey::find_program_address(
+        &[authority.as_ref(), spl_token::id().as_ref(), mint.as_ref()],
+        &ASSOCIATED_TOKEN_PROGRAM_ID,
+    )
+    .0
+}
+
+// Seed of a staker's `VoterStake` PDA
+pub const VOTER_STAKE_SEED: &[u8] = b"rps_voter";
+
+pub fn voter_stake_address(program_id: &Pubkey, voter: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[VOTER_STAKE_SEED, voter.as_ref()], program_id)
+}
+
+// Seed of a `Proposal` PDA, numbered from 0 by its little-endian index
+pub const PROPOSAL_SEED: &[u8] = b"rps_proposal";
+
+pub fn proposal_address(program_id: &Pubkey, index: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[PROPOSAL_SEED, &index.to_le_bytes()], program_id)
+}
+
+// Seed of the `VoteRecord` PDA a voter creates on a proposal; one per
+// voter and proposal, so nobody votes twice
+pub const VOTE_RECORD_SEED: &[u8] = b"rps_vote";
+
+pub fn vote_record_address(program_id: &Pubkey, proposal: &Pubkey, voter: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[VOTE_RECORD_SEED, proposal.as_ref(), voter.as_ref()], program_id)
+}
+
+// Seed of the devnet test token mint. The mint PDA is its own mint
+// authority, and `ClaimDemoTokens` creates it on first use.
+pub const DEMO_MINT_SEED: &[u8] = b"rps_demo_mint";
+
+pub fn demo_mint_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[DEMO_MINT_SEED], program_id)
+}
+
+// Seed of the per-wallet `DemoClaim` PDA that rate-limits the faucet
+pub const DEMO_CLAIM_SEED: &[u8] = b"rps_demo_claim";
+
+pub fn demo_claim_address(program_id: &Pubkey, player: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[DEMO_CLAIM_SEED, player.as_ref()], program_id)
+}
+
+// Where `ClaimDemoTokens` pays a wallet: its associated token account for
+// the demo mint, which the claim creates when missing
+pub fn demo_token_address(program_id: &Pubkey, player: &Pubkey) -> Pubkey {
+    let mint = demo_mint_address(program_id).0;
+    Pubkey::find_program_address(
+        &[player.as_ref(), spl_token::id().as_ref(), mint.as_ref()],
+        &ASSOCIATED_TOKEN_PROGRAM_ID,
+    )
+    .0
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
 pub struct Player {
     pub pubkey: Pubkey,
     pub choice: Choice,
     pub committed_choice: [u8; 64], // Upgraded to SHA512 hash size
-    pub salt: [u8; 32],             // Store salt separately for verification
+    // Unused and always zero: salts arrive with the reveal. It stays because
+    // every game account already written holds it in each seat, and older
+    // layouts only decode when the fields they lack come at the end
+    pub salt: [u8; 32],
     pub revealed: bool,
     pub score: u8,
 }
 
-#[derive(BorshSerialize, BorshDeserialize, Debug)]
+impl Player {
+    pub const LEN: usize = 32 + 1 + 64 + 32 + 1 + 1;
+}
+
+// Per-seat tallies the collusion heuristics read when the game finishes
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct SeatWatch {
+    pub player: Pubkey,
+    pub last_hand: u8,           // Hand byte of the last revealed hand, 0 if none
+    pub hand_streak: u8,         // Rounds in a row `last_hand` was played
+    pub lost_to: Option<Pubkey>, // Opponent that beat this seat every round of `loss_streak`
+    pub loss_streak: u8,
+}
+
+impl SeatWatch {
+    pub const LEN: usize = 32 + 1 + 1 + 1 + 32 + 1;
+}
+
+// A seat AddBotPlayers filled, and how BotAct picks its hands
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct BotSeat {
+    pub pubkey: Pubkey,
+    pub strategy: BotStrategy,
+    pub funder: Pubkey, // Wallet that paid for the seat; its winnings and refunds go here
+}
+
+impl BotSeat {
+    pub const LEN: usize = 32 + 1 + 32;
+}
+
+// How a bot picks its hand, as the byte AddBotPlayers takes
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BotStrategy {
+    Random,          // 0: any hand of the rule set, all equally likely
+    CounterLastMove, // 1: a hand beating what the other seats played most last round
+    Weighted,        // 2: favours the rule set's first hands, rock most of all
+}
+
+// Who a finished game pays and how much, fixed when it finished so claims
+// look their share up rather than work it out from scores and the pot
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct Settlement {
+    pub payouts: Vec<Payout>, // One per winner, in seat order
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Payout {
+    pub player: Pubkey,
+    pub amount: u64, // Share of pot_snapshot; the last winner's includes the rounding dust
+}
+
+impl Payout {
+    pub const LEN: usize = 32 + 8;
+}
+
+impl Settlement {
+    // `pot` split between the seats in `winners`
+    pub fn new(players: &[Player], pot: u64, winners: u8) -> Self {
+        let payouts = simulate::pot_shares(pot, winners)
+            .into_iter()
+            .filter_map(|(seat, amount)| players.get(seat).map(|p| Payout { player: p.pubkey, amount }))
+            .collect();
+        Settlement { payouts }
+    }
+
+    // The share recorded for `player`, if they won
+    pub fn share_of(&self, player: &Pubkey) -> Option<u64> {
+        self.payouts.iter().find(|p| p.player == *player).map(|p| p.amount)
+    }
+}
+
+// A large payout waiting out its delay; see `InitiateClaim`
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct PendingClaim {
+    pub player: Pubkey,
+    pub ready_at: u64, // FinalizeClaim is accepted from here on
+}
+
+impl PendingClaim {
+    pub const LEN: usize = 32 + 8;
+}
+
+// A seat's SOL entry, held by the game until play starts
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct EntryEscrow {
+    pub player: Pubkey,
+    pub amount: u64,       // Pot share paid in; the platform fee is not escrowed
+    pub refund_after: u64, // RefundExpiredEscrow is accepted from here on
+}
+
+impl EntryEscrow {
+    pub const LEN: usize = 32 + 8 + 8;
+}
+
+// A mystery-stake game's sealed entry fee; until it is revealed the game's
+// `entry_fee` is the cap every seat escrows
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct HiddenFee {
+    pub commitment: [u8; 32], // hidden_fee_commitment(fee, salt)
+    pub min_fee: u64,
+}
+
+impl HiddenFee {
+    pub const LEN: usize = 32 + 8;
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
 pub enum GameState {
     WaitingForPlayers,
     CommitPhase,
@@ -137,6 +833,27 @@ pub enum GameState {
     Finished,
 }
 
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct StakeVault {
+    pub mint: Pubkey,
+    pub price_feed: Pubkey, // Pyth price account for this mint
+    pub vault: Pubkey,      // Token account owned by the game PDA
+    pub decimals: u8,
+    pub balance: u64,       // Staked amount still owed to winners
+    pub fees: u64,          // Platform fee retained in the vault
+}
+
+impl StakeVault {
+    pub const LEN: usize = 32 + 32 + 32 + 1 + 8 + 8;
+}
+
+// Everything in a game account is public while a round is live, so it must
+// never hold anything that narrows down a hand not yet revealed: only the
+// salted commitment of each seat is stored, salts arrive with the reveal,
+// and draws such as the seat count and reveal order are made once their
+// inputs are fixed. A feature that needs to keep a secret across a round
+// (bot seeds, pending randomness) has to keep it in its own account that
+// is closed at the reveal, not here.
 #[derive(BorshSerialize, BorshDeserialize, Debug)]
 pub struct Game {
     pub host: Pubkey,
@@ -150,7 +867,7 @@ pub struct Game {
     pub game_pot: u64,
     pub required_timeout: u64,
     pub last_action_timestamp: u64,
-    pub player_count: u8,        // Actual number of players (randomized between 3-4)
+    pub player_count: u8,        // Actual number of players (drawn between min and max)
     pub losers_can_rejoin: bool, // Indicates if losers can rejoin for another game
     pub game_mode: GameMode,     // Manual or Automated
     pub auto_round_delay: u64,   // Time between automated rounds in seconds
@@ -159,12 +876,71 @@ pub struct Game {
     pub currency_mode: CurrencyMode, // SOL or RPSToken
     pub fee_collected: u64,      // Track fees collected
     pub token_mint: Option<Pubkey>, // Token mint address (if using RPSToken)
+    pub base_price_feed: Option<Pubkey>, // Pyth feed pricing the game currency
+    pub base_decimals: u8,       // Decimals of the game currency
+    pub stake_vaults: Vec<StakeVault>, // Extra mints accepted for entry
+    pub phase_nonce: u64,        // Bumped on every phase or round change
+    pub claimed_bitmap: u8,      // Bit per seat index, set once that winner is paid
+    pub pot_snapshot: u64,       // game_pot when the game finished
+    pub tournament: Option<Pubkey>, // Tournament this game decides a match of
+    pub tournament_match: u8,    // Bracket match index within that tournament
+    pub phase_deadline: u64,     // When the current phase can be timed out; 0 once finished
+    pub allow_public_bots: bool, // Anyone may add bots, not just the host
+    pub commitment_scheme: CommitmentScheme, // Hash players commit with
+    pub team_lineup: Vec<Pubkey>, // Reserved seats of a tournament match game, side A then side B; empty otherwise
+    pub phase_started_at: u64,   // When the current phase began; 0 for games older than the field
+    pub participation_bitmap: u8, // Bit per seat index, set once that player's finish is recorded
+    pub flagged: bool,           // Settlement heuristics suspect collusion; cleared by the operator
+    pub flag_reasons: u8,        // FLAG_* bits found at the last finish
+    pub seat_watch: Vec<SeatWatch>, // Collusion tallies of the game in progress
+    pub bot_funders: Vec<Pubkey>, // Wallets that paid for bot seats
+    pub pending_claims: Vec<PendingClaim>, // Initiated large payouts of the current finish
+    pub escrows: Vec<EntryEscrow>, // SOL entries refundable until play starts
+    pub pot_subsidy: u64,        // Lamports the treasury added to guarantee the pot
+    pub layout_version: u8,      // GAME_LAYOUT_VERSION the game was created (or upgraded) with
+    pub player_count_drawn: bool, // player_count is final; see `draw_player_count`
+    pub reveal_order: Vec<u8>,   // Seat indices in the order the last reveal phase is staged
+    pub hidden_fee: Option<HiddenFee>, // Sealed entry fee of a mystery-stake game, until revealed
+    pub theme_id: u16,           // Client skin (holiday event, sponsor) to render with; 0 = default
+    pub fee_bps: Option<u16>,    // Platform fee rate, fixed at creation; None = simulate::DEFAULT_FEE_BPS
+    pub rule_set: RuleSet,       // Hands the game accepts
+    pub winners: u8,             // Bit per seat holding the top score at finish; 0 until then
+    pub cancelled: bool,         // Finished by a timeout before it could be played; seats take RefundEntry
+    pub join_timeout: u64,       // Lobby countdown in seconds; 0 = required_timeout
+    pub commit_timeout: u64,     // Commit phase countdown in seconds; 0 = required_timeout
+    pub reveal_timeout: u64,     // Reveal phase countdown in seconds; 0 = required_timeout
+    pub crank_reward_lamports: u64, // Paid to whoever resolves a timeout; 0 = none
+    pub crank_bounty: u64,       // Host-funded lamports left for crank rewards, held in the pot vault
+    pub slash_non_revealers: bool, // Seats that commit but never reveal forfeit their entry to revealers
+    pub slashed: Vec<Pubkey>,    // Players slashed so far; they cannot win while anyone revealed
+    pub bot_config: Vec<BotSeat>, // Seats filled by AddBotPlayers; BotAct plays their hands
+    pub settlement: Settlement,  // Winners' shares, fixed at finish; empty before then or once cancelled
 }
 
 // Define instruction types
 #[derive(BorshSerialize, BorshDeserialize, Debug)]
 pub enum RPSInstruction {
-    // Initialize a new game
+    // Initialize a new game. Accounts: host, game (new keypair), system
+    // program, the treasury PDA (receives the platform fee of a SOL entry),
+    // the game's pot vault PDA (`pot_vault_address`; created for SOL games,
+    // which keep their pot there), then the token program and mint for RPSToken games (plus the host's
+    // token account and the game vault when the entry is paid), then the
+    // config PDA, whose fee rate the game keeps and whose adaptive default
+    // it inherits when `timeout_seconds` is 0, and the host's self-exclusion
+    // PDA (see `SelfExclude`).
+    // `join_timeout`, `commit_timeout` and `reveal_timeout` give the lobby,
+    // commit and reveal phases their own countdowns; each left at 0 uses
+    // `timeout_seconds`. A SOL game can pay `crank_reward_lamports`, at most
+    // `MAX_CRANK_REWARD_BPS` of the entry fee, to each caller of
+    // ResolveTimeout, first out of the `crank_bounty` the host deposits into
+    // the pot vault here, then out of the pot. With
+    // `slash_non_revealers`, a seat timed out in a reveal phase forfeits its
+    // entry: it can no longer win, so the pot goes to seats that revealed.
+    // RPSToken entries are paid whole into the game vault, the associated
+    // token account of the game authority PDA (`game_vault_address`); the
+    // platform fee stays there until CollectFees. The vault is not created
+    // here: send CreateGameTokenVault first, in the same transaction or an
+    // earlier one.
     InitializeGame {
         min_players: u8,
         max_players: u8,
@@ -177,51 +953,109 @@ pub enum RPSInstruction {
         auto_round_delay: u64,  // Only used if game_mode = Automated
         max_auto_rounds: u64,   // Only used if game_mode = Automated
         token_mint: Option<Pubkey>, // Token mint address (if using RPSToken)
+        allow_public_bots: bool, // Let anyone, not just the host, add bots
+        commitment_scheme: u8,  // 0 = HMAC-SHA512, 1 = SHA-256
+        theme_id: u16,          // Client skin to render the game with; 0 = default
+        rule_set: u8,           // 0 = rock-paper-scissors, 1 = with lizard and Spock
+        join_timeout: u64,      // 0 = timeout_seconds
+        commit_timeout: u64,    // 0 = timeout_seconds
+        reveal_timeout: u64,    // 0 = timeout_seconds
+        crank_reward_lamports: u64, // Per resolved timeout; SOL games only
+        crank_bounty: u64,      // Host's deposit toward crank rewards
+        slash_non_revealers: bool, // Forfeit the entries of seats that never reveal
     },
 
-    // Join an existing game
+    // Join an existing game. Accounts: player, game, system program, the
+    // joiner's ban list chunk, the config PDA, the treasury PDA (receives
+    // the platform fee of SOL entries), the pot vault PDA, the joiner's
+    // limits PDA (writable; see `SetLimits`) and self-exclusion PDA (see
+    // `SelfExclude`), the compliance credential token
+    // account and mint (paid entries under compliance mode only), then the
+    // token program, player token account and game vault (RPSToken only).
+    // A join that brings a range lobby to its minimum also needs the
+    // SlotHashes sysvar, listed last, to draw the seat count; the same goes
+    // for every instruction that seats players.
     JoinGame,
 
-    // Submit a hashed choice (commit phase)
+    // Submit a hashed choice (commit phase). Only the commitment goes
+    // on-chain; the salt stays with the player until the reveal. Accounts:
+    // player, game, config PDA (for the kill switch; the same goes for every
+    // commit and reveal), then optionally the stats PDA to fold this commit's
+    // timing into it; the same optional trailing account applies to every
+    // commit and reveal. The commit that closes the phase draws the round's
+    // reveal order, mixing in the SlotHashes sysvar when it is listed last.
     CommitChoice {
         committed_choice: [u8; 64], // Upgraded to SHA512 hash size
-        salt: [u8; 32],             // Store salt for later verification
     },
 
-    // Reveal your choice
+    // Reveal your choice with the salt it was committed under
     RevealChoice {
         choice: Choice,
+        salt: [u8; 32],
     },
 
-    // Force resolve the game if timeout occurred
-    ResolveTimeout,
+    // Force resolve the game if timeout occurred. `phase_nonce` must match
+    // the game's current phase so a stale or replayed call is rejected.
+    // Closing a commit phase draws the reveal order as CommitChoice does.
+    // Accounts: caller (writable, receives the game's crank reward), game,
+    // the pot vault PDA (read only when the game pays a crank reward), then
+    // optionally the SlotHashes sysvar.
+    ResolveTimeout {
+        phase_nonce: u64,
+    },
 
-    // Claim winnings after game finishes
+    // Claim winnings after game finishes. A winning bot seat's share is
+    // claimed by the wallet that paid for it, one seat per call. Accounts:
+    // winner, game, system
+    // program, the config PDA, the pot vault PDA, the instructions sysvar
+    // (read when the payout guard is on), then for RPSToken games the token
+    // program, winner token account, game vault and game authority PDA, then
+    // the stake vault accounts.
     ClaimWinnings,
 
-    // Rejoin game as a loser (if enabled)
+    // Rejoin game as a loser (if enabled). Accounts: player, game, system
+    // program, the treasury PDA, the pot vault PDA, then for RPSToken games the token program,
+    // player token account and game vault.
     RejoinGame,
 
-    // Start a new game round with same players
+    // Start a new game round with same players, once the finished game has
+    // paid every winner (or refunded every seat, if it was cancelled)
     StartNewGameRound,
 
-    // For auto-play, trigger the next round
+    // For auto-play, trigger the next round; paid out first as above
     AutoPlayNextRound,
 
-    // For auto-play, add bot players
+    // For auto-play, add bot players. In paid games the initiator pays
+    // `entry_fee * count` up front: SOL pot shares go to the pot vault and
+    // fees to the treasury; token entries go whole into the game's token
+    // account. Only the host may add bots unless the game allows public bots.
+    // `strategy` is how BotAct plays them: 0 random, 1 countering the last
+    // round's most played hand, 2 weighted towards rock. The initiator is
+    // paid the bots' winnings and refunds (see `Game::payee`).
+    //   accounts: initiator (signer), game, system program, treasury,
+    //             pot vault, config,
+    //             [token program, initiator token account, game token account],
+    //             [SlotHashes sysvar]
     AddBotPlayers {
         count: u8,
+        strategy: u8,
     },
     
-    // Collect fees from the game account
+    // Collect fees from the game. Once the config names a fee collector
+    // only it may collect, at any time; until then anyone may, once the game
+    // is finished. Accounts: fee collector, game, system program, the pot
+    // vault PDA, the config PDA, the instructions sysvar (read when the
+    // payout guard is on), then for RPSToken games the token program,
+    // collector token account, game vault and game authority PDA.
     CollectFees,
 
     /* ─────────────────────────────  NEW  ─────────────────────────────
-     * TOURNAMENT INSTRUCTIONS (step-1 foundation)
+     * TOURNAMENT INSTRUCTIONS
      *  - CreateTournament : host creates a lobby, deposits prize-seed
      *  - JoinTournament   : player pays entry fee & registers
-     *  Further instructions (Start / Advance / Finish) will be
-     *  introduced in follow-up patches.
+     *  - Bracket play, administration and settlement instructions
+     *    are declared after JoinGameWithMint to keep discriminants
+     *    stable.
      * ──────────────────────────────────────────────────────────────── */
 
     /// Create a new tournament lobby.
@@ -229,13 +1063,16 @@ pub enum RPSInstruction {
     ///   0. [signer]   host
     ///   1. [writable] tournament PDA (created & funded by host)
     ///   2. []         system program
-    ///   3. [optional] fee-collector       (SOL mode)
+    ///   3. []         config PDA (fixes the tournament's fee rate)
     ///   4. [...]      token accounts…     (token mode, to come)
     CreateTournament {
         max_players: u8,
         entry_fee: u64,
         currency_mode: u8, // CurrencyMode discriminator
         token_mint: Option<Pubkey>,
+        qualifier_of: Option<Pubkey>, // Finished tournament entrants must have placed in
+        qualifier_top_n: u8,          // Placings in the qualifier that earn entry
+        team_entries: bool,           // Entered by clans via JoinTournamentAsClan
     },
 
     /// Join an existing tournament.
@@ -243,473 +1080,9207 @@ pub enum RPSInstruction {
     ///   0. [signer]   player
     ///   1. [writable] tournament PDA
     ///   2. []         system program
-    ///   3. [optional] fee-collector
+    ///   3. []         ban list chunk for the player
+    ///   4. []         self-exclusion PDA for the player
+    ///   5. []         config PDA
+    ///   6. []         credential token account   (paid entry, compliance mode)
+    ///   7. []         credential mint            (paid entry, compliance mode)
+    ///   8. []         qualifier tournament (when the tournament has one)
     JoinTournament,
-}
 
-// Program entrypoint
-entrypoint!(process_instruction);
+    /// Join a game paying the entry fee in USDC. The USDC is swapped through
+    /// a Jupiter route into the game currency before the regular join runs.
+    ///   accounts:
+    ///   0. [signer]   player
+    ///   1. [writable] game account
+    ///   2. []         system program
+    ///   3. []         Jupiter aggregator program
+    ///   4. []         SPL token program
+    ///   5. [writable] player USDC token account
+    ///   6. [writable] player destination token account (game mint or wSOL)
+    ///   7. [...]      Jupiter route accounts (`route_accounts_len` of them)
+    ///   8. [...]      remaining JoinGame accounts (ban list chunk, config,
+    ///      treasury, pot vault, limits, self-exclusion, credential, token
+    ///      accounts)
+    JoinGameWithSwap {
+        usdc_amount_in: u64,   // USDC amount quoted for the route
+        max_slippage_bps: u16, // Max extra USDC the player accepts to spend
+        route_accounts_len: u8,
+        route_data: Vec<u8>,   // Serialized Jupiter route instruction data
+    },
 
-// Process instruction logic
-pub fn process_instruction(
-    program_id: &Pubkey,
-    accounts: &[AccountInfo],
-    instruction_data: &[u8],
-) -> ProgramResult {
-    let instruction = RPSInstruction::try_from_slice(instruction_data)
-        .map_err(|_| ProgramError::InvalidInstructionData)?;
+    /// Approve extra mints players may stake instead of the game currency.
+    /// Host only, before anyone else has joined.
+    ///   accounts:
+    ///   0. [signer]   host
+    ///   1. [writable] game account
+    ///   2. []         Pyth price account for the game currency
+    ///   3. [...]      per mint: mint account, vault token account (owned by game PDA)
+    ConfigureStakeMints {
+        base_decimals: u8,
+        price_feeds: Vec<Pubkey>, // Pyth price account per mint, in account order
+    },
 
-    match instruction {
-        RPSInstruction::InitializeGame {
-            min_players,
-            max_players,
-            total_rounds,
-            entry_fee,
-            timeout_seconds,
-            losers_can_rejoin,
-            game_mode,
-            currency_mode,
-            auto_round_delay,
-            max_auto_rounds,
-            token_mint,
-        } => {
-            process_initialize_game(
-                program_id,
-                accounts,
-                min_players,
-                max_players,
-                total_rounds,
-                entry_fee,
-                timeout_seconds,
-                losers_can_rejoin,
-                game_mode,
-                currency_mode,
-                auto_round_delay,
-                max_auto_rounds,
-                token_mint,
-            )
-        },
-        RPSInstruction::JoinGame => {
-            process_join_game(program_id, accounts)
-        },
-        RPSInstruction::CommitChoice { committed_choice, salt } => {
-            process_commit_choice(program_id, accounts, committed_choice, salt)
-        },
-        RPSInstruction::RevealChoice { choice } => {
-            process_reveal_choice(program_id, accounts, choice)
-        },
-        RPSInstruction::ResolveTimeout => {
-            process_resolve_timeout(program_id, accounts)
-        },
-        RPSInstruction::ClaimWinnings => {
-            process_claim_winnings(program_id, accounts)
-        },
-        RPSInstruction::RejoinGame => {
-            process_rejoin_game(program_id, accounts)
-        },
-        RPSInstruction::StartNewGameRound => {
-            process_start_new_game_round(program_id, accounts)
+    /// Join a game staking one of the approved mints at equivalent value.
+    ///   accounts:
+    ///   0. [signer]   player
+    ///   1. [writable] game account
+    ///   2. []         SPL token program
+    ///   3. []         Pyth price account for the game currency
+    ///   4. []         Pyth price account for the staked mint
+    ///   5. [writable] player token account for the staked mint
+    ///   6. [writable] game vault token account for the staked mint
+    ///   7. []         ban list chunk for the player
+    ///   8. []         self-exclusion PDA for the player
+    ///   9. []         config PDA
+    ///  10. [writable] player limits PDA
+    ///  11. []         credential token account   (compliance mode)
+    ///  12. []         credential mint            (compliance mode)
+    JoinGameWithMint {
+        mint_index: u8,
+    },
+
+    /// Close tournament registration and seed the single-elimination
+    /// bracket. Host or co-admin.
+    ///   accounts:
+    ///   0. [signer]   host or co-admin
+    ///   1. [writable] tournament account
+    StartTournament,
+
+    /// Record a bracket match result from the finished game linked to the
+    /// match, and promote the winner. Anyone may report.
+    ///   accounts:
+    ///   0. [signer]   caller (anyone)
+    ///   1. [writable] tournament account
+    ///   2. []         match game account
+    ReportMatchResult {
+        match_index: u8,
+    },
+
+    /// Settle a tournament once its final is decided: record the champion
+    /// and standings, and sweep the platform fee to the treasury. Placed
+    /// wallets listed after the treasury, in standings order, are paid
+    /// their prizes on the spot; the rest claim with `ClaimTournamentPrize`.
+    ///   accounts:
+    ///   0. [signer]   caller (anyone)
+    ///   1. [writable] tournament account
+    ///   2. [writable] treasury PDA
+    ///   3. [...]      placed wallets, champion first (writable, optional)
+    FinishTournament,
+
+    /// Pay a placed player their share of a finished tournament's prize pool.
+    ///   accounts:
+    ///   0. [signer]   player
+    ///   1. [writable] tournament account
+    ClaimTournamentPrize,
+
+    /// Link a freshly created game to a bracket match so its result can be
+    /// reported. The game host must be one of the match's players.
+    ///   accounts:
+    ///   0. [signer]   game host
+    ///   1. [writable] tournament account
+    ///   2. [writable] match game account (waiting for players)
+    OpenTournamentMatch {
+        match_index: u8,
+    },
+
+    /// Remove a player from a running tournament. Their current match, and
+    /// any they would later reach, goes to the opponent by walkover.
+    /// Host or co-admin.
+    ///   accounts:
+    ///   0. [signer]   host or co-admin
+    ///   1. [writable] tournament account
+    DisqualifyPlayer {
+        player: Pubkey,
+    },
+
+    /// Replace the tournament's co-admins, who may start the tournament and
+    /// disqualify players on the host's behalf. Host only.
+    ///   accounts:
+    ///   0. [signer]   host
+    ///   1. [writable] tournament account
+    SetTournamentAdmins {
+        admins: Vec<Pubkey>,
+    },
+
+    /// Create the program config PDA. The signer becomes the operator
+    /// authority, so run it as part of deployment.
+    ///   accounts:
+    ///   0. [signer, writable] authority (pays for the account)
+    ///   1. [writable] config PDA
+    ///   2. []         system program
+    InitializeConfig,
+
+    /// Add a wallet to the ban list, creating its chunk on first use.
+    /// Config authority only.
+    ///   accounts:
+    ///   0. [signer, writable] authority (pays for a new chunk)
+    ///   1. []         config PDA
+    ///   2. [writable] ban list chunk for the wallet
+    ///   3. []         system program
+    BanPlayer {
+        wallet: Pubkey,
+    },
+
+    /// Remove a wallet from the ban list. Config authority only.
+    ///   accounts:
+    ///   0. [signer]   authority
+    ///   1. []         config PDA
+    ///   2. [writable] ban list chunk for the wallet
+    UnbanPlayer {
+        wallet: Pubkey,
+    },
+
+    /// Turn compliance mode on or off and set the approved credential
+    /// attestors. Config authority only.
+    ///   accounts:
+    ///   0. [signer]   authority
+    ///   1. [writable] config PDA
+    SetCompliance {
+        enabled: bool,
+        attestors: Vec<Pubkey>,
+    },
+
+    /// View: set the return data to the borsh-encoded `Claimable` for
+    /// `player`, all zero when they cannot claim right now. Meant for
+    /// simulation; changes nothing.
+    ///   accounts:
+    ///   0. []         game account
+    GetClaimable {
+        player: Pubkey,
+    },
+
+    /// Call out a specific opponent to a one-throw match, escrowing `stake`
+    /// in a new challenge account. Lapses if not accepted within a day.
+    ///   accounts:
+    ///   0. [signer, writable] challenger
+    ///   1. [signer, writable] challenge account (new keypair)
+    ///   2. []         system program
+    ///   3. []         ban list chunk for the challenger
+    ///   4. []         self-exclusion PDA for the challenger
+    ///   5. []         config PDA
+    ///   6. []         credential token account   (compliance mode)
+    ///   7. []         credential mint            (compliance mode)
+    CreateChallenge {
+        opponent: Pubkey,
+        stake: u64,
+    },
+
+    /// Accept a challenge, matching its stake. Named opponent only.
+    ///   accounts:
+    ///   0. [signer, writable] opponent
+    ///   1. [writable] challenge account
+    ///   2. []         system program
+    ///   3. []         ban list chunk for the opponent
+    ///   4. []         self-exclusion PDA for the opponent
+    ///   5. []         config PDA
+    ///   6. []         credential token account   (compliance mode)
+    ///   7. []         credential mint            (compliance mode)
+    AcceptChallenge,
+
+    /// Commit to a choice in an accepted challenge.
+    ///   accounts:
+    ///   0. [signer]   challenger or opponent
+    ///   1. [writable] challenge account
+    CommitChallengeChoice {
+        commitment: [u8; 64],
+    },
+
+    /// Reveal a committed choice. The second reveal settles the challenge.
+    /// A tie closes its account; a decided one stays open for the loser's
+    /// `OfferDoubleOrNothing` until `ExpireChallenge` closes it.
+    ///   accounts:
+    ///   0. [signer]   challenger or opponent
+    ///   1. [writable] challenge account
+    ///   2. [writable] challenger (receives the account rent)
+    ///   3. [writable] opponent
+    RevealChallengeChoice {
+        choice: Choice,
+        salt: [u8; 32],
+    },
+
+    /// Settle a challenge past its deadline: an unaccepted challenge is
+    /// refunded, and in an accepted one a side that revealed takes the pot
+    /// from one that did not. A settled challenge whose double-or-nothing
+    /// window has passed is closed, refunding any offer. Anyone may call.
+    ///   accounts:
+    ///   0. [signer]   caller (anyone)
+    ///   1. [writable] challenge account
+    ///   2. [writable] challenger (receives the account rent)
+    ///   3. [writable] opponent
+    ExpireChallenge,
+
+    /// Replace the caller's commitment. Only allowed while nobody else in
+    /// the game has committed this round; `CommitChoice` never overwrites.
+    ///   accounts:
+    ///   0. [signer]   player
+    ///   1. [writable] game account
+    ///   2. []         config PDA
+    RecommitChoice {
+        committed_choice: [u8; 64],
+    },
+
+    /// Write the immutable `GameResult` snapshot of a finished game, so its
+    /// outcome survives the game account being closed. Anyone may record,
+    /// once per game.
+    ///   accounts:
+    ///   0. [signer, writable] payer
+    ///   1. []         game account (finished)
+    ///   2. [writable] game result PDA
+    ///   3. []         system program
+    RecordGameResult,
+
+    /// View: recompute the lamports the account holding the pot should have
+    /// from the game state (rent + pot + uncollected fees) and set the
+    /// return data to the borsh-encoded `GameAudit`. That is the pot vault
+    /// for SOL games, and the game account otherwise. Anyone may call;
+    /// changes nothing.
+    ///   accounts:
+    ///   0. []         game account
+    ///   1. []         pot vault PDA
+    AuditGame,
+
+    /// Create a bot-only tournament that plays itself out through
+    /// `CrankAutoTournament`: `bots` seeded bot entrants, free entry, no
+    /// prizes. Each match is best of `rounds_per_match` decisive throws.
+    ///   accounts:
+    ///   0. [signer]   host
+    ///   1. [signer, writable] tournament account (new keypair)
+    ///   2. []         system program
+    CreateAutoTournament {
+        bots: u8,
+        rounds_per_match: u8,
+    },
+
+    /// Play the next ready match of an auto tournament and advance its
+    /// winner; the crank that decides the final also settles the standings.
+    /// Anyone may crank.
+    ///   accounts:
+    ///   0. [signer]   caller (anyone)
+    ///   1. [writable] tournament account
+    CrankAutoTournament,
+
+    /// Reveal with a signed, human-readable message instead of a raw
+    /// salt, for wallets that can sign text but not arbitrary hashes. The
+    /// instruction just before this one must be an ed25519 verify of the
+    /// player's signature over `reveal_message(game, round, choice)`; the
+    /// commitment is checked with `signed_reveal_salt(signature)` as salt.
+    ///   accounts:
+    ///   0. [signer]   player
+    ///   1. [writable] game account
+    ///   2. []         config PDA
+    ///   3. []         instructions sysvar
+    RevealChoiceSigned {
+        choice: Choice,
+    },
+
+    /// Register a clan under a unique name. `split_bps` is each member's
+    /// share of the clan's team prizes in basis points, in roster order,
+    /// and must add up to 10_000. The leader must be on the roster.
+    ///   accounts:
+    ///   0. [signer, writable] leader (pays rent)
+    ///   1. [writable] clan PDA (`clan_address(name)`)
+    ///   2. []         system program
+    CreateClan {
+        name: String,
+        members: Vec<Pubkey>,
+        split_bps: Vec<u16>,
+    },
+
+    /// Enter a clan into a team tournament; the leader pays the entry fee.
+    ///   accounts:
+    ///   0. [signer, writable] clan leader
+    ///   1. [writable] tournament account
+    ///   2. []         clan PDA
+    ///   3. []         system program
+    JoinTournamentAsClan,
+
+    /// Spawn the team game that decides a team tournament match. The first
+    /// two members of each clan's roster are its lineup, and only they can
+    /// take the four seats. Either clan leader or a tournament admin may
+    /// open it.
+    ///   accounts:
+    ///   0. [signer, writable] caller (pays rent)
+    ///   1. [writable] tournament account
+    ///   2. [signer, writable] match game account (new keypair)
+    ///   3. []         clan PDA of the match's side A
+    ///   4. []         clan PDA of the match's side B
+    ///   5. []         system program
+    OpenTeamMatch {
+        match_index: u8,
+        total_rounds: u8,
+        timeout_seconds: u64,
+    },
+
+    /// Pay a placed clan's share of a finished team tournament's prize pool
+    /// out to its members by the clan's split; rounding dust goes to the
+    /// leader. Anyone may call.
+    ///   accounts:
+    ///   0. [signer]   caller (anyone)
+    ///   1. [writable] tournament account
+    ///   2. []         clan PDA
+    ///   3. [...]      member wallets, in roster order (writable)
+    ClaimTeamPrize,
+
+    /// Create the `GlobalStats` PDA. Anyone may pay for it, once.
+    ///   accounts:
+    ///   0. [signer, writable] payer
+    ///   1. [writable] stats PDA
+    ///   2. []         system program
+    InitializeStats,
+
+    /// Configure adaptive default timeouts: the default becomes
+    /// `multiplier_pct`% of the average commit/reveal latency since the
+    /// last refresh, clamped to `[min_secs, max_secs]`. Until the first
+    /// refresh the default is `max_secs`. A multiplier of 0 turns the
+    /// default off. Config authority only.
+    ///   accounts:
+    ///   0. [signer]   authority
+    ///   1. [writable] config PDA
+    SetTimeoutPolicy {
+        multiplier_pct: u16,
+        min_secs: u64,
+        max_secs: u64,
+    },
+
+    /// Recompute the default timeout from the commits and reveals
+    /// `GlobalStats` recorded since the last refresh. Anyone may crank once
+    /// enough new actions are in.
+    ///   accounts:
+    ///   0. [signer]   caller (anyone)
+    ///   1. [writable] config PDA
+    ///   2. []         stats PDA
+    RefreshDefaultTimeout,
+
+    /// Credit the signing player with one finished game in the current
+    /// epoch's `Participation` PDA, creating it on first use. Each seat
+    /// counts once per finish of the game.
+    ///   accounts:
+    ///   0. [signer, writable] player (pays for a new PDA)
+    ///   1. [writable] game account
+    ///   2. [writable] participation PDA for the current epoch
+    ///   3. []         system program
+    RecordParticipation,
+
+    /// Choose whether games the collusion heuristics flag stop paying out
+    /// until reviewed. Off by default. Config authority only.
+    ///   accounts:
+    ///   0. [signer]   authority
+    ///   1. [writable] config PDA
+    SetCollusionPolicy {
+        hold_flagged_payouts: bool,
+    },
+
+    /// Clear a game's collusion flag after review, releasing held payouts.
+    /// The reasons stay on the game for the record. Config authority only.
+    ///   accounts:
+    ///   0. [signer]   authority
+    ///   1. []         config PDA
+    ///   2. [writable] game account
+    ClearGameFlag,
+
+    /// Make pots above `large_payout_threshold` pay out in two steps, with
+    /// `claim_delay_secs` between them. A threshold of 0 turns it off.
+    /// Config authority only, until `InitializeGovernance`.
+    ///   accounts:
+    ///   0. [signer]   authority
+    ///   1. [writable] config PDA
+    SetClaimPolicy {
+        large_payout_threshold: u64,
+        claim_delay_secs: u64,
+    },
+
+    /// Start claiming a large pot: records the intent and starts the delay
+    /// during which the operator can flag the game and stop the payout.
+    ///   accounts:
+    ///   0. [signer]   winner
+    ///   1. [writable] game account
+    ///   2. []         config PDA
+    InitiateClaim,
+
+    /// Pay out an initiated claim once its delay has passed. Accounts as
+    /// for `ClaimWinnings`.
+    FinalizeClaim,
+
+    /// Point a lobby name at a game or recurring lobby the registrant hosts. Names are 3 to 32
+    /// characters of `a-z`, `0-9` and `-`; the first registrant owns the
+    /// name and may re-point it later.
+    ///   accounts:
+    ///   0. [signer, writable] registrant (pays for a new name)
+    ///   1. [writable] lobby name PDA
+    ///   2. []         target game or recurring lobby
+    ///   3. []         system program
+    RegisterLobbyName {
+        name: String,
+    },
+
+    /// Create a standing lobby that spawns a fresh SOL game from `template`
+    /// every `interval_secs`, starting at `first_spawn_at`.
+    ///   accounts:
+    ///   0. [signer, writable] host
+    ///   1. [signer, writable] recurring lobby account (new keypair)
+    ///   2. []         system program
+    CreateRecurringLobby {
+        template: LobbyTemplate,
+        interval_secs: u64,
+        first_spawn_at: u64,
+    },
+
+    /// Spawn the next game of a recurring lobby once it is due. Anyone may
+    /// crank; the caller pays the rent of the new game and its pot vault.
+    ///   accounts:
+    ///   0. [signer, writable] caller
+    ///   1. [writable] recurring lobby account
+    ///   2. [signer, writable] new game account (new keypair)
+    ///   3. []         system program
+    ///   4. [writable] pot vault PDA of the new game
+    ///   5. []         config PDA (fixes the new game's fee rate)
+    SpawnScheduledLobby,
+
+    /// Queue for a seat in a full SOL lobby that has not started yet. The
+    /// full entry fee is escrowed in the game's waitlist PDA.
+    ///   accounts:
+    ///   0. [signer, writable] player
+    ///   1. []         game account
+    ///   2. [writable] waitlist PDA
+    ///   3. []         system program
+    ///   4. []         ban list chunk PDA for the player
+    ///   5. []         self-exclusion PDA for the player
+    ///   6. []         config PDA (compliance settings)
+    ///   7. [...]      credential accounts when the config requires them
+    JoinWaitlist,
+
+    /// Give up a seat before play starts. The pot share of the entry is
+    /// refunded; the first waitlisted player takes the seat.
+    ///   accounts:
+    ///   0. [signer, writable] player
+    ///   1. [writable] game account
+    ///   2. [writable] waitlist PDA
+    ///   3. [writable] treasury PDA
+    ///   4. [writable] pot vault PDA
+    LeaveGame,
+
+    /// Host removes a seated player before play starts, with the same
+    /// refund and promotion as `LeaveGame`.
+    ///   accounts:
+    ///   0. [signer]   host
+    ///   1. [writable] game account
+    ///   2. [writable] waitlist PDA
+    ///   3. [writable] treasury PDA
+    ///   4. [writable] pot vault PDA
+    ///   5. [writable] kicked player's wallet
+    KickPlayer,
+
+    /// Return every escrowed waitlist entry once the game has started.
+    /// Anyone may crank.
+    ///   accounts:
+    ///   0. [signer]   caller
+    ///   1. []         game account
+    ///   2. [writable] waitlist PDA
+    ///   3. [...]      waitlisted wallets, in queue order (writable)
+    RefundWaitlist,
+
+    /// Return a seat's escrowed SOL entry from a lobby that is still
+    /// waiting for players `ENTRY_REFUND_SECS` after the entry was paid.
+    /// Anyone may crank; the seat is given up.
+    ///   accounts:
+    ///   0. [signer]   caller
+    ///   1. [writable] game account
+    ///   2. [writable] escrowed player's wallet
+    ///   3. [writable] pot vault PDA
+    RefundExpiredEscrow,
+
+    /// Guarantee SOL games with an entry fee of at least
+    /// `subsidy_entry_floor` a pot of `guaranteed_min_pot`, topped up from
+    /// the treasury. A minimum of 0 turns it off. Config authority only,
+    /// until `InitializeGovernance`.
+    ///   accounts:
+    ///   0. [signer]   authority
+    ///   1. [writable] config PDA
+    SetPotGuarantee {
+        guaranteed_min_pot: u64,
+        subsidy_entry_floor: u64,
+    },
+
+    /// Top a started game's pot up to the guaranteed minimum from the
+    /// treasury. Once per game; anyone may crank.
+    ///   accounts:
+    ///   0. [signer]   caller
+    ///   1. [writable] game account
+    ///   2. []         config PDA
+    ///   3. [writable] treasury PDA
+    ///   4. []         system program
+    ///   5. [writable] pot vault PDA
+    ApplyPotSubsidy,
+
+    /// View: set the return data to the borsh-encoded `FeeQuote` for an
+    /// entry of `entry_fee` in `currency_mode` (0 = SOL, 1 = RPSToken), at
+    /// the rate a game created now is charged. Meant for simulation;
+    /// changes nothing.
+    ///   accounts:
+    ///   0. []         config PDA
+    GetFeeQuote {
+        entry_fee: u64,
+        currency_mode: u8,
+    },
+
+    /// Incident kill switch: bit n of `paused_instructions` pauses the
+    /// instruction with discriminant n, and `token_entries_paused` pauses
+    /// entries into RPSToken games. Only instructions that always carry the
+    /// config PDA can be paused (`PAUSABLE_INSTRUCTIONS`); setting any other
+    /// bit fails. This instruction itself cannot be paused. Config
+    /// authority only.
+    ///   accounts:
+    ///   0. [signer]   authority
+    ///   1. [writable] config PDA
+    SetPausedInstructions {
+        paused_instructions: u128,
+        token_entries_paused: bool,
+    },
+
+    /// Settle and close a game created under an older layout, which the
+    /// current program can read but may not have room to write. Games from
+    /// before the pot vault are settled this way too, as their SOL pot is
+    /// still held in the game account. A finished game pays its unclaimed
+    /// winnings; one still in progress refunds the pot to its seats in
+    /// equal shares. The rent goes back to the host. SOL games only. Anyone
+    /// may call.
+    ///   accounts:
+    ///   0. [signer]   caller
+    ///   1. [writable] game account (legacy layout)
+    ///   2. [writable] host wallet
+    ///   3. [...]      seated wallets, in seat order (writable)
+    FinalizeLegacyGame,
+
+    /// Offer the winner of a decided challenge double or nothing, escrowing
+    /// their takings (twice the stake) in the settled challenge account.
+    /// Loser only, within ten minutes of the result. `ExpireChallenge`
+    /// refunds the offer if it is not accepted within ten minutes.
+    ///   accounts:
+    ///   0. [signer, writable] loser
+    ///   1. [writable] settled challenge account
+    ///   2. []         system program
+    ///   3. []         ban list chunk for the loser
+    ///   4. []         self-exclusion PDA for the loser
+    ///   5. []         config PDA
+    ///   6. []         credential token account   (compliance mode)
+    ///   7. []         credential mint            (compliance mode)
+    OfferDoubleOrNothing,
+
+    /// Accept a double-or-nothing offer, matching it in a new challenge
+    /// that starts already accepted with the combined pot. The settled
+    /// challenge is closed. Winner only.
+    ///   accounts:
+    ///   0. [signer, writable] winner
+    ///   1. [writable] settled challenge account
+    ///   2. [signer, writable] rematch challenge account (new keypair)
+    ///   3. []         system program
+    ///   4. [writable] challenger of the settled challenge (receives its rent)
+    ///   5. []         ban list chunk for the winner
+    ///   6. []         self-exclusion PDA for the winner
+    ///   7. []         config PDA
+    ///   8. []         credential token account   (compliance mode)
+    ///   9. []         credential mint            (compliance mode)
+    AcceptDoubleOrNothing,
+
+    /// Turn a fresh SOL lobby into a mystery-stake game: the entry fee is
+    /// sealed as `commitment` (`hidden_fee_commitment(fee, salt)`) somewhere
+    /// between `min_fee` and the game's `entry_fee`, which becomes the cap
+    /// every seat escrows until `RevealEntryFee`. Host only, before anyone
+    /// else has joined.
+    ///   accounts:
+    ///   0. [signer]   host
+    ///   1. [writable] game account
+    SealEntryFee {
+        commitment: [u8; 32],
+        min_fee: u64,
+    },
+
+    /// Open a mystery-stake game's sealed entry fee once the lobby is full,
+    /// refunding every seat the difference to the cap (pot share from the
+    /// vault, platform fee from the treasury). Commits wait for it. The host
+    /// reveals `fee` and `salt`; once the commit deadline has passed anyone
+    /// may call, and the fee falls to the minimum.
+    ///   accounts:
+    ///   0. [signer]   caller
+    ///   1. [writable] game account
+    ///   2. [writable] pot vault PDA
+    ///   3. [writable] treasury PDA
+    ///   4. []         system program
+    ///   5. [...]      seated wallets, in seat order (writable)
+    RevealEntryFee {
+        fee: u64,
+        salt: [u8; 32],
+    },
+
+    /// Spawn the game that decides a solo tournament match: a free two-seat
+    /// game linked back to the bracket, with the seats reserved for the
+    /// match's two players. Either player or a tournament admin may open it;
+    /// `ReportMatchResult` reads the winner off it once it has finished.
+    ///   accounts:
+    ///   0. [signer, writable] caller (pays rent)
+    ///   1. [writable] tournament account
+    ///   2. [signer, writable] match game account (new keypair)
+    ///   3. []         system program
+    SpawnTournamentMatch {
+        match_index: u8,
+        total_rounds: u8,
+        timeout_seconds: u64,
+    },
+
+    /// Crank: fold recorded `GameResult`s from a past archive `epoch` into
+    /// that epoch's `ResultArchive` (created on first use) and close them,
+    /// their rent going to the cranker. Anyone may call once the epoch is
+    /// over; each result's game must already be closed.
+    ///   accounts:
+    ///   0. [signer, writable] cranker (pays for a new archive)
+    ///   1. [writable] result archive PDA for `epoch`
+    ///   2. []         system program
+    ///   3. [...]      per game finished in `epoch`: its result PDA
+    ///      (writable), then the closed game account
+    ArchiveGameResults {
+        epoch: u64,
+    },
+
+    /// Charge `fee` of `fee_mint` to the treasury for every `SetNickname`;
+    /// a fee of 0 makes nicknames free. Config authority only.
+    ///   accounts:
+    ///   0. [signer]   authority
+    ///   1. [writable] config PDA
+    SetNicknamePolicy {
+        fee: u64,
+        fee_mint: Option<Pubkey>,
+    },
+
+    /// Set the signer's display name, creating their `PlayerProfile` on
+    /// first use. Nicknames are 3 to 20 characters of `A-Z`, `a-z`, `0-9`,
+    /// `_` and `-`, unique regardless of case, and can change once per
+    /// `NICKNAME_COOLDOWN_SECS`; a change frees the old name.
+    ///   accounts:
+    ///   0. [signer, writable] player (pays for new accounts)
+    ///   1. [writable] profile PDA
+    ///   2. [writable] nickname PDA for `nickname`
+    ///   3. []         config PDA
+    ///   4. []         system program
+    ///   5. [writable] nickname PDA of the current nickname (when renaming)
+    ///
+    ///   then, when the config charges a fee:
+    ///   .  []         token program
+    ///   .  [writable] player's token account for the fee mint
+    ///   .  [writable] treasury's token account for the fee mint
+    SetNickname {
+        nickname: String,
+    },
+
+    /// View: set the return data to the borsh-encoded `SpectatorView` of a
+    /// game: phase, pot and per-seat committed/revealed flags and scores,
+    /// without any commitment or salt. Meant for simulation; changes nothing.
+    ///   accounts:
+    ///   0. []         game account
+    GetSpectatorView,
+
+    /// Close a finished game whose winnings are all paid out, returning the
+    /// rent of the game account and its pot vault to `recipient`. Anyone may
+    /// close it to the host; only the host may name another recipient. Its
+    /// result must be recorded first (see `RecordGameResult`), and a
+    /// tournament game can only close once its match has been reported.
+    ///   accounts:
+    ///   0. [signer]   caller
+    ///   1. [writable] game account
+    ///   2. [writable] pot vault PDA
+    ///   3. [writable] recipient
+    ///   4. []         game result PDA
+    ///   5. []         tournament account (tournament games only)
+    CloseGame,
+
+    /// Set the platform fee rate of games and tournaments created from now
+    /// on, at most `MAX_FEE_BPS`, and the wallet that alone may collect
+    /// held fees. `None` restores the default rate, or lets anyone collect
+    /// a finished game's fees. Config authority only, until
+    /// `InitializeGovernance`.
+    ///   accounts:
+    ///   0. [signer]   authority
+    ///   1. [writable] config PDA
+    SetFeePolicy {
+        fee_collector: Option<Pubkey>,
+        fee_bps: Option<u16>,
+    },
+
+    /// Turn the payout guard on or off. While on, `ClaimWinnings`,
+    /// `FinalizeClaim` and `CollectFees` fail unless they are called
+    /// directly by the transaction and every other instruction in it belongs
+    /// to this program or the compute budget program. Config authority only.
+    ///   accounts:
+    ///   0. [signer]   authority
+    ///   1. [writable] config PDA
+    SetPayoutGuard {
+        enabled: bool,
+    },
+
+    /// Hand the fee and payout settings over to stakers of `stake_mint`.
+    /// From then on `SetFeePolicy`, `SetClaimPolicy` and `SetPotGuarantee`
+    /// are refused and those settings change only by `ExecuteProposal`. A
+    /// proposal passes with at least `quorum` staked tokens voting and more
+    /// for than against, `voting_secs` after it was made. Config authority
+    /// only, once.
+    ///   accounts:
+    ///   0. [signer, writable] authority (pays for the governance PDA)
+    ///   1. [writable] config PDA
+    ///   2. [writable] governance PDA
+    ///   3. []         system program
+    InitializeGovernance {
+        stake_mint: Pubkey,
+        quorum: u64,
+        voting_secs: u64,
+    },
+
+    /// Stake `amount` of the stake mint, creating the staker's `VoterStake`
+    /// on first use. Votes weigh the whole stake.
+    ///   accounts:
+    ///   0. [signer, writable] staker
+    ///   1. [writable] voter stake PDA
+    ///   2. [writable] governance PDA
+    ///   3. []         system program
+    ///   4. []         token program
+    ///   5. [writable] staker's token account for the stake mint
+    ///   6. [writable] governance vault (`governance_vault_address`)
+    StakeGovernanceTokens {
+        amount: u64,
+    },
+
+    /// Withdraw `amount` of stake. A stake that voted stays locked until
+    /// voting on the proposal closes, so it cannot be moved and vote again.
+    ///   accounts:
+    ///   0. [signer]   staker
+    ///   1. [writable] voter stake PDA
+    ///   2. [writable] governance PDA
+    ///   3. []         token program
+    ///   4. [writable] staker's token account for the stake mint
+    ///   5. [writable] governance vault
+    UnstakeGovernanceTokens {
+        amount: u64,
+    },
+
+    /// Propose a fee or payout change; voting opens at once. Stakers only.
+    ///   accounts:
+    ///   0. [signer, writable] proposer (pays for the proposal)
+    ///   1. []         proposer's voter stake PDA
+    ///   2. [writable] governance PDA
+    ///   3. []         config PDA
+    ///   4. [writable] proposal PDA for the governance's next index
+    ///   5. []         system program
+    ProposeParameterChange {
+        change: ParameterChange,
+    },
+
+    /// Vote for or against an open proposal with the whole stake.
+    ///   accounts:
+    ///   0. [signer, writable] voter (pays for the vote record)
+    ///   1. [writable] voter stake PDA
+    ///   2. [writable] proposal PDA
+    ///   3. [writable] vote record PDA
+    ///   4. []         system program
+    CastVote {
+        approve: bool,
+    },
+
+    /// Crank: apply a proposal that passed once its voting has closed.
+    ///   accounts:
+    ///   0. [signer]   caller
+    ///   1. [writable] proposal PDA
+    ///   2. []         governance PDA
+    ///   3. [writable] config PDA
+    ExecuteProposal,
+
+    /// Set the signer's own limits, creating their `PlayerLimits` on first
+    /// use: at most `daily_limit` lamports of SOL entries per
+    /// `WAGER_WINDOW_SECS` (0 = none), and, when `cool_off_secs` is non-zero,
+    /// no joins at all for that long. Lower limits and longer cool-offs apply
+    /// at once; a higher or removed limit waits `LIMIT_RAISE_DELAY_SECS`, and
+    /// a cool-off can't be cut short. Pass the current limit to keep it.
+    ///   accounts:
+    ///   0. [signer, writable] player (pays for the limits account)
+    ///   1. [writable] limits PDA
+    ///   2. []         system program
+    SetLimits {
+        daily_limit: u64,
+        cool_off_secs: u64,
+    },
+
+    /// Exclude the signer from play until `until` (unix seconds): every
+    /// game, waitlist, tournament and challenge entry refuses them with
+    /// `SelfExcluded`. Irrevocable; sending it again can only extend it.
+    ///   accounts:
+    ///   0. [signer, writable] player (pays for the exclusion account)
+    ///   1. [writable] self-exclusion PDA
+    ///   2. []         system program
+    SelfExclude {
+        until: u64,
+    },
+
+    /// Create the cold treasury: `signers` propose and approve its actions,
+    /// `threshold` of them must approve each one, and none runs until
+    /// `timelock_secs` after it was proposed. Fees above `hot_cap` lamports
+    /// are swept into it from the treasury. Config authority only, once.
+    ///   accounts:
+    ///   0. [signer, writable] config authority (pays for the account)
+    ///   1. []         config PDA
+    ///   2. [writable] cold treasury PDA
+    ///   3. []         system program
+    InitializeColdTreasury {
+        signers: Vec<Pubkey>,
+        threshold: u8,
+        timelock_secs: u64,
+        hot_cap: u64,
+    },
+
+    /// Move everything the treasury holds above the cold treasury's
+    /// `hot_cap` into the cold treasury. Anyone can crank it.
+    ///   accounts:
+    ///   0. [signer]   caller
+    ///   1. [writable] treasury PDA
+    ///   2. [writable] cold treasury PDA
+    ///   3. []         system program
+    ///   4. [writable] insurance fund PDA (optional; takes its share first)
+    SweepTreasury,
+
+    /// Propose `action` on the cold treasury, counting as the proposer's
+    /// approval. Cold signers only.
+    ///   accounts:
+    ///   0. [signer, writable] cold signer (pays for the proposal)
+    ///   1. [writable] cold treasury PDA
+    ///   2. [writable] cold proposal PDA for the cold treasury's
+    ///      `proposal_count`
+    ///   3. []         system program
+    ProposeColdAction {
+        action: ColdAction,
+    },
+
+    /// Approve a pending cold proposal. Cold signers only.
+    ///   accounts:
+    ///   0. [signer]   cold signer
+    ///   1. []         cold treasury PDA
+    ///   2. [writable] cold proposal PDA
+    ApproveColdAction,
+
+    /// Carry out a cold proposal once its timelock has passed with enough
+    /// approvals. Anyone can crank it.
+    ///   accounts:
+    ///   0. [signer]   caller
+    ///   1. [writable] cold treasury PDA
+    ///   2. [writable] cold proposal PDA
+    ///   3. [writable] recipient (`Withdraw` only)
+    ExecuteColdAction,
+
+    /// Pay every unpaid winner of a finished SOL game its share from the
+    /// pot vault in one call, as `ClaimWinnings` would for each of them, so
+    /// automated games settle without the winners signing anything. Anyone
+    /// can crank it. Games with stake vaults, and pots that must wait out
+    /// the large payout delay, are still claimed one winner at a time.
+    /// A bot seat is paid to the wallet that bought it (`Game::payee`).
+    ///   accounts:
+    ///   0. [signer]   caller
+    ///   1. [writable] game account
+    ///   2. []         config PDA
+    ///   3. [writable] pot vault PDA
+    ///   4. []         instructions sysvar
+    ///   5. [...]      each unpaid winner's payee, in seat order (writable)
+    DistributeWinnings,
+
+    /// Create the insurance fund, or change its share, `share_bps` of every
+    /// treasury sweep (at most `MAX_INSURANCE_SHARE_BPS`). Config authority
+    /// only.
+    ///   accounts:
+    ///   0. [signer, writable] config authority (pays for a new fund)
+    ///   1. []         config PDA
+    ///   2. [writable] insurance fund PDA
+    ///   3. []         system program
+    ConfigureInsuranceFund {
+        share_bps: u16,
+    },
+
+    /// Ask the insurance fund to reimburse `requested` lamports lost to a
+    /// program fault. `evidence` is the hash of the incident report the
+    /// operator adjudicates, e.g. the failing transaction and its analysis.
+    ///   accounts:
+    ///   0. [signer, writable] claimant (pays for the claim)
+    ///   1. [writable] insurance fund PDA
+    ///   2. [writable] insurance claim PDA for the fund's `claim_count`
+    ///   3. []         system program
+    FileInsuranceClaim {
+        requested: u64,
+        evidence: [u8; 32],
+    },
+
+    /// Rule on a pending insurance claim: pay the claimant `amount` (at
+    /// most what was requested) from the fund, or reject it with 0. The
+    /// claim account keeps the ruling. Config authority only.
+    ///   accounts:
+    ///   0. [signer]   config authority
+    ///   1. []         config PDA
+    ///   2. [writable] insurance fund PDA
+    ///   3. [writable] insurance claim PDA
+    ///   4. [writable] claimant
+    ResolveInsuranceClaim {
+        amount: u64,
+    },
+
+    /// Return a seat's entry from a game `ResolveTimeout` cancelled before
+    /// it could be played: what the seat paid into the pot (see
+    /// `Game::refundable`), paid to the seat's own wallet, or for a bot seat to the wallet that paid for it. Anyone
+    /// may crank; each seat is refunded once, so a wallet that funded
+    /// several bots is refunded once per seat.
+    ///   accounts:
+    ///   0. [signer]   caller
+    ///   1. [writable] game account
+    ///   2. [writable] seat's payee (`Game::payee`)
+    ///   3. [writable] pot vault PDA
+    ///   4. [writable] treasury PDA (gets a pot subsidy back with the last refund)
+    ///
+    ///   then for RPSToken games the token program, the seat's token
+    ///   account, game vault and game authority PDA
+    RefundEntry,
+
+    /// Mint `DEMO_CLAIM_AMOUNT` test tokens of the demo mint to a wallet
+    /// that has a `PlayerProfile`, at most once per
+    /// `DEMO_CLAIM_COOLDOWN_SECS`, so onboarding can run on devnet without
+    /// a faucet service. The first claim creates the mint, and each claim
+    /// creates the player's token account when it is missing. Only builds
+    /// with the `devnet` feature process it; others reject it as invalid
+    /// data.
+    ///   accounts:
+    ///   0. [signer, writable] player (pays for new accounts)
+    ///   1. []         profile PDA
+    ///   2. [writable] demo claim PDA
+    ///   3. [writable] demo mint PDA
+    ///   4. [writable] player's token account for the demo mint (see
+    ///      `demo_token_address` for a new one)
+    ///   5. []         token program
+    ///   6. []         system program
+    ///   7. []         associated token account program
+    ClaimDemoTokens,
+
+    /// Create the token vault of an RPSToken game (`game_vault_address`)
+    /// ahead of InitializeGame, which then only checks it, so creating a
+    /// token game stays within one transaction's compute and account
+    /// limits. The game account need not exist yet. Succeeds without
+    /// change when the vault already exists, so clients can always send it
+    /// first.
+    ///   accounts:
+    ///   0. [signer, writable] payer (funds the vault's rent)
+    ///   1. []         game account (or its future address)
+    ///   2. []         game authority PDA
+    ///   3. [writable] game vault
+    ///   4. []         game mint
+    ///   5. []         system program
+    ///   6. []         token program
+    ///   7. []         associated token account program
+    CreateGameTokenVault,
+
+    /// Play the seats AddBotPlayers filled, which have no key to sign
+    /// with. In a commit phase every bot seat that has not committed does;
+    /// in a reveal phase every committed bot seat reveals the hand drawn
+    /// for it when the commit phase closed (`bot_hand`).
+    /// Anyone may crank, and it fails when no bot seat has anything to do.
+    ///   accounts:
+    ///   0. [signer]   caller
+    ///   1. [writable] game account
+    ///   2. []         SlotHashes sysvar
+    BotAct,
+}
+
+// Program entrypoint
+entrypoint!(process_instruction);
+
+// Process instruction logic
+pub fn process_instruction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    dispatch_instruction(program_id, accounts, instruction_data).map_err(|error| {
+        log_error_context(program_id, accounts, instruction_data, &error);
+        error
+    })
+}
+
+// Logs one compact line describing a failed instruction, so failed user
+// transactions can be diagnosed from their logs alone:
+//   rps-error code=<code> ix=<tag> state=<game state> account=<index>
+// Fields that can't be determined are logged as "-".
+fn log_error_context(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+    error: &ProgramError,
+) {
+    let code = match error {
+        ProgramError::Custom(code) => code.to_string(),
+        // Builtin variant name, without any payload
+        other => format!("{:?}", other)
+            .split('(')
+            .next()
+            .unwrap_or_default()
+            .to_string(),
+    };
+    let tag = instruction_data.first().copied();
+
+    // Every game instruction passes the game account second
+    let state = accounts
+        .get(1)
+        .filter(|acc| acc.owner == program_id)
+        .and_then(|acc| Game::unpack_from(&acc.data.borrow()).ok())
+        .map(|game| format!("{:?}", game.state));
+
+    let account = tag.and_then(|tag| offending_account(program_id, accounts, tag, error));
+
+    msg!(
+        "rps-error code={} ix={} state={} account={}",
+        code,
+        tag.map_or("-".to_string(), |t| t.to_string()),
+        state.as_deref().unwrap_or("-"),
+        account.map_or("-".to_string(), |i| i.to_string())
+    );
+}
+
+// Best-effort index of the account an error is about
+fn offending_account(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    tag: u8,
+    error: &ProgramError,
+) -> Option<usize> {
+    let specs = accounts::instruction_accounts(tag);
+    let unsigned = specs
+        .iter()
+        .enumerate()
+        .find(|&(i, spec)| spec.signer && accounts.get(i).is_some_and(|acc| !acc.is_signer))
+        .map(|(i, _)| i);
+
+    match error {
+        ProgramError::MissingRequiredSignature => unsigned,
+        // A missing signature, or else the actor (always first) lacks the role
+        e if *e == ProgramError::from(RPSError::NotAuthorized) => Some(unsigned.unwrap_or(0)),
+        e if *e == ProgramError::from(RPSError::AccountNotWritable) => specs
+            .iter()
+            .enumerate()
+            .find(|&(i, spec)| spec.writable && accounts.get(i).is_some_and(|acc| !acc.is_writable))
+            .map(|(i, _)| i),
+        ProgramError::InvalidAccountData | ProgramError::IllegalOwner => accounts
+            .get(1)
+            .filter(|acc| acc.owner != program_id || Game::unpack_from(&acc.data.borrow()).is_err())
+            .map(|_| 1),
+        ProgramError::NotEnoughAccountKeys => Some(accounts.len()),
+        _ => None,
+    }
+}
+
+// Instructions a `SetPausedInstructions` bit can reach, as a bitmask over
+// their discriminants: those that always carry the config PDA the switch
+// lives in
+pub const PAUSABLE_INSTRUCTIONS: u128 = instruction_mask(&[
+    1, 2, 3, 5, 9, 10, 11, 12, 13, 15, 23, 24, 25, 26, 28, 29, 33, 38, 44, 45, 47, 48, 49, 50, 51, 54, 55, 60, 61,
+    62, 65, 66, 71, 72, 75, 76, 77, 80, 82, 85, 90, 91, 93,
+]);
+
+const fn instruction_mask(tags: &[u8]) -> u128 {
+    let mut mask = 0;
+    let mut i = 0;
+    while i < tags.len() {
+        mask |= 1u128 << tags[i];
+        i += 1;
+    }
+    mask
+}
+
+// Entry instructions `token_entries_paused` applies to, by discriminant
+// (JoinGame, AddBotPlayers, JoinGameWithSwap, JoinGameWithMint)
+const TOKEN_ENTRY_INSTRUCTIONS: [u8; 4] = [1, 9, 13, 15];
+
+// Fails instructions the operator has paused. The switch lives in the
+// config PDA, so it is read wherever an instruction carries the config.
+fn check_not_paused(program_id: &Pubkey, accounts: &[AccountInfo], tag: u8) -> ProgramResult {
+    // SetPausedInstructions stays available to lift a pause
+    if tag == 63 {
+        return Ok(());
+    }
+    let pausable = tag < 128 && PAUSABLE_INSTRUCTIONS & (1u128 << tag) != 0;
+    let mut candidates = accounts
+        .iter()
+        .filter(|acc| acc.owner == program_id && acc.data_len() == ProgramConfig::SPACE)
+        .peekable();
+    if candidates.peek().is_none() && !pausable {
+        return Ok(());
+    }
+    let config_key = config_address(program_id).0;
+    // A pausable instruction cannot dodge its pause by leaving the config out
+    if pausable && !accounts.iter().any(|acc| *acc.key == config_key) {
+        msg!("Instruction {} needs the config PDA", tag);
+        return Err(RPSError::InvalidParameter.into());
+    }
+    let Some(config_account) = candidates.find(|acc| *acc.key == config_key) else {
+        return Ok(());
+    };
+    let config = ProgramConfig::unpack_from(&config_account.data.borrow())?;
+
+    if tag < 128 && config.paused_instructions & (1u128 << tag) != 0 {
+        msg!("Instruction {} is paused", tag);
+        return Err(RPSError::InstructionPaused.into());
+    }
+    // A swap join seats the player through JoinGame, so pausing JoinGame
+    // holds it too
+    if tag == 13 && config.paused_instructions & (1u128 << 1) != 0 {
+        msg!("Joins are paused");
+        return Err(RPSError::InstructionPaused.into());
+    }
+    if config.token_entries_paused && TOKEN_ENTRY_INSTRUCTIONS.contains(&tag) {
+        let token_game = accounts
+            .get(1)
+            .filter(|acc| acc.owner == program_id)
+            .and_then(|acc| Game::unpack_from(&acc.data.borrow()).ok())
+            .is_some_and(|game| matches!(game.currency_mode, CurrencyMode::RPSToken));
+        if token_game {
+            msg!("Token-mode entries are paused");
+            return Err(RPSError::InstructionPaused.into());
+        }
+    }
+    Ok(())
+}
+
+// Decode and route an instruction to its handler
+fn dispatch_instruction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let instruction = RPSInstruction::try_from_slice(instruction_data)
+        .map_err(|_| ProgramError::InvalidInstructionData)?;
+    accounts::check_accounts(instruction_data[0], accounts)?;
+    check_not_paused(program_id, accounts, instruction_data[0])?;
+
+    match instruction {
+        RPSInstruction::InitializeGame {
+            min_players,
+            max_players,
+            total_rounds,
+            entry_fee,
+            timeout_seconds,
+            losers_can_rejoin,
+            game_mode,
+            currency_mode,
+            auto_round_delay,
+            max_auto_rounds,
+            token_mint,
+            allow_public_bots,
+            commitment_scheme,
+            theme_id,
+            rule_set,
+            join_timeout,
+            commit_timeout,
+            reveal_timeout,
+            crank_reward_lamports,
+            crank_bounty,
+            slash_non_revealers,
+        } => {
+            process_initialize_game(
+                program_id,
+                accounts,
+                min_players,
+                max_players,
+                total_rounds,
+                entry_fee,
+                timeout_seconds,
+                losers_can_rejoin,
+                game_mode,
+                currency_mode,
+                auto_round_delay,
+                max_auto_rounds,
+                token_mint,
+                allow_public_bots,
+                commitment_scheme,
+                theme_id,
+                rule_set,
+                join_timeout,
+                commit_timeout,
+                reveal_timeout,
+                crank_reward_lamports,
+                crank_bounty,
+                slash_non_revealers,
+            )
+        },
+        RPSInstruction::JoinGame => {
+            process_join_game(program_id, accounts)
+        },
+        RPSInstruction::CommitChoice { committed_choice } => {
+            process_commit_choice(program_id, accounts, committed_choice, false)
+        },
+        RPSInstruction::RevealChoice { choice, salt } => {
+            process_reveal_choice(program_id, accounts, choice, Some(salt))
+        },
+        RPSInstruction::ResolveTimeout { phase_nonce } => {
+            process_resolve_timeout(program_id, accounts, phase_nonce)
+        },
+        RPSInstruction::ClaimWinnings => {
+            process_claim_winnings(program_id, accounts, false)
+        },
+        RPSInstruction::RejoinGame => {
+            process_rejoin_game(program_id, accounts)
+        },
+        RPSInstruction::StartNewGameRound => {
+            process_start_new_game_round(program_id, accounts)
+        },
+        RPSInstruction::AutoPlayNextRound => {
+            process_auto_play_next_round(program_id, accounts)
+        },
+        RPSInstruction::AddBotPlayers { count, strategy } => {
+            process_add_bot_players(program_id, accounts, count, strategy)
+        },
+        RPSInstruction::CollectFees => {
+            process_collect_fees(program_id, accounts)
+
+        /* ─── Tournament foundation ──────────────────────────────── */
+        }, RPSInstruction::CreateTournament {
+            max_players,
+            entry_fee,
+            currency_mode,
+            token_mint,
+            qualifier_of,
+            qualifier_top_n,
+            team_entries,
+        } => {
+            process_create_tournament(
+                program_id,
+                accounts,
+                max_players,
+                entry_fee,
+                currency_mode,
+                token_mint,
+                qualifier_of,
+                qualifier_top_n,
+                team_entries,
+            )
+        }, RPSInstruction::JoinTournament => {
+            process_join_tournament(program_id, accounts)
+        },
+        RPSInstruction::JoinGameWithSwap {
+            usdc_amount_in,
+            max_slippage_bps,
+            route_accounts_len,
+            route_data,
+        } => {
+            process_join_game_with_swap(
+                program_id,
+                accounts,
+                usdc_amount_in,
+                max_slippage_bps,
+                route_accounts_len,
+                route_data,
+            )
+        },
+        RPSInstruction::ConfigureStakeMints { base_decimals, price_feeds } => {
+            process_configure_stake_mints(program_id, accounts, base_decimals, price_feeds)
+        },
+        RPSInstruction::JoinGameWithMint { mint_index } => {
+            process_join_game_with_mint(program_id, accounts, mint_index)
+        },
+        RPSInstruction::StartTournament => {
+            process_start_tournament(program_id, accounts)
+        },
+        RPSInstruction::ReportMatchResult { match_index } => {
+            process_report_match_result(program_id, accounts, match_index)
+        },
+        RPSInstruction::FinishTournament => {
+            process_finish_tournament(program_id, accounts)
+        },
+        RPSInstruction::ClaimTournamentPrize => {
+            process_claim_tournament_prize(program_id, accounts)
+        },
+        RPSInstruction::OpenTournamentMatch { match_index } => {
+            process_open_tournament_match(program_id, accounts, match_index)
+        },
+        RPSInstruction::DisqualifyPlayer { player } => {
+            process_disqualify_player(program_id, accounts, player)
+        },
+        RPSInstruction::SetTournamentAdmins { admins } => {
+            process_set_tournament_admins(program_id, accounts, admins)
+        },
+        RPSInstruction::InitializeConfig => {
+            process_initialize_config(program_id, accounts)
+        },
+        RPSInstruction::BanPlayer { wallet } => {
+            process_ban_player(program_id, accounts, wallet)
+        },
+        RPSInstruction::UnbanPlayer { wallet } => {
+            process_unban_player(program_id, accounts, wallet)
+        },
+        RPSInstruction::SetCompliance { enabled, attestors } => {
+            process_set_compliance(program_id, accounts, enabled, attestors)
+        },
+        RPSInstruction::GetClaimable { player } => {
+            process_get_claimable(program_id, accounts, player)
+        },
+        RPSInstruction::CreateChallenge { opponent, stake } => {
+            process_create_challenge(program_id, accounts, opponent, stake)
+        },
+        RPSInstruction::AcceptChallenge => {
+            process_accept_challenge(program_id, accounts)
+        },
+        RPSInstruction::CommitChallengeChoice { commitment } => {
+            process_commit_challenge_choice(program_id, accounts, commitment)
+        },
+        RPSInstruction::RevealChallengeChoice { choice, salt } => {
+            process_reveal_challenge_choice(program_id, accounts, choice, salt)
+        },
+        RPSInstruction::ExpireChallenge => {
+            process_expire_challenge(program_id, accounts)
+        },
+        RPSInstruction::RecommitChoice { committed_choice } => {
+            process_commit_choice(program_id, accounts, committed_choice, true)
+        },
+        RPSInstruction::RecordGameResult => {
+            process_record_game_result(program_id, accounts)
+        },
+        RPSInstruction::AuditGame => {
+            process_audit_game(program_id, accounts)
+        },
+        RPSInstruction::CreateAutoTournament { bots, rounds_per_match } => {
+            process_create_auto_tournament(program_id, accounts, bots, rounds_per_match)
+        },
+        RPSInstruction::CrankAutoTournament => {
+            process_crank_auto_tournament(program_id, accounts)
+        },
+        RPSInstruction::RevealChoiceSigned { choice } => {
+            process_reveal_choice(program_id, accounts, choice, None)
+        },
+        RPSInstruction::CreateClan { name, members, split_bps } => {
+            process_create_clan(program_id, accounts, name, members, split_bps)
+        },
+        RPSInstruction::JoinTournamentAsClan => {
+            process_join_tournament_as_clan(program_id, accounts)
+        },
+        RPSInstruction::OpenTeamMatch {
+            match_index,
+            total_rounds,
+            timeout_seconds,
+        } => {
+            process_open_team_match(program_id, accounts, match_index, total_rounds, timeout_seconds)
+        },
+        RPSInstruction::ClaimTeamPrize => {
+            process_claim_team_prize(program_id, accounts)
+        },
+        RPSInstruction::InitializeStats => {
+            process_initialize_stats(program_id, accounts)
+        },
+        RPSInstruction::SetTimeoutPolicy {
+            multiplier_pct,
+            min_secs,
+            max_secs,
+        } => {
+            process_set_timeout_policy(program_id, accounts, multiplier_pct, min_secs, max_secs)
+        },
+        RPSInstruction::RefreshDefaultTimeout => {
+            process_refresh_default_timeout(program_id, accounts)
+        },
+        RPSInstruction::RecordParticipation => {
+            process_record_participation(program_id, accounts)
+        },
+        RPSInstruction::SetCollusionPolicy { hold_flagged_payouts } => {
+            process_set_collusion_policy(program_id, accounts, hold_flagged_payouts)
+        },
+        RPSInstruction::ClearGameFlag => {
+            process_clear_game_flag(program_id, accounts)
+        },
+        RPSInstruction::SetClaimPolicy {
+            large_payout_threshold,
+            claim_delay_secs,
+        } => {
+            process_set_claim_policy(program_id, accounts, large_payout_threshold, claim_delay_secs)
+        },
+        RPSInstruction::InitiateClaim => {
+            process_initiate_claim(program_id, accounts)
+        },
+        RPSInstruction::FinalizeClaim => {
+            process_claim_winnings(program_id, accounts, true)
+        },
+        RPSInstruction::RegisterLobbyName { name } => {
+            process_register_lobby_name(program_id, accounts, name)
+        },
+        RPSInstruction::CreateRecurringLobby {
+            template,
+            interval_secs,
+            first_spawn_at,
+        } => {
+            process_create_recurring_lobby(program_id, accounts, template, interval_secs, first_spawn_at)
+        },
+        RPSInstruction::SpawnScheduledLobby => {
+            process_spawn_scheduled_lobby(program_id, accounts)
+        },
+        RPSInstruction::JoinWaitlist => {
+            process_join_waitlist(program_id, accounts)
+        },
+        RPSInstruction::LeaveGame => {
+            process_vacate_seat(program_id, accounts, false)
+        },
+        RPSInstruction::KickPlayer => {
+            process_vacate_seat(program_id, accounts, true)
+        },
+        RPSInstruction::RefundWaitlist => {
+            process_refund_waitlist(program_id, accounts)
+        },
+        RPSInstruction::RefundExpiredEscrow => {
+            process_refund_expired_escrow(program_id, accounts)
+        },
+        RPSInstruction::SetPotGuarantee {
+            guaranteed_min_pot,
+            subsidy_entry_floor,
+        } => {
+            process_set_pot_guarantee(program_id, accounts, guaranteed_min_pot, subsidy_entry_floor)
+        },
+        RPSInstruction::ApplyPotSubsidy => {
+            process_apply_pot_subsidy(program_id, accounts)
+        },
+        RPSInstruction::GetFeeQuote { entry_fee, currency_mode } => {
+            process_get_fee_quote(program_id, accounts, entry_fee, currency_mode)
+        },
+        RPSInstruction::SetPausedInstructions {
+            paused_instructions,
+            token_entries_paused,
+        } => {
+            process_set_paused_instructions(program_id, accounts, paused_instructions, token_entries_paused)
+        },
+        RPSInstruction::FinalizeLegacyGame => {
+            process_finalize_legacy_game(program_id, accounts)
+        },
+        RPSInstruction::OfferDoubleOrNothing => {
+            process_offer_double_or_nothing(program_id, accounts)
+        },
+        RPSInstruction::AcceptDoubleOrNothing => {
+            process_accept_double_or_nothing(program_id, accounts)
+        },
+        RPSInstruction::SealEntryFee { commitment, min_fee } => {
+            process_seal_entry_fee(program_id, accounts, commitment, min_fee)
+        },
+        RPSInstruction::RevealEntryFee { fee, salt } => {
+            process_reveal_entry_fee(program_id, accounts, fee, salt)
+        },
+        RPSInstruction::SpawnTournamentMatch {
+            match_index,
+            total_rounds,
+            timeout_seconds,
+        } => {
+            process_spawn_tournament_match(program_id, accounts, match_index, total_rounds, timeout_seconds)
+        },
+        RPSInstruction::ArchiveGameResults { epoch } => {
+            process_archive_game_results(program_id, accounts, epoch)
+        },
+        RPSInstruction::SetNicknamePolicy { fee, fee_mint } => {
+            process_set_nickname_policy(program_id, accounts, fee, fee_mint)
+        },
+        RPSInstruction::SetNickname { nickname } => {
+            process_set_nickname(program_id, accounts, nickname)
+        },
+        RPSInstruction::GetSpectatorView => {
+            process_get_spectator_view(program_id, accounts)
+        },
+        RPSInstruction::CloseGame => {
+            process_close_game(program_id, accounts)
+        },
+        RPSInstruction::SetFeePolicy { fee_collector, fee_bps } => {
+            process_set_fee_policy(program_id, accounts, fee_collector, fee_bps)
+        },
+        RPSInstruction::SetPayoutGuard { enabled } => {
+            process_set_payout_guard(program_id, accounts, enabled)
+        },
+        RPSInstruction::InitializeGovernance { stake_mint, quorum, voting_secs } => {
+            process_initialize_governance(program_id, accounts, stake_mint, quorum, voting_secs)
+        },
+        RPSInstruction::StakeGovernanceTokens { amount } => {
+            process_stake_governance_tokens(program_id, accounts, amount)
+        },
+        RPSInstruction::UnstakeGovernanceTokens { amount } => {
+            process_unstake_governance_tokens(program_id, accounts, amount)
+        },
+        RPSInstruction::ProposeParameterChange { change } => {
+            process_propose_parameter_change(program_id, accounts, change)
+        },
+        RPSInstruction::CastVote { approve } => {
+            process_cast_vote(program_id, accounts, approve)
+        },
+        RPSInstruction::ExecuteProposal => {
+            process_execute_proposal(program_id, accounts)
+        },
+        RPSInstruction::SetLimits { daily_limit, cool_off_secs } => {
+            process_set_limits(program_id, accounts, daily_limit, cool_off_secs)
+        },
+        RPSInstruction::SelfExclude { until } => {
+            process_self_exclude(program_id, accounts, until)
+        },
+        RPSInstruction::InitializeColdTreasury {
+            signers,
+            threshold,
+            timelock_secs,
+            hot_cap,
+        } => process_initialize_cold_treasury(program_id, accounts, signers, threshold, timelock_secs, hot_cap),
+        RPSInstruction::SweepTreasury => {
+            process_sweep_treasury(program_id, accounts)
+        },
+        RPSInstruction::ProposeColdAction { action } => {
+            process_propose_cold_action(program_id, accounts, action)
+        },
+        RPSInstruction::ApproveColdAction => {
+            process_approve_cold_action(program_id, accounts)
+        },
+        RPSInstruction::ExecuteColdAction => {
+            process_execute_cold_action(program_id, accounts)
+        },
+        RPSInstruction::DistributeWinnings => {
+            process_distribute_winnings(program_id, accounts)
+        },
+        RPSInstruction::ConfigureInsuranceFund { share_bps } => {
+            process_configure_insurance_fund(program_id, accounts, share_bps)
+        },
+        RPSInstruction::FileInsuranceClaim { requested, evidence } => {
+            process_file_insurance_claim(program_id, accounts, requested, evidence)
+        },
+        RPSInstruction::ResolveInsuranceClaim { amount } => {
+            process_resolve_insurance_claim(program_id, accounts, amount)
+        },
+        RPSInstruction::RefundEntry => {
+            process_refund_entry(program_id, accounts)
+        },
+        RPSInstruction::ClaimDemoTokens => {
+            process_claim_demo_tokens(program_id, accounts)
+        },
+        RPSInstruction::CreateGameTokenVault => {
+            process_create_game_token_vault(program_id, accounts)
+        },
+        RPSInstruction::BotAct => {
+            process_bot_act(program_id, accounts)
+        },
+    }
+}
+
+/* ╔══════════════════════════════════════════════════════════════════╗
+   ║                       TOURNAMENT STATE                           ║
+   ╚══════════════════════════════════════════════════════════════════╝ */
+
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct TournamentState {
+    pub host: Pubkey,
+    pub max_players: u8,
+    pub entry_fee: u64,
+    pub currency_mode: CurrencyMode,
+    pub players: Vec<Pubkey>,
+    pub prize_pool: u64,
+    pub is_started: bool,
+    pub token_mint: Option<Pubkey>,
+    pub bracket: Vec<BracketMatch>, // Single elimination, rounds back to back, final last
+    pub champion: Option<Pubkey>,
+    pub standings: Vec<Pubkey>,     // Final placings, champion first
+    pub is_finished: bool,
+    pub prizes_claimed: u8,         // Bit per paid standings position
+    pub disqualified: Vec<Pubkey>,  // Players who lose every remaining match
+    pub admins: Vec<Pubkey>,        // Co-admins who may act for the host
+    pub qualifier_of: Option<Pubkey>, // Entry requires placing in this tournament
+    pub qualifier_top_n: u8,
+    pub auto_rounds: u8,            // Bot-only auto tournament: throws to win a match; 0 otherwise
+    pub team_entries: bool,         // Entrants are clans and matches are team games
+    pub fee_bps: Option<u16>,       // Platform fee rate, fixed at creation; None = simulate::DEFAULT_FEE_BPS
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, Default)]
+pub struct BracketMatch {
+    pub player_a: Option<Pubkey>,
+    pub player_b: Option<Pubkey>, // None in a first-round bye
+    pub winner: Option<Pubkey>,
+    pub game: Option<Pubkey>,     // Game linked to decide this match
+}
+
+impl BracketMatch {
+    pub const LEN: usize = 4 * (1 + 32);
+
+    pub fn loser(&self) -> Option<Pubkey> {
+        let winner = self.winner?;
+        [self.player_a, self.player_b]
+            .into_iter()
+            .flatten()
+            .find(|p| *p != winner)
+    }
+}
+
+impl TournamentState {
+    // Deserialize from account data, ignoring the zero padding after the state
+    pub fn unpack_from(data: &[u8]) -> Result<Self, ProgramError> {
+        let mut slice = data;
+        Self::deserialize(&mut slice).map_err(|_| ProgramError::InvalidAccountData)
+    }
+
+    pub fn get_max_size(max_players: u8) -> usize {
+        32  // host
+        + 1 // max_players
+        + 8 // entry_fee
+        + 1 // currency_mode
+        + 4 + (max_players as usize * 32) // vec<Pubkey>
+        + 8 // prize_pool
+        + 1 // is_started
+        + 1 + 32 // option<mint>
+        + 4 + (Self::bracket_size(max_players) - 1) * BracketMatch::LEN // bracket
+        + 1 + 32 // option<champion>
+        + 4 + (max_players as usize * 32) // standings
+        + 1 // is_finished
+        + 1 // prizes_claimed
+        + 4 + (max_players as usize * 32) // disqualified
+        + 4 + MAX_TOURNAMENT_ADMINS * 32 // admins
+        + 1 + 32 // option<qualifier_of>
+        + 1 // qualifier_top_n
+        + 1 // auto_rounds
+        + 1 // team_entries
+        + 1 + 2 // option<fee_bps>
+    }
+
+    // The host or one of their co-admins
+    pub fn is_admin(&self, key: &Pubkey) -> bool {
+        self.host == *key || self.admins.contains(key)
+    }
+
+    // Player slots in the bracket: the field rounded up to a power of two
+    pub fn bracket_size(players: u8) -> usize {
+        (players.max(2) as usize).next_power_of_two()
+    }
+}
+
+/* ─────────────────────  Create Tournament  ─────────────────────── */
+#[allow(clippy::too_many_arguments)]
+fn process_create_tournament(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    max_players: u8,
+    entry_fee: u64,
+    currency_mode: u8,
+    token_mint: Option<Pubkey>,
+    qualifier_of: Option<Pubkey>,
+    qualifier_top_n: u8,
+    team_entries: bool,
+) -> ProgramResult {
+    let ai = &mut accounts.iter();
+    let host = next_account_info(ai)?;
+    let tourney_account = next_account_info(ai)?;
+    let system_program = next_account_info(ai)?;
+    check_system_program(system_program)?;
+    let config_account = next_account_info(ai)?;
+
+    if !host.is_signer {
+        return Err(RPSError::NotAuthorized.into());
+    }
+    let fee_bps = config_fee_bps(program_id, config_account)?;
+    if !(2..=32).contains(&max_players) {
+        return Err(RPSError::InvalidParameter.into());
+    }
+    if qualifier_of.is_some() && qualifier_top_n == 0 {
+        msg!("A qualifier needs at least one qualifying placing");
+        return Err(RPSError::InvalidParameter.into());
+    }
+    if team_entries && qualifier_of.is_some() {
+        msg!("Team tournaments cannot require a qualifier");
+        return Err(RPSError::InvalidParameter.into());
+    }
+
+    /* account creation */
+    let rent = Rent::get()?;
+    let space = TournamentState::get_max_size(max_players);
+    let lamports = rent.minimum_balance(space);
+    invoke(
+        &system_instruction::create_account(
+            host.key,
+            tourney_account.key,
+            lamports,
+            space as u64,
+            program_id,
+        ),
+        &[host.clone(), tourney_account.clone(), system_program.clone()],
+    )?;
+
+    /* init state */
+    let state = TournamentState {
+        host: *host.key,
+        max_players,
+        entry_fee,
+        currency_mode: match currency_mode {
+            0 => CurrencyMode::SOL,
+            1 => CurrencyMode::RPSToken,
+            _ => return Err(RPSError::InvalidParameter.into()),
+        },
+        players: vec![],
+        prize_pool: 0,
+        is_started: false,
+        token_mint,
+        bracket: vec![],
+        champion: None,
+        standings: vec![],
+        is_finished: false,
+        prizes_claimed: 0,
+        disqualified: vec![],
+        admins: vec![],
+        qualifier_of,
+        qualifier_top_n,
+        auto_rounds: 0,
+        team_entries,
+        fee_bps,
+    };
+    state.serialize(&mut *tourney_account.data.borrow_mut())?;
+    debug_msg!("Tournament created: {}", tourney_account.key);
+    emit_event(&RPSEvent::TournamentCreated {
+        tournament: *tourney_account.key,
+        host: *host.key,
+        max_players,
+        entry_fee,
+    });
+    Ok(())
+}
+
+/* ─────────────────────  Join Tournament  ───────────────────────── */
+fn process_join_tournament(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let ai = &mut accounts.iter();
+    let player = next_account_info(ai)?;
+    let tourney_account = next_account_info(ai)?;
+    let system_program = next_account_info(ai)?;
+    check_system_program(system_program)?;
+
+    if !player.is_signer {
+        return Err(RPSError::NotAuthorized.into());
+    }
+
+    let mut tourney = TournamentState::unpack_from(&tourney_account.data.borrow())?;
+    if tourney.team_entries {
+        msg!("Team tournaments are entered by clans");
+        return Err(RPSError::InvalidGameState.into());
+    }
+    if tourney.is_started {
+        msg!("Tournament already started");
+        return Err(RPSError::InvalidGameState.into());
+    }
+    if tourney.players.len() as u8 >= tourney.max_players {
+        return Err(RPSError::GameFull.into());
+    }
+    if tourney.players.contains(player.key) {
+        return Err(RPSError::PlayerAlreadyJoined.into());
+    }
+    check_not_banned(program_id, next_account_info(ai)?, player.key)?;
+    check_not_excluded(program_id, next_account_info(ai)?, player.key)?;
+    check_compliance(program_id, next_account_info(ai)?, ai, player.key, tourney.entry_fee)?;
+
+    /* qualification: a top-N placing in the designated qualifier */
+    if let Some(qualifier_of) = tourney.qualifier_of {
+        let qualifier_account = next_account_info(ai)?;
+        if *qualifier_account.key != qualifier_of || qualifier_account.owner != program_id {
+            msg!("Qualifier account does not match the tournament's qualifier");
+            return Err(RPSError::InvalidParameter.into());
+        }
+        let qualifier = TournamentState::unpack_from(&qualifier_account.data.borrow())?;
+        if !qualifier.is_finished {
+            msg!("Qualifier has not finished");
+            return Err(RPSError::NotQualified.into());
+        }
+        let placed = qualifier
+            .standings
+            .iter()
+            .take(tourney.qualifier_top_n as usize)
+            .any(|p| p == player.key && !qualifier.disqualified.contains(p));
+        if !placed {
+            msg!("Player did not place in the top {} of the qualifier", tourney.qualifier_top_n);
+            return Err(RPSError::NotQualified.into());
+        }
+    }
+
+    /* fee payment (SOL only for step-1) */
+    if tourney.entry_fee > 0 && matches!(tourney.currency_mode, CurrencyMode::SOL) {
+        invoke(
+            &system_instruction::transfer(
+                player.key,
+                tourney_account.key,
+                tourney.entry_fee,
+            ),
+            &[player.clone(), tourney_account.clone(), system_program.clone()],
+        )?;
+        tourney.prize_pool = tourney.prize_pool.saturating_add(tourney.entry_fee);
+    }
+
+    tourney.players.push(*player.key);
+    tourney.serialize(&mut *tourney_account.data.borrow_mut())?;
+    debug_msg!("{} joined tournament", player.key);
+    emit_event(&RPSEvent::TournamentJoined {
+        tournament: *tourney_account.key,
+        entrant: *player.key,
+    });
+    Ok(())
+}
+
+/* ─────────────────────  Start Tournament  ──────────────────────── */
+fn process_start_tournament(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let ai = &mut accounts.iter();
+    let host = next_account_info(ai)?;
+    let tourney_account = next_account_info(ai)?;
+
+    if !host.is_signer {
+        return Err(RPSError::NotAuthorized.into());
+    }
+
+    assert_owned_by(tourney_account, program_id)?;
+    let mut tourney = TournamentState::unpack_from(&tourney_account.data.borrow())?;
+    if !tourney.is_admin(host.key) {
+        msg!("Only the host or a co-admin can start the tournament");
+        return Err(RPSError::NotAuthorized.into());
+    }
+    if tourney.is_started {
+        msg!("Tournament already started");
+        return Err(RPSError::InvalidGameState.into());
+    }
+    if tourney.players.len() < 2 {
+        msg!("At least two players are needed to start");
+        return Err(RPSError::InvalidGameState.into());
+    }
+
+    tourney.bracket = seed_bracket(&tourney.players);
+    tourney.is_started = true;
+    tourney.serialize(&mut *tourney_account.data.borrow_mut())?;
+    debug_msg!("Tournament started with {} players", tourney.players.len());
+    emit_event(&RPSEvent::TournamentStarted {
+        tournament: *tourney_account.key,
+        entrants: tourney.players.len() as u8,
+    });
+    Ok(())
+}
+
+/* ─────────────────────  Open Tournament Match  ─────────────────── */
+fn process_open_tournament_match(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    match_index: u8,
+) -> ProgramResult {
+    let ai = &mut accounts.iter();
+    let host = next_account_info(ai)?;
+    let tourney_account = next_account_info(ai)?;
+    let game_account = next_account_info(ai)?;
+
+    if !host.is_signer {
+        return Err(RPSError::NotAuthorized.into());
+    }
+    if game_account.owner != program_id || tourney_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let mut tourney = TournamentState::unpack_from(&tourney_account.data.borrow())?;
+    let mut game = Game::unpack_from(&game_account.data.borrow())?;
+
+    if tourney.auto_rounds > 0 {
+        msg!("Auto tournament matches are played by the crank");
+        return Err(RPSError::InvalidGameState.into());
+    }
+    if tourney.team_entries {
+        msg!("Team matches are opened with OpenTeamMatch");
+        return Err(RPSError::InvalidGameState.into());
+    }
+    let bracket_match = open_bracket_match(&tourney, match_index)?;
+    if bracket_match.game.is_some() {
+        msg!("Match {} already has a game", match_index);
+        return Err(RPSError::InvalidGameState.into());
+    }
+    if game.host != *host.key
+        || (bracket_match.player_a != Some(game.host) && bracket_match.player_b != Some(game.host))
+    {
+        msg!("Game host must be playing in match {}", match_index);
+        return Err(RPSError::NotAuthorized.into());
+    }
+    state_machine::require(game.state, Action::Seat)?;
+    if game.tournament.is_some() {
+        msg!("Only a fresh, unlinked game can decide a match");
+        return Err(RPSError::InvalidGameState.into());
+    }
+
+    game.tournament = Some(*tourney_account.key);
+    game.tournament_match = match_index;
+    tourney.bracket[match_index as usize].game = Some(*game_account.key);
+
+    game.serialize(&mut *game_account.data.borrow_mut())?;
+    tourney.serialize(&mut *tourney_account.data.borrow_mut())?;
+    debug_msg!("Match {} will be decided by {}", match_index, game_account.key);
+    emit_event(&RPSEvent::MatchOpened {
+        tournament: *tourney_account.key,
+        match_index,
+        game: *game_account.key,
+    });
+    Ok(())
+}
+
+/* ─────────────────────  Spawn Tournament Match  ────────────────── */
+fn process_spawn_tournament_match(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    match_index: u8,
+    total_rounds: u8,
+    timeout_seconds: u64,
+) -> ProgramResult {
+    let ai = &mut accounts.iter();
+    let caller = next_account_info(ai)?;
+    let tourney_account = next_account_info(ai)?;
+    let game_account = next_account_info(ai)?;
+    let system_program = next_account_info(ai)?;
+    check_system_program(system_program)?;
+
+    if !caller.is_signer || !game_account.is_signer {
+        return Err(RPSError::NotAuthorized.into());
+    }
+    assert_owned_by(tourney_account, program_id)?;
+    if total_rounds == 0 {
+        msg!("Total rounds must be greater than 0");
+        return Err(RPSError::InvalidParameter.into());
+    }
+
+    let mut tourney = TournamentState::unpack_from(&tourney_account.data.borrow())?;
+    if tourney.auto_rounds > 0 {
+        msg!("Auto tournament matches are played by the crank");
+        return Err(RPSError::InvalidGameState.into());
+    }
+    if tourney.team_entries {
+        msg!("Team matches are opened with OpenTeamMatch");
+        return Err(RPSError::InvalidGameState.into());
+    }
+    let bracket_match = open_bracket_match(&tourney, match_index)?;
+    if bracket_match.game.is_some() {
+        msg!("Match {} already has a game", match_index);
+        return Err(RPSError::InvalidGameState.into());
+    }
+    let (Some(player_a), Some(player_b)) = (bracket_match.player_a, bracket_match.player_b) else {
+        msg!("Match {} is still waiting on a player", match_index);
+        return Err(RPSError::InvalidGameState.into());
+    };
+    if !tourney.is_admin(caller.key) && *caller.key != player_a && *caller.key != player_b {
+        msg!("Only a match player or tournament admin can open the match");
+        return Err(RPSError::NotAuthorized.into());
+    }
+
+    /* game account creation */
+    let space = Game::get_max_size(2);
+    let rent = Rent::get()?;
+    invoke(
+        &system_instruction::create_account(
+            caller.key,
+            game_account.key,
+            rent.minimum_balance(space),
+            space as u64,
+            program_id,
+        ),
+        &[caller.clone(), game_account.clone(), system_program.clone()],
+    )?;
+
+    let now = Clock::get()?.unix_timestamp as u64;
+    let lineup = vec![player_a, player_b];
+    let game = match_game(*caller.key, *tourney_account.key, match_index, lineup, total_rounds, timeout_seconds, now);
+    game.serialize(&mut *game_account.data.borrow_mut())?;
+
+    tourney.bracket[match_index as usize].game = Some(*game_account.key);
+    tourney.serialize(&mut *tourney_account.data.borrow_mut())?;
+
+    debug_msg!("Match {} will be decided by {}", match_index, game_account.key);
+    emit_event(&RPSEvent::MatchOpened {
+        tournament: *tourney_account.key,
+        match_index,
+        game: *game_account.key,
+    });
+    emit_event(&RPSEvent::GameCreated {
+        game: *game_account.key,
+        host: *caller.key,
+        theme_id: 0,
+    });
+    emit_event(&RPSEvent::PhaseStarted {
+        game: *game_account.key,
+        phase_nonce: game.phase_nonce,
+        deadline: game.phase_deadline,
+    });
+    Ok(())
+}
+
+/* ─────────────────────  Report Match Result  ───────────────────── */
+fn process_report_match_result(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    match_index: u8,
+) -> ProgramResult {
+    let ai = &mut accounts.iter();
+    let caller = next_account_info(ai)?;
+    let tourney_account = next_account_info(ai)?;
+    let game_account = next_account_info(ai)?;
+
+    if !caller.is_signer {
+        return Err(RPSError::NotAuthorized.into());
+    }
+    if game_account.owner != program_id || tourney_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let mut tourney = TournamentState::unpack_from(&tourney_account.data.borrow())?;
+    let game = Game::unpack_from(&game_account.data.borrow())?;
+
+    // The game must be the one linked to this slot, from both sides
+    let bracket_match = open_bracket_match(&tourney, match_index)?;
+    if bracket_match.game != Some(*game_account.key)
+        || game.tournament != Some(*tourney_account.key)
+        || game.tournament_match != match_index
+    {
+        msg!("Game is not linked to match {}", match_index);
+        return Err(RPSError::InvalidParameter.into());
+    }
+    state_machine::require(game.state, Action::Settle)?;
+
+    // Whichever side outscored the other wins. A side is the bracket
+    // player's seat, or in a team match the clan's lineup with its points
+    // summed; a side that never took its seats, or was dropped from them at
+    // a timeout, scores nothing
+    let side = |team: usize, entrant: Option<Pubkey>| {
+        let seats = game.players.iter().filter(|p| {
+            if tourney.team_entries {
+                game.team_side(&p.pubkey) == Some(team)
+            } else {
+                Some(p.pubkey) == entrant
+            }
+        });
+        seats.fold((0u32, false), |(score, _), p| (score + p.score as u32, true))
+    };
+    let (score_a, seated_a) = side(0, bracket_match.player_a);
+    let (score_b, seated_b) = side(1, bracket_match.player_b);
+    let index = match_index as usize;
+
+    let winner = if score_a != score_b {
+        if score_a > score_b { bracket_match.player_a } else { bracket_match.player_b }
+    } else {
+        // Level on points: if only one of them is still seated, the game
+        // timed out on the other and they win by walkover
+        match (seated_a, seated_b) {
+            (true, false) => bracket_match.player_a,
+            (false, true) => bracket_match.player_b,
+            _ => None,
+        }
+    };
+    let Some(winner) = winner else {
+        // Drawn: free the slot so the players can open a replay
+        tourney.bracket[index].game = None;
+        tourney.serialize(&mut *tourney_account.data.borrow_mut())?;
+        debug_msg!("Match {} drawn, replay required", match_index);
+        emit_event(&RPSEvent::MatchDecided {
+            tournament: *tourney_account.key,
+            match_index,
+            winner: None,
+        });
+        return Ok(());
+    };
+
+    record_match_winner(&mut tourney.bracket, index, winner);
+    apply_walkovers(&mut tourney);
+    tourney.serialize(&mut *tourney_account.data.borrow_mut())?;
+    debug_msg!("{} won match {}", winner, match_index);
+    emit_event(&RPSEvent::MatchDecided {
+        tournament: *tourney_account.key,
+        match_index,
+        winner: Some(winner),
+    });
+    Ok(())
+}
+
+/* ─────────────────────  Disqualify Player  ─────────────────────── */
+fn process_disqualify_player(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    player: Pubkey,
+) -> ProgramResult {
+    let ai = &mut accounts.iter();
+    let host = next_account_info(ai)?;
+    let tourney_account = next_account_info(ai)?;
+
+    if !host.is_signer {
+        return Err(RPSError::NotAuthorized.into());
+    }
+
+    assert_owned_by(tourney_account, program_id)?;
+    let mut tourney = TournamentState::unpack_from(&tourney_account.data.borrow())?;
+    if !tourney.is_admin(host.key) {
+        msg!("Only the host or a co-admin can disqualify players");
+        return Err(RPSError::NotAuthorized.into());
+    }
+    if !tourney.is_started || tourney.is_finished {
+        msg!("Tournament is not in progress");
+        return Err(RPSError::InvalidGameState.into());
+    }
+    if !tourney.players.contains(&player) {
+        return Err(RPSError::PlayerNotFound.into());
+    }
+    if tourney.disqualified.contains(&player) {
+        msg!("Player is already disqualified");
+        return Err(RPSError::InvalidParameter.into());
+    }
+
+    tourney.disqualified.push(player);
+    apply_walkovers(&mut tourney);
+    tourney.serialize(&mut *tourney_account.data.borrow_mut())?;
+    debug_msg!("{} disqualified", player);
+    emit_event(&RPSEvent::EntrantDisqualified {
+        tournament: *tourney_account.key,
+        entrant: player,
+    });
+    Ok(())
+}
+
+/* ─────────────────────  Set Tournament Admins  ─────────────────── */
+fn process_set_tournament_admins(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    admins: Vec<Pubkey>,
+) -> ProgramResult {
+    let ai = &mut accounts.iter();
+    let host = next_account_info(ai)?;
+    let tourney_account = next_account_info(ai)?;
+
+    if !host.is_signer {
+        return Err(RPSError::NotAuthorized.into());
+    }
+
+    assert_owned_by(tourney_account, program_id)?;
+    let mut tourney = TournamentState::unpack_from(&tourney_account.data.borrow())?;
+    // Co-admins cannot appoint each other
+    if tourney.host != *host.key {
+        msg!("Only the host can set co-admins");
+        return Err(RPSError::NotAuthorized.into());
+    }
+    if admins.len() > MAX_TOURNAMENT_ADMINS {
+        msg!("At most {} co-admins are allowed", MAX_TOURNAMENT_ADMINS);
+        return Err(RPSError::InvalidParameter.into());
+    }
+
+    tourney.admins = admins;
+    tourney.serialize(&mut *tourney_account.data.borrow_mut())?;
+    debug_msg!("Tournament has {} co-admins", tourney.admins.len());
+    Ok(())
+}
+
+/* ─────────────────────  Finish Tournament  ─────────────────────── */
+fn process_finish_tournament(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let ai = &mut accounts.iter();
+    let caller = next_account_info(ai)?;
+    let tourney_account = next_account_info(ai)?;
+    let treasury_account = next_account_info(ai)?;
+
+    if !caller.is_signer {
+        return Err(RPSError::NotAuthorized.into());
+    }
+    check_treasury(program_id, treasury_account)?;
+
+    let mut tourney = TournamentState::unpack_from(&tourney_account.data.borrow())?;
+    if !tourney.is_started || tourney.is_finished {
+        msg!("Tournament is not in progress");
+        return Err(RPSError::InvalidGameState.into());
+    }
+    if !matches!(tourney.currency_mode, CurrencyMode::SOL) {
+        msg!("Only SOL tournaments can be settled");
+        return Err(RPSError::InvalidParameter.into());
+    }
+
+    // The final is the last match; it must have been played out
+    if tourney.bracket.last().and_then(|m| m.winner).is_none() {
+        msg!("The final has not been decided");
+        return Err(RPSError::InvalidGameState.into());
+    }
+
+    let champion = settle_standings(&mut tourney);
+
+    // Sweep the platform fee; what is left is the prize pool
+    let fee = calculate_fee(tourney.prize_pool, tourney.fee_bps);
+    if fee > 0 {
+        pay_from_program_account(tourney_account, treasury_account, fee)?;
+        emit_event(&RPSEvent::FeeCollected {
+            source: *tourney_account.key,
+            recipient: *treasury_account.key,
+            amount: fee,
+        });
+    }
+    tourney.prize_pool -= fee;
+    tourney.is_finished = true;
+
+    // Pay out the placings whose wallets were passed, top down
+    let payees = ai.as_slice();
+    let paid_places = tourney.standings.len().min(TOURNAMENT_PRIZE_SPLIT_BPS.len());
+    if !payees.is_empty() && tourney.team_entries {
+        msg!("Team prizes are paid out with ClaimTeamPrize");
+        return Err(RPSError::InvalidParameter.into());
+    }
+    if payees.len() > paid_places {
+        msg!("Only the top {} placings win prizes", paid_places);
+        return Err(RPSError::InvalidParameter.into());
+    }
+    for (position, wallet) in payees.iter().enumerate() {
+        if tourney.standings[position] != *wallet.key {
+            msg!("Payout accounts must follow the standings");
+            return Err(RPSError::InvalidParameter.into());
+        }
+        let amount = tournament_prize(tourney.prize_pool, position, tourney.standings.len());
+        if amount > 0 {
+            pay_from_program_account(tourney_account, wallet, amount)?;
+        }
+        tourney.prizes_claimed |= 1 << position;
+        emit_event(&RPSEvent::PrizePaid {
+            tournament: *tourney_account.key,
+            recipient: *wallet.key,
+            placing: position as u8 + 1,
+            amount,
+        });
+    }
+
+    tourney.serialize(&mut *tourney_account.data.borrow_mut())?;
+    debug_msg!("Tournament won by {}", champion);
+    emit_event(&RPSEvent::TournamentFinished {
+        tournament: *tourney_account.key,
+        champion,
+        prize_pool: tourney.prize_pool,
+    });
+    Ok(())
+}
+
+/* ─────────────────────  Claim Tournament Prize  ────────────────── */
+fn process_claim_tournament_prize(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let ai = &mut accounts.iter();
+    let player = next_account_info(ai)?;
+    let tourney_account = next_account_info(ai)?;
+
+    if !player.is_signer {
+        return Err(RPSError::NotAuthorized.into());
+    }
+
+    assert_owned_by(tourney_account, program_id)?;
+    let mut tourney = TournamentState::unpack_from(&tourney_account.data.borrow())?;
+    if !tourney.is_finished {
+        msg!("Tournament is not finished");
+        return Err(RPSError::InvalidGameState.into());
+    }
+
+    let position = tourney
+        .standings
+        .iter()
+        .position(|p| p == player.key)
+        .filter(|&i| i < TOURNAMENT_PRIZE_SPLIT_BPS.len())
+        .ok_or_else(|| {
+            msg!("Player did not place in the prizes");
+            RPSError::NotWinner
+        })?;
+    let bit = 1u8 << position;
+    if tourney.prizes_claimed & bit != 0 {
+        msg!("Prize already claimed");
+        return Err(RPSError::AlreadyClaimed.into());
+    }
+
+    let amount = tournament_prize(tourney.prize_pool, position, tourney.standings.len());
+    if amount > 0 {
+        pay_from_program_account(tourney_account, player, amount)?;
+    }
+    tourney.prizes_claimed |= bit;
+
+    tourney.serialize(&mut *tourney_account.data.borrow_mut())?;
+    debug_msg!("Paid {} to placing {}", amount, position + 1);
+    emit_event(&RPSEvent::PrizePaid {
+        tournament: *tourney_account.key,
+        recipient: *player.key,
+        placing: position as u8 + 1,
+        amount,
+    });
+    Ok(())
+}
+
+/* ─────────────────────  Create Auto Tournament  ────────────────── */
+fn process_create_auto_tournament(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    bots: u8,
+    rounds_per_match: u8,
+) -> ProgramResult {
+    let ai = &mut accounts.iter();
+    let host = next_account_info(ai)?;
+    let tourney_account = next_account_info(ai)?;
+    let system_program = next_account_info(ai)?;
+    check_system_program(system_program)?;
+
+    if !host.is_signer {
+        return Err(RPSError::NotAuthorized.into());
+    }
+    if !(2..=32).contains(&bots) || !(1..=MAX_AUTO_MATCH_ROUNDS).contains(&rounds_per_match) {
+        return Err(RPSError::InvalidParameter.into());
+    }
+
+    /* account creation */
+    let rent = Rent::get()?;
+    let space = TournamentState::get_max_size(bots);
+    invoke(
+        &system_instruction::create_account(
+            host.key,
+            tourney_account.key,
+            rent.minimum_balance(space),
+            space as u64,
+            program_id,
+        ),
+        &[host.clone(), tourney_account.clone(), system_program.clone()],
+    )?;
+
+    /* bots are seated and seeded straight away */
+    let players: Vec<Pubkey> = (0..bots).map(|i| auto_bot_pubkey(tourney_account.key, i)).collect();
+    let state = TournamentState {
+        host: *host.key,
+        max_players: bots,
+        entry_fee: 0,
+        currency_mode: CurrencyMode::SOL,
+        bracket: seed_bracket(&players),
+        players,
+        prize_pool: 0,
+        is_started: true,
+        token_mint: None,
+        champion: None,
+        standings: vec![],
+        is_finished: false,
+        prizes_claimed: 0,
+        disqualified: vec![],
+        admins: vec![],
+        qualifier_of: None,
+        qualifier_top_n: 0,
+        auto_rounds: rounds_per_match,
+        team_entries: false,
+        fee_bps: None,
+    };
+    state.serialize(&mut *tourney_account.data.borrow_mut())?;
+    debug_msg!("Auto tournament created with {} bots", bots);
+    emit_event(&RPSEvent::TournamentCreated {
+        tournament: *tourney_account.key,
+        host: *host.key,
+        max_players: bots,
+        entry_fee: 0,
+    });
+    Ok(())
+}
+
+/* ─────────────────────  Crank Auto Tournament  ─────────────────── */
+fn process_crank_auto_tournament(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let ai = &mut accounts.iter();
+    let caller = next_account_info(ai)?;
+    let tourney_account = next_account_info(ai)?;
+
+    if !caller.is_signer {
+        return Err(RPSError::NotAuthorized.into());
+    }
+    if tourney_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let mut tourney = TournamentState::unpack_from(&tourney_account.data.borrow())?;
+    if tourney.auto_rounds == 0 {
+        msg!("Not an auto tournament");
+        return Err(RPSError::InvalidGameState.into());
+    }
+    if tourney.is_finished {
+        msg!("Tournament is not in progress");
+        return Err(RPSError::InvalidGameState.into());
+    }
+
+    // Matches become ready in index order, so the first ready one is next
+    let (index, a, b) = tourney
+        .bracket
+        .iter()
+        .enumerate()
+        .find_map(|(i, m)| match (m.player_a, m.player_b, m.winner) {
+            (Some(a), Some(b), None) => Some((i, a, b)),
+            _ => None,
+        })
+        .ok_or(RPSError::InvalidGameState)?;
+
+    let clock = Clock::get()?;
+    let (wins_a, wins_b) = play_auto_match(tourney_account.key, index as u8, tourney.auto_rounds, &clock);
+    let winner = if wins_a > wins_b { a } else { b };
+
+    record_match_winner(&mut tourney.bracket, index, winner);
+    apply_walkovers(&mut tourney);
+    let champion = if tourney.bracket.last().and_then(|m| m.winner).is_some() {
+        let champion = settle_standings(&mut tourney);
+        tourney.is_finished = true;
+        debug_msg!("Auto tournament won by {}", champion);
+        Some(champion)
+    } else {
+        None
+    };
+    tourney.serialize(&mut *tourney_account.data.borrow_mut())?;
+
+    emit_event(&RPSEvent::AutoMatchPlayed {
+        tournament: *tourney_account.key,
+        match_index: index as u8,
+        winner,
+        wins_a,
+        wins_b,
+    });
+    if let Some(champion) = champion {
+        emit_event(&RPSEvent::TournamentFinished {
+            tournament: *tourney_account.key,
+            champion,
+            prize_pool: 0,
+        });
+    }
+    Ok(())
+}
+
+// Seeded bot entrant `index` of an auto tournament
+fn auto_bot_pubkey(tournament: &Pubkey, index: u8) -> Pubkey {
+    Pubkey::new_from_array(hashv(&[b"rps_bot", tournament.as_ref(), &[index]]).to_bytes())
+}
+
+// Throw bot hands for a best of `rounds`; draws are rethrown.
+// Hands come from the clock at crank time, which is fine for stakeless bot
+// play but predictable by whoever lands the crank.
+fn play_auto_match(tournament: &Pubkey, match_index: u8, rounds: u8, clock: &Clock) -> (u8, u8) {
+    let needed = rounds / 2 + 1;
+    let (mut wins_a, mut wins_b) = (0u8, 0u8);
+    let mut throw: u16 = 0;
+    while wins_a < needed && wins_b < needed {
+        let hash = hashv(&[
+            tournament.as_ref(),
+            &[match_index],
+            &throw.to_le_bytes(),
+            &clock.slot.to_le_bytes(),
+            &clock.unix_timestamp.to_le_bytes(),
+        ])
+        .to_bytes();
+        // 0 = rock, 1 = paper, 2 = scissors: the hand one ahead wins
+        let (hand_a, hand_b) = (hash[0] % 3, hash[1] % 3);
+        match (hand_a + 3 - hand_b) % 3 {
+            1 => wins_a += 1,
+            2 => wins_b += 1,
+            _ => {}
+        }
+        throw = throw.wrapping_add(1);
+    }
+    (wins_a, wins_b)
+}
+
+/* ─────────────────────  Join Tournament As Clan  ──────────────── */
+fn process_join_tournament_as_clan(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let ai = &mut accounts.iter();
+    let leader = next_account_info(ai)?;
+    let tourney_account = next_account_info(ai)?;
+    let clan_account = next_account_info(ai)?;
+    let system_program = next_account_info(ai)?;
+    check_system_program(system_program)?;
+
+    if !leader.is_signer {
+        return Err(RPSError::NotAuthorized.into());
+    }
+    assert_owned_by(tourney_account, program_id)?;
+    let clan = load_clan(program_id, clan_account)?;
+    if clan.leader != *leader.key {
+        msg!("Only the clan leader can enter the clan");
+        return Err(RPSError::NotAuthorized.into());
+    }
+
+    let mut tourney = TournamentState::unpack_from(&tourney_account.data.borrow())?;
+    if !tourney.team_entries {
+        msg!("Tournament is not a team tournament");
+        return Err(RPSError::InvalidGameState.into());
+    }
+    if tourney.is_started {
+        msg!("Tournament already started");
+        return Err(RPSError::InvalidGameState.into());
+    }
+    if tourney.players.len() as u8 >= tourney.max_players {
+        return Err(RPSError::GameFull.into());
+    }
+    if tourney.players.contains(clan_account.key) {
+        return Err(RPSError::PlayerAlreadyJoined.into());
+    }
+
+    if tourney.entry_fee > 0 && matches!(tourney.currency_mode, CurrencyMode::SOL) {
+        invoke(
+            &system_instruction::transfer(leader.key, tourney_account.key, tourney.entry_fee),
+            &[leader.clone(), tourney_account.clone(), system_program.clone()],
+        )?;
+        tourney.prize_pool = tourney.prize_pool.saturating_add(tourney.entry_fee);
+    }
+
+    tourney.players.push(*clan_account.key);
+    tourney.serialize(&mut *tourney_account.data.borrow_mut())?;
+    debug_msg!("Clan {} joined tournament", clan.name);
+    emit_event(&RPSEvent::TournamentJoined {
+        tournament: *tourney_account.key,
+        entrant: *clan_account.key,
+    });
+    Ok(())
+}
+
+/* ─────────────────────  Open Team Match  ───────────────────────── */
+fn process_open_team_match(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    match_index: u8,
+    total_rounds: u8,
+    timeout_seconds: u64,
+) -> ProgramResult {
+    let ai = &mut accounts.iter();
+    let caller = next_account_info(ai)?;
+    let tourney_account = next_account_info(ai)?;
+    let game_account = next_account_info(ai)?;
+    let clan_a_account = next_account_info(ai)?;
+    let clan_b_account = next_account_info(ai)?;
+    let system_program = next_account_info(ai)?;
+    check_system_program(system_program)?;
+
+    if !caller.is_signer || !game_account.is_signer {
+        return Err(RPSError::NotAuthorized.into());
+    }
+    assert_owned_by(tourney_account, program_id)?;
+    if total_rounds == 0 {
+        msg!("Total rounds must be greater than 0");
+        return Err(RPSError::InvalidParameter.into());
+    }
+
+    let mut tourney = TournamentState::unpack_from(&tourney_account.data.borrow())?;
+    if !tourney.team_entries {
+        msg!("Tournament is not a team tournament");
+        return Err(RPSError::InvalidGameState.into());
+    }
+    let bracket_match = open_bracket_match(&tourney, match_index)?;
+    if bracket_match.game.is_some() {
+        msg!("Match {} already has a game", match_index);
+        return Err(RPSError::InvalidGameState.into());
+    }
+    if bracket_match.player_a != Some(*clan_a_account.key) || bracket_match.player_b != Some(*clan_b_account.key) {
+        msg!("Clans are not the two sides of match {}", match_index);
+        return Err(RPSError::InvalidParameter.into());
+    }
+    let clan_a = load_clan(program_id, clan_a_account)?;
+    let clan_b = load_clan(program_id, clan_b_account)?;
+    if !tourney.is_admin(caller.key) && *caller.key != clan_a.leader && *caller.key != clan_b.leader {
+        msg!("Only a clan leader or tournament admin can open the match");
+        return Err(RPSError::NotAuthorized.into());
+    }
+
+    // Each clan fields the head of its roster
+    let lineup: Vec<Pubkey> = clan_a
+        .members
+        .iter()
+        .take(TEAM_MATCH_SIZE)
+        .chain(clan_b.members.iter().take(TEAM_MATCH_SIZE))
+        .copied()
+        .collect();
+    if lineup.iter().enumerate().any(|(i, p)| lineup[..i].contains(p)) {
+        msg!("A wallet cannot play for both clans");
+        return Err(RPSError::InvalidParameter.into());
+    }
+
+    /* game account creation */
+    let seats = lineup.len() as u8;
+    let space = Game::get_max_size(seats);
+    let rent = Rent::get()?;
+    invoke(
+        &system_instruction::create_account(
+            caller.key,
+            game_account.key,
+            rent.minimum_balance(space),
+            space as u64,
+            program_id,
+        ),
+        &[caller.clone(), game_account.clone(), system_program.clone()],
+    )?;
+
+    let now = Clock::get()?.unix_timestamp as u64;
+    let game = match_game(*caller.key, *tourney_account.key, match_index, lineup, total_rounds, timeout_seconds, now);
+    game.serialize(&mut *game_account.data.borrow_mut())?;
+
+    tourney.bracket[match_index as usize].game = Some(*game_account.key);
+    tourney.serialize(&mut *tourney_account.data.borrow_mut())?;
+
+    debug_msg!("Team match {} will be decided by {}", match_index, game_account.key);
+    emit_event(&RPSEvent::MatchOpened {
+        tournament: *tourney_account.key,
+        match_index,
+        game: *game_account.key,
+    });
+    emit_event(&RPSEvent::GameCreated {
+        game: *game_account.key,
+        host: *caller.key,
+        theme_id: 0,
+    });
+    emit_event(&RPSEvent::PhaseStarted {
+        game: *game_account.key,
+        phase_nonce: game.phase_nonce,
+        deadline: game.phase_deadline,
+    });
+    Ok(())
+}
+
+/* ─────────────────────  Claim Team Prize  ──────────────────────── */
+fn process_claim_team_prize(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let ai = &mut accounts.iter();
+    let caller = next_account_info(ai)?;
+    let tourney_account = next_account_info(ai)?;
+    let clan_account = next_account_info(ai)?;
+
+    if !caller.is_signer {
+        return Err(RPSError::NotAuthorized.into());
+    }
+    assert_owned_by(tourney_account, program_id)?;
+    let clan = load_clan(program_id, clan_account)?;
+
+    let mut tourney = TournamentState::unpack_from(&tourney_account.data.borrow())?;
+    if !tourney.is_finished {
+        msg!("Tournament is not finished");
+        return Err(RPSError::InvalidGameState.into());
+    }
+
+    let position = tourney
+        .standings
+        .iter()
+        .position(|p| p == clan_account.key)
+        .filter(|&i| i < TOURNAMENT_PRIZE_SPLIT_BPS.len())
+        .ok_or_else(|| {
+            msg!("Clan did not place in the prizes");
+            RPSError::NotWinner
+        })?;
+    let bit = 1u8 << position;
+    if tourney.prizes_claimed & bit != 0 {
+        msg!("Prize already claimed");
+        return Err(RPSError::AlreadyClaimed.into());
+    }
+
+    // Split by the clan's rules, rounding down; the leader takes the dust
+    let amount = tournament_prize(tourney.prize_pool, position, tourney.standings.len());
+    let mut shares: Vec<u64> = clan
+        .split_bps
+        .iter()
+        .map(|&bps| (amount as u128 * bps as u128 / BPS_DENOMINATOR as u128) as u64)
+        .collect();
+    let dust = amount - shares.iter().sum::<u64>();
+    if let Some(leader_seat) = clan.members.iter().position(|m| *m == clan.leader) {
+        shares[leader_seat] += dust;
+    }
+
+    for (member, share) in clan.members.iter().zip(shares) {
+        let wallet = next_account_info(ai)?;
+        if wallet.key != member {
+            msg!("Member wallets must follow the clan roster");
+            return Err(RPSError::InvalidParameter.into());
+        }
+        if share > 0 {
+            pay_from_program_account(tourney_account, wallet, share)?;
+        }
+    }
+    tourney.prizes_claimed |= bit;
+
+    tourney.serialize(&mut *tourney_account.data.borrow_mut())?;
+    debug_msg!("Paid {} to clan {} for placing {}", amount, clan.name, position + 1);
+    emit_event(&RPSEvent::PrizePaid {
+        tournament: *tourney_account.key,
+        recipient: *clan_account.key,
+        placing: position as u8 + 1,
+        amount,
+    });
+    Ok(())
+}
+
+/* ─────────────────────  Bracket helpers  ───────────────────────── */
+
+// Bracket match that is ready to be played: tournament in progress, both
+// players known, no winner yet
+// A free, manual game deciding bracket match `match_index`, with its seats
+// reserved for `lineup` (side A then side B); the prize is the
+// tournament's, so nothing is staked here
+fn match_game(
+    host: Pubkey,
+    tournament: Pubkey,
+    match_index: u8,
+    lineup: Vec<Pubkey>,
+    total_rounds: u8,
+    timeout_seconds: u64,
+    now: u64,
+) -> Game {
+    let seats = lineup.len() as u8;
+    Game {
+        host,
+        players: Vec::new(),
+        min_players: seats,
+        max_players: seats,
+        state: GameState::WaitingForPlayers,
+        current_round: 1,
+        total_rounds,
+        entry_fee: 0,
+        game_pot: 0,
+        required_timeout: timeout_seconds,
+        last_action_timestamp: now,
+        player_count: seats,
+        losers_can_rejoin: false,
+        game_mode: GameMode::Manual,
+        auto_round_delay: 0,
+        max_auto_rounds: 0,
+        current_auto_round: 0,
+        currency_mode: CurrencyMode::SOL,
+        fee_collected: 0,
+        token_mint: None,
+        base_price_feed: None,
+        base_decimals: 0,
+        stake_vaults: Vec::new(),
+        phase_nonce: 0,
+        claimed_bitmap: 0,
+        pot_snapshot: 0,
+        tournament: Some(tournament),
+        tournament_match: match_index,
+        phase_deadline: now.saturating_add(timeout_seconds),
+        allow_public_bots: false,
+        commitment_scheme: CommitmentScheme::HmacSha512,
+        team_lineup: lineup,
+        phase_started_at: now,
+        participation_bitmap: 0,
+        flagged: false,
+        flag_reasons: 0,
+        seat_watch: Vec::new(),
+        bot_funders: Vec::new(),
+        pending_claims: Vec::new(),
+        escrows: Vec::new(),
+        pot_subsidy: 0,
+        layout_version: GAME_LAYOUT_VERSION,
+        player_count_drawn: true,
+        reveal_order: Vec::new(),
+        hidden_fee: None,
+        theme_id: 0,
+        fee_bps: None,
+        rule_set: RuleSet::Classic,
+        winners: 0,
+        cancelled: false,
+        join_timeout: 0,
+        commit_timeout: 0,
+        reveal_timeout: 0,
+        crank_reward_lamports: 0,
+        crank_bounty: 0,
+        slash_non_revealers: false,
+        slashed: Vec::new(),
+        bot_config: Vec::new(),
+        settlement: Settlement::default(),
+    }
+}
+
+fn open_bracket_match(tourney: &TournamentState, match_index: u8) -> Result<BracketMatch, ProgramError> {
+    if !tourney.is_started || tourney.is_finished {
+        msg!("Tournament is not in progress");
+        return Err(RPSError::InvalidGameState.into());
+    }
+    let bracket_match = tourney.bracket.get(match_index as usize).copied().ok_or_else(|| {
+        msg!("Match {} is not in the bracket", match_index);
+        RPSError::InvalidParameter
+    })?;
+    if bracket_match.winner.is_some() {
+        msg!("Match {} is already decided", match_index);
+        return Err(RPSError::InvalidGameState.into());
+    }
+    if bracket_match.player_a.is_none() || bracket_match.player_b.is_none() {
+        msg!("Match {} is still waiting for its players", match_index);
+        return Err(RPSError::InvalidGameState.into());
+    }
+    Ok(bracket_match)
+}
+
+// Seed a single-elimination bracket: player i meets player i + size/2, so
+// when the field isn't a power of two the byes go to the earliest joiners
+fn seed_bracket(players: &[Pubkey]) -> Vec<BracketMatch> {
+    let size = players.len().next_power_of_two();
+    let mut bracket = vec![BracketMatch::default(); size - 1];
+    for (i, slot) in bracket.iter_mut().take(size / 2).enumerate() {
+        slot.player_a = players.get(i).copied();
+        slot.player_b = players.get(i + size / 2).copied();
+    }
+
+    // Byes advance straight away
+    for i in 0..size / 2 {
+        if let (Some(a), None) = (bracket[i].player_a, bracket[i].player_b) {
+            record_match_winner(&mut bracket, i, a);
+        }
+    }
+    bracket
+}
+
+// Settle every ready match that has a disqualified player in it. Winners
+// only ever move to later matches, so one forward pass follows a walkover
+// all the way up the bracket. If both players are disqualified the first
+// seat advances, to be walked over in turn.
+fn apply_walkovers(tourney: &mut TournamentState) {
+    for index in 0..tourney.bracket.len() {
+        let m = tourney.bracket[index];
+        let (Some(a), Some(b)) = (m.player_a, m.player_b) else {
+            continue;
+        };
+        if m.winner.is_some() {
+            continue;
+        }
+        let winner = match (tourney.disqualified.contains(&a), tourney.disqualified.contains(&b)) {
+            (false, true) | (true, true) => a,
+            (true, false) => b,
+            (false, false) => continue,
+        };
+        record_match_winner(&mut tourney.bracket, index, winner);
+    }
+}
+
+// Set a match winner and seat them in the match they advance into
+fn record_match_winner(bracket: &mut [BracketMatch], index: usize, winner: Pubkey) {
+    bracket[index].winner = Some(winner);
+    if let Some((next, seat_b)) = next_match(bracket.len(), index) {
+        if seat_b {
+            bracket[next].player_b = Some(winner);
+        } else {
+            bracket[next].player_a = Some(winner);
+        }
+    }
+}
+
+// Match the winner of `index` advances into, and whether they take seat B.
+// None for the final.
+fn next_match(bracket_len: usize, index: usize) -> Option<(usize, bool)> {
+    let mut start = 0;
+    let mut width = bracket_len.div_ceil(2);
+    while width > 1 {
+        if index < start + width {
+            let offset = index - start;
+            return Some((start + width + offset / 2, offset % 2 == 1));
+        }
+        start += width;
+        width /= 2;
+    }
+    None
+}
+
+// Record the champion and standings once the final is decided. Disqualified
+// players drop below everyone who finished in good standing, even if a
+// double walkover carried them up the bracket.
+fn settle_standings(tourney: &mut TournamentState) -> Pubkey {
+    let mut standings = final_standings(&tourney.bracket);
+    standings.sort_by_key(|p| tourney.disqualified.contains(p));
+    let champion = standings[0];
+    tourney.champion = Some(champion);
+    tourney.standings = standings;
+    champion
+}
+
+// Champion first, then the losers of each round from the final backwards
+fn final_standings(bracket: &[BracketMatch]) -> Vec<Pubkey> {
+    let mut standings: Vec<Pubkey> = bracket.last().and_then(|m| m.winner).into_iter().collect();
+    let mut end = bracket.len();
+    let mut width = 1;
+    while end > 0 {
+        let start = end - width;
+        standings.extend(bracket[start..end].iter().filter_map(|m| m.loser()));
+        end = start;
+        width *= 2;
+    }
+    standings
+}
+
+// Prize for a standings position; the champion also takes the rounding dust
+fn tournament_prize(prize_pool: u64, position: usize, placed: usize) -> u64 {
+    let paid_places = placed.min(TOURNAMENT_PRIZE_SPLIT_BPS.len());
+    let share = |i: usize| {
+        (prize_pool as u128 * TOURNAMENT_PRIZE_SPLIT_BPS[i] as u128 / BPS_DENOMINATOR as u128) as u64
+    };
+    if position == 0 {
+        prize_pool - (1..paid_places).map(share).sum::<u64>()
+    } else {
+        share(position)
+    }
+}
+
+
+/* ╔══════════════════════════════════════════════════════════════════╗
+   ║                             CLANS                                ║
+   ╚══════════════════════════════════════════════════════════════════╝ */
+
+// A named team entered into team tournaments; see `clan_address`
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct Clan {
+    pub leader: Pubkey,
+    pub name: String,
+    pub members: Vec<Pubkey>,  // Roster; the first two form the match lineup
+    pub split_bps: Vec<u16>,   // Prize share per member, parallel to `members`
+}
+
+impl Clan {
+    pub fn unpack_from(data: &[u8]) -> Result<Self, ProgramError> {
+        let mut slice = data;
+        Self::deserialize(&mut slice).map_err(|_| ProgramError::InvalidAccountData)
+    }
+}
+
+// Loads a clan, checking the account is the PDA its name derives
+fn load_clan(program_id: &Pubkey, clan_account: &AccountInfo) -> Result<Clan, ProgramError> {
+    assert_owned_by(clan_account, program_id)?;
+    let clan = Clan::unpack_from(&clan_account.data.borrow())?;
+    if clan_address(program_id, &clan.name).0 != *clan_account.key {
+        msg!("Account is not a clan");
+        return Err(RPSError::InvalidParameter.into());
+    }
+    Ok(clan)
+}
+
+/* ─────────────────────  Create Clan  ───────────────────────────── */
+fn process_create_clan(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    name: String,
+    members: Vec<Pubkey>,
+    split_bps: Vec<u16>,
+) -> ProgramResult {
+    let ai = &mut accounts.iter();
+    let leader = next_account_info(ai)?;
+    let clan_account = next_account_info(ai)?;
+    let system_program = next_account_info(ai)?;
+    check_system_program(system_program)?;
+
+    if !leader.is_signer {
+        return Err(RPSError::NotAuthorized.into());
+    }
+    if name.is_empty() || name.len() > MAX_CLAN_NAME_LEN {
+        msg!("Clan names are 1 to {} bytes", MAX_CLAN_NAME_LEN);
+        return Err(RPSError::InvalidParameter.into());
+    }
+    if members.len() < TEAM_MATCH_SIZE || members.len() > MAX_CLAN_MEMBERS || split_bps.len() != members.len() {
+        msg!("A clan has {} to {} members, each with a prize share", TEAM_MATCH_SIZE, MAX_CLAN_MEMBERS);
+        return Err(RPSError::InvalidParameter.into());
+    }
+    if !members.contains(leader.key) {
+        msg!("The leader must be on the roster");
+        return Err(RPSError::InvalidParameter.into());
+    }
+    if members.iter().enumerate().any(|(i, m)| members[..i].contains(m)) {
+        msg!("Roster lists a member twice");
+        return Err(RPSError::InvalidParameter.into());
+    }
+    if split_bps.iter().map(|&bps| bps as u64).sum::<u64>() != BPS_DENOMINATOR {
+        msg!("Prize shares must add up to {} bps", BPS_DENOMINATOR);
+        return Err(RPSError::InvalidParameter.into());
+    }
+
+    let (clan_key, bump) = clan_address(program_id, &name);
+    if *clan_account.key != clan_key {
+        msg!("Clan account is not the PDA for this name");
+        return Err(RPSError::InvalidParameter.into());
+    }
+    if clan_account.owner == program_id {
+        msg!("Clan name is taken");
+        return Err(RPSError::InvalidGameState.into());
+    }
+
+    let clan = Clan {
+        leader: *leader.key,
+        name,
+        members,
+        split_bps,
+    };
+    let data = clan.try_to_vec()?;
+
+    let rent = Rent::get()?;
+    invoke_signed(
+        &system_instruction::create_account(
+            leader.key,
+            clan_account.key,
+            rent.minimum_balance(data.len()),
+            data.len() as u64,
+            program_id,
+        ),
+        &[leader.clone(), clan_account.clone(), system_program.clone()],
+        &[&[CLAN_SEED, clan.name.as_bytes(), &[bump]]],
+    )?;
+    clan_account.data.borrow_mut().copy_from_slice(&data);
+    debug_msg!("Clan {} created with {} members", clan.name, clan.members.len());
+    Ok(())
+}
+
+
+/* ╔══════════════════════════════════════════════════════════════════╗
+   ║                  PROGRAM CONFIG & BAN LIST                       ║
+   ╚══════════════════════════════════════════════════════════════════╝ */
+
+// Program-wide operator settings. The account is allocated with headroom
+// so fields appended later read as zero on accounts created before them.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct ProgramConfig {
+    pub authority: Pubkey, // Operator allowed to manage program-wide settings
+    pub compliance_mode: bool, // Real-money entries require a credential
+    pub attestors: Vec<Pubkey>, // Approved credential issuers
+    pub timeout_multiplier_pct: u16, // Adaptive default timeout as % of recent action latency; 0 = off
+    pub min_timeout_secs: u64,   // Bounds the adaptive default is clamped to
+    pub max_timeout_secs: u64,
+    pub default_timeout_secs: u64, // Inherited by games created without a timeout; 0 = none
+    pub timeout_refreshed_at: u64,
+    pub refreshed_actions: u64,  // `GlobalStats` action count at the last refresh
+    pub refreshed_latency_secs: u64, // and its summed latency
+    pub hold_flagged_payouts: bool, // Flagged games pay out only once cleared
+    pub large_payout_threshold: u64, // Pots above this pay out in two steps; 0 = off
+    pub claim_delay_secs: u64,   // Wait between InitiateClaim and FinalizeClaim
+    pub guaranteed_min_pot: u64, // Treasury tops small SOL pots up to this; 0 = off
+    pub subsidy_entry_floor: u64, // Smallest entry fee a game needs to be topped up
+    pub paused_instructions: u128, // Bit n pauses the instruction with discriminant n
+    pub token_entries_paused: bool, // Pauses entries into RPSToken games only
+    pub nickname_fee: u64,       // Token fee for SetNickname; 0 = free
+    pub nickname_fee_mint: Option<Pubkey>,
+    pub fee_collector: Option<Pubkey>, // Sole collector of held fees; None = anyone, once the game is finished
+    pub fee_bps: Option<u16>,    // Platform fee rate of new games; None = simulate::DEFAULT_FEE_BPS
+    pub payout_guard: bool,      // Payouts must run alone; see `check_transaction_guard`
+    pub governed: bool,          // Fee and payout settings change only by stakers' vote; see `Governance`
+}
+
+impl ProgramConfig {
+    pub const SPACE: usize = 512;
+
+    pub fn unpack_from(data: &[u8]) -> Result<Self, ProgramError> {
+        let mut slice = data;
+        Self::deserialize(&mut slice).map_err(|_| ProgramError::InvalidAccountData)
+    }
+}
+
+// One shard of the ban list; see `ban_list_address`
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Default)]
+pub struct BanList {
+    pub wallets: Vec<Pubkey>,
+}
+
+impl BanList {
+    pub const SPACE: usize = 4 + BAN_LIST_CHUNK_CAPACITY * 32;
+
+    pub fn unpack_from(data: &[u8]) -> Result<Self, ProgramError> {
+        let mut slice = data;
+        Self::deserialize(&mut slice).map_err(|_| ProgramError::InvalidAccountData)
+    }
+}
+
+// Load the config PDA, checking it is the real one
+fn load_config(program_id: &Pubkey, config_account: &AccountInfo) -> Result<ProgramConfig, ProgramError> {
+    if *config_account.key != config_address(program_id).0 || config_account.owner != program_id {
+        msg!("Config account is not the program config");
+        return Err(RPSError::InvalidParameter.into());
+    }
+    ProgramConfig::unpack_from(&config_account.data.borrow())
+}
+
+// Load the config PDA if the operator has created it; None means no
+// operator settings at all
+fn load_config_if_any(program_id: &Pubkey, config_account: &AccountInfo) -> Result<Option<ProgramConfig>, ProgramError> {
+    if *config_account.key != config_address(program_id).0 {
+        msg!("Config account is not the program config");
+        return Err(RPSError::InvalidParameter.into());
+    }
+    if config_account.owner != program_id {
+        return Ok(None);
+    }
+    ProgramConfig::unpack_from(&config_account.data.borrow()).map(Some)
+}
+
+// Fee rate for a game or tournament created now: the configured one, or
+// None (the default rate) while no config exists
+fn config_fee_bps(program_id: &Pubkey, config_account: &AccountInfo) -> Result<Option<u16>, ProgramError> {
+    Ok(load_config_if_any(program_id, config_account)?.and_then(|config| config.fee_bps))
+}
+
+// Transaction guard: with `payout_guard` on, a payout must be a top-level
+// instruction, not a CPI, and share its transaction only with instructions
+// of this program and the compute budget program. Nothing can then run
+// before or after it to take advantage of the funds it moves.
+fn check_transaction_guard(
+    program_id: &Pubkey,
+    config: Option<&ProgramConfig>,
+    instructions_sysvar: &AccountInfo,
+) -> ProgramResult {
+    if *instructions_sysvar.key != solana_program::sysvar::instructions::id() {
+        msg!("Instructions sysvar account is not the sysvar");
+        return Err(RPSError::InvalidParameter.into());
+    }
+    if !config.is_some_and(|config| config.payout_guard) {
+        return Ok(());
+    }
+    let current = load_current_index_checked(instructions_sysvar)? as usize;
+    let mut index = 0;
+    while let Ok(instruction) = load_instruction_at_checked(index, instructions_sysvar) {
+        // Under CPI the top-level instruction belongs to the caller
+        let allowed = instruction.program_id == *program_id
+            || (index != current && instruction.program_id == COMPUTE_BUDGET_PROGRAM_ID);
+        if !allowed {
+            msg!("Instruction {} of the transaction calls {}", index, instruction.program_id);
+            return Err(RPSError::UnexpectedInstruction.into());
+        }
+        index += 1;
+    }
+    Ok(())
+}
+
+// Load the config and require its authority to have signed
+fn require_config_authority(
+    program_id: &Pubkey,
+    authority: &AccountInfo,
+    config_account: &AccountInfo,
+) -> Result<ProgramConfig, ProgramError> {
+    let config = load_config(program_id, config_account)?;
+    if !authority.is_signer || config.authority != *authority.key {
+        msg!("Only the config authority can do this");
+        return Err(RPSError::NotAuthorized.into());
+    }
+    Ok(config)
+}
+
+// Like `require_config_authority`, for the fee and payout settings, which
+// the authority gives up once governance is set up
+fn require_economics_authority(
+    program_id: &Pubkey,
+    authority: &AccountInfo,
+    config_account: &AccountInfo,
+) -> Result<ProgramConfig, ProgramError> {
+    let config = require_config_authority(program_id, authority, config_account)?;
+    if config.governed {
+        msg!("Fee and payout settings are changed by governance proposals");
+        return Err(RPSError::NotAuthorized.into());
+    }
+    Ok(config)
+}
+
+// Reject a banned wallet. The chunk is derived from the wallet, so it
+// cannot be swapped for another; a chunk nobody was banned into yet does
+// not exist and bans no one.
+fn check_not_banned(program_id: &Pubkey, ban_list_account: &AccountInfo, wallet: &Pubkey) -> ProgramResult {
+    if *ban_list_account.key != ban_list_address(program_id, wallet).0 {
+        msg!("Ban list account does not match the player");
+        return Err(RPSError::InvalidParameter.into());
+    }
+    if ban_list_account.owner != program_id {
+        return Ok(());
+    }
+    if BanList::unpack_from(&ban_list_account.data.borrow())?.wallets.contains(wallet) {
+        msg!("{} is banned", wallet);
+        return Err(RPSError::PlayerBanned.into());
+    }
+    Ok(())
+}
+
+// Reject a wallet that excluded itself with `SelfExclude` and is still
+// inside the exclusion. Wallets that never did have no exclusion account.
+fn check_not_excluded(program_id: &Pubkey, exclusion_account: &AccountInfo, wallet: &Pubkey) -> ProgramResult {
+    if *exclusion_account.key != exclusion_address(program_id, wallet).0 {
+        msg!("Self-exclusion account does not match the player");
+        return Err(RPSError::InvalidParameter.into());
+    }
+    if exclusion_account.owner != program_id {
+        return Ok(());
+    }
+    let exclusion = SelfExclusion::unpack_from(&exclusion_account.data.borrow())?;
+    if (Clock::get()?.unix_timestamp as u64) < exclusion.until {
+        msg!("{} is self-excluded until {}", wallet, exclusion.until);
+        return Err(RPSError::SelfExcluded.into());
+    }
+    Ok(())
+}
+
+// Under compliance mode a paid entry needs a credential: a token of a mint
+// whose mint and freeze authority is an approved attestor, held frozen in
+// the player's own account so it cannot be passed around. The credential
+// token account and mint are read from `accounts` only when required.
+fn check_compliance<'a, 'b>(
+    program_id: &Pubkey,
+    config_account: &'b AccountInfo<'a>,
+    accounts: &mut std::slice::Iter<'b, AccountInfo<'a>>,
+    player: &Pubkey,
+    entry_fee: u64,
+) -> ProgramResult {
+    if *config_account.key != config_address(program_id).0 {
+        msg!("Config account is not the program config");
+        return Err(RPSError::InvalidParameter.into());
+    }
+    // No config yet means no operator settings at all
+    if config_account.owner != program_id || entry_fee == 0 {
+        return Ok(());
+    }
+    let config = ProgramConfig::unpack_from(&config_account.data.borrow())?;
+    if !config.compliance_mode {
+        return Ok(());
+    }
+
+    let credential_account = next_account_info(accounts)?;
+    let credential_mint = next_account_info(accounts)?;
+    let credential = unpack_token_account(credential_account).map_err(|_| RPSError::MissingCredential)?;
+    assert_owned_by(credential_mint, &spl_token::id())?;
+    let mint = spl_token::state::Mint::unpack(&credential_mint.data.borrow())
+        .map_err(|_| RPSError::MissingCredential)?;
+
+    let attestor = match mint.mint_authority {
+        COption::Some(attestor) if config.attestors.contains(&attestor) => attestor,
+        _ => {
+            msg!("Credential mint is not issued by an approved attestor");
+            return Err(RPSError::MissingCredential.into());
+        }
+    };
+    if credential.owner != *player
+        || credential.mint != *credential_mint.key
+        || credential.amount == 0
+        || !credential.is_frozen()
+        || mint.freeze_authority != COption::Some(attestor)
+    {
+        msg!("Player does not hold a valid compliance credential");
+        return Err(RPSError::MissingCredential.into());
+    }
+    Ok(())
+}
+
+/* ─────────────────────  Initialize Config  ─────────────────────── */
+fn process_initialize_config(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let ai = &mut accounts.iter();
+    let authority = next_account_info(ai)?;
+    let config_account = next_account_info(ai)?;
+    let system_program = next_account_info(ai)?;
+    check_system_program(system_program)?;
+
+    if !authority.is_signer {
+        return Err(RPSError::NotAuthorized.into());
+    }
+    let (config_key, bump) = config_address(program_id);
+    if *config_account.key != config_key {
+        msg!("Config account is not the program config PDA");
+        return Err(RPSError::InvalidParameter.into());
+    }
+    if config_account.owner == program_id {
+        msg!("Config already initialized");
+        return Err(RPSError::InvalidGameState.into());
+    }
+
+    let rent = Rent::get()?;
+    invoke_signed(
+        &system_instruction::create_account(
+            authority.key,
+            config_account.key,
+            rent.minimum_balance(ProgramConfig::SPACE),
+            ProgramConfig::SPACE as u64,
+            program_id,
+        ),
+        &[authority.clone(), config_account.clone(), system_program.clone()],
+        &[&[CONFIG_SEED, &[bump]]],
+    )?;
+
+    let config = ProgramConfig {
+        authority: *authority.key,
+        compliance_mode: false,
+        attestors: vec![],
+        timeout_multiplier_pct: 0,
+        min_timeout_secs: 0,
+        max_timeout_secs: 0,
+        default_timeout_secs: 0,
+        timeout_refreshed_at: 0,
+        refreshed_actions: 0,
+        refreshed_latency_secs: 0,
+        hold_flagged_payouts: false,
+        large_payout_threshold: 0,
+        claim_delay_secs: 0,
+        guaranteed_min_pot: 0,
+        subsidy_entry_floor: 0,
+        paused_instructions: 0,
+        token_entries_paused: false,
+        nickname_fee: 0,
+        nickname_fee_mint: None,
+        fee_collector: None,
+        fee_bps: None,
+        payout_guard: false,
+        governed: false,
+    };
+    config.serialize(&mut *config_account.data.borrow_mut())?;
+    debug_msg!("Program config initialized, authority {}", authority.key);
+    Ok(())
+}
+
+/* ─────────────────────  Ban / Unban Player  ────────────────────── */
+fn process_ban_player(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    wallet: Pubkey,
+) -> ProgramResult {
+    let ai = &mut accounts.iter();
+    let authority = next_account_info(ai)?;
+    let config_account = next_account_info(ai)?;
+    let ban_list_account = next_account_info(ai)?;
+    let system_program = next_account_info(ai)?;
+    check_system_program(system_program)?;
+
+    require_config_authority(program_id, authority, config_account)?;
+    let (chunk_key, bump) = ban_list_address(program_id, &wallet);
+    if *ban_list_account.key != chunk_key {
+        msg!("Ban list account does not match the wallet");
+        return Err(RPSError::InvalidParameter.into());
+    }
+
+    // First ban in this shard: create the chunk
+    if ban_list_account.owner != program_id {
+        let rent = Rent::get()?;
+        invoke_signed(
+            &system_instruction::create_account(
+                authority.key,
+                ban_list_account.key,
+                rent.minimum_balance(BanList::SPACE),
+                BanList::SPACE as u64,
+                program_id,
+            ),
+            &[authority.clone(), ban_list_account.clone(), system_program.clone()],
+            &[&[BAN_LIST_SEED, &wallet.as_ref()[..1], &[bump]]],
+        )?;
+    }
+
+    let mut ban_list = BanList::unpack_from(&ban_list_account.data.borrow())?;
+    if ban_list.wallets.contains(&wallet) {
+        msg!("{} is already banned", wallet);
+        return Err(RPSError::InvalidParameter.into());
+    }
+    if ban_list.wallets.len() >= BAN_LIST_CHUNK_CAPACITY {
+        msg!("Ban list chunk is full");
+        return Err(RPSError::InvalidParameter.into());
+    }
+
+    ban_list.wallets.push(wallet);
+    ban_list.serialize(&mut *ban_list_account.data.borrow_mut())?;
+    debug_msg!("{} banned", wallet);
+    Ok(())
+}
+
+fn process_unban_player(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    wallet: Pubkey,
+) -> ProgramResult {
+    let ai = &mut accounts.iter();
+    let authority = next_account_info(ai)?;
+    let config_account = next_account_info(ai)?;
+    let ban_list_account = next_account_info(ai)?;
+
+    require_config_authority(program_id, authority, config_account)?;
+    if *ban_list_account.key != ban_list_address(program_id, &wallet).0 || ban_list_account.owner != program_id {
+        msg!("Ban list account does not match the wallet");
+        return Err(RPSError::InvalidParameter.into());
+    }
+
+    let mut ban_list = BanList::unpack_from(&ban_list_account.data.borrow())?;
+    let Some(position) = ban_list.wallets.iter().position(|w| *w == wallet) else {
+        msg!("{} is not banned", wallet);
+        return Err(RPSError::PlayerNotFound.into());
+    };
+
+    ban_list.wallets.swap_remove(position);
+    ban_list.serialize(&mut *ban_list_account.data.borrow_mut())?;
+    debug_msg!("{} unbanned", wallet);
+    Ok(())
+}
+
+/* ─────────────────────  Set Compliance  ────────────────────────── */
+fn process_set_compliance(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    enabled: bool,
+    attestors: Vec<Pubkey>,
+) -> ProgramResult {
+    let ai = &mut accounts.iter();
+    let authority = next_account_info(ai)?;
+    let config_account = next_account_info(ai)?;
+
+    let mut config = require_config_authority(program_id, authority, config_account)?;
+    if attestors.len() > MAX_ATTESTORS {
+        msg!("At most {} attestors are allowed", MAX_ATTESTORS);
+        return Err(RPSError::InvalidParameter.into());
+    }
+    if enabled && attestors.is_empty() {
+        msg!("Compliance mode needs at least one attestor");
+        return Err(RPSError::InvalidParameter.into());
+    }
+
+    config.compliance_mode = enabled;
+    config.attestors = attestors;
+    config.serialize(&mut *config_account.data.borrow_mut())?;
+    debug_msg!("Compliance mode {}", if enabled { "on" } else { "off" });
+    Ok(())
+}
+
+/* ─────────────────────  Set Timeout Policy  ────────────────────── */
+fn process_set_timeout_policy(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    multiplier_pct: u16,
+    min_secs: u64,
+    max_secs: u64,
+) -> ProgramResult {
+    let ai = &mut accounts.iter();
+    let authority = next_account_info(ai)?;
+    let config_account = next_account_info(ai)?;
+
+    let mut config = require_config_authority(program_id, authority, config_account)?;
+    if multiplier_pct > 0 && (min_secs == 0 || min_secs > max_secs) {
+        msg!("Timeout bounds must satisfy 0 < min <= max");
+        return Err(RPSError::InvalidParameter.into());
+    }
+
+    config.timeout_multiplier_pct = multiplier_pct;
+    config.min_timeout_secs = min_secs;
+    config.max_timeout_secs = max_secs;
+    config.default_timeout_secs = match (multiplier_pct, config.default_timeout_secs) {
+        (0, _) => 0,
+        (_, 0) => max_secs,
+        (_, current) => current.clamp(min_secs, max_secs),
+    };
+    config.serialize(&mut *config_account.data.borrow_mut())?;
+    debug_msg!("Default timeout is now {}s", config.default_timeout_secs);
+    Ok(())
+}
+
+/* ─────────────────────  Refresh Default Timeout  ───────────────── */
+fn process_refresh_default_timeout(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let ai = &mut accounts.iter();
+    let caller = next_account_info(ai)?;
+    let config_account = next_account_info(ai)?;
+    let stats_account = next_account_info(ai)?;
+
+    if !caller.is_signer {
+        return Err(RPSError::NotAuthorized.into());
+    }
+    let mut config = load_config(program_id, config_account)?;
+    if config.timeout_multiplier_pct == 0 {
+        msg!("Adaptive timeouts are off");
+        return Err(RPSError::InvalidGameState.into());
+    }
+    if *stats_account.key != stats_address(program_id).0 {
+        msg!("Stats account is not the stats PDA");
+        return Err(RPSError::InvalidParameter.into());
+    }
+    assert_owned_by(stats_account, program_id)?;
+    let stats = GlobalStats::unpack_from(&stats_account.data.borrow())?;
+
+    // Average only what happened since the last refresh, so the default
+    // follows current conditions rather than the all-time mean
+    let actions = stats.commits.saturating_add(stats.reveals);
+    let latency = stats.commit_latency_secs.saturating_add(stats.reveal_latency_secs);
+    let new_actions = actions.saturating_sub(config.refreshed_actions);
+    if new_actions < MIN_TIMEOUT_SAMPLE {
+        msg!("Only {} new actions since the last refresh; {} needed", new_actions, MIN_TIMEOUT_SAMPLE);
+        return Err(RPSError::InvalidGameState.into());
+    }
+    let average = latency.saturating_sub(config.refreshed_latency_secs) / new_actions;
+    let scaled = (average as u128 * config.timeout_multiplier_pct as u128 / 100) as u64;
+
+    config.default_timeout_secs = scaled.clamp(config.min_timeout_secs, config.max_timeout_secs);
+    config.timeout_refreshed_at = Clock::get()?.unix_timestamp as u64;
+    config.refreshed_actions = actions;
+    config.refreshed_latency_secs = latency;
+    config.serialize(&mut *config_account.data.borrow_mut())?;
+    debug_msg!("Default timeout refreshed to {}s", config.default_timeout_secs);
+    Ok(())
+}
+
+/* ─────────────────────  Set Collusion Policy  ──────────────────── */
+fn process_set_collusion_policy(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    hold_flagged_payouts: bool,
+) -> ProgramResult {
+    let ai = &mut accounts.iter();
+    let authority = next_account_info(ai)?;
+    let config_account = next_account_info(ai)?;
+
+    let mut config = require_config_authority(program_id, authority, config_account)?;
+    config.hold_flagged_payouts = hold_flagged_payouts;
+    config.serialize(&mut *config_account.data.borrow_mut())?;
+    debug_msg!("Flagged payouts {}", if hold_flagged_payouts { "held" } else { "released" });
+    Ok(())
+}
+
+/* ─────────────────────  Clear Game Flag  ───────────────────────── */
+fn process_clear_game_flag(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let ai = &mut accounts.iter();
+    let authority = next_account_info(ai)?;
+    let config_account = next_account_info(ai)?;
+    let game_account = next_account_info(ai)?;
+
+    require_config_authority(program_id, authority, config_account)?;
+    assert_owned_by(game_account, program_id)?;
+    let mut game = Game::unpack_from(&game_account.data.borrow())?;
+    if !game.flagged {
+        msg!("Game is not flagged");
+        return Err(RPSError::InvalidGameState.into());
+    }
+    game.flagged = false;
+    game.serialize(&mut *game_account.data.borrow_mut())?;
+    debug_msg!("Collusion flag cleared on {}", game_account.key);
+    Ok(())
+}
+
+/* ─────────────────────  Set Fee Policy  ────────────────────────── */
+fn process_set_fee_policy(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    fee_collector: Option<Pubkey>,
+    fee_bps: Option<u16>,
+) -> ProgramResult {
+    let ai = &mut accounts.iter();
+    let authority = next_account_info(ai)?;
+    let config_account = next_account_info(ai)?;
+
+    let mut config = require_economics_authority(program_id, authority, config_account)?;
+    apply_fee_policy(&mut config, fee_collector, fee_bps)?;
+    config.serialize(&mut *config_account.data.borrow_mut())?;
+    debug_msg!("New games pay a {:?} bps fee", fee_bps);
+    Ok(())
+}
+
+fn apply_fee_policy(config: &mut ProgramConfig, fee_collector: Option<Pubkey>, fee_bps: Option<u16>) -> ProgramResult {
+    if fee_bps.is_some_and(|bps| bps > MAX_FEE_BPS) {
+        msg!("Fee rate above the {} bps cap", MAX_FEE_BPS);
+        return Err(RPSError::InvalidParameter.into());
+    }
+    config.fee_collector = fee_collector;
+    config.fee_bps = fee_bps;
+    Ok(())
+}
+
+/* ─────────────────────  Set Payout Guard  ──────────────────────── */
+fn process_set_payout_guard(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    enabled: bool,
+) -> ProgramResult {
+    let ai = &mut accounts.iter();
+    let authority = next_account_info(ai)?;
+    let config_account = next_account_info(ai)?;
+
+    let mut config = require_config_authority(program_id, authority, config_account)?;
+    config.payout_guard = enabled;
+    config.serialize(&mut *config_account.data.borrow_mut())?;
+    debug_msg!("Payout guard {}", if enabled { "on" } else { "off" });
+    Ok(())
+}
+
+/* ─────────────────────  Set Claim Policy  ──────────────────────── */
+fn process_set_claim_policy(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    large_payout_threshold: u64,
+    claim_delay_secs: u64,
+) -> ProgramResult {
+    let ai = &mut accounts.iter();
+    let authority = next_account_info(ai)?;
+    let config_account = next_account_info(ai)?;
+
+    let mut config = require_economics_authority(program_id, authority, config_account)?;
+    apply_claim_policy(&mut config, large_payout_threshold, claim_delay_secs)?;
+    config.serialize(&mut *config_account.data.borrow_mut())?;
+    debug_msg!("Pots above {} now wait {}s to pay out", large_payout_threshold, claim_delay_secs);
+    Ok(())
+}
+
+fn apply_claim_policy(config: &mut ProgramConfig, large_payout_threshold: u64, claim_delay_secs: u64) -> ProgramResult {
+    if large_payout_threshold > 0 && claim_delay_secs == 0 {
+        msg!("Two-step payouts need a delay");
+        return Err(RPSError::InvalidParameter.into());
+    }
+    config.large_payout_threshold = large_payout_threshold;
+    config.claim_delay_secs = claim_delay_secs;
+    Ok(())
+}
+
+fn process_set_pot_guarantee(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    guaranteed_min_pot: u64,
+    subsidy_entry_floor: u64,
+) -> ProgramResult {
+    let ai = &mut accounts.iter();
+    let authority = next_account_info(ai)?;
+    let config_account = next_account_info(ai)?;
+
+    let mut config = require_economics_authority(program_id, authority, config_account)?;
+    apply_pot_guarantee(&mut config, guaranteed_min_pot, subsidy_entry_floor)?;
+    config.serialize(&mut *config_account.data.borrow_mut())?;
+    debug_msg!("Pots of games entering at {}+ guaranteed {}", subsidy_entry_floor, guaranteed_min_pot);
+    Ok(())
+}
+
+fn apply_pot_guarantee(config: &mut ProgramConfig, guaranteed_min_pot: u64, subsidy_entry_floor: u64) -> ProgramResult {
+    if guaranteed_min_pot > 0 && subsidy_entry_floor == 0 {
+        msg!("Free games cannot be subsidized");
+        return Err(RPSError::InvalidParameter.into());
+    }
+    config.guaranteed_min_pot = guaranteed_min_pot;
+    config.subsidy_entry_floor = subsidy_entry_floor;
+    Ok(())
+}
+
+fn process_apply_pot_subsidy(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let ai = &mut accounts.iter();
+    let caller = next_account_info(ai)?;
+    let game_account = next_account_info(ai)?;
+    let config_account = next_account_info(ai)?;
+    let treasury_account = next_account_info(ai)?;
+    check_treasury(program_id, treasury_account)?;
+    let system_program = next_account_info(ai)?;
+    check_system_program(system_program)?;
+    let vault_account = next_account_info(ai)?;
+
+    if !caller.is_signer {
+        return Err(RPSError::NotAuthorized.into());
+    }
+    let config = load_config(program_id, config_account)?;
+    assert_owned_by(game_account, program_id)?;
+    let mut game = Game::unpack_from(&game_account.data.borrow())?;
+
+    // Only games in play: seats are final and the pot is not yet split
+    state_machine::require(game.state, Action::Play)?;
+    if !game.has_started() || game.pot_subsidy > 0 {
+        msg!("Only a started, unsubsidized game can be topped up");
+        return Err(RPSError::InvalidGameState.into());
+    }
+    if config.guaranteed_min_pot == 0
+        || !matches!(game.currency_mode, CurrencyMode::SOL)
+        || game.entry_fee < config.subsidy_entry_floor
+        || game.game_pot >= config.guaranteed_min_pot
+    {
+        msg!("Game does not qualify for the pot guarantee");
+        return Err(RPSError::InvalidGameState.into());
+    }
+
+    check_pot_vault(program_id, game_account.key, &game, vault_account)?;
+
+    let amount = config.guaranteed_min_pot - game.game_pot;
+    let available = treasury_account
+        .lamports()
+        .saturating_sub(Rent::get()?.minimum_balance(0));
+    if available < amount {
+        msg!("Treasury holds {} spendable lamports, {} needed", available, amount);
+        return Err(RPSError::InsufficientFunds.into());
+    }
+    let (_, bump) = treasury_address(program_id);
+    invoke_signed(
+        &system_instruction::transfer(treasury_account.key, vault_account.key, amount),
+        &[treasury_account.clone(), vault_account.clone(), system_program.clone()],
+        &[&[TREASURY_SEED, &[bump]]],
+    )?;
+
+    game.game_pot += amount;
+    game.pot_subsidy = amount;
+    game.serialize(&mut *game_account.data.borrow_mut())?;
+    debug_msg!("Treasury topped up {} by {}", game_account.key, amount);
+    emit_event(&RPSEvent::PotSubsidized {
+        game: *game_account.key,
+        amount,
+        pot: game.game_pot,
+    });
+    Ok(())
+}
+
+fn process_set_paused_instructions(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    paused_instructions: u128,
+    token_entries_paused: bool,
+) -> ProgramResult {
+    let ai = &mut accounts.iter();
+    let authority = next_account_info(ai)?;
+    let config_account = next_account_info(ai)?;
+
+    let mut config = require_config_authority(program_id, authority, config_account)?;
+    if paused_instructions & !PAUSABLE_INSTRUCTIONS != 0 {
+        msg!("Only instructions that carry the config PDA can be paused");
+        return Err(RPSError::InvalidParameter.into());
+    }
+    config.paused_instructions = paused_instructions;
+    config.token_entries_paused = token_entries_paused;
+    config.serialize(&mut *config_account.data.borrow_mut())?;
+    debug_msg!("Paused instructions {:#x}, token entries paused: {}", paused_instructions, token_entries_paused);
+    Ok(())
+}
+
+// Payout rules the operator set for `game`: whether flagged payouts are
+// held, and whether its pot is large enough to need two steps. No config
+// yet means no operator settings at all.
+fn payout_policy(
+    program_id: &Pubkey,
+    config_account: &AccountInfo,
+    game: &Game,
+) -> Result<(bool, Option<u64>), ProgramError> {
+    if *config_account.key != config_address(program_id).0 {
+        msg!("Config account is not the program config");
+        return Err(RPSError::InvalidParameter.into());
+    }
+    if config_account.owner != program_id {
+        return Ok((false, None));
+    }
+    let config = ProgramConfig::unpack_from(&config_account.data.borrow())?;
+    let large = config.large_payout_threshold > 0 && game.pot_snapshot > config.large_payout_threshold;
+    Ok((config.hold_flagged_payouts, large.then_some(config.claim_delay_secs)))
+}
+
+/* ╔══════════════════════════════════════════════════════════════════╗
+   ║                          CHALLENGES                              ║
+   ╚══════════════════════════════════════════════════════════════════╝ */
+
+// One side of a challenge: the challenger is always side 0, the named
+// opponent side 1
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct ChallengeSide {
+    pub player: Pubkey,
+    pub commitment: [u8; 64], // Zero until this side commits
+    pub choice: Choice,       // None until this side reveals
+}
+
+impl ChallengeSide {
+    pub const LEN: usize = 32 + 64 + 1;
+
+    pub fn committed(&self) -> bool {
+        self.commitment != [0; 64]
+    }
+
+    pub fn revealed(&self) -> bool {
+        !matches!(self.choice, Choice::None)
+    }
+}
+
+// A staked one-throw match between two named players. Only the challenger's
+// stake is escrowed until the opponent accepts. Once a decided challenge
+// pays out, the account stays open briefly as a record the loser can offer
+// double or nothing against.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct ChallengeState {
+    pub sides: Vec<ChallengeSide>,
+    pub stake: u64,
+    pub deadline: i64, // Accept by this while open, play by it once accepted
+    pub accepted: bool,
+    pub settled: bool, // Paid out; only a double-or-nothing offer remains
+    pub offer: u64,    // The loser's double-or-nothing escrow, zero until offered
+}
+
+impl ChallengeState {
+    pub const SPACE: usize = 4 + 2 * ChallengeSide::LEN + 8 + 8 + 1 + 1 + 8;
+
+    pub fn unpack_from(data: &[u8]) -> Result<Self, ProgramError> {
+        let mut slice = data;
+        if let Ok(challenge) = Self::deserialize(&mut slice) {
+            return Ok(challenge);
+        }
+        // Challenges opened before `settled` and `offer` existed end
+        // without them; they read as unsettled with no offer
+        let mut padded = data.to_vec();
+        padded.resize(data.len() + 1 + 8, 0);
+        let mut slice = padded.as_slice();
+        Self::deserialize(&mut slice).map_err(|_| ProgramError::InvalidAccountData)
+    }
+
+    // The side that lost outright, if the challenge was decided
+    fn loser(&self) -> Option<usize> {
+        if !self.accepted {
+            return None;
+        }
+        match self.payouts() {
+            [0, _] => Some(0),
+            [_, 0] => Some(1),
+            _ => None,
+        }
+    }
+
+    fn side_of(&self, key: &Pubkey) -> Option<usize> {
+        self.sides.iter().position(|side| side.player == *key)
+    }
+
+    // Lamports owed to each side if the challenge settled now
+    fn payouts(&self) -> [u64; 2] {
+        if !self.accepted {
+            return [self.stake, 0];
+        }
+        let pot = self.stake.saturating_mul(2);
+        // A side that revealed beats one that never did
+        let outcome = match (self.sides[0].choice.hand(), self.sides[1].choice.hand()) {
+            (Some(a), Some(b)) => rules::Classic.beats(a, b),
+            (Some(_), None) => Ordering::Greater,
+            (None, Some(_)) => Ordering::Less,
+            (None, None) => Ordering::Equal,
+        };
+        match outcome {
+            Ordering::Greater => [pot, 0],
+            Ordering::Less => [0, pot],
+            // Tie, or nobody revealed
+            Ordering::Equal => [self.stake, self.stake],
+        }
+    }
+}
+
+fn load_challenge(program_id: &Pubkey, challenge_account: &AccountInfo) -> Result<ChallengeState, ProgramError> {
+    assert_owned_by(challenge_account, program_id)?;
+    let challenge = ChallengeState::unpack_from(&challenge_account.data.borrow())?;
+    // A settled challenge is zeroed before it is reclaimed
+    if challenge.sides.len() != 2 {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    Ok(challenge)
+}
+
+// Checks the wallets passed for a challenge's two sides
+fn check_challenge_sides(challenge: &ChallengeState, challenger: &AccountInfo, opponent: &AccountInfo) -> ProgramResult {
+    if *challenger.key != challenge.sides[0].player || *opponent.key != challenge.sides[1].player {
+        msg!("Payout accounts do not match the challenge");
+        return Err(RPSError::InvalidParameter.into());
+    }
+    Ok(())
+}
+
+// Pay out a challenge. A decided one is kept open as a record for
+// `DOUBLE_OR_NOTHING_SECS`; anything else is closed, returning the rent to
+// the challenger.
+fn settle_challenge(
+    challenge_account: &AccountInfo,
+    mut challenge: ChallengeState,
+    challenger: &AccountInfo,
+    opponent: &AccountInfo,
+) -> ProgramResult {
+    check_challenge_sides(&challenge, challenger, opponent)?;
+    let payouts = challenge.payouts();
+    pay_from_program_account(challenge_account, challenger, payouts[0])?;
+    pay_from_program_account(challenge_account, opponent, payouts[1])?;
+    debug_msg!("Challenge {} settled: {} / {}", challenge_account.key, payouts[0], payouts[1]);
+    emit_event(&RPSEvent::ChallengeSettled {
+        challenge: *challenge_account.key,
+        payouts,
+    });
+
+    // Accounts opened under the older layout have no room for the record
+    if challenge.loser().is_some() && challenge_account.data_len() >= ChallengeState::SPACE {
+        challenge.settled = true;
+        challenge.deadline = Clock::get()?.unix_timestamp.saturating_add(DOUBLE_OR_NOTHING_SECS);
+        challenge.serialize(&mut *challenge_account.data.borrow_mut())?;
+        return Ok(());
+    }
+    close_program_account(challenge_account, challenger)
+}
+
+// Close a program account, e.g. a settled challenge, returning what is left
+// in it to `recipient`
+fn close_program_account(account: &AccountInfo, recipient: &AccountInfo) -> ProgramResult {
+    let remaining = account.lamports();
+    **account.try_borrow_mut_lamports()? = 0;
+    **recipient.try_borrow_mut_lamports()? = recipient
+        .lamports()
+        .checked_add(remaining)
+        .ok_or(RPSError::FeeCalculationError)?;
+    account.data.borrow_mut().fill(0);
+    Ok(())
+}
+
+/* ─────────────────────  Create Challenge  ──────────────────────── */
+fn process_create_challenge(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    opponent: Pubkey,
+    stake: u64,
+) -> ProgramResult {
+    let ai = &mut accounts.iter();
+    let challenger = next_account_info(ai)?;
+    let challenge_account = next_account_info(ai)?;
+    let system_program = next_account_info(ai)?;
+    check_system_program(system_program)?;
+
+    if !challenger.is_signer {
+        return Err(RPSError::NotAuthorized.into());
+    }
+    if opponent == *challenger.key || stake == 0 {
+        msg!("A challenge needs another player and a stake");
+        return Err(RPSError::InvalidParameter.into());
+    }
+    check_not_banned(program_id, next_account_info(ai)?, challenger.key)?;
+    check_not_excluded(program_id, next_account_info(ai)?, challenger.key)?;
+    check_compliance(program_id, next_account_info(ai)?, ai, challenger.key, stake)?;
+
+    /* account creation, funded with the challenger's stake */
+    let rent = Rent::get()?;
+    let lamports = rent
+        .minimum_balance(ChallengeState::SPACE)
+        .checked_add(stake)
+        .ok_or(RPSError::FeeCalculationError)?;
+    invoke(
+        &system_instruction::create_account(
+            challenger.key,
+            challenge_account.key,
+            lamports,
+            ChallengeState::SPACE as u64,
+            program_id,
+        ),
+        &[challenger.clone(), challenge_account.clone(), system_program.clone()],
+    )?;
+
+    let side = |player: Pubkey| ChallengeSide {
+        player,
+        commitment: [0; 64],
+        choice: Choice::None,
+    };
+    let state = ChallengeState {
+        sides: vec![side(*challenger.key), side(opponent)],
+        stake,
+        deadline: Clock::get()?.unix_timestamp.saturating_add(CHALLENGE_ACCEPT_SECS),
+        accepted: false,
+        settled: false,
+        offer: 0,
+    };
+    state.serialize(&mut *challenge_account.data.borrow_mut())?;
+    debug_msg!("{} challenged {} for {}", challenger.key, opponent, stake);
+    emit_event(&RPSEvent::ChallengeCreated {
+        challenge: *challenge_account.key,
+        challenger: *challenger.key,
+        opponent,
+        stake,
+    });
+    Ok(())
+}
+
+/* ─────────────────────  Accept Challenge  ──────────────────────── */
+fn process_accept_challenge(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let ai = &mut accounts.iter();
+    let opponent = next_account_info(ai)?;
+    let challenge_account = next_account_info(ai)?;
+    let system_program = next_account_info(ai)?;
+    check_system_program(system_program)?;
+
+    if !opponent.is_signer {
+        return Err(RPSError::NotAuthorized.into());
+    }
+    let mut challenge = load_challenge(program_id, challenge_account)?;
+    if challenge.sides[1].player != *opponent.key {
+        msg!("Only the named opponent can accept");
+        return Err(RPSError::NotAuthorized.into());
+    }
+    if challenge.accepted {
+        return Err(RPSError::InvalidGameState.into());
+    }
+    let now = Clock::get()?.unix_timestamp;
+    if now > challenge.deadline {
+        msg!("Challenge expired");
+        return Err(RPSError::InvalidGameState.into());
+    }
+    check_not_banned(program_id, next_account_info(ai)?, opponent.key)?;
+    check_not_excluded(program_id, next_account_info(ai)?, opponent.key)?;
+    check_compliance(program_id, next_account_info(ai)?, ai, opponent.key, challenge.stake)?;
+
+    invoke(
+        &system_instruction::transfer(opponent.key, challenge_account.key, challenge.stake),
+        &[opponent.clone(), challenge_account.clone(), system_program.clone()],
+    )?;
+
+    challenge.accepted = true;
+    challenge.deadline = now.saturating_add(CHALLENGE_PLAY_SECS);
+    challenge.serialize(&mut *challenge_account.data.borrow_mut())?;
+    debug_msg!("{} accepted challenge {}", opponent.key, challenge_account.key);
+    emit_event(&RPSEvent::ChallengeAccepted {
+        challenge: *challenge_account.key,
+        opponent: *opponent.key,
+    });
+    Ok(())
+}
+
+/* ─────────────────────  Commit Challenge Choice  ───────────────── */
+fn process_commit_challenge_choice(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    commitment: [u8; 64],
+) -> ProgramResult {
+    let ai = &mut accounts.iter();
+    let player = next_account_info(ai)?;
+    let challenge_account = next_account_info(ai)?;
+
+    if !player.is_signer {
+        return Err(RPSError::NotAuthorized.into());
+    }
+    let mut challenge = load_challenge(program_id, challenge_account)?;
+    if !challenge.accepted || challenge.settled || Clock::get()?.unix_timestamp > challenge.deadline {
+        msg!("Challenge is not open for play");
+        return Err(RPSError::InvalidGameState.into());
+    }
+    let side = challenge.side_of(player.key).ok_or(RPSError::PlayerNotFound)?;
+    if challenge.sides[side].committed() || commitment == [0; 64] {
+        return Err(RPSError::InvalidPlayerState.into());
+    }
+
+    challenge.sides[side].commitment = commitment;
+    challenge.serialize(&mut *challenge_account.data.borrow_mut())?;
+    debug_msg!("{} committed in challenge {}", player.key, challenge_account.key);
+    emit_event(&RPSEvent::ChoiceCommitted {
+        game: *challenge_account.key,
+        player: *player.key,
+    });
+    Ok(())
+}
+
+/* ─────────────────────  Reveal Challenge Choice  ───────────────── */
+fn process_reveal_challenge_choice(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    choice: Choice,
+    salt: [u8; 32],
+) -> ProgramResult {
+    let ai = &mut accounts.iter();
+    let player = next_account_info(ai)?;
+    let challenge_account = next_account_info(ai)?;
+    let challenger = next_account_info(ai)?;
+    let opponent = next_account_info(ai)?;
+
+    if !player.is_signer {
+        return Err(RPSError::NotAuthorized.into());
+    }
+    let mut challenge = load_challenge(program_id, challenge_account)?;
+    if !challenge.sides.iter().all(ChallengeSide::committed) {
+        msg!("Both sides must commit before revealing");
+        return Err(RPSError::InvalidGameState.into());
+    }
+    let side = challenge.side_of(player.key).ok_or(RPSError::PlayerNotFound)?;
+    if challenge.sides[side].revealed() {
+        return Err(RPSError::InvalidPlayerState.into());
+    }
+    // A challenge is a single round
+    if compute_commitment(challenge_account.key, 1, player.key, &salt, &choice)? != challenge.sides[side].commitment {
+        msg!("Revealed choice does not match committed choice");
+        return Err(RPSError::InvalidHash.into());
+    }
+    // Challenges are played by the classic rules
+    if !choice.hand().is_some_and(|hand| rules::Classic.allows(hand)) {
+        msg!("Challenges take rock, paper or scissors");
+        return Err(RPSError::InvalidChoice.into());
+    }
+
+    challenge.sides[side].choice = choice;
+    emit_event(&RPSEvent::ChoiceRevealed {
+        game: *challenge_account.key,
+        player: *player.key,
+    });
+    if challenge.sides.iter().all(ChallengeSide::revealed) {
+        return settle_challenge(challenge_account, challenge, challenger, opponent);
+    }
+    challenge.serialize(&mut *challenge_account.data.borrow_mut())?;
+    debug_msg!("{} revealed in challenge {}", player.key, challenge_account.key);
+    Ok(())
+}
+
+/* ─────────────────────  Expire Challenge  ──────────────────────── */
+fn process_expire_challenge(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let ai = &mut accounts.iter();
+    let _caller = next_account_info(ai)?;
+    let challenge_account = next_account_info(ai)?;
+    let challenger = next_account_info(ai)?;
+    let opponent = next_account_info(ai)?;
+
+    let challenge = load_challenge(program_id, challenge_account)?;
+    if Clock::get()?.unix_timestamp <= challenge.deadline {
+        return Err(RPSError::TimeoutNotReached.into());
+    }
+    if !challenge.settled {
+        return settle_challenge(challenge_account, challenge, challenger, opponent);
+    }
+
+    // A lapsed double-or-nothing window: refund any offer and close
+    check_challenge_sides(&challenge, challenger, opponent)?;
+    if challenge.offer > 0 {
+        let loser = if challenge.loser() == Some(0) { challenger } else { opponent };
+        pay_from_program_account(challenge_account, loser, challenge.offer)?;
+        debug_msg!("Double-or-nothing offer on {} refunded", challenge_account.key);
+    }
+    close_program_account(challenge_account, challenger)
+}
+
+/* ─────────────────────  Offer Double Or Nothing  ───────────────── */
+fn process_offer_double_or_nothing(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let ai = &mut accounts.iter();
+    let loser = next_account_info(ai)?;
+    let challenge_account = next_account_info(ai)?;
+    let system_program = next_account_info(ai)?;
+    check_system_program(system_program)?;
+
+    if !loser.is_signer {
+        return Err(RPSError::NotAuthorized.into());
+    }
+    let mut challenge = load_challenge(program_id, challenge_account)?;
+    if !challenge.settled || challenge.offer > 0 {
+        msg!("Double or nothing needs a decided challenge without an offer");
+        return Err(RPSError::InvalidGameState.into());
+    }
+    let side = challenge.side_of(loser.key).ok_or(RPSError::PlayerNotFound)?;
+    if challenge.loser() != Some(side) {
+        msg!("Only the loser can offer double or nothing");
+        return Err(RPSError::NotAuthorized.into());
+    }
+    let now = Clock::get()?.unix_timestamp;
+    if now > challenge.deadline {
+        msg!("Double-or-nothing window closed");
+        return Err(RPSError::InvalidGameState.into());
+    }
+
+    // Match the winner's takings, so the rematch pot doubles them
+    let offer = challenge.stake.checked_mul(2).ok_or(RPSError::FeeCalculationError)?;
+    check_not_banned(program_id, next_account_info(ai)?, loser.key)?;
+    check_not_excluded(program_id, next_account_info(ai)?, loser.key)?;
+    check_compliance(program_id, next_account_info(ai)?, ai, loser.key, offer)?;
+    invoke(
+        &system_instruction::transfer(loser.key, challenge_account.key, offer),
+        &[loser.clone(), challenge_account.clone(), system_program.clone()],
+    )?;
+
+    challenge.offer = offer;
+    challenge.deadline = now.saturating_add(DOUBLE_OR_NOTHING_SECS);
+    challenge.serialize(&mut *challenge_account.data.borrow_mut())?;
+    debug_msg!("{} offered double or nothing on {} for {}", loser.key, challenge_account.key, offer);
+    emit_event(&RPSEvent::DoubleOrNothingOffered {
+        challenge: *challenge_account.key,
+        offer,
+    });
+    Ok(())
+}
+
+/* ─────────────────────  Accept Double Or Nothing  ──────────────── */
+fn process_accept_double_or_nothing(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let ai = &mut accounts.iter();
+    let winner = next_account_info(ai)?;
+    let challenge_account = next_account_info(ai)?;
+    let rematch_account = next_account_info(ai)?;
+    let system_program = next_account_info(ai)?;
+    check_system_program(system_program)?;
+    let challenger = next_account_info(ai)?;
+
+    if !winner.is_signer {
+        return Err(RPSError::NotAuthorized.into());
+    }
+    let challenge = load_challenge(program_id, challenge_account)?;
+    if !challenge.settled || challenge.offer == 0 {
+        msg!("No double-or-nothing offer to accept");
+        return Err(RPSError::InvalidGameState.into());
+    }
+    let side = challenge.side_of(winner.key).ok_or(RPSError::PlayerNotFound)?;
+    let loser = challenge.loser().ok_or(RPSError::InvalidGameState)?;
+    if side == loser {
+        msg!("Only the winner can accept double or nothing");
+        return Err(RPSError::NotAuthorized.into());
+    }
+    let now = Clock::get()?.unix_timestamp;
+    if now > challenge.deadline {
+        msg!("Double-or-nothing offer expired");
+        return Err(RPSError::InvalidGameState.into());
+    }
+    if *challenger.key != challenge.sides[0].player {
+        msg!("Rent recipient does not match the challenge");
+        return Err(RPSError::InvalidParameter.into());
+    }
+    check_not_banned(program_id, next_account_info(ai)?, winner.key)?;
+    check_not_excluded(program_id, next_account_info(ai)?, winner.key)?;
+    check_compliance(program_id, next_account_info(ai)?, ai, winner.key, challenge.offer)?;
+
+    /* the rematch, funded with the winner's matching stake */
+    let rent = Rent::get()?;
+    let lamports = rent
+        .minimum_balance(ChallengeState::SPACE)
+        .checked_add(challenge.offer)
+        .ok_or(RPSError::FeeCalculationError)?;
+    invoke(
+        &system_instruction::create_account(
+            winner.key,
+            rematch_account.key,
+            lamports,
+            ChallengeState::SPACE as u64,
+            program_id,
+        ),
+        &[winner.clone(), rematch_account.clone(), system_program.clone()],
+    )?;
+    pay_from_program_account(challenge_account, rematch_account, challenge.offer)?;
+
+    // The winner paid the rent, so they take the challenger's side
+    let side = |player: Pubkey| ChallengeSide {
+        player,
+        commitment: [0; 64],
+        choice: Choice::None,
+    };
+    let rematch = ChallengeState {
+        sides: vec![side(*winner.key), side(challenge.sides[loser].player)],
+        stake: challenge.offer,
+        deadline: now.saturating_add(CHALLENGE_PLAY_SECS),
+        accepted: true,
+        settled: false,
+        offer: 0,
+    };
+    rematch.serialize(&mut *rematch_account.data.borrow_mut())?;
+    close_program_account(challenge_account, challenger)?;
+    debug_msg!("Double or nothing on {} accepted as {}", challenge_account.key, rematch_account.key);
+    emit_event(&RPSEvent::DoubleOrNothingAccepted {
+        challenge: *challenge_account.key,
+        rematch: *rematch_account.key,
+    });
+    Ok(())
+}
+
+/* ╔══════════════════════════════════════════════════════════════════╗
+   ║                          GLOBAL STATS                            ║
+   ╚══════════════════════════════════════════════════════════════════╝ */
+
+// Program-wide timing aggregates for dashboards and timeout tuning; see
+// `stats_address`. Commits and reveals feed it when they pass the PDA.
+// Latencies are measured from the start of the action's phase, and a
+// phase's length is recorded by the action that completes it.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Default)]
+pub struct GlobalStats {
+    pub commits: u64,
+    pub commit_latency_secs: u64,   // Summed over `commits`
+    pub reveals: u64,
+    pub reveal_latency_secs: u64,   // Summed over `reveals`
+    pub commit_phases: u64,         // Commit phases every player completed
+    pub commit_phase_secs: u64,
+    pub reveal_phases: u64,         // Reveal phases every player completed
+    pub reveal_phase_secs: u64,
+}
+
+impl GlobalStats {
+    pub const LEN: usize = 8 * 8;
+
+    pub fn unpack_from(data: &[u8]) -> Result<Self, ProgramError> {
+        let mut slice = data;
+        Self::deserialize(&mut slice).map_err(|_| ProgramError::InvalidAccountData)
+    }
+
+    pub fn average_commit_latency(&self) -> Option<u64> {
+        self.commit_latency_secs.checked_div(self.commits)
+    }
+
+    pub fn average_reveal_latency(&self) -> Option<u64> {
+        self.reveal_latency_secs.checked_div(self.reveals)
+    }
+
+    pub fn average_commit_phase(&self) -> Option<u64> {
+        self.commit_phase_secs.checked_div(self.commit_phases)
+    }
+
+    pub fn average_reveal_phase(&self) -> Option<u64> {
+        self.reveal_phase_secs.checked_div(self.reveal_phases)
+    }
+}
+
+// Folds one commit or reveal into the stats, if the caller passed the stats
+// PDA. `completed_phase` marks the action that finished its phase.
+fn record_action_stats(
+    program_id: &Pubkey,
+    stats_account: Option<&AccountInfo>,
+    phase: GameState,
+    latency: u64,
+    completed_phase: bool,
+) -> ProgramResult {
+    let Some(stats_account) = stats_account else {
+        return Ok(());
+    };
+    if *stats_account.key != stats_address(program_id).0 {
+        msg!("Stats account is not the stats PDA");
+        return Err(RPSError::InvalidParameter.into());
+    }
+    assert_owned_by(stats_account, program_id)?;
+    let mut stats = GlobalStats::unpack_from(&stats_account.data.borrow())?;
+
+    let (actions, action_secs, phases, phase_secs) = match phase {
+        GameState::CommitPhase => (
+            &mut stats.commits,
+            &mut stats.commit_latency_secs,
+            &mut stats.commit_phases,
+            &mut stats.commit_phase_secs,
+        ),
+        _ => (
+            &mut stats.reveals,
+            &mut stats.reveal_latency_secs,
+            &mut stats.reveal_phases,
+            &mut stats.reveal_phase_secs,
+        ),
+    };
+    *actions = actions.saturating_add(1);
+    *action_secs = action_secs.saturating_add(latency);
+    if completed_phase {
+        // The phase started when its countdown did, so it lasted as long
+        // as the action that closed it waited
+        *phases = phases.saturating_add(1);
+        *phase_secs = phase_secs.saturating_add(latency);
+    }
+
+    stats.serialize(&mut *stats_account.data.borrow_mut())?;
+    Ok(())
+}
+
+/* ─────────────────────  Initialize Stats  ──────────────────────── */
+fn process_initialize_stats(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let ai = &mut accounts.iter();
+    let payer = next_account_info(ai)?;
+    let stats_account = next_account_info(ai)?;
+    let system_program = next_account_info(ai)?;
+    check_system_program(system_program)?;
+
+    if !payer.is_signer {
+        return Err(RPSError::NotAuthorized.into());
+    }
+    let (stats_key, bump) = stats_address(program_id);
+    if *stats_account.key != stats_key {
+        msg!("Stats account is not the stats PDA");
+        return Err(RPSError::InvalidParameter.into());
+    }
+    if stats_account.owner == program_id {
+        msg!("Stats already initialized");
+        return Err(RPSError::InvalidGameState.into());
+    }
+
+    let rent = Rent::get()?;
+    invoke_signed(
+        &system_instruction::create_account(
+            payer.key,
+            stats_account.key,
+            rent.minimum_balance(GlobalStats::LEN),
+            GlobalStats::LEN as u64,
+            program_id,
+        ),
+        &[payer.clone(), stats_account.clone(), system_program.clone()],
+        &[&[STATS_SEED, &[bump]]],
+    )?;
+    GlobalStats::default().serialize(&mut *stats_account.data.borrow_mut())?;
+    debug_msg!("Global stats initialized");
+    Ok(())
+}
+
+/* ╔══════════════════════════════════════════════════════════════════╗
+   ║                          PARTICIPATION                           ║
+   ╚══════════════════════════════════════════════════════════════════╝ */
+
+// Finished games a player has recorded in one epoch; see
+// `participation_address`. Deliberately small and append-only in layout so
+// loyalty and airdrop programs can read it without depending on `Game`:
+// the PDA's owner and seeds vouch for it, and its fields are fixed-width.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Participation {
+    pub player: Pubkey,
+    pub epoch: u64,
+    pub games: u32,
+}
+
+impl Participation {
+    pub const LEN: usize = 32 + 8 + 4;
+
+    pub fn unpack_from(data: &[u8]) -> Result<Self, ProgramError> {
+        let mut slice = data;
+        Self::deserialize(&mut slice).map_err(|_| ProgramError::InvalidAccountData)
+    }
+}
+
+/* ─────────────────────  Record Participation  ──────────────────── */
+fn process_record_participation(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let ai = &mut accounts.iter();
+    let player = next_account_info(ai)?;
+    let game_account = next_account_info(ai)?;
+    let participation_account = next_account_info(ai)?;
+    let system_program = next_account_info(ai)?;
+    check_system_program(system_program)?;
+
+    if !player.is_signer {
+        return Err(RPSError::NotAuthorized.into());
+    }
+    assert_owned_by(game_account, program_id)?;
+    let mut game = Game::unpack_from(&game_account.data.borrow())?;
+    state_machine::require(game.state, Action::Settle)?;
+    let seat = game
+        .players
+        .iter()
+        .position(|p| p.pubkey == *player.key)
+        .ok_or(RPSError::PlayerNotFound)?;
+    let bit = 1u8 << seat;
+    if game.participation_bitmap & bit != 0 {
+        return Err(RPSError::AlreadyClaimed.into());
+    }
+
+    let epoch = Clock::get()?.epoch;
+    let (participation_key, bump) = participation_address(program_id, player.key, epoch);
+    if *participation_account.key != participation_key {
+        msg!("Participation account is not this epoch's PDA");
+        return Err(RPSError::InvalidParameter.into());
+    }
+    let mut participation = if participation_account.owner == program_id {
+        Participation::unpack_from(&participation_account.data.borrow())?
+    } else {
+        let rent = Rent::get()?;
+        invoke_signed(
+            &system_instruction::create_account(
+                player.key,
+                participation_account.key,
+                rent.minimum_balance(Participation::LEN),
+                Participation::LEN as u64,
+                program_id,
+            ),
+            &[player.clone(), participation_account.clone(), system_program.clone()],
+            &[&[PARTICIPATION_SEED, player.key.as_ref(), &epoch.to_le_bytes(), &[bump]]],
+        )?;
+        Participation {
+            player: *player.key,
+            epoch,
+            games: 0,
+        }
+    };
+    participation.games = participation.games.saturating_add(1);
+    participation.serialize(&mut *participation_account.data.borrow_mut())?;
+
+    game.participation_bitmap |= bit;
+    game.serialize(&mut *game_account.data.borrow_mut())?;
+    debug_msg!("Participation recorded for {} in epoch {}", player.key, epoch);
+    Ok(())
+}
+
+/* ╔══════════════════════════════════════════════════════════════════╗
+   ║                          LOBBY NAMES                             ║
+   ╚══════════════════════════════════════════════════════════════════╝ */
+
+// Registry entry mapping a shareable name to a lobby; see
+// `lobby_name_address`
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct LobbyName {
+    pub registrant: Pubkey, // Only they may re-point the name
+    pub target: Pubkey,
+    pub name: String,
+}
+
+impl LobbyName {
+    pub fn unpack_from(data: &[u8]) -> Result<Self, ProgramError> {
+        let mut slice = data;
+        Self::deserialize(&mut slice).map_err(|_| ProgramError::InvalidAccountData)
+    }
+}
+
+/* ─────────────────────  Register Lobby Name  ───────────────────── */
+fn process_register_lobby_name(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    name: String,
+) -> ProgramResult {
+    let ai = &mut accounts.iter();
+    let registrant = next_account_info(ai)?;
+    let name_account = next_account_info(ai)?;
+    let target_account = next_account_info(ai)?;
+    let system_program = next_account_info(ai)?;
+    check_system_program(system_program)?;
+
+    if !registrant.is_signer {
+        return Err(RPSError::NotAuthorized.into());
+    }
+    let slug = name.bytes().all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'-');
+    if !slug || name.len() < MIN_LOBBY_NAME_LEN || name.len() > MAX_LOBBY_NAME_LEN {
+        msg!("Lobby names are {} to {} characters of a-z, 0-9 and -", MIN_LOBBY_NAME_LEN, MAX_LOBBY_NAME_LEN);
+        return Err(RPSError::InvalidParameter.into());
+    }
+
+    // Names only ever point at lobbies their registrant runs
+    assert_owned_by(target_account, program_id)?;
+    let host = if target_account.data_len() == RecurringLobby::LEN {
+        RecurringLobby::unpack_from(&target_account.data.borrow())?.host
+    } else {
+        Game::unpack_from(&target_account.data.borrow())?.host
+    };
+    if host != *registrant.key {
+        msg!("Only the host can name a lobby");
+        return Err(RPSError::NotAuthorized.into());
+    }
+
+    let (name_key, bump) = lobby_name_address(program_id, &name);
+    if *name_account.key != name_key {
+        msg!("Lobby name account is not the PDA for this name");
+        return Err(RPSError::InvalidParameter.into());
+    }
+
+    if name_account.owner == program_id {
+        let mut entry = LobbyName::unpack_from(&name_account.data.borrow())?;
+        if entry.registrant != *registrant.key {
+            msg!("Lobby name is taken");
+            return Err(RPSError::InvalidGameState.into());
+        }
+        entry.target = *target_account.key;
+        entry.serialize(&mut *name_account.data.borrow_mut())?;
+        debug_msg!("Lobby name {} now points at {}", entry.name, entry.target);
+        return Ok(());
+    }
+
+    let entry = LobbyName {
+        registrant: *registrant.key,
+        target: *target_account.key,
+        name,
+    };
+    let data = entry.try_to_vec()?;
+
+    let rent = Rent::get()?;
+    invoke_signed(
+        &system_instruction::create_account(
+            registrant.key,
+            name_account.key,
+            rent.minimum_balance(data.len()),
+            data.len() as u64,
+            program_id,
+        ),
+        &[registrant.clone(), name_account.clone(), system_program.clone()],
+        &[&[LOBBY_NAME_SEED, &lobby_name_hash(&entry.name), &[bump]]],
+    )?;
+    name_account.data.borrow_mut().copy_from_slice(&data);
+    debug_msg!("Lobby name {} registered for {}", entry.name, entry.target);
+    Ok(())
+}
+
+/* ╔══════════════════════════════════════════════════════════════════╗
+   ║                        PLAYER PROFILES                           ║
+   ╚══════════════════════════════════════════════════════════════════╝ */
+
+// A player's public profile; see `profile_address`. Allocated at full size
+// so the nickname can change in place.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct PlayerProfile {
+    pub player: Pubkey,
+    pub nickname: String,
+    pub nickname_set_at: u64,
+}
+
+impl PlayerProfile {
+    pub const SPACE: usize = 32 + 4 + MAX_NICKNAME_LEN + 8;
+
+    pub fn unpack_from(data: &[u8]) -> Result<Self, ProgramError> {
+        let mut slice = data;
+        Self::deserialize(&mut slice).map_err(|_| ProgramError::InvalidAccountData)
+    }
+}
+
+// Registry entry reserving a nickname; see `nickname_address`
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct NicknameEntry {
+    pub owner: Pubkey,
+}
+
+impl NicknameEntry {
+    pub const LEN: usize = 32;
+
+    pub fn unpack_from(data: &[u8]) -> Result<Self, ProgramError> {
+        let mut slice = data;
+        Self::deserialize(&mut slice).map_err(|_| ProgramError::InvalidAccountData)
+    }
+}
+
+fn process_set_nickname_policy(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    fee: u64,
+    fee_mint: Option<Pubkey>,
+) -> ProgramResult {
+    let ai = &mut accounts.iter();
+    let authority = next_account_info(ai)?;
+    let config_account = next_account_info(ai)?;
+
+    let mut config = require_config_authority(program_id, authority, config_account)?;
+    if fee > 0 && fee_mint.is_none() {
+        msg!("A nickname fee needs a mint");
+        return Err(RPSError::InvalidParameter.into());
+    }
+    config.nickname_fee = fee;
+    config.nickname_fee_mint = fee_mint;
+    config.serialize(&mut *config_account.data.borrow_mut())?;
+    debug_msg!("Nicknames now cost {}", fee);
+    Ok(())
+}
+
+/* ─────────────────────  Set Nickname  ──────────────────────────── */
+fn process_set_nickname(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    nickname: String,
+) -> ProgramResult {
+    let ai = &mut accounts.iter();
+    let player = next_account_info(ai)?;
+    let profile_account = next_account_info(ai)?;
+    let name_account = next_account_info(ai)?;
+    let config_account = next_account_info(ai)?;
+    let system_program = next_account_info(ai)?;
+    check_system_program(system_program)?;
+
+    if !player.is_signer {
+        return Err(RPSError::NotAuthorized.into());
+    }
+    let charset = nickname
+        .bytes()
+        .all(|b| b.is_ascii_alphanumeric() || b == b'_' || b == b'-');
+    if !charset || nickname.len() < MIN_NICKNAME_LEN || nickname.len() > MAX_NICKNAME_LEN {
+        msg!("Nicknames are {} to {} characters of A-Z, a-z, 0-9, _ and -", MIN_NICKNAME_LEN, MAX_NICKNAME_LEN);
+        return Err(RPSError::InvalidParameter.into());
+    }
+    let (profile_key, profile_bump) = profile_address(program_id, player.key);
+    if *profile_account.key != profile_key {
+        msg!("Profile account is not the player's profile PDA");
+        return Err(RPSError::InvalidParameter.into());
+    }
+    let (name_key, name_bump) = nickname_address(program_id, &nickname);
+    if *name_account.key != name_key {
+        msg!("Nickname account is not the PDA for this nickname");
+        return Err(RPSError::InvalidParameter.into());
+    }
+
+    let now = Clock::get()?.unix_timestamp as u64;
+    let rent = Rent::get()?;
+    let previous = if profile_account.owner == program_id {
+        Some(PlayerProfile::unpack_from(&profile_account.data.borrow())?)
+    } else {
+        None
+    };
+
+    // Free the current name, unless this only changes its case
+    if let Some(profile) = &previous {
+        let next_change = profile.nickname_set_at.saturating_add(NICKNAME_COOLDOWN_SECS);
+        if now < next_change {
+            msg!("Nickname can change again at {}", next_change);
+            return Err(RPSError::InvalidGameState.into());
+        }
+        let old_name_account = next_account_info(ai)?;
+        if *old_name_account.key != nickname_address(program_id, &profile.nickname).0 {
+            msg!("Old nickname account is not the PDA for {}", profile.nickname);
+            return Err(RPSError::InvalidParameter.into());
+        }
+        if old_name_account.key != name_account.key {
+            close_program_account(old_name_account, player)?;
+        }
+    }
+
+    if name_account.owner == program_id {
+        let entry = NicknameEntry::unpack_from(&name_account.data.borrow())?;
+        if entry.owner != *player.key {
+            msg!("Nickname is taken");
+            return Err(RPSError::InvalidGameState.into());
+        }
+    } else {
+        invoke_signed(
+            &system_instruction::create_account(
+                player.key,
+                name_account.key,
+                rent.minimum_balance(NicknameEntry::LEN),
+                NicknameEntry::LEN as u64,
+                program_id,
+            ),
+            &[player.clone(), name_account.clone(), system_program.clone()],
+            &[&[NICKNAME_SEED, &nickname_hash(&nickname), &[name_bump]]],
+        )?;
+        NicknameEntry { owner: *player.key }.serialize(&mut *name_account.data.borrow_mut())?;
+    }
+
+    if previous.is_none() {
+        invoke_signed(
+            &system_instruction::create_account(
+                player.key,
+                profile_account.key,
+                rent.minimum_balance(PlayerProfile::SPACE),
+                PlayerProfile::SPACE as u64,
+                program_id,
+            ),
+            &[player.clone(), profile_account.clone(), system_program.clone()],
+            &[&[PROFILE_SEED, player.key.as_ref(), &[profile_bump]]],
+        )?;
+    }
+
+    // The operator's fee, if any, goes to the treasury's token account. No
+    // config yet means nicknames are free.
+    if *config_account.key != config_address(program_id).0 {
+        msg!("Config account is not the program config");
+        return Err(RPSError::InvalidParameter.into());
+    }
+    if config_account.owner == program_id {
+        let config = ProgramConfig::unpack_from(&config_account.data.borrow())?;
+        if let (true, Some(mint)) = (config.nickname_fee > 0, config.nickname_fee_mint) {
+            let (Ok(token_program), Ok(source), Ok(destination)) =
+                (next_account_info(ai), next_account_info(ai), next_account_info(ai))
+            else {
+                msg!("Token program and token accounts required for the nickname fee");
+                return Err(RPSError::InvalidParameter.into());
+            };
+            check_token_program(token_program)?;
+            check_token_account(source, player.key, &mint)?;
+            check_token_account(destination, &treasury_address(program_id).0, &mint)?;
+            invoke(
+                &spl_token::instruction::transfer(
+                    token_program.key,
+                    source.key,
+                    destination.key,
+                    player.key,
+                    &[],
+                    config.nickname_fee,
+                )?,
+                &[source.clone(), destination.clone(), player.clone(), token_program.clone()],
+            )?;
+        }
+    }
+
+    let profile = PlayerProfile {
+        player: *player.key,
+        nickname,
+        nickname_set_at: now,
+    };
+    profile.serialize(&mut *profile_account.data.borrow_mut())?;
+    debug_msg!("{} is now known as {}", player.key, profile.nickname);
+    Ok(())
+}
+
+// A player's self-imposed limits; see `limits_address` and `SetLimits`.
+// Wagers are the SOL entries of `JoinGame`, counted per window.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct PlayerLimits {
+    pub player: Pubkey,
+    pub daily_limit: u64,    // Max lamports wagered per window; 0 = none
+    pub pending_limit: u64,  // Looser limit waiting out LIMIT_RAISE_DELAY_SECS
+    pub pending_from: u64,   // When `pending_limit` applies; 0 = nothing pending
+    pub cool_off_until: u64, // Joins are refused until then
+    pub window_start: u64,   // Start of the current WAGER_WINDOW_SECS window
+    pub wagered: u64,        // Lamports wagered since `window_start`
+}
+
+impl PlayerLimits {
+    pub const SPACE: usize = 32 + 8 * 6;
+
+    pub fn unpack_from(data: &[u8]) -> Result<Self, ProgramError> {
+        let mut slice = data;
+        Self::deserialize(&mut slice).map_err(|_| ProgramError::InvalidAccountData)
+    }
+
+    // Limit in force at `now`, once any pending raise has waited long enough
+    pub fn limit_at(&self, now: u64) -> u64 {
+        if self.pending_from != 0 && now >= self.pending_from {
+            self.pending_limit
+        } else {
+            self.daily_limit
+        }
+    }
+
+    // Lamports that can still be wagered at `now`; None = no limit
+    pub fn remaining_at(&self, now: u64) -> Option<u64> {
+        let limit = self.limit_at(now);
+        if limit == 0 {
+            return None;
+        }
+        let wagered = if now >= self.window_start.saturating_add(WAGER_WINDOW_SECS) {
+            0
+        } else {
+            self.wagered
+        };
+        Some(limit.saturating_sub(wagered))
+    }
+
+    fn settle(&mut self, now: u64) {
+        self.daily_limit = self.limit_at(now);
+        if self.pending_from != 0 && now >= self.pending_from {
+            self.pending_limit = 0;
+            self.pending_from = 0;
+        }
+        if now >= self.window_start.saturating_add(WAGER_WINDOW_SECS) {
+            self.window_start = now;
+            self.wagered = 0;
+        }
+    }
+
+    // Counts a wager of `amount`, refusing it during a cool-off or past
+    // the limit
+    fn record_wager(&mut self, amount: u64, now: u64) -> ProgramResult {
+        if now < self.cool_off_until {
+            msg!("Cooling off until {}", self.cool_off_until);
+            return Err(RPSError::SelfLimitReached.into());
+        }
+        if self.remaining_at(now).is_some_and(|remaining| amount > remaining) {
+            msg!("Wager of {} is over the daily limit of {}", amount, self.limit_at(now));
+            return Err(RPSError::SelfLimitReached.into());
+        }
+        self.settle(now);
+        self.wagered = self.wagered.saturating_add(amount);
+        Ok(())
+    }
+}
+
+// Holds an entry of `amount` lamports to the joiner's limits.
+// Wallets that never set any have no limits account and join freely.
+fn check_self_limits(
+    program_id: &Pubkey,
+    limits_account: &AccountInfo,
+    player: &Pubkey,
+    amount: u64,
+    now: u64,
+) -> ProgramResult {
+    if *limits_account.key != limits_address(program_id, player).0 {
+        msg!("Limits account is not the player's limits PDA");
+        return Err(RPSError::InvalidParameter.into());
+    }
+    if limits_account.owner != program_id {
+        return Ok(());
+    }
+    let mut limits = PlayerLimits::unpack_from(&limits_account.data.borrow())?;
+    limits.record_wager(amount, now)?;
+    limits.serialize(&mut *limits_account.data.borrow_mut())?;
+    Ok(())
+}
+
+// The checks every join runs on the wallet taking a seat, whichever way it
+// pays: banned wallets are kept out of rated play, players who excluded
+// themselves out of any, and the player's own limits count SOL entries
+// while a cool-off stops any join
+fn check_entrant(
+    program_id: &Pubkey,
+    player: &Pubkey,
+    game: &Game,
+    ban_list_account: &AccountInfo,
+    exclusion_account: &AccountInfo,
+    limits_account: &AccountInfo,
+) -> ProgramResult {
+    check_not_banned(program_id, ban_list_account, player)?;
+    check_not_excluded(program_id, exclusion_account, player)?;
+    let wager = if matches!(game.currency_mode, CurrencyMode::SOL) { game.entry_fee } else { 0 };
+    check_self_limits(program_id, limits_account, player, wager, Clock::get()?.unix_timestamp as u64)
+}
+
+/* ─────────────────────  Set Limits  ────────────────────────────── */
+fn process_set_limits(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    daily_limit: u64,
+    cool_off_secs: u64,
+) -> ProgramResult {
+    let ai = &mut accounts.iter();
+    let player = next_account_info(ai)?;
+    let limits_account = next_account_info(ai)?;
+    let system_program = next_account_info(ai)?;
+    check_system_program(system_program)?;
+
+    if !player.is_signer {
+        return Err(RPSError::NotAuthorized.into());
+    }
+    let (limits_key, bump) = limits_address(program_id, player.key);
+    if *limits_account.key != limits_key {
+        msg!("Limits account is not the player's limits PDA");
+        return Err(RPSError::InvalidParameter.into());
+    }
+
+    let now = Clock::get()?.unix_timestamp as u64;
+    let mut limits = if limits_account.owner == program_id {
+        PlayerLimits::unpack_from(&limits_account.data.borrow())?
+    } else {
+        let rent = Rent::get()?;
+        invoke_signed(
+            &system_instruction::create_account(
+                player.key,
+                limits_account.key,
+                rent.minimum_balance(PlayerLimits::SPACE),
+                PlayerLimits::SPACE as u64,
+                program_id,
+            ),
+            &[player.clone(), limits_account.clone(), system_program.clone()],
+            &[&[LIMITS_SEED, player.key.as_ref(), &[bump]]],
+        )?;
+        PlayerLimits {
+            player: *player.key,
+            daily_limit: 0,
+            pending_limit: 0,
+            pending_from: 0,
+            cool_off_until: 0,
+            window_start: now,
+            wagered: 0,
+        }
+    };
+    limits.settle(now);
+
+    // Tightening is immediate and drops any pending raise; loosening waits
+    let current = limits.daily_limit;
+    if daily_limit != 0 && (current == 0 || daily_limit <= current) {
+        limits.daily_limit = daily_limit;
+        limits.pending_limit = 0;
+        limits.pending_from = 0;
+    } else if daily_limit != current {
+        limits.pending_limit = daily_limit;
+        limits.pending_from = now.saturating_add(LIMIT_RAISE_DELAY_SECS);
+        debug_msg!("Limit of {} applies from {}", daily_limit, limits.pending_from);
+    }
+    if cool_off_secs > 0 {
+        limits.cool_off_until = limits.cool_off_until.max(now.saturating_add(cool_off_secs));
+    }
+
+    limits.serialize(&mut *limits_account.data.borrow_mut())?;
+    debug_msg!("{} limits daily wagers to {}", player.key, limits.daily_limit);
+    Ok(())
+}
+
+// A player's exclusion of themselves from play; see `exclusion_address`
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct SelfExclusion {
+    pub player: Pubkey,
+    pub until: u64, // No game, tournament or challenge entry before this
+}
+
+impl SelfExclusion {
+    pub const SPACE: usize = 32 + 8;
+
+    pub fn unpack_from(data: &[u8]) -> Result<Self, ProgramError> {
+        let mut slice = data;
+        Self::deserialize(&mut slice).map_err(|_| ProgramError::InvalidAccountData)
+    }
+}
+
+/* ─────────────────────  Self Exclude  ──────────────────────────── */
+fn process_self_exclude(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    until: u64,
+) -> ProgramResult {
+    let ai = &mut accounts.iter();
+    let player = next_account_info(ai)?;
+    let exclusion_account = next_account_info(ai)?;
+    let system_program = next_account_info(ai)?;
+    check_system_program(system_program)?;
+
+    if !player.is_signer {
+        return Err(RPSError::NotAuthorized.into());
+    }
+    let (exclusion_key, bump) = exclusion_address(program_id, player.key);
+    if *exclusion_account.key != exclusion_key {
+        msg!("Self-exclusion account is not the player's exclusion PDA");
+        return Err(RPSError::InvalidParameter.into());
+    }
+    let now = Clock::get()?.unix_timestamp as u64;
+    if until <= now {
+        msg!("A self-exclusion must end in the future");
+        return Err(RPSError::InvalidParameter.into());
+    }
+
+    // Irrevocable: a running exclusion can be extended, never shortened
+    if exclusion_account.owner == program_id {
+        let current = SelfExclusion::unpack_from(&exclusion_account.data.borrow())?;
+        if until < current.until {
+            msg!("Self-exclusion runs until {} and cannot be shortened", current.until);
+            return Err(RPSError::SelfExcluded.into());
+        }
+    } else {
+        let rent = Rent::get()?;
+        invoke_signed(
+            &system_instruction::create_account(
+                player.key,
+                exclusion_account.key,
+                rent.minimum_balance(SelfExclusion::SPACE),
+                SelfExclusion::SPACE as u64,
+                program_id,
+            ),
+            &[player.clone(), exclusion_account.clone(), system_program.clone()],
+            &[&[EXCLUSION_SEED, player.key.as_ref(), &[bump]]],
+        )?;
+    }
+
+    let exclusion = SelfExclusion {
+        player: *player.key,
+        until,
+    };
+    exclusion.serialize(&mut *exclusion_account.data.borrow_mut())?;
+    debug_msg!("{} is self-excluded until {}", player.key, until);
+    Ok(())
+}
+
+/* ╔══════════════════════════════════════════════════════════════════╗
+   ║                          DEVNET FAUCET                           ║
+   ╚══════════════════════════════════════════════════════════════════╝ */
+
+// Rate limit of one wallet's demo token claims; see `demo_claim_address`
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct DemoClaim {
+    pub player: Pubkey,
+    pub last_claimed_at: u64,
+    pub total_claimed: u64,
+}
+
+impl DemoClaim {
+    pub const SPACE: usize = 32 + 8 + 8;
+
+    pub fn unpack_from(data: &[u8]) -> Result<Self, ProgramError> {
+        let mut slice = data;
+        Self::deserialize(&mut slice).map_err(|_| ProgramError::InvalidAccountData)
+    }
+}
+
+/* ─────────────────────  Claim Demo Tokens  ─────────────────────── */
+fn process_claim_demo_tokens(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    // Free tokens have no place outside devnet, where the instruction
+    // reads as unknown
+    if !cfg!(feature = "devnet") {
+        msg!("ClaimDemoTokens is only available in devnet builds");
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    let ai = &mut accounts.iter();
+    let player = next_account_info(ai)?;
+    let profile_account = next_account_info(ai)?;
+    let claim_account = next_account_info(ai)?;
+    let mint_account = next_account_info(ai)?;
+    let player_tokens = next_account_info(ai)?;
+    let token_program = next_account_info(ai)?;
+    check_token_program(token_program)?;
+    let system_program = next_account_info(ai)?;
+    check_system_program(system_program)?;
+    let associated_token_program = next_account_info(ai)?;
+    check_associated_token_program(associated_token_program)?;
+
+    if !player.is_signer {
+        return Err(RPSError::NotAuthorized.into());
+    }
+    if *profile_account.key != profile_address(program_id, player.key).0 {
+        msg!("Profile account is not the player's profile PDA");
+        return Err(RPSError::InvalidParameter.into());
+    }
+    if profile_account.owner != program_id {
+        msg!("Create a profile with SetNickname before claiming demo tokens");
+        return Err(RPSError::PlayerNotFound.into());
+    }
+    let (claim_key, claim_bump) = demo_claim_address(program_id, player.key);
+    if *claim_account.key != claim_key {
+        msg!("Claim account is not the player's demo claim PDA");
+        return Err(RPSError::InvalidParameter.into());
+    }
+    let (mint_key, mint_bump) = demo_mint_address(program_id);
+    if *mint_account.key != mint_key {
+        msg!("Mint account is not the demo mint PDA");
+        return Err(RPSError::InvalidParameter.into());
+    }
+
+    let now = Clock::get()?.unix_timestamp as u64;
+    let rent = Rent::get()?;
+    let mut claim = if claim_account.owner == program_id {
+        let claim = DemoClaim::unpack_from(&claim_account.data.borrow())?;
+        let next_claim = claim.last_claimed_at.saturating_add(DEMO_CLAIM_COOLDOWN_SECS);
+        if now < next_claim {
+            msg!("Demo tokens can be claimed again at {}", next_claim);
+            return Err(RPSError::InvalidGameState.into());
+        }
+        claim
+    } else {
+        invoke_signed(
+            &system_instruction::create_account(
+                player.key,
+                claim_account.key,
+                rent.minimum_balance(DemoClaim::SPACE),
+                DemoClaim::SPACE as u64,
+                program_id,
+            ),
+            &[player.clone(), claim_account.clone(), system_program.clone()],
+            &[&[DEMO_CLAIM_SEED, player.key.as_ref(), &[claim_bump]]],
+        )?;
+        DemoClaim {
+            player: *player.key,
+            last_claimed_at: 0,
+            total_claimed: 0,
+        }
+    };
+    claim.last_claimed_at = now;
+    claim.total_claimed = claim.total_claimed.saturating_add(DEMO_CLAIM_AMOUNT);
+    claim.serialize(&mut *claim_account.data.borrow_mut())?;
+
+    let mint_seeds: &[&[u8]] = &[DEMO_MINT_SEED, &[mint_bump]];
+    if *mint_account.owner != spl_token::id() {
+        invoke_signed(
+            &system_instruction::create_account(
+                player.key,
+                mint_account.key,
+                rent.minimum_balance(spl_token::state::Mint::LEN),
+                spl_token::state::Mint::LEN as u64,
+                &spl_token::id(),
+            ),
+            &[player.clone(), mint_account.clone(), system_program.clone()],
+            &[mint_seeds],
+        )?;
+        invoke(
+            &spl_token::instruction::initialize_mint2(
+                token_program.key,
+                mint_account.key,
+                mint_account.key,
+                None,
+                DEMO_TOKEN_DECIMALS,
+            )?,
+            &[mint_account.clone(), token_program.clone()],
+        )?;
+    }
+    // A new player's token account can only be opened once the mint
+    // exists, so it is opened here rather than by the client
+    if *player_tokens.owner != spl_token::id() {
+        if *player_tokens.key != demo_token_address(program_id, player.key) {
+            msg!("A new demo token account must be the player's associated token account");
+            return Err(RPSError::InvalidTokenAccount.into());
+        }
+        invoke(
+            &Instruction {
+                program_id: ASSOCIATED_TOKEN_PROGRAM_ID,
+                accounts: vec![
+                    AccountMeta::new(*player.key, true),
+                    AccountMeta::new(*player_tokens.key, false),
+                    AccountMeta::new_readonly(*player.key, false),
+                    AccountMeta::new_readonly(*mint_account.key, false),
+                    AccountMeta::new_readonly(*system_program.key, false),
+                    AccountMeta::new_readonly(*token_program.key, false),
+                ],
+                // CreateIdempotent
+                data: vec![1],
+            },
+            &[
+                player.clone(),
+                player_tokens.clone(),
+                mint_account.clone(),
+                system_program.clone(),
+                token_program.clone(),
+                associated_token_program.clone(),
+            ],
+        )?;
+    }
+    check_token_account(player_tokens, player.key, mint_account.key)?;
+    invoke_signed(
+        &spl_token::instruction::mint_to(
+            token_program.key,
+            mint_account.key,
+            player_tokens.key,
+            mint_account.key,
+            &[],
+            DEMO_CLAIM_AMOUNT,
+        )?,
+        &[mint_account.clone(), player_tokens.clone(), token_program.clone()],
+        &[mint_seeds],
+    )?;
+
+    debug_msg!("Minted {} demo tokens to {}", DEMO_CLAIM_AMOUNT, player.key);
+    emit_event(&RPSEvent::DemoTokensClaimed {
+        player: *player.key,
+        amount: DEMO_CLAIM_AMOUNT,
+    });
+    Ok(())
+}
+
+/* ╔══════════════════════════════════════════════════════════════════╗
+   ║                       RECURRING LOBBIES                          ║
+   ╚══════════════════════════════════════════════════════════════════╝ */
+
+// Settings every game of a recurring lobby starts with. Spawned games are
+// manual SOL games with no seated host, so entries are paid as players join.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct LobbyTemplate {
+    pub min_players: u8,
+    pub max_players: u8,
+    pub total_rounds: u8,
+    pub entry_fee: u64,
+    pub timeout_seconds: u64,
+    pub losers_can_rejoin: bool,
+    pub allow_public_bots: bool,
+    pub commitment_scheme: CommitmentScheme,
+}
+
+impl LobbyTemplate {
+    pub const LEN: usize = 1 + 1 + 1 + 8 + 8 + 1 + 1 + 1;
+}
+
+// A standing lobby, e.g. a daily or weekly game; see `SpawnScheduledLobby`
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct RecurringLobby {
+    pub host: Pubkey,
+    pub template: LobbyTemplate,
+    pub interval_secs: u64,
+    pub next_spawn_at: u64,      // Unix time the next game may be spawned
+    pub spawned: u64,            // Games spawned so far
+    pub last_game: Option<Pubkey>,
+}
+
+impl RecurringLobby {
+    pub const LEN: usize = 32 + LobbyTemplate::LEN + 8 + 8 + 8 + 1 + 32;
+
+    pub fn unpack_from(data: &[u8]) -> Result<Self, ProgramError> {
+        let mut slice = data;
+        Self::deserialize(&mut slice).map_err(|_| ProgramError::InvalidAccountData)
+    }
+
+    // The first slot on the schedule after `now`. Missed slots are skipped
+    // rather than spawned in a burst, and the schedule never drifts.
+    pub fn following_slot(&self, now: u64) -> u64 {
+        let missed = now.saturating_sub(self.next_spawn_at) / self.interval_secs;
+        self.next_spawn_at
+            .saturating_add(self.interval_secs.saturating_mul(missed.saturating_add(1)))
+    }
+}
+
+/* ─────────────────────  Create Recurring Lobby  ────────────────── */
+fn process_create_recurring_lobby(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    template: LobbyTemplate,
+    interval_secs: u64,
+    first_spawn_at: u64,
+) -> ProgramResult {
+    let ai = &mut accounts.iter();
+    let host = next_account_info(ai)?;
+    let recurring_account = next_account_info(ai)?;
+    let system_program = next_account_info(ai)?;
+    check_system_program(system_program)?;
+
+    if !host.is_signer || !recurring_account.is_signer {
+        return Err(RPSError::NotAuthorized.into());
+    }
+    if template.min_players < MIN_SEATS
+        || template.max_players > MAX_SEATS
+        || template.min_players > template.max_players
+    {
+        msg!("Invalid player count. Must be between {} and {} players", MIN_SEATS, MAX_SEATS);
+        return Err(RPSError::InvalidParameter.into());
+    }
+    if template.total_rounds == 0 || template.timeout_seconds == 0 || interval_secs == 0 {
+        msg!("Rounds, timeout and interval must be greater than 0");
+        return Err(RPSError::InvalidParameter.into());
+    }
+
+    let rent = Rent::get()?;
+    invoke(
+        &system_instruction::create_account(
+            host.key,
+            recurring_account.key,
+            rent.minimum_balance(RecurringLobby::LEN),
+            RecurringLobby::LEN as u64,
+            program_id,
+        ),
+        &[host.clone(), recurring_account.clone(), system_program.clone()],
+    )?;
+
+    let recurring = RecurringLobby {
+        host: *host.key,
+        template,
+        interval_secs,
+        next_spawn_at: first_spawn_at,
+        spawned: 0,
+        last_game: None,
+    };
+    recurring.serialize(&mut *recurring_account.data.borrow_mut())?;
+    debug_msg!("Recurring lobby {} spawns every {}s from {}", recurring_account.key, interval_secs, first_spawn_at);
+    Ok(())
+}
+
+/* ─────────────────────  Spawn Scheduled Lobby  ─────────────────── */
+fn process_spawn_scheduled_lobby(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let ai = &mut accounts.iter();
+    let caller = next_account_info(ai)?;
+    let recurring_account = next_account_info(ai)?;
+    let game_account = next_account_info(ai)?;
+    let system_program = next_account_info(ai)?;
+    check_system_program(system_program)?;
+    let vault_account = next_account_info(ai)?;
+    let config_account = next_account_info(ai)?;
+
+    if !caller.is_signer || !game_account.is_signer {
+        return Err(RPSError::NotAuthorized.into());
+    }
+    assert_owned_by(recurring_account, program_id)?;
+    let fee_bps = config_fee_bps(program_id, config_account)?;
+    let mut recurring = RecurringLobby::unpack_from(&recurring_account.data.borrow())?;
+
+    let now = Clock::get()?.unix_timestamp as u64;
+    if now < recurring.next_spawn_at {
+        msg!("Next game is due at {}", recurring.next_spawn_at);
+        return Err(RPSError::TimeoutNotReached.into());
+    }
+
+    /* game account creation */
+    let template = recurring.template.clone();
+    let space = Game::get_max_size(template.max_players);
+    let rent = Rent::get()?;
+    invoke(
+        &system_instruction::create_account(
+            caller.key,
+            game_account.key,
+            rent.minimum_balance(space),
+            space as u64,
+            program_id,
+        ),
+        &[caller.clone(), game_account.clone(), system_program.clone()],
+    )?;
+    open_pot_vault(program_id, game_account.key, caller, vault_account, system_program)?;
+
+    // Seat count is drawn when the lobby fills, as for InitializeGame
+    let game = Game {
+        host: recurring.host,
+        players: Vec::new(),
+        min_players: template.min_players,
+        max_players: template.max_players,
+        state: GameState::WaitingForPlayers,
+        current_round: 1,
+        total_rounds: template.total_rounds,
+        entry_fee: template.entry_fee,
+        game_pot: 0,
+        required_timeout: template.timeout_seconds,
+        last_action_timestamp: now,
+        player_count: template.max_players,
+        losers_can_rejoin: template.losers_can_rejoin,
+        game_mode: GameMode::Manual,
+        auto_round_delay: 0,
+        max_auto_rounds: 0,
+        current_auto_round: 0,
+        currency_mode: CurrencyMode::SOL,
+        fee_collected: 0,
+        token_mint: None,
+        base_price_feed: None,
+        base_decimals: 0,
+        stake_vaults: Vec::new(),
+        phase_nonce: 0,
+        claimed_bitmap: 0,
+        pot_snapshot: 0,
+        tournament: None,
+        tournament_match: 0,
+        phase_deadline: now.saturating_add(template.timeout_seconds),
+        allow_public_bots: template.allow_public_bots,
+        commitment_scheme: template.commitment_scheme,
+        team_lineup: Vec::new(),
+        phase_started_at: now,
+        participation_bitmap: 0,
+        flagged: false,
+        flag_reasons: 0,
+        seat_watch: Vec::new(),
+        bot_funders: Vec::new(),
+        pending_claims: Vec::new(),
+        escrows: Vec::new(),
+        pot_subsidy: 0,
+        layout_version: GAME_LAYOUT_VERSION,
+        player_count_drawn: template.min_players == template.max_players,
+        reveal_order: Vec::new(),
+        hidden_fee: None,
+        theme_id: 0,
+        fee_bps,
+        rule_set: RuleSet::Classic,
+        winners: 0,
+        cancelled: false,
+        join_timeout: 0,
+        commit_timeout: 0,
+        reveal_timeout: 0,
+        crank_reward_lamports: 0,
+        crank_bounty: 0,
+        slash_non_revealers: false,
+        slashed: Vec::new(),
+        bot_config: Vec::new(),
+        settlement: Settlement::default(),
+    };
+    game.serialize(&mut *game_account.data.borrow_mut())?;
+
+    recurring.next_spawn_at = recurring.following_slot(now);
+    recurring.spawned = recurring.spawned.saturating_add(1);
+    recurring.last_game = Some(*game_account.key);
+    recurring.serialize(&mut *recurring_account.data.borrow_mut())?;
+
+    debug_msg!("Recurring lobby {} spawned {}", recurring_account.key, game_account.key);
+    emit_event(&RPSEvent::GameCreated {
+        game: *game_account.key,
+        host: recurring.host,
+        theme_id: 0,
+    });
+    emit_event(&RPSEvent::PhaseStarted {
+        game: *game_account.key,
+        phase_nonce: game.phase_nonce,
+        deadline: game.phase_deadline,
+    });
+    Ok(())
+}
+
+/* ╔══════════════════════════════════════════════════════════════════╗
+   ║                          ENTRY ESCROW                            ║
+   ╚══════════════════════════════════════════════════════════════════╝ */
+
+/* ─────────────────────  Create Game Token Vault  ───────────────── */
+fn process_create_game_token_vault(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let ai = &mut accounts.iter();
+    let payer = next_account_info(ai)?;
+    let game_account = next_account_info(ai)?;
+    let authority = next_account_info(ai)?;
+    let vault = next_account_info(ai)?;
+    let mint_account = next_account_info(ai)?;
+    let system_program = next_account_info(ai)?;
+    let token_program = next_account_info(ai)?;
+    let associated_token_program = next_account_info(ai)?;
+
+    if !payer.is_signer {
+        return Err(RPSError::NotAuthorized.into());
+    }
+    check_system_program(system_program)?;
+    check_token_program(token_program)?;
+    check_associated_token_program(associated_token_program)?;
+    assert_owned_by(mint_account, &spl_token::id())?;
+    if *authority.key != game_authority_address(program_id, game_account.key).0 {
+        msg!("Account is not the game authority of {}", game_account.key);
+        return Err(RPSError::InvalidParameter.into());
+    }
+
+    // An existing vault must already be the right one; it is left as it is
+    if *vault.owner == spl_token::id() {
+        check_game_vault(program_id, game_account.key, vault, mint_account.key)?;
+        debug_msg!("Token vault of {} already exists", game_account.key);
+        return Ok(());
+    }
+    if *vault.key != game_vault_address(program_id, game_account.key, mint_account.key) {
+        msg!("Game token account must be the game authority's associated token account");
+        return Err(RPSError::InvalidTokenAccount.into());
+    }
+    invoke(
+        &Instruction {
+            program_id: ASSOCIATED_TOKEN_PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(*payer.key, true),
+                AccountMeta::new(*vault.key, false),
+                AccountMeta::new_readonly(*authority.key, false),
+                AccountMeta::new_readonly(*mint_account.key, false),
+                AccountMeta::new_readonly(*system_program.key, false),
+                AccountMeta::new_readonly(*token_program.key, false),
+            ],
+            // CreateIdempotent
+            data: vec![1],
+        },
+        &[
+            payer.clone(),
+            vault.clone(),
+            authority.clone(),
+            mint_account.clone(),
+            system_program.clone(),
+            token_program.clone(),
+            associated_token_program.clone(),
+        ],
+    )?;
+
+    debug_msg!("Created token vault {} of {}", vault.key, game_account.key);
+    Ok(())
+}
+
+/* ─────────────────────  Refund Expired Escrow  ─────────────────── */
+fn process_refund_expired_escrow(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let ai = &mut accounts.iter();
+    let caller = next_account_info(ai)?;
+    let game_account = next_account_info(ai)?;
+    let wallet = next_account_info(ai)?;
+    let vault_account = next_account_info(ai)?;
+
+    if !caller.is_signer {
+        return Err(RPSError::NotAuthorized.into());
+    }
+    assert_owned_by(game_account, program_id)?;
+    let mut game = Game::unpack_from(&game_account.data.borrow())?;
+    check_pot_vault(program_id, game_account.key, &game, vault_account)?;
+
+    // Only lobbies that never filled; full ones are unwound by TimeoutGame
+    state_machine::require(game.state, Action::Seat)?;
+    let escrow = game
+        .escrows
+        .iter()
+        .find(|e| e.player == *wallet.key)
+        .cloned()
+        .ok_or(RPSError::PlayerNotFound)?;
+    let now = Clock::get()?.unix_timestamp as u64;
+    if now < escrow.refund_after {
+        msg!("Entry can be refunded from {}", escrow.refund_after);
+        return Err(RPSError::TimeoutNotReached.into());
+    }
+
+    game.release_escrow(wallet.key);
+    game.players.retain(|p| p.pubkey != *wallet.key);
+    game.game_pot = game.game_pot.saturating_sub(escrow.amount);
+    game.serialize(&mut *game_account.data.borrow_mut())?;
+    pay_from_program_account(vault_account, wallet, escrow.amount)?;
+
+    debug_msg!("Refunded expired entry of {} from {}", wallet.key, game_account.key);
+    emit_event(&RPSEvent::PlayerLeft {
+        game: *game_account.key,
+        player: *wallet.key,
+    });
+    Ok(())
+}
+
+/* ─────────────────────  Refund Entry  ──────────────────────────── */
+fn process_refund_entry(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let ai = &mut accounts.iter();
+    let caller = next_account_info(ai)?;
+    let game_account = next_account_info(ai)?;
+    let wallet = next_account_info(ai)?;
+    let vault_account = next_account_info(ai)?;
+    let treasury_account = next_account_info(ai)?;
+    check_treasury(program_id, treasury_account)?;
+
+    if !caller.is_signer {
+        return Err(RPSError::NotAuthorized.into());
+    }
+    assert_owned_by(game_account, program_id)?;
+    let mut game = Game::unpack_from(&game_account.data.borrow())?;
+
+    // The entry only ever goes back to the wallet that paid it, the seat's
+    // own or a bot seat's funder, so the seat need not sign. Nothing was
+    // played, so flags and large payout delays do not apply.
+    let seat_key = game.seat_paid_to(wallet.key, !game.claimed_bitmap).unwrap_or(*wallet.key);
+    let amount = game.refundable(&seat_key).map_err(|e| {
+        msg!("Cannot refund: {}", e);
+        e
+    })?;
+    let seat = game
+        .players
+        .iter()
+        .position(|p| p.pubkey == seat_key)
+        .ok_or(RPSError::PlayerNotFound)?;
+    game.claimed_bitmap |= 1 << seat;
+    game.game_pot -= amount;
+    // The last refund sends what is left of a treasury top-up back to it
+    let subsidy = if game.paid_out() { std::mem::take(&mut game.game_pot) } else { 0 };
+    if game.paid_out() {
+        game.escrows.clear();
+    }
+    game.serialize(&mut *game_account.data.borrow_mut())?;
+
+    if subsidy > 0 {
+        check_pot_vault(program_id, game_account.key, &game, vault_account)?;
+        pay_from_program_account(vault_account, treasury_account, subsidy)?;
+        debug_msg!("Returned {} of subsidy to the treasury", subsidy);
+    }
+    if amount > 0 {
+        match game.currency_mode {
+            CurrencyMode::SOL => {
+                check_pot_vault(program_id, game_account.key, &game, vault_account)?;
+                pay_from_program_account(vault_account, wallet, amount)?;
+            }
+            CurrencyMode::RPSToken => {
+                let mint = game.token_mint.ok_or(RPSError::InvalidGameState)?;
+                withdraw_tokens(program_id, game_account.key, &mint, wallet.key, ai.as_slice(), amount)?;
+            }
+        }
+    }
+
+    debug_msg!("Refunded {} to {} from cancelled {}", amount, wallet.key, game_account.key);
+    emit_event(&RPSEvent::EntryRefunded {
+        game: *game_account.key,
+        player: *wallet.key,
+        amount,
+    });
+    Ok(())
+}
+
+/* ╔══════════════════════════════════════════════════════════════════╗
+   ║                          MYSTERY STAKES                          ║
+   ╚══════════════════════════════════════════════════════════════════╝ */
+
+/* ─────────────────────  Seal Entry Fee  ────────────────────────── */
+fn process_seal_entry_fee(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    commitment: [u8; 32],
+    min_fee: u64,
+) -> ProgramResult {
+    let ai = &mut accounts.iter();
+    let host = next_account_info(ai)?;
+    let game_account = next_account_info(ai)?;
+
+    if !host.is_signer {
+        return Err(RPSError::NotAuthorized.into());
+    }
+    assert_owned_by(game_account, program_id)?;
+    let mut game = Game::unpack_from(&game_account.data.borrow())?;
+    if game.host != *host.key {
+        msg!("Only the host can seal the entry fee");
+        return Err(RPSError::NotAuthorized.into());
+    }
+
+    // Sealed before anyone else escrows, so every seat pays against the cap
+    state_machine::require(game.state, Action::Seat)?;
+    if game.players.len() > 1 || game.hidden_fee.is_some() {
+        msg!("The entry fee can only be sealed in a fresh lobby");
+        return Err(RPSError::InvalidGameState.into());
+    }
+    if !matches!(game.currency_mode, CurrencyMode::SOL)
+        || !game.stake_vaults.is_empty()
+        || !game.team_lineup.is_empty()
+        || game.tournament.is_some()
+        || game.is_legacy()
+    {
+        msg!("Mystery stakes are only offered for open SOL lobbies");
+        return Err(RPSError::InvalidGameState.into());
+    }
+    if game.entry_fee == 0 || min_fee > game.entry_fee {
+        msg!("Sealed fee bounds {}..={} are invalid", min_fee, game.entry_fee);
+        return Err(RPSError::InvalidParameter.into());
+    }
+
+    game.hidden_fee = Some(HiddenFee { commitment, min_fee });
+    game.serialize(&mut *game_account.data.borrow_mut())?;
+    debug_msg!("Entry fee of {} sealed between {} and {}", game_account.key, min_fee, game.entry_fee);
+    emit_event(&RPSEvent::EntryFeeSealed {
+        game: *game_account.key,
+        min_fee,
+        max_fee: game.entry_fee,
+    });
+    Ok(())
+}
+
+/* ─────────────────────  Reveal Entry Fee  ──────────────────────── */
+fn process_reveal_entry_fee(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    fee: u64,
+    salt: [u8; 32],
+) -> ProgramResult {
+    let ai = &mut accounts.iter();
+    let caller = next_account_info(ai)?;
+    let game_account = next_account_info(ai)?;
+    let vault_account = next_account_info(ai)?;
+    let treasury_account = next_account_info(ai)?;
+    check_treasury(program_id, treasury_account)?;
+    let system_program = next_account_info(ai)?;
+    check_system_program(system_program)?;
+
+    if !caller.is_signer {
+        return Err(RPSError::NotAuthorized.into());
+    }
+    assert_owned_by(game_account, program_id)?;
+    let mut game = Game::unpack_from(&game_account.data.borrow())?;
+    let Some(hidden) = game.hidden_fee.clone() else {
+        msg!("Game has no sealed entry fee");
+        return Err(RPSError::InvalidGameState.into());
+    };
+    state_machine::require(game.state, Action::Commit)?;
+    if game.has_started() {
+        msg!("The entry fee is revealed once the lobby is full");
+        return Err(RPSError::InvalidGameState.into());
+    }
+    check_pot_vault(program_id, game_account.key, &game, vault_account)?;
+
+    // A host who sits on the seal past the deadline forfeits the surprise
+    let now = Clock::get()?.unix_timestamp as u64;
+    let fee = if *caller.key == game.host {
+        if hidden_fee_commitment(fee, &salt) != hidden.commitment {
+            msg!("Fee and salt do not match the sealed entry fee");
+            return Err(RPSError::InvalidHash.into());
+        }
+        if fee < hidden.min_fee || fee > game.entry_fee {
+            msg!("Sealed fee {} is outside {}..={}", fee, hidden.min_fee, game.entry_fee);
+            return Err(RPSError::InvalidParameter.into());
+        }
+        fee
+    } else if now >= game.timeout_deadline() {
+        hidden.min_fee
+    } else {
+        msg!("Only the host can reveal before the commit deadline");
+        return Err(RPSError::NotAuthorized.into());
+    };
+
+    // Each seat paid the cap's fee and pot share; both shrink to the fee's
+    let (sealed, revealed) = (FeeQuote::new(game.entry_fee, game.fee_bps), FeeQuote::new(fee, game.fee_bps));
+    let pot_refund = sealed.pot_contribution - revealed.pot_contribution;
+    let fee_refund = sealed.fee - revealed.fee;
+    let (_, bump) = treasury_address(program_id);
+    for player in &game.players {
+        let wallet = next_account_info(ai)?;
+        if *wallet.key != player.pubkey {
+            msg!("Refund accounts must follow the seat order");
+            return Err(RPSError::InvalidParameter.into());
+        }
+        if pot_refund > 0 {
+            pay_from_program_account(vault_account, wallet, pot_refund)?;
+        }
+        if fee_refund > 0 {
+            invoke_signed(
+                &system_instruction::transfer(treasury_account.key, wallet.key, fee_refund),
+                &[treasury_account.clone(), wallet.clone(), system_program.clone()],
+                &[&[TREASURY_SEED, &[bump]]],
+            )?;
+        }
+    }
+    for escrow in game.escrows.iter_mut() {
+        escrow.amount = escrow.amount.saturating_sub(pot_refund);
+    }
+    let seats = game.players.len() as u64;
+    game.game_pot = game.game_pot.saturating_sub(pot_refund * seats);
+    game.entry_fee = fee;
+    game.hidden_fee = None;
+
+    // Players get a full commit phase from the reveal
+    enter_phase(game_account.key, &mut game, Transition::RestartCommits, now)?;
+    game.last_action_timestamp = now;
+    game.serialize(&mut *game_account.data.borrow_mut())?;
+    debug_msg!("Entry fee of {} revealed as {}", game_account.key, fee);
+    emit_event(&RPSEvent::EntryFeeRevealed {
+        game: *game_account.key,
+        entry_fee: fee,
+        refund: pot_refund + fee_refund,
+    });
+    Ok(())
+}
+
+/* ╔══════════════════════════════════════════════════════════════════╗
+   ║                            WAITLIST                              ║
+   ╚══════════════════════════════════════════════════════════════════╝ */
+
+// Players queued for a full lobby, each with `entry_fee` escrowed in this
+// PDA; see `waitlist_address`
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct Waitlist {
+    pub game: Pubkey,
+    pub queue: Vec<Pubkey>,
+}
+
+impl Waitlist {
+    pub const SPACE: usize = 32 + 4 + MAX_WAITLIST * 32;
+
+    pub fn unpack_from(data: &[u8]) -> Result<Self, ProgramError> {
+        let mut slice = data;
+        Self::deserialize(&mut slice).map_err(|_| ProgramError::InvalidAccountData)
+    }
+}
+
+// Helper to load a game's waitlist; a PDA that was never created is an
+// empty queue
+fn load_waitlist(program_id: &Pubkey, game_key: &Pubkey, waitlist_account: &AccountInfo) -> Result<Waitlist, ProgramError> {
+    if *waitlist_account.key != waitlist_address(program_id, game_key).0 {
+        msg!("Waitlist account does not match the game");
+        return Err(RPSError::InvalidParameter.into());
+    }
+    if waitlist_account.owner != program_id {
+        return Ok(Waitlist {
+            game: *game_key,
+            queue: Vec::new(),
+        });
+    }
+    Waitlist::unpack_from(&waitlist_account.data.borrow())
+}
+
+/* ─────────────────────  Join Waitlist  ─────────────────────────── */
+fn process_join_waitlist(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let ai = &mut accounts.iter();
+    let player = next_account_info(ai)?;
+    let game_account = next_account_info(ai)?;
+    let waitlist_account = next_account_info(ai)?;
+    let system_program = next_account_info(ai)?;
+    check_system_program(system_program)?;
+
+    if !player.is_signer {
+        return Err(RPSError::NotAuthorized.into());
+    }
+    assert_owned_by(game_account, program_id)?;
+    let game = Game::unpack_from(&game_account.data.borrow())?;
+
+    // Promotion skips JoinGame, so the same gates apply up front
+    check_not_banned(program_id, next_account_info(ai)?, player.key)?;
+    check_not_excluded(program_id, next_account_info(ai)?, player.key)?;
+    check_compliance(program_id, next_account_info(ai)?, ai, player.key, game.entry_fee)?;
+
+    if game.players.len() < game.player_count as usize || game.has_started() {
+        msg!("Only full lobbies that have not started take a waitlist");
+        return Err(RPSError::InvalidGameState.into());
+    }
+    if !matches!(game.currency_mode, CurrencyMode::SOL)
+        || !game.stake_vaults.is_empty()
+        || !game.team_lineup.is_empty()
+        || game.tournament.is_some()
+        || game.hidden_fee.is_some()
+    {
+        msg!("Waitlists are only kept for open SOL lobbies");
+        return Err(RPSError::InvalidGameState.into());
+    }
+    if game.players.iter().any(|p| p.pubkey == *player.key) {
+        return Err(RPSError::PlayerAlreadyJoined.into());
+    }
+
+    let mut waitlist = load_waitlist(program_id, game_account.key, waitlist_account)?;
+    if waitlist.queue.contains(player.key) {
+        return Err(RPSError::PlayerAlreadyJoined.into());
+    }
+    if waitlist.queue.len() >= MAX_WAITLIST {
+        msg!("Waitlist is full");
+        return Err(RPSError::GameFull.into());
+    }
+
+    if waitlist_account.owner != program_id {
+        let (_, bump) = waitlist_address(program_id, game_account.key);
+        invoke_signed(
+            &system_instruction::create_account(
+                player.key,
+                waitlist_account.key,
+                Rent::get()?.minimum_balance(Waitlist::SPACE),
+                Waitlist::SPACE as u64,
+                program_id,
+            ),
+            &[player.clone(), waitlist_account.clone(), system_program.clone()],
+            &[&[WAITLIST_SEED, game_account.key.as_ref(), &[bump]]],
+        )?;
+    }
+    if game.entry_fee > 0 {
+        invoke(
+            &system_instruction::transfer(player.key, waitlist_account.key, game.entry_fee),
+            &[player.clone(), waitlist_account.clone(), system_program.clone()],
+        )?;
+    }
+
+    waitlist.queue.push(*player.key);
+    waitlist.serialize(&mut *waitlist_account.data.borrow_mut())?;
+    debug_msg!("{} is #{} on the waitlist for {}", player.key, waitlist.queue.len(), game_account.key);
+    emit_event(&RPSEvent::WaitlistJoined {
+        game: *game_account.key,
+        player: *player.key,
+        position: waitlist.queue.len() as u8,
+    });
+    Ok(())
+}
+
+/* ─────────────────────  Leave Game / Kick Player  ──────────────── */
+fn process_vacate_seat(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    kick: bool,
+) -> ProgramResult {
+    let ai = &mut accounts.iter();
+    let signer = next_account_info(ai)?;
+    let game_account = next_account_info(ai)?;
+    let waitlist_account = next_account_info(ai)?;
+    let treasury_account = next_account_info(ai)?;
+    check_treasury(program_id, treasury_account)?;
+    let vault_account = next_account_info(ai)?;
+    let leaver = if kick { next_account_info(ai)? } else { signer };
+
+    if !signer.is_signer {
+        return Err(RPSError::NotAuthorized.into());
+    }
+    assert_owned_by(game_account, program_id)?;
+    let mut game = Game::unpack_from(&game_account.data.borrow())?;
+
+    if kick && game.host != *signer.key {
+        msg!("Only the host can remove players");
+        return Err(RPSError::NotAuthorized.into());
+    }
+    if *leaver.key == game.host {
+        msg!("The host's seat holds the lobby open");
+        return Err(RPSError::InvalidPlayerState.into());
+    }
+    if game.has_started() {
+        msg!("Seats can only change before play starts");
+        return Err(RPSError::InvalidGameState.into());
+    }
+    if !matches!(game.currency_mode, CurrencyMode::SOL)
+        || !game.stake_vaults.is_empty()
+        || !game.team_lineup.is_empty()
+        || game.tournament.is_some()
+    {
+        msg!("Seats in this game cannot be given up");
+        return Err(RPSError::InvalidGameState.into());
+    }
+    check_pot_vault(program_id, game_account.key, &game, vault_account)?;
+    let seat = game
+        .players
+        .iter()
+        .position(|p| p.pubkey == *leaver.key)
+        .ok_or(RPSError::PlayerNotFound)?;
+
+    // The platform fee was paid on entry and is not returned
+    let pot_amount = game.entry_fee.saturating_sub(calculate_fee(game.entry_fee, game.fee_bps));
+    let fee_amount = game.entry_fee - pot_amount;
+    game.players.remove(seat);
+    game.bot_config.retain(|bot| bot.pubkey != *leaver.key);
+    game.release_escrow(leaver.key);
+    game.game_pot = game.game_pot.saturating_sub(pot_amount);
+    if pot_amount > 0 {
+        pay_from_program_account(vault_account, leaver, pot_amount)?;
+    }
+    emit_event(&RPSEvent::PlayerLeft {
+        game: *game_account.key,
+        player: *leaver.key,
+    });
+
+    let now = Clock::get()?.unix_timestamp as u64;
+    let mut waitlist = load_waitlist(program_id, game_account.key, waitlist_account)?;
+    if waitlist.queue.is_empty() {
+        // Nobody to promote: the lobby reopens
+        if !state_machine::allows(game.state, Action::Seat) {
+            enter_phase(game_account.key, &mut game, Transition::Reopen, now)?;
+        }
+    } else {
+        // The seat passes to the head of the queue with its escrowed entry;
+        // the lobby stays full and the commit phase restarts for the newcomer
+        let promoted = waitlist.queue.remove(0);
+        game.players.push(Player {
+            pubkey: promoted,
+            choice: Choice::None,
+            committed_choice: [0; 64],
+            salt: [0; 32],
+            revealed: false,
+            score: 0,
+        });
+        game.game_pot += pot_amount;
+        game.escrow_entry(promoted, pot_amount, now);
+        if pot_amount > 0 {
+            pay_from_program_account(waitlist_account, vault_account, pot_amount)?;
+        }
+        if fee_amount > 0 {
+            pay_from_program_account(waitlist_account, treasury_account, fee_amount)?;
+            emit_event(&RPSEvent::FeeCollected {
+                source: *game_account.key,
+                recipient: *treasury_account.key,
+                amount: fee_amount,
+            });
+        }
+        waitlist.serialize(&mut *waitlist_account.data.borrow_mut())?;
+        emit_event(&RPSEvent::PlayerJoined {
+            game: *game_account.key,
+            player: promoted,
+        });
+        if game.players.len() >= game.player_count as usize {
+            enter_phase(game_account.key, &mut game, Transition::Fill, now)?;
+        }
+    }
+
+    game.last_action_timestamp = now;
+    game.serialize(&mut *game_account.data.borrow_mut())?;
+    debug_msg!("{} left game {}", leaver.key, game_account.key);
+    Ok(())
+}
+
+/* ─────────────────────  Refund Waitlist  ───────────────────────── */
+fn process_refund_waitlist(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let ai = &mut accounts.iter();
+    let caller = next_account_info(ai)?;
+    let game_account = next_account_info(ai)?;
+    let waitlist_account = next_account_info(ai)?;
+
+    if !caller.is_signer {
+        return Err(RPSError::NotAuthorized.into());
+    }
+    assert_owned_by(game_account, program_id)?;
+    let game = Game::unpack_from(&game_account.data.borrow())?;
+    if !game.has_started() {
+        msg!("Waitlisted players may still be promoted");
+        return Err(RPSError::InvalidGameState.into());
+    }
+    assert_owned_by(waitlist_account, program_id)?;
+    let mut waitlist = load_waitlist(program_id, game_account.key, waitlist_account)?;
+    if waitlist.queue.is_empty() {
+        return Err(RPSError::AlreadyClaimed.into());
+    }
+
+    for queued in &waitlist.queue {
+        let wallet = next_account_info(ai)?;
+        if wallet.key != queued {
+            msg!("Refund accounts must follow the queue order");
+            return Err(RPSError::InvalidParameter.into());
+        }
+        if game.entry_fee > 0 {
+            pay_from_program_account(waitlist_account, wallet, game.entry_fee)?;
+        }
+    }
+
+    debug_msg!("Refunded {} waitlisted entries for {}", waitlist.queue.len(), game_account.key);
+    emit_event(&RPSEvent::WaitlistRefunded {
+        game: *game_account.key,
+        entries: waitlist.queue.len() as u8,
+    });
+    waitlist.queue.clear();
+    waitlist.serialize(&mut *waitlist_account.data.borrow_mut())?;
+    Ok(())
+}
+
+/* ╔══════════════════════════════════════════════════════════════════╗
+   ║                          GAME RESULTS                            ║
+   ╚══════════════════════════════════════════════════════════════════╝ */
+
+// Compact, write-once record of how a game ended; see `game_result_address`
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct GameResult {
+    pub game: Pubkey,
+    pub host: Pubkey,
+    pub players: Vec<Pubkey>,
+    pub scores: Vec<u8>,       // By seat, parallel to `players`
+    pub winners: u8,           // Bit per seat holding the top score
+    pub pot: u64,              // Pot when the game finished
+    pub entry_fee: u64,
+    pub currency_mode: CurrencyMode,
+    pub token_mint: Option<Pubkey>,
+    pub rounds: u8,
+    pub finished_at: u64,      // Last action before the game finished
+    pub recorded_at: u64,
+}
+
+impl GameResult {
+    pub fn unpack_from(data: &[u8]) -> Result<Self, ProgramError> {
+        let mut slice = data;
+        Self::deserialize(&mut slice).map_err(|_| ProgramError::InvalidAccountData)
+    }
+}
+
+/* ─────────────────────  Record Game Result  ────────────────────── */
+fn process_record_game_result(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let ai = &mut accounts.iter();
+    let payer = next_account_info(ai)?;
+    let game_account = next_account_info(ai)?;
+    let result_account = next_account_info(ai)?;
+    let system_program = next_account_info(ai)?;
+    check_system_program(system_program)?;
+
+    if !payer.is_signer {
+        return Err(RPSError::NotAuthorized.into());
+    }
+    assert_owned_by(game_account, program_id)?;
+    let game = Game::unpack_from(&game_account.data.borrow())?;
+    state_machine::require(game.state, Action::Settle)?;
+
+    let (result_key, bump) = game_result_address(program_id, game_account.key);
+    if *result_account.key != result_key {
+        msg!("Result account is not the game's result PDA");
+        return Err(RPSError::InvalidParameter.into());
+    }
+    // Results are immutable once written
+    if result_account.owner == program_id {
+        msg!("Result already recorded");
+        return Err(RPSError::InvalidGameState.into());
+    }
+
+    let result = GameResult {
+        game: *game_account.key,
+        host: game.host,
+        players: game.players.iter().map(|p| p.pubkey).collect(),
+        scores: game.players.iter().map(|p| p.score).collect(),
+        winners: game.winner_bitmap(),
+        pot: game.pot_snapshot,
+        entry_fee: game.entry_fee,
+        currency_mode: game.currency_mode,
+        token_mint: game.token_mint,
+        rounds: game.current_round,
+        finished_at: game.last_action_timestamp,
+        recorded_at: Clock::get()?.unix_timestamp as u64,
+    };
+    let data = result.try_to_vec()?;
+
+    let rent = Rent::get()?;
+    invoke_signed(
+        &system_instruction::create_account(
+            payer.key,
+            result_account.key,
+            rent.minimum_balance(data.len()),
+            data.len() as u64,
+            program_id,
+        ),
+        &[payer.clone(), result_account.clone(), system_program.clone()],
+        &[&[GAME_RESULT_SEED, game_account.key.as_ref(), &[bump]]],
+    )?;
+    result_account.data.borrow_mut().copy_from_slice(&data);
+    debug_msg!("Result recorded for game {}", game_account.key);
+    Ok(())
+}
+
+// Largest SOL wins an archive keeps
+pub const ARCHIVE_TOP_WINNERS: usize = 5;
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ArchivedWin {
+    pub player: Pubkey,
+    pub amount: u64, // Winner's share of the pot, in lamports
+}
+
+// Aggregate of the game results folded in from one archive epoch; see
+// `result_archive_address`
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Default)]
+pub struct ResultArchive {
+    pub epoch: u64,
+    pub games: u32,
+    pub token_games: u32,
+    pub sol_volume: u64,                // SOL pots, in lamports
+    pub top_winners: Vec<ArchivedWin>,  // Largest SOL wins, largest first
+}
+
+impl ResultArchive {
+    pub const SPACE: usize = 8 + 4 + 4 + 8 + 4 + ARCHIVE_TOP_WINNERS * (32 + 8);
+
+    pub fn unpack_from(data: &[u8]) -> Result<Self, ProgramError> {
+        let mut slice = data;
+        Self::deserialize(&mut slice).map_err(|_| ProgramError::InvalidAccountData)
+    }
+
+    // Adds one result; a SOL pot is split evenly over its winning seats
+    pub fn fold(&mut self, result: &GameResult) {
+        self.games = self.games.saturating_add(1);
+        if !matches!(result.currency_mode, CurrencyMode::SOL) {
+            self.token_games = self.token_games.saturating_add(1);
+            return;
+        }
+        self.sol_volume = self.sol_volume.saturating_add(result.pot);
+
+        let winners: Vec<Pubkey> = result
+            .players
+            .iter()
+            .enumerate()
+            .filter(|(seat, _)| result.winners & (1u8 << seat) != 0)
+            .map(|(_, player)| *player)
+            .collect();
+        if winners.is_empty() || result.pot == 0 {
+            return;
+        }
+        let amount = result.pot / winners.len() as u64;
+        for player in winners {
+            let at = self.top_winners.iter().position(|w| w.amount < amount).unwrap_or(self.top_winners.len());
+            self.top_winners.insert(at, ArchivedWin { player, amount });
+        }
+        self.top_winners.truncate(ARCHIVE_TOP_WINNERS);
+    }
+}
+
+/* ─────────────────────  Archive Game Results  ──────────────────── */
+fn process_archive_game_results(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    epoch: u64,
+) -> ProgramResult {
+    let ai = &mut accounts.iter();
+    let cranker = next_account_info(ai)?;
+    let archive_account = next_account_info(ai)?;
+    let system_program = next_account_info(ai)?;
+    check_system_program(system_program)?;
+
+    if !cranker.is_signer {
+        return Err(RPSError::NotAuthorized.into());
+    }
+    let now = Clock::get()?.unix_timestamp as u64;
+    if epoch >= now / ARCHIVE_EPOCH_SECONDS {
+        msg!("Archive epoch {} is not over yet", epoch);
+        return Err(RPSError::InvalidGameState.into());
+    }
+    let (archive_key, bump) = result_archive_address(program_id, epoch);
+    if *archive_account.key != archive_key {
+        msg!("Archive account is not the epoch's archive PDA");
+        return Err(RPSError::InvalidParameter.into());
+    }
+
+    let mut archive = if archive_account.owner == program_id {
+        ResultArchive::unpack_from(&archive_account.data.borrow())?
+    } else {
+        let rent = Rent::get()?;
+        invoke_signed(
+            &system_instruction::create_account(
+                cranker.key,
+                archive_account.key,
+                rent.minimum_balance(ResultArchive::SPACE),
+                ResultArchive::SPACE as u64,
+                program_id,
+            ),
+            &[cranker.clone(), archive_account.clone(), system_program.clone()],
+            &[&[RESULT_ARCHIVE_SEED, &epoch.to_le_bytes(), &[bump]]],
+        )?;
+        ResultArchive {
+            epoch,
+            ..ResultArchive::default()
+        }
+    };
+
+    let mut folded = 0u32;
+    while let Some(result_account) = ai.next() {
+        let game_account = next_account_info(ai)?;
+        assert_owned_by(result_account, program_id)?;
+        let result = GameResult::unpack_from(&result_account.data.borrow())?;
+        // A closed or listed-twice result no longer matches its PDA
+        if *result_account.key != game_result_address(program_id, &result.game).0 {
+            msg!("{} is not a game result PDA", result_account.key);
+            return Err(RPSError::InvalidParameter.into());
+        }
+        if *game_account.key != result.game {
+            msg!("{} is not the game of result {}", game_account.key, result_account.key);
+            return Err(RPSError::InvalidParameter.into());
+        }
+        if result.finished_at / ARCHIVE_EPOCH_SECONDS != epoch {
+            msg!("Result for {} is not from epoch {}", result.game, epoch);
+            return Err(RPSError::InvalidParameter.into());
+        }
+        // CloseGame needs the result, so it must outlive the game
+        if game_account.lamports() != 0 {
+            msg!("Close game {} before archiving its result", result.game);
+            return Err(RPSError::InvalidGameState.into());
+        }
+        archive.fold(&result);
+        close_program_account(result_account, cranker)?;
+        folded += 1;
+    }
+
+    archive.serialize(&mut *archive_account.data.borrow_mut())?;
+    debug_msg!("Archived {} results into epoch {}", folded, epoch);
+    Ok(())
+}
+
+/* ╔══════════════════════════════════════════════════════════════════╗
+   ║                           GOVERNANCE                             ║
+   ╚══════════════════════════════════════════════════════════════════╝ */
+
+// A fee or payout setting a proposal changes. Each is checked and applied
+// exactly like the operator instruction of the same name.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub enum ParameterChange {
+    FeePolicy {
+        fee_collector: Option<Pubkey>,
+        fee_bps: Option<u16>,
+    },
+    ClaimPolicy {
+        large_payout_threshold: u64,
+        claim_delay_secs: u64,
+    },
+    PotGuarantee {
+        guaranteed_min_pot: u64,
+        subsidy_entry_floor: u64,
+    },
+}
+
+impl ParameterChange {
+    pub const MAX_SIZE: usize = 1 + 33 + 3; // FeePolicy, the largest
+
+    fn apply(&self, config: &mut ProgramConfig) -> ProgramResult {
+        match *self {
+            ParameterChange::FeePolicy { fee_collector, fee_bps } => apply_fee_policy(config, fee_collector, fee_bps),
+            ParameterChange::ClaimPolicy {
+                large_payout_threshold,
+                claim_delay_secs,
+            } => apply_claim_policy(config, large_payout_threshold, claim_delay_secs),
+            ParameterChange::PotGuarantee {
+                guaranteed_min_pot,
+                subsidy_entry_floor,
+            } => apply_pot_guarantee(config, guaranteed_min_pot, subsidy_entry_floor),
+        }
+    }
+}
+
+// Voting rules and proposal counter; see `governance_address`
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct Governance {
+    pub stake_mint: Pubkey,
+    pub quorum: u64,         // Staked tokens that must vote, for and against together
+    pub voting_secs: u64,    // How long a proposal is open for votes
+    pub total_staked: u64,
+    pub proposal_count: u64, // Index of the next proposal
+}
+
+impl Governance {
+    pub const SPACE: usize = 32 + 8 + 8 + 8 + 8;
+
+    pub fn unpack_from(data: &[u8]) -> Result<Self, ProgramError> {
+        let mut slice = data;
+        Self::deserialize(&mut slice).map_err(|_| ProgramError::InvalidAccountData)
+    }
+}
+
+// A staker's tokens in the governance vault; see `voter_stake_address`
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct VoterStake {
+    pub voter: Pubkey,
+    pub amount: u64,
+    pub locked_until: u64, // Close of the latest vote cast; no withdrawals before
+}
+
+impl VoterStake {
+    pub const SPACE: usize = 32 + 8 + 8;
+
+    pub fn unpack_from(data: &[u8]) -> Result<Self, ProgramError> {
+        let mut slice = data;
+        Self::deserialize(&mut slice).map_err(|_| ProgramError::InvalidAccountData)
+    }
+}
+
+// A proposed change and its tally; see `proposal_address`
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct Proposal {
+    pub index: u64,
+    pub proposer: Pubkey,
+    pub change: ParameterChange,
+    pub voting_ends_at: u64,
+    pub votes_for: u64,
+    pub votes_against: u64,
+    pub executed: bool,
+}
+
+impl Proposal {
+    pub const SPACE: usize = 8 + 32 + ParameterChange::MAX_SIZE + 8 + 8 + 8 + 1;
+
+    pub fn unpack_from(data: &[u8]) -> Result<Self, ProgramError> {
+        let mut slice = data;
+        Self::deserialize(&mut slice).map_err(|_| ProgramError::InvalidAccountData)
+    }
+
+    // Enough stake voted, and more of it for than against
+    pub fn passed(&self, quorum: u64) -> bool {
+        self.votes_for.saturating_add(self.votes_against) >= quorum && self.votes_for > self.votes_against
+    }
+}
+
+// One voter's vote on one proposal; see `vote_record_address`
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct VoteRecord {
+    pub weight: u64,
+    pub approve: bool,
+}
+
+impl VoteRecord {
+    pub const SPACE: usize = 8 + 1;
+}
+
+// Load the governance PDA, checking it is the real one
+fn load_governance(program_id: &Pubkey, governance_account: &AccountInfo) -> Result<Governance, ProgramError> {
+    if *governance_account.key != governance_address(program_id).0 || governance_account.owner != program_id {
+        msg!("Governance account is not the governance PDA");
+        return Err(RPSError::InvalidParameter.into());
+    }
+    Governance::unpack_from(&governance_account.data.borrow())
+}
+
+// Load `voter`'s stake; a PDA that was never created stakes nothing
+fn load_voter_stake(program_id: &Pubkey, voter: &Pubkey, stake_account: &AccountInfo) -> Result<VoterStake, ProgramError> {
+    if *stake_account.key != voter_stake_address(program_id, voter).0 {
+        msg!("Voter stake account does not match the voter");
+        return Err(RPSError::InvalidParameter.into());
+    }
+    if stake_account.owner != program_id {
+        return Ok(VoterStake {
+            voter: *voter,
+            amount: 0,
+            locked_until: 0,
+        });
+    }
+    VoterStake::unpack_from(&stake_account.data.borrow())
+}
+
+// Load a proposal, checking the account is the PDA its index derives
+fn load_proposal(program_id: &Pubkey, proposal_account: &AccountInfo) -> Result<Proposal, ProgramError> {
+    assert_owned_by(proposal_account, program_id)?;
+    let proposal = Proposal::unpack_from(&proposal_account.data.borrow())?;
+    if proposal_address(program_id, proposal.index).0 != *proposal_account.key {
+        msg!("Account is not a proposal");
+        return Err(RPSError::InvalidParameter.into());
+    }
+    Ok(proposal)
+}
+
+// Checks `vault` is the governance vault for `mint`
+fn check_governance_vault(program_id: &Pubkey, vault: &AccountInfo, mint: &Pubkey) -> ProgramResult {
+    if *vault.key != governance_vault_address(program_id, mint) {
+        msg!("Governance token account must be the governance PDA's associated token account");
+        return Err(RPSError::InvalidTokenAccount.into());
+    }
+    check_token_account(vault, &governance_address(program_id).0, mint)
+}
+
+/* ─────────────────────  Initialize Governance  ─────────────────── */
+fn process_initialize_governance(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    stake_mint: Pubkey,
+    quorum: u64,
+    voting_secs: u64,
+) -> ProgramResult {
+    let ai = &mut accounts.iter();
+    let authority = next_account_info(ai)?;
+    let config_account = next_account_info(ai)?;
+    let governance_account = next_account_info(ai)?;
+    let system_program = next_account_info(ai)?;
+    check_system_program(system_program)?;
+
+    let mut config = require_config_authority(program_id, authority, config_account)?;
+    if quorum == 0 || voting_secs == 0 {
+        msg!("Quorum and voting period must be greater than 0");
+        return Err(RPSError::InvalidParameter.into());
+    }
+    let (governance_key, bump) = governance_address(program_id);
+    if *governance_account.key != governance_key {
+        msg!("Governance account is not the governance PDA");
+        return Err(RPSError::InvalidParameter.into());
+    }
+    if governance_account.owner == program_id {
+        msg!("Governance already initialized");
+        return Err(RPSError::InvalidGameState.into());
+    }
+
+    invoke_signed(
+        &system_instruction::create_account(
+            authority.key,
+            governance_account.key,
+            Rent::get()?.minimum_balance(Governance::SPACE),
+            Governance::SPACE as u64,
+            program_id,
+        ),
+        &[authority.clone(), governance_account.clone(), system_program.clone()],
+        &[&[GOVERNANCE_SEED, &[bump]]],
+    )?;
+    let governance = Governance {
+        stake_mint,
+        quorum,
+        voting_secs,
+        total_staked: 0,
+        proposal_count: 0,
+    };
+    governance.serialize(&mut *governance_account.data.borrow_mut())?;
+
+    config.governed = true;
+    config.serialize(&mut *config_account.data.borrow_mut())?;
+    debug_msg!("Stakers of {} now govern fees and payouts", stake_mint);
+    Ok(())
+}
+
+/* ─────────────────────  Stake / Unstake  ───────────────────────── */
+fn process_stake_governance_tokens(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    amount: u64,
+) -> ProgramResult {
+    let ai = &mut accounts.iter();
+    let staker = next_account_info(ai)?;
+    let stake_account = next_account_info(ai)?;
+    let governance_account = next_account_info(ai)?;
+    let system_program = next_account_info(ai)?;
+    check_system_program(system_program)?;
+    let token_program = next_account_info(ai)?;
+    check_token_program(token_program)?;
+    let source = next_account_info(ai)?;
+    let vault = next_account_info(ai)?;
+
+    if !staker.is_signer {
+        return Err(RPSError::NotAuthorized.into());
+    }
+    if amount == 0 {
+        msg!("Stake must be greater than 0");
+        return Err(RPSError::InvalidParameter.into());
+    }
+    let mut governance = load_governance(program_id, governance_account)?;
+    let mut stake = load_voter_stake(program_id, staker.key, stake_account)?;
+    check_token_account(source, staker.key, &governance.stake_mint)?;
+    check_governance_vault(program_id, vault, &governance.stake_mint)?;
+
+    if stake_account.owner != program_id {
+        let (_, bump) = voter_stake_address(program_id, staker.key);
+        invoke_signed(
+            &system_instruction::create_account(
+                staker.key,
+                stake_account.key,
+                Rent::get()?.minimum_balance(VoterStake::SPACE),
+                VoterStake::SPACE as u64,
+                program_id,
+            ),
+            &[staker.clone(), stake_account.clone(), system_program.clone()],
+            &[&[VOTER_STAKE_SEED, staker.key.as_ref(), &[bump]]],
+        )?;
+    }
+    invoke(
+        &spl_token::instruction::transfer(token_program.key, source.key, vault.key, staker.key, &[], amount)?,
+        &[source.clone(), vault.clone(), staker.clone(), token_program.clone()],
+    )?;
+
+    stake.amount = stake.amount.checked_add(amount).ok_or(RPSError::FeeCalculationError)?;
+    governance.total_staked = governance
+        .total_staked
+        .checked_add(amount)
+        .ok_or(RPSError::FeeCalculationError)?;
+    stake.serialize(&mut *stake_account.data.borrow_mut())?;
+    governance.serialize(&mut *governance_account.data.borrow_mut())?;
+    debug_msg!("{} staked {}, {} in total", staker.key, amount, stake.amount);
+    Ok(())
+}
+
+fn process_unstake_governance_tokens(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    amount: u64,
+) -> ProgramResult {
+    let ai = &mut accounts.iter();
+    let staker = next_account_info(ai)?;
+    let stake_account = next_account_info(ai)?;
+    let governance_account = next_account_info(ai)?;
+    let token_program = next_account_info(ai)?;
+    check_token_program(token_program)?;
+    let destination = next_account_info(ai)?;
+    let vault = next_account_info(ai)?;
+
+    if !staker.is_signer {
+        return Err(RPSError::NotAuthorized.into());
+    }
+    let mut governance = load_governance(program_id, governance_account)?;
+    let mut stake = load_voter_stake(program_id, staker.key, stake_account)?;
+    if amount == 0 || amount > stake.amount {
+        msg!("Can withdraw 1 to {} staked tokens", stake.amount);
+        return Err(RPSError::InsufficientFunds.into());
+    }
+    let now = Clock::get()?.unix_timestamp as u64;
+    if now < stake.locked_until {
+        msg!("Stake is locked by a vote until {}", stake.locked_until);
+        return Err(RPSError::InvalidGameState.into());
+    }
+    check_token_account(destination, staker.key, &governance.stake_mint)?;
+    check_governance_vault(program_id, vault, &governance.stake_mint)?;
+
+    let (_, bump) = governance_address(program_id);
+    invoke_signed(
+        &spl_token::instruction::transfer(
+            token_program.key,
+            vault.key,
+            destination.key,
+            governance_account.key,
+            &[],
+            amount,
+        )?,
+        &[vault.clone(), destination.clone(), governance_account.clone(), token_program.clone()],
+        &[&[GOVERNANCE_SEED, &[bump]]],
+    )?;
+
+    stake.amount -= amount;
+    governance.total_staked = governance.total_staked.saturating_sub(amount);
+    stake.serialize(&mut *stake_account.data.borrow_mut())?;
+    governance.serialize(&mut *governance_account.data.borrow_mut())?;
+    debug_msg!("{} withdrew {}, {} still staked", staker.key, amount, stake.amount);
+    Ok(())
+}
+
+/* ─────────────────────  Propose Parameter Change  ──────────────── */
+fn process_propose_parameter_change(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    change: ParameterChange,
+) -> ProgramResult {
+    let ai = &mut accounts.iter();
+    let proposer = next_account_info(ai)?;
+    let stake_account = next_account_info(ai)?;
+    let governance_account = next_account_info(ai)?;
+    let config_account = next_account_info(ai)?;
+    let proposal_account = next_account_info(ai)?;
+    let system_program = next_account_info(ai)?;
+    check_system_program(system_program)?;
+
+    if !proposer.is_signer {
+        return Err(RPSError::NotAuthorized.into());
+    }
+    let mut governance = load_governance(program_id, governance_account)?;
+    if load_voter_stake(program_id, proposer.key, stake_account)?.amount == 0 {
+        msg!("Only stakers can make proposals");
+        return Err(RPSError::NotAuthorized.into());
+    }
+    // A change that could never be applied is refused up front
+    change.apply(&mut load_config(program_id, config_account)?)?;
+
+    let index = governance.proposal_count;
+    let (proposal_key, bump) = proposal_address(program_id, index);
+    if *proposal_account.key != proposal_key {
+        msg!("Proposal account is not the PDA for proposal {}", index);
+        return Err(RPSError::InvalidParameter.into());
+    }
+    invoke_signed(
+        &system_instruction::create_account(
+            proposer.key,
+            proposal_account.key,
+            Rent::get()?.minimum_balance(Proposal::SPACE),
+            Proposal::SPACE as u64,
+            program_id,
+        ),
+        &[proposer.clone(), proposal_account.clone(), system_program.clone()],
+        &[&[PROPOSAL_SEED, &index.to_le_bytes(), &[bump]]],
+    )?;
+
+    let now = Clock::get()?.unix_timestamp as u64;
+    let proposal = Proposal {
+        index,
+        proposer: *proposer.key,
+        change,
+        voting_ends_at: now.saturating_add(governance.voting_secs),
+        votes_for: 0,
+        votes_against: 0,
+        executed: false,
+    };
+    proposal.serialize(&mut *proposal_account.data.borrow_mut())?;
+    governance.proposal_count += 1;
+    governance.serialize(&mut *governance_account.data.borrow_mut())?;
+    debug_msg!("Proposal {} open for votes until {}", index, proposal.voting_ends_at);
+    emit_event(&RPSEvent::ProposalCreated {
+        proposal: *proposal_account.key,
+        index,
+        change: proposal.change,
+        voting_ends_at: proposal.voting_ends_at,
+    });
+    Ok(())
+}
+
+/* ─────────────────────  Cast Vote  ─────────────────────────────── */
+fn process_cast_vote(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    approve: bool,
+) -> ProgramResult {
+    let ai = &mut accounts.iter();
+    let voter = next_account_info(ai)?;
+    let stake_account = next_account_info(ai)?;
+    let proposal_account = next_account_info(ai)?;
+    let record_account = next_account_info(ai)?;
+    let system_program = next_account_info(ai)?;
+    check_system_program(system_program)?;
+
+    if !voter.is_signer {
+        return Err(RPSError::NotAuthorized.into());
+    }
+    let mut stake = load_voter_stake(program_id, voter.key, stake_account)?;
+    if stake.amount == 0 {
+        msg!("Only stakers can vote");
+        return Err(RPSError::NotAuthorized.into());
+    }
+    let mut proposal = load_proposal(program_id, proposal_account)?;
+    let now = Clock::get()?.unix_timestamp as u64;
+    if now >= proposal.voting_ends_at {
+        msg!("Voting on proposal {} closed at {}", proposal.index, proposal.voting_ends_at);
+        return Err(RPSError::InvalidGameState.into());
+    }
+    let (record_key, bump) = vote_record_address(program_id, proposal_account.key, voter.key);
+    if *record_account.key != record_key {
+        msg!("Vote record account does not match the proposal and voter");
+        return Err(RPSError::InvalidParameter.into());
+    }
+    if record_account.owner == program_id {
+        msg!("Already voted on proposal {}", proposal.index);
+        return Err(RPSError::InvalidGameState.into());
+    }
+
+    invoke_signed(
+        &system_instruction::create_account(
+            voter.key,
+            record_account.key,
+            Rent::get()?.minimum_balance(VoteRecord::SPACE),
+            VoteRecord::SPACE as u64,
+            program_id,
+        ),
+        &[voter.clone(), record_account.clone(), system_program.clone()],
+        &[&[VOTE_RECORD_SEED, proposal_account.key.as_ref(), voter.key.as_ref(), &[bump]]],
+    )?;
+    VoteRecord {
+        weight: stake.amount,
+        approve,
+    }
+    .serialize(&mut *record_account.data.borrow_mut())?;
+
+    if approve {
+        proposal.votes_for = proposal.votes_for.saturating_add(stake.amount);
+    } else {
+        proposal.votes_against = proposal.votes_against.saturating_add(stake.amount);
+    }
+    stake.locked_until = stake.locked_until.max(proposal.voting_ends_at);
+    proposal.serialize(&mut *proposal_account.data.borrow_mut())?;
+    stake.serialize(&mut *stake_account.data.borrow_mut())?;
+    let side = if approve { "for" } else { "against" };
+    debug_msg!("{} voted {} proposal {} with {}", voter.key, side, proposal.index, stake.amount);
+    emit_event(&RPSEvent::VoteCast {
+        proposal: *proposal_account.key,
+        voter: *voter.key,
+        approve,
+        weight: stake.amount,
+    });
+    Ok(())
+}
+
+/* ─────────────────────  Execute Proposal  ──────────────────────── */
+fn process_execute_proposal(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let ai = &mut accounts.iter();
+    let caller = next_account_info(ai)?;
+    let proposal_account = next_account_info(ai)?;
+    let governance_account = next_account_info(ai)?;
+    let config_account = next_account_info(ai)?;
+
+    if !caller.is_signer {
+        return Err(RPSError::NotAuthorized.into());
+    }
+    let governance = load_governance(program_id, governance_account)?;
+    let mut proposal = load_proposal(program_id, proposal_account)?;
+    let now = Clock::get()?.unix_timestamp as u64;
+    if now < proposal.voting_ends_at {
+        msg!("Voting on proposal {} is open until {}", proposal.index, proposal.voting_ends_at);
+        return Err(RPSError::TimeoutNotReached.into());
+    }
+    if proposal.executed {
+        msg!("Proposal {} was already executed", proposal.index);
+        return Err(RPSError::InvalidGameState.into());
+    }
+    if !proposal.passed(governance.quorum) {
+        msg!(
+            "Proposal {} did not pass: {} for, {} against, quorum {}",
+            proposal.index,
+            proposal.votes_for,
+            proposal.votes_against,
+            governance.quorum
+        );
+        return Err(RPSError::InvalidGameState.into());
+    }
+
+    let mut config = load_config(program_id, config_account)?;
+    proposal.change.apply(&mut config)?;
+    config.serialize(&mut *config_account.data.borrow_mut())?;
+    proposal.executed = true;
+    proposal.serialize(&mut *proposal_account.data.borrow_mut())?;
+    debug_msg!("Proposal {} executed: {:?}", proposal.index, proposal.change);
+    emit_event(&RPSEvent::ProposalExecuted {
+        proposal: *proposal_account.key,
+        index: proposal.index,
+    });
+    Ok(())
+}
+
+/* ╔══════════════════════════════════════════════════════════════════╗
+   ║                          COLD TREASURY                           ║
+   ╚══════════════════════════════════════════════════════════════════╝ */
+
+// What a cold proposal does once approved and timelocked
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub enum ColdAction {
+    // Pay `amount` lamports out of the cold treasury
+    Withdraw { recipient: Pubkey, amount: u64 },
+    // Change how much the hot treasury keeps before a sweep
+    SetHotCap { hot_cap: u64 },
+    // Replace the signers; proposals still pending under the old ones lapse
+    SetSigners { signers: Vec<Pubkey>, threshold: u8 },
+}
+
+impl ColdAction {
+    pub const MAX_SIZE: usize = 1 + 4 + MAX_COLD_SIGNERS * 32 + 1; // SetSigners, the largest
+}
+
+// Multisig and timelock guarding the swept fees; see `cold_treasury_address`.
+// The account holds the lamports itself.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct ColdTreasury {
+    pub signers: Vec<Pubkey>,
+    pub threshold: u8,       // Approvals a proposal needs
+    pub timelock_secs: u64,  // Wait between proposing and executing
+    pub hot_cap: u64,        // Lamports the hot treasury keeps; the rest is swept here
+    pub proposal_count: u64, // Index of the next proposal
+    pub signer_set: u32,     // Bumped whenever the signers change
+}
+
+impl ColdTreasury {
+    pub const SPACE: usize = 4 + MAX_COLD_SIGNERS * 32 + 1 + 8 + 8 + 8 + 4;
+
+    pub fn unpack_from(data: &[u8]) -> Result<Self, ProgramError> {
+        let mut slice = data;
+        Self::deserialize(&mut slice).map_err(|_| ProgramError::InvalidAccountData)
+    }
+
+    fn signer_index(&self, key: &Pubkey) -> Result<usize, ProgramError> {
+        self.signers.iter().position(|s| s == key).ok_or_else(|| {
+            msg!("{} is not a cold treasury signer", key);
+            RPSError::NotAuthorized.into()
+        })
+    }
+}
+
+// A proposed cold treasury action and its approvals; see
+// `cold_proposal_address`
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct ColdProposal {
+    pub index: u64,
+    pub action: ColdAction,
+    pub ready_at: u64,    // Executable from here on, given enough approvals
+    pub signer_set: u32,  // The treasury's `signer_set` when proposed
+    pub approvals: u8,    // Bit n: signer n approved
+    pub executed: bool,
+}
+
+impl ColdProposal {
+    pub const SPACE: usize = 8 + ColdAction::MAX_SIZE + 8 + 4 + 1 + 1;
+
+    pub fn unpack_from(data: &[u8]) -> Result<Self, ProgramError> {
+        let mut slice = data;
+        Self::deserialize(&mut slice).map_err(|_| ProgramError::InvalidAccountData)
+    }
+}
+
+// Signers and threshold a cold treasury can be given: distinct keys, and a
+// threshold no single key meets
+fn check_cold_signers(signers: &[Pubkey], threshold: u8) -> ProgramResult {
+    let distinct = signers.iter().enumerate().all(|(i, s)| !signers[..i].contains(s));
+    if signers.len() > MAX_COLD_SIGNERS || !distinct {
+        msg!("A cold treasury takes up to {} distinct signers", MAX_COLD_SIGNERS);
+        return Err(RPSError::InvalidParameter.into());
+    }
+    if threshold < MIN_COLD_THRESHOLD || threshold as usize > signers.len() {
+        msg!("Threshold must be between {} and the number of signers", MIN_COLD_THRESHOLD);
+        return Err(RPSError::InvalidParameter.into());
+    }
+    Ok(())
+}
+
+// Load the cold treasury PDA, checking it is the real one
+fn load_cold_treasury(program_id: &Pubkey, cold_account: &AccountInfo) -> Result<ColdTreasury, ProgramError> {
+    if *cold_account.key != cold_treasury_address(program_id).0 || cold_account.owner != program_id {
+        msg!("Cold treasury account is not the cold treasury PDA");
+        return Err(RPSError::InvalidParameter.into());
+    }
+    ColdTreasury::unpack_from(&cold_account.data.borrow())
+}
+
+// Load a cold proposal that is still pending under the current signers
+fn load_pending_cold_proposal(
+    program_id: &Pubkey,
+    cold: &ColdTreasury,
+    proposal_account: &AccountInfo,
+) -> Result<ColdProposal, ProgramError> {
+    assert_owned_by(proposal_account, program_id)?;
+    let proposal = ColdProposal::unpack_from(&proposal_account.data.borrow())?;
+    if cold_proposal_address(program_id, proposal.index).0 != *proposal_account.key {
+        msg!("Account is not a cold proposal");
+        return Err(RPSError::InvalidParameter.into());
+    }
+    if proposal.executed || proposal.signer_set != cold.signer_set {
+        msg!("Cold proposal {} is no longer pending", proposal.index);
+        return Err(RPSError::InvalidGameState.into());
+    }
+    Ok(proposal)
+}
+
+/* ─────────────────────  Initialize Cold Treasury  ──────────────── */
+fn process_initialize_cold_treasury(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    signers: Vec<Pubkey>,
+    threshold: u8,
+    timelock_secs: u64,
+    hot_cap: u64,
+) -> ProgramResult {
+    let ai = &mut accounts.iter();
+    let authority = next_account_info(ai)?;
+    let config_account = next_account_info(ai)?;
+    let cold_account = next_account_info(ai)?;
+    let system_program = next_account_info(ai)?;
+    check_system_program(system_program)?;
+
+    require_config_authority(program_id, authority, config_account)?;
+    check_cold_signers(&signers, threshold)?;
+    if timelock_secs < MIN_COLD_TIMELOCK_SECS {
+        msg!("The cold treasury timelock must be at least {} seconds", MIN_COLD_TIMELOCK_SECS);
+        return Err(RPSError::InvalidParameter.into());
+    }
+    let (cold_key, bump) = cold_treasury_address(program_id);
+    if *cold_account.key != cold_key {
+        msg!("Cold treasury account is not the cold treasury PDA");
+        return Err(RPSError::InvalidParameter.into());
+    }
+    if cold_account.owner == program_id {
+        msg!("Cold treasury already initialized");
+        return Err(RPSError::InvalidGameState.into());
+    }
+
+    invoke_signed(
+        &system_instruction::create_account(
+            authority.key,
+            cold_account.key,
+            Rent::get()?.minimum_balance(ColdTreasury::SPACE),
+            ColdTreasury::SPACE as u64,
+            program_id,
+        ),
+        &[authority.clone(), cold_account.clone(), system_program.clone()],
+        &[&[COLD_TREASURY_SEED, &[bump]]],
+    )?;
+    let cold = ColdTreasury {
+        signers,
+        threshold,
+        timelock_secs,
+        hot_cap,
+        proposal_count: 0,
+        signer_set: 0,
+    };
+    cold.serialize(&mut *cold_account.data.borrow_mut())?;
+    debug_msg!("Cold treasury: {} of {} signers, {}s timelock", threshold, cold.signers.len(), timelock_secs);
+    Ok(())
+}
+
+/* ─────────────────────  Sweep Treasury  ────────────────────────── */
+fn process_sweep_treasury(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let ai = &mut accounts.iter();
+    let caller = next_account_info(ai)?;
+    let treasury_account = next_account_info(ai)?;
+    check_treasury(program_id, treasury_account)?;
+    let cold_account = next_account_info(ai)?;
+    let system_program = next_account_info(ai)?;
+    check_system_program(system_program)?;
+
+    if !caller.is_signer {
+        return Err(RPSError::NotAuthorized.into());
+    }
+    let cold = load_cold_treasury(program_id, cold_account)?;
+
+    // The hot treasury stays rent exempt whatever the cap
+    let keep = cold.hot_cap.max(Rent::get()?.minimum_balance(0));
+    let mut amount = treasury_account.lamports().saturating_sub(keep);
+    if amount == 0 {
+        msg!("Treasury holds no more than its cap of {}", keep);
+        return Err(RPSError::InsufficientFunds.into());
+    }
+    let (_, bump) = treasury_address(program_id);
+
+    // The insurance fund, once configured, takes its share of the sweep
+    if let Some(insurance_account) = ai.next().filter(|a| a.owner == program_id) {
+        let mut fund = load_insurance_fund(program_id, insurance_account)?;
+        let share = simulate::fee_at(amount, fund.share_bps);
+        if share > 0 {
+            invoke_signed(
+                &system_instruction::transfer(treasury_account.key, insurance_account.key, share),
+                &[treasury_account.clone(), insurance_account.clone(), system_program.clone()],
+                &[&[TREASURY_SEED, &[bump]]],
+            )?;
+            amount -= share;
+            fund.contributed += share;
+            fund.serialize(&mut *insurance_account.data.borrow_mut())?;
+            debug_msg!("Insurance fund took {} of the sweep", share);
+            emit_event(&RPSEvent::InsuranceContributed {
+                amount: share,
+                balance: insurance_account.lamports(),
+            });
+        }
+    }
+    invoke_signed(
+        &system_instruction::transfer(treasury_account.key, cold_account.key, amount),
+        &[treasury_account.clone(), cold_account.clone(), system_program.clone()],
+        &[&[TREASURY_SEED, &[bump]]],
+    )?;
+    debug_msg!("Swept {} into the cold treasury", amount);
+    emit_event(&RPSEvent::TreasurySwept {
+        amount,
+        hot_balance: treasury_account.lamports(),
+    });
+    Ok(())
+}
+
+/* ─────────────────────  Propose Cold Action  ───────────────────── */
+fn process_propose_cold_action(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    action: ColdAction,
+) -> ProgramResult {
+    let ai = &mut accounts.iter();
+    let signer = next_account_info(ai)?;
+    let cold_account = next_account_info(ai)?;
+    let proposal_account = next_account_info(ai)?;
+    let system_program = next_account_info(ai)?;
+    check_system_program(system_program)?;
+
+    if !signer.is_signer {
+        return Err(RPSError::NotAuthorized.into());
+    }
+    let mut cold = load_cold_treasury(program_id, cold_account)?;
+    let seat = cold.signer_index(signer.key)?;
+    // An action that could never be carried out is refused up front
+    match &action {
+        ColdAction::Withdraw { amount, .. } if *amount == 0 => {
+            msg!("A withdrawal needs an amount");
+            return Err(RPSError::InvalidParameter.into());
+        }
+        ColdAction::SetSigners { signers, threshold } => check_cold_signers(signers, *threshold)?,
+        _ => {}
+    }
+
+    let index = cold.proposal_count;
+    let (proposal_key, bump) = cold_proposal_address(program_id, index);
+    if *proposal_account.key != proposal_key {
+        msg!("Proposal account is not the PDA for cold proposal {}", index);
+        return Err(RPSError::InvalidParameter.into());
+    }
+    invoke_signed(
+        &system_instruction::create_account(
+            signer.key,
+            proposal_account.key,
+            Rent::get()?.minimum_balance(ColdProposal::SPACE),
+            ColdProposal::SPACE as u64,
+            program_id,
+        ),
+        &[signer.clone(), proposal_account.clone(), system_program.clone()],
+        &[&[COLD_PROPOSAL_SEED, &index.to_le_bytes(), &[bump]]],
+    )?;
+
+    let now = Clock::get()?.unix_timestamp as u64;
+    let proposal = ColdProposal {
+        index,
+        action,
+        ready_at: now.saturating_add(cold.timelock_secs),
+        signer_set: cold.signer_set,
+        approvals: 1 << seat,
+        executed: false,
+    };
+    proposal.serialize(&mut *proposal_account.data.borrow_mut())?;
+    cold.proposal_count += 1;
+    cold.serialize(&mut *cold_account.data.borrow_mut())?;
+    debug_msg!("Cold proposal {} executable from {}", index, proposal.ready_at);
+    emit_event(&RPSEvent::ColdActionProposed {
+        proposal: *proposal_account.key,
+        index,
+        action: proposal.action,
+        ready_at: proposal.ready_at,
+    });
+    Ok(())
+}
+
+/* ─────────────────────  Approve Cold Action  ───────────────────── */
+fn process_approve_cold_action(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let ai = &mut accounts.iter();
+    let signer = next_account_info(ai)?;
+    let cold_account = next_account_info(ai)?;
+    let proposal_account = next_account_info(ai)?;
+
+    if !signer.is_signer {
+        return Err(RPSError::NotAuthorized.into());
+    }
+    let cold = load_cold_treasury(program_id, cold_account)?;
+    let seat = cold.signer_index(signer.key)?;
+    let mut proposal = load_pending_cold_proposal(program_id, &cold, proposal_account)?;
+    if proposal.approvals & (1 << seat) != 0 {
+        msg!("{} already approved cold proposal {}", signer.key, proposal.index);
+        return Err(RPSError::InvalidGameState.into());
+    }
+
+    proposal.approvals |= 1 << seat;
+    proposal.serialize(&mut *proposal_account.data.borrow_mut())?;
+    debug_msg!("{} approved cold proposal {}", signer.key, proposal.index);
+    emit_event(&RPSEvent::ColdActionApproved {
+        proposal: *proposal_account.key,
+        signer: *signer.key,
+        approvals: proposal.approvals.count_ones() as u8,
+    });
+    Ok(())
+}
+
+/* ─────────────────────  Execute Cold Action  ───────────────────── */
+fn process_execute_cold_action(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let ai = &mut accounts.iter();
+    let caller = next_account_info(ai)?;
+    let cold_account = next_account_info(ai)?;
+    let proposal_account = next_account_info(ai)?;
+
+    if !caller.is_signer {
+        return Err(RPSError::NotAuthorized.into());
+    }
+    let mut cold = load_cold_treasury(program_id, cold_account)?;
+    let mut proposal = load_pending_cold_proposal(program_id, &cold, proposal_account)?;
+    let now = Clock::get()?.unix_timestamp as u64;
+    if now < proposal.ready_at {
+        msg!("Cold proposal {} is timelocked until {}", proposal.index, proposal.ready_at);
+        return Err(RPSError::TimeoutNotReached.into());
+    }
+    let approvals = proposal.approvals.count_ones();
+    if approvals < cold.threshold as u32 {
+        msg!("Cold proposal {} has {} of {} approvals", proposal.index, approvals, cold.threshold);
+        return Err(RPSError::NotAuthorized.into());
+    }
+
+    match &proposal.action {
+        ColdAction::Withdraw { recipient, amount } => {
+            let recipient_account = next_account_info(ai)?;
+            if recipient_account.key != recipient {
+                msg!("Recipient account does not match the proposal");
+                return Err(RPSError::InvalidParameter.into());
+            }
+            pay_from_program_account(cold_account, recipient_account, *amount)?;
+        }
+        ColdAction::SetHotCap { hot_cap } => cold.hot_cap = *hot_cap,
+        ColdAction::SetSigners { signers, threshold } => {
+            cold.signers = signers.clone();
+            cold.threshold = *threshold;
+            cold.signer_set = cold.signer_set.wrapping_add(1);
+        }
+    }
+    cold.serialize(&mut *cold_account.data.borrow_mut())?;
+    proposal.executed = true;
+    proposal.serialize(&mut *proposal_account.data.borrow_mut())?;
+    debug_msg!("Cold proposal {} executed: {:?}", proposal.index, proposal.action);
+    emit_event(&RPSEvent::ColdActionExecuted {
+        proposal: *proposal_account.key,
+        index: proposal.index,
+    });
+    Ok(())
+}
+
+/* ╔══════════════════════════════════════════════════════════════════╗
+   ║                          INSURANCE FUND                          ║
+   ╚══════════════════════════════════════════════════════════════════╝ */
+
+// Reimburses players for funds lost to a program fault; see
+// `insurance_fund_address`. The account holds the lamports itself.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct InsuranceFund {
+    pub share_bps: u16,    // Share of every treasury sweep paid in
+    pub claim_count: u64,  // Index of the next claim
+    pub contributed: u64,  // Lamports paid in so far
+    pub paid_out: u64,     // Lamports reimbursed so far
+}
+
+impl InsuranceFund {
+    pub const SPACE: usize = 2 + 8 + 8 + 8;
+
+    pub fn unpack_from(data: &[u8]) -> Result<Self, ProgramError> {
+        let mut slice = data;
+        Self::deserialize(&mut slice).map_err(|_| ProgramError::InvalidAccountData)
+    }
+}
+
+// A claim on the insurance fund and, once ruled on, what it paid; see
+// `insurance_claim_address`
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct InsuranceClaim {
+    pub index: u64,
+    pub claimant: Pubkey,
+    pub requested: u64,
+    pub evidence: [u8; 32], // Hash of the incident report
+    pub filed_at: u64,
+    pub resolved: bool,
+    pub paid: u64,          // 0 for a rejected claim
+}
+
+impl InsuranceClaim {
+    pub const SPACE: usize = 8 + 32 + 8 + 32 + 8 + 1 + 8;
+
+    pub fn unpack_from(data: &[u8]) -> Result<Self, ProgramError> {
+        let mut slice = data;
+        Self::deserialize(&mut slice).map_err(|_| ProgramError::InvalidAccountData)
+    }
+}
+
+// Load the insurance fund PDA, checking it is the real one
+fn load_insurance_fund(program_id: &Pubkey, fund_account: &AccountInfo) -> Result<InsuranceFund, ProgramError> {
+    if *fund_account.key != insurance_fund_address(program_id).0 || fund_account.owner != program_id {
+        msg!("Insurance fund account is not the insurance fund PDA");
+        return Err(RPSError::InvalidParameter.into());
+    }
+    InsuranceFund::unpack_from(&fund_account.data.borrow())
+}
+
+/* ─────────────────────  Configure Insurance Fund  ──────────────── */
+fn process_configure_insurance_fund(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    share_bps: u16,
+) -> ProgramResult {
+    let ai = &mut accounts.iter();
+    let authority = next_account_info(ai)?;
+    let config_account = next_account_info(ai)?;
+    let fund_account = next_account_info(ai)?;
+    let system_program = next_account_info(ai)?;
+    check_system_program(system_program)?;
+
+    require_config_authority(program_id, authority, config_account)?;
+    if share_bps > MAX_INSURANCE_SHARE_BPS {
+        msg!("The insurance share is capped at {} bps", MAX_INSURANCE_SHARE_BPS);
+        return Err(RPSError::InvalidParameter.into());
+    }
+    let (fund_key, bump) = insurance_fund_address(program_id);
+    if *fund_account.key != fund_key {
+        msg!("Insurance fund account is not the insurance fund PDA");
+        return Err(RPSError::InvalidParameter.into());
+    }
+
+    let fund = if fund_account.owner == program_id {
+        InsuranceFund {
+            share_bps,
+            ..InsuranceFund::unpack_from(&fund_account.data.borrow())?
+        }
+    } else {
+        invoke_signed(
+            &system_instruction::create_account(
+                authority.key,
+                fund_account.key,
+                Rent::get()?.minimum_balance(InsuranceFund::SPACE),
+                InsuranceFund::SPACE as u64,
+                program_id,
+            ),
+            &[authority.clone(), fund_account.clone(), system_program.clone()],
+            &[&[INSURANCE_FUND_SEED, &[bump]]],
+        )?;
+        InsuranceFund {
+            share_bps,
+            claim_count: 0,
+            contributed: 0,
+            paid_out: 0,
+        }
+    };
+    fund.serialize(&mut *fund_account.data.borrow_mut())?;
+    debug_msg!("Insurance fund takes {} bps of each sweep", share_bps);
+    Ok(())
+}
+
+/* ─────────────────────  File Insurance Claim  ──────────────────── */
+fn process_file_insurance_claim(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    requested: u64,
+    evidence: [u8; 32],
+) -> ProgramResult {
+    let ai = &mut accounts.iter();
+    let claimant = next_account_info(ai)?;
+    let fund_account = next_account_info(ai)?;
+    let claim_account = next_account_info(ai)?;
+    let system_program = next_account_info(ai)?;
+    check_system_program(system_program)?;
+
+    if !claimant.is_signer {
+        return Err(RPSError::NotAuthorized.into());
+    }
+    if requested == 0 {
+        msg!("A claim needs an amount");
+        return Err(RPSError::InvalidParameter.into());
+    }
+    let mut fund = load_insurance_fund(program_id, fund_account)?;
+
+    let index = fund.claim_count;
+    let (claim_key, bump) = insurance_claim_address(program_id, index);
+    if *claim_account.key != claim_key {
+        msg!("Claim account is not the PDA for insurance claim {}", index);
+        return Err(RPSError::InvalidParameter.into());
+    }
+    invoke_signed(
+        &system_instruction::create_account(
+            claimant.key,
+            claim_account.key,
+            Rent::get()?.minimum_balance(InsuranceClaim::SPACE),
+            InsuranceClaim::SPACE as u64,
+            program_id,
+        ),
+        &[claimant.clone(), claim_account.clone(), system_program.clone()],
+        &[&[INSURANCE_CLAIM_SEED, &index.to_le_bytes(), &[bump]]],
+    )?;
+
+    let claim = InsuranceClaim {
+        index,
+        claimant: *claimant.key,
+        requested,
+        evidence,
+        filed_at: Clock::get()?.unix_timestamp as u64,
+        resolved: false,
+        paid: 0,
+    };
+    claim.serialize(&mut *claim_account.data.borrow_mut())?;
+    fund.claim_count += 1;
+    fund.serialize(&mut *fund_account.data.borrow_mut())?;
+    debug_msg!("Insurance claim {} filed by {} for {}", index, claimant.key, requested);
+    emit_event(&RPSEvent::InsuranceClaimFiled {
+        claim: *claim_account.key,
+        index,
+        claimant: *claimant.key,
+        requested,
+    });
+    Ok(())
+}
+
+/* ─────────────────────  Resolve Insurance Claim  ───────────────── */
+fn process_resolve_insurance_claim(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    amount: u64,
+) -> ProgramResult {
+    let ai = &mut accounts.iter();
+    let authority = next_account_info(ai)?;
+    let config_account = next_account_info(ai)?;
+    let fund_account = next_account_info(ai)?;
+    let claim_account = next_account_info(ai)?;
+    let claimant = next_account_info(ai)?;
+
+    require_config_authority(program_id, authority, config_account)?;
+    let mut fund = load_insurance_fund(program_id, fund_account)?;
+    assert_owned_by(claim_account, program_id)?;
+    let mut claim = InsuranceClaim::unpack_from(&claim_account.data.borrow())?;
+    if insurance_claim_address(program_id, claim.index).0 != *claim_account.key {
+        msg!("Account is not an insurance claim");
+        return Err(RPSError::InvalidParameter.into());
+    }
+    if claim.resolved {
+        msg!("Insurance claim {} was already resolved", claim.index);
+        return Err(RPSError::InvalidGameState.into());
+    }
+    if *claimant.key != claim.claimant {
+        msg!("Claimant account does not match the claim");
+        return Err(RPSError::InvalidParameter.into());
+    }
+    if amount > claim.requested {
+        msg!("Claim {} asked for {}, not {}", claim.index, claim.requested, amount);
+        return Err(RPSError::InvalidParameter.into());
+    }
+
+    if amount > 0 {
+        pay_from_program_account(fund_account, claimant, amount)?;
+        fund.paid_out += amount;
+        fund.serialize(&mut *fund_account.data.borrow_mut())?;
+    }
+    claim.resolved = true;
+    claim.paid = amount;
+    claim.serialize(&mut *claim_account.data.borrow_mut())?;
+    debug_msg!("Insurance claim {} resolved, paying {}", claim.index, amount);
+    emit_event(&RPSEvent::InsuranceClaimResolved {
+        claim: *claim_account.key,
+        index: claim.index,
+        claimant: claim.claimant,
+        paid: amount,
+    });
+    Ok(())
+}
+
+// Implementation for initializing a new game
+#[allow(clippy::too_many_arguments)]
+fn process_initialize_game(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    min_players: u8,
+    max_players: u8,
+    total_rounds: u8,
+    entry_fee: u64,
+    timeout_seconds: u64,
+    losers_can_rejoin: bool,
+    game_mode: u8,
+    currency_mode: u8,
+    auto_round_delay: u64,
+    max_auto_rounds: u64,
+    token_mint: Option<Pubkey>,
+    allow_public_bots: bool,
+    commitment_scheme: u8,
+    theme_id: u16,
+    rule_set: u8,
+    join_timeout: u64,
+    commit_timeout: u64,
+    reveal_timeout: u64,
+    crank_reward_lamports: u64,
+    crank_bounty: u64,
+    slash_non_revealers: bool,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+
+    let initializer = next_account_info(accounts_iter)?;
+    let game_account = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+    check_system_program(system_program)?;
+    let treasury_account = next_account_info(accounts_iter)?;
+    check_treasury(program_id, treasury_account)?;
+    let vault_account = next_account_info(accounts_iter)?;
+    
+    // Get token program and mint accounts if using RPSToken; a paid game
+    // also takes the host's token account and the game vault
+    let (token_mint_account, host_token_accounts) = if currency_mode == 1 {
+        if accounts_iter.len() < 2 {
+            return Err(RPSError::InvalidParameter.into());
+        }
+        let token_program = next_account_info(accounts_iter)?;
+        check_token_program(token_program)?;
+        let token_mint_account = next_account_info(accounts_iter)?;
+        assert_owned_by(token_mint_account, &spl_token::id())?;
+        let host_token_accounts = if entry_fee > 0 {
+            let host_tokens = next_account_info(accounts_iter)?;
+            let vault = next_account_info(accounts_iter)?;
+            Some([token_program.clone(), host_tokens.clone(), vault.clone()])
+        } else {
+            None
+        };
+        (Some(token_mint_account), host_token_accounts)
+    } else {
+        (None, None)
+    };
+
+    // The game keeps the fee rate configured now for its whole life
+    let config_account = next_account_info(accounts_iter)?;
+    let fee_bps = config_fee_bps(program_id, config_account)?;
+    check_not_excluded(program_id, next_account_info(accounts_iter)?, initializer.key)?;
+
+    // No timeout given: inherit the default the stats crank maintains
+    let timeout_seconds = if timeout_seconds == 0 {
+        let config = load_config(program_id, config_account)?;
+        if config.default_timeout_secs == 0 {
+            msg!("No adaptive default timeout is configured; pass a timeout");
+            return Err(RPSError::InvalidParameter.into());
+        }
+        config.default_timeout_secs
+    } else {
+        timeout_seconds
+    };
+
+    // Ensure the initializer signed the transaction
+    if !initializer.is_signer {
+        msg!("Initializer must sign the transaction");
+        return Err(RPSError::NotAuthorized.into());
+    }
+
+    // Validate parameters - 2 (a duel) to MAX_SEATS players
+    if min_players < MIN_SEATS || max_players > MAX_SEATS || min_players > max_players {
+        msg!("Invalid player count. Must be between {} and {} players", MIN_SEATS, MAX_SEATS);
+        return Err(RPSError::InvalidParameter.into());
+    }
+
+    if total_rounds == 0 {
+        msg!("Total rounds must be greater than 0");
+        return Err(RPSError::InvalidParameter.into());
+    }
+
+    // Parse game mode
+    let game_mode = match game_mode {
+        0 => GameMode::Manual,
+        1 => GameMode::Automated,
+        _ => {
+            msg!("Invalid game mode. Must be 0 (Manual) or 1 (Automated)");
+            return Err(RPSError::InvalidParameter.into());
+        }
+    };
+
+    // Parse currency mode
+    let currency_mode = match currency_mode {
+        0 => CurrencyMode::SOL,
+        1 => CurrencyMode::RPSToken,
+        _ => {
+            msg!("Invalid currency mode. Must be 0 (SOL) or 1 (RPSToken)");
+            return Err(RPSError::InvalidParameter.into());
+        }
+    };
+
+    // Parse commitment scheme
+    let commitment_scheme = match commitment_scheme {
+        0 => CommitmentScheme::HmacSha512,
+        1 => CommitmentScheme::Sha256,
+        _ => {
+            msg!("Invalid commitment scheme. Must be 0 (HMAC-SHA512) or 1 (SHA-256)");
+            return Err(RPSError::InvalidParameter.into());
+        }
+    };
+
+    // Parse rule set
+    let rule_set = match rule_set {
+        0 => RuleSet::Classic,
+        1 => RuleSet::LizardSpock,
+        _ => {
+            msg!("Invalid rule set. Must be 0 (classic) or 1 (lizard-Spock)");
+            return Err(RPSError::InvalidParameter.into());
+        }
+    };
+
+    // Crank rewards are lamports paid out of the pot vault, which only SOL
+    // games have
+    if (crank_reward_lamports > 0 || crank_bounty > 0) && matches!(currency_mode, CurrencyMode::RPSToken) {
+        msg!("Crank rewards are paid in SOL; RPSToken games cannot offer one");
+        return Err(RPSError::InvalidParameter.into());
+    }
+    if crank_bounty > 0 && crank_reward_lamports == 0 {
+        msg!("A crank bounty needs a crank reward to pay out");
+        return Err(RPSError::InvalidParameter.into());
+    }
+    let max_crank_reward = (entry_fee as u128 * MAX_CRANK_REWARD_BPS as u128 / BPS_DENOMINATOR as u128) as u64;
+    if crank_reward_lamports > max_crank_reward {
+        msg!("Crank rewards are capped at {} bps of the entry fee", MAX_CRANK_REWARD_BPS);
+        return Err(RPSError::InvalidParameter.into());
+    }
+    
+    // If using RPSToken, the mint account passed is the game mint
+    let token_mint_pubkey = match token_mint_account {
+        Some(mint_account) if token_mint.map_or(true, |mint| mint == *mint_account.key) => Some(*mint_account.key),
+        Some(_) => {
+            msg!("Token mint account does not match the game's token mint");
+            return Err(RPSError::InvalidParameter.into());
+        }
+        None => None,
+    };
+
+    // Create game account
+    let rent = Rent::get()?;
+    let rent_lamports = rent.minimum_balance(Game::get_max_size(max_players));
+
+    invoke(
+        &system_instruction::create_account(
+            initializer.key,
+            game_account.key,
+            rent_lamports,
+            Game::get_max_size(max_players) as u64,
+            program_id,
+        ),
+        &[initializer.clone(), game_account.clone(), system_program.clone()],
+    )?;
+
+    // Initialize host as first player
+    let players = vec![Player {
+        pubkey: *initializer.key,
+        choice: Choice::None,
+        committed_choice: [0; 64], // Upgraded to SHA512 hash size
+        salt: [0; 32],
+        revealed: false,
+        score: 0,
+    }];
+
+    // Initialize game state
+    let clock = Clock::get()?;
+
+    // A range lobby keeps every seat open until it reaches min_players,
+    // where the actual count is drawn (see `settle_player_count`)
+    let player_count = max_players;
+
+    // Calculate fee for the entry
+    let fee_amount = if entry_fee > 0 {
+        calculate_fee(entry_fee, fee_bps)
+    } else {
+        0
+    };
+    
+    // Adjust game pot to account for fee
+    let initial_pot = if entry_fee > 0 {
+        entry_fee - fee_amount
+    } else {
+        0
+    };
+
+    let mut game = Game {
+        host: *initializer.key,
+        players,
+        min_players,
+        max_players,
+        state: GameState::WaitingForPlayers,
+        current_round: 1,
+        total_rounds,
+        entry_fee,
+        game_pot: initial_pot, // Host pays entry fee minus fee
+        required_timeout: timeout_seconds,
+        last_action_timestamp: clock.unix_timestamp as u64,
+        player_count,
+        losers_can_rejoin,
+        game_mode,
+        auto_round_delay,
+        max_auto_rounds,
+        current_auto_round: 0,
+        currency_mode,
+        fee_collected: match currency_mode {
+            CurrencyMode::SOL => 0, // Paid straight to the treasury below
+            CurrencyMode::RPSToken => fee_amount,
+        },
+        token_mint: token_mint_pubkey,
+        base_price_feed: None,
+        base_decimals: 0,
+        stake_vaults: Vec::new(),
+        phase_nonce: 0,
+        claimed_bitmap: 0,
+        pot_snapshot: 0,
+        tournament: None,
+        tournament_match: 0,
+        phase_deadline: 0, // Set below, once the lobby's timeout is known
+        allow_public_bots,
+        commitment_scheme,
+        team_lineup: Vec::new(),
+        phase_started_at: clock.unix_timestamp as u64,
+        participation_bitmap: 0,
+        flagged: false,
+        flag_reasons: 0,
+        seat_watch: Vec::new(),
+        bot_funders: Vec::new(),
+        pending_claims: Vec::new(),
+        escrows: Vec::new(),
+        pot_subsidy: 0,
+        layout_version: GAME_LAYOUT_VERSION,
+        player_count_drawn: min_players == max_players,
+        reveal_order: Vec::new(),
+        hidden_fee: None,
+        theme_id,
+        fee_bps,
+        rule_set,
+        winners: 0,
+        cancelled: false,
+        join_timeout,
+        commit_timeout,
+        reveal_timeout,
+        crank_reward_lamports,
+        crank_bounty,
+        slash_non_revealers,
+        slashed: Vec::new(),
+        bot_config: Vec::new(),
+        settlement: Settlement::default(),
+    };
+    let lobby_timeout = game.phase_timeout(GameState::WaitingForPlayers);
+    game.phase_deadline = (clock.unix_timestamp as u64).saturating_add(lobby_timeout);
+    if matches!(currency_mode, CurrencyMode::SOL) {
+        game.escrow_entry(*initializer.key, initial_pot, clock.unix_timestamp as u64);
+        open_pot_vault(program_id, game_account.key, initializer, vault_account, system_program)?;
+    }
+
+    // Save game state to account
+    game.serialize(&mut *game_account.data.borrow_mut())?;
+
+    // Handle payment based on currency mode
+    if entry_fee > 0 {
+        match currency_mode {
+            CurrencyMode::SOL => {
+                pay_sol_entry(game_account.key, initializer, vault_account, treasury_account, system_program, initial_pot, fee_amount)?;
+            },
+            CurrencyMode::RPSToken => {
+                // The whole entry goes to the vault; the fee stays there
+                // until CollectFees
+                let (Some(mint), Some(tail)) = (token_mint_pubkey, &host_token_accounts) else {
+                    msg!("Token program and token accounts required for RPSToken games");
+                    return Err(RPSError::InvalidParameter.into());
+                };
+                deposit_tokens(program_id, game_account.key, &mint, initializer, tail, entry_fee)?;
+            }
+        }
+    }
+    // The bounty waits beside the pot; whatever keepers don't earn goes
+    // back with the vault's rent at CloseGame
+    if crank_bounty > 0 {
+        invoke(
+            &system_instruction::transfer(initializer.key, vault_account.key, crank_bounty),
+            &[initializer.clone(), vault_account.clone(), system_program.clone()],
+        )?;
+    }
+
+    debug_msg!("Game initialized with ID: {}", game_account.key);
+    emit_event(&RPSEvent::GameCreated {
+        game: *game_account.key,
+        host: *initializer.key,
+        theme_id,
+    });
+    emit_event(&RPSEvent::PhaseStarted {
+        game: *game_account.key,
+        phase_nonce: game.phase_nonce,
+        deadline: game.phase_deadline,
+    });
+    Ok(())
+}
+
+// Implementation for joining a game
+fn process_join_game(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+
+    let player = next_account_info(accounts_iter)?;
+    let game_account = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+    check_system_program(system_program)?;
+    let ban_list_account = next_account_info(accounts_iter)?;
+    let config_account = next_account_info(accounts_iter)?;
+    let treasury_account = next_account_info(accounts_iter)?;
+    check_treasury(program_id, treasury_account)?;
+    let vault_account = next_account_info(accounts_iter)?;
+    let limits_account = next_account_info(accounts_iter)?;
+    let exclusion_account = next_account_info(accounts_iter)?;
+
+    // Load game state
+    let mut game = Game::unpack_from(&game_account.data.borrow())?;
+
+    // Paid entries may need a compliance credential (consumes its accounts)
+    check_compliance(program_id, config_account, accounts_iter, player.key, game.entry_fee)?;
+
+    // Token program, player token account and game vault (RPSToken only)
+    let token_tail = accounts_iter.as_slice();
+
+    // Ensure the player signed the transaction
+    if !player.is_signer {
+        msg!("Player must sign the transaction");
+        return Err(RPSError::NotAuthorized.into());
+    }
+
+    check_entrant(program_id, player.key, &game, ban_list_account, exclusion_account, limits_account)?;
+
+    // Calculate fee for the entry
+    let fee_amount = if game.entry_fee > 0 {
+        calculate_fee(game.entry_fee, game.fee_bps)
+    } else {
+        0
+    };
+    
+    // Adjust amount to add to game pot
+    let pot_amount = if game.entry_fee > 0 {
+        game.entry_fee - fee_amount
+    } else {
+        0
+    };
+
+    // Validate the seat and add player to the game
+    admit_player(game_account.key, &mut game, player.key, accounts)?;
+
+    // Update game pot; SOL fees go straight to the treasury, token fees are
+    // held by the game until collected
+    game.game_pot += pot_amount;
+    if matches!(game.currency_mode, CurrencyMode::RPSToken) {
+        game.fee_collected += fee_amount;
+    }
+
+    // Update last action timestamp
+    let clock = Clock::get()?;
+    game.last_action_timestamp = clock.unix_timestamp as u64;
+    if matches!(game.currency_mode, CurrencyMode::SOL) {
+        game.escrow_entry(*player.key, pot_amount, game.last_action_timestamp);
+    }
+
+    // Save game state
+    game.serialize(&mut *game_account.data.borrow_mut())?;
+
+    // Handle payment based on currency mode
+    if game.entry_fee > 0 {
+        match game.currency_mode {
+            CurrencyMode::SOL => {
+                check_pot_vault(program_id, game_account.key, &game, vault_account)?;
+                pay_sol_entry(game_account.key, player, vault_account, treasury_account, system_program, pot_amount, fee_amount)?;
+            },
+            CurrencyMode::RPSToken => {
+                let mint = game.token_mint.ok_or(RPSError::InvalidGameState)?;
+                deposit_tokens(program_id, game_account.key, &mint, player, token_tail, game.entry_fee)?;
+            }
+        }
+    }
+
+    debug_msg!("Player joined game: {}", player.key);
+    emit_event(&RPSEvent::PlayerJoined {
+        game: *game_account.key,
+        player: *player.key,
+    });
+
+    Ok(())
+}
+
+// Implementation for approving additional stake mints on a game
+fn process_configure_stake_mints(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    base_decimals: u8,
+    price_feeds: Vec<Pubkey>,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+
+    let host = next_account_info(accounts_iter)?;
+    let game_account = next_account_info(accounts_iter)?;
+    let base_price_account = next_account_info(accounts_iter)?;
+
+    // Ensure the host signed the transaction
+    if !host.is_signer {
+        msg!("Host must sign the transaction");
+        return Err(RPSError::NotAuthorized.into());
+    }
+
+    // Load game state
+    let mut game = Game::unpack_from(&game_account.data.borrow())?;
+
+    if game.host != *host.key {
+        msg!("Only the host can configure stake mints");
+        return Err(RPSError::NotAuthorized.into());
+    }
+
+    // Stakes must be priced before anyone else puts money in
+    state_machine::require(game.state, Action::Seat)?;
+    if game.players.len() > 1 {
+        msg!("Stake mints can only be configured before other players join");
+        return Err(RPSError::InvalidGameState.into());
+    }
+
+    if price_feeds.is_empty() || price_feeds.len() > MAX_STAKE_MINTS {
+        msg!("Between 1 and {} stake mints can be configured", MAX_STAKE_MINTS);
+        return Err(RPSError::InvalidParameter.into());
+    }
+
+    if matches!(game.currency_mode, CurrencyMode::SOL) && base_decimals != 9 {
+        msg!("SOL games must use 9 base decimals");
+        return Err(RPSError::InvalidParameter.into());
+    }
+
+    // Make sure the base feed currently yields a price
+    load_pyth_price(base_price_account)?;
+
+    let game_key = game_account.key;
+    let seeds = rps_pda_seeds(game_key);
+    let (vault_authority, _bump) = Pubkey::find_program_address(&seeds, program_id);
+
+    let mut stake_vaults = Vec::with_capacity(price_feeds.len());
+    for price_feed in price_feeds {
+        let mint_account = next_account_info(accounts_iter)?;
+        let vault_account = next_account_info(accounts_iter)?;
+
+        if !APPROVED_STAKE_MINTS.contains(mint_account.key)
+            || stake_vaults.iter().any(|v: &StakeVault| v.mint == *mint_account.key)
+        {
+            msg!("Mint {} is not approved for staking", mint_account.key);
+            return Err(RPSError::MintNotApproved.into());
+        }
+
+        assert_owned_by(mint_account, &spl_token::id())?;
+        let mint = spl_token::state::Mint::unpack(&mint_account.data.borrow())
+            .map_err(|_| ProgramError::InvalidAccountData)?;
+
+        // The vault must hold this mint and be controlled by the game PDA
+        let vault = unpack_token_account(vault_account)?;
+        if vault.mint != *mint_account.key || vault.owner != vault_authority {
+            msg!("Vault for mint {} must be owned by the game authority", mint_account.key);
+            return Err(RPSError::InvalidTokenAccount.into());
+        }
+
+        stake_vaults.push(StakeVault {
+            mint: *mint_account.key,
+            price_feed,
+            vault: *vault_account.key,
+            decimals: mint.decimals,
+            balance: 0,
+            fees: 0,
+        });
+    }
+
+    game.base_price_feed = Some(*base_price_account.key);
+    game.base_decimals = base_decimals;
+    game.stake_vaults = stake_vaults;
+
+    // Save game state
+    game.serialize(&mut *game_account.data.borrow_mut())?;
+
+    msg!("Configured {} stake mints", game.stake_vaults.len());
+
+    Ok(())
+}
+
+// Implementation for joining a game with a stake in an approved mint
+fn process_join_game_with_mint(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    mint_index: u8,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+
+    let player = next_account_info(accounts_iter)?;
+    let game_account = next_account_info(accounts_iter)?;
+    let token_program = next_account_info(accounts_iter)?;
+    check_token_program(token_program)?;
+    let base_price_account = next_account_info(accounts_iter)?;
+    let mint_price_account = next_account_info(accounts_iter)?;
+    let player_token_account = next_account_info(accounts_iter)?;
+    let vault_account = next_account_info(accounts_iter)?;
+    let ban_list_account = next_account_info(accounts_iter)?;
+    let exclusion_account = next_account_info(accounts_iter)?;
+    let config_account = next_account_info(accounts_iter)?;
+    let limits_account = next_account_info(accounts_iter)?;
+
+    // Ensure the player signed the transaction
+    if !player.is_signer {
+        msg!("Player must sign the transaction");
+        return Err(RPSError::NotAuthorized.into());
+    }
+
+    // Load game state
+    let mut game = Game::unpack_from(&game_account.data.borrow())?;
+    check_entrant(program_id, player.key, &game, ban_list_account, exclusion_account, limits_account)?;
+    check_compliance(program_id, config_account, accounts_iter, player.key, game.entry_fee)?;
+
+    if game.base_price_feed != Some(*base_price_account.key) {
+        msg!("Base price feed does not match the game");
+        return Err(RPSError::InvalidParameter.into());
+    }
+
+    let (stake_mint, stake_price_feed, stake_vault, stake_decimals) = {
+        let vault = game
+            .stake_vaults
+            .get(mint_index as usize)
+            .ok_or(RPSError::MintNotApproved)?;
+        (vault.mint, vault.price_feed, vault.vault, vault.decimals)
+    };
+
+    if stake_price_feed != *mint_price_account.key || stake_vault != *vault_account.key {
+        msg!("Price feed or vault does not match the configured stake mint");
+        return Err(RPSError::InvalidParameter.into());
+    }
+
+    let source = unpack_token_account(player_token_account)?;
+    if source.owner != *player.key || source.mint != stake_mint {
+        msg!("Player token account must hold the stake mint");
+        return Err(RPSError::InvalidTokenAccount.into());
+    }
+
+    // Price the entry fee in the staked mint
+    let base_price = load_pyth_price(base_price_account)?;
+    let mint_price = load_pyth_price(mint_price_account)?;
+    let stake_amount = convert_by_price(
+        game.entry_fee,
+        base_price,
+        game.base_decimals,
+        mint_price,
+        stake_decimals,
+    )?;
+
+    // Validate the seat and add player to the game
+    admit_player(game_account.key, &mut game, player.key, accounts)?;
+
+    // Split fee and pot in the staked mint
+    let fee_amount = calculate_fee(stake_amount, game.fee_bps);
+    let vault = &mut game.stake_vaults[mint_index as usize];
+    vault.balance = vault.balance.saturating_add(stake_amount - fee_amount);
+    vault.fees = vault.fees.saturating_add(fee_amount);
+
+    // Update last action timestamp
+    let clock = Clock::get()?;
+    game.last_action_timestamp = clock.unix_timestamp as u64;
+
+    // Save game state
+    game.serialize(&mut *game_account.data.borrow_mut())?;
+
+    invoke(
+        &spl_token::instruction::transfer(
+            token_program.key,
+            player_token_account.key,
+            vault_account.key,
+            player.key,
+            &[],
+            stake_amount,
+        )?,
+        &[player_token_account.clone(), vault_account.clone(), player.clone(), token_program.clone()],
+    )?;
+
+    debug_msg!("Player {} staked {} of mint {}", player.key, stake_amount, stake_mint);
+    emit_event(&RPSEvent::PlayerJoined {
+        game: *game_account.key,
+        player: *player.key,
+    });
+
+    Ok(())
+}
+
+// Helper to seat a new player: checks the lobby is open, not full and the
+// player is not already in it, then moves to commit phase once full.
+// `accounts` must hold the SlotHashes sysvar if this seat can reach
+// `min_players` of a range lobby.
+fn admit_player(game_key: &Pubkey, game: &mut Game, player: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    // Check if game is in correct state
+    state_machine::require(game.state, Action::Seat)?;
+
+    // Check if player already joined
+    if game.players.iter().any(|p| p.pubkey == *player) {
+        msg!("Player has already joined the game");
+        return Err(RPSError::PlayerAlreadyJoined.into());
+    }
+
+    // Tournament match seats are reserved for the match lineup
+    if !game.team_lineup.is_empty() && !game.team_lineup.contains(player) {
+        msg!("Seat is reserved for the match lineup");
+        return Err(RPSError::NotAuthorized.into());
+    }
+
+    // Check if game is full; until the draw, every seat up to max_players is open
+    if game.players.len() >= game.player_count as usize {
+        msg!("Game is full");
+        return Err(RPSError::GameFull.into());
+    }
+
+    game.players.push(Player {
+        pubkey: *player,
+        choice: Choice::None,
+        committed_choice: [0; 64], // Upgraded to SHA512 hash size
+        salt: [0; 32],
+        revealed: false,
+        score: 0,
+    });
+    settle_player_count(game_key, game, accounts)?;
+
+    // Update game state if required player count is reached
+    if game.players.len() >= game.player_count as usize {
+        enter_phase(game_key, game, Transition::Fill, Clock::get()?.unix_timestamp as u64)?;
+        debug_msg!("Required player count reached: {}", game.player_count);
+    }
+
+    Ok(())
+}
+
+// Implementation for joining a game with a USDC entry swapped via Jupiter
+fn process_join_game_with_swap(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    usdc_amount_in: u64,
+    max_slippage_bps: u16,
+    route_accounts_len: u8,
+    route_data: Vec<u8>,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+
+    let player = next_account_info(accounts_iter)?;
+    let game_account = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+    check_system_program(system_program)?;
+    let jupiter_program = next_account_info(accounts_iter)?;
+    let token_program = next_account_info(accounts_iter)?;
+    check_token_program(token_program)?;
+    let usdc_account = next_account_info(accounts_iter)?;
+    let destination_account = next_account_info(accounts_iter)?;
+
+    // Split the remaining accounts into the Jupiter route and the JoinGame tail
+    let remaining = accounts_iter.as_slice();
+    let route_len = route_accounts_len as usize;
+    if remaining.len() < route_len {
+        msg!("Missing Jupiter route accounts");
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+    let (route_accounts, join_tail) = remaining.split_at(route_len);
+
+    // Ensure the player signed the transaction
+    if !player.is_signer {
+        msg!("Player must sign the transaction");
+        return Err(RPSError::NotAuthorized.into());
+    }
+
+    if *jupiter_program.key != JUPITER_PROGRAM_ID {
+        msg!("Unexpected swap program");
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    if usdc_amount_in == 0 || max_slippage_bps > MAX_SWAP_SLIPPAGE_BPS {
+        msg!("Invalid swap amount or slippage (max {} bps)", MAX_SWAP_SLIPPAGE_BPS);
+        return Err(RPSError::InvalidParameter.into());
+    }
+
+    // Load game state to learn what currency the pot expects
+    let game = Game::unpack_from(&game_account.data.borrow())?;
+
+    if game.entry_fee == 0 {
+        msg!("Game has no entry fee to swap for");
+        return Err(RPSError::InvalidParameter.into());
+    }
+
+    let expected_output_mint = match game.currency_mode {
+        CurrencyMode::SOL => spl_token::native_mint::id(),
+        CurrencyMode::RPSToken => game.token_mint.ok_or(RPSError::InvalidGameState)?,
+    };
+
+    // Both token accounts must belong to the player
+    let usdc_before = unpack_token_account(usdc_account)?;
+    if usdc_before.owner != *player.key || usdc_before.mint != USDC_MINT {
+        msg!("USDC account must be a USDC account owned by the player");
+        return Err(RPSError::InvalidTokenAccount.into());
+    }
+    let destination_before = unpack_token_account(destination_account)?;
+    if destination_before.owner != *player.key || destination_before.mint != expected_output_mint {
+        msg!("Destination account must hold the game currency and be owned by the player");
+        return Err(RPSError::InvalidTokenAccount.into());
+    }
+
+    // Execute the Jupiter route exactly as quoted off-chain
+    let route_metas: Vec<AccountMeta> = route_accounts
+        .iter()
+        .map(|a| AccountMeta {
+            pubkey: *a.key,
+            is_signer: a.is_signer,
+            is_writable: a.is_writable,
+        })
+        .collect();
+    let mut route_infos: Vec<AccountInfo> = route_accounts.to_vec();
+    route_infos.push(jupiter_program.clone());
+
+    invoke(
+        &Instruction {
+            program_id: JUPITER_PROGRAM_ID,
+            accounts: route_metas,
+            data: route_data,
+        },
+        &route_infos,
+    )?;
+
+    // Enforce slippage on both sides of the swap
+    let usdc_after = unpack_token_account(usdc_account)?;
+    let destination_after = unpack_token_account(destination_account)?;
+
+    let usdc_spent = usdc_before.amount.saturating_sub(usdc_after.amount);
+    let max_usdc_spent = (usdc_amount_in as u128)
+        .saturating_mul(BPS_DENOMINATOR as u128 + max_slippage_bps as u128)
+        / BPS_DENOMINATOR as u128;
+    if usdc_spent as u128 > max_usdc_spent {
+        msg!("Swap spent {} USDC, limit was {}", usdc_spent, max_usdc_spent);
+        return Err(RPSError::SlippageExceeded.into());
+    }
+
+    let received = destination_after.amount.saturating_sub(destination_before.amount);
+    if received < game.entry_fee {
+        msg!("Swap returned {} but entry fee is {}", received, game.entry_fee);
+        return Err(RPSError::SlippageExceeded.into());
+    }
+
+    // SOL games are paid in lamports, so unwrap the swapped wSOL back to the player
+    if matches!(game.currency_mode, CurrencyMode::SOL) {
+        invoke(
+            &spl_token::instruction::close_account(
+                token_program.key,
+                destination_account.key,
+                player.key,
+                player.key,
+                &[],
+            )?,
+            &[destination_account.clone(), player.clone(), token_program.clone()],
+        )?;
+    }
+
+    debug_msg!("Swapped {} USDC into {} for entry", usdc_spent, received);
+
+    // Hand over to the regular join flow with the swapped funds
+    let mut join_accounts = vec![player.clone(), game_account.clone(), system_program.clone()];
+    join_accounts.extend_from_slice(join_tail);
+    process_join_game(program_id, &join_accounts)
+}
+
+// Implementation for committing a choice (hash of choice + salt); with
+// `recommit` it replaces the player's existing commitment (RecommitChoice)
+fn process_commit_choice(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    committed_choice: [u8; 64], // Upgraded to SHA512 hash size
+    recommit: bool,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+
+    let player = next_account_info(accounts_iter)?;
+    let game_account = next_account_info(accounts_iter)?;
+    let _config = next_account_info(accounts_iter)?; // read by the kill switch
+
+    // Ensure the player signed the transaction
+    if !player.is_signer {
+        msg!("Player must sign the transaction");
+        return Err(RPSError::NotAuthorized.into());
+    }
+
+    // Load game state
+    let mut game = Game::unpack_from(&game_account.data.borrow())?;
+
+    // Check if game is in correct state
+    state_machine::require(game.state, Action::Commit)?;
+    if game.hidden_fee.is_some() {
+        msg!("Entry fee is still sealed; commits open once it is revealed");
+        return Err(RPSError::InvalidGameState.into());
+    }
+
+    // Find player and update their committed choice
+    let seat = game.players.iter().position(|p| p.pubkey == *player.key).ok_or_else(|| {
+        msg!("Player not found in game");
+        RPSError::PlayerNotFound
+    })?;
+    let has_committed = game.players[seat].committed_choice != [0; 64];
+    if recommit {
+        if !has_committed {
+            msg!("Nothing to recommit; use CommitChoice");
+            return Err(RPSError::InvalidPlayerState.into());
+        }
+        // Once another commitment is in, changing yours could react to it
+        let others_committed = game
+            .players
+            .iter()
+            .enumerate()
+            .any(|(i, p)| i != seat && p.committed_choice != [0; 64]);
+        if others_committed {
+            msg!("Another player has committed; commitments are locked");
+            return Err(RPSError::AlreadyCommitted.into());
+        }
+    } else if has_committed {
+        msg!("Player has already committed; use RecommitChoice");
+        return Err(RPSError::AlreadyCommitted.into());
+    }
+    game.players[seat].committed_choice = committed_choice;
+
+    // Check if all players have committed and transition to reveal phase if so
+    let committed = game.players.iter().filter(|p| p.committed_choice != [0; 64]).count();
+    let all_committed = committed == game.players.len();
+
+    let now = Clock::get()?.unix_timestamp as u64;
+    if !recommit {
+        if let Some(latency) = game.phase_elapsed(now) {
+            // The SlotHashes sysvar, if passed, trails the optional stats PDA
+            let stats_account = accounts_iter.next().filter(|a| *a.key != slot_hashes::id());
+            record_action_stats(program_id, stats_account, GameState::CommitPhase, latency, all_committed)?;
+        }
+    }
+    if all_committed {
+        draw_reveal_order(game_account.key, &mut game, newest_slot_hash(accounts)?);
+        enter_phase(game_account.key, &mut game, Transition::CloseCommits, now)?;
+        debug_msg!("All players have committed their choices. Moving to reveal phase.");
+        emit_event(&RPSEvent::RevealPhaseStarted { game: *game_account.key });
+    }
+
+    // Update last action timestamp
+    game.last_action_timestamp = now;
+
+    // Save game state
+    game.serialize(&mut *game_account.data.borrow_mut())?;
+
+    if recommit {
+        debug_msg!("Player recommitted choice: {}", player.key);
+        emit_event(&RPSEvent::ChoiceRecommitted {
+            game: *game_account.key,
+            player: *player.key,
+        });
+        return Ok(());
+    }
+
+    debug_msg!("Player committed choice: {}", player.key);
+    emit_event(&RPSEvent::ChoiceCommitted {
+        game: *game_account.key,
+        player: *player.key,
+    });
+    emit_event(&RPSEvent::CommitProgress {
+        game: *game_account.key,
+        committed: committed as u8,
+        total: game.players.len() as u8,
+    });
+
+    Ok(())
+}
+
+// Implementation for revealing a choice; without a `salt` it is a signed
+// reveal (RevealChoiceSigned)
+fn process_reveal_choice(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    choice: Choice,
+    salt: Option<[u8; 32]>,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+
+    let player = next_account_info(accounts_iter)?;
+    let game_account = next_account_info(accounts_iter)?;
+    let _config = next_account_info(accounts_iter)?; // read by the kill switch
+
+    // Ensure the player signed the transaction
+    if !player.is_signer {
+        msg!("Player must sign the transaction");
+        return Err(RPSError::NotAuthorized.into());
+    }
+
+    // Load game state
+    let mut game = Game::unpack_from(&game_account.data.borrow())?;
+
+    // Check if game is in correct state
+    state_machine::require(game.state, Action::Reveal)?;
+
+    // A signed reveal takes its salt from the signature over the reveal
+    // message instead of the instruction data
+    let salt = match salt {
+        Some(salt) => salt,
+        None => {
+            let instructions_sysvar = next_account_info(accounts_iter)?;
+            let message = reveal_message(game_account.key, game.current_round, &choice);
+            let signature = verified_ed25519_signature(instructions_sysvar, player.key, &message)?;
+            signed_reveal_salt(&signature)
+        }
+    };
+
+    // Find player's index and verify the commit matches reveal
+    let mut player_index = None;
+    for (i, game_player) in game.players.iter().enumerate() {
+        if game_player.pubkey == *player.key {
+            player_index = Some(i);
+
+            // Verify that the revealed choice matches the committed choice
+            let result = compute_commitment_with(
+                game.commitment_scheme,
+                game_account.key,
+                game.current_round,
+                player.key,
+                &salt,
+                &choice,
+            )?;
+
+            // Compare with committed hash
+            if result != game_player.committed_choice {
+                msg!("Revealed choice does not match committed choice");
+                return Err(RPSError::InvalidHash.into());
+            }
+
+            // A hand outside the game's rules can be committed, since the
+            // commitment hides it, but never counts
+            if !choice.hand().is_some_and(|hand| game.rule_set.rules().allows(hand)) {
+                msg!("Choice is not part of this game's rule set");
+                return Err(RPSError::InvalidChoice.into());
+            }
+
+            break;
+        }
+    }
+
+    let player_index = player_index.ok_or_else(|| {
+        msg!("Player not found in game");
+        RPSError::PlayerNotFound
+    })?;
+
+    // Update player's choice and revealed status
+    game.players[player_index].choice = choice;
+    game.players[player_index].revealed = true;
+    debug_msg!("Player revealed choice: {}", player.key);
+    emit_event(&RPSEvent::ChoiceRevealed {
+        game: *game_account.key,
+        player: *player.key,
+    });
+
+    // Check if all players have revealed and process round if so
+    let revealed = game.players.iter().filter(|p| p.revealed).count();
+    let all_revealed = revealed == game.players.len();
+    emit_event(&RPSEvent::RevealProgress {
+        game: *game_account.key,
+        revealed: revealed as u8,
+        total: game.players.len() as u8,
+    });
+
+    let now = Clock::get()?.unix_timestamp as u64;
+    if let Some(latency) = game.phase_elapsed(now) {
+        record_action_stats(program_id, accounts_iter.next(), GameState::RevealPhase, latency, all_revealed)?;
+    }
+    if all_revealed {
+        close_round(game_account.key, &mut game, now)?;
+    }
+
+    // Update last action timestamp
+    game.last_action_timestamp = now;
+
+    // Save game state
+    game.serialize(&mut *game_account.data.borrow_mut())?;
+
+    Ok(())
+}
+
+// Scores a round every seat has revealed, then finishes the game or opens
+// the next round
+fn close_round(game_key: &Pubkey, game: &mut Game, now: u64) -> ProgramResult {
+    // Calculate round winners
+    process_round_results(game_key, game);
+
+    // Check if game should end
+    if game.current_round >= game.total_rounds {
+        enter_phase(game_key, game, Transition::Finish, now)?;
+        debug_msg!("Game finished after {} rounds", game.total_rounds);
+        emit_event(&RPSEvent::GameFinished { game: *game_key });
+    } else {
+        // Reset for next round
+        enter_phase(game_key, game, Transition::NextRound, now)?;
+        game.current_round += 1;
+        debug_msg!("Starting round {} of {}", game.current_round, game.total_rounds);
+        emit_event(&RPSEvent::RoundStarted {
+            game: *game_key,
+            round: game.current_round,
+            total_rounds: game.total_rounds,
+        });
+
+        // Reset player choices for next round
+        for player in &mut game.players {
+            player.choice = Choice::None;
+            player.committed_choice = [0; 64];
+            player.revealed = false;
+        }
+    }
+    Ok(())
+}
+
+// Implementation for resolving timeouts
+fn process_resolve_timeout(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    phase_nonce: u64,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+
+    let caller = next_account_info(accounts_iter)?;
+    let game_account = next_account_info(accounts_iter)?;
+
+    // Ensure the caller signed the transaction
+    if !caller.is_signer {
+        msg!("Caller must sign the transaction");
+        return Err(RPSError::NotAuthorized.into());
+    }
+
+    // Load game state
+    let mut game = Game::unpack_from(&game_account.data.borrow())?;
+
+    // Only the phase instance the caller observed can time out, once
+    if phase_nonce != game.phase_nonce {
+        msg!("Phase nonce {} does not match current phase {}", phase_nonce, game.phase_nonce);
+        return Err(RPSError::StalePhase.into());
+    }
+
+    // Check if timeout has occurred
+    let clock = Clock::get()?;
+    let current_time = clock.unix_timestamp as u64;
+    // The deadline was set from the current phase's own timeout when the
+    // phase began (see `Game::phase_timeout`)
+    if current_time < game.timeout_deadline() {
+        msg!("{:?} timeout of {}s has not elapsed yet", game.state, game.phase_timeout(game.state));
+        return Err(RPSError::TimeoutNotReached.into());
+    }
+    // A full mystery-stake lobby settles its fee before anything else
+    if game.hidden_fee.is_some() && !state_machine::allows(game.state, Action::Seat) {
+        msg!("Entry fee is still sealed; crank RevealEntryFee first");
+        return Err(RPSError::InvalidGameState.into());
+    }
+    // Taken before the phase changes, so a game finishing here already
+    // splits the pot without it
+    let reward = if game.crank_reward_lamports > 0 {
+        let vault_account = next_account_info(accounts_iter)?;
+        check_pot_vault(program_id, game_account.key, &game, vault_account)?;
+        Some((vault_account, take_crank_reward(&mut game)))
+    } else {
+        None
+    };
+
+    // Process timeout based on current game state
+    match game.state {
+        GameState::WaitingForPlayers => {
+            // If no players joined, just end the game
+            if game.players.len() <= 1 {
+                cancel_game(game_account.key, &mut game, current_time)?;
+                debug_msg!("Game cancelled due to timeout while waiting for players");
+            } else if game.players.len() >= game.min_players as usize {
+                // If we have at least the minimum number of players, start
+                // the game with the seats it has
+                game.player_count = game.players.len() as u8;
+                game.player_count_drawn = true;
+                enter_phase(game_account.key, &mut game, Transition::Fill, current_time)?;
+                debug_msg!("Starting game with {} players due to timeout", game.players.len());
+            } else {
+                // Not enough players, end the game
+                cancel_game(game_account.key, &mut game, current_time)?;
+                debug_msg!("Game cancelled due to timeout - not enough players joined");
+            }
         },
-        RPSInstruction::AutoPlayNextRound => {
-            process_auto_play_next_round(program_id, accounts)
+        GameState::CommitPhase => {
+            // Remove players who didn't commit and continue if enough remain
+            let committed_players: Vec<Player> = game.players
+                .iter()
+                .filter(|p| p.committed_choice != [0; 64])
+                .cloned()
+                .collect();
+
+            if committed_players.len() >= game.min_players as usize {
+                game.players = committed_players;
+                draw_reveal_order(game_account.key, &mut game, newest_slot_hash(accounts)?);
+                enter_phase(game_account.key, &mut game, Transition::CloseCommits, current_time)?;
+                debug_msg!("Moving to reveal phase with {} committed players", game.players.len());
+            } else {
+                // Not enough players committed, end game
+                cancel_game(game_account.key, &mut game, current_time)?;
+                debug_msg!("Game ended due to timeout - not enough players committed");
+            }
+        },
+        GameState::RevealPhase => {
+            // Process round with revealed choices only
+            // Players who didn't reveal score nothing; in a duel their
+            // opponent takes the round
+            let mut silent = Vec::new();
+            
+            for player in &mut game.players {
+                if !player.revealed {
+                    player.choice = Choice::None; // Default to None for non-revealed
+                    player.revealed = true;
+                    silent.push(player.pubkey);
+                }
+            }
+            
+            if !silent.is_empty() {
+                debug_msg!("Some players did not reveal their choices - using default (None)");
+            }
+            // Slashed seats keep playing, but their entries now belong to
+            // the seats that revealed (see `Game::score_leaders`)
+            if game.slash_non_revealers {
+                for player in silent {
+                    if !game.slashed.contains(&player) {
+                        game.slashed.push(player);
+                        emit_event(&RPSEvent::PlayerSlashed {
+                            game: *game_account.key,
+                            player,
+                            round: game.current_round,
+                        });
+                    }
+                }
+            }
+
+            // Calculate round winners
+            process_round_results(game_account.key, &mut game);
+
+            // Check if game should end
+            if game.current_round >= game.total_rounds {
+                enter_phase(game_account.key, &mut game, Transition::Finish, current_time)?;
+                debug_msg!("Game finished after {} rounds", game.total_rounds);
+                emit_event(&RPSEvent::GameFinished { game: *game_account.key });
+            } else {
+                // Reset for next round
+                enter_phase(game_account.key, &mut game, Transition::NextRound, current_time)?;
+                game.current_round += 1;
+                debug_msg!("Starting round {} of {}", game.current_round, game.total_rounds);
+                emit_event(&RPSEvent::RoundStarted {
+                    game: *game_account.key,
+                    round: game.current_round,
+                    total_rounds: game.total_rounds,
+                });
+
+                // Reset player choices for next round
+                for player in &mut game.players {
+                    player.choice = Choice::None;
+                    player.committed_choice = [0; 64];
+                    player.revealed = false;
+                }
+            }
         },
-        RPSInstruction::AddBotPlayers { count } => {
-            process_add_bot_players(program_id, accounts, count)
+        GameState::Finished => {
+            msg!("Game is already finished");
+            return Err(RPSError::InvalidGameState.into());
         },
-        RPSInstruction::CollectFees => {
-            process_collect_fees(program_id, accounts)
+    }
+
+    // Update last action timestamp
+    game.last_action_timestamp = current_time;
+
+    // Save game state
+    game.serialize(&mut *game_account.data.borrow_mut())?;
+
+    debug_msg!("Timeout resolved");
+    emit_event(&RPSEvent::TimeoutResolved { game: *game_account.key });
+    if let Some((vault_account, amount)) = reward.filter(|(_, amount)| *amount > 0) {
+        pay_crank_reward(game_account.key, vault_account, caller, amount)?;
+    }
+
+    Ok(())
+}
+
+// Takes the crank reward a game owes its next keeper off its books: from
+// the host's bounty while it lasts, then out of the pot. The lamports stay
+// in the pot vault until `pay_crank_reward` moves them, once the game is
+// saved.
+fn take_crank_reward(game: &mut Game) -> u64 {
+    let from_bounty = game.crank_reward_lamports.min(game.crank_bounty);
+    let from_pot = (game.crank_reward_lamports - from_bounty).min(game.game_pot);
+    game.crank_bounty -= from_bounty;
+    game.game_pot -= from_pot;
+    from_bounty + from_pot
+}
+
+// Pays a keeper the reward `take_crank_reward` set aside, from the pot vault
+fn pay_crank_reward(game_key: &Pubkey, vault: &AccountInfo, keeper: &AccountInfo, amount: u64) -> ProgramResult {
+    pay_from_program_account(vault, keeper, amount)?;
+    debug_msg!("Paid {} lamports to keeper {}", amount, keeper.key);
+    emit_event(&RPSEvent::CrankRewarded {
+        game: *game_key,
+        keeper: *keeper.key,
+        amount,
+    });
+    Ok(())
+}
+
+// Implementation for claiming winnings
+fn process_claim_winnings(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    finalize: bool,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+
+    let winner = next_account_info(accounts_iter)?;
+    let game_account = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+    check_system_program(system_program)?;
+    let config_account = next_account_info(accounts_iter)?;
+    let vault_account = next_account_info(accounts_iter)?;
+    let instructions_sysvar = next_account_info(accounts_iter)?;
+
+    // Ensure the winner signed the transaction
+    if !winner.is_signer {
+        msg!("Winner must sign the transaction");
+        return Err(RPSError::NotAuthorized.into());
+    }
+    let config = load_config_if_any(program_id, config_account)?;
+    check_transaction_guard(program_id, config.as_ref(), instructions_sysvar)?;
+
+    // Load game state
+    let mut game = Game::unpack_from(&game_account.data.borrow())?;
+
+    // Large pots wait out a delay precisely so a flag can stop them
+    let (hold_flagged, large_delay) = payout_policy(program_id, config_account, &game)?;
+    if game.flagged && (hold_flagged || large_delay.is_some()) {
+        msg!("Game is flagged for review; payouts are held");
+        return Err(RPSError::PayoutHeld.into());
+    }
+    if large_delay.is_some() || finalize {
+        let now = Clock::get()?.unix_timestamp as u64;
+        let pending = game.pending_claims.iter().find(|c| c.player == *winner.key);
+        match pending {
+            Some(claim) if finalize && now >= claim.ready_at => {}
+            Some(claim) if finalize => {
+                msg!("Claim can be finalized from {}", claim.ready_at);
+                return Err(RPSError::ClaimNotReady.into());
+            }
+            Some(_) => {
+                msg!("Finalize an initiated claim with FinalizeClaim");
+                return Err(RPSError::ClaimNotReady.into());
+            }
+            None => {
+                msg!("Pots this large are claimed with InitiateClaim, then FinalizeClaim");
+                return Err(RPSError::ClaimNotReady.into());
+            }
+        }
+    }
 
-        /* ─── Tournament foundation ──────────────────────────────── */
-        }, RPSInstruction::CreateTournament {
-            max_players,
-            entry_fee,
-            currency_mode,
-            token_mint,
-        } => {
-            process_create_tournament(
-                program_id,
-                accounts,
-                max_players,
-                entry_fee,
-                currency_mode,
-                token_mint,
-            )
-        }, RPSInstruction::JoinTournament => {
-            process_join_tournament(program_id, accounts)
+    // Stake vault accounts (token program + vault/destination pairs) trail the list
+    let remaining = accounts_iter.as_slice();
+    let vault_tail_len = if game.stake_vaults.is_empty() {
+        0
+    } else {
+        1 + 2 * game.stake_vaults.len()
+    };
+    if remaining.len() < vault_tail_len {
+        msg!("Stake vault accounts required to settle a multi-currency pot");
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+    let (token_tail, vault_tail) = remaining.split_at(remaining.len() - vault_tail_len);
+
+    // Work out the caller's share; this also checks the game is finished,
+    // the caller won and has not been paid yet. A bot seat's share is
+    // claimed by the wallet that paid for it.
+    let owed = game.winner_bitmap() & !game.claimed_bitmap;
+    let seat_key = game.seat_paid_to(winner.key, owed).unwrap_or(*winner.key);
+    let claimable = game.claimable(&seat_key).map_err(|e| {
+        msg!("Cannot claim: {}", e);
+        e
+    })?;
+    let winner_share = claimable.amount;
+    let seat = game.players.iter().position(|p| p.pubkey == seat_key);
+    let seat_bit = 1u8 << seat.ok_or(RPSError::NotWinner)?;
+
+    // The remaining pot must still be backed by the vault's lamports
+    if matches!(game.currency_mode, CurrencyMode::SOL) && game.game_pot > 0 {
+        check_pot_vault(program_id, game_account.key, &game, vault_account)?;
+        let spendable = vault_account
+            .lamports()
+            .saturating_sub(Rent::get()?.minimum_balance(0));
+        if spendable < game.game_pot {
+            msg!("Pot vault balance {} does not cover the pot {}", spendable, game.game_pot);
+            return Err(RPSError::InsufficientFunds.into());
+        }
+    }
+
+    if winner_share == 0 && claimable.vault_amounts.iter().all(|&a| a == 0) {
+        msg!("No winnings to claim");
+        return Err(RPSError::InsufficientFunds.into());
+    }
+
+    // Transfer winner's share based on currency mode
+    match game.currency_mode {
+        CurrencyMode::SOL => {
+            // A share implies a pot, so the vault was checked above
+            pay_from_program_account(vault_account, winner, winner_share)?;
         },
+        CurrencyMode::RPSToken if winner_share > 0 => {
+            let mint = game.token_mint.ok_or(RPSError::InvalidGameState)?;
+            withdraw_tokens(program_id, game_account.key, &mint, winner.key, token_tail, winner_share)?;
+        }
+        CurrencyMode::RPSToken => {}
     }
-}
 
-/* ╔══════════════════════════════════════════════════════════════════╗
-   ║                  TOURNAMENT STATE  (step-1)                      ║
-   ╚══════════════════════════════════════════════════════════════════╝ */
+    // Update game pot
+    game.game_pot -= winner_share;
 
-#[derive(BorshSerialize, BorshDeserialize, Debug)]
-pub struct TournamentState {
-    pub host: Pubkey,
-    pub max_players: u8,
-    pub entry_fee: u64,
-    pub currency_mode: CurrencyMode,
-    pub players: Vec<Pubkey>,
-    pub prize_pool: u64,
-    pub is_started: bool,
-    pub token_mint: Option<Pubkey>,
-    // future fields: bracket tree, round tracker, etc.
-}
+    // Pay the same proportion of every stake vault
+    if !vault_tail.is_empty() {
+        let vault_token_program = &vault_tail[0];
+        check_token_program(vault_token_program)?;
 
-impl TournamentState {
-    pub fn get_max_size(max_players: u8) -> usize {
-        32  // host
-        + 1 // max_players
-        + 8 // entry_fee
-        + 1 // currency_mode
-        + 4 + (max_players as usize * 32) // vec<Pubkey>
-        + 8 // prize_pool
-        + 1 // is_started
-        + 1 + 32 // option<mint>
+        let game_key = game_account.key;
+        let seeds = rps_pda_seeds(game_key);
+        let (vault_authority, bump) = Pubkey::find_program_address(&seeds, program_id);
+        let signer_seeds: &[&[u8]] = &[seeds[0], seeds[1], &[bump]];
+
+        for (i, pair) in vault_tail[1..].chunks(2).enumerate() {
+            let (vault_account, destination) = (&pair[0], &pair[1]);
+            let vault = &mut game.stake_vaults[i];
+
+            if *vault_account.key != vault.vault {
+                msg!("Stake vault {} does not match the game", i);
+                return Err(RPSError::InvalidTokenAccount.into());
+            }
+            let dest = unpack_token_account(destination)?;
+            if dest.owner != *winner.key || dest.mint != vault.mint {
+                msg!("Winner token account for stake vault {} is invalid", i);
+                return Err(RPSError::InvalidTokenAccount.into());
+            }
+
+            let vault_share = claimable.vault_amounts[i];
+            if vault_share == 0 {
+                continue;
+            }
+
+            invoke_signed(
+                &spl_token::instruction::transfer(
+                    vault_token_program.key,
+                    vault_account.key,
+                    destination.key,
+                    &vault_authority,
+                    &[],
+                    vault_share,
+                )?,
+                &[vault_account.clone(), destination.clone(), vault_token_program.clone()],
+                &[signer_seeds],
+            )?;
+
+            vault.balance -= vault_share;
+            debug_msg!("Paid {} from stake vault {}", vault_share, i);
+        }
     }
-}
 
-/* ─────────────────────  PDA seed helper  ───────────────────────── */
-#[inline(always)]
-fn tourney_pda_seeds(tournament_key: &Pubkey) -> [&[u8]; 2] {
-    [b\"rps_tournament\", tournament_key.as_ref()]
+    // Mark player as paid
+    game.claimed_bitmap |= seat_bit;
+    game.pending_claims.retain(|c| c.player != *winner.key);
+
+    // Save game state
+    game.serialize(&mut *game_account.data.borrow_mut())?;
+
+    debug_msg!("Winnings claimed by: {}", winner.key);
+    emit_event(&RPSEvent::WinningsClaimed {
+        game: *game_account.key,
+        winner: *winner.key,
+        amount: winner_share,
+    });
+
+    Ok(())
 }
 
-/* ─────────────────────  Create Tournament  ─────────────────────── */
-fn process_create_tournament(
+// Implementation for paying every winner of a game at once
+fn process_distribute_winnings(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
-    max_players: u8,
-    entry_fee: u64,
-    currency_mode: u8,
-    token_mint: Option<Pubkey>,
 ) -> ProgramResult {
-    let ai = &mut accounts.iter();
-    let host = next_account_info(ai)?;
-    let tourney_account = next_account_info(ai)?;
-    let system_program = next_account_info(ai)?;
+    let accounts_iter = &mut accounts.iter();
 
-    if !host.is_signer {
+    let caller = next_account_info(accounts_iter)?;
+    let game_account = next_account_info(accounts_iter)?;
+    let config_account = next_account_info(accounts_iter)?;
+    let vault_account = next_account_info(accounts_iter)?;
+    let instructions_sysvar = next_account_info(accounts_iter)?;
+
+    if !caller.is_signer {
         return Err(RPSError::NotAuthorized.into());
     }
-    if max_players < 2 || max_players > 32 {
-        return Err(RPSError::InvalidParameter.into());
+    assert_owned_by(game_account, program_id)?;
+    let config = load_config_if_any(program_id, config_account)?;
+    check_transaction_guard(program_id, config.as_ref(), instructions_sysvar)?;
+
+    let mut game = Game::unpack_from(&game_account.data.borrow())?;
+    state_machine::require(game.state, Action::Settle)?;
+    if !matches!(game.currency_mode, CurrencyMode::SOL) || !game.stake_vaults.is_empty() {
+        msg!("Only SOL games without stake vaults can be distributed; claim with ClaimWinnings");
+        return Err(RPSError::InvalidGameState.into());
+    }
+    if game.cancelled {
+        msg!("A cancelled game has no winners; refund its seats with RefundEntry");
+        return Err(RPSError::InvalidGameState.into());
     }
 
-    /* account creation */
-    let rent = Rent::get()?;
-    let space = TournamentState::get_max_size(max_players);
-    let lamports = rent.minimum_balance(space);
-    invoke(
-        &system_instruction::create_account(
-            host.key,
-            tourney_account.key,
-            lamports,
-            space as u64,
-            program_id,
-        ),
-        &[host.clone(), tourney_account.clone(), system_program.clone()],
-    )?;
+    // The same holds ClaimWinnings honours; a large pot keeps its delay
+    let (hold_flagged, large_delay) = payout_policy(program_id, config_account, &game)?;
+    if game.flagged && (hold_flagged || large_delay.is_some()) {
+        msg!("Game is flagged for review; payouts are held");
+        return Err(RPSError::PayoutHeld.into());
+    }
+    if large_delay.is_some() {
+        msg!("Pots this large are claimed with InitiateClaim, then FinalizeClaim");
+        return Err(RPSError::ClaimNotReady.into());
+    }
+    if game.all_claimed() {
+        msg!("Every winner has already been paid");
+        return Err(RPSError::AlreadyClaimed.into());
+    }
 
-    /* init state */
-    let state = TournamentState {
-        host: *host.key,
-        max_players,
-        entry_fee,
-        currency_mode: match currency_mode {
-            0 => CurrencyMode::SOL,
-            1 => CurrencyMode::RPSToken,
-            _ => return Err(RPSError::InvalidParameter.into()),
-        },
-        players: vec![],
-        prize_pool: 0,
-        is_started: false,
-        token_mint,
-    };
-    state.serialize(&mut *tourney_account.data.borrow_mut())?;
-    msg!(\"Tournament created: {}\", tourney_account.key);
+    if game.game_pot > 0 {
+        check_pot_vault(program_id, game_account.key, &game, vault_account)?;
+        let spendable = vault_account
+            .lamports()
+            .saturating_sub(Rent::get()?.minimum_balance(0));
+        if spendable < game.game_pot {
+            msg!("Pot vault balance {} does not cover the pot {}", spendable, game.game_pot);
+            return Err(RPSError::InsufficientFunds.into());
+        }
+    }
+
+    let unpaid = game.winner_bitmap() & !game.claimed_bitmap;
+    for seat in 0..game.players.len() {
+        if unpaid & (1 << seat) == 0 {
+            continue;
+        }
+        let winner = next_account_info(accounts_iter)?;
+        if *winner.key != game.payee(seat) {
+            msg!("Winner accounts must follow the seat order");
+            return Err(RPSError::InvalidParameter.into());
+        }
+
+        // Each winner is paid the share settled at finish, exactly as
+        // ClaimWinnings would pay it
+        let share = game.claimable(&game.players[seat].pubkey)?.amount;
+        if share > 0 {
+            pay_from_program_account(vault_account, winner, share)?;
+        }
+        game.game_pot -= share;
+        game.claimed_bitmap |= 1 << seat;
+        game.pending_claims.retain(|c| c.player != *winner.key);
+
+        debug_msg!("Winnings paid to: {}", winner.key);
+        emit_event(&RPSEvent::WinningsClaimed {
+            game: *game_account.key,
+            winner: *winner.key,
+            amount: share,
+        });
+    }
+
+    game.serialize(&mut *game_account.data.borrow_mut())?;
+
+    debug_msg!("Winnings of {} distributed to {} winners", game_account.key, unpaid.count_ones());
     Ok(())
 }
 
-/* ─────────────────────  Join Tournament  ───────────────────────── */
-fn process_join_tournament(
+// Implementation for starting a two-step claim of a large pot
+fn process_initiate_claim(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
 ) -> ProgramResult {
     let ai = &mut accounts.iter();
-    let player = next_account_info(ai)?;
-    let tourney_account = next_account_info(ai)?;
-    let system_program = next_account_info(ai)?;
+    let winner = next_account_info(ai)?;
+    let game_account = next_account_info(ai)?;
+    let config_account = next_account_info(ai)?;
 
-    if !player.is_signer {
+    if !winner.is_signer {
+        msg!("Winner must sign the transaction");
         return Err(RPSError::NotAuthorized.into());
     }
+    assert_owned_by(game_account, program_id)?;
+    let mut game = Game::unpack_from(&game_account.data.borrow())?;
+
+    // Only a winner who is still owed something may start the clock
+    let owed = game.winner_bitmap() & !game.claimed_bitmap;
+    let seat_key = game.seat_paid_to(winner.key, owed).unwrap_or(*winner.key);
+    game.claimable(&seat_key).map_err(|e| {
+        msg!("Cannot claim: {}", e);
+        e
+    })?;
+    let Some(delay) = payout_policy(program_id, config_account, &game)?.1 else {
+        msg!("Pot is small enough to claim directly");
+        return Err(RPSError::InvalidGameState.into());
+    };
+    if game.pending_claims.iter().any(|c| c.player == *winner.key) {
+        msg!("Claim already initiated");
+        return Err(RPSError::InvalidGameState.into());
+    }
 
-    let mut tourney = TournamentState::try_from_slice(&tourney_account.data.borrow())?;
-    if tourney.is_started {
-        msg!(\"Tournament already started\");
+    let ready_at = (Clock::get()?.unix_timestamp as u64).saturating_add(delay);
+    game.pending_claims.push(PendingClaim {
+        player: *winner.key,
+        ready_at,
+    });
+    game.serialize(&mut *game_account.data.borrow_mut())?;
+
+    debug_msg!("Claim by {} can be finalized from {}", winner.key, ready_at);
+    emit_event(&RPSEvent::ClaimInitiated {
+        game: *game_account.key,
+        winner: *winner.key,
+        ready_at,
+    });
+    Ok(())
+}
+
+// Implementation for the claimable-winnings view
+fn process_get_claimable(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    player: Pubkey,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let game_account = next_account_info(accounts_iter)?;
+
+    if game_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    let game = Game::unpack_from(&game_account.data.borrow())?;
+
+    let claimable = game.claimable(&player).unwrap_or_else(|_| Claimable {
+        amount: 0,
+        vault_amounts: vec![0; game.stake_vaults.len()],
+    });
+    set_return_data(&claimable.try_to_vec()?);
+    Ok(())
+}
+
+// Implementation for settling a game from an older layout
+fn process_finalize_legacy_game(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let caller = next_account_info(accounts_iter)?;
+    let game_account = next_account_info(accounts_iter)?;
+    let host = next_account_info(accounts_iter)?;
+
+    if !caller.is_signer {
+        return Err(RPSError::NotAuthorized.into());
+    }
+    assert_owned_by(game_account, program_id)?;
+    let mut game = Game::unpack_from(&game_account.data.borrow())?;
+    if !game.is_legacy() {
+        msg!("Game already uses layout {}", game.layout_version);
         return Err(RPSError::InvalidGameState.into());
     }
-    if tourney.players.len() as u8 >= tourney.max_players {
-        return Err(RPSError::GameFull.into());
+    if !matches!(game.currency_mode, CurrencyMode::SOL) || !game.stake_vaults.is_empty() {
+        msg!("Only SOL games can be settled without their token accounts");
+        return Err(RPSError::InvalidGameState.into());
     }
-    if tourney.players.contains(player.key) {
-        return Err(RPSError::PlayerAlreadyJoined.into());
+    if *host.key != game.host {
+        return Err(RPSError::InvalidParameter.into());
     }
 
-    /* fee payment (SOL only for step-1) */
-    if tourney.entry_fee > 0 && matches!(tourney.currency_mode, CurrencyMode::SOL) {
-        invoke(
-            &system_instruction::transfer(
-                player.key,
-                tourney_account.key,
-                tourney.entry_fee,
-            ),
-            &[player.clone(), tourney_account.clone(), system_program.clone()],
-        )?;
-        tourney.prize_pool = tourney.prize_pool.saturating_add(tourney.entry_fee);
+    let finished = state_machine::allows(game.state, Action::Settle);
+    let seats = game.players.len() as u64;
+    for i in 0..game.players.len() {
+        let wallet = next_account_info(accounts_iter)?;
+        if *wallet.key != game.players[i].pubkey {
+            msg!("Payout accounts must follow the seat order");
+            return Err(RPSError::InvalidParameter.into());
+        }
+        let amount = if finished {
+            // Unclaimed winnings, exactly as ClaimWinnings would pay them
+            match game.claimable(wallet.key) {
+                Ok(claimable) => {
+                    game.claimed_bitmap |= 1 << i;
+                    claimable.amount
+                }
+                Err(_) => 0,
+            }
+        } else {
+            // A game in progress may be mid-way through rules that have
+            // changed, so it is not resumed: every seat gets its share back
+            let dust = if i + 1 == game.players.len() { game.game_pot % seats } else { 0 };
+            game.game_pot / seats + dust
+        };
+        if amount > 0 {
+            pay_from_program_account(game_account, wallet, amount)?;
+        }
+        if finished {
+            game.game_pot = game.game_pot.saturating_sub(amount);
+        }
     }
 
-    tourney.players.push(*player.key);
-    tourney.serialize(&mut *tourney_account.data.borrow_mut())?;
-    msg!(\"{} joined tournament\", player.key);
+    // Close the account; its rent goes back to the host who paid it
+    let remaining = game_account.lamports();
+    emit_event(&RPSEvent::GameClosed {
+        game: *game_account.key,
+        recipient: *host.key,
+        lamports: remaining,
+    });
+    **game_account.try_borrow_mut_lamports()? = 0;
+    **host.try_borrow_mut_lamports()? = host
+        .lamports()
+        .checked_add(remaining)
+        .ok_or(RPSError::FeeCalculationError)?;
+    game_account.data.borrow_mut().fill(0);
+    debug_msg!("Legacy game {} settled and closed", game_account.key);
     Ok(())
 }
 
-
-// Implementation for initializing a new game
-fn process_initialize_game(
+// Implementation for closing a settled game
+fn process_close_game(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
-    min_players: u8,
-    max_players: u8,
-    total_rounds: u8,
-    entry_fee: u64,
-    timeout_seconds: u64,
-    losers_can_rejoin: bool,
-    game_mode: u8,
-    currency_mode: u8,
-    auto_round_delay: u64,
-    max_auto_rounds: u64,
-    token_mint: Option<Pubkey>,
 ) -> ProgramResult {
     let accounts_iter = &mut accounts.iter();
-
-    let initializer = next_account_info(accounts_iter)?;
+    let caller = next_account_info(accounts_iter)?;
     let game_account = next_account_info(accounts_iter)?;
-    let system_program = next_account_info(accounts_iter)?;
-    
-    // Get fee collector account if provided
-    let fee_collector_account = if accounts_iter.len() > 0 {
-        Some(next_account_info(accounts_iter)?)
-    } else {
-        None
-    };
-    
-    // Get token program and mint accounts if using RPSToken
-    let (token_program, token_mint_account) = if currency_mode == 1 {
-        if accounts_iter.len() < 2 {
-            return Err(RPSError::InvalidParameter.into());
-        }
-        let token_program = next_account_info(accounts_iter)?;
-        let token_mint_account = next_account_info(accounts_iter)?;
-        (Some(token_program), Some(token_mint_account))
-    } else {
-        (None, None)
-    };
+    let vault_account = next_account_info(accounts_iter)?;
+    let recipient = next_account_info(accounts_iter)?;
+    let result_account = next_account_info(accounts_iter)?;
 
-    // Ensure the initializer signed the transaction
-    if !initializer.is_signer {
-        msg!("Initializer must sign the transaction");
+    if !caller.is_signer {
+        return Err(RPSError::NotAuthorized.into());
+    }
+    assert_owned_by(game_account, program_id)?;
+    let game = Game::unpack_from(&game_account.data.borrow())?;
+    if game.is_legacy() {
+        msg!("Game predates the pot vault; settle it with FinalizeLegacyGame");
+        return Err(RPSError::InvalidGameState.into());
+    }
+    if *recipient.key != game.host && *caller.key != game.host {
+        msg!("Only the host can send the rent anywhere but to the host");
         return Err(RPSError::NotAuthorized.into());
     }
-
-    // Validate parameters - ensure only 3 or 4 players
-    if min_players < 3 || max_players > 4 || min_players > max_players {
-        msg!("Invalid player count. Must be between 3 and 4 players");
+    if *vault_account.key != pot_vault_address(program_id, game_account.key).0 {
+        msg!("Pot vault does not match the game");
         return Err(RPSError::InvalidParameter.into());
     }
 
-    if total_rounds == 0 {
-        msg!("Total rounds must be greater than 0");
+    // Nothing may be left that a winner, a pending claim or the fee sweep
+    // still needs the game for
+    state_machine::require(game.state, Action::Settle)?;
+    let unpaid_vault = game.stake_vaults.iter().any(|v| v.balance != 0 || v.fees != 0);
+    if game.game_pot != 0 || !game.pending_claims.is_empty() || game.fee_collected != 0 || unpaid_vault {
+        msg!("Game still holds {} unclaimed, {} in fees", game.game_pot, game.fee_collected);
+        return Err(RPSError::InvalidGameState.into());
+    }
+    // The result snapshot is the game's history once the account is gone
+    if *result_account.key != game_result_address(program_id, game_account.key).0 {
+        msg!("Result account is not the game's result PDA");
         return Err(RPSError::InvalidParameter.into());
     }
-
-    // Parse game mode
-    let game_mode = match game_mode {
-        0 => GameMode::Manual,
-        1 => GameMode::Automated,
-        _ => {
-            msg!("Invalid game mode. Must be 0 (Manual) or 1 (Automated)");
-            return Err(RPSError::InvalidParameter.into());
-        }
-    };
-
-    // Parse currency mode
-    let currency_mode = match currency_mode {
-        0 => CurrencyMode::SOL,
-        1 => CurrencyMode::RPSToken,
-        _ => {
-            msg!("Invalid currency mode. Must be 0 (SOL) or 1 (RPSToken)");
+    if result_account.owner != program_id {
+        msg!("Record the game's result before closing it");
+        return Err(RPSError::InvalidGameState.into());
+    }
+    if let Some(tournament) = game.tournament {
+        let tourney_account = next_account_info(accounts_iter)?;
+        if *tourney_account.key != tournament {
             return Err(RPSError::InvalidParameter.into());
         }
-    };
-    
-    // If using RPSToken, validate token mint
-    let token_mint_pubkey = if matches!(currency_mode, CurrencyMode::RPSToken) {
-        if token_mint.is_none() && token_mint_account.is_none() {
-            msg!("Token mint must be provided for RPSToken games");
-            return Err(RPSError::InvalidParameter.into());
+        // A closed tournament no longer needs the game
+        if tourney_account.owner == program_id {
+            let tourney = TournamentState::unpack_from(&tourney_account.data.borrow())?;
+            let awaiting_report = tourney
+                .bracket
+                .get(game.tournament_match as usize)
+                .is_some_and(|m| m.game == Some(*game_account.key) && m.winner.is_none());
+            if awaiting_report {
+                msg!("Report match {} before closing its game", game.tournament_match);
+                return Err(RPSError::InvalidGameState.into());
+            }
         }
-        
-        // Use provided token mint or extract from account
-        token_mint.or_else(|| Some(*token_mint_account.unwrap().key))
-    } else {
-        None
-    };
-
-    // Create game account
-    let rent = Rent::get()?;
-    let rent_lamports = rent.minimum_balance(Game::get_max_size(max_players));
-
-    invoke(
-        &system_instruction::create_account(
-            initializer.key,
-            game_account.key,
-            rent_lamports,
-            Game::get_max_size(max_players) as u64,
-            program_id,
-        ),
-        &[initializer.clone(), game_account.clone(), system_program.clone()],
-    )?;
+    }
 
-    // Initialize host as first player
-    let mut players = Vec::new();
-    players.push(Player {
-        pubkey: *initializer.key,
-        choice: Choice::None,
-        committed_choice: [0; 64], // Upgraded to SHA512 hash size
-        salt: [0; 32],
-        revealed: false,
-        score: 0,
+    // SOL games hold their pot in the vault; token games never opened one
+    let mut lamports = game_account.lamports();
+    if vault_account.owner == program_id {
+        lamports += vault_account.lamports();
+        close_program_account(vault_account, recipient)?;
+    }
+    close_program_account(game_account, recipient)?;
+    emit_event(&RPSEvent::GameClosed {
+        game: *game_account.key,
+        recipient: *recipient.key,
+        lamports,
     });
+    debug_msg!("Game {} closed, {} lamports to {}", game_account.key, lamports, recipient.key);
+    Ok(())
+}
 
-    // Initialize game state
-    let clock = Clock::get()?;
+// Implementation for the fee quote view
+fn process_get_fee_quote(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    entry_fee: u64,
+    currency_mode: u8,
+) -> ProgramResult {
+    let ai = &mut accounts.iter();
+    let config_account = next_account_info(ai)?;
 
-    // Randomly choose the actual player count (either 3 or 4)
-    let player_count = if min_players == max_players {
-        min_players
-    } else {
-        // Use the last bit of the timestamp as randomness
-        // This is not cryptographically secure but sufficient for this purpose
-        if (clock.unix_timestamp & 1) == 0 { 3 } else { 4 }
-    };
+    if currency_mode > 1 {
+        return Err(RPSError::InvalidParameter.into());
+    }
+    let fee_bps = config_fee_bps(program_id, config_account)?;
+    set_return_data(&FeeQuote::new(entry_fee, fee_bps).try_to_vec()?);
+    Ok(())
+}
 
-    // Calculate fee for the entry
-    let fee_amount = if entry_fee > 0 {
-        calculate_fee(entry_fee)
-    } else {
-        0
-    };
-    
-    // Adjust game pot to account for fee
-    let initial_pot = if entry_fee > 0 {
-        entry_fee - fee_amount
-    } else {
-        0
-    };
+// Implementation for the spectator view
+fn process_get_spectator_view(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let game_account = next_account_info(accounts_iter)?;
 
-    let game = Game {
-        host: *initializer.key,
-        players,
-        min_players,
-        max_players,
-        state: GameState::WaitingForPlayers,
-        current_round: 1,
-        total_rounds,
-        entry_fee,
-        game_pot: initial_pot, // Host pays entry fee minus fee
-        required_timeout: timeout_seconds,
-        last_action_timestamp: clock.unix_timestamp as u64,
-        player_count,
-        losers_can_rejoin,
-        game_mode,
-        auto_round_delay,
-        max_auto_rounds,
-        current_auto_round: 0,
-        currency_mode,
-        fee_collected: fee_amount, // Track fee collected
-        token_mint: token_mint_pubkey,
-    };
+    if game_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    let game = Game::unpack_from(&game_account.data.borrow())?;
+    set_return_data(&SpectatorView::new(&game).try_to_vec()?);
+    Ok(())
+}
 
-    // Save game state to account
-    game.serialize(&mut *game_account.data.borrow_mut())?;
+// Implementation for the accounting audit view
+fn process_audit_game(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let game_account = next_account_info(accounts_iter)?;
+    let vault_account = next_account_info(accounts_iter)?;
 
-    // Handle payment based on currency mode
-    if entry_fee > 0 {
-        match currency_mode {
-            CurrencyMode::SOL => {
-                // Transfer entry fee from initializer to game account
-                invoke(
-                    &system_instruction::transfer(
-                        initializer.key,
-                        game_account.key,
-                        entry_fee,
-                    ),
-                    &[initializer.clone(), game_account.clone(), system_program.clone()],
-                )?;
-                
-                // If fee collector is provided, transfer fee
-                if let Some(fee_account) = fee_collector_account {
-                    if fee_amount > 0 {
-                        // Transfer fee from game account to fee collector
-                        let game_key = game_account.key;
-                        let seeds = rps_pda_seeds(game_key);
-                        let (_pda, bump) = Pubkey::find_program_address(&seeds, program_id);
-                        let signer_seeds: &[&[u8]] = &[seeds[0], seeds[1], &[bump]];
-                        
-                        invoke_signed(
-                            &system_instruction::transfer(
-                                game_account.key,
-                                fee_account.key,
-                                fee_amount,
-                            ),
-                            &[game_account.clone(), fee_account.clone(), system_program.clone()],
-                            &[signer_seeds],
-                        )?;
-                    }
-                }
-            },
-            CurrencyMode::RPSToken => {
-                // For token transfers, we would need additional accounts and logic
-                // This is a simplified version - in a real implementation, you would:
-                // 1. Get the initializer's token account
-                // 2. Get the game's token account (or create one)
-                // 3. Transfer tokens from initializer to game account
-                
-                if token_program.is_none() || token_mint_account.is_none() {
-                    msg!("Token program and mint accounts required for RPSToken games");
-                    return Err(RPSError::InvalidParameter.into());
-                }
-                
-                // Token transfer would be implemented here
-                // This is a placeholder for the actual token transfer logic
-                msg!("Token transfer for game creation would happen here");
-                
-                // Note: In a complete implementation, you would create a token account for the game
-                // and transfer tokens from the initializer to this account
-            }
-        }
+    if game_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
     }
+    let game = Game::unpack_from(&game_account.data.borrow())?;
 
-    msg!("Game initialized with ID: {}", game_account.key);
+    // SOL pots sit in the pot vault; games from before it still hold theirs
+    let (holder, rent) = if matches!(game.currency_mode, CurrencyMode::SOL) && !game.is_legacy() {
+        check_pot_vault(program_id, game_account.key, &game, vault_account)?;
+        (vault_account, Rent::get()?.minimum_balance(0))
+    } else {
+        (game_account, Rent::get()?.minimum_balance(game_account.data_len()))
+    };
+    let audit = GameAudit::new(&game, rent, holder.lamports());
+    if audit.discrepancy != 0 {
+        msg!(
+            "{} holds {} lamports, state accounts for {}",
+            holder.key,
+            audit.actual_lamports,
+            audit.expected_lamports
+        );
+    }
+    set_return_data(&audit.try_to_vec()?);
     Ok(())
 }
 
-// Implementation for joining a game
-fn process_join_game(
+// Implementation for rejoining a game as a loser
+fn process_rejoin_game(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
 ) -> ProgramResult {
@@ -718,23 +10289,13 @@ fn process_join_game(
     let player = next_account_info(accounts_iter)?;
     let game_account = next_account_info(accounts_iter)?;
     let system_program = next_account_info(accounts_iter)?;
-    
-    // Get fee collector account if provided
-    let fee_collector_account = if accounts_iter.len() > 0 {
-        Some(next_account_info(accounts_iter)?)
-    } else {
-        None
-    };
-    
-    // Get token accounts if needed
-    let (token_program, player_token_account, game_token_account) = if accounts_iter.len() >= 3 {
-        let token_program = next_account_info(accounts_iter)?;
-        let player_token_account = next_account_info(accounts_iter)?;
-        let game_token_account = next_account_info(accounts_iter)?;
-        (Some(token_program), Some(player_token_account), Some(game_token_account))
-    } else {
-        (None, None, None)
-    };
+    check_system_program(system_program)?;
+    let treasury_account = next_account_info(accounts_iter)?;
+    check_treasury(program_id, treasury_account)?;
+    let vault_account = next_account_info(accounts_iter)?;
+
+    // Token program, player token account and game vault (RPSToken only)
+    let token_tail = accounts_iter.as_slice();
 
     // Ensure the player signed the transaction
     if !player.is_signer {
@@ -743,32 +10304,38 @@ fn process_join_game(
     }
 
     // Load game state
-    let mut game = Game::try_from_slice(&game_account.data.borrow())
-        .map_err(|_| ProgramError::InvalidAccountData)?;
+    let mut game = Game::unpack_from(&game_account.data.borrow())?;
 
-    // Check if game is in correct state
-    if !matches!(game.state, GameState::WaitingForPlayers) {
-        msg!("Game is not in waiting for players state");
+    // Check if game is in correct state and losers can rejoin
+    state_machine::require(game.state, Action::Settle)?;
+    if !game.losers_can_rejoin {
+        msg!("Losers cannot rejoin this game");
         return Err(RPSError::InvalidGameState.into());
     }
 
-    // Check if player already joined
-    for existing_player in &game.players {
-        if existing_player.pubkey == *player.key {
-            msg!("Player has already joined the game");
-            return Err(RPSError::PlayerAlreadyJoined.into());
+    // Check if player was a loser in the previous game
+    let mut was_player = false;
+    let mut was_loser = false;
+    let max_score = game.players.iter().map(|p| p.score).max().unwrap_or(0);
+
+    for player_data in &game.players {
+        if player_data.pubkey == *player.key {
+            was_player = true;
+            if player_data.score < max_score {
+                was_loser = true;
+            }
+            break;
         }
     }
 
-    // Check if game is full based on the randomized player_count
-    if game.players.len() >= game.player_count as usize {
-        msg!("Game is full");
-        return Err(RPSError::GameFull.into());
+    if !was_player || !was_loser {
+        msg!("Player was not in the game or was not a loser");
+        return Err(RPSError::InvalidPlayerState.into());
     }
 
     // Calculate fee for the entry
     let fee_amount = if game.entry_fee > 0 {
-        calculate_fee(game.entry_fee)
+        calculate_fee(game.entry_fee, game.fee_bps)
     } else {
         0
     };
@@ -780,24 +10347,33 @@ fn process_join_game(
         0
     };
 
-    // Add player to the game
-    game.players.push(Player {
-        pubkey: *player.key,
-        choice: Choice::None,
-        committed_choice: [0; 64], // Upgraded to SHA512 hash size
-        salt: [0; 32],
-        revealed: false,
-        score: 0,
-    });
+    // Handle payment based on currency mode
+    if game.entry_fee > 0 {
+        match game.currency_mode {
+            CurrencyMode::SOL => {
+                check_pot_vault(program_id, game_account.key, &game, vault_account)?;
+                pay_sol_entry(game_account.key, player, vault_account, treasury_account, system_program, pot_amount, fee_amount)?;
+                game.game_pot += pot_amount;
+            },
+            CurrencyMode::RPSToken => {
+                let mint = game.token_mint.ok_or(RPSError::InvalidGameState)?;
+                deposit_tokens(program_id, game_account.key, &mint, player, token_tail, game.entry_fee)?;
 
-    // Update game pot and fee collected
-    game.game_pot += pot_amount;
-    game.fee_collected += fee_amount;
+                // Update game pot and fee collected
+                game.game_pot += pot_amount;
+                game.fee_collected += fee_amount;
+            }
+        }
+    }
 
-    // Update game state if required player count is reached
-    if game.players.len() >= game.player_count as usize {
-        game.state = GameState::CommitPhase;
-        msg!("Required player count reached: {}", game.player_count);
+    // Reset this player's stats for the next game
+    for player_data in &mut game.players {
+        if player_data.pubkey == *player.key {
+            player_data.choice = Choice::None;
+            player_data.committed_choice = [0; 64];
+            player_data.revealed = false;
+            break;
+        }
     }
 
     // Update last action timestamp
@@ -807,1032 +10383,1574 @@ fn process_join_game(
     // Save game state
     game.serialize(&mut *game_account.data.borrow_mut())?;
 
-    // Handle payment based on currency mode
-    if game.entry_fee > 0 {
-        match game.currency_mode {
-            CurrencyMode::SOL => {
-                // Transfer entry fee from player to game account
-                invoke(
-                    &system_instruction::transfer(
-                        player.key,
-                        game_account.key,
-                        game.entry_fee,
-                    ),
-                    &[player.clone(), game_account.clone(), system_program.clone()],
-                )?;
-                
-                // If fee collector is provided, transfer fee
-                if let Some(fee_account) = fee_collector_account {
-                    if fee_amount > 0 {
-                        // Transfer fee from game account to fee collector
-                        let game_key = game_account.key;
-                        let seeds = rps_pda_seeds(game_key);
-                        let (_pda, bump) = Pubkey::find_program_address(&seeds, program_id);
-                        let signer_seeds: &[&[u8]] = &[seeds[0], seeds[1], &[bump]];
-                        
-                        invoke_signed(
-                            &system_instruction::transfer(
-                                game_account.key,
-                                fee_account.key,
-                                fee_amount,
-                            ),
-                            &[game_account.clone(), fee_account.clone(), system_program.clone()],
-                            &[signer_seeds],
-                        )?;
-                    }
-                }
-            },
-            CurrencyMode::RPSToken => {
-                // For token transfers, we would need additional accounts and logic
-                // This is a simplified version - in a real implementation, you would:
-                // 1. Verify the player's token account
-                // 2. Verify the game's token account
-                // 3. Transfer tokens from player to game account
-                
-                if token_program.is_none() || player_token_account.is_none() || game_token_account.is_none() {
-                    msg!("Token program and token accounts required for RPSToken games");
-                    return Err(RPSError::InvalidParameter.into());
-                }
-                
-                // Token transfer would be implemented here
-                // This is a placeholder for the actual token transfer logic
-                msg!("Token transfer for joining game would happen here");
-                
-                // Note: In a complete implementation, you would transfer tokens from the player's token account
-                // to the game's token account
-            }
-        }
-    }
-
-    msg!("Player joined game: {}", player.key);
+    msg!("Player rejoined game: {}", player.key);
+    emit_event(&RPSEvent::PlayerJoined {
+        game: *game_account.key,
+        player: *player.key,
+    });
 
     Ok(())
 }
 
-// Implementation for committing a choice (hash of choice + salt)
-fn process_commit_choice(
+// Implementation for starting a new game round with the same players
+fn process_start_new_game_round(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
-    committed_choice: [u8; 64], // Upgraded to SHA512 hash size
-    salt: [u8; 32],
 ) -> ProgramResult {
     let accounts_iter = &mut accounts.iter();
 
-    let player = next_account_info(accounts_iter)?;
+    let initiator = next_account_info(accounts_iter)?;
     let game_account = next_account_info(accounts_iter)?;
 
-    // Ensure the player signed the transaction
-    if !player.is_signer {
-        msg!("Player must sign the transaction");
+    // Ensure the initiator signed the transaction
+    if !initiator.is_signer {
+        msg!("Initiator must sign the transaction");
         return Err(RPSError::NotAuthorized.into());
     }
+    assert_owned_by(game_account, program_id)?;
 
     // Load game state
-    let mut game = Game::try_from_slice(&game_account.data.borrow())
-        .map_err(|_| ProgramError::InvalidAccountData)?;
+    let mut game = Game::unpack_from(&game_account.data.borrow())?;
 
-    // Check if game is in correct state
-    if !matches!(game.state, GameState::CommitPhase) {
-        msg!("Game is not in commit phase");
-        return Err(RPSError::InvalidGameState.into());
-    }
+    // Check if game is in finished state
+    state_machine::require(game.state, Action::Settle)?;
 
-    // Find player and update their committed choice
-    let mut player_found = false;
-    for game_player in &mut game.players {
-        if game_player.pubkey == *player.key {
-            game_player.committed_choice = committed_choice;
-            game_player.salt = salt; // Store salt for verification
-            player_found = true;
-            break;
-        }
-    }
+    // Check if initiator is host or a player
+    let is_participant = game.host == *initiator.key ||
+        game.players.iter().any(|p| p.pubkey == *initiator.key);
 
-    if !player_found {
-        msg!("Player not found in game");
-        return Err(RPSError::PlayerNotFound.into());
+    if !is_participant {
+        msg!("Initiator is not a participant in the game");
+        return Err(RPSError::NotAuthorized.into());
+    }
+    if !game.paid_out() {
+        msg!("Pay every winner, or refund every seat, before replaying");
+        return Err(RPSError::InvalidGameState.into());
     }
 
-    // Check if all players have committed and transition to reveal phase if so
-    let all_committed = game.players.iter().all(|p| p.committed_choice != [0; 64]);
+    // Reset game state for a new round
+    let clock = Clock::get()?;
+    game.current_round = 1;
+    enter_phase(game_account.key, &mut game, Transition::Replay, clock.unix_timestamp as u64)?;
 
-    if all_committed {
-        game.state = GameState::RevealPhase;
-        msg!("All players have committed their choices. Moving to reveal phase.");
+    // Reset all players
+    for player in &mut game.players {
+        player.choice = Choice::None;
+        player.committed_choice = [0; 64];
+        player.revealed = false;
+        player.score = 0;
     }
 
     // Update last action timestamp
-    let clock = Clock::get()?;
     game.last_action_timestamp = clock.unix_timestamp as u64;
 
     // Save game state
     game.serialize(&mut *game_account.data.borrow_mut())?;
 
-    msg!("Player committed choice: {}", player.key);
+    msg!("New game round started");
+    emit_event(&RPSEvent::RoundStarted {
+        game: *game_account.key,
+        round: game.current_round,
+        total_rounds: game.total_rounds,
+    });
 
     Ok(())
 }
 
-// Implementation for revealing a choice
-fn process_reveal_choice(
+// Implementation for auto-playing the next round
+fn process_auto_play_next_round(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
-    choice: Choice,
 ) -> ProgramResult {
     let accounts_iter = &mut accounts.iter();
 
-    let player = next_account_info(accounts_iter)?;
+    let initiator = next_account_info(accounts_iter)?;
     let game_account = next_account_info(accounts_iter)?;
 
-    // Ensure the player signed the transaction
-    if !player.is_signer {
-        msg!("Player must sign the transaction");
+    // Ensure the initiator signed the transaction
+    if !initiator.is_signer {
+        msg!("Initiator must sign the transaction");
         return Err(RPSError::NotAuthorized.into());
     }
+    assert_owned_by(game_account, program_id)?;
 
     // Load game state
-    let mut game = Game::try_from_slice(&game_account.data.borrow())
-        .map_err(|_| ProgramError::InvalidAccountData)?;
+    let mut game = Game::unpack_from(&game_account.data.borrow())?;
 
-    // Check if game is in correct state
-    if !matches!(game.state, GameState::RevealPhase) {
-        msg!("Game is not in reveal phase");
+    // Check if game is in automated mode
+    if !matches!(game.game_mode, GameMode::Automated) {
+        msg!("Game is not in automated mode");
         return Err(RPSError::InvalidGameState.into());
     }
 
-    // Find player's index and verify the commit matches reveal
-    let mut player_index = None;
-    for (i, game_player) in game.players.iter().enumerate() {
-        if game_player.pubkey == *player.key {
-            player_index = Some(i);
-
-            // Verify that the revealed choice matches the committed choice
-            // Convert choice to a byte for hashing
-            let choice_byte = match choice {
-                Choice::Rock => 1u8,
-                Choice::Paper => 2u8,
-                Choice::Scissors => 3u8,
-                Choice::None => {
-                    msg!("Invalid choice: None");
-                    return Err(RPSError::InvalidChoice.into());
-                }
-            };
-            
-            // Create input for HMAC-SHA512
-            type HmacSha512 = Hmac<Sha512>;
-            let mut mac = HmacSha512::new_from_slice(&game_player.salt)
-                .map_err(|_| ProgramError::InvalidArgument)?;
-            mac.update(&[choice_byte]);
-            let result = mac.finalize().into_bytes(); // 64-byte array
-
-            // Compare with committed hash
-            if result[..] != game_player.committed_choice {
-                msg!("Revealed choice does not match committed choice");
-                return Err(RPSError::InvalidHash.into());
-            }
+    // Check if game is in finished state
+    state_machine::require(game.state, Action::Settle)?;
 
-            break;
-        }
+    // Check if we've reached the maximum number of auto rounds
+    if game.current_auto_round >= game.max_auto_rounds {
+        msg!("Maximum number of auto rounds reached");
+        return Err(RPSError::InvalidGameState.into());
     }
 
-    let player_index = player_index.ok_or_else(|| {
-        msg!("Player not found in game");
-        RPSError::PlayerNotFound
-    })?;
-
-    // Update player's choice and revealed status
-    game.players[player_index].choice = choice;
-    game.players[player_index].revealed = true;
-
-    // Check if all players have revealed and process round if so
-    let all_revealed = game.players.iter().all(|p| p.revealed);
+    // Check if initiator is host or a player
+    let is_participant = game.host == *initiator.key ||
+        game.players.iter().any(|p| p.pubkey == *initiator.key);
 
-    if all_revealed {
-        // Calculate round winners
-        process_round_results(&mut game);
+    if !is_participant {
+        msg!("Initiator is not a participant in the game");
+        return Err(RPSError::NotAuthorized.into());
+    }
+    if !game.paid_out() {
+        msg!("Pay every winner, or refund every seat, before replaying");
+        return Err(RPSError::InvalidGameState.into());
+    }
 
-        // Check if game should end
-        if game.current_round >= game.total_rounds {
-            game.state = GameState::Finished;
-            msg!("Game finished after {} rounds", game.total_rounds);
-        } else {
-            // Reset for next round
-            game.current_round += 1;
-            game.state = GameState::CommitPhase;
-            msg!("Starting round {} of {}", game.current_round, game.total_rounds);
+    // Reset game state for a new round
+    let clock = Clock::get()?;
+    game.current_round = 1;
+    enter_phase(game_account.key, &mut game, Transition::Replay, clock.unix_timestamp as u64)?;
+    game.current_auto_round += 1;
 
-            // Reset player choices for next round
-            for player in &mut game.players {
-                player.choice = Choice::None;
-                player.committed_choice = [0; 64];
-                player.salt = [0; 32];
-                player.revealed = false;
-            }
-        }
+    // Reset all players
+    for player in &mut game.players {
+        player.choice = Choice::None;
+        player.committed_choice = [0; 64];
+        player.revealed = false;
+        player.score = 0;
     }
 
     // Update last action timestamp
-    let clock = Clock::get()?;
     game.last_action_timestamp = clock.unix_timestamp as u64;
 
     // Save game state
     game.serialize(&mut *game_account.data.borrow_mut())?;
 
-    msg!("Player revealed choice: {}", player.key);
+    msg!("New automated game round started");
+    emit_event(&RPSEvent::RoundStarted {
+        game: *game_account.key,
+        round: game.current_round,
+        total_rounds: game.total_rounds,
+    });
 
     Ok(())
 }
 
-// Implementation for resolving timeouts
-fn process_resolve_timeout(
+// Implementation for adding bot players
+fn process_add_bot_players(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
+    count: u8,
+    strategy: u8,
 ) -> ProgramResult {
     let accounts_iter = &mut accounts.iter();
 
-    let caller = next_account_info(accounts_iter)?;
+    let initiator = next_account_info(accounts_iter)?;
     let game_account = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+    check_system_program(system_program)?;
+    let treasury_account = next_account_info(accounts_iter)?;
+    check_treasury(program_id, treasury_account)?;
+    let vault_account = next_account_info(accounts_iter)?;
+    // Only read by the pause check; validated so it can't be swapped out
+    let config_account = next_account_info(accounts_iter)?;
+    if *config_account.key != config_address(program_id).0 {
+        msg!("Config account is not the program config");
+        return Err(RPSError::InvalidParameter.into());
+    }
+    // Token program, initiator token account and game vault (RPSToken only)
+    let token_tail = accounts_iter.as_slice();
 
-    // Ensure the caller signed the transaction
-    if !caller.is_signer {
-        msg!("Caller must sign the transaction");
+    // Ensure the initiator signed the transaction
+    if !initiator.is_signer {
+        msg!("Initiator must sign the transaction");
         return Err(RPSError::NotAuthorized.into());
     }
 
+    let strategy = match strategy {
+        0 => BotStrategy::Random,
+        1 => BotStrategy::CounterLastMove,
+        2 => BotStrategy::Weighted,
+        _ => {
+            msg!("Invalid bot strategy. Must be 0 (random), 1 (counter last move) or 2 (weighted)");
+            return Err(RPSError::InvalidParameter.into());
+        }
+    };
+
     // Load game state
-    let mut game = Game::try_from_slice(&game_account.data.borrow())
-        .map_err(|_| ProgramError::InvalidAccountData)?;
+    let mut game = Game::unpack_from(&game_account.data.borrow())?;
 
-    // Check if timeout has occurred
-    let clock = Clock::get()?;
-    let current_time = clock.unix_timestamp as u64;
-    let time_elapsed = current_time.saturating_sub(game.last_action_timestamp);
+    // Bots take real seats, so only the host decides who fills the lobby
+    if !game.allow_public_bots && *initiator.key != game.host {
+        msg!("Only the host can add bots to this game");
+        return Err(RPSError::NotAuthorized.into());
+    }
+    if !game.team_lineup.is_empty() {
+        msg!("Tournament match seats cannot go to bots");
+        return Err(RPSError::InvalidGameState.into());
+    }
+    // Bot seats have no wallet to refund when the fee is revealed
+    if game.hidden_fee.is_some() {
+        msg!("Mystery-stake seats cannot go to bots");
+        return Err(RPSError::InvalidGameState.into());
+    }
 
-    if time_elapsed < game.required_timeout {
-        msg!("Timeout period has not elapsed yet");
-        return Err(RPSError::TimeoutNotReached.into());
+    // Check if game is in correct state
+    state_machine::require(game.state, Action::Seat)?;
+
+    // Check if there's room for bot players
+    if count == 0 || game.players.len() >= game.player_count as usize {
+        msg!("No room for bot players");
+        return Err(RPSError::GameFull.into());
     }
 
-    // Process timeout based on current game state
-    match game.state {
-        GameState::WaitingForPlayers => {
-            // If no players joined, just end the game
-            if game.players.len() <= 1 {
-                game.state = GameState::Finished;
-                msg!("Game cancelled due to timeout while waiting for players");
-            } else if game.players.len() >= game.min_players as usize {
-                // If we have at least the minimum number of players, start the game
-                game.state = GameState::CommitPhase;
-                msg!("Starting game with {} players due to timeout", game.players.len());
-            } else {
-                // Not enough players, end the game
-                game.state = GameState::Finished;
-                msg!("Game cancelled due to timeout - not enough players joined");
-            }
-        },
-        GameState::CommitPhase => {
-            // Remove players who didn't commit and continue if enough remain
-            let committed_players: Vec<Player> = game.players
-                .iter()
-                .filter(|p| p.committed_choice != [0; 64])
-                .cloned()
-                .collect();
+    // Add bot players; the seat count may be drawn part way through, which
+    // can leave fewer seats than requested
+    let mut bot_count = 0u64;
+    for i in 0..count as usize {
+        if game.players.len() >= game.player_count as usize {
+            break;
+        }
+        // Create a deterministic bot pubkey based on game account and index
+        let seed = format!("bot_{}_{}_{}", game_account.key, game.players.len(), i);
+        let hash = hashv(&[seed.as_bytes()]).to_bytes();
+        let mut bot_pubkey_bytes = [0u8; 32];
+        bot_pubkey_bytes.copy_from_slice(&hash[0..32]);
+        let bot_pubkey = Pubkey::new_from_array(bot_pubkey_bytes);
 
-            if committed_players.len() >= game.min_players as usize {
-                game.players = committed_players;
-                game.state = GameState::RevealPhase;
-                msg!("Moving to reveal phase with {} committed players", game.players.len());
-            } else {
-                // Not enough players committed, end game
-                game.state = GameState::Finished;
-                msg!("Game ended due to timeout - not enough players committed");
-            }
-        },
-        GameState::RevealPhase => {
-            // Process round with revealed choices only
-            // Players who didn't reveal get a default loss
-            let mut all_revealed = true;
-            
-            for player in &mut game.players {
-                if !player.revealed {
-                    player.choice = Choice::None; // Default to None for non-revealed
-                    player.revealed = true;
-                    all_revealed = false;
-                }
-            }
-            
-            if !all_revealed {
-                msg!("Some players did not reveal their choices - using default (None)");
-            }
+        game.players.push(Player {
+            pubkey: bot_pubkey,
+            choice: Choice::None,
+            committed_choice: [0; 64],
+            salt: [0; 32],
+            revealed: false,
+            score: 0,
+        });
+        game.bot_config.push(BotSeat {
+            pubkey: bot_pubkey,
+            strategy,
+            funder: *initiator.key,
+        });
 
-            // Calculate round winners
-            process_round_results(&mut game);
+        debug_msg!("Added bot player: {}", bot_pubkey);
+        emit_event(&RPSEvent::PlayerJoined {
+            game: *game_account.key,
+            player: bot_pubkey,
+        });
+        bot_count += 1;
+        settle_player_count(game_account.key, &mut game, accounts)?;
+    }
 
-            // Check if game should end
-            if game.current_round >= game.total_rounds {
-                game.state = GameState::Finished;
-                msg!("Game finished after {} rounds", game.total_rounds);
-            } else {
-                // Reset for next round
-                game.current_round += 1;
-                game.state = GameState::CommitPhase;
-                msg!("Starting round {} of {}", game.current_round, game.total_rounds);
+    // Bots have no wallet, so the initiator buys all their seats at once
+    let fee_amount = calculate_fee(game.entry_fee, game.fee_bps);
+    let seats = bot_count;
+    let entry_total = game.entry_fee.checked_mul(seats).ok_or(RPSError::FeeCalculationError)?;
+    let fee_total = fee_amount * seats;
+    let pot_total = entry_total - fee_total;
+    if entry_total > 0 && !game.bot_funders.contains(initiator.key) {
+        game.bot_funders.push(*initiator.key);
+    }
 
-                // Reset player choices for next round
-                for player in &mut game.players {
-                    player.choice = Choice::None;
-                    player.committed_choice = [0; 64];
-                    player.salt = [0; 32];
-                    player.revealed = false;
-                }
-            }
-        },
-        GameState::Finished => {
-            msg!("Game is already finished");
-            return Err(RPSError::InvalidGameState.into());
-        },
+    // SOL fees go straight to the treasury, token fees are held by the game
+    // until collected
+    game.game_pot += pot_total;
+    if matches!(game.currency_mode, CurrencyMode::RPSToken) {
+        game.fee_collected += fee_total;
+    }
+
+    // Update game state if required player count is reached
+    let clock = Clock::get()?;
+    if game.players.len() >= game.player_count as usize {
+        enter_phase(game_account.key, &mut game, Transition::Fill, clock.unix_timestamp as u64)?;
+        debug_msg!("Required player count reached: {}", game.player_count);
     }
 
     // Update last action timestamp
-    game.last_action_timestamp = current_time;
+    game.last_action_timestamp = clock.unix_timestamp as u64;
 
     // Save game state
     game.serialize(&mut *game_account.data.borrow_mut())?;
 
-    msg!("Timeout resolved");
+    if entry_total > 0 {
+        match game.currency_mode {
+            CurrencyMode::SOL => {
+                check_pot_vault(program_id, game_account.key, &game, vault_account)?;
+                pay_sol_entry(game_account.key, initiator, vault_account, treasury_account, system_program, pot_total, fee_total)?;
+            },
+            CurrencyMode::RPSToken => {
+                let mint = game.token_mint.ok_or(RPSError::InvalidGameState)?;
+                deposit_tokens(program_id, game_account.key, &mint, initiator, token_tail, entry_total)?;
+            }
+        }
+    }
+
+    msg!("Added {} bot players", bot_count);
 
     Ok(())
 }
 
-// Implementation for claiming winnings
-fn process_claim_winnings(
+// Implementation for playing bot seats. A bot has no key, so its
+// commitment only binds the seat to the slot hash it committed at; its hand
+// is drawn with the reveal order when the commit phase closes, after every
+// human hand is locked in, and revealing only shows it. Whoever closes the
+// commit phase picks that slot, though, so like auto tournament matches
+// this is fine for casual play but not meant to be unpredictable.
+fn process_bot_act(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
 ) -> ProgramResult {
     let accounts_iter = &mut accounts.iter();
 
-    let winner = next_account_info(accounts_iter)?;
+    let caller = next_account_info(accounts_iter)?;
     let game_account = next_account_info(accounts_iter)?;
-    let system_program = next_account_info(accounts_iter)?;
-    
-    // Get token accounts if needed
-    let (token_program, winner_token_account, game_token_account) = if accounts_iter.len() >= 3 {
-        let token_program = next_account_info(accounts_iter)?;
-        let winner_token_account = next_account_info(accounts_iter)?;
-        let game_token_account = next_account_info(accounts_iter)?;
-        (Some(token_program), Some(winner_token_account), Some(game_token_account))
-    } else {
-        (None, None, None)
-    };
-
-    // Ensure the winner signed the transaction
-    if !winner.is_signer {
-        msg!("Winner must sign the transaction");
-        return Err(RPSError::NotAuthorized.into());
-    }
-
-    // Load game state
-    let mut game = Game::try_from_slice(&game_account.data.borrow())
-        .map_err(|_| ProgramError::InvalidAccountData)?;
 
-    // Check if game is finished
-    if !matches!(game.state, GameState::Finished) {
-        msg!("Game is not finished");
-        return Err(RPSError::InvalidGameState.into());
-    }
-
-    // Find the winner(s) - those with highest score
-    let mut max_score = 0;
-    for player in &game.players {
-        if player.score > max_score {
-            max_score = player.score;
-        }
-    }
-
-    let winners: Vec<&Player> = game.players
-        .iter()
-        .filter(|p| p.score == max_score)
-        .collect();
-
-    // Check if caller is among winners
-    let caller_is_winner = winners.iter().any(|p| p.pubkey == *winner.key);
-
-    if !caller_is_winner {
-        msg!("Caller is not a winner");
-        return Err(RPSError::NotWinner.into());
-    }
-
-    // Calculate winner's share - all winners take equal share of the pot
-    let winner_share = game.game_pot / winners.len() as u64;
-    
-    if winner_share == 0 {
-        msg!("No winnings to claim");
-        return Err(RPSError::InsufficientFunds.into());
-    }
-
-    // Transfer winner's share based on currency mode
-    match game.currency_mode {
-        CurrencyMode::SOL => {
-            // Transfer SOL from game account to winner
-            let game_key = game_account.key;
-            let seeds = rps_pda_seeds(game_key);
-            let (_pda, bump) = Pubkey::find_program_address(&seeds, program_id);
-            let signer_seeds: &[&[u8]] = &[seeds[0], seeds[1], &[bump]];
-            
-            invoke_signed(
-                &system_instruction::transfer(
-                    game_account.key,
-                    winner.key,
-                    winner_share,
-                ),
-                &[game_account.clone(), winner.clone(), system_program.clone()],
-                &[signer_seeds],
-            )?;
-        },
-        CurrencyMode::RPSToken => {
-            // For token transfers, we need token program and accounts
-            if token_program.is_none() || winner_token_account.is_none() || game_token_account.is_none() {
-                msg!("Token program and accounts required for RPSToken winnings");
-                return Err(RPSError::InvalidParameter.into());
-            }
-            
-            // Token transfer would be implemented here
-            // This is a placeholder for the actual token transfer logic
-            msg!("Token transfer for winnings would happen here");
-            
-            // Note: In a complete implementation, you would transfer tokens from the game's token account
-            // to the winner's token account
-        }
+    if !caller.is_signer {
+        msg!("Caller must sign the transaction");
+        return Err(RPSError::NotAuthorized.into());
     }
+    assert_owned_by(game_account, program_id)?;
+    let Some(recent) = newest_slot_hash(accounts)? else {
+        msg!("Bot hands are drawn from the SlotHashes sysvar: pass it");
+        return Err(RPSError::InvalidParameter.into());
+    };
 
-    // Update game pot
-    game.game_pot -= winner_share;
+    let mut game = Game::unpack_from(&game_account.data.borrow())?;
+    let now = Clock::get()?.unix_timestamp as u64;
+    let is_bot = |player: &Player| game.bot_config.iter().any(|bot| bot.pubkey == player.pubkey);
 
-    // Mark player as paid
-    for player in &mut game.players {
-        if player.pubkey == *winner.key {
-            player.score = 0; // Set to 0 to prevent double claiming
-            break;
+    if state_machine::allows(game.state, Action::Commit) {
+        let pending: Vec<usize> = (0..game.players.len())
+            .filter(|&i| is_bot(&game.players[i]) && game.players[i].committed_choice == [0; 64])
+            .collect();
+        if pending.is_empty() {
+            msg!("No bot seat has a choice to commit");
+            return Err(RPSError::InvalidGameState.into());
+        }
+        for seat in pending {
+            let bot = game.players[seat].pubkey;
+            let commitment = hashv(&[
+                b"rps-bot-commit",
+                game_account.key.as_ref(),
+                &[game.current_round],
+                bot.as_ref(),
+                &recent,
+            ]);
+            game.players[seat].committed_choice[..32].copy_from_slice(commitment.as_ref());
+            debug_msg!("Bot committed choice: {}", bot);
+            emit_event(&RPSEvent::ChoiceCommitted {
+                game: *game_account.key,
+                player: bot,
+            });
         }
-    }
 
-    // Save game state
-    game.serialize(&mut *game_account.data.borrow_mut())?;
+        let committed = game.players.iter().filter(|p| p.committed_choice != [0; 64]).count();
+        emit_event(&RPSEvent::CommitProgress {
+            game: *game_account.key,
+            committed: committed as u8,
+            total: game.players.len() as u8,
+        });
+        if committed == game.players.len() {
+            draw_reveal_order(game_account.key, &mut game, Some(recent));
+            enter_phase(game_account.key, &mut game, Transition::CloseCommits, now)?;
+            debug_msg!("All players have committed their choices. Moving to reveal phase.");
+            emit_event(&RPSEvent::RevealPhaseStarted { game: *game_account.key });
+        }
+    } else {
+        state_machine::require(game.state, Action::Reveal)?;
+        let pending: Vec<usize> = (0..game.players.len())
+            .filter(|&i| {
+                let player = &game.players[i];
+                is_bot(player) && player.committed_choice != [0; 64] && !player.revealed
+            })
+            .collect();
+        if pending.is_empty() {
+            msg!("No bot seat has a choice to reveal");
+            return Err(RPSError::InvalidGameState.into());
+        }
+        for seat in pending {
+            let player = &mut game.players[seat];
+            player.revealed = true;
+            debug_msg!("Bot revealed choice: {}", player.pubkey);
+            emit_event(&RPSEvent::ChoiceRevealed {
+                game: *game_account.key,
+                player: player.pubkey,
+            });
+        }
 
-    msg!("Winnings claimed by: {}", winner.key);
+        let revealed = game.players.iter().filter(|p| p.revealed).count();
+        emit_event(&RPSEvent::RevealProgress {
+            game: *game_account.key,
+            revealed: revealed as u8,
+            total: game.players.len() as u8,
+        });
+        if revealed == game.players.len() {
+            close_round(game_account.key, &mut game, now)?;
+        }
+    }
 
+    game.last_action_timestamp = now;
+    game.serialize(&mut *game_account.data.borrow_mut())?;
     Ok(())
 }
 
-// Implementation for rejoining a game as a loser
-fn process_rejoin_game(
+// Implementation for collecting fees
+fn process_collect_fees(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
 ) -> ProgramResult {
     let accounts_iter = &mut accounts.iter();
 
-    let player = next_account_info(accounts_iter)?;
+    let fee_collector = next_account_info(accounts_iter)?;
     let game_account = next_account_info(accounts_iter)?;
     let system_program = next_account_info(accounts_iter)?;
-    
-    // Get fee collector account if provided
-    let fee_collector_account = if accounts_iter.len() > 0 {
-        Some(next_account_info(accounts_iter)?)
-    } else {
-        None
-    };
-    
-    // Get token accounts if needed
-    let (token_program, player_token_account, game_token_account) = if accounts_iter.len() >= 3 {
-        let token_program = next_account_info(accounts_iter)?;
-        let player_token_account = next_account_info(accounts_iter)?;
-        let game_token_account = next_account_info(accounts_iter)?;
-        (Some(token_program), Some(player_token_account), Some(game_token_account))
-    } else {
-        (None, None, None)
-    };
+    check_system_program(system_program)?;
+    let vault_account = next_account_info(accounts_iter)?;
+    let config_account = next_account_info(accounts_iter)?;
+    let instructions_sysvar = next_account_info(accounts_iter)?;
 
-    // Ensure the player signed the transaction
-    if !player.is_signer {
-        msg!("Player must sign the transaction");
+    // Token program, collector token account, game vault and game authority
+    // (RPSToken only)
+    let token_tail = accounts_iter.as_slice();
+
+    // Ensure the fee collector signed the transaction
+    if !fee_collector.is_signer {
+        msg!("Fee collector must sign the transaction");
         return Err(RPSError::NotAuthorized.into());
     }
 
     // Load game state
-    let mut game = Game::try_from_slice(&game_account.data.borrow())
-        .map_err(|_| ProgramError::InvalidAccountData)?;
+    let mut game = Game::unpack_from(&game_account.data.borrow())?;
 
-    // Check if game is in correct state and losers can rejoin
-    if !matches!(game.state, GameState::Finished) || !game.losers_can_rejoin {
-        msg!("Game is not finished or losers cannot rejoin");
-        return Err(RPSError::InvalidGameState.into());
+    // Check if there are fees to collect
+    if game.fee_collected == 0 {
+        msg!("No fees to collect");
+        return Err(RPSError::InsufficientFunds.into());
     }
 
-    // Check if player was a loser in the previous game
-    let mut was_player = false;
-    let mut was_loser = false;
-    let max_score = game.players.iter().map(|p| p.score).max().unwrap_or(0);
+    let config = load_config_if_any(program_id, config_account)?;
+    check_transaction_guard(program_id, config.as_ref(), instructions_sysvar)?;
 
-    for player_data in &game.players {
-        if player_data.pubkey == *player.key {
-            was_player = true;
-            if player_data.score < max_score {
-                was_loser = true;
-            }
-            break;
+    // A configured collector is the only one, and may collect early;
+    // without one, fees are collected from finished games
+    match config.and_then(|config| config.fee_collector) {
+        Some(collector) if collector != *fee_collector.key => {
+            msg!("Only the configured fee collector can collect fees");
+            return Err(RPSError::NotAuthorized.into());
         }
-    }
-
-    if !was_player || !was_loser {
-        msg!("Player was not in the game or was not a loser");
-        return Err(RPSError::InvalidPlayerState.into());
-    }
-
-    // Calculate fee for the entry
-    let fee_amount = if game.entry_fee > 0 {
-        calculate_fee(game.entry_fee)
-    } else {
-        0
-    };
-    
-    // Adjust amount to add to game pot
-    let pot_amount = if game.entry_fee > 0 {
-        game.entry_fee - fee_amount
-    } else {
-        0
-    };
-
-    // Handle payment based on currency mode
-    if game.entry_fee > 0 {
-        match game.currency_mode {
-            CurrencyMode::SOL => {
-                // Transfer entry fee from player to game account
-                invoke(
-                    &system_instruction::transfer(
-                        player.key,
-                        game_account.key,
-                        game.entry_fee,
-                    ),
-                    &[player.clone(), game_account.clone(), system_program.clone()],
-                )?;
-                
-                // If fee collector is provided, transfer fee
-                if let Some(fee_account) = fee_collector_account {
-                    if fee_amount > 0 {
-                        // Transfer fee from game account to fee collector
-                        let game_key = game_account.key;
-                        let seeds = &[b"rps_game", game_key.as_ref(), &[1]];
-                        let (pda, bump) = Pubkey::find_program_address(seeds, program_id);
-                        let signer_seeds = &[b"rps_game", game_key.as_ref(), &[bump][..]];
-                        
-                        invoke_signed(
-                            &system_instruction::transfer(
-                                game_account.key,
-                                fee_account.key,
-                                fee_amount,
-                            ),
-                            &[game_account.clone(), fee_account.clone(), system_program.clone()],
-                            &[signer_seeds],
-                        )?;
-                    }
-                }
-                
-                // Update game pot and fee collected
-                game.game_pot += pot_amount;
-                game.fee_collected += fee_amount;
-            },
-            CurrencyMode::RPSToken => {
-                // For token transfers, we need token program and accounts
-                if token_program.is_none() || player_token_account.is_none() || game_token_account.is_none() {
-                    msg!("Token program and accounts required for RPSToken games");
-                    return Err(RPSError::InvalidParameter.into());
-                }
-                
-                // Token transfer would be implemented here
-                // This is a placeholder for the actual token transfer logic
-                msg!("Token transfer for rejoining game would happen here");
-                
-                // Update game pot and fee collected
-                game.game_pot += pot_amount;
-                game.fee_collected += fee_amount;
-            }
+        None if !state_machine::allows(game.state, Action::Settle) => {
+            msg!("Game is not finished and no fee collector is configured");
+            return Err(RPSError::NotAuthorized.into());
         }
+        _ => {}
     }
 
-    // Reset this player's stats for the next game
-    for player_data in &mut game.players {
-        if player_data.pubkey == *player.key {
-            player_data.choice = Choice::None;
-            player_data.committed_choice = [0; 64];
-            player_data.salt = [0; 32];
-            player_data.revealed = false;
-            break;
+    // Transfer fees based on currency mode
+    let fee_amount = game.fee_collected;
+    match game.currency_mode {
+        CurrencyMode::SOL => {
+            check_pot_vault(program_id, game_account.key, &game, vault_account)?;
+            pay_from_program_account(vault_account, fee_collector, fee_amount)?;
+        },
+        CurrencyMode::RPSToken => {
+            let mint = game.token_mint.ok_or(RPSError::InvalidGameState)?;
+            withdraw_tokens(program_id, game_account.key, &mint, fee_collector.key, token_tail, fee_amount)?;
         }
     }
 
-    // Update last action timestamp
-    let clock = Clock::get()?;
-    game.last_action_timestamp = clock.unix_timestamp as u64;
+    // Reset fee collected
+    game.fee_collected = 0;
 
     // Save game state
     game.serialize(&mut *game_account.data.borrow_mut())?;
 
-    msg!("Player rejoined game: {}", player.key);
+    msg!("Collected {} fees", fee_amount);
+    emit_event(&RPSEvent::FeeCollected {
+        source: *game_account.key,
+        recipient: *fee_collector.key,
+        amount: fee_amount,
+    });
 
     Ok(())
 }
 
-// Implementation for starting a new game round with the same players
-fn process_start_new_game_round(
-    program_id: &Pubkey,
-    accounts: &[AccountInfo],
-) -> ProgramResult {
-    let accounts_iter = &mut accounts.iter();
+// Helper function to process round results
+// Make `transition`, if the state machine allows it from the game's phase,
+// and announce the new phase's deadline
+fn enter_phase(game_key: &Pubkey, game: &mut Game, transition: Transition, now: u64) -> ProgramResult {
+    let state = state_machine::next_state(Phase::of(game), transition)?;
+    if matches!(state, GameState::Finished) {
+        flag_collusion(game_key, game);
+    }
+    if let Some(seconds) = game.phase_elapsed(now) {
+        emit_event(&RPSEvent::PhaseEnded {
+            game: *game_key,
+            phase_nonce: game.phase_nonce,
+            state: game.state,
+            seconds,
+        });
+    }
+    game.set_phase(state, now);
+    emit_event(&RPSEvent::PhaseStarted {
+        game: *game_key,
+        phase_nonce: game.phase_nonce,
+        deadline: game.phase_deadline,
+    });
+    Ok(())
+}
 
-    let initiator = next_account_info(accounts_iter)?;
-    let game_account = next_account_info(accounts_iter)?;
+// Finishes a game that timed out before it could be played. Its seats take
+// their entries back with `RefundEntry` instead of splitting the pot as tied
+// winners; a pot spread over stake vaults still settles through claims.
+fn cancel_game(game_key: &Pubkey, game: &mut Game, now: u64) -> ProgramResult {
+    // The escrow records say what each seat paid in, so they outlive the
+    // finish until every seat is refunded
+    let escrows = std::mem::take(&mut game.escrows);
+    enter_phase(game_key, game, Transition::Finish, now)?;
+    game.cancelled = game.stake_vaults.is_empty();
+    if game.cancelled {
+        game.settlement = Settlement::default();
+        game.escrows = escrows;
+    }
+    Ok(())
+}
 
-    // Ensure the initiator signed the transaction
-    if !initiator.is_signer {
-        msg!("Initiator must sign the transaction");
-        return Err(RPSError::NotAuthorized.into());
+fn process_round_results(game_key: &Pubkey, game: &mut Game) {
+    // A point for every opponent a player's hand beats under the game's
+    // rules; they live in rps-state so off-chain simulations score
+    // identically. Scoring and the
+    // collusion tallies compare every pair of seats, which at MAX_SEATS is
+    // 28 pairs of byte compares: noise next to the reveal's HMAC.
+    let hands: Vec<Option<Hand>> = game.players.iter().map(|p| p.choice.hand()).collect();
+    for (player, points) in game.players.iter_mut().zip(simulate::round_points(game.rule_set.rules(), &hands)) {
+        player.score += points;
+    }
+    watch_seats(game, &hands);
+    
+    // Log scores for debugging
+    for (i, player) in game.players.iter().enumerate() {
+        debug_msg!("Player {} score: {}", i, player.score);
     }
 
-    // Load game state
-    let mut game = Game::try_from_slice(&game_account.data.borrow())
-        .map_err(|_| ProgramError::InvalidAccountData)?;
+    emit_event(&RPSEvent::RoundResolved {
+        game: *game_key,
+        round: game.current_round,
+        scores: game.players.iter().map(|p| p.score).collect(),
+    });
+}
 
-    // Check if game is in finished state
-    if !matches!(game.state, GameState::Finished) {
-        msg!("Game is not finished");
-        return Err(RPSError::InvalidGameState.into());
+// Folds one scored round into the collusion tallies
+fn watch_seats(game: &mut Game, hands: &[Option<Hand>]) {
+    let rules = game.rule_set.rules();
+    for (player, hand) in game.players.iter().zip(hands) {
+        let index = match game.seat_watch.iter().position(|w| w.player == player.pubkey) {
+            Some(index) => index,
+            None => {
+                game.seat_watch.push(SeatWatch {
+                    player: player.pubkey,
+                    last_hand: 0,
+                    hand_streak: 0,
+                    lost_to: None,
+                    loss_streak: 0,
+                });
+                game.seat_watch.len() - 1
+            }
+        };
+        let watch = &mut game.seat_watch[index];
+
+        // A missed reveal is a timeout, not a pattern
+        let Some(hand) = *hand else {
+            watch.last_hand = 0;
+            watch.hand_streak = 0;
+            watch.lost_to = None;
+            watch.loss_streak = 0;
+            continue;
+        };
+        if watch.last_hand == hand.byte() {
+            watch.hand_streak = watch.hand_streak.saturating_add(1);
+        } else {
+            watch.last_hand = hand.byte();
+            watch.hand_streak = 1;
+        }
+
+        let beat_anyone = hands.iter().any(|other| other.is_some_and(|o| rules.beats(hand, o).is_gt()));
+        let mut beaten_by = hands
+            .iter()
+            .zip(&game.players)
+            .filter(|(other, _)| other.is_some_and(|o| rules.beats(hand, o).is_lt()))
+            .map(|(_, p)| p.pubkey);
+        if beat_anyone {
+            watch.lost_to = None;
+            watch.loss_streak = 0;
+        } else if let Some(same) = watch.lost_to.filter(|w| beaten_by.clone().any(|b| b == *w)) {
+            watch.lost_to = Some(same);
+            watch.loss_streak = watch.loss_streak.saturating_add(1);
+        } else {
+            watch.lost_to = beaten_by.next();
+            watch.loss_streak = watch.lost_to.is_some() as u8;
+        }
     }
+}
 
-    // Check if initiator is host or a player
-    let is_participant = game.host == *initiator.key ||
-        game.players.iter().any(|p| p.pubkey == *initiator.key);
+// Settlement-time collusion heuristics. A flag is a prompt for off-chain
+// review; it only stops payouts if the operator holds flagged payouts.
+fn flag_collusion(game_key: &Pubkey, game: &mut Game) {
+    let mut reasons = 0;
+    let funder_seated = game
+        .bot_funders
+        .iter()
+        .any(|funder| game.players.iter().any(|p| p.pubkey == *funder));
+    if game.entry_fee > 0 && funder_seated {
+        reasons |= FLAG_SHARED_FUNDING;
+    }
+    if game.seat_watch.iter().any(|w| w.hand_streak >= SUSPICIOUS_HAND_STREAK) {
+        reasons |= FLAG_HAND_PATTERN;
+    }
+    if game.seat_watch.iter().any(|w| w.loss_streak >= DUMP_LOSS_ROUNDS) {
+        reasons |= FLAG_DUMP_LOSSES;
+    }
 
-    if !is_participant {
-        msg!("Initiator is not a participant in the game");
-        return Err(RPSError::NotAuthorized.into());
+    // Restarted games are judged on their own rounds
+    game.seat_watch.clear();
+    game.flagged = reasons != 0;
+    game.flag_reasons = reasons;
+    if game.flagged {
+        debug_msg!("Game flagged for review: reasons {:#04b}", reasons);
+        emit_event(&RPSEvent::CollusionSuspected { game: *game_key, reasons });
     }
+}
 
-    // Reset game state for a new round
-    game.current_round = 1;
-    game.state = GameState::CommitPhase;
+// Commitment for `player`'s choice in `round` of `game`: HMAC-SHA512 keyed
+// by the salt over the game key, round, player key and choice byte. Binding
+// the game, round and player means a commitment copied from another seat,
+// round or game never verifies. Public so clients and test fixtures hash
+// exactly like the program does.
+pub fn compute_commitment(
+    game: &Pubkey,
+    round: u8,
+    player: &Pubkey,
+    salt: &[u8; 32],
+    choice: &Choice,
+) -> Result<[u8; 64], ProgramError> {
+    compute_commitment_with(CommitmentScheme::HmacSha512, game, round, player, salt, choice)
+}
 
-    // Potentially randomize player count again for the new game
-    let clock = Clock::get()?;
-    if game.min_players != game.max_players {
-        game.player_count = if (clock.unix_timestamp & 1) == 0 { 3 } else { 4 };
-    }
+// Commitment for a choice under a game's chosen scheme
+pub fn compute_commitment_with(
+    scheme: CommitmentScheme,
+    game: &Pubkey,
+    round: u8,
+    player: &Pubkey,
+    salt: &[u8; 32],
+    choice: &Choice,
+) -> Result<[u8; 64], ProgramError> {
+    let Some(hand) = choice.hand() else {
+        msg!("Invalid choice: None");
+        return Err(RPSError::InvalidChoice.into());
+    };
+    let binding = simulate::CommitBinding {
+        game: game.to_bytes(),
+        round,
+        player: player.to_bytes(),
+    };
+    Ok(simulate::commitment_with(scheme.rules(), &binding, salt, hand))
+}
 
-    // Reset all players
-    for player in &mut game.players {
-        player.choice = Choice::None;
-        player.committed_choice = [0; 64];
-        player.salt = [0; 32];
-        player.revealed = false;
-        player.score = 0;
-    }
+// Seal of a mystery-stake entry fee: SHA-256 over the fee's little-endian
+// bytes and the host's salt
+pub fn hidden_fee_commitment(fee: u64, salt: &[u8; 32]) -> [u8; 32] {
+    hashv(&[b"rps-hidden-fee", &fee.to_le_bytes(), salt]).to_bytes()
+}
 
-    // Update last action timestamp
-    game.last_action_timestamp = clock.unix_timestamp as u64;
+// Text a wallet signs to reveal `choice` in `round` of `game`. It is plain
+// ASCII so hardware wallets can show it on screen before signing.
+pub fn reveal_message(game: &Pubkey, round: u8, choice: &Choice) -> Vec<u8> {
+    let hand = match choice {
+        Choice::None => "none",
+        Choice::Rock => "rock",
+        Choice::Paper => "paper",
+        Choice::Scissors => "scissors",
+        Choice::Lizard => "lizard",
+        Choice::Spock => "spock",
+    };
+    format!("Solana RPS reveal\ngame: {}\nround: {}\nchoice: {}", game, round, hand).into_bytes()
+}
 
-    // Save game state
-    game.serialize(&mut *game_account.data.borrow_mut())?;
+// Salt for a commitment revealed through `RevealChoiceSigned`. Ed25519
+// signatures are deterministic, so a player can sign the reveal message
+// before committing and keep the signature secret until the reveal.
+pub fn signed_reveal_salt(signature: &[u8; 64]) -> [u8; 32] {
+    hashv(&[b"rps-reveal-salt", signature]).to_bytes()
+}
 
-    msg!("New game round started");
+// Seat count of a lobby that allows a range, drawn from the newest slot hash
+// and the game key. It is only drawn once the lobby reaches `min_players`,
+// so nobody can know or pick it while seats are still filling.
+pub fn draw_player_count(recent_slot_hash: &[u8; 32], game: &Pubkey, min_players: u8, max_players: u8) -> u8 {
+    let span = max_players.saturating_sub(min_players) as u16 + 1;
+    let seed = hashv(&[b"rps-player-count", recent_slot_hash, game.as_ref()]).to_bytes();
+    min_players + (u16::from(seed[0]) % span) as u8
+}
+
+// Hash of the newest slot in the SlotHashes sysvar, wherever it sits in
+// `accounts`, or `None` if it was not passed. Read in place: the sysvar
+// holds 512 entries and deserializing all of them costs far more than the
+// one needed.
+fn newest_slot_hash(accounts: &[AccountInfo]) -> Result<Option<[u8; 32]>, ProgramError> {
+    let Some(sysvar) = accounts.iter().find(|a| *a.key == slot_hashes::id()) else {
+        return Ok(None);
+    };
+    // u64 entry count, then (slot: u64, hash: [u8; 32]) entries newest first
+    let data = sysvar.try_borrow_data()?;
+    if data.len() < 48 || data[..8] == [0; 8] {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let mut hash = [0u8; 32];
+    hash.copy_from_slice(&data[16..48]);
+    Ok(Some(hash))
+}
 
+// Fixes `player_count` the moment a range lobby reaches `min_players`
+fn settle_player_count(game_key: &Pubkey, game: &mut Game, accounts: &[AccountInfo]) -> ProgramResult {
+    if game.player_count_drawn || game.players.len() < game.min_players as usize {
+        return Ok(());
+    }
+    let Some(recent) = newest_slot_hash(accounts)? else {
+        msg!("Filling this lobby draws its seat count: pass the SlotHashes sysvar");
+        return Err(RPSError::InvalidParameter.into());
+    };
+    game.player_count = draw_player_count(&recent, game_key, game.min_players, game.max_players);
+    game.player_count_drawn = true;
+    debug_msg!("Drew {} seats for the game", game.player_count);
     Ok(())
 }
 
-// Implementation for auto-playing the next round
-fn process_auto_play_next_round(
-    program_id: &Pubkey,
-    accounts: &[AccountInfo],
-) -> ProgramResult {
-    let accounts_iter = &mut accounts.iter();
+// Shuffles the seats into the reveal order of the round whose commits just
+// closed, seeded by every commitment and, when the sysvar is passed, the
+// newest slot hash. Nobody knows it before the last commitment is in, but
+// that committer could grind their salt against it, so it orders the
+// presentation of reveals and is not meant to decide payouts alone.
+fn draw_reveal_order(game_key: &Pubkey, game: &mut Game, recent_slot_hash: Option<[u8; 32]>) {
+    let mut seed = hashv(&[
+        b"rps-reveal-order",
+        game_key.as_ref(),
+        &[game.current_round],
+        &recent_slot_hash.unwrap_or_default(),
+    ]);
+    for player in &game.players {
+        seed = hashv(&[seed.as_ref(), &player.committed_choice]);
+    }
 
-    let initiator = next_account_info(accounts_iter)?;
-    let game_account = next_account_info(accounts_iter)?;
+    // Fisher-Yates, one hash per swap
+    let mut order: Vec<u8> = (0..game.players.len() as u8).collect();
+    for i in (1..order.len()).rev() {
+        let draw = hashv(&[seed.as_ref(), &[i as u8]]).to_bytes();
+        let j = u64::from_le_bytes(draw[..8].try_into().unwrap_or_default()) % (i as u64 + 1);
+        order.swap(i, j as usize);
+    }
+    game.reveal_order = order;
+
+    // Bot hands are fixed by the same draw, so when BotAct reveals them
+    // changes nothing
+    let seed = seed.to_bytes();
+    for seat in 0..game.players.len() {
+        if game.bot_config.iter().any(|bot| bot.pubkey == game.players[seat].pubkey) {
+            game.players[seat].choice = game.bot_hand(seat, &seed);
+        }
+    }
+    emit_event(&RPSEvent::RevealOrderDrawn {
+        game: *game_key,
+        round: game.current_round,
+        order: game.reveal_order.clone(),
+    });
+}
 
-    // Ensure the initiator signed the transaction
-    if !initiator.is_signer {
-        msg!("Initiator must sign the transaction");
-        return Err(RPSError::NotAuthorized.into());
+// `message` wrapped in the version 0 off-chain message envelope, which is
+// what wallets actually sign when asked to sign a text message
+fn offchain_message(message: &[u8]) -> Vec<u8> {
+    let mut framed = Vec::with_capacity(20 + message.len());
+    framed.extend_from_slice(b"\xffsolana offchain");
+    framed.push(0); // version
+    framed.push(0); // restricted ASCII
+    framed.extend_from_slice(&(message.len() as u16).to_le_bytes());
+    framed.extend_from_slice(message);
+    framed
+}
+
+// Returns the signature checked by the ed25519 verify instruction just
+// before the current one, after making sure it is `signer`'s signature over
+// `message` (raw or in the off-chain envelope). The precompile has already
+// verified it by the time this program runs.
+fn verified_ed25519_signature(
+    instructions_sysvar: &AccountInfo,
+    signer: &Pubkey,
+    message: &[u8],
+) -> Result<[u8; 64], ProgramError> {
+    let current = load_current_index_checked(instructions_sysvar)? as usize;
+    let Some(previous) = current.checked_sub(1) else {
+        msg!("Signed reveal must follow an ed25519 verify instruction");
+        return Err(RPSError::InvalidRevealSignature.into());
+    };
+    let verify = load_instruction_at_checked(previous, instructions_sysvar)?;
+    if verify.program_id != ed25519_program::id() {
+        msg!("Signed reveal must follow an ed25519 verify instruction");
+        return Err(RPSError::InvalidRevealSignature.into());
     }
 
-    // Load game state
-    let mut game = Game::try_from_slice(&game_account.data.borrow())
-        .map_err(|_| ProgramError::InvalidAccountData)?;
+    // [count, padding] then per signature seven u16 offsets: signature,
+    // signature ix, pubkey, pubkey ix, message, message size, message ix
+    let data = &verify.data;
+    if data.len() < 16 || data[0] != 1 {
+        msg!("Ed25519 verify must check exactly one signature");
+        return Err(RPSError::InvalidRevealSignature.into());
+    }
+    let offset = |i: usize| u16::from_le_bytes([data[2 + 2 * i], data[3 + 2 * i]]);
+    // Everything must live in the verify instruction itself
+    if [offset(1), offset(3), offset(6)].iter().any(|&ix| ix != u16::MAX) {
+        msg!("Ed25519 verify must carry its own key, signature and message");
+        return Err(RPSError::InvalidRevealSignature.into());
+    }
+    let field = |start: u16, len: usize| data.get(start as usize..start as usize + len);
+    let (Some(signature), Some(key), Some(signed)) = (
+        field(offset(0), 64),
+        field(offset(2), 32),
+        field(offset(4), offset(5) as usize),
+    ) else {
+        return Err(RPSError::InvalidRevealSignature.into());
+    };
 
-    // Check if game is in automated mode
-    if !matches!(game.game_mode, GameMode::Automated) {
-        msg!("Game is not in automated mode");
-        return Err(RPSError::InvalidGameState.into());
+    if key != signer.as_ref() {
+        msg!("Reveal was signed by another wallet");
+        return Err(RPSError::InvalidRevealSignature.into());
     }
+    if signed != message && signed != offchain_message(message).as_slice() {
+        msg!("Signed message is not the reveal message for this round");
+        return Err(RPSError::InvalidRevealSignature.into());
+    }
+    signature.try_into().map_err(|_| RPSError::InvalidRevealSignature.into())
+}
 
-    // Check if game is in finished state
-    if !matches!(game.state, GameState::Finished) {
-        msg!("Game is not finished");
-        return Err(RPSError::InvalidGameState.into());
+// Helper to unpack an SPL token account after checking the token program owns it
+fn unpack_token_account(acc: &AccountInfo) -> Result<spl_token::state::Account, ProgramError> {
+    assert_owned_by(acc, &spl_token::id())?;
+    spl_token::state::Account::unpack(&acc.data.borrow())
+        .map_err(|_| RPSError::InvalidTokenAccount.into())
+}
+
+// Checks `acc` holds `mint` for `owner`
+fn check_token_account(acc: &AccountInfo, owner: &Pubkey, mint: &Pubkey) -> ProgramResult {
+    let state = unpack_token_account(acc)?;
+    if state.owner != *owner || state.mint != *mint {
+        msg!("Token account {} must hold the game mint for {}", acc.key, owner);
+        return Err(RPSError::InvalidTokenAccount.into());
     }
+    Ok(())
+}
 
-    // Check if we've reached the maximum number of auto rounds
-    if game.current_auto_round >= game.max_auto_rounds {
-        msg!("Maximum number of auto rounds reached");
-        return Err(RPSError::InvalidGameState.into());
+// Checks `vault` is the game's token vault (see `game_vault_address`)
+fn check_game_vault(program_id: &Pubkey, game_key: &Pubkey, vault: &AccountInfo, mint: &Pubkey) -> ProgramResult {
+    if *vault.key != game_vault_address(program_id, game_key, mint) {
+        msg!("Game token account must be the game authority's associated token account");
+        return Err(RPSError::InvalidTokenAccount.into());
     }
+    check_token_account(vault, &game_authority_address(program_id, game_key).0, mint)
+}
 
-    // Check if initiator is host or a player
-    let is_participant = game.host == *initiator.key ||
-        game.players.iter().any(|p| p.pubkey == *initiator.key);
+// Moves `amount` of the game mint into the game vault. `tail` is the token
+// program, `owner`'s token account and the vault.
+fn deposit_tokens<'a>(
+    program_id: &Pubkey,
+    game_key: &Pubkey,
+    mint: &Pubkey,
+    owner: &AccountInfo<'a>,
+    tail: &[AccountInfo<'a>],
+    amount: u64,
+) -> ProgramResult {
+    let [token_program, source, vault, ..] = tail else {
+        msg!("Token program and token accounts required for RPSToken games");
+        return Err(RPSError::InvalidParameter.into());
+    };
+    check_token_program(token_program)?;
+    check_token_account(source, owner.key, mint)?;
+    check_game_vault(program_id, game_key, vault, mint)?;
+    invoke(
+        &spl_token::instruction::transfer(token_program.key, source.key, vault.key, owner.key, &[], amount)?,
+        &[source.clone(), vault.clone(), owner.clone(), token_program.clone()],
+    )
+}
 
-    if !is_participant {
-        msg!("Initiator is not a participant in the game");
-        return Err(RPSError::NotAuthorized.into());
+// Pays `amount` out of the game vault, signed by the game authority PDA.
+// `tail` is the token program, `recipient`'s token account, the vault and
+// the game authority.
+fn withdraw_tokens(
+    program_id: &Pubkey,
+    game_key: &Pubkey,
+    mint: &Pubkey,
+    recipient: &Pubkey,
+    tail: &[AccountInfo],
+    amount: u64,
+) -> ProgramResult {
+    let [token_program, destination, vault, authority, ..] = tail else {
+        msg!("Token program, token accounts and game authority required for RPSToken payouts");
+        return Err(RPSError::InvalidParameter.into());
+    };
+    check_token_program(token_program)?;
+    check_token_account(destination, recipient, mint)?;
+    check_game_vault(program_id, game_key, vault, mint)?;
+    let seeds = rps_pda_seeds(game_key);
+    let (authority_key, bump) = Pubkey::find_program_address(&seeds, program_id);
+    if *authority.key != authority_key {
+        msg!("Game authority account does not match the game");
+        return Err(RPSError::InvalidParameter.into());
     }
+    invoke_signed(
+        &spl_token::instruction::transfer(token_program.key, vault.key, destination.key, authority.key, &[], amount)?,
+        &[vault.clone(), destination.clone(), authority.clone(), token_program.clone()],
+        &[&[seeds[0], seeds[1], &[bump]]],
+    )
+}
 
-    // Reset game state for a new round
-    game.current_round = 1;
-    game.state = GameState::CommitPhase;
-    game.current_auto_round += 1;
-
-    // Potentially randomize player count again for the new game
-    let clock = Clock::get()?;
-    if game.min_players != game.max_players {
-        game.player_count = if (clock.unix_timestamp & 1) == 0 { 3 } else { 4 };
+// Helper to read a fresh price from a Pyth price account
+fn load_pyth_price(acc: &AccountInfo) -> Result<pyth_sdk_solana::Price, ProgramError> {
+    let feed = SolanaPriceAccount::account_info_to_feed(acc)
+        .map_err(|_| RPSError::StalePriceFeed)?;
+    let now = Clock::get()?.unix_timestamp;
+    let price = feed
+        .get_price_no_older_than(now, MAX_PRICE_AGE_SECS)
+        .ok_or(RPSError::StalePriceFeed)?;
+    if price.price <= 0 {
+        return Err(RPSError::StalePriceFeed.into());
     }
+    Ok(price)
+}
 
-    // Reset all players
-    for player in &mut game.players {
-        player.choice = Choice::None;
-        player.committed_choice = [0; 64];
-        player.salt = [0; 32];
-        player.revealed = false;
-        player.score = 0;
+// Helper to convert an amount between two priced currencies, rounding up so
+// a stake is never worth less than the amount it replaces
+fn convert_by_price(
+    amount: u64,
+    from_price: pyth_sdk_solana::Price,
+    from_decimals: u8,
+    to_price: pyth_sdk_solana::Price,
+    to_decimals: u8,
+) -> Result<u64, ProgramError> {
+    // amount * from_price * 10^(from_expo + to_decimals)
+    //   / (to_price * 10^(to_expo + from_decimals))
+    let exponent = from_price.expo + to_decimals as i32 - to_price.expo - from_decimals as i32;
+    let scale = 10u128
+        .checked_pow(exponent.unsigned_abs())
+        .ok_or(RPSError::FeeCalculationError)?;
+
+    let mut numerator = (amount as u128)
+        .checked_mul(from_price.price as u128)
+        .ok_or(RPSError::FeeCalculationError)?;
+    let mut denominator = to_price.price as u128;
+    if exponent >= 0 {
+        numerator = numerator.checked_mul(scale).ok_or(RPSError::FeeCalculationError)?;
+    } else {
+        denominator = denominator.checked_mul(scale).ok_or(RPSError::FeeCalculationError)?;
     }
 
-    // Update last action timestamp
-    game.last_action_timestamp = clock.unix_timestamp as u64;
+    let converted = numerator.div_ceil(denominator);
+    converted.try_into().map_err(|_| RPSError::FeeCalculationError.into())
+}
 
-    // Save game state
-    game.serialize(&mut *game_account.data.borrow_mut())?;
+// Helper to move lamports out of an account this program owns. The runtime
+// only lets the owner debit an account directly, and never below rent
+// exemption.
+fn pay_from_program_account(from: &AccountInfo, to: &AccountInfo, amount: u64) -> ProgramResult {
+    let rent = Rent::get()?;
+    let available = from
+        .lamports()
+        .saturating_sub(rent.minimum_balance(from.data_len()));
+    if available < amount {
+        msg!("Account {} holds {} spendable lamports, {} needed", from.key, available, amount);
+        return Err(RPSError::InsufficientFunds.into());
+    }
 
-    msg!("New automated game round started");
+    **from.try_borrow_mut_lamports()? -= amount;
+    let credited = to
+        .lamports()
+        .checked_add(amount)
+        .ok_or(RPSError::FeeCalculationError)?;
+    **to.try_borrow_mut_lamports()? = credited;
+    Ok(())
+}
 
+// Helper to check an account is the program treasury PDA
+fn check_treasury(program_id: &Pubkey, treasury_account: &AccountInfo) -> ProgramResult {
+    if *treasury_account.key != treasury_address(program_id).0 {
+        msg!("Treasury account does not match the program treasury");
+        return Err(RPSError::InvalidParameter.into());
+    }
     Ok(())
 }
 
-// Implementation for adding bot players
-fn process_add_bot_players(
+// Helper to check an account is the pot vault of a game whose pot it holds.
+// Games from before the vault keep their pot in the game account and are
+// settled with FinalizeLegacyGame instead.
+fn check_pot_vault(program_id: &Pubkey, game_key: &Pubkey, game: &Game, vault: &AccountInfo) -> ProgramResult {
+    if game.is_legacy() {
+        msg!("Game predates the pot vault; settle it with FinalizeLegacyGame");
+        return Err(RPSError::InvalidGameState.into());
+    }
+    if *vault.key != pot_vault_address(program_id, game_key).0 {
+        msg!("Pot vault does not match the game");
+        return Err(RPSError::InvalidParameter.into());
+    }
+    assert_owned_by(vault, program_id)
+}
+
+// Create a game's pot vault as an empty, rent-exempt program account, paid
+// for by `payer`
+fn open_pot_vault<'a>(
     program_id: &Pubkey,
-    accounts: &[AccountInfo],
-    count: u8,
+    game_key: &Pubkey,
+    payer: &AccountInfo<'a>,
+    vault: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
 ) -> ProgramResult {
-    let accounts_iter = &mut accounts.iter();
-
-    let initiator = next_account_info(accounts_iter)?;
-    let game_account = next_account_info(accounts_iter)?;
-    let system_program = next_account_info(accounts_iter)?;
+    let (vault_key, bump) = pot_vault_address(program_id, game_key);
+    if *vault.key != vault_key {
+        msg!("Pot vault does not match the game");
+        return Err(RPSError::InvalidParameter.into());
+    }
+    invoke_signed(
+        &system_instruction::create_account(payer.key, vault.key, Rent::get()?.minimum_balance(0), 0, program_id),
+        &[payer.clone(), vault.clone(), system_program.clone()],
+        &[&[POT_VAULT_SEED, game_key.as_ref(), &[bump]]],
+    )
+}
 
-    // Ensure the initiator signed the transaction
-    if !initiator.is_signer {
-        msg!("Initiator must sign the transaction");
-        return Err(RPSError::NotAuthorized.into());
+// Helper to take a SOL entry for `game` from `payer` in single hops: the pot
+// share into the game's pot vault and the platform fee straight into the
+// treasury, so the game never holds (or has to forward) fees
+fn pay_sol_entry<'a>(
+    game: &Pubkey,
+    payer: &AccountInfo<'a>,
+    vault: &AccountInfo<'a>,
+    treasury_account: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    pot_amount: u64,
+    fee_amount: u64,
+) -> ProgramResult {
+    invoke(
+        &system_instruction::transfer(payer.key, vault.key, pot_amount),
+        &[payer.clone(), vault.clone(), system_program.clone()],
+    )?;
+    if fee_amount > 0 {
+        invoke(
+            &system_instruction::transfer(payer.key, treasury_account.key, fee_amount),
+            &[payer.clone(), treasury_account.clone(), system_program.clone()],
+        )?;
+        emit_event(&RPSEvent::FeeCollected {
+            source: *game,
+            recipient: *treasury_account.key,
+            amount: fee_amount,
+        });
     }
+    Ok(())
+}
 
-    // Load game state
-    let mut game = Game::try_from_slice(&game_account.data.borrow())
-        .map_err(|_| ProgramError::InvalidAccountData)?;
+// Helper function to calculate fee at a game's or tournament's rate
+fn calculate_fee(amount: u64, fee_bps: Option<u16>) -> u64 {
+    // fee_bps / 10_000 of the amount, e.g. 100 bps = 1%
+    simulate::fee_at(amount, fee_bps.unwrap_or(simulate::DEFAULT_FEE_BPS))
+}
 
-    // Check if game is in correct state
-    if !matches!(game.state, GameState::WaitingForPlayers) {
-        msg!("Game is not in waiting for players state");
-        return Err(RPSError::InvalidGameState.into());
+// Helper methods for Game struct
+impl Game {
+    // Deserialize from account data. Accounts are allocated for the largest
+    // possible game, so the zero padding after the state must be ignored
+    // (try_from_slice would reject it).
+    pub fn unpack_from(data: &[u8]) -> Result<Self, ProgramError> {
+        let mut slice = data;
+        if let Ok(game) = Self::deserialize(&mut slice) {
+            return Ok(game);
+        }
+        // An account sized for an older layout can end before the fields
+        // appended since; they read as zero, i.e. empty or default, and
+        // `layout_version` as 0
+        let mut padded = data.to_vec();
+        padded.resize(data.len() + LEGACY_TAIL_PADDING, 0);
+        let mut slice = padded.as_slice();
+        Self::deserialize(&mut slice).map_err(|_| ProgramError::InvalidAccountData)
     }
 
-    // Check if there's room for bot players
-    let available_slots = game.player_count as usize - game.players.len();
-    let bot_count = std::cmp::min(count as usize, available_slots);
+    // Created before the current layout; see `FinalizeLegacyGame`
+    pub fn is_legacy(&self) -> bool {
+        self.layout_version < GAME_LAYOUT_VERSION
+    }
 
-    if bot_count == 0 {
-        msg!("No room for bot players");
-        return Err(RPSError::GameFull.into());
+    // Borsh size of a game with `max_players` seats and every vector and
+    // option at its fullest, field by field in declaration order. Vectors
+    // are a u32 length plus their items, options a tag byte plus the value.
+    // The `large_lobbies` fixture test serializes such a game to hold this
+    // to the real layout.
+    pub fn get_max_size(max_players: u8) -> usize {
+        32 + // host pubkey
+        4 + (max_players as usize * Player::LEN) + // players
+        1 + // min_players
+        1 + // max_players
+        1 + // game state
+        1 + // current_round
+        1 + // total_rounds
+        8 + // entry_fee
+        8 + // game_pot
+        8 + // required_timeout
+        8 + // last_action_timestamp
+        1 + // player_count
+        1 + // losers_can_rejoin
+        1 + // game_mode
+        8 + // auto_round_delay
+        8 + // max_auto_rounds
+        8 + // current_auto_round
+        1 + // currency_mode
+        8 + // fee_collected
+        1 + 32 + // Optional token mint (1 for option tag, 32 for pubkey)
+        1 + 32 + // Optional base price feed
+        1 + // base_decimals
+        4 + MAX_STAKE_MINTS * StakeVault::LEN + // Stake vaults
+        8 + // phase_nonce
+        1 + // claimed_bitmap
+        8 + // pot_snapshot
+        1 + 32 + // Optional tournament
+        1 + // tournament_match
+        8 + // phase_deadline
+        1 + // allow_public_bots
+        1 + // commitment_scheme
+        4 + 2 * TEAM_MATCH_SIZE * 32 + // team_lineup
+        8 + // phase_started_at
+        1 + // participation_bitmap
+        1 + // flagged
+        1 + // flag_reasons
+        4 + (max_players as usize * SeatWatch::LEN) + // seat_watch
+        4 + (max_players as usize * 32) + // bot_funders
+        4 + (max_players as usize * PendingClaim::LEN) + // pending_claims
+        4 + (max_players as usize * EntryEscrow::LEN) + // escrows
+        8 + // pot_subsidy
+        1 + // layout_version
+        1 + // player_count_drawn
+        4 + max_players as usize + // reveal_order
+        1 + HiddenFee::LEN + // Optional hidden fee
+        2 + // theme_id
+        1 + 2 + // Optional fee_bps
+        1 + // rule_set
+        1 + // winners
+        1 + // cancelled
+        8 + // join_timeout
+        8 + // commit_timeout
+        8 + // reveal_timeout
+        8 + // crank_reward_lamports
+        8 + // crank_bounty
+        1 + // slash_non_revealers
+        4 + (max_players as usize * 32) + // slashed
+        4 + (max_players as usize * BotSeat::LEN) + // bot_config
+        4 + (max_players as usize * Payout::LEN) // settlement
     }
 
-    // Add bot players
-    for i in 0..bot_count {
-        // Create a deterministic bot pubkey based on game account and index
-        let seed = format!("bot_{}_{}_{}", game_account.key, game.players.len(), i);
-        let hash = hashv(&[seed.as_bytes()]).to_bytes();
-        let mut bot_pubkey_bytes = [0u8; 32];
-        bot_pubkey_bytes.copy_from_slice(&hash[0..32]);
-        let bot_pubkey = Pubkey::new_from_array(bot_pubkey_bytes);
+    // Enter a new phase instance at `now`, restarting its countdown. Round
+    // changes count too, even when the state itself stays CommitPhase.
+    pub fn set_phase(&mut self, state: GameState, now: u64) {
+        // Freeze the pot, who splits it and how, and start a fresh set of
+        // claims
+        self.cancelled = false;
+        if matches!(state, GameState::Finished) {
+            self.pot_snapshot = self.game_pot;
+            self.winners = self.score_leaders();
+            self.settlement = Settlement::new(&self.players, self.pot_snapshot, self.winners);
+            self.claimed_bitmap = 0;
+            self.participation_bitmap = 0;
+            self.pending_claims.clear();
+            self.escrows.clear();
+            self.phase_deadline = 0;
+        } else {
+            self.winners = 0;
+            self.settlement = Settlement::default();
+            self.phase_deadline = now.saturating_add(self.phase_timeout(state));
+        }
+        self.state = state;
+        self.phase_nonce = self.phase_nonce.wrapping_add(1);
+        self.phase_started_at = now;
+    }
 
-        game.players.push(Player {
-            pubkey: bot_pubkey,
-            choice: Choice::None,
-            committed_choice: [0; 64],
-            salt: [0; 32],
-            revealed: false,
-            score: 0,
-        });
+    // How long the current phase has been running at `now`
+    pub fn phase_elapsed(&self, now: u64) -> Option<u64> {
+        (self.phase_started_at != 0).then(|| now.saturating_sub(self.phase_started_at))
+    }
 
-        // Update game pot for bot players - simulate them paying entry fee
-        // Calculate fee for the entry
-        let fee_amount = if game.entry_fee > 0 {
-            calculate_fee(game.entry_fee)
+    // When the current phase can be timed out. Games created before
+    // `phase_deadline` existed fall back to the last action.
+    pub fn timeout_deadline(&self) -> u64 {
+        if self.phase_deadline != 0 {
+            self.phase_deadline
         } else {
-            0
+            self.last_action_timestamp.saturating_add(self.phase_timeout(self.state))
+        }
+    }
+
+    // How long a phase of `state` runs before it can be timed out: its own
+    // timeout if the host set one, otherwise `required_timeout`
+    pub fn phase_timeout(&self, state: GameState) -> u64 {
+        let own = match state {
+            GameState::WaitingForPlayers => self.join_timeout,
+            GameState::CommitPhase => self.commit_timeout,
+            GameState::RevealPhase => self.reveal_timeout,
+            GameState::Finished => 0,
         };
-        
-        // Adjust amount to add to game pot
-        let pot_amount = if game.entry_fee > 0 {
-            game.entry_fee - fee_amount
+        if own != 0 {
+            own
         } else {
-            0
-        };
-        
-        game.game_pot += pot_amount;
-        game.fee_collected += fee_amount;
+            self.required_timeout
+        }
+    }
 
-        msg!("Added bot player: {}", bot_pubkey);
+    // Play starts with the first commit of the first round; until then
+    // seats can still change hands through `LeaveGame` and the waitlist
+    pub fn has_started(&self) -> bool {
+        match self.state {
+            GameState::WaitingForPlayers => false,
+            GameState::CommitPhase => {
+                self.current_round > 1 || self.players.iter().any(|p| p.committed_choice != [0; 64])
+            }
+            _ => true,
+        }
     }
 
-    // Update game state if required player count is reached
-    if game.players.len() >= game.player_count as usize {
-        game.state = GameState::CommitPhase;
-        msg!("Required player count reached: {}", game.player_count);
+    // Escrows a seat's SOL entry until play starts
+    pub fn escrow_entry(&mut self, player: Pubkey, amount: u64, now: u64) {
+        if amount > 0 {
+            self.escrows.push(EntryEscrow {
+                player,
+                amount,
+                refund_after: now.saturating_add(ENTRY_REFUND_SECS),
+            });
+        }
     }
 
-    // Update last action timestamp
-    let clock = Clock::get()?;
-    game.last_action_timestamp = clock.unix_timestamp as u64;
+    // Drops `player`'s escrow record, e.g. once their entry is refunded
+    pub fn release_escrow(&mut self, player: &Pubkey) -> Option<EntryEscrow> {
+        let index = self.escrows.iter().position(|e| e.player == *player)?;
+        Some(self.escrows.remove(index))
+    }
 
-    // Save game state
-    game.serialize(&mut *game_account.data.borrow_mut())?;
+    // Side of a tournament match `player` is lined up for: 0 for side A,
+    // 1 for B
+    pub fn team_side(&self, player: &Pubkey) -> Option<usize> {
+        let side_size = (self.team_lineup.len() / 2).max(1);
+        self.team_lineup
+            .iter()
+            .position(|p| p == player)
+            .map(|i| i / side_size)
+    }
 
-    msg!("Added {} bot players", bot_count);
+    // Seats holding the top score, as a bitmap over player indices. Once
+    // the game has finished these are the winners recorded then, so shares
+    // never depend on the scores afterwards; games that finished before
+    // `winners` existed still read their scores. A cancelled game has none.
+    pub fn winner_bitmap(&self) -> u8 {
+        if self.cancelled {
+            return 0;
+        }
+        if self.winners != 0 {
+            return self.winners;
+        }
+        self.score_leaders()
+    }
 
-    Ok(())
-}
+    // Top scorers. Slashed seats are left out unless every seat was
+    // slashed, so their entries go to the best of the seats that revealed.
+    fn score_leaders(&self) -> u8 {
+        let slashed = self.slashed_seats();
+        let every_seat = ((1u16 << self.players.len()) - 1) as u8;
+        if slashed == 0 || slashed == every_seat {
+            let scores: Vec<u8> = self.players.iter().map(|p| p.score).collect();
+            return simulate::winner_bitmap(&scores);
+        }
+        let scores: Vec<u8> = self
+            .players
+            .iter()
+            .enumerate()
+            .map(|(i, p)| if slashed & (1 << i) != 0 { 0 } else { p.score })
+            .collect();
+        simulate::winner_bitmap(&scores) & !slashed
+    }
 
-// Implementation for collecting fees
-fn process_collect_fees(
-    program_id: &Pubkey,
-    accounts: &[AccountInfo],
-) -> ProgramResult {
-    let accounts_iter = &mut accounts.iter();
+    // Bit per seat whose player was slashed for not revealing
+    pub fn slashed_seats(&self) -> u8 {
+        self.players
+            .iter()
+            .enumerate()
+            .filter(|(_, p)| self.slashed.contains(&p.pubkey))
+            .fold(0, |bits, (i, _)| bits | (1 << i))
+    }
 
-    let fee_collector = next_account_info(accounts_iter)?;
-    let game_account = next_account_info(accounts_iter)?;
-    let system_program = next_account_info(accounts_iter)?;
-    
-    // Get token accounts if needed
-    let (token_program, fee_token_account, game_token_account) = if accounts_iter.len() >= 3 {
-        let token_program = next_account_info(accounts_iter)?;
-        let fee_token_account = next_account_info(accounts_iter)?;
-        let game_token_account = next_account_info(accounts_iter)?;
-        (Some(token_program), Some(fee_token_account), Some(game_token_account))
-    } else {
-        (None, None, None)
-    };
+    // Hand the bot in `seat` plays, drawn from `seed` (the reveal order
+    // draw), the seat's commitment and the bot's key as its strategy directs
+    pub fn bot_hand(&self, seat: usize, seed: &[u8; 32]) -> Choice {
+        let player = &self.players[seat];
+        let rules = self.rule_set.rules();
+        let hands = rules.valid_choices();
+        let draw = hashv(&[b"rps-bot-hand", seed, &player.committed_choice, player.pubkey.as_ref()]);
+        let roll = u64::from_le_bytes(draw.to_bytes()[..8].try_into().unwrap_or_default());
+        let strategy = self
+            .bot_config
+            .iter()
+            .find(|bot| bot.pubkey == player.pubkey)
+            .map_or(BotStrategy::Random, |bot| bot.strategy);
+
+        let hand = match strategy {
+            BotStrategy::Random => hands[(roll % hands.len() as u64) as usize],
+            BotStrategy::CounterLastMove => {
+                // Random until there is a last round to counter
+                let counters: Vec<Hand> = match self.most_played_last_round(seat) {
+                    Some(target) => hands.iter().copied().filter(|&h| rules.beats(h, target).is_gt()).collect(),
+                    None => Vec::new(),
+                };
+                let pool = if counters.is_empty() { hands } else { &counters[..] };
+                pool[(roll % pool.len() as u64) as usize]
+            }
+            BotStrategy::Weighted => {
+                // Weights n, n-1, ..., 1 in rule set order
+                let n = hands.len() as u64;
+                let mut ticket = roll % (n * (n + 1) / 2);
+                let mut pick = hands[0];
+                for (i, &hand) in hands.iter().enumerate() {
+                    let weight = n - i as u64;
+                    if ticket < weight {
+                        pick = hand;
+                        break;
+                    }
+                    ticket -= weight;
+                }
+                pick
+            }
+        };
+        Choice::from_hand(hand)
+    }
 
-    // Ensure the fee collector signed the transaction
-    if !fee_collector.is_signer {
-        msg!("Fee collector must sign the transaction");
-        return Err(RPSError::NotAuthorized.into());
+    // The hand the seats other than `seat` revealed most in the last scored
+    // round, the earliest seat's on a tie
+    fn most_played_last_round(&self, seat: usize) -> Option<Hand> {
+        let mut counts: Vec<(Hand, usize)> = Vec::new();
+        for (i, player) in self.players.iter().enumerate() {
+            if i == seat {
+                continue;
+            }
+            let last = self.seat_watch.iter().find(|w| w.player == player.pubkey).map(|w| w.last_hand);
+            let Some(hand) = last.and_then(Hand::from_byte) else {
+                continue;
+            };
+            match counts.iter_mut().find(|(h, _)| *h == hand) {
+                Some((_, count)) => *count += 1,
+                None => counts.push((hand, 1)),
+            }
+        }
+        let most = counts.iter().map(|&(_, count)| count).max()?;
+        counts.into_iter().find(|&(_, count)| count == most).map(|(hand, _)| hand)
     }
 
-    // Load game state
-    let mut game = Game::try_from_slice(&game_account.data.borrow())
-        .map_err(|_| ProgramError::InvalidAccountData)?;
+    // Wallet the seat's winnings and refunds are paid to: the player's own,
+    // or for a bot seat, which has no key, the wallet that paid for it
+    pub fn payee(&self, seat: usize) -> Pubkey {
+        let player = self.players[seat].pubkey;
+        self.bot_config
+            .iter()
+            .find(|bot| bot.pubkey == player)
+            .map_or(player, |bot| bot.funder)
+    }
 
-    // Check if there are fees to collect
-    if game.fee_collected == 0 {
-        msg!("No fees to collect");
-        return Err(RPSError::InsufficientFunds.into());
+    // Key of the seat a claim or refund paid to `wallet` settles: the first
+    // of its seats still marked in `owed`, else its first seat, so a wallet
+    // that funded several bots is paid for them one call at a time
+    pub fn seat_paid_to(&self, wallet: &Pubkey, owed: u8) -> Option<Pubkey> {
+        let seats: Vec<usize> = (0..self.players.len()).filter(|&i| self.payee(i) == *wallet).collect();
+        let seat = seats.iter().find(|&&i| owed & (1 << i) != 0).or(seats.first())?;
+        Some(self.players[*seat].pubkey)
     }
 
-    // Only allow fee collection from finished games or by authorized collectors
-    if !matches!(game.state, GameState::Finished) && *fee_collector.key != Pubkey::new_from_array(FEE_COLLECTOR.as_bytes()) {
-        msg!("Game is not finished and caller is not authorized fee collector");
-        return Err(RPSError::NotAuthorized.into());
+    // True once every winner of a finished game has been paid
+    pub fn all_claimed(&self) -> bool {
+        let winners = self.winner_bitmap();
+        self.claimed_bitmap & winners == winners
     }
 
-    // Transfer fees based on currency mode
-    let fee_amount = game.fee_collected;
-    match game.currency_mode {
-        CurrencyMode::SOL => {
-            // Transfer SOL from game account to fee collector
-            let game_key = game_account.key;
-            let seeds = rps_pda_seeds(game_key);
-            let (_pda, bump) = Pubkey::find_program_address(&seeds, program_id);
-            let signer_seeds: &[&[u8]] = &[seeds[0], seeds[1], &[bump]];
-            
-            invoke_signed(
-                &system_instruction::transfer(
-                    game_account.key,
-                    fee_collector.key,
-                    fee_amount,
-                ),
-                &[game_account.clone(), fee_collector.clone(), system_program.clone()],
-                &[signer_seeds],
-            )?;
-        },
-        CurrencyMode::RPSToken => {
-            // For token transfers, we need token program and accounts
-            if token_program.is_none() || fee_token_account.is_none() || game_token_account.is_none() {
-                msg!("Token program and accounts required for RPSToken fee collection");
-                return Err(RPSError::InvalidParameter.into());
-            }
-            
-            // Token transfer would be implemented here
-            // This is a placeholder for the actual token transfer logic
-            msg!("Token transfer for fee collection would happen here");
+    // True once a finished game owes nothing more: every winner paid, or
+    // for a cancelled game every seat refunded. Replaying before then would
+    // hand the unpaid pot to the next round.
+    pub fn paid_out(&self) -> bool {
+        if self.cancelled {
+            let seats = ((1u16 << self.players.len()) - 1) as u8;
+            self.claimed_bitmap & seats == seats
+        } else {
+            self.all_claimed()
         }
     }
 
-    // Reset fee collected
-    game.fee_collected = 0;
+    // What a claim by `player` would pay right now: the share `settlement`
+    // recorded for them at finish, whatever has happened to the game since.
+    // Games that finished before `settlement` existed split the pot captured
+    // then equally, the last claimant taking the rounding dust. Stake vaults
+    // are split evenly between the winners still unpaid.
+    pub fn claimable(&self, player: &Pubkey) -> Result<Claimable, RPSError> {
+        state_machine::require(self.state, Action::Settle)?;
+
+        let winners = self.winner_bitmap();
+        let seat_bit = match self.players.iter().position(|p| p.pubkey == *player) {
+            Some(i) if winners & (1 << i) != 0 => 1u8 << i,
+            _ => return Err(RPSError::NotWinner),
+        };
+        // Each winner is paid exactly once
+        if self.claimed_bitmap & seat_bit != 0 {
+            return Err(RPSError::AlreadyClaimed);
+        }
 
-    // Save game state
-    game.serialize(&mut *game_account.data.borrow_mut())?;
+        let unclaimed = (winners & !self.claimed_bitmap).count_ones() as u64;
+        let amount = match self.settlement.share_of(player) {
+            Some(share) => share.min(self.game_pot),
+            None if unclaimed == 1 => self.game_pot,
+            None => self.pot_snapshot / winners.count_ones() as u64,
+        };
+        Ok(Claimable {
+            amount,
+            vault_amounts: self.stake_vaults.iter().map(|v| v.balance / unclaimed).collect(),
+        })
+    }
 
-    msg!("Collected {} fees", fee_amount);
+    // What `RefundEntry` would return `player` from a cancelled game: the
+    // entry the seat escrowed, or for seats without an escrow (bots and
+    // RPSToken entries) an equal share of what the escrowed seats did not
+    // pay in. The treasury's `pot_subsidy` is nobody's entry and stays for
+    // the treasury; the last seat refunded takes the rest, rounding dust
+    // included. Refunded seats are marked in `claimed_bitmap`.
+    pub fn refundable(&self, player: &Pubkey) -> Result<u64, RPSError> {
+        state_machine::require(self.state, Action::Settle)?;
+        if !self.cancelled {
+            return Err(RPSError::InvalidGameState);
+        }
+        let seat = self
+            .players
+            .iter()
+            .position(|p| p.pubkey == *player)
+            .ok_or(RPSError::PlayerNotFound)?;
+        if self.claimed_bitmap & (1 << seat) != 0 {
+            return Err(RPSError::AlreadyClaimed);
+        }
 
-    Ok(())
+        let entries = self.game_pot.saturating_sub(self.pot_subsidy);
+        let unrefunded = self.players.len() - self.claimed_bitmap.count_ones() as usize;
+        if unrefunded == 1 {
+            return Ok(entries);
+        }
+        let entry = match self.escrows.iter().find(|e| e.player == *player) {
+            Some(escrow) => escrow.amount,
+            None => {
+                let escrowed: u64 = self.escrows.iter().map(|e| e.amount).sum();
+                let unescrowed = self.players.len().saturating_sub(self.escrows.len()).max(1) as u64;
+                self.pot_snapshot.saturating_sub(self.pot_subsidy).saturating_sub(escrowed) / unescrowed
+            }
+        };
+        Ok(entry.min(entries))
+    }
 }
 
-// Helper function to process round results
-fn process_round_results(game: &mut Game) {
-    let player_count = game.players.len();
-
-    // For each player, compare against every other player
-    for i in 0..player_count {
-        for j in (i+1)..player_count {
-            let choice_i = &game.players[i].choice;
-            let choice_j = &game.players[j].choice;
-
-            match (choice_i, choice_j) {
-                (Choice::Rock, Choice::Scissors) |
-                (Choice::Paper, Choice::Rock) |
-                (Choice::Scissors, Choice::Paper) => {
-                    // Player i wins against player j
-                    game.players[i].score += 1;
-                },
-                (Choice::Scissors, Choice::Rock) |
-                (Choice::Rock, Choice::Paper) |
-                (Choice::Paper, Choice::Scissors) => {
-                    // Player j wins against player i
-                    game.players[j].score += 1;
-                },
-                _ => {
-                    // Tie or invalid choices - no points awarded
-                }
-            }
+// Return data of `AuditGame`. A positive discrepancy is lamports the state
+// does not account for; a negative one means winners could not all be paid.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct GameAudit {
+    pub expected_lamports: u64,
+    pub actual_lamports: u64,
+    pub discrepancy: i64,
+}
+
+impl GameAudit {
+    pub fn new(game: &Game, rent: u64, actual_lamports: u64) -> Self {
+        // Token games keep the pot and fees in token accounts
+        let owed = match game.currency_mode {
+            CurrencyMode::SOL => game.game_pot.saturating_add(game.fee_collected),
+            CurrencyMode::RPSToken => 0,
+        };
+        let expected_lamports = rent.saturating_add(owed);
+        let discrepancy = (actual_lamports as i128 - expected_lamports as i128)
+            .clamp(i64::MIN as i128, i64::MAX as i128) as i64;
+        Self {
+            expected_lamports,
+            actual_lamports,
+            discrepancy,
         }
     }
-    
-    // Log scores for debugging
-    for (i, player) in game.players.iter().enumerate() {
-        msg!("Player {} score: {}", i, player.score);
+}
+
+// Return data of `GetFeeQuote`: the platform fee taken from an entry and
+// what is left for the pot. SOL fees go to the treasury, token fees are held
+// by the game until collected; the amounts are the same either way.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct FeeQuote {
+    pub fee: u64,
+    pub pot_contribution: u64,
+}
+
+impl FeeQuote {
+    pub fn new(entry_fee: u64, fee_bps: Option<u16>) -> Self {
+        let fee = calculate_fee(entry_fee, fee_bps);
+        FeeQuote {
+            fee,
+            pot_contribution: entry_fee - fee,
+        }
     }
 }
 
-// Helper function to calculate fee
-fn calculate_fee(amount: u64) -> u64 {
-    // Calculate fee as FEE_PERCENTAGE / FEE_DENOMINATOR of the amount
-    // For example, 10/1000 = 1%
-    amount.saturating_mul(FEE_PERCENTAGE).saturating_div(FEE_DENOMINATOR)
+// Return data of `GetClaimable`: the game-currency amount, then the amount
+// from each stake vault in `Game::stake_vaults` order
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct Claimable {
+    pub amount: u64,
+    pub vault_amounts: Vec<u64>,
 }
 
-// Helper methods for Game struct
-impl Game {
-    pub fn get_max_size(max_players: u8) -> usize {
-        // Calculate max size needed for serialized Game struct with max_players
-        // This is a rough estimate - actual implementation would need precise calculation
-        8 + // host pubkey
-        4 + (max_players as usize * (32 + 1 + 64 + 32 + 1 + 1)) + // Vector of Player structs with SHA512 hashes
-        1 + // min_players
-        1 + // max_players
-        1 + // game state
-        1 + // current_round
-        1 + // total_rounds
-        8 + // entry_fee
-        8 + // game_pot
-        8 + // required_timeout
-        8 + // last_action_timestamp
-        1 + // player_count
-        1 + // losers_can_rejoin
-        1 + // game_mode
-        8 + // auto_round_delay
-        8 + // max_auto_rounds
-        8 + // current_auto_round
-        1 + // currency_mode
-        8 + // fee_collected
-        1 + 32 // Optional token mint (1 for option tag, 32 for pubkey)
+// One seat of a `SpectatorView`. `choice` stays None until the seat reveals.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct SpectatorSeat {
+    pub player: Pubkey,
+    pub committed: bool,
+    pub revealed: bool,
+    pub choice: Choice,
+    pub score: u8,
+}
+
+// Return data of `GetSpectatorView`: what a watcher needs to follow a game.
+// Commitments and salts are left out, so nothing in it helps guess a hand.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct SpectatorView {
+    pub state: GameState,
+    pub current_round: u8,
+    pub total_rounds: u8,
+    pub currency_mode: CurrencyMode,
+    pub entry_fee: u64,
+    pub pot: u64,
+    pub phase_deadline: u64,
+    pub seats: Vec<SpectatorSeat>,
+}
+
+impl SpectatorView {
+    pub fn new(game: &Game) -> Self {
+        let seats = game
+            .players
+            .iter()
+            .map(|player| SpectatorSeat {
+                player: player.pubkey,
+                committed: player.committed_choice != [0; 64],
+                revealed: player.revealed,
+                choice: if player.revealed { player.choice.clone() } else { Choice::None },
+                score: player.score,
+            })
+            .collect();
+        SpectatorView {
+            state: game.state,
+            current_round: game.current_round,
+            total_rounds: game.total_rounds,
+            currency_mode: game.currency_mode,
+            entry_fee: game.entry_fee,
+            pot: game.game_pot,
+            phase_deadline: game.phase_deadline,
+            seats,
+        }
     }
 }
@@ -0,0 +1,96 @@
+// Game phases as an explicit state machine. A handler names the `Action` it
+// performs and the `Transition` it makes; whether the game's phase allows
+// either is decided here and nowhere else. Phase changes only happen
+// through `enter_phase`, which refuses any transition this table does not
+// list, so a handler cannot reach a phase by a path nobody checked.
+
+use solana_program::msg;
+
+use crate::{Game, GameState, RPSError};
+
+// What an instruction does to a game, as far as its phase is concerned
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Seat,   // Take, give up or prepare a lobby seat; WaitingForPlayers only
+    Commit, // Commit a hand, or settle what must precede the first commit
+    Reveal, // Reveal a committed hand
+    Play,   // Act on a round in progress, in either of its phases
+    Settle, // Pay out, record, report or replay a finished game
+}
+
+// A change of phase
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Transition {
+    Fill,           // The last seat is taken: commits open (or start over, before play)
+    Reopen,         // A seat is given up before play: back to the lobby
+    RestartCommits, // The commit phase starts over before play, e.g. a revealed entry fee
+    CloseCommits,   // Every hand is in: reveals open
+    NextRound,      // A round that is not the last is scored: the next one's commits open
+    Finish,         // The last round is scored, or the game is abandoned
+    Replay,         // A finished game is played again from its first round
+}
+
+// The facts about a game the table depends on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Phase {
+    pub state: GameState,
+    pub started: bool,    // See `Game::has_started`
+    pub last_round: bool, // The current round is the final one
+}
+
+impl Phase {
+    pub fn of(game: &Game) -> Self {
+        Phase {
+            state: game.state,
+            started: game.has_started(),
+            last_round: game.current_round >= game.total_rounds,
+        }
+    }
+}
+
+// Whether `action` is allowed in `state`
+pub fn allows(state: GameState, action: Action) -> bool {
+    use GameState::*;
+    match action {
+        Action::Seat => state == WaitingForPlayers,
+        Action::Commit => state == CommitPhase,
+        Action::Reveal => state == RevealPhase,
+        Action::Play => matches!(state, CommitPhase | RevealPhase),
+        Action::Settle => state == Finished,
+    }
+}
+
+// Fail with InvalidGameState unless `action` is allowed in `state`
+pub fn require(state: GameState, action: Action) -> Result<(), RPSError> {
+    if allows(state, action) {
+        return Ok(());
+    }
+    msg!("{:?} is not allowed while the game is in {:?}", action, state);
+    Err(RPSError::InvalidGameState)
+}
+
+// The state `transition` leads to from `phase`, or InvalidGameState when
+// the table does not list it
+pub fn next_state(phase: Phase, transition: Transition) -> Result<GameState, RPSError> {
+    use GameState::*;
+    let Phase {
+        state,
+        started,
+        last_round,
+    } = phase;
+    let target = match (transition, state) {
+        (Transition::Fill, WaitingForPlayers) => Some(CommitPhase),
+        (Transition::Fill | Transition::RestartCommits, CommitPhase) if !started => Some(CommitPhase),
+        (Transition::Reopen, CommitPhase) if !started => Some(WaitingForPlayers),
+        (Transition::CloseCommits, CommitPhase) => Some(RevealPhase),
+        (Transition::NextRound, RevealPhase) if !last_round => Some(CommitPhase),
+        (Transition::Finish, WaitingForPlayers | CommitPhase) => Some(Finished),
+        (Transition::Finish, RevealPhase) if last_round => Some(Finished),
+        (Transition::Replay, Finished) => Some(CommitPhase),
+        _ => None,
+    };
+    target.ok_or_else(|| {
+        msg!("{:?} is not a transition out of {:?}", transition, state);
+        RPSError::InvalidGameState
+    })
+}
@@ -129,7 +129,9 @@ export enum Choice {
   None = 0,
   Rock = 1,
   Paper = 2,
-  Scissors = 3
+  Scissors = 3,
+  Lizard = 4, // Lizard-Spock games only
+  Spock = 5
 }
 
 // Game outcome
@@ -68,6 +68,18 @@ const CreateGameView: React.FC<CreateGameViewProps> = ({
           <div className="form-group mt-6">
             <label className="form-label">Player Count</label>
             <div className="flex space-x-4">
+              <button
+                type="button"
+                className={`flex-1 py-3 rounded-lg ${
+                  playerCount === 2
+                    ? 'bg-purple-600'
+                    : 'bg-gray-700 hover:bg-gray-600'
+                }`}
+                onClick={() => setPlayerCount(2)}
+                disabled={isLoading}
+              >
+                1v1
+              </button>
               <button
                 type="button"
                 className={`flex-1 py-3 rounded-lg ${
@@ -100,7 +100,7 @@ const HomeView: React.FC<HomeViewProps> = ({ setCurrentView, connected }) => {
             <li>Paper beats Rock</li>
             <li>Each player earns 1 point for each win against another player</li>
             <li>The player with the most points after all rounds wins</li>
-            <li>Games require 3-4 players to start automatically</li>
+            <li>Games take 2-8 players and start automatically once full</li>
           </ul>
         </div>
       </div>
@@ -33,6 +33,10 @@ const RevealChoiceView: React.FC<RevealChoiceViewProps> = ({
         return '✋';
       case Choice.Scissors:
         return '✌️';
+      case Choice.Lizard:
+        return '🦎';
+      case Choice.Spock:
+        return '🖖';
       default:
         return '❓';
     }
@@ -47,6 +51,10 @@ const RevealChoiceView: React.FC<RevealChoiceViewProps> = ({
         return 'Paper';
       case Choice.Scissors:
         return 'Scissors';
+      case Choice.Lizard:
+        return 'Lizard';
+      case Choice.Spock:
+        return 'Spock';
       default:
         return 'Unknown';
     }